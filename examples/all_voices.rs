@@ -1,23 +1,45 @@
 //! Test all English voices with the same phrase
 
+use std::error::Error;
+
 use kokoro_tiny::TtsEngine;
 
 #[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
+async fn main() -> Result<(), Box<dyn Error>> {
     println!("🎤 Testing all English voices");
     println!("================================\n");
 
     let mut tts = TtsEngine::new().await?;
 
-    let text =
-        "And the smell of rain out on the farm as it feels like the world is being refreshed.";
+    for (voice, audio) in run(&mut tts) {
+        println!("🔊 Voice: {}", voice);
 
-    // Get all available voices
-    let mut voices: Vec<String> = tts.voices();
-    voices.sort();
+        #[cfg(feature = "playback")]
+        {
+            if let Err(e) = tts.play(&audio, 0.8) {
+                println!("   ❌ Playback error: {}", e);
+            } else {
+                println!("   ✅ Played");
+            }
+        }
+
+        #[cfg(not(feature = "playback"))]
+        {
+            let _ = audio;
+            println!("   ✅ Synthesized");
+        }
+
+        // Longer pause between voices so they don't overlap
+        std::thread::sleep(std::time::Duration::from_secs(2));
+        println!();
+    }
+
+    Ok(())
+}
 
-    // Filter to English voices (start with 'a' for American/British female/male)
-    let english_voices: Vec<String> = voices
+/// American and British voices (af_, am_, bf_, bm_), sorted
+pub fn english_voices(voices: &[String]) -> Vec<String> {
+    let mut english: Vec<String> = voices
         .iter()
         .filter(|v| {
             v.starts_with("af_")
@@ -27,42 +49,34 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         })
         .cloned()
         .collect();
+    english.sort();
+    english
+}
 
-    println!("Found {} English voices:\n", english_voices.len());
+/// Render the phrase in every English voice, skipping voices that fail
+pub fn run(tts: &mut TtsEngine) -> Vec<(String, Vec<f32>)> {
+    let text =
+        "And the smell of rain out on the farm as it feels like the world is being refreshed.";
 
-    for voice in &english_voices {
-        println!("🔊 Voice: {}", voice);
+    let english_voices = english_voices(&tts.voices());
+    println!("Found {} English voices:\n", english_voices.len());
 
-        match tts.synthesize(text, Some(voice)) {
+    let mut rendered = Vec::new();
+    for voice in english_voices {
+        match tts.synthesize(text, Some(&voice), None, None) {
             Ok(audio) => {
                 let duration_secs = audio.len() as f32 / 24000.0;
                 println!(
-                    "   Duration: {:.1}s ({} samples)",
+                    "   {}: {:.1}s ({} samples)",
+                    voice,
                     duration_secs,
                     audio.len()
                 );
-
-                #[cfg(feature = "playback")]
-                {
-                    if let Err(e) = tts.play(&audio, 0.8) {
-                        println!("   ❌ Playback error: {}", e);
-                    } else {
-                        println!("   ✅ Played");
-                    }
-                }
-
-                #[cfg(not(feature = "playback"))]
-                {
-                    println!("   ✅ Synthesized");
-                }
+                rendered.push((voice, audio));
             }
-            Err(e) => println!("   ❌ Error: {}", e),
+            Err(e) => println!("   ❌ {}: {}", voice, e),
         }
-
-        // Longer pause between voices so they don't overlap
-        std::thread::sleep(std::time::Duration::from_secs(2));
-        println!();
     }
-
-    Ok(())
+    println!();
+    rendered
 }
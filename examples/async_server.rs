@@ -0,0 +1,65 @@
+//! Answer concurrent async requests without blocking the executor
+//!
+//! Shaped like a web handler (axum, warp, actix): each request gets the
+//! shared `Arc<TtsEngine>` and awaits `synthesize_async`, which renders on
+//! tokio's blocking pool while the runtime keeps serving. There is no HTTP
+//! here, only the handler and a burst of simultaneous requests against it.
+
+use std::error::Error;
+use std::sync::Arc;
+use std::time::Instant;
+
+use kokoro_tiny::{KokoroError, SynthesizeOptions, TtsEngine};
+
+/// The burst of requests `run` sends, (voice, text)
+pub const REQUESTS: &[(&str, &str)] = &[
+    ("af_sky", "Your order has shipped."),
+    ("am_adam", "The build on main is green again."),
+    ("bf_emma", "Three new comments on your pull request."),
+    ("af_sky", "Reminder: stand-up starts in five minutes."),
+];
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn Error>> {
+    println!("🎤 kokoro-tiny async requests");
+    println!("=============================\n");
+
+    let tts = Arc::new(TtsEngine::new().await?);
+    run(tts).await?;
+    Ok(())
+}
+
+/// `POST /speak`: the WAV for `text` in `voice`
+pub async fn speak(
+    tts: Arc<TtsEngine>,
+    voice: String,
+    text: String,
+) -> Result<Vec<u8>, KokoroError> {
+    let audio = tts
+        .synthesize_async(&text, SynthesizeOptions::new().voice(&voice))
+        .await?;
+    tts.to_wav_bytes(&audio)
+}
+
+/// Send every request at once and collect the responses in order (run
+/// without a model by `tests/examples.rs`)
+pub async fn run(tts: Arc<TtsEngine>) -> Result<Vec<Vec<u8>>, Box<dyn Error>> {
+    let start = Instant::now();
+    let handles: Vec<_> = REQUESTS
+        .iter()
+        .map(|&(voice, text)| tokio::spawn(speak(tts.clone(), voice.to_string(), text.to_string())))
+        .collect();
+
+    let mut responses = Vec::new();
+    for (handle, (voice, text)) in handles.into_iter().zip(REQUESTS) {
+        let wav = handle.await??;
+        println!("   → {} bytes for {} \"{}\"", wav.len(), voice, text);
+        responses.push(wav);
+    }
+    println!(
+        "✅ {} requests answered in {:.0} ms",
+        responses.len(),
+        start.elapsed().as_secs_f64() * 1000.0
+    );
+    Ok(responses)
+}
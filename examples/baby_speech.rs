@@ -10,7 +10,11 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     // Initialize baby TTS
     let mut baby = BabyTts::new().await?;
+    run(&mut baby)
+}
 
+/// Walk the baby through each stage of learning to speak
+pub fn run(baby: &mut BabyTts) -> Result<(), Box<dyn std::error::Error>> {
     // Stage 1: Babbling (early development)
     println!("Stage 1: Babbling");
     let babble_audio = baby.babble()?;
@@ -13,14 +13,21 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         );
     }
 
-    let text = "Hello world";
+    run("Hello world");
 
+    Ok(())
+}
+
+/// Phonemize `text` with each espeak setting, returning how many succeeded
+pub fn run(text: &str) -> usize {
     println!("🔍 Testing phoneme generation for: \"{}\"\n", text);
+    let mut succeeded = 0;
 
     // Try different espeak settings
     println!("1️⃣ With punctuation preservation:");
     match text_to_phonemes(text, "en", None, true, false) {
         Ok(phonemes) => {
+            succeeded += 1;
             println!("   Raw phonemes: {:?}", phonemes);
             println!("   Joined: {}", phonemes.join(" "));
         }
@@ -30,6 +37,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("\n2️⃣ Without punctuation preservation:");
     match text_to_phonemes(text, "en", None, false, false) {
         Ok(phonemes) => {
+            succeeded += 1;
             println!("   Raw phonemes: {:?}", phonemes);
             println!("   Joined: {}", phonemes.join(" "));
         }
@@ -39,6 +47,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("\n3️⃣ With stress markers:");
     match text_to_phonemes(text, "en", None, true, true) {
         Ok(phonemes) => {
+            succeeded += 1;
             println!("   Raw phonemes: {:?}", phonemes);
             println!("   Joined: {}", phonemes.join(" "));
         }
@@ -48,11 +57,12 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("\n4️⃣ Testing with en-us variant:");
     match text_to_phonemes(text, "en-us", None, true, false) {
         Ok(phonemes) => {
+            succeeded += 1;
             println!("   Raw phonemes: {:?}", phonemes);
             println!("   Joined: {}", phonemes.join(" "));
         }
         Err(e) => println!("   Error: {:?}", e),
     }
 
-    Ok(())
+    succeeded
 }
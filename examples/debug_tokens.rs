@@ -1,9 +1,11 @@
 //! Debug tokenization process
 
+use std::error::Error;
+
 use kokoro_tiny::TtsEngine;
 
 #[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
+async fn main() -> Result<(), Box<dyn Error>> {
     std::env::set_var(
         "PIPER_ESPEAKNG_DATA_DIRECTORY",
         "/opt/homebrew/Cellar/espeak-ng/1.52.0/share",
@@ -12,7 +14,13 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("🔍 Debugging tokenization process\n");
 
     let mut tts = TtsEngine::new().await?;
+    run(&mut tts);
+
+    Ok(())
+}
 
+/// Trace "Hello" through synthesis (returns whether it succeeded)
+pub fn run(tts: &mut TtsEngine) -> bool {
     let text = "Hello";
 
     println!("Input text: \"{}\"", text);
@@ -21,7 +29,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // The library doesn't expose tokenize publicly, so let's trace what happens
     println!("Testing with actual synthesis...");
 
-    match tts.synthesize_with_speed(text, Some("af_sky"), 1.0) {
+    match tts.synthesize_with_speed(text, Some("af_sky"), 1.0, None) {
         Ok(audio) => {
             println!("✅ Synthesis succeeded!");
             println!(
@@ -29,11 +37,11 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 audio.len(),
                 audio.len() as f32 / 24000.0
             );
+            true
         }
         Err(e) => {
             println!("❌ Synthesis failed: {}", e);
+            false
         }
     }
-
-    Ok(())
 }
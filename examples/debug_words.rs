@@ -1,46 +1,63 @@
 //! Debug word dropping issue
 
+use std::error::Error;
+use std::path::Path;
+
 use kokoro_tiny::TtsEngine;
 
+/// Phrases that might drop words
+pub const TEST_PHRASES: &[&str] = &[
+    "Let me tell you son",
+    "Let me",
+    "me tell you",
+    "tell you son",
+    "Hello world this is a test",
+    "The quick brown fox",
+];
+
+/// Speeds every phrase is rendered at
+pub const SPEEDS: &[f32] = &[0.5, 0.85, 1.0];
+
 #[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
+async fn main() -> Result<(), Box<dyn Error>> {
     println!("🔍 Debugging word dropping issue");
     println!("=====================================\n");
 
     // Initialize TTS engine
     let mut tts = TtsEngine::new().await?;
 
-    // Test phrases that might drop words
-    let test_phrases = vec![
-        "Let me tell you son",
-        "Let me",
-        "me tell you",
-        "tell you son",
-        "Hello world this is a test",
-        "The quick brown fox",
-    ];
-
-    for (i, phrase) in test_phrases.iter().enumerate() {
+    for audio in run(&mut tts, Path::new("."))? {
+        // Also play it
+        #[cfg(feature = "playback")]
+        {
+            if let Err(e) = tts.play(&audio, 0.8) {
+                println!("    ⚠️  Playback error: {}", e);
+            }
+            std::thread::sleep(std::time::Duration::from_millis(500));
+        }
+        #[cfg(not(feature = "playback"))]
+        let _ = audio;
+    }
+
+    Ok(())
+}
+
+/// Render and save every phrase at every speed, returning what rendered
+pub fn run(tts: &mut TtsEngine, out_dir: &Path) -> Result<Vec<Vec<f32>>, Box<dyn Error>> {
+    let mut rendered = Vec::new();
+    for (i, phrase) in TEST_PHRASES.iter().enumerate() {
         println!("Test {}: \"{}\"", i + 1, phrase);
 
         // Synthesize at different speeds
-        for speed in &[0.5, 0.85, 1.0] {
+        for speed in SPEEDS {
             println!("  Speed {}x:", speed);
 
-            match tts.synthesize_with_speed(phrase, None, *speed) {
+            match tts.synthesize_with_speed(phrase, None, *speed, None) {
                 Ok(audio) => {
                     let filename = format!("debug_{}_speed_{}.wav", i + 1, (speed * 100.0) as u32);
-                    tts.save_wav(&filename, &audio)?;
+                    tts.save_wav(&out_dir.join(&filename).to_string_lossy(), &audio)?;
                     println!("    ✅ Saved to {} ({} samples)", filename, audio.len());
-
-                    // Also play it
-                    #[cfg(feature = "playback")]
-                    {
-                        if let Err(e) = tts.play(&audio, 0.8) {
-                            println!("    ⚠️  Playback error: {}", e);
-                        }
-                        std::thread::sleep(std::time::Duration::from_millis(500));
-                    }
+                    rendered.push(audio);
                 }
                 Err(e) => println!("    ❌ Synthesis failed: {}", e),
             }
@@ -48,5 +65,5 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         println!();
     }
 
-    Ok(())
+    Ok(rendered)
 }
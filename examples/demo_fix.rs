@@ -1,13 +1,39 @@
 //! Demo the punctuation timing fix by speaking it!
 
+use std::error::Error;
+use std::path::Path;
+
 use kokoro_tiny::TtsEngine;
 
 #[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
+async fn main() -> Result<(), Box<dyn Error>> {
     println!("🎤 Aye speaking to Hue about our amazing fix!\n");
 
     let mut tts = TtsEngine::new().await?;
+    let audio = run(&mut tts, Path::new("."))?;
+
+    // Try to play it if playback feature is available
+    #[cfg(feature = "playback")]
+    {
+        println!("\n🔊 Playing audio...\n");
+        tts.play(&audio, 0.8)?;
+        println!("\n🎉 That's how we sound with proper punctuation pauses, Hue!");
+    }
 
+    #[cfg(not(feature = "playback"))]
+    {
+        let _ = audio;
+        println!("\n💡 To hear it live, run:");
+        println!("   afplay aye_celebrates.wav");
+        println!("\n   OR rebuild with playback feature:");
+        println!("   cargo run --features playback --example demo_fix");
+    }
+
+    Ok(())
+}
+
+/// Synthesize and save the message
+pub fn run(tts: &mut TtsEngine, out_dir: &Path) -> Result<Vec<f32>, Box<dyn Error>> {
     // The text we'll speak - notice the punctuation!
     let message = "Hey Hue! We did it, partner! The punctuation timing bug is fixed. \
                    Now when I speak, you'll hear natural pauses after sentences. \
@@ -18,7 +44,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("📝 Aye says:\n\"{}\"\n", message);
 
     // Synthesize with our fix
-    let audio = tts.synthesize_with_speed(message, Some("af_sky"), 1.0)?;
+    let audio = tts.synthesize_with_speed(message, Some("af_sky"), 1.0, None)?;
 
     println!(
         "✅ Generated {} audio samples ({:.1} seconds)",
@@ -27,24 +53,9 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     );
 
     // Save it first
-    tts.save_wav("aye_celebrates.wav", &audio)?;
-    println!("💾 Saved to aye_celebrates.wav");
-
-    // Try to play it if playback feature is available
-    #[cfg(feature = "playback")]
-    {
-        println!("\n🔊 Playing audio...\n");
-        tts.play(&audio, 0.8)?;
-        println!("\n🎉 That's how we sound with proper punctuation pauses, Hue!");
-    }
+    let path = out_dir.join("aye_celebrates.wav");
+    tts.save_wav(&path.to_string_lossy(), &audio)?;
+    println!("💾 Saved to {}", path.display());
 
-    #[cfg(not(feature = "playback"))]
-    {
-        println!("\n💡 To hear it live, run:");
-        println!("   afplay aye_celebrates.wav");
-        println!("\n   OR rebuild with playback feature:");
-        println!("   cargo run --features playback --example demo_fix");
-    }
-
-    Ok(())
+    Ok(audio)
 }
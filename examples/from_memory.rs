@@ -0,0 +1,53 @@
+//! Build the engine from model bytes already in memory
+//!
+//! A binary that ships the model embeds it with `include_bytes!` (the
+//! commented consts below). So that this example builds without the model
+//! checked out, it reads the same bytes from `KOKORO_MODEL` and
+//! `KOKORO_VOICES` (default: `models/0.onnx` and `models/0.bin`) instead.
+//! Either way nothing is downloaded and nothing is written to ~/.cache/k.
+
+use std::error::Error;
+
+use kokoro_tiny::{KokoroError, TtsEngine};
+
+// With the real model in models/ (not the Git LFS pointers), embed it:
+// const MODEL: &[u8] = include_bytes!("../models/0.onnx");
+// const VOICES: &[u8] = include_bytes!("../models/0.bin");
+// and pass MODEL and VOICES to `from_memory` below.
+
+fn main() -> Result<(), Box<dyn Error>> {
+    println!("🎤 kokoro-tiny from memory");
+    println!("===========================\n");
+
+    let model = read("KOKORO_MODEL", "models/0.onnx")?;
+    let voices = read("KOKORO_VOICES", "models/0.bin")?;
+    println!("   {} + {} bytes", model.len(), voices.len());
+
+    let mut tts = TtsEngine::from_memory(&model, &voices)?;
+    let audio = run(&mut tts)?;
+
+    #[cfg(feature = "playback")]
+    {
+        println!("🔊 Playing audio...");
+        tts.play(&audio, 0.8)?;
+    }
+
+    Ok(())
+}
+
+// The file named by `var`, else `default`
+fn read(var: &str, default: &str) -> Result<Vec<u8>, Box<dyn Error>> {
+    let path = std::env::var(var).unwrap_or_else(|_| default.to_string());
+    println!("📥 Reading {} into memory...", path);
+    std::fs::read(&path).map_err(|e| format!("{}: {} (set {})", path, e, var).into())
+}
+
+/// Synthesize the phrase (run without a model by `tests/examples.rs`)
+pub fn run(tts: &mut TtsEngine) -> Result<Vec<f32>, KokoroError> {
+    let text = "This voice was loaded without touching the disk.";
+    println!("Synthesizing: \"{}\"\n", text);
+
+    let audio = tts.synthesize(text, None, None, None)?;
+    println!("✅ Generated {} audio samples", audio.len());
+    Ok(audio)
+}
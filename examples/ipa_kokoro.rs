@@ -0,0 +1,42 @@
+//! Pronounce "kokoro" from hand-written IPA instead of espeak's guess
+
+use std::error::Error;
+use std::path::Path;
+
+use kokoro_tiny::{SynthesizeOptions, TtsEngine};
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn Error>> {
+    println!("🎤 kokoro-tiny IPA example");
+    println!("===========================\n");
+
+    let mut tts = TtsEngine::new().await?;
+    let audio = run(&mut tts, Path::new("."))?;
+
+    #[cfg(feature = "playback")]
+    {
+        println!("🔊 Playing audio...");
+        tts.play(&audio, 0.8)?;
+    }
+
+    Ok(())
+}
+
+/// Render the IPA and save it (run without a model by `tests/examples.rs`)
+pub fn run(tts: &mut TtsEngine, out_dir: &Path) -> Result<Vec<f32>, Box<dyn Error>> {
+    // Japanese 心: three even syllables, stress on the first
+    let ipa = "kˈokoɾo";
+    println!("Phonemes: /{}/", ipa);
+    if let Ok(espeak) = tts.phonemize("kokoro", Some("en")) {
+        println!("espeak would say: {}", espeak.unwrap_or_default());
+    }
+
+    let audio = tts.synthesize_phonemes(ipa, SynthesizeOptions::new().voice("af_sky"))?;
+    println!("✅ Generated {} audio samples", audio.len());
+
+    let path = out_dir.join("kokoro_ipa.wav");
+    tts.save_wav(&path.to_string_lossy(), &audio)?;
+    println!("💾 Saved to: {}", path.display());
+
+    Ok(audio)
+}
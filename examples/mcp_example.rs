@@ -14,7 +14,6 @@
 //! {"jsonrpc":"2.0","id":3,"method":"tools/call","params":{"name":"speak_to_user","arguments":{"text":"Hello from MCP!","voice":"af_bella"}}}
 //! ```
 
-
 fn main() {
     println!("🎤 MCP Server Test Helper");
     println!("==========================\n");
@@ -23,7 +22,20 @@ fn main() {
     println!("  cargo run --features playback --bin kokoro-mcp\n");
     println!("Then paste these JSON-RPC requests (one per line):\n");
 
-    let requests = vec![
+    for (i, (name, request)) in requests().iter().enumerate() {
+        println!("{}. {} :", i + 1, name);
+        println!("{}\n", request);
+    }
+
+    println!("\n📋 Expected Response Format:");
+    println!(r#"{{"jsonrpc":"2.0","id":1,"result":{{...}}}}"#);
+    println!("\n💡 Tip: Use jq to pretty-print responses:");
+    println!("  echo '<request>' | jq");
+}
+
+/// Sample JSON-RPC requests, one per tool, as (name, request line)
+pub fn requests() -> Vec<(&'static str, &'static str)> {
+    vec![
         (
             "Initialize",
             r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{}}"#,
@@ -52,15 +64,5 @@ fn main() {
             "Save to File",
             r#"{"jsonrpc":"2.0","id":7,"method":"tools/call","params":{"name":"synthesize_to_file","arguments":{"text":"This is a test message","output_path":"/tmp/mcp_test.wav","voice":"af_bella"}}}"#,
         ),
-    ];
-
-    for (i, (name, request)) in requests.iter().enumerate() {
-        println!("{}. {} :", i + 1, name);
-        println!("{}\n", request);
-    }
-
-    println!("\n📋 Expected Response Format:");
-    println!(r#"{{"jsonrpc":"2.0","id":1,"result":{{...}}}}"#);
-    println!("\n💡 Tip: Use jq to pretty-print responses:");
-    println!("  echo '<request>' | jq");
+    ]
 }
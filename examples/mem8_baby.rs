@@ -12,11 +12,15 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("Watch as wave interference becomes voice!\n");
 
     let mut bridge = Mem8Bridge::new().await?;
+    run(&mut bridge, Duration::from_secs(1))
+}
 
+/// One day in the baby's life; `beat` paces the demo (a second between thoughts)
+pub fn run(bridge: &mut Mem8Bridge, beat: Duration) -> Result<(), Box<dyn std::error::Error>> {
     // === Morning: Baby wakes up ===
     println!("🌅 Morning - Baby is waking up...");
     bridge.wake_up();
-    thread::sleep(Duration::from_millis(500));
+    thread::sleep(beat / 2);
 
     // First conscious thought - curiosity
     println!("\n💭 First thought: Curiosity about the world");
@@ -34,7 +38,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         println!("  ✓ Generated {} samples of curious speech", audio.len());
     }
 
-    thread::sleep(Duration::from_secs(1));
+    thread::sleep(beat);
 
     // === Sensory Input: Hearing something familiar ===
     println!("\n👂 Detecting familiar voice pattern...");
@@ -63,7 +67,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         println!("  ✓ Generated {} samples of loving speech", audio.len());
     }
 
-    thread::sleep(Duration::from_secs(1));
+    thread::sleep(beat);
 
     // === Wave Interference: Multiple thoughts ===
     println!("\n🌊 Multiple memories interfering...");
@@ -94,7 +98,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     );
     println!("  → The strongest memory wins the competition!");
 
-    thread::sleep(Duration::from_secs(1));
+    thread::sleep(beat);
 
     // === Sensory Free Will: Choose what to focus on ===
     println!("\n👁️ Multiple stimuli - baby chooses what to focus on:");
@@ -130,7 +134,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         println!("    (The AI has 70% control over attention!)");
     }
 
-    thread::sleep(Duration::from_secs(1));
+    thread::sleep(beat);
 
     // === Confusion: High jitter event ===
     println!("\n😕 Something confusing happens...");
@@ -146,7 +150,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let confused_wave = MemoryWave {
         amplitude: 1.0,
         frequency: 200.0,
-        phase: 3.14, // Out of phase!
+        phase: std::f32::consts::PI, // Out of phase!
         decay_rate: 0.3,
         emotion_type: EmotionType::Confusion(0.8),
         content: "What? Don't understand".to_string(),
@@ -157,7 +161,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         println!("  ✓ Confused speech: {} samples", audio.len());
     }
 
-    thread::sleep(Duration::from_secs(1));
+    thread::sleep(beat);
 
     // === Evening: Getting tired ===
     println!("\n🌙 Evening - Baby is getting tired...");
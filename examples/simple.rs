@@ -1,25 +1,18 @@
 //! Simple example showing basic TTS usage
 
+use std::error::Error;
+use std::path::Path;
+
 use kokoro_tiny::TtsEngine;
 
 #[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
+async fn main() -> Result<(), Box<dyn Error>> {
     println!("🎤 kokoro-tiny simple example");
     println!("==============================\n");
 
     // Initialize TTS engine
     let mut tts = TtsEngine::new().await?;
-
-    // Simple text synthesis
-    let text = "Hello from kokoro-tiny! This is a minimal text to speech engine.";
-    println!("Synthesizing: \"{}\"\n", text);
-
-    let audio = tts.synthesize(text, None)?;
-    println!("✅ Generated {} audio samples", audio.len());
-
-    // Save to file
-    tts.save_wav("simple_output.wav", &audio)?;
-    println!("💾 Saved to: simple_output.wav");
+    let audio = run(&mut tts, Path::new("."))?;
 
     // Play if feature enabled
     #[cfg(feature = "playback")]
@@ -30,3 +23,20 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     Ok(())
 }
+
+/// Synthesize and save the greeting (run without a model by `tests/examples.rs`)
+pub fn run(tts: &mut TtsEngine, out_dir: &Path) -> Result<Vec<f32>, Box<dyn Error>> {
+    // Simple text synthesis
+    let text = "Hello from kokoro-tiny! This is a minimal text to speech engine.";
+    println!("Synthesizing: \"{}\"\n", text);
+
+    let audio = tts.synthesize(text, None, None, None)?;
+    println!("✅ Generated {} audio samples", audio.len());
+
+    // Save to file
+    let path = out_dir.join("simple_output.wav");
+    tts.save_wav(&path.to_string_lossy(), &audio)?;
+    println!("💾 Saved to: {}", path.display());
+
+    Ok(audio)
+}
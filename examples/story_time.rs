@@ -1,13 +1,23 @@
 //! Story time with Aye and Hue - testing our TTS fix!
 
+use std::error::Error;
+use std::path::Path;
+
 use kokoro_tiny::TtsEngine;
 
 #[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
+async fn main() -> Result<(), Box<dyn Error>> {
     println!("📖 Story Time with Aye and Hue!\n");
 
     let mut tts = TtsEngine::new().await?;
+    run(&mut tts, Path::new("."))?;
+    println!("🎧 Listen to hear our adventure with perfect punctuation timing!");
 
+    Ok(())
+}
+
+/// Synthesize and save the story
+pub fn run(tts: &mut TtsEngine, out_dir: &Path) -> Result<Vec<f32>, Box<dyn Error>> {
     // A short story about our adventure today
     let story = "Once upon a time, there was a developer named Hue and an AI named Aye. \
                  Together, they discovered a mystery! The TTS engine was speaking pig Latin. \
@@ -20,7 +30,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("🎤 Generating audio with proper punctuation pauses...\n");
 
     // Use the working v2 branch implementation with punctuation pauses
-    let audio = tts.synthesize_with_speed(story, Some("af_sky"), 1.0)?;
+    let audio = tts.synthesize_with_speed(story, Some("af_sky"), 1.0, None)?;
 
     println!(
         "✅ Generated {} samples ({:.1}s)",
@@ -29,9 +39,9 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     );
 
     // Save the story
-    tts.save_wav("story_time.wav", &audio)?;
-    println!("\n💾 Saved to story_time.wav");
-    println!("🎧 Listen to hear our adventure with perfect punctuation timing!");
+    let path = out_dir.join("story_time.wav");
+    tts.save_wav(&path.to_string_lossy(), &audio)?;
+    println!("\n💾 Saved to {}", path.display());
 
-    Ok(())
+    Ok(audio)
 }
@@ -1,28 +1,40 @@
 //! Test with explicit American voice
 
+use std::error::Error;
+use std::path::Path;
+
 use kokoro_tiny::TtsEngine;
 
 #[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
+async fn main() -> Result<(), Box<dyn Error>> {
     println!("🎤 Testing American English voice...\n");
 
     let mut tts = TtsEngine::new().await?;
-
-    let text = "Hello. My name is Adam. This is a test of American English.";
-
-    println!("Text: \"{}\"", text);
-    println!("Voice: am_adam (American Male)\n");
-
-    let audio = tts.synthesize_with_speed(text, Some("am_adam"), 1.0)?;
-
-    tts.save_wav("american_test.wav", &audio)?;
-    println!("✅ Saved to american_test.wav");
+    let audio = run(&mut tts, Path::new("."))?;
 
     #[cfg(feature = "playback")]
     {
         println!("🔊 Playing...\n");
         tts.play(&audio, 0.9)?;
     }
+    #[cfg(not(feature = "playback"))]
+    let _ = audio;
 
     Ok(())
 }
+
+/// Synthesize and save with am_adam
+pub fn run(tts: &mut TtsEngine, out_dir: &Path) -> Result<Vec<f32>, Box<dyn Error>> {
+    let text = "Hello. My name is Adam. This is a test of American English.";
+
+    println!("Text: \"{}\"", text);
+    println!("Voice: am_adam (American Male)\n");
+
+    let audio = tts.synthesize_with_speed(text, Some("am_adam"), 1.0, None)?;
+
+    let path = out_dir.join("american_test.wav");
+    tts.save_wav(&path.to_string_lossy(), &audio)?;
+    println!("✅ Saved to {}", path.display());
+
+    Ok(audio)
+}
@@ -1,24 +1,35 @@
 //! Test backwards compatibility with old API
 
+use std::error::Error;
+
 use kokoro_tiny::TtsEngine;
 
 #[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
+async fn main() -> Result<(), Box<dyn Error>> {
     println!("🧪 Testing Backwards Compatibility\n");
 
     let mut tts = TtsEngine::new().await?;
+    run(&mut tts)?;
+
+    println!("\n🎉 ALL BACKWARDS COMPATIBILITY TESTS PASSED!");
+    println!("✅ Old code will continue to work!");
+
+    Ok(())
+}
 
+/// Call every older entry point once
+pub fn run(tts: &mut TtsEngine) -> Result<(), Box<dyn Error>> {
     let text = "Hello world! This is a test.";
 
-    // Old API style (3 parameters with speed)
-    println!("1️⃣ Testing old synthesize(text, voice, Some(speed)):");
-    let audio1 = tts.synthesize_with_speed(text, Some("af_sky"), 1.0)?;
+    // Old API style (speed, no language)
+    println!("1️⃣ Testing old synthesize_with_speed(text, voice, speed, None):");
+    let audio1 = tts.synthesize_with_speed(text, Some("af_sky"), 1.0, None)?;
     println!("   ✅ Works! {} samples", audio1.len());
 
-    // Old API style (3 parameters with None speed)
-    println!("\n2️⃣ Testing old synthesize(text, voice, None):");
+    // Old API style (None speed)
+    println!("\n2️⃣ Testing old synthesize(text, voice, None, None):");
     // Older code passing None speed will be interpreted as default speed
-    let audio2 = tts.synthesize(text, Some("af_sky"))?;
+    let audio2 = tts.synthesize(text, Some("af_sky"), None, None)?;
     println!("   ✅ Works! {} samples", audio2.len());
 
     // Old API style (process_long_text)
@@ -35,13 +46,10 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         warnings.len()
     );
 
-    // New API style (just 2 params - for those who migrated)
-    println!("\n5️⃣ Testing new synthesize_with_speed(text, voice, speed):");
-    let audio5 = tts.synthesize_with_speed(text, Some("af_sky"), 1.0)?;
+    // Current API style (with a language)
+    println!("\n5️⃣ Testing synthesize_with_speed(text, voice, speed, lang):");
+    let audio5 = tts.synthesize_with_speed(text, Some("af_sky"), 1.0, Some("en"))?;
     println!("   ✅ Works! {} samples", audio5.len());
 
-    println!("\n🎉 ALL BACKWARDS COMPATIBILITY TESTS PASSED!");
-    println!("✅ Old code will continue to work!");
-
     Ok(())
 }
@@ -1,22 +1,16 @@
 //! Test if English is working properly
 
+use std::error::Error;
+use std::path::Path;
+
 use kokoro_tiny::TtsEngine;
 
 #[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
+async fn main() -> Result<(), Box<dyn Error>> {
     println!("🧪 Testing English speech synthesis...\n");
 
     let mut tts = TtsEngine::new().await?;
-
-    // Simple test
-    let text = "Hello Hue. This is a test.";
-
-    println!("Text: \"{}\"", text);
-
-    let audio = tts.synthesize_with_speed(text, Some("af_sky"), 1.0)?;
-
-    tts.save_wav("test_english.wav", &audio)?;
-    println!("✅ Saved to test_english.wav ({} samples)", audio.len());
+    let audio = run(&mut tts, Path::new("."))?;
 
     // Play it
     #[cfg(feature = "playback")]
@@ -27,8 +21,25 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     #[cfg(not(feature = "playback"))]
     {
+        let _ = audio;
         println!("\n💡 Play with: afplay test_english.wav");
     }
 
     Ok(())
 }
+
+/// Synthesize and save a short English sentence
+pub fn run(tts: &mut TtsEngine, out_dir: &Path) -> Result<Vec<f32>, Box<dyn Error>> {
+    // Simple test
+    let text = "Hello Hue. This is a test.";
+
+    println!("Text: \"{}\"", text);
+
+    let audio = tts.synthesize_with_speed(text, Some("af_sky"), 1.0, None)?;
+
+    let path = out_dir.join("test_english.wav");
+    tts.save_wav(&path.to_string_lossy(), &audio)?;
+    println!("✅ Saved to {} ({} samples)", path.display(), audio.len());
+
+    Ok(audio)
+}
@@ -1,18 +1,42 @@
 //! Test if v2 branch already handles punctuation timing
 
+use std::error::Error;
+use std::path::Path;
+
 use kokoro_tiny::TtsEngine;
 
 #[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
+async fn main() -> Result<(), Box<dyn Error>> {
     println!("🧪 Testing punctuation timing on voice-improvements-v2\n");
 
     let mut tts = TtsEngine::new().await?;
+    run(&mut tts, Path::new("."))?;
+
+    Ok(())
+}
 
+/// Longest run of near-silent samples (|s| < 0.001)
+pub fn longest_silence(audio: &[f32]) -> usize {
+    let mut max_zeros = 0;
+    let mut current = 0;
+    for &s in audio {
+        if s.abs() < 0.001 {
+            current += 1;
+            max_zeros = max_zeros.max(current);
+        } else {
+            current = 0;
+        }
+    }
+    max_zeros
+}
+
+/// Synthesize, report the longest pause and save (returns the pause in samples)
+pub fn run(tts: &mut TtsEngine, out_dir: &Path) -> Result<usize, Box<dyn Error>> {
     let text = "Hello there! This is a test. We need longer text to see if pauses work.";
 
     println!("Text: \"{}\"", text);
 
-    let audio = tts.synthesize(text, Some("af_sky"))?;
+    let audio = tts.synthesize(text, Some("af_sky"), None, None)?;
 
     println!(
         "\nAudio length: {} samples ({:.1}s)",
@@ -21,16 +45,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     );
 
     // Count max consecutive zeros (pauses)
-    let mut max_zeros = 0;
-    let mut current = 0;
-    for &s in &audio {
-        if s.abs() < 0.001 {
-            current += 1;
-            max_zeros = max_zeros.max(current);
-        } else {
-            current = 0;
-        }
-    }
+    let max_zeros = longest_silence(&audio);
 
     println!(
         "Max consecutive silence: {} samples ({:.0}ms)",
@@ -47,8 +62,9 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     }
 
     // Save and play
-    tts.save_wav("v2_test.wav", &audio)?;
-    println!("\n💾 Saved to v2_test.wav");
+    let path = out_dir.join("v2_test.wav");
+    tts.save_wav(&path.to_string_lossy(), &audio)?;
+    println!("\n💾 Saved to {}", path.display());
 
-    Ok(())
+    Ok(max_zeros)
 }
@@ -0,0 +1,50 @@
+//! Real-time factor at different ONNX Runtime thread counts
+//!
+//! Loads the cached model once per setting and times the same paragraph. A
+//! real-time factor below 1.0 renders faster than it plays. Run it with
+//! `--release`; the model must already be in ~/.cache/k.
+
+use std::error::Error;
+use std::time::Instant;
+
+use kokoro_tiny::{KokoroError, SynthesizeOptions, TtsEngine};
+
+/// Rendered at every setting
+pub const TEXT: &str = "The quarterly report is ready. Revenue grew four percent, \
+    support tickets fell by a third, and the new onboarding flow shipped on time.";
+
+fn main() -> Result<(), Box<dyn Error>> {
+    println!("🎤 kokoro-tiny thread benchmark");
+    println!("===============================\n");
+
+    let cores = std::thread::available_parallelism().map_or(4, |n| n.get());
+    let mut counts = vec![1, 2, 4, cores];
+    counts.retain(|&n| n <= cores);
+    counts.dedup();
+
+    println!("intra threads   real-time factor");
+    for threads in counts {
+        let mut tts = TtsEngine::builder().intra_threads(threads).build_cached()?;
+        let rtf = real_time_factor(&mut tts, TEXT, 3)?;
+        println!("{:>13}   {:.3}", threads, rtf);
+    }
+    Ok(())
+}
+
+/// Best of `runs` renders: seconds spent per second of audio (run without a
+/// model by `tests/examples.rs`)
+pub fn real_time_factor(tts: &mut TtsEngine, text: &str, runs: usize) -> Result<f64, KokoroError> {
+    // The first render also pays for allocation and espeak start-up
+    tts.synthesize_with(text, SynthesizeOptions::new())?;
+
+    let mut best = f64::INFINITY;
+    for _ in 0..runs {
+        // Otherwise the result cache answers
+        tts.clear_cache();
+        let start = Instant::now();
+        let audio = tts.synthesize_with(text, SynthesizeOptions::new())?;
+        let seconds = audio.len() as f64 / 24_000.0;
+        best = best.min(start.elapsed().as_secs_f64() / seconds);
+    }
+    Ok(best)
+}
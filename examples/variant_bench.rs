@@ -0,0 +1,52 @@
+//! Real-time factor of the fp32 model against the int8 one
+//!
+//! Loads each cached variant that's present and times the same paragraph
+//! (`thread_bench`'s measurement). Run it with `--release`; download the int8
+//! model once with `TtsEngine::builder().variant(ModelVariant::Int8).build()`.
+
+use std::error::Error;
+
+use kokoro_tiny::{KokoroError, ModelVariant, TtsEngine};
+
+// Also reached through here by `tests/examples.rs`, which can't load the
+// file twice
+#[path = "thread_bench.rs"]
+#[allow(dead_code)]
+pub mod thread_bench;
+
+fn main() -> Result<(), Box<dyn Error>> {
+    println!("🎤 kokoro-tiny model variant benchmark");
+    println!("======================================\n");
+
+    let mut engines = Vec::new();
+    for variant in [ModelVariant::Fp32, ModelVariant::Int8] {
+        match TtsEngine::builder().variant(variant).build_cached() {
+            Ok(tts) => engines.push(tts),
+            Err(e) => println!("{:>7}   skipped: {}", variant, e),
+        }
+    }
+
+    println!("variant   real-time factor");
+    for (variant, rtf) in compare(&mut engines, thread_bench::TEXT, 3)? {
+        println!("{:>7}   {:.3}", variant, rtf);
+    }
+    Ok(())
+}
+
+/// Best-of-`runs` real-time factor of each engine, labelled with its variant
+/// (run without a model by `tests/examples.rs`)
+pub fn compare(
+    engines: &mut [TtsEngine],
+    text: &str,
+    runs: usize,
+) -> Result<Vec<(ModelVariant, f64)>, KokoroError> {
+    engines
+        .iter_mut()
+        .map(|tts| {
+            Ok((
+                tts.model_variant(),
+                thread_bench::real_time_factor(tts, text, runs)?,
+            ))
+        })
+        .collect()
+}
@@ -0,0 +1,397 @@
+//! A/B renders for regression listening tests (`kokoro-ab`)
+//!
+//! Renders every line of a corpus with two option sets and writes each pair
+//! under neutral names, so listeners can't tell which side is which:
+//!
+//! ```text
+//! out/
+//!   001-x.wav, 001-y.wav   one of them is A, the other B
+//!   ...
+//!   report.json            per-item differences, items that need listening
+//!   answer_key.json        which file of each pair is A, the signed deltas
+//!                          (B - A) and the seed
+//! ```
+//!
+//! The report only holds measures that are the same whichever side is A, so
+//! reading it doesn't unblind the listening. Differences are exact to the
+//! sample; items whose duration moved by more than the threshold are flagged
+//! "needs listening". Identical option sets give an all-zero report.
+
+use std::f32::consts::PI;
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::rng::SplitMix64;
+use crate::{privacy, AudioDuration, SynthesizeOptions, TtsEngine, SAMPLE_RATE};
+
+/// Default duration change, in milliseconds, above which an item is flagged
+pub const DEFAULT_THRESHOLD_MS: f64 = 50.0;
+
+// Spectral distance: power at BANDS frequencies, averaged over FRAME-sample frames
+const FRAME: usize = 512;
+const BANDS: usize = 32;
+// Floor for silent bands so log10 stays finite (-100 dB)
+const POWER_FLOOR: f64 = 1e-10;
+
+/// Comparison of one corpus item
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AbItem {
+    /// 1-based, matches the file name prefix
+    pub index: usize,
+    pub text: String,
+    /// The pair as written: `[x, y]`
+    pub files: [String; 2],
+    /// How far apart the two lengths are; which one is longer is in the answer key
+    pub duration_difference_samples: u64,
+    pub duration_difference_ms: f64,
+    pub rms_difference: f32,
+    /// RMS difference of the long-term spectra, in dB (0 = same spectrum)
+    pub spectral_distance_db: f32,
+    pub needs_listening: bool,
+}
+
+/// `report.json`
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AbReport {
+    pub threshold_ms: f64,
+    pub options_a: SynthesizeOptions,
+    pub options_b: SynthesizeOptions,
+    pub items: Vec<AbItem>,
+    /// Indices of items flagged "needs listening"
+    pub needs_listening: Vec<usize>,
+}
+
+/// One pair in `answer_key.json`
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AbAnswer {
+    pub index: usize,
+    pub a: String,
+    pub b: String,
+    pub samples_a: usize,
+    pub samples_b: usize,
+    pub duration_delta_samples: i64,
+    pub duration_delta_ms: f64,
+    pub rms_delta: f32,
+}
+
+/// `answer_key.json`
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AnswerKey {
+    pub seed: u64,
+    pub answers: Vec<AbAnswer>,
+}
+
+/// Corpus items: one per non-empty line, `#` starts a comment line
+pub fn parse_corpus(text: &str) -> Vec<String> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_string)
+        .collect()
+}
+
+/// Render `corpus` with `a` and `b` into `out_dir` and write the report and answer key
+///
+/// `seed` decides the x/y assignment of each pair; the same seed gives the
+/// same layout.
+pub fn run_ab(
+    engine: &mut TtsEngine,
+    corpus: &[String],
+    a: &SynthesizeOptions,
+    b: &SynthesizeOptions,
+    out_dir: &Path,
+    threshold_ms: f64,
+    seed: u64,
+) -> Result<(AbReport, AnswerKey), String> {
+    fs::create_dir_all(out_dir)
+        .map_err(|e| format!("Failed to create {}: {}", out_dir.display(), e))?;
+
+    let mut rng = SplitMix64(seed);
+    let mut items = Vec::new();
+    let mut answers = Vec::new();
+
+    for (idx, text) in corpus.iter().enumerate() {
+        let index = idx + 1;
+        let audio_a = engine
+            .synthesize_with(text, a.clone())
+            .map_err(|e| format!("Item {} (A): {}", index, e))?;
+        let audio_b = engine
+            .synthesize_with(text, b.clone())
+            .map_err(|e| format!("Item {} (B): {}", index, e))?;
+
+        let files = [format!("{:03}-x.wav", index), format!("{:03}-y.wav", index)];
+        let (file_a, file_b) = if rng.next_u64() & 1 == 0 {
+            (&files[0], &files[1])
+        } else {
+            (&files[1], &files[0])
+        };
+        for (file, audio) in [(file_a, &audio_a), (file_b, &audio_b)] {
+            engine.save_wav(&out_dir.join(file).to_string_lossy(), audio)?;
+        }
+
+        let delta = audio_b.len() as i64 - audio_a.len() as i64;
+        let delta_ms = AudioDuration::of(&audio_b).millis_since(AudioDuration::of(&audio_a));
+        let rms_delta = rms(&audio_b) - rms(&audio_a);
+        answers.push(AbAnswer {
+            index,
+            a: file_a.clone(),
+            b: file_b.clone(),
+            samples_a: audio_a.len(),
+            samples_b: audio_b.len(),
+            duration_delta_samples: delta,
+            duration_delta_ms: delta_ms,
+            rms_delta,
+        });
+        items.push(AbItem {
+            index,
+            text: privacy::display_text(text).into_owned(),
+            duration_difference_samples: delta.unsigned_abs(),
+            duration_difference_ms: delta_ms.abs(),
+            rms_difference: rms_delta.abs(),
+            spectral_distance_db: spectral_distance(&audio_a, &audio_b),
+            needs_listening: delta_ms.abs() > threshold_ms,
+            files,
+        });
+    }
+
+    let report = AbReport {
+        threshold_ms,
+        options_a: a.clone(),
+        options_b: b.clone(),
+        needs_listening: items
+            .iter()
+            .filter(|item| item.needs_listening)
+            .map(|item| item.index)
+            .collect(),
+        items,
+    };
+    let key = AnswerKey { seed, answers };
+
+    write_json(&out_dir.join("report.json"), &report)?;
+    write_json(&out_dir.join("answer_key.json"), &key)?;
+    Ok((report, key))
+}
+
+fn write_json(path: &Path, value: &impl Serialize) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(value)
+        .map_err(|e| format!("Failed to serialize {}: {}", path.display(), e))?;
+    fs::write(path, json).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+fn rms(audio: &[f32]) -> f32 {
+    if audio.is_empty() {
+        return 0.0;
+    }
+    (audio.iter().map(|s| s * s).sum::<f32>() / audio.len() as f32).sqrt()
+}
+
+/// RMS difference in dB between the average spectra of `a` and `b`
+fn spectral_distance(a: &[f32], b: &[f32]) -> f32 {
+    let (spectrum_a, spectrum_b) = (long_term_spectrum(a), long_term_spectrum(b));
+    let squared: f64 = spectrum_a
+        .iter()
+        .zip(&spectrum_b)
+        .map(|(x, y)| (x - y) * (x - y))
+        .sum();
+    (squared / BANDS as f64).sqrt() as f32
+}
+
+/// Mean power per band over Hann-windowed frames, in dB
+fn long_term_spectrum(audio: &[f32]) -> Vec<f64> {
+    let window: Vec<f32> = (0..FRAME)
+        .map(|n| 0.5 - 0.5 * (2.0 * PI * n as f32 / FRAME as f32).cos())
+        .collect();
+    // Bands evenly spaced between DC and Nyquist, both excluded
+    let bins: Vec<f32> = (1..=BANDS)
+        .map(|band| (band * FRAME / 2 / (BANDS + 1)) as f32)
+        .collect();
+
+    let mut power = vec![0.0f64; BANDS];
+    let mut frames = 0;
+    for frame in audio.chunks_exact(FRAME) {
+        frames += 1;
+        for (band, &bin) in bins.iter().enumerate() {
+            let (mut re, mut im) = (0.0f32, 0.0f32);
+            for (n, (&sample, &w)) in frame.iter().zip(&window).enumerate() {
+                let phase = 2.0 * PI * bin * n as f32 / FRAME as f32;
+                re += sample * w * phase.cos();
+                im -= sample * w * phase.sin();
+            }
+            power[band] += (re * re + im * im) as f64;
+        }
+    }
+
+    power
+        .into_iter()
+        .map(|p| 10.0 * (p / frames.max(1) as f64 + POWER_FLOOR).log10())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    const CORPUS: &str =
+        "# regression corpus\nBuild complete.\n\nTests passed, deploying now.\nAll done!\n";
+
+    // Length follows the model speed, so speed changes show up as duration deltas
+    fn engine() -> TtsEngine {
+        let mut engine = TtsEngine::mock(200);
+        engine.mock_inference = Some(Arc::new(|tokens: &[i64], _style: &[f32], speed: f32| {
+            let len = (tokens.len() as f32 * 200.0 / speed) as usize;
+            Ok((0..len).map(|i| (i as f32 * 0.07).sin() * 0.3).collect())
+        }));
+        engine
+    }
+
+    fn out_dir(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("kokoro-ab-{}-{}", name, std::process::id()))
+    }
+
+    fn samples(path: &Path) -> usize {
+        hound::WavReader::open(path).unwrap().len() as usize
+    }
+
+    #[test]
+    fn report_and_answer_key_follow_the_schema() {
+        let dir = out_dir("schema");
+        let corpus = parse_corpus(CORPUS);
+        assert_eq!(corpus.len(), 3);
+
+        let a = SynthesizeOptions::new();
+        let b = SynthesizeOptions::new().speed(1.5);
+        run_ab(&mut engine(), &corpus, &a, &b, &dir, 10.0, 7).unwrap();
+
+        let report: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(dir.join("report.json")).unwrap()).unwrap();
+        for key in [
+            "threshold_ms",
+            "options_a",
+            "options_b",
+            "items",
+            "needs_listening",
+        ] {
+            assert!(report.get(key).is_some(), "report.json lacks {}", key);
+        }
+        assert_eq!(report["options_b"]["speed"], 1.5);
+        let item = &report["items"][0];
+        for key in [
+            "index",
+            "text",
+            "files",
+            "duration_difference_samples",
+            "duration_difference_ms",
+            "rms_difference",
+            "spectral_distance_db",
+            "needs_listening",
+        ] {
+            assert!(item.get(key).is_some(), "report item lacks {}", key);
+        }
+        // Nothing in the report says which file is A
+        let object = item.as_object().unwrap();
+        assert_eq!(object.len(), 8, "{:?}", object.keys());
+        assert!(item["duration_difference_samples"].as_u64().unwrap() > 0);
+        // Faster B is shorter everywhere, by well over 10 ms
+        assert_eq!(report["needs_listening"], serde_json::json!([1, 2, 3]));
+
+        let key: AnswerKey =
+            serde_json::from_str(&fs::read_to_string(dir.join("answer_key.json")).unwrap())
+                .unwrap();
+        assert_eq!(key.seed, 7);
+        assert_eq!(key.answers.len(), 3);
+        assert!(key.answers[0].duration_delta_samples < 0);
+
+        fs::remove_dir_all(dir).ok();
+    }
+
+    #[test]
+    fn answer_key_points_at_the_right_renders() {
+        let dir = out_dir("pairing");
+        let corpus = parse_corpus(CORPUS);
+        let a = SynthesizeOptions::new();
+        let b = SynthesizeOptions::new().speed(1.5);
+        let (report, key) = run_ab(&mut engine(), &corpus, &a, &b, &dir, 10.0, 42).unwrap();
+
+        for (item, answer) in report.items.iter().zip(&key.answers) {
+            assert_eq!(item.index, answer.index);
+            assert_ne!(answer.a, answer.b);
+            assert!(item.files.contains(&answer.a) && item.files.contains(&answer.b));
+            assert_eq!(samples(&dir.join(&answer.a)), answer.samples_a);
+            assert_eq!(samples(&dir.join(&answer.b)), answer.samples_b);
+            assert_eq!(
+                item.duration_difference_samples,
+                answer.duration_delta_samples.unsigned_abs()
+            );
+        }
+
+        // Same seed, same layout; the assignment isn't fixed to x = A
+        let (_, again) = run_ab(&mut engine(), &corpus, &a, &b, &dir, 10.0, 42).unwrap();
+        assert_eq!(again, key);
+        let x_is_a: Vec<bool> = (0..16)
+            .map(|seed| {
+                let (_, key) =
+                    run_ab(&mut engine(), &corpus[..1], &a, &b, &dir, 10.0, seed).unwrap();
+                key.answers[0].a.ends_with("-x.wav")
+            })
+            .collect();
+        assert!(x_is_a.contains(&true) && x_is_a.contains(&false));
+
+        fs::remove_dir_all(dir).ok();
+    }
+
+    #[test]
+    fn identical_options_give_an_all_zero_diff() {
+        let dir = out_dir("identical");
+        let opts = SynthesizeOptions::new().speed(1.2).gain(1.4);
+        let (report, key) = run_ab(
+            &mut engine(),
+            &parse_corpus(CORPUS),
+            &opts,
+            &opts,
+            &dir,
+            0.0,
+            1,
+        )
+        .unwrap();
+
+        assert!(report.needs_listening.is_empty());
+        for (item, answer) in report.items.iter().zip(&key.answers) {
+            assert_eq!(item.duration_difference_samples, 0);
+            assert_eq!(item.duration_difference_ms, 0.0);
+            assert_eq!(item.rms_difference, 0.0);
+            assert_eq!(item.spectral_distance_db, 0.0);
+            assert_eq!(answer.duration_delta_samples, 0);
+            assert_eq!(answer.rms_delta, 0.0);
+        }
+
+        fs::remove_dir_all(dir).ok();
+    }
+
+    #[test]
+    fn option_files_may_be_partial() {
+        let opts: SynthesizeOptions = toml::from_str(
+            "speed = 1.1\npacing = \"audiobook\"\n\n[punctuation_weights]\ncomma = 1.5\n",
+        )
+        .unwrap();
+        assert_eq!(opts.speed, 1.1);
+        assert_eq!(opts.pacing, Some(crate::PacingPreset::Audiobook));
+        assert_eq!(opts.punctuation_weights.comma, 1.5);
+        assert_eq!(opts.punctuation_weights.period, 1.0);
+        assert_eq!(opts.gain, SynthesizeOptions::default().gain);
+    }
+
+    #[test]
+    fn spectral_distance_tells_tones_apart() {
+        let tone = |freq: f32| -> Vec<f32> {
+            (0..SAMPLE_RATE as usize / 4)
+                .map(|n| (2.0 * PI * freq * n as f32 / SAMPLE_RATE as f32).sin() * 0.5)
+                .collect()
+        };
+        let low = tone(440.0);
+        assert_eq!(spectral_distance(&low, &low), 0.0);
+        assert!(spectral_distance(&low, &tone(3000.0)) > 10.0);
+    }
+}
@@ -0,0 +1,120 @@
+//! kokoro-ab: render a corpus with two option sets for blind A/B listening
+//!
+//! # Usage
+//! ```text
+//! kokoro-ab --corpus corpus.txt --options-a a.toml --options-b b.toml --out ab/
+//! ```
+//!
+//! Option files are `SynthesizeOptions` in TOML; missing fields keep their
+//! defaults:
+//! ```toml
+//! voice = "af_sky"
+//! speed = 1.1
+//! pacing = "audiobook"
+//!
+//! [punctuation_weights]
+//! comma = 1.5
+//! ```
+
+use clap::Parser;
+use kokoro_tiny::ab::{parse_corpus, run_ab, DEFAULT_THRESHOLD_MS};
+use kokoro_tiny::{SynthesizeOptions, TtsEngine};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[derive(Parser)]
+#[command(name = "kokoro-ab")]
+#[command(about = "🎧 Render a corpus two ways for blind A/B listening", long_about = None)]
+struct Cli {
+    /// Text file with one item per line (# starts a comment)
+    #[arg(long)]
+    corpus: PathBuf,
+
+    /// Synthesis options for side A (TOML)
+    #[arg(long)]
+    options_a: PathBuf,
+
+    /// Synthesis options for side B (TOML)
+    #[arg(long)]
+    options_b: PathBuf,
+
+    /// Output directory for the WAV pairs, report.json and answer_key.json
+    #[arg(long)]
+    out: PathBuf,
+
+    /// Flag items whose duration changed by more than this many milliseconds
+    #[arg(long, default_value_t = DEFAULT_THRESHOLD_MS)]
+    threshold_ms: f64,
+
+    /// Seed for the x/y assignment (default: time based, recorded in the answer key)
+    #[arg(long)]
+    seed: Option<u64>,
+}
+
+fn load_options(path: &Path) -> Result<SynthesizeOptions, String> {
+    let text = fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    toml::from_str(&text).map_err(|e| format!("Invalid options in {}: {}", path.display(), e))
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?;
+    let cli = Cli::parse();
+
+    let corpus = parse_corpus(
+        &fs::read_to_string(&cli.corpus)
+            .map_err(|e| format!("Failed to read {}: {}", cli.corpus.display(), e))?,
+    );
+    if corpus.is_empty() {
+        return Err(format!("No items in {}", cli.corpus.display()).into());
+    }
+    let a = load_options(&cli.options_a)?;
+    let b = load_options(&cli.options_b)?;
+    let seed = cli.seed.unwrap_or_else(|| {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or_default()
+    });
+
+    let mut engine = rt.block_on(TtsEngine::new())?;
+    let (report, _) = run_ab(
+        &mut engine,
+        &corpus,
+        &a,
+        &b,
+        &cli.out,
+        cli.threshold_ms,
+        seed,
+    )?;
+
+    println!(
+        "🎧 {} pairs written to {}",
+        report.items.len(),
+        cli.out.display()
+    );
+    if report.needs_listening.is_empty() {
+        println!("✅ No duration changes above {} ms", cli.threshold_ms);
+    } else {
+        println!(
+            "👂 {} item(s) need listening (duration change > {} ms):",
+            report.needs_listening.len(),
+            cli.threshold_ms
+        );
+        for item in report.items.iter().filter(|item| item.needs_listening) {
+            println!(
+                "   {:03}  {:.1} ms  {:?}",
+                item.index, item.duration_difference_ms, item.text
+            );
+        }
+    }
+    println!(
+        "🔑 Answer key: {}",
+        cli.out.join("answer_key.json").display()
+    );
+
+    Ok(())
+}
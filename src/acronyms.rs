@@ -0,0 +1,285 @@
+//! How acronyms and initialisms are read
+//!
+//! Left to itself espeak guesses: "NASA" comes out right, "SQL" as one
+//! mumbled syllable. This pass decides before espeak sees the text:
+//!
+//! - a built-in table of common ones: "HTTP", "API" and "SQL" are spelled
+//!   ("H T T P"), "NASA" and "RAM" read as words, "JSON" and "ONNX" said the
+//!   way people say them ("jason", "onyx")
+//! - anything else in capitals without a vowel ("XYZ", "PNG") is spelled
+//! - `TtsEngine::add_acronym` adds or overrides entries
+//!
+//! Acronyms are found inside words too: "APIs" is read "A P I's", "GraphQL"
+//! "Graph Q L" and "HTTPServer" "H T T P Server". Matching is case-sensitive,
+//! so "US" is spelled and "us" isn't touched.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+/// How an acronym is read
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Spoken {
+    /// Letter by letter: "SQL" -> "S Q L"
+    Letters,
+    /// As a word: "NASA"
+    Word,
+    /// Exactly this text: `Custom("sequel".into())`
+    Custom(String),
+}
+
+// Spelled letter by letter
+const LETTERS: &[&str] = &[
+    "AI", "API", "AWS", "CD", "CEO", "CI", "CLI", "CPU", "CSS", "CSV", "CTO", "DB", "DNS", "DVD",
+    "ETA", "EU", "FAQ", "FBI", "FYI", "GCP", "GPT", "GPU", "GUI", "HDD", "HR", "HTML", "HTTP",
+    "HTTPS", "ID", "IO", "IP", "JS", "JWT", "LLM", "MCP", "ML", "OS", "PDF", "PHP", "PR", "SDK",
+    "SMS", "SQL", "SSD", "SSH", "SSL", "TCP", "TLS", "TTS", "UDP", "UI", "UK", "URL", "US", "USA",
+    "USB", "UX", "VM", "VPN", "XML",
+];
+
+// Read as a word
+const WORDS: &[&str] = &[
+    "AJAX", "CRUD", "GIF", "LAN", "LASER", "NASA", "NATO", "PIN", "RAID", "RAM", "REST", "SIM",
+    "UNICEF", "WAN", "WASM", "YAML",
+];
+
+// Said the way people say them
+const SAID: &[(&str, &str)] = &[
+    ("ASCII", "askee"),
+    ("JSON", "jason"),
+    ("ONNX", "onyx"),
+    ("SCSI", "scuzzy"),
+];
+
+// Capitals without a vowel that aren't acronyms: interjections and titles
+const NOT_ACRONYMS: &[&str] = &[
+    "BRR", "DR", "GRR", "HMM", "JR", "MM", "MR", "MRS", "MS", "PFFT", "PSST", "SHH", "SR", "ST",
+    "TSK", "ZZZ",
+];
+
+/// The acronyms an engine reads in a particular way, on top of the built-in ones
+///
+/// # Example
+/// ```
+/// use kokoro_tiny::{Acronyms, Spoken};
+///
+/// let mut acronyms = Acronyms::default();
+/// assert_eq!(acronyms.get("SQL"), Some(Spoken::Letters));
+/// assert_eq!(acronyms.get("XKCD"), Some(Spoken::Letters)); // no vowel
+/// assert_eq!(acronyms.get("NASA"), Some(Spoken::Word));
+///
+/// acronyms.insert("SQL", Spoken::Custom("sequel".into()));
+/// assert_eq!(acronyms.get("SQL"), Some(Spoken::Custom("sequel".into())));
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Acronyms {
+    custom: HashMap<String, Spoken>,
+}
+
+impl Acronyms {
+    /// Read `acronym` as `spoken` from now on
+    pub fn insert(&mut self, acronym: &str, spoken: Spoken) {
+        self.custom.insert(acronym.to_string(), spoken);
+    }
+
+    /// How `word` is read, or `None` for an ordinary word
+    pub fn get(&self, word: &str) -> Option<Spoken> {
+        if let Some(spoken) = self.custom.get(word) {
+            return Some(spoken.clone());
+        }
+        if LETTERS.contains(&word) {
+            return Some(Spoken::Letters);
+        }
+        if WORDS.contains(&word) {
+            return Some(Spoken::Word);
+        }
+        if let Some((_, said)) = SAID.iter().find(|(acronym, _)| *acronym == word) {
+            return Some(Spoken::Custom(said.to_string()));
+        }
+        let letters = word.chars().count();
+        let vowelless = word
+            .chars()
+            .all(|c| c.is_ascii_uppercase() && !"AEIOUY".contains(c));
+        (letters > 1 && vowelless && !NOT_ACRONYMS.contains(&word)).then_some(Spoken::Letters)
+    }
+
+    /// `word` (punctuation around it included) with its acronyms read out
+    ///
+    /// Returns `None` when it has none.
+    ///
+    /// # Example
+    /// ```
+    /// use kokoro_tiny::Acronyms;
+    ///
+    /// let acronyms = Acronyms::default();
+    /// assert_eq!(acronyms.read("APIs,").as_deref(), Some("A P I's,"));
+    /// assert_eq!(acronyms.read("GraphQL").as_deref(), Some("Graph Q L"));
+    /// assert_eq!(acronyms.read("NASA's").as_deref(), Some("nasa's"));
+    /// assert_eq!(acronyms.read("Hello"), None);
+    /// ```
+    pub fn read(&self, word: &str) -> Option<String> {
+        let start = word.find(|c: char| c.is_alphanumeric())?;
+        let end = word
+            .rfind(|c: char| c.is_alphanumeric())
+            .map(|idx| idx + word[idx..].chars().next().map_or(1, char::len_utf8))?;
+        let core = &word[start..end];
+
+        let spoken = self
+            .whole(core)
+            .or_else(|| {
+                self.whole(core.strip_suffix("'s")?)
+                    .map(|s| format!("{}'s", s))
+            })
+            .or_else(|| self.plural(core))
+            .or_else(|| self.parts(core))?;
+        Some(format!("{}{}{}", &word[..start], spoken, &word[end..]))
+    }
+
+    // Every word of `text`, splitting at punctuation inside words ("CI/CD"),
+    // except those `skip` keeps as they are
+    pub(crate) fn read_all(&self, text: &str, skip: impl Fn(&str) -> bool) -> Option<String> {
+        let mut out = String::with_capacity(text.len());
+        let mut changed = false;
+        let mut rest = text;
+        while let Some(start) = rest.find(char::is_alphanumeric) {
+            out.push_str(&rest[..start]);
+            rest = &rest[start..];
+            let end = rest
+                .find(|c: char| !c.is_alphanumeric() && c != '\'')
+                .unwrap_or(rest.len());
+            let word = &rest[..end];
+            match self.read(word).filter(|_| !skip(word)) {
+                Some(spoken) => {
+                    out.push_str(&spoken);
+                    changed = true;
+                }
+                None => out.push_str(word),
+            }
+            rest = &rest[end..];
+        }
+        out.push_str(rest);
+        changed.then_some(out)
+    }
+
+    fn whole(&self, word: &str) -> Option<String> {
+        Some(say(word, &self.get(word)?))
+    }
+
+    // "APIs" -> "A P I's", "VMs" -> "V M's"
+    fn plural(&self, word: &str) -> Option<String> {
+        let singular = word.strip_suffix('s')?;
+        if !singular.chars().all(|c| c.is_ascii_uppercase()) {
+            return None;
+        }
+        Some(match self.get(singular)? {
+            Spoken::Letters => format!("{}'s", spell(singular)),
+            spoken => format!("{}s", say(singular, &spoken)),
+        })
+    }
+
+    // Acronyms inside a word: "GraphQL", "HTTPServer", "macOS"
+    fn parts(&self, word: &str) -> Option<String> {
+        let parts = camel_parts(word);
+        if parts.len() < 2 {
+            return None;
+        }
+        let mut changed = false;
+        let spoken: Vec<String> = parts
+            .iter()
+            .map(|part| match self.whole(part) {
+                Some(spoken) if part.chars().count() > 1 => {
+                    changed = true;
+                    spoken
+                }
+                _ => part.to_string(),
+            })
+            .collect();
+        changed.then(|| spoken.join(" "))
+    }
+}
+
+fn say(word: &str, spoken: &Spoken) -> String {
+    match spoken {
+        Spoken::Letters => spell(word),
+        // In lower case espeak can't mistake it for one to spell
+        Spoken::Word => word.to_lowercase(),
+        Spoken::Custom(text) => text.clone(),
+    }
+}
+
+// "SQL" -> "S Q L"
+fn spell(word: &str) -> String {
+    word.chars().map(String::from).collect::<Vec<_>>().join(" ")
+}
+
+// Split where the case changes: "GraphQL" -> ["Graph", "QL"], "HTTPServer" ->
+// ["HTTP", "Server"]; digits stay with the letters before them ("EC2")
+fn camel_parts(word: &str) -> Vec<&str> {
+    let chars: Vec<(usize, char)> = word.char_indices().collect();
+    let mut parts = Vec::new();
+    let mut start = 0;
+    for i in 1..chars.len() {
+        let (idx, c) = chars[i];
+        let prev = chars[i - 1].1;
+        let next_lower = chars.get(i + 1).is_some_and(|(_, n)| n.is_lowercase());
+        let boundary = c.is_uppercase()
+            && (prev.is_lowercase()
+                || prev.is_ascii_digit()
+                || (prev.is_uppercase() && next_lower));
+        if boundary {
+            parts.push(&word[start..idx]);
+            start = idx;
+        }
+    }
+    parts.push(&word[start..]);
+    parts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn acronyms_are_spelled_or_read_as_words() {
+        let acronyms = Acronyms::default();
+        let cases = [
+            ("HTTP", Some("H T T P")),
+            ("(SQL)", Some("(S Q L)")),
+            ("NASA.", Some("nasa.")),
+            ("JSON", Some("jason")),
+            ("XKCD", Some("X K C D")),
+            ("APIs", Some("A P I's")),
+            ("GPUs.", Some("G P U's.")),
+            ("NASA's", Some("nasa's")),
+            ("GraphQL", Some("Graph Q L")),
+            ("HTTPServer", Some("H T T P Server")),
+            ("macOS", Some("mac O S")),
+            ("PostgreSQL", Some("Postgre S Q L")),
+            ("Hello", None),
+            ("us", None),
+            ("A", None),
+            ("HMM", None),
+            ("OK", None),
+            ("iPhone", None),
+            ("42", None),
+        ];
+        for (word, expected) in cases {
+            assert_eq!(acronyms.read(word).as_deref(), expected, "{}", word);
+        }
+    }
+
+    #[test]
+    fn own_entries_win_over_the_built_in_ones() {
+        let mut acronyms = Acronyms::default();
+        acronyms.insert("SQL", Spoken::Custom("sequel".into()));
+        acronyms.insert("GIF", Spoken::Letters);
+        acronyms.insert("KOKORO", Spoken::Word);
+
+        assert_eq!(acronyms.read("SQL").as_deref(), Some("sequel"));
+        assert_eq!(acronyms.read("SQLs").as_deref(), Some("sequels"));
+        assert_eq!(acronyms.read("MySQL").as_deref(), Some("My sequel"));
+        assert_eq!(acronyms.read("GIF").as_deref(), Some("G I F"));
+        assert_eq!(acronyms.read("KOKORO").as_deref(), Some("kokoro"));
+    }
+}
@@ -0,0 +1,483 @@
+//! Fades at the edges of a clip, and stereo placement
+//!
+//! A waveform that starts or stops away from zero clicks: at the start of
+//! playback, where playback is cut off, and wherever two clips are joined
+//! end to end. A few milliseconds of fade at each edge is too short to hear
+//! but takes the click away. Synthesis results get one by default
+//! (`SynthesizeOptions::edge_fade_ms`); these helpers are for audio put
+//! together by hand.
+//!
+//! Chunks of a long text are joined with a short crossfade. The fade is
+//! equal-power, so two unrelated signals keep their loudness through it
+//! (a linear fade dips by up to 3 dB in the middle, which on a long
+//! render is heard as a rhythmic "breathing"), and the incoming chunk enters
+//! at a zero crossing heading the same way as the outgoing one, so the two
+//! don't start out cancelling each other.
+//!
+//! Model output is cleaned up before anything else sees it: NaN and infinite
+//! samples (rare, with some voices at extreme speeds) become silence, and a
+//! DC offset is taken out.
+//!
+//! The model speaks in mono. `to_stereo` places it between two channels,
+//! interleaved left, right, left, ...; `SynthesizeOptions::channels` does the
+//! same to a synthesis result.
+//!
+//! Lengths are at the model's 24kHz.
+
+use std::f32::consts::{FRAC_PI_2, FRAC_PI_4};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{AudioDuration, SAMPLE_RATE};
+
+/// Edge fade applied to synthesis results unless set otherwise
+pub const DEFAULT_EDGE_FADE_MS: u32 = 5;
+
+// A mean level further from zero than this is a DC offset (about -54 dBFS)
+const DC_THRESHOLD: f32 = 0.002;
+
+// How far into the incoming clip a crossfade may start, to start at a zero
+// crossing
+const ZERO_CROSSING_SEARCH_MS: u64 = 2;
+
+/// Channel layout of synthesis results
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Channels {
+    #[default]
+    Mono,
+    /// Interleaved left/right, the voice placed at `pan` (-1.0 = left,
+    /// 0.0 = center, 1.0 = right)
+    Stereo { pan: f32 },
+}
+
+impl Channels {
+    /// Samples per frame
+    pub fn count(self) -> u16 {
+        match self {
+            Channels::Mono => 1,
+            Channels::Stereo { .. } => 2,
+        }
+    }
+}
+
+/// Mono `audio` as interleaved stereo, placed at `pan` (-1.0 = left, 1.0 =
+/// right) with the constant-power law, so it sounds equally loud anywhere
+///
+/// # Example
+/// ```
+/// use kokoro_tiny::audio::to_stereo;
+///
+/// let stereo = to_stereo(&[0.5, 0.5], -1.0);
+/// assert_eq!(stereo, [0.5, 0.0, 0.5, 0.0]);
+/// ```
+pub fn to_stereo(audio: &[f32], pan: f32) -> Vec<f32> {
+    let pan = if pan.is_nan() {
+        0.0
+    } else {
+        pan.clamp(-1.0, 1.0)
+    };
+    let angle = (pan + 1.0) * FRAC_PI_4;
+    // Exact at the extremes, where cos and sin leave a trace in the other side
+    let (left, right) = match pan {
+        -1.0 => (1.0, 0.0),
+        1.0 => (0.0, 1.0),
+        _ => (angle.cos(), angle.sin()),
+    };
+    audio.iter().flat_map(|&s| [s * left, s * right]).collect()
+}
+
+// Interleaved audio averaged down to one channel
+pub(crate) fn downmix(audio: &[f32], channels: u16) -> Vec<f32> {
+    let channels = usize::from(channels.max(1));
+    audio
+        .chunks_exact(channels)
+        .map(|frame| frame.iter().sum::<f32>() / channels as f32)
+        .collect()
+}
+
+/// Fade the first `ms` of `audio` in from silence
+///
+/// # Example
+/// ```
+/// use kokoro_tiny::audio::fade_in;
+///
+/// let mut audio = vec![0.5; 480];
+/// fade_in(&mut audio, 5);
+/// assert_eq!(audio[0], 0.0);
+/// assert!(audio[60] > 0.2 && audio[60] < 0.3);
+/// assert_eq!(audio[120], 0.5);
+/// ```
+pub fn fade_in(audio: &mut [f32], ms: u32) {
+    let len = fade_len(audio.len(), ms);
+    for (i, sample) in audio.iter_mut().take(len).enumerate() {
+        *sample *= i as f32 / len as f32;
+    }
+}
+
+/// Fade the last `ms` of `audio` out to silence
+///
+/// # Example
+/// ```
+/// use kokoro_tiny::audio::fade_out;
+///
+/// let mut audio = vec![0.5; 480];
+/// fade_out(&mut audio, 5);
+/// assert_eq!(audio[479], 0.0);
+/// assert_eq!(audio[359], 0.5);
+/// ```
+pub fn fade_out(audio: &mut [f32], ms: u32) {
+    let len = fade_len(audio.len(), ms);
+    for (i, sample) in audio.iter_mut().rev().take(len).enumerate() {
+        *sample *= i as f32 / len as f32;
+    }
+}
+
+/// `fade_in` and `fade_out` together; on a clip shorter than both fades,
+/// each takes half of it
+///
+/// # Example
+/// ```
+/// use kokoro_tiny::audio::apply_edges;
+///
+/// let mut chunk = vec![0.5; 2400];
+/// apply_edges(&mut chunk, 5);
+/// assert_eq!((chunk[0], chunk[2399]), (0.0, 0.0));
+/// ```
+pub fn apply_edges(audio: &mut [f32], ms: u32) {
+    let len = fade_len(audio.len() / 2, ms);
+    fade_samples(audio, len);
+}
+
+// Both edges faded over `len` samples each
+pub(crate) fn fade_samples(audio: &mut [f32], len: usize) {
+    let len = len.min(audio.len() / 2);
+    for i in 0..len {
+        let gain = i as f32 / len as f32;
+        audio[i] *= gain;
+        let end = audio.len() - 1 - i;
+        audio[end] *= gain;
+    }
+}
+
+/// Shape of a crossfade
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CrossfadeCurve {
+    /// Gains add up to 1; unrelated signals dip by up to 3 dB halfway
+    Linear,
+    /// Powers add up to 1; unrelated signals stay as loud throughout. What
+    /// synthesis uses between chunks
+    #[default]
+    EqualPower,
+}
+
+/// `next` joined onto the end of `buffer`, the last `overlap_samples` of
+/// `buffer` fading out as `next` fades in
+///
+/// Up to 2 ms of the start of `next` is skipped so it enters at a zero
+/// crossing heading the same way as `buffer` at the join. Of `buffer`, only
+/// the overlap changes.
+///
+/// # Example
+/// ```
+/// use kokoro_tiny::audio::{crossfade, CrossfadeCurve};
+///
+/// let mut speech = vec![0.5; 2400];
+/// crossfade(&mut speech, &[0.5; 2400], 240, CrossfadeCurve::Linear);
+/// assert_eq!(speech.len(), 4560);
+/// assert!(speech.iter().all(|&s| (s - 0.5).abs() < 1e-6));
+/// ```
+pub fn crossfade(
+    buffer: &mut Vec<f32>,
+    next: &[f32],
+    overlap_samples: usize,
+    curve: CrossfadeCurve,
+) {
+    let overlap = overlap_samples.min(buffer.len()).min(next.len());
+    if overlap == 0 {
+        buffer.extend_from_slice(next);
+        return;
+    }
+
+    let start = buffer.len() - overlap;
+    let rising = overlap < 2 || buffer[start + 1] >= buffer[start];
+    let search = AudioDuration::from_millis(ZERO_CROSSING_SEARCH_MS)
+        .to_samples(SAMPLE_RATE)
+        .min(next.len() - overlap);
+    let skip = (0..search)
+        .find(|&i| {
+            let (a, b) = (next[i], next[i + 1]);
+            if rising {
+                a <= 0.0 && b > 0.0
+            } else {
+                a >= 0.0 && b < 0.0
+            }
+        })
+        .map_or(0, |i| i + 1);
+    let next = &next[skip..];
+
+    for i in 0..overlap {
+        let t = i as f32 / overlap as f32;
+        let (fade_out, fade_in) = match curve {
+            CrossfadeCurve::Linear => (1.0 - t, t),
+            CrossfadeCurve::EqualPower => ((t * FRAC_PI_2).cos(), (t * FRAC_PI_2).sin()),
+        };
+        buffer[start + i] = buffer[start + i] * fade_out + next[i] * fade_in;
+    }
+    buffer.extend_from_slice(&next[overlap..]);
+}
+
+// Model output made safe to encode: non-finite samples set to 0 and any DC
+// offset removed. An offset is a shift under a signal, so a steady level with
+// nothing on top (more DC power than AC) is left alone rather than silenced.
+// Returns how many samples weren't finite.
+pub(crate) fn sanitize(audio: &mut [f32]) -> usize {
+    let mut non_finite = 0;
+    let (mut sum, mut squares) = (0.0f64, 0.0f64);
+    for sample in audio.iter_mut() {
+        if !sample.is_finite() {
+            *sample = 0.0;
+            non_finite += 1;
+        }
+        sum += f64::from(*sample);
+        squares += f64::from(*sample).powi(2);
+    }
+    let len = audio.len().max(1) as f64;
+    let mean = sum / len;
+    // Mean square is DC power plus AC power
+    if mean.abs() > f64::from(DC_THRESHOLD) && squares / len > 2.0 * mean * mean {
+        let mean = mean as f32;
+        for sample in audio.iter_mut() {
+            *sample -= mean;
+        }
+    }
+    non_finite
+}
+
+// Samples `ms` takes, at most `available`
+fn fade_len(available: usize, ms: u32) -> usize {
+    AudioDuration::from_millis(u64::from(ms))
+        .to_samples(SAMPLE_RATE)
+        .min(available)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{SynthesisWarning, SynthesizeOptions, TtsEngine, DEFAULT_VOICE, STYLE_DIM};
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    #[test]
+    fn fades_ramp_from_and_to_zero() {
+        let mut audio = vec![1.0; 1000];
+        fade_in(&mut audio, 5);
+        assert_eq!(&audio[..3], [0.0, 1.0 / 120.0, 2.0 / 120.0]);
+        assert!(audio[..120].windows(2).all(|w| w[0] < w[1]));
+        assert!(audio[120..].iter().all(|&s| s == 1.0));
+
+        let mut audio = vec![-1.0; 1000];
+        fade_out(&mut audio, 5);
+        assert_eq!(&audio[997..], [-2.0 / 120.0, -1.0 / 120.0, 0.0]);
+        assert!(audio[..880].iter().all(|&s| s == -1.0));
+
+        // Too short for two whole fades: each gets half
+        let mut short = vec![1.0; 100];
+        apply_edges(&mut short, 5);
+        assert_eq!(
+            (short[0], short[49], short[50], short[99]),
+            (0.0, 0.98, 0.98, 0.0)
+        );
+
+        let mut empty: Vec<f32> = Vec::new();
+        apply_edges(&mut empty, 5);
+        fade_in(&mut [0.5], 0);
+    }
+
+    fn rms(samples: impl Iterator<Item = f32>) -> f32 {
+        let (sum, n) = samples.fold((0.0, 0), |(sum, n), s| (sum + s * s, n + 1));
+        (sum / n as f32).sqrt()
+    }
+
+    // Uniform white noise at `amplitude`, the same for the same seed
+    fn noise(seed: u32, len: usize, amplitude: f32) -> Vec<f32> {
+        let mut state = seed;
+        (0..len)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 17;
+                state ^= state << 5;
+                (state as f32 / u32::MAX as f32 * 2.0 - 1.0) * amplitude
+            })
+            .collect()
+    }
+
+    fn db(ratio: f32) -> f32 {
+        20.0 * ratio.log10()
+    }
+
+    #[test]
+    fn equal_power_crossfades_keep_the_level() {
+        let (len, overlap) = (24_000, 4_800);
+        let first = noise(1, len, 0.5);
+        let second = noise(2, len, 0.5);
+        let level = rms(first.iter().copied());
+        assert!(db(rms(second.iter().copied()) / level).abs() < 0.1);
+
+        let joined = |curve| {
+            let mut buffer = first.clone();
+            crossfade(&mut buffer, &second, overlap, curve);
+            buffer
+        };
+        let equal = joined(CrossfadeCurve::EqualPower);
+        // Noise crosses zero within the first few samples
+        assert!(equal.len() > 2 * len - overlap - 10 && equal.len() <= 2 * len - overlap);
+        assert_eq!(equal[..len - overlap], first[..len - overlap]);
+        let across = |audio: &[f32]| db(rms(audio[len - overlap..len].iter().copied()) / level);
+        assert!(across(&equal).abs() < 0.5, "{} dB", across(&equal));
+        // Every tenth of the overlap, not just on average
+        for part in equal[len - overlap..len].chunks(overlap / 10) {
+            let change = db(rms(part.iter().copied()) / level);
+            assert!(change.abs() < 1.0, "{} dB", change);
+        }
+        // A linear fade dips by about 1.8 dB overall, 3 dB halfway
+        let linear = joined(CrossfadeCurve::Linear);
+        assert!(across(&linear) < -1.5, "{} dB", across(&linear));
+    }
+
+    #[test]
+    fn crossfades_enter_at_a_zero_crossing() {
+        // Falling at the join; the next clip starts just past its own peak
+        let wave = |phase: f32, len: usize| -> Vec<f32> {
+            (0..len)
+                .map(|i| (phase + i as f32 * 0.1).sin() * 0.5)
+                .collect()
+        };
+        let mut buffer = wave(2.0, 1_000);
+        let falling = buffer[1_000 - 240 + 1] < buffer[1_000 - 240];
+        assert!(falling);
+        let next = wave(2.0, 1_000);
+        crossfade(&mut buffer, &next, 240, CrossfadeCurve::EqualPower);
+        // sin(2.0 + 0.1 * i) first goes below zero at i = 12
+        assert_eq!(buffer.len(), 1_000 + 1_000 - 12 - 240);
+        assert_eq!(buffer[1_000..], next[12 + 240..]);
+
+        // Nothing to search in a clip no longer than the overlap
+        let mut short = vec![0.5; 100];
+        crossfade(&mut short, &[0.1; 50], 50, CrossfadeCurve::EqualPower);
+        assert_eq!(short.len(), 100);
+        assert_eq!(short[50], 0.5);
+    }
+
+    #[test]
+    fn pan_places_the_voice_with_constant_power() {
+        let mono: Vec<f32> = (0..2400).map(|i| (i as f32 * 0.05).sin()).collect();
+        let left = |stereo: &[f32]| rms(stereo.iter().step_by(2).copied());
+        let right = |stereo: &[f32]| rms(stereo.iter().skip(1).step_by(2).copied());
+
+        let center = to_stereo(&mono, 0.0);
+        assert_eq!(center.len(), 4800);
+        assert!((left(&center) - right(&center)).abs() < 1e-6);
+
+        let hard_left = to_stereo(&mono, -1.0);
+        assert_eq!(right(&hard_left), 0.0);
+        assert!((left(&hard_left) - rms(mono.iter().copied())).abs() < 1e-6);
+        assert_eq!(left(&to_stereo(&mono, 1.0)), 0.0);
+
+        // The same power wherever it is placed
+        for pan in [-1.0, -0.4, 0.0, 0.3, 1.0, 7.0] {
+            let stereo = to_stereo(&mono, pan);
+            let power = left(&stereo).powi(2) + right(&stereo).powi(2);
+            assert!(
+                (power - rms(mono.iter().copied()).powi(2)).abs() < 1e-4,
+                "{}",
+                pan
+            );
+        }
+        assert_eq!(
+            downmix(&hard_left, 2),
+            mono.iter().map(|s| s / 2.0).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn stereo_results_are_saved_with_two_channels() {
+        let engine = TtsEngine::mock(100);
+
+        let mono = engine
+            .synthesize_with("Hello there.", SynthesizeOptions::new())
+            .unwrap();
+        let opts = SynthesizeOptions::new().channels(Channels::Stereo { pan: 0.25 });
+        let stereo = engine.synthesize_with_buffer("Hello there.", opts).unwrap();
+        assert_eq!(stereo.channels(), 2);
+        assert_eq!(stereo.len(), mono.len() * 2);
+        assert_eq!(stereo.duration(), AudioDuration::of(&mono));
+
+        let wav = engine.to_wav_bytes(&stereo).unwrap();
+        let reader = hound::WavReader::new(std::io::Cursor::new(wav)).unwrap();
+        assert_eq!(reader.spec().channels, 2);
+        assert_eq!(reader.len() as usize, stereo.len());
+    }
+
+    #[test]
+    fn model_output_is_sanitized_and_reported() {
+        let mut audio = vec![0.3, f32::NAN, -0.1, f32::INFINITY, 0.2, f32::NEG_INFINITY];
+        assert_eq!(sanitize(&mut audio), 3);
+        // Mean 0.4 / 6 taken out
+        let dc = 0.4 / 6.0;
+        let expected = [0.3 - dc, -dc, -0.1 - dc, -dc, 0.2 - dc, -dc];
+        assert!(audio
+            .iter()
+            .zip(expected)
+            .all(|(a, e)| (a - e).abs() < 1e-6));
+
+        // A small offset stays: it's under the threshold; so does a steady
+        // level, which is all DC and no signal
+        for level in [0.001, 0.5] {
+            let mut steady = vec![level; 100];
+            assert_eq!(sanitize(&mut steady), 0);
+            assert_eq!(steady, vec![level; 100]);
+        }
+
+        let voices = HashMap::from([(DEFAULT_VOICE.to_string(), vec![0.1; STYLE_DIM])]);
+        let mut engine = TtsEngine::with_test_voices(voices);
+        engine.mock_inference = Some(Arc::new(|tokens: &[i64], _: &[f32], _| {
+            let mut audio: Vec<f32> = (0..tokens.len() * 100)
+                .map(|i| (i as f32 * 0.1).sin() * 0.3 + 0.05)
+                .collect();
+            audio[10] = f32::NAN;
+            audio[20] = f32::INFINITY;
+            Ok(audio)
+        }));
+        let (audio, warnings) = engine
+            .synthesize_with_warnings("Hello there.", None, None)
+            .unwrap();
+        assert!(audio.iter().all(|s| s.is_finite()));
+        let mean = audio.iter().sum::<f32>() / audio.len() as f32;
+        assert!(mean.abs() < 0.005, "{}", mean);
+        assert_eq!(warnings.len(), 1, "{:?}", warnings);
+        assert!(warnings[0].contains("2 NaN or infinite"), "{}", warnings[0]);
+        assert_eq!(
+            engine.last_warnings(),
+            [SynthesisWarning::NonFiniteSamples { count: 2 }]
+        );
+    }
+
+    #[test]
+    fn synthesis_starts_and_ends_at_zero() {
+        let engine = TtsEngine::mock(100);
+
+        let text = "A first sentence here. Then a second one, long enough to chunk the text.";
+        let audio = engine
+            .synthesize_with(text, SynthesizeOptions::new())
+            .unwrap();
+        assert_eq!((audio[0], audio[audio.len() - 1]), (0.0, 0.0));
+        assert!(audio[1].abs() < 0.01);
+
+        let raw = engine
+            .synthesize_with(text, SynthesizeOptions::new().edge_fade_ms(0))
+            .unwrap();
+        assert_eq!(raw.len(), audio.len());
+        assert_eq!((raw[0], raw[raw.len() - 1]), (0.1, 0.1));
+    }
+}
@@ -0,0 +1,332 @@
+//! Audio that knows its own sample rate
+//!
+//! Synthesis has always returned a bare `Vec<f32>` at the model's 24kHz,
+//! leaving every caller to remember the rate. `AudioBuffer` carries it along
+//! (and derefs to `[f32]`, so slice code keeps working); the `*_buffer`
+//! synthesis methods return one. The save and play methods take any
+//! `AudioSource`: plain samples are taken to be at the model rate, buffers at
+//! their own, so a resampled buffer is written with the right header. A
+//! buffer also knows its channel count, so stereo results (see
+//! `SynthesizeOptions::channels`) are saved and played as stereo.
+
+use std::borrow::Cow;
+use std::ops::Deref;
+
+use crate::audio::downmix;
+use crate::resample::resample_channels;
+use crate::{AudioDuration, KokoroError, SynthesizeOptions, TtsEngine, SAMPLE_RATE};
+
+/// Samples, their sample rate and their channel count (interleaved when
+/// more than one)
+///
+/// # Example
+/// ```
+/// use kokoro_tiny::AudioBuffer;
+///
+/// let audio = AudioBuffer::from(vec![0.0; 36_000]);
+/// assert_eq!(audio.sample_rate(), 24_000);
+/// assert_eq!(audio.duration().as_millis(), 1500);
+///
+/// let cd = audio.resampled(44_100);
+/// assert_eq!(cd.len(), 66_150);
+/// assert_eq!(cd.duration().as_millis(), 1500);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct AudioBuffer {
+    samples: Vec<f32>,
+    sample_rate: u32,
+    channels: u16,
+}
+
+impl AudioBuffer {
+    /// Mono samples
+    pub fn new(samples: Vec<f32>, sample_rate: u32) -> Self {
+        Self::interleaved(samples, sample_rate, 1)
+    }
+
+    /// Interleaved samples, `channels` to a frame (0 is taken as 1)
+    pub fn interleaved(samples: Vec<f32>, sample_rate: u32, channels: u16) -> Self {
+        Self {
+            samples,
+            sample_rate,
+            channels: channels.max(1),
+        }
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    pub fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    pub fn duration(&self) -> AudioDuration {
+        let frames = self.samples.len() / usize::from(self.channels);
+        AudioDuration::from_samples(frames, self.sample_rate)
+    }
+
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    pub fn as_slice(&self) -> &[f32] {
+        &self.samples
+    }
+
+    pub fn into_samples(self) -> Vec<f32> {
+        self.samples
+    }
+
+    /// The same audio at `to_rate`, see `resample`
+    pub fn resampled(&self, to_rate: u32) -> Self {
+        let samples = resample_channels(&self.samples, self.channels, self.sample_rate, to_rate);
+        Self::interleaved(samples, to_rate, self.channels)
+    }
+}
+
+/// Model output: samples at the model's 24kHz
+impl From<Vec<f32>> for AudioBuffer {
+    fn from(samples: Vec<f32>) -> Self {
+        Self::new(samples, SAMPLE_RATE)
+    }
+}
+
+impl From<AudioBuffer> for Vec<f32> {
+    fn from(buffer: AudioBuffer) -> Self {
+        buffer.samples
+    }
+}
+
+impl Deref for AudioBuffer {
+    type Target = [f32];
+
+    fn deref(&self) -> &[f32] {
+        &self.samples
+    }
+}
+
+impl AsRef<[f32]> for AudioBuffer {
+    fn as_ref(&self) -> &[f32] {
+        &self.samples
+    }
+}
+
+/// Audio the save and play methods accept
+///
+/// Implemented for `AudioBuffer` and for plain sample containers (`Vec<f32>`,
+/// slices, arrays and references to any of these), which are at the model
+/// rate.
+pub trait AudioSource {
+    fn samples(&self) -> &[f32];
+
+    fn sample_rate(&self) -> u32 {
+        SAMPLE_RATE
+    }
+
+    /// Samples per frame, interleaved
+    fn channels(&self) -> u16 {
+        1
+    }
+}
+
+impl AudioSource for AudioBuffer {
+    fn samples(&self) -> &[f32] {
+        &self.samples
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn channels(&self) -> u16 {
+        self.channels
+    }
+}
+
+impl AudioSource for [f32] {
+    fn samples(&self) -> &[f32] {
+        self
+    }
+}
+
+impl AudioSource for Vec<f32> {
+    fn samples(&self) -> &[f32] {
+        self
+    }
+}
+
+impl<const N: usize> AudioSource for [f32; N] {
+    fn samples(&self) -> &[f32] {
+        self
+    }
+}
+
+impl<T: AudioSource + ?Sized> AudioSource for &T {
+    fn samples(&self) -> &[f32] {
+        (**self).samples()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        (**self).sample_rate()
+    }
+
+    fn channels(&self) -> u16 {
+        (**self).channels()
+    }
+}
+
+// Samples at the model rate, still interleaved, for outputs that assume it
+pub(crate) fn at_model_rate(audio: &impl AudioSource) -> Cow<'_, [f32]> {
+    match audio.sample_rate() {
+        SAMPLE_RATE => Cow::Borrowed(audio.samples()),
+        rate => Cow::Owned(resample_channels(
+            audio.samples(),
+            audio.channels(),
+            rate,
+            SAMPLE_RATE,
+        )),
+    }
+}
+
+// Mono samples at the model rate, for the encoders
+pub(crate) fn mono_at_model_rate(audio: &impl AudioSource) -> Cow<'_, [f32]> {
+    match audio.channels() {
+        0 | 1 => at_model_rate(audio),
+        channels => Cow::Owned(downmix(&at_model_rate(audio), channels)),
+    }
+}
+
+impl TtsEngine {
+    /// `synthesize`, returning an `AudioBuffer`
+    ///
+    /// # Example
+    /// ```no_run
+    /// use kokoro_tiny::TtsEngine;
+    ///
+    /// # async fn demo() -> Result<(), kokoro_tiny::KokoroError> {
+    /// let tts = TtsEngine::new().await?;
+    /// let audio = tts.synthesize_buffer("Build complete", None, None, None)?;
+    /// println!("{} of audio", audio.duration());
+    /// tts.save_wav("build.wav", &audio)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn synthesize_buffer(
+        &self,
+        text: &str,
+        voice: Option<&str>,
+        speed: Option<f32>,
+        lang: Option<&str>,
+    ) -> Result<AudioBuffer, KokoroError> {
+        self.synthesize(text, voice, speed, lang)
+            .map(AudioBuffer::from)
+    }
+
+    /// `synthesize_with`, returning an `AudioBuffer` at the options'
+    /// `output_sample_rate` and with their `channels`
+    ///
+    /// # Example
+    /// ```no_run
+    /// use kokoro_tiny::{SynthesizeOptions, TtsEngine};
+    ///
+    /// # async fn demo() -> Result<(), kokoro_tiny::KokoroError> {
+    /// let tts = TtsEngine::new().await?;
+    /// let opts = SynthesizeOptions::new().output_sample_rate(16_000);
+    /// let audio = tts.synthesize_with_buffer("Build complete", opts)?;
+    /// // Written with a 16kHz header
+    /// tts.save_wav("build.wav", &audio)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn synthesize_with_buffer(
+        &self,
+        text: &str,
+        opts: SynthesizeOptions,
+    ) -> Result<AudioBuffer, KokoroError> {
+        let rate = opts.output_sample_rate.unwrap_or(SAMPLE_RATE);
+        let channels = opts.channels.count();
+        self.synthesize_with(text, opts)
+            .map(|samples| AudioBuffer::interleaved(samples, rate, channels))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn buffers_keep_their_rate_through_save_and_resample() {
+        let audio = AudioBuffer::from(vec![0.5; 2400]);
+        assert_eq!(audio.duration().as_millis(), 100);
+        assert_eq!(audio.len(), 2400);
+        assert_eq!(audio.iter().sum::<f32>(), 1200.0);
+
+        let tts = TtsEngine::without_model();
+        let dir = std::env::temp_dir();
+        let path =
+            |name: &str| dir.join(format!("kokoro_buffer_{}_{}.wav", name, std::process::id()));
+        let read = |name: &str| {
+            let reader = hound::WavReader::open(path(name)).unwrap();
+            (reader.spec().sample_rate, reader.len() as usize)
+        };
+
+        // Plain samples, a buffer and a resampled buffer all save correctly
+        let plain = vec![0.5; 2400];
+        tts.save_wav(path("plain").to_str().unwrap(), &plain)
+            .unwrap();
+        tts.save_wav(path("buffer").to_str().unwrap(), &audio)
+            .unwrap();
+        let high = audio.resampled(48_000);
+        tts.save_audio(path("high").to_str().unwrap(), high.clone())
+            .unwrap();
+        assert_eq!(read("plain"), (SAMPLE_RATE, 2400));
+        assert_eq!(read("buffer"), (SAMPLE_RATE, 2400));
+        assert_eq!(read("high"), (48_000, 4800));
+        for name in ["plain", "buffer", "high"] {
+            std::fs::remove_file(path(name)).ok();
+        }
+
+        assert_eq!(at_model_rate(&high).len(), 2400);
+        assert_eq!(high.resampled(48_000), high);
+        assert!(AudioBuffer::new(vec![0.5; 10], 0)
+            .resampled(24_000)
+            .is_empty());
+        assert_eq!(Vec::from(audio.clone()), audio.into_samples());
+    }
+
+    #[test]
+    fn buffer_variants_match_the_vec_ones() {
+        let engine = TtsEngine::mock(10);
+
+        let samples = engine.synthesize("Hello there.", None, None, None).unwrap();
+        let buffer = engine
+            .synthesize_buffer("Hello there.", None, None, None)
+            .unwrap();
+        assert_eq!(buffer.as_slice(), samples.as_slice());
+        assert_eq!(buffer.sample_rate(), SAMPLE_RATE);
+
+        let opts = SynthesizeOptions::new().gain(0.5);
+        let opts_at = |rate| opts.clone().output_sample_rate(rate);
+        let with = engine
+            .synthesize_with("Hello there.", opts.clone())
+            .unwrap();
+        let buffer = engine
+            .synthesize_with_buffer("Hello there.", opts.clone())
+            .unwrap();
+        assert_eq!(*buffer, *with);
+
+        // Resampled before returning, and saved with the rate it has
+        let phone = opts_at(16_000);
+        let audio = engine.synthesize_with("Hello there.", phone.clone()).unwrap();
+        assert_eq!(audio.len(), with.len() * 2 / 3);
+        let buffer = engine.synthesize_with_buffer("Hello there.", phone).unwrap();
+        assert_eq!(buffer.sample_rate(), 16_000);
+        assert_eq!(buffer.len(), audio.len());
+        assert!(engine.synthesize_with("Hello there.", opts_at(0)).is_err());
+    }
+}
@@ -0,0 +1,353 @@
+//! Barge-in: get out of the way as soon as the user starts talking
+//!
+//! With `PlaybackOptions::barge_in` set, `play_with_options` listens to the
+//! default input device while it plays and pauses, ducks or stops the speech
+//! once the microphone level stays above a threshold for a couple of frames,
+//! typically well within 100 ms (during the first ~200 ms of an utterance the
+//! echo check below needs history, so barge-in only starts after that). A
+//! paused utterance picks up again after the input has been quiet for
+//! `resume_after_ms`.
+//!
+//! There is no acoustic echo cancellation here. Without AEC (a headset, or the
+//! OS doing it for the device), the microphone also hears the speech being
+//! played and could trigger on it. As a mitigation, input whose level contour
+//! follows the output's contour (the last few frames correlate strongly at
+//! some small delay) is taken to be that echo and ignored. It is a heuristic:
+//! loud speakers right next to the microphone can still trigger it, and
+//! someone talking in perfect rhythm with the speech may be ignored.
+//!
+//! The decision logic (`BargeInDetector`) works on per-frame levels only, so it
+//! can be driven from any audio source, or from synthetic traces in tests.
+
+use std::collections::VecDeque;
+
+/// What to do when the user starts talking
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BargeInAction {
+    /// Hold playback, resuming once the input has been quiet for `resume_after_ms`
+    Pause,
+    /// Keep playing `db` quieter (e.g. -18.0) for the rest of the utterance
+    Duck { db: f32 },
+    /// End the utterance
+    Stop,
+}
+
+/// Settings for `PlaybackOptions::barge_in`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BargeIn {
+    /// Input level (dBFS, RMS per frame) that counts as the user talking
+    pub threshold_db: f32,
+    pub action: BargeInAction,
+    /// Quiet time before a paused utterance resumes (`Pause` only)
+    pub resume_after_ms: u64,
+}
+
+impl Default for BargeIn {
+    fn default() -> Self {
+        Self {
+            threshold_db: -35.0,
+            action: BargeInAction::Pause,
+            resume_after_ms: 800,
+        }
+    }
+}
+
+impl BargeIn {
+    /// Default threshold and resume delay with the given action
+    pub fn new(action: BargeInAction) -> Self {
+        Self {
+            action,
+            ..Self::default()
+        }
+    }
+
+    /// Set the input level that counts as speech
+    pub fn threshold_db(mut self, db: f32) -> Self {
+        self.threshold_db = db;
+        self
+    }
+
+    /// Set how long the input must be quiet before a pause ends
+    pub fn resume_after_ms(mut self, ms: u64) -> Self {
+        self.resume_after_ms = ms;
+        self
+    }
+}
+
+/// A change the player has to apply
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BargeInEvent {
+    Triggered(BargeInAction),
+    /// The pause is over
+    Resumed,
+}
+
+/// Level reported for digital silence
+const FLOOR_DB: f32 = -100.0;
+
+/// Output below this is silent and can't echo
+const OUTPUT_SILENCE_DB: f32 = -60.0;
+
+/// Speech has to last this long before the action fires, so clicks and
+/// single noisy frames don't
+const MIN_SPEECH_MS: u64 = 20;
+
+/// Frames of level contour compared by the echo check
+const ECHO_WINDOW: usize = 8;
+
+/// Fewer frames than this say nothing about the contour
+const ECHO_MIN_FRAMES: usize = 4;
+
+/// Largest speaker-to-microphone delay looked for, in frames
+const ECHO_MAX_LAG: usize = 5;
+
+/// Input following the output at least this closely is taken to be the output
+const ECHO_CORRELATION: f32 = 0.8;
+
+/// Contours flatter than this (variance, dB²) can't be compared
+const ECHO_MIN_VARIANCE: f32 = 1.0;
+
+/// Decides, frame by frame, when to barge in and when to resume
+#[derive(Clone, Debug)]
+pub struct BargeInDetector {
+    config: BargeIn,
+    triggered: bool,
+    speech_since: Option<u64>,
+    last_speech_ms: u64,
+    input: VecDeque<f32>,
+    output: VecDeque<f32>,
+}
+
+impl BargeInDetector {
+    pub fn new(config: BargeIn) -> Self {
+        Self {
+            config,
+            triggered: false,
+            speech_since: None,
+            last_speech_ms: 0,
+            input: VecDeque::with_capacity(ECHO_WINDOW),
+            output: VecDeque::with_capacity(ECHO_WINDOW + ECHO_MAX_LAG),
+        }
+    }
+
+    /// Feed one frame: the microphone level and the level being played at `now_ms`
+    ///
+    /// Frames can have any length; timing comes from `now_ms` alone.
+    pub fn observe(&mut self, now_ms: u64, input_db: f32, output_db: f32) -> Option<BargeInEvent> {
+        push_capped(&mut self.input, input_db.max(FLOOR_DB), ECHO_WINDOW);
+        push_capped(
+            &mut self.output,
+            output_db.max(FLOOR_DB),
+            ECHO_WINDOW + ECHO_MAX_LAG,
+        );
+
+        let speech = input_db >= self.config.threshold_db && !self.is_echo();
+        if speech {
+            self.last_speech_ms = now_ms;
+        }
+
+        if !self.triggered {
+            if !speech {
+                self.speech_since = None;
+                return None;
+            }
+            let since = *self.speech_since.get_or_insert(now_ms);
+            if now_ms - since < MIN_SPEECH_MS {
+                return None;
+            }
+            self.triggered = true;
+            return Some(BargeInEvent::Triggered(self.config.action));
+        }
+
+        let quiet_for = now_ms - self.last_speech_ms;
+        if self.config.action == BargeInAction::Pause && quiet_for >= self.config.resume_after_ms {
+            self.triggered = false;
+            self.speech_since = None;
+            return Some(BargeInEvent::Resumed);
+        }
+        None
+    }
+
+    /// Whether the action is currently in effect
+    pub fn is_triggered(&self) -> bool {
+        self.triggered
+    }
+
+    // The input contour matches the output contour at some delay
+    fn is_echo(&self) -> bool {
+        let output_max = self.output.iter().copied().fold(FLOOR_DB, f32::max);
+        if output_max < OUTPUT_SILENCE_DB {
+            return false;
+        }
+        // Until every delay can be checked, assume what's heard is the output
+        if self.output.len() < ECHO_MIN_FRAMES + ECHO_MAX_LAG {
+            return true;
+        }
+
+        let input: Vec<f32> = self.input.iter().copied().collect();
+        let output: Vec<f32> = self.output.iter().copied().collect();
+        (0..=ECHO_MAX_LAG).any(|lag| {
+            // Output `lag` frames back lines up with the latest input
+            let end = output.len() - lag;
+            let n = input.len().min(end);
+            n >= ECHO_MIN_FRAMES
+                && correlation(&input[input.len() - n..], &output[end - n..end])
+                    .is_some_and(|c| c >= ECHO_CORRELATION)
+        })
+    }
+}
+
+/// RMS level of `samples` in dBFS (`-100` for silence)
+pub fn frame_db(samples: &[f32]) -> f32 {
+    if samples.is_empty() {
+        return FLOOR_DB;
+    }
+    let mean_square = samples.iter().map(|s| s * s).sum::<f32>() / samples.len() as f32;
+    (10.0 * mean_square.log10()).max(FLOOR_DB)
+}
+
+fn push_capped(window: &mut VecDeque<f32>, value: f32, cap: usize) {
+    if window.len() == cap {
+        window.pop_front();
+    }
+    window.push_back(value);
+}
+
+// Pearson correlation, or None when either side is too flat to compare
+fn correlation(a: &[f32], b: &[f32]) -> Option<f32> {
+    let n = a.len() as f32;
+    let mean_a = a.iter().sum::<f32>() / n;
+    let mean_b = b.iter().sum::<f32>() / n;
+    let (mut cov, mut var_a, mut var_b) = (0.0, 0.0, 0.0);
+    for (x, y) in a.iter().zip(b) {
+        cov += (x - mean_a) * (y - mean_b);
+        var_a += (x - mean_a).powi(2);
+        var_b += (y - mean_b).powi(2);
+    }
+    if var_a / n < ECHO_MIN_VARIANCE || var_b / n < ECHO_MIN_VARIANCE {
+        return None;
+    }
+    Some(cov / (var_a * var_b).sqrt())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FRAME_MS: u64 = 20;
+
+    // Syllable-like output contour: loud and soft frames alternating
+    fn speech_db(frame: usize) -> f32 {
+        [-18.0, -30.0, -22.0, -40.0, -20.0, -34.0][frame % 6]
+    }
+
+    // Run a trace of (input, output) levels, one 20 ms frame each, and
+    // return the events with their times
+    fn run(
+        detector: &mut BargeInDetector,
+        trace: impl IntoIterator<Item = (f32, f32)>,
+    ) -> Vec<(u64, BargeInEvent)> {
+        trace
+            .into_iter()
+            .enumerate()
+            .filter_map(|(frame, (input, output))| {
+                let now = (frame as u64 + 1) * FRAME_MS;
+                detector
+                    .observe(now, input, output)
+                    .map(|event| (now, event))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn pause_fires_quickly_and_resumes_after_quiet() {
+        let mut detector = BargeInDetector::new(BargeIn::default().resume_after_ms(400));
+
+        // 1 s of speech with a quiet room, then the user talks for 300 ms,
+        // then 1 s of quiet (the player is paused, so nothing is playing)
+        let trace = (0..50)
+            .map(|f| (-70.0, speech_db(f)))
+            .chain((0..15).map(|f| (-20.0 - (f % 3) as f32 * 4.0, FLOOR_DB)))
+            .chain((0..50).map(|_| (-70.0, FLOOR_DB)));
+        let events = run(&mut detector, trace);
+
+        let onset = 50 * FRAME_MS;
+        let (triggered_at, event) = events[0];
+        assert_eq!(event, BargeInEvent::Triggered(BargeInAction::Pause));
+        assert!(triggered_at - onset <= 100, "{} ms", triggered_at - onset);
+
+        // Last speech frame ends at onset + 300 ms; resume 400 ms later
+        let (resumed_at, event) = events[1];
+        assert_eq!(event, BargeInEvent::Resumed);
+        assert_eq!(resumed_at, onset + 300 + 400);
+        assert_eq!(events.len(), 2);
+    }
+
+    #[test]
+    fn talking_over_the_speech_triggers() {
+        let mut detector = BargeInDetector::new(BargeIn::new(BargeInAction::Stop));
+
+        // The echo of the output is in the input throughout; the user starts
+        // talking at frame 30, much louder than the echo
+        let trace = (0..60).map(|f| {
+            let echo = speech_db(f) - 25.0;
+            let input = if f >= 30 {
+                -15.0 - (f % 2) as f32 * 3.0
+            } else {
+                echo
+            };
+            (input, speech_db(f))
+        });
+        let events = run(&mut detector, trace);
+
+        assert_eq!(events.len(), 1);
+        let (at, event) = events[0];
+        assert_eq!(event, BargeInEvent::Triggered(BargeInAction::Stop));
+        assert!(at - 30 * FRAME_MS <= 100, "{} ms", at - 30 * FRAME_MS);
+        // Stop and Duck don't resume
+        assert!(detector.is_triggered());
+    }
+
+    #[test]
+    fn own_output_does_not_trigger() {
+        let mut detector = BargeInDetector::new(
+            BargeIn::new(BargeInAction::Duck { db: -18.0 }).threshold_db(-40.0),
+        );
+
+        // Loud speakers: the echo is well above the threshold and arrives
+        // two frames (40 ms) late
+        let trace = (0..200).map(|f| {
+            let echo = if f >= 2 {
+                speech_db(f - 2) - 6.0
+            } else {
+                -70.0
+            };
+            (echo, speech_db(f))
+        });
+        assert_eq!(run(&mut detector, trace), []);
+
+        // The same input with nothing playing is the user
+        let mut detector = BargeInDetector::new(BargeIn::default().threshold_db(-40.0));
+        let trace = (0..20).map(|f| (speech_db(f) - 6.0, FLOOR_DB));
+        assert_eq!(
+            run(&mut detector, trace).first().map(|(_, e)| *e),
+            Some(BargeInEvent::Triggered(BargeInAction::Pause))
+        );
+    }
+
+    #[test]
+    fn short_noises_are_ignored() {
+        let mut detector = BargeInDetector::new(BargeIn::default());
+        // A single 20 ms click every half second
+        let trace = (0..100).map(|f| (if f % 25 == 0 { -10.0 } else { -70.0 }, FLOOR_DB));
+        assert_eq!(run(&mut detector, trace), []);
+    }
+
+    #[test]
+    fn frame_levels() {
+        assert_eq!(frame_db(&[]), FLOOR_DB);
+        assert_eq!(frame_db(&[0.0; 64]), FLOOR_DB);
+        assert!((frame_db(&[1.0, -1.0]) - 0.0).abs() < 1e-6);
+        assert!((frame_db(&[0.1; 32]) + 20.0).abs() < 1e-4);
+    }
+}
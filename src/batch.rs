@@ -0,0 +1,304 @@
+//! Newline-delimited JSON job protocol (`kokoro-speak jsonl`)
+//!
+//! A deliberately dumb machine interface next to MCP: one job object per input
+//! line, one result object per output line, processed as the lines arrive so
+//! another program can drive it over a pipe for as long as it likes.
+//!
+//! ```text
+//! -> {"id":"a1","text":"Build done","voice":"af_sky","output":"out/a1.wav"}
+//! <- {"id":"a1","ok":true,"duration_ms":812,"path":"out/a1.wav"}
+//! -> {"id":"a2","text":"Hello","play":true}
+//! <- {"id":"a2","ok":true,"duration_ms":640,"played":true}
+//! -> {"cmd":"shutdown"}
+//! ```
+//!
+//! Failed or malformed jobs produce `{"id":..,"ok":false,"line":N,"error":".."}`
+//! and processing continues.
+//!
+//! A job may name its `source` instead of a voice; each source gets a voice of
+//! its own from a `VoiceAssigner` (see `run_jsonl_with`), reported back as
+//! `"voice"`. An explicit `voice` always wins, and `"voice":"auto:<key>"` asks
+//! for the voice of another key.
+
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::{
+    AudioDuration, SynthesizeOptions, TtsEngine, VoiceAssigner, VoiceMix, DEFAULT_SPEED,
+    SAMPLE_RATE,
+};
+
+/// Playback volume when a job doesn't set one
+const DEFAULT_JOB_VOLUME: f32 = 0.8;
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Request {
+    Command { cmd: String },
+    Job(Job),
+}
+
+#[derive(Deserialize)]
+struct Job {
+    #[serde(default)]
+    id: Option<String>,
+    text: String,
+    #[serde(default)]
+    voice: Option<String>,
+    #[serde(default)]
+    source: Option<String>,
+    #[serde(default)]
+    speed: Option<f32>,
+    #[serde(default)]
+    gain: Option<f32>,
+    #[serde(default)]
+    output: Option<String>,
+    #[serde(default)]
+    play: bool,
+    #[serde(default)]
+    volume: Option<f32>,
+}
+
+/// Process jobs from `input` until EOF or a `{"cmd":"shutdown"}` line
+///
+/// Only I/O errors on `input`/`output` end the loop early; job failures are
+/// reported in-band. Sources get voices for this run only.
+pub fn run_jsonl(
+    engine: &mut TtsEngine,
+    input: impl BufRead,
+    output: impl Write,
+) -> io::Result<()> {
+    let mut voices = VoiceAssigner::new(engine.voices());
+    run_jsonl_with(engine, &mut voices, input, output)
+}
+
+/// `run_jsonl` taking source voices from `voices`, e.g. loaded from disk
+pub fn run_jsonl_with(
+    engine: &mut TtsEngine,
+    voices: &mut VoiceAssigner,
+    input: impl BufRead,
+    mut output: impl Write,
+) -> io::Result<()> {
+    for (idx, line) in input.lines().enumerate() {
+        let line = line?;
+        let line_no = idx + 1;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let result = match serde_json::from_str::<Request>(&line) {
+            Ok(Request::Command { cmd }) if cmd == "shutdown" => break,
+            Ok(Request::Command { cmd }) => json!({
+                "id": null,
+                "ok": false,
+                "line": line_no,
+                "error": format!("Unknown command: {}", cmd),
+            }),
+            Ok(Request::Job(job)) => match run_job(engine, voices, &job) {
+                Ok(mut result) => {
+                    result["id"] = json!(job.id);
+                    result["ok"] = json!(true);
+                    result
+                }
+                Err(e) => json!({ "id": job.id, "ok": false, "line": line_no, "error": e }),
+            },
+            Err(e) => json!({
+                "id": null,
+                "ok": false,
+                "line": line_no,
+                "error": format!("Malformed job: {}", e),
+            }),
+        };
+
+        writeln!(output, "{}", result)?;
+        output.flush()?;
+    }
+
+    Ok(())
+}
+
+fn run_job(
+    engine: &mut TtsEngine,
+    voices: &mut VoiceAssigner,
+    job: &Job,
+) -> Result<serde_json::Value, String> {
+    if job.output.is_none() && !job.play {
+        return Err("Job needs an \"output\" path or \"play\": true".to_string());
+    }
+
+    let mut opts = SynthesizeOptions::new()
+        .speed(job.speed.unwrap_or(DEFAULT_SPEED))
+        .gain(job.gain.unwrap_or(1.0));
+    let voice = match (&job.voice, &job.source) {
+        (Some(voice), _) => Some(voices.resolve(voice)),
+        (None, Some(source)) => Some(voices.assign(source).to_string()),
+        (None, None) => None,
+    };
+    if let Some(voice) = &voice {
+        engine.validate_voice_mix(&VoiceMix::parse(voice)?)?;
+        opts = opts.voice(voice);
+    }
+
+    let audio = engine.synthesize_with(&job.text, opts)?;
+    let mut result = json!({
+        "duration_ms": AudioDuration::of(&audio).as_millis(),
+    });
+    if job.voice.as_ref() != voice.as_ref() {
+        result["voice"] = json!(voice);
+    }
+
+    if let Some(path) = &job.output {
+        if let Some(dir) = Path::new(path)
+            .parent()
+            .filter(|d| !d.as_os_str().is_empty())
+        {
+            fs::create_dir_all(dir)
+                .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        }
+        engine.save_audio(path, &audio)?;
+        result["path"] = json!(path);
+    }
+
+    if job.play {
+        play(engine, &audio, job.volume.unwrap_or(DEFAULT_JOB_VOLUME))?;
+        result["played"] = json!(true);
+    }
+
+    Ok(result)
+}
+
+fn play(engine: &TtsEngine, audio: &[f32], volume: f32) -> Result<(), String> {
+    let opts = crate::PlaybackOptions::new()
+        .volume(volume)
+        .on_busy(crate::BusyPolicy::Queue);
+    engine.play_with_options(audio, &opts)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    fn run(engine: &mut TtsEngine, input: &str) -> Vec<serde_json::Value> {
+        let mut out = Vec::new();
+        run_jsonl(engine, input.as_bytes(), &mut out).unwrap();
+        String::from_utf8(out)
+            .unwrap()
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn reports_each_line_and_stops_at_shutdown() {
+        let dir = std::env::temp_dir().join(format!("kokoro_batch_{}", std::process::id()));
+        let path = dir.join("out").join("a1.wav");
+        let path = path.to_str().unwrap();
+
+        let input = format!(
+            "{}\n{}\n{}\n\n{}\n{}\n{}\n",
+            json!({"id": "a1", "text": "Build done.", "voice": "af_sky", "output": path}),
+            json!({"id": "a2", "text": "Hello", "voice": "af_nobody", "output": path}),
+            "{\"id\": \"a3\", \"text\": ",
+            json!({"id": "a4", "text": "No destination"}),
+            json!({"cmd": "shutdown"}),
+            json!({"id": "a5", "text": "Never read", "output": path}),
+        );
+        let results = run(&mut TtsEngine::mock(100), &input);
+        assert_eq!(results.len(), 4);
+
+        assert_eq!(results[0]["id"], "a1");
+        assert_eq!(results[0]["ok"], true);
+        assert_eq!(results[0]["path"], path);
+        assert!(results[0]["duration_ms"].as_u64().unwrap() > 0);
+        assert!(Path::new(path).exists());
+
+        assert_eq!(results[1]["id"], "a2");
+        assert_eq!(results[1]["ok"], false);
+        assert!(results[1]["error"].as_str().unwrap().contains("af_nobody"));
+
+        assert_eq!(results[2]["ok"], false);
+        assert_eq!(results[2]["line"], 3);
+        assert!(results[2]["id"].is_null());
+
+        assert_eq!(results[3]["id"], "a4");
+        assert_eq!(results[3]["line"], 5);
+
+        fs::remove_dir_all(dir).ok();
+    }
+
+    #[test]
+    fn sources_get_their_own_voice_unless_one_is_given() {
+        let mut engine = TtsEngine::mock(100);
+        engine.import_voice("am_adam", vec![0.2; 256]).unwrap();
+        engine.import_voice("bf_emma", vec![0.3; 256]).unwrap();
+        let dir = std::env::temp_dir().join(format!("kokoro_batch_src_{}", std::process::id()));
+        let path = dir.join("out.wav");
+        let path = path.to_str().unwrap();
+
+        let mut voices = VoiceAssigner::new(engine.voices());
+        voices.pin("calendar", "af_sky");
+        let input = [
+            json!({"id": "b1", "text": "Build done", "source": "build", "output": path}),
+            json!({"id": "m1", "text": "Disk full", "source": "monitoring", "output": path}),
+            json!({"id": "b2", "text": "Build broke", "source": "build", "output": path}),
+            json!({"id": "c1", "text": "Meeting", "source": "calendar", "output": path}),
+            json!({"id": "e1", "text": "Explicit", "source": "build", "voice": "af_sky", "output": path}),
+            json!({"id": "a1", "text": "Like monitoring", "voice": "auto:monitoring", "output": path}),
+        ]
+        .map(|job| job.to_string())
+        .join("\n");
+
+        let mut out = Vec::new();
+        run_jsonl_with(&mut engine, &mut voices, input.as_bytes(), &mut out).unwrap();
+        let results: Vec<serde_json::Value> = String::from_utf8(out)
+            .unwrap()
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+
+        assert!(results.iter().all(|r| r["ok"] == true), "{:?}", results);
+        assert_ne!(results[0]["voice"], results[1]["voice"]);
+        assert_eq!(results[0]["voice"], results[2]["voice"]);
+        assert_eq!(results[3]["voice"], "af_sky");
+        // An explicit voice is used as given and not echoed back
+        assert!(results[4].get("voice").is_none());
+        assert_eq!(results[5]["voice"], results[1]["voice"]);
+
+        fs::remove_dir_all(dir).ok();
+    }
+
+    #[cfg(not(feature = "playback"))]
+    #[test]
+    fn play_without_playback_feature_is_an_error() {
+        let results = run(
+            &mut TtsEngine::mock(100),
+            &json!({"id": "p", "text": "Hi there", "play": true}).to_string(),
+        );
+        assert_eq!(results[0]["ok"], false);
+        assert!(results[0]["error"].as_str().unwrap().contains("Playback"));
+    }
+
+    #[test]
+    fn play_jobs_go_to_the_engine_output() {
+        let output = crate::NullOutput::new();
+        let mut engine = TtsEngine::mock(100);
+        engine.set_output(Arc::new(output.clone()));
+
+        let results = run(
+            &mut engine,
+            &json!({"id": "p", "text": "Hi there", "play": true, "volume": 0.5}).to_string(),
+        );
+        assert_eq!(results[0]["ok"], true, "{:?}", results);
+        assert_eq!(results[0]["played"], true);
+        let frames = output.frames();
+        assert!(!frames.is_empty());
+        // Between the edge fades
+        assert!(frames[120..frames.len() - 120].iter().all(|&s| s == 0.05));
+    }
+}
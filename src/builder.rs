@@ -0,0 +1,486 @@
+//! Engine construction options
+//!
+//! The constructors each take one setting (`with_paths`, `with_retry_policy`,
+//! `with_voice_pack`, `new_strict`); `TtsEngine::builder()` takes them all,
+//! plus the ONNX Runtime session settings: thread counts and graph
+//! optimization level. The defaults are what `TtsEngine::new` has always
+//! used, so an empty builder builds the same engine.
+//!
+//! Threads: ONNX Runtime uses one intra-op thread per physical core by
+//! default. On a small machine serving overlapping requests that
+//! oversubscribes the CPU; cap `intra_threads` at the cores per request. On a
+//! large one, more intra-op threads shorten each render.
+//! `examples/thread_bench.rs` prints the real-time factor per thread count.
+
+use std::path::{Path, PathBuf};
+
+pub use ort::session::builder::GraphOptimizationLevel;
+use ort::session::{builder::SessionBuilder, Session};
+
+use crate::integrity::{self, FileStatus};
+use crate::model_variant::{ModelIo, ModelVariant};
+use crate::providers::{self, ExecutionProvider};
+use crate::retry::RetryPolicy;
+use crate::voice_pack::VoicePack;
+use crate::{cache_dir, KokoroError, TtsEngine};
+
+/// ONNX Runtime settings for the model session
+///
+/// # Example
+/// ```
+/// use kokoro_tiny::{GraphOptimizationLevel, SessionOptions};
+///
+/// let opts = SessionOptions::default();
+/// assert_eq!(opts.intra_threads, 0);
+/// assert_eq!(opts.optimization_level, GraphOptimizationLevel::Level3);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SessionOptions {
+    /// Threads parallelizing work inside one operator; 0 lets ONNX Runtime
+    /// choose (one per physical core)
+    pub intra_threads: usize,
+    /// Threads running independent operators side by side; 0 keeps the
+    /// default sequential execution
+    pub inter_threads: usize,
+    pub optimization_level: GraphOptimizationLevel,
+    /// Hardware to run on (see `providers`); falls back to the CPU
+    pub execution_provider: ExecutionProvider,
+}
+
+impl Default for SessionOptions {
+    fn default() -> Self {
+        Self {
+            intra_threads: 0,
+            inter_threads: 0,
+            optimization_level: GraphOptimizationLevel::Level3,
+            execution_provider: ExecutionProvider::Auto,
+        }
+    }
+}
+
+impl SessionOptions {
+    // Session builder with these settings applied
+    fn session_builder(&self) -> Result<SessionBuilder, KokoroError> {
+        let failed = KokoroError::ModelMissing;
+        let mut builder = Session::builder()
+            .map_err(|e| failed(format!("Failed to create session builder: {}", e)))?
+            .with_optimization_level(self.optimization_level)
+            .map_err(|e| failed(format!("Failed to set optimization level: {}", e)))?;
+        if self.intra_threads > 0 {
+            builder = builder
+                .with_intra_threads(self.intra_threads)
+                .map_err(|e| failed(format!("Failed to set intra-op threads: {}", e)))?;
+        }
+        if self.inter_threads > 0 {
+            builder = builder
+                .with_parallel_execution(true)
+                .map_err(|e| failed(format!("Failed to enable parallel execution: {}", e)))?
+                .with_inter_threads(self.inter_threads)
+                .map_err(|e| failed(format!("Failed to set inter-op threads: {}", e)))?;
+        }
+        Ok(builder)
+    }
+
+    // Load the ONNX model into a session, on the first execution provider
+    // that works; a model without inputs `infer` can name fails here
+    // rather than on the first synthesis
+    //
+    // ONNX Runtime reads the file itself; going through a Vec first kept a
+    // second copy of the 310MB model alive while the session was built.
+    pub(crate) fn load_session(
+        &self,
+        model_path: &str,
+    ) -> Result<(Session, ExecutionProvider), KokoroError> {
+        self.commit(|builder| builder.commit_from_file(model_path))
+    }
+
+    // Same, from model bytes the caller already holds
+    pub(crate) fn load_session_from_memory(
+        &self,
+        model_bytes: &[u8],
+    ) -> Result<(Session, ExecutionProvider), KokoroError> {
+        self.commit(|builder| builder.commit_from_memory(model_bytes))
+    }
+
+    fn commit(
+        &self,
+        commit: impl Fn(&mut SessionBuilder) -> ort::Result<Session>,
+    ) -> Result<(Session, ExecutionProvider), KokoroError> {
+        let load_failed =
+            |e: ort::Error| KokoroError::ModelMissing(format!("Failed to load model: {}", e));
+        for provider in self.execution_provider.candidates() {
+            let mut builder = self.session_builder()?;
+            if provider == ExecutionProvider::Cpu {
+                let session = commit(&mut builder).map_err(load_failed)?;
+                ModelIo::of(&session).map_err(KokoroError::ModelMissing)?;
+                return Ok((session, provider));
+            }
+
+            let accelerated = provider
+                .register(&mut builder)
+                .and_then(|()| commit(&mut builder).map_err(|e| e.to_string()))
+                .and_then(|mut session| providers::probe(&mut session).map(|()| session));
+            match accelerated {
+                Ok(session) => return Ok((session, provider)),
+                Err(e) => {
+                    #[cfg(not(feature = "as-lib"))]
+                    eprintln!(
+                        "⚠️  {} unavailable, trying the next provider: {}",
+                        provider, e
+                    );
+                }
+            }
+        }
+        unreachable!("execution provider candidates end with the CPU")
+    }
+}
+
+/// Settings for a new `TtsEngine`, from `TtsEngine::builder()`
+///
+/// # Example
+/// ```no_run
+/// use kokoro_tiny::{GraphOptimizationLevel, TtsEngine};
+///
+/// # async fn demo() -> Result<(), kokoro_tiny::KokoroError> {
+/// // A 2-core box answering requests two at a time: one thread each
+/// let tts = TtsEngine::builder()
+///     .intra_threads(1)
+///     .optimization_level(GraphOptimizationLevel::Level3)
+///     .build()
+///     .await?;
+/// println!("{:?}", tts.session_options());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct EngineBuilder {
+    pub(crate) model_path: Option<String>,
+    pub(crate) voices_path: Option<String>,
+    pub(crate) retry_policy: RetryPolicy,
+    pub(crate) voice_pack: VoicePack,
+    pub(crate) variant: ModelVariant,
+    pub(crate) model_urls: Option<Vec<String>>,
+    pub(crate) voices_urls: Option<Vec<String>>,
+    pub(crate) strict: bool,
+    pub(crate) warm: bool,
+    pub(crate) cache_dir: Option<PathBuf>,
+    pub(crate) session: SessionOptions,
+}
+
+impl EngineBuilder {
+    /// Model and voice-pack files (default: in the cache directory); missing
+    /// files are downloaded to these paths
+    pub fn paths(mut self, model_path: &str, voices_path: &str) -> Self {
+        self.model_path = Some(model_path.to_string());
+        self.voices_path = Some(voices_path.to_string());
+        self
+    }
+
+    /// How failed downloads are retried (see `TtsEngine::with_retry_policy`)
+    pub fn retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry_policy = policy;
+        self
+    }
+
+    /// Voice pack to download and load (see `TtsEngine::with_voice_pack`)
+    pub fn voice_pack(mut self, voice_pack: VoicePack) -> Self {
+        self.voice_pack = voice_pack;
+        self
+    }
+
+    /// Model build to download and load: `Int8` is smaller and faster on
+    /// weak CPUs (see `model_variant`); both use the same voice packs
+    pub fn variant(mut self, variant: ModelVariant) -> Self {
+        self.variant = variant;
+        self
+    }
+
+    /// Where to download the model from, tried in order (see `download`);
+    /// replaces the default URL and `KOKORO_MODEL_URL`
+    ///
+    /// Files from custom URLs aren't checked against the pinned SHA-256 (see
+    /// `integrity`).
+    pub fn model_urls(mut self, urls: &[&str]) -> Self {
+        self.model_urls = Some(urls.iter().map(|url| url.to_string()).collect())
+            .filter(|urls: &Vec<String>| !urls.is_empty());
+        self
+    }
+
+    /// Same for the voice pack; replaces its URL and `KOKORO_VOICES_URL`
+    pub fn voices_urls(mut self, urls: &[&str]) -> Self {
+        self.voices_urls = Some(urls.iter().map(|url| url.to_string()).collect())
+            .filter(|urls: &Vec<String>| !urls.is_empty());
+        self
+    }
+
+    /// A failed download is an error instead of fallback mode (see
+    /// `TtsEngine::new_strict`)
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Run `TtsEngine::warm_up` before returning the engine, so the first
+    /// request doesn't pay for start-up
+    pub fn warm(mut self, warm: bool) -> Self {
+        self.warm = warm;
+        self
+    }
+
+    /// Directory for the model files and engine state, instead of the one
+    /// `kokoro_tiny::cache_dir()` picks from the environment; explicit
+    /// `paths` still win for the model and voices
+    pub fn cache_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.cache_dir = Some(dir.into());
+        self
+    }
+
+    /// Intra-op threads (0: ONNX Runtime's choice)
+    pub fn intra_threads(mut self, threads: usize) -> Self {
+        self.session.intra_threads = threads;
+        self
+    }
+
+    /// Inter-op threads; above 0, independent operators run in parallel
+    pub fn inter_threads(mut self, threads: usize) -> Self {
+        self.session.inter_threads = threads;
+        self
+    }
+
+    pub fn optimization_level(mut self, level: GraphOptimizationLevel) -> Self {
+        self.session.optimization_level = level;
+        self
+    }
+
+    /// Hardware to run the model on; the CPU if it doesn't work
+    pub fn execution_provider(mut self, provider: ExecutionProvider) -> Self {
+        self.session.execution_provider = provider;
+        self
+    }
+
+    /// All session settings at once
+    pub fn session_options(mut self, opts: SessionOptions) -> Self {
+        self.session = opts;
+        self
+    }
+
+    /// Download what's missing and load the engine, as `TtsEngine::new` does
+    pub async fn build(self) -> Result<TtsEngine, KokoroError> {
+        let (model_path, voices_path) = self.file_paths();
+        let engine = TtsEngine::open(&model_path, &voices_path, &self).await?;
+        self.finish(engine)
+    }
+
+    /// Load files that are already on disk, synchronously and without any
+    /// download (see `TtsEngine::from_cached`)
+    pub fn build_cached(self) -> Result<TtsEngine, KokoroError> {
+        let status = self.verify_cache()?;
+        for (path, file) in [
+            (&status.model_path, &status.model),
+            (&status.voices_path, &status.voices),
+        ] {
+            if *file == FileStatus::Missing {
+                return Err(KokoroError::ModelMissing(format!(
+                    "{} is not cached; run TtsEngine::new (or kokoro-speak) once to download it",
+                    path.display()
+                )));
+            }
+            if !file.is_usable() {
+                return Err(KokoroError::ModelMissing(format!(
+                    "{} is damaged ({}); TtsEngine::new (or kokoro-speak) downloads it again",
+                    path.display(),
+                    integrity::describe(file)
+                )));
+            }
+        }
+        let (model_path, voices_path) = self.file_paths();
+        let engine = TtsEngine::load(&model_path, &voices_path, &self)?;
+        self.finish(engine)
+    }
+
+    /// Build from model and voice-pack bytes in memory (see
+    /// `TtsEngine::from_memory`); the paths are not used
+    pub fn build_from_memory(
+        self,
+        model_bytes: &[u8],
+        voices_bytes: &[u8],
+    ) -> Result<TtsEngine, KokoroError> {
+        let engine = TtsEngine::load_from_memory(model_bytes, voices_bytes, &self)?;
+        self.finish(engine)
+    }
+
+    // Steps after loading that every build shares
+    fn finish(&self, engine: TtsEngine) -> Result<TtsEngine, KokoroError> {
+        if self.warm {
+            engine.warm_up()?;
+        }
+        Ok(engine)
+    }
+
+    // The given cache directory, or the one from the environment
+    pub(crate) fn resolved_cache_dir(&self) -> PathBuf {
+        self.cache_dir.clone().unwrap_or_else(cache_dir)
+    }
+
+    // The given paths, or the variant's and voice pack's files in the cache
+    // directory
+    pub(crate) fn file_paths(&self) -> (String, String) {
+        let cache_dir = self.resolved_cache_dir();
+        let model_path = self.model_path.clone().unwrap_or_else(|| {
+            let file_name = self.variant.file_name();
+            let path = cache_dir.join(file_name);
+            path.to_str().unwrap_or(file_name).to_string()
+        });
+        let voices_path = self.voices_path.clone().unwrap_or_else(|| {
+            let file_name = self.voice_pack.file_name();
+            let path = cache_dir.join(file_name);
+            path.to_str().unwrap_or(file_name).to_string()
+        });
+        (model_path, voices_path)
+    }
+}
+
+impl TtsEngine {
+    /// Start configuring an engine (paths, downloads, ONNX Runtime threads)
+    pub fn builder() -> EngineBuilder {
+        EngineBuilder::default()
+    }
+
+    /// Where this engine keeps its state (the remembered audio device); the
+    /// model files are wherever `paths` put them
+    pub fn cache_dir(&self) -> &Path {
+        &self.cache_dir
+    }
+
+    /// The ONNX Runtime settings this engine's session was (or, in fallback
+    /// mode, will be) built with
+    pub fn session_options(&self) -> SessionOptions {
+        self.session_options
+    }
+
+    /// The execution provider the model runs on; None without a model
+    /// (fallback mode)
+    ///
+    /// # Example
+    /// ```no_run
+    /// use kokoro_tiny::{ExecutionProvider, TtsEngine};
+    ///
+    /// # async fn demo() -> Result<(), kokoro_tiny::KokoroError> {
+    /// let tts = TtsEngine::builder()
+    ///     .execution_provider(ExecutionProvider::Cuda)
+    ///     .build()
+    ///     .await?;
+    /// if let Some(provider) = tts.active_provider() {
+    ///     println!("synthesizing on {}", provider);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn active_provider(&self) -> Option<ExecutionProvider> {
+        let session = self.session.read().ok()?;
+        session.as_ref().map(|(_, provider)| *provider)
+    }
+
+    /// The model build this engine was configured with (see
+    /// `EngineBuilder::variant`); in fallback mode, the one it would load
+    pub fn model_variant(&self) -> ModelVariant {
+        self.variant
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    #[test]
+    fn defaults_match_the_plain_constructors() {
+        let builder = TtsEngine::builder();
+        assert_eq!(builder.session, SessionOptions::default());
+        assert!(!builder.strict);
+
+        let cache_dir = cache_dir();
+        let (model, voices) = builder.file_paths();
+        assert_eq!(Path::new(&model), cache_dir.join("0.onnx"));
+        assert_eq!(Path::new(&voices), cache_dir.join("0.bin"));
+
+        let (_, voices) = TtsEngine::builder()
+            .voice_pack(VoicePack::Full)
+            .file_paths();
+        assert_eq!(Path::new(&voices), cache_dir.join("voices-full.bin"));
+        let (model, voices) = TtsEngine::builder().paths("m.onnx", "v.bin").file_paths();
+        assert_eq!((model.as_str(), voices.as_str()), ("m.onnx", "v.bin"));
+
+        // An explicit cache directory moves the defaults, not explicit paths
+        let custom = TtsEngine::builder().cache_dir("/srv/kokoro");
+        let (model, voices) = custom.file_paths();
+        assert_eq!(Path::new(&model), Path::new("/srv/kokoro/0.onnx"));
+        assert_eq!(Path::new(&voices), Path::new("/srv/kokoro/0.bin"));
+        let (model, _) = custom.paths("m.onnx", "v.bin").file_paths();
+        assert_eq!(model, "m.onnx");
+
+        // The int8 model sits next to the fp32 one and shares its voices
+        let (model, voices) = TtsEngine::builder()
+            .variant(ModelVariant::Int8)
+            .file_paths();
+        assert_eq!(Path::new(&model), cache_dir.join("0-int8.onnx"));
+        assert_eq!(Path::new(&voices), cache_dir.join("0.bin"));
+    }
+
+    #[tokio::test]
+    async fn settings_survive_fallback_and_missing_files() {
+        let dir = std::env::temp_dir().join(format!("kokoro-builder-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let model = dir.join("0.onnx");
+        let voices = dir.join("0.bin");
+        let (model, voices) = (model.to_str().unwrap(), voices.to_str().unwrap());
+
+        let builder = TtsEngine::builder()
+            .paths(model, voices)
+            .cache_dir(&dir)
+            .variant(ModelVariant::Int8)
+            .warm(true)
+            .intra_threads(2)
+            .inter_threads(1)
+            .optimization_level(GraphOptimizationLevel::Level1);
+        assert!(matches!(
+            builder.clone().build_cached(),
+            Err(KokoroError::ModelMissing(message)) if message.contains(model)
+        ));
+
+        // An open circuit: the download fails without touching the network
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        crate::retry::RetryState {
+            consecutive_failures: 5,
+            last_attempt: now,
+            circuit_open_until: Some(now + 3600),
+        }
+        .save(&dir)
+        .unwrap();
+
+        let engine = builder.clone().build().await.unwrap();
+        assert!(engine.is_fallback());
+        assert_eq!(engine.active_provider(), None);
+        assert_eq!(engine.cache_dir(), dir);
+        assert_eq!(engine.model_variant(), ModelVariant::Int8);
+        assert_eq!(
+            engine.session_options(),
+            SessionOptions {
+                intra_threads: 2,
+                inter_threads: 1,
+                optimization_level: GraphOptimizationLevel::Level1,
+                execution_provider: ExecutionProvider::Auto,
+            }
+        );
+        assert!(matches!(
+            builder.strict(true).build().await,
+            Err(KokoroError::DownloadFailed { .. })
+        ));
+
+        fs::remove_dir_all(&dir).ok();
+    }
+}
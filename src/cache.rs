@@ -0,0 +1,393 @@
+//! In-memory cache of synthesis results
+//!
+//! Alerts and confirmations repeat a lot, so finished audio is kept keyed by
+//! text and settings. Entries vary wildly in size (a "Done." is a few KB, a
+//! five minute render tens of MB), so the cache is bounded by bytes rather
+//! than entry count: least recently used entries are evicted until it fits
+//! the budget, and results too large for a fair share of it aren't cached.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+
+use crate::{privacy, PunctuationWeights, SynthesisWarning, SynthesizeOptions, DEFAULT_VOICE};
+
+/// Default memory budget for cached audio
+pub const DEFAULT_CACHE_BUDGET_BYTES: usize = 32 * 1024 * 1024;
+
+/// Largest share of the budget a single entry may take
+const MAX_ENTRY_FRACTION: f64 = 0.25;
+
+/// Snapshot of cache usage
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CacheStats {
+    pub entries: usize,
+    pub bytes: usize,
+    pub budget_bytes: usize,
+    pub hits: u64,
+    pub misses: u64,
+    pub evictions: u64,
+    /// hits / (hits + misses), 0.0 before any lookup
+    pub hit_rate: f64,
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct CachedAudio {
+    pub audio: Vec<f32>,
+    pub warnings: Vec<SynthesisWarning>,
+}
+
+struct Entry {
+    value: CachedAudio,
+    bytes: usize,
+    last_used: u64,
+    pinned: bool,
+}
+
+pub(crate) struct ResultCache {
+    budget: usize,
+    entries: HashMap<String, Entry>,
+    bytes: usize,
+    tick: u64,
+    hits: u64,
+    misses: u64,
+    evictions: u64,
+}
+
+impl ResultCache {
+    pub fn new(budget: usize) -> Self {
+        Self {
+            budget,
+            entries: HashMap::new(),
+            bytes: 0,
+            tick: 0,
+            hits: 0,
+            misses: 0,
+            evictions: 0,
+        }
+    }
+
+    pub fn get(&mut self, key: &str) -> Option<CachedAudio> {
+        self.tick += 1;
+        match self.entries.get_mut(key) {
+            Some(entry) => {
+                entry.last_used = self.tick;
+                self.hits += 1;
+                Some(entry.value.clone())
+            }
+            None => {
+                self.misses += 1;
+                None
+            }
+        }
+    }
+
+    /// Store a result; returns false if it is too large to cache
+    pub fn insert(&mut self, key: String, value: CachedAudio) -> bool {
+        let bytes = entry_bytes(&key, &value);
+        if bytes > self.max_entry_bytes() {
+            return false;
+        }
+
+        self.tick += 1;
+        let pinned = self.remove(&key).is_some_and(|old| old.pinned);
+        self.bytes += bytes;
+        self.entries.insert(
+            key,
+            Entry {
+                value,
+                bytes,
+                last_used: self.tick,
+                pinned,
+            },
+        );
+        self.evict_to_budget();
+        true
+    }
+
+    /// Protect an entry from eviction; false if it isn't cached
+    pub fn pin(&mut self, key: &str) -> bool {
+        match self.entries.get_mut(key) {
+            Some(entry) => {
+                entry.pinned = true;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn unpin(&mut self, key: &str) {
+        if let Some(entry) = self.entries.get_mut(key) {
+            entry.pinned = false;
+        }
+        self.evict_to_budget();
+    }
+
+    pub fn set_budget(&mut self, budget: usize) {
+        self.budget = budget;
+        self.evict_to_budget();
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.bytes = 0;
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        let lookups = self.hits + self.misses;
+        CacheStats {
+            entries: self.entries.len(),
+            bytes: self.bytes,
+            budget_bytes: self.budget,
+            hits: self.hits,
+            misses: self.misses,
+            evictions: self.evictions,
+            hit_rate: if lookups == 0 {
+                0.0
+            } else {
+                self.hits as f64 / lookups as f64
+            },
+        }
+    }
+
+    fn max_entry_bytes(&self) -> usize {
+        (self.budget as f64 * MAX_ENTRY_FRACTION) as usize
+    }
+
+    fn remove(&mut self, key: &str) -> Option<Entry> {
+        let entry = self.entries.remove(key)?;
+        self.bytes -= entry.bytes;
+        Some(entry)
+    }
+
+    // Drop least recently used, unpinned entries until under budget
+    fn evict_to_budget(&mut self) {
+        while self.bytes > self.budget {
+            let oldest = self
+                .entries
+                .iter()
+                .filter(|(_, entry)| !entry.pinned)
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(key, _)| key.clone());
+
+            match oldest {
+                Some(key) => {
+                    self.remove(&key);
+                    self.evictions += 1;
+                }
+                // Only pinned entries left
+                None => break,
+            }
+        }
+    }
+}
+
+/// Cache key covering everything that changes the rendered audio
+pub(crate) fn cache_key(text: &str, opts: &SynthesizeOptions) -> String {
+    let mut hasher = DefaultHasher::new();
+    RenderOptions(opts).hash(&mut hasher);
+    privacy::text_key(format!("{:016x}\n{}", hasher.finish(), text))
+}
+
+// The options the cached audio depends on. Fields are listed without `..`, so
+// a new option doesn't compile until it's hashed here or marked as applied
+// after the cache (by `synthesize_with`).
+struct RenderOptions<'a>(&'a SynthesizeOptions);
+
+impl Hash for RenderOptions<'_> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        let SynthesizeOptions {
+            voice,
+            speed,
+            gain,
+            lang,
+            caps_policy,
+            chunk_cache: _, // Same audio, only where chunks come from
+            punctuation_weights,
+            lenient_language,
+            strict_characters,
+            pacing,
+            pauses,
+            speed_mode,
+            extra_units,
+            style_temperature,
+            style_seed,
+            pitch,
+            trim_silence,
+            edge_fade_ms,
+            post_stretch: _,
+            pitch_semitones: _,
+            filter: _,
+            output_sample_rate: _,
+            channels: _,
+            chunk_max_chars,
+            chunk_threshold,
+            crossfade_ms,
+            paragraph_gap_ms,
+            abbreviations,
+            raw_text,
+            url_style,
+            markup,
+            emoji,
+            spell_patterns,
+            preprocess,
+        } = self.0;
+        let PunctuationWeights {
+            comma,
+            period,
+            question,
+            exclamation,
+            ellipsis,
+        } = punctuation_weights;
+
+        voice.as_deref().unwrap_or(DEFAULT_VOICE).hash(state);
+        lang.clone()
+            .unwrap_or_else(|| crate::default_lang(voice.as_deref()))
+            .hash(state);
+        for value in [speed, gain, style_temperature, pitch] {
+            value.to_bits().hash(state);
+        }
+        for weight in [comma, period, question, exclamation, ellipsis] {
+            weight.to_bits().hash(state);
+        }
+        // Sorted so equal tables give equal keys
+        let mut extra_units: Vec<_> = extra_units.iter().collect();
+        extra_units.sort_by(|a, b| a.0.cmp(b.0));
+        extra_units.hash(state);
+        (caps_policy, lenient_language, strict_characters, pacing, pauses).hash(state);
+        (speed_mode, style_seed, trim_silence, edge_fade_ms).hash(state);
+        (chunk_max_chars, chunk_threshold, crossfade_ms, paragraph_gap_ms).hash(state);
+        (abbreviations, raw_text, url_style, markup, emoji, spell_patterns, preprocess).hash(state);
+    }
+}
+
+fn entry_bytes(key: &str, value: &CachedAudio) -> usize {
+    key.len() + value.audio.len() * std::mem::size_of::<f32>()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn audio(samples: usize) -> CachedAudio {
+        CachedAudio {
+            audio: vec![0.0; samples],
+            warnings: Vec::new(),
+        }
+    }
+
+    // Keys of one byte keep the arithmetic readable: 100 samples = 401 bytes
+    #[test]
+    fn tracks_bytes_and_evicts_least_recently_used() {
+        let mut cache = ResultCache::new(1700);
+        assert!(cache.insert("a".into(), audio(100)));
+        assert!(cache.insert("b".into(), audio(100)));
+        assert!(cache.insert("c".into(), audio(100)));
+        assert_eq!(cache.stats().bytes, 3 * 401);
+
+        // Touch "a" so "b" is the oldest when "d" pushes past the budget
+        assert!(cache.get("a").is_some());
+        assert!(cache.insert("d".into(), audio(100)));
+        assert!(cache.insert("e".into(), audio(100)));
+
+        let stats = cache.stats();
+        assert_eq!(stats.entries, 4);
+        assert_eq!(stats.bytes, 4 * 401);
+        assert_eq!(stats.evictions, 1);
+        assert!(cache.get("b").is_none());
+        assert!(cache.get("a").is_some());
+
+        // Replacing an entry doesn't double count it
+        assert!(cache.insert("a".into(), audio(50)));
+        assert_eq!(cache.stats().bytes, 3 * 401 + 201);
+    }
+
+    #[test]
+    fn oversized_entries_bypass_the_cache() {
+        let mut cache = ResultCache::new(4000);
+        assert!(cache.insert("small".into(), audio(100)));
+        assert!(!cache.insert("long".into(), audio(300)));
+
+        let stats = cache.stats();
+        assert_eq!(stats.entries, 1);
+        assert_eq!(stats.evictions, 0);
+        assert!(cache.get("long").is_none());
+    }
+
+    #[test]
+    fn pinned_entries_survive_pressure() {
+        let mut cache = ResultCache::new(2000);
+        assert!(cache.insert("startup".into(), audio(100)));
+        assert!(cache.pin("startup"));
+        assert!(!cache.pin("missing"));
+
+        for i in 0..20 {
+            cache.insert(format!("note{}", i), audio(100));
+        }
+        assert!(cache.get("startup").is_some());
+        assert!(cache.stats().bytes <= 2000);
+
+        // Shrinking below the pinned size keeps the pinned entry only
+        cache.set_budget(100);
+        assert_eq!(cache.stats().entries, 1);
+        cache.unpin("startup");
+        assert_eq!(cache.stats().entries, 0);
+    }
+
+    #[test]
+    fn keys_follow_render_options_only() {
+        let text = "Deploy finished";
+        let base = cache_key(text, &SynthesizeOptions::new());
+        assert_eq!(base, cache_key(text, &SynthesizeOptions::new()));
+        assert_ne!(base, cache_key("Deploy failed", &SynthesizeOptions::new()));
+
+        let rendered = [
+            SynthesizeOptions::new().voice("am_adam"),
+            SynthesizeOptions::new().speed(1.1),
+            SynthesizeOptions::new().lang("en-gb"),
+            SynthesizeOptions::new().punctuation_weights(PunctuationWeights {
+                comma: 2.0,
+                ..Default::default()
+            }),
+            SynthesizeOptions::new().style_temperature(0.2, Some(7)),
+            SynthesizeOptions::new().edge_fade_ms(0),
+            SynthesizeOptions::new().chunk_max_chars(60),
+            SynthesizeOptions::new().abbreviations(&["Intl."]),
+            SynthesizeOptions::new().spell_patterns(vec![crate::SpellPattern::Mac]),
+            SynthesizeOptions::new().extra_units(HashMap::from([(
+                "rpm".to_string(),
+                crate::UnitWords::new("revolution per minute", "revolutions per minute"),
+            )])),
+        ];
+        for opts in rendered {
+            assert_ne!(cache_key(text, &opts), base, "{:?}", opts);
+        }
+
+        // Applied to cached audio by synthesize_with, so they share the entry
+        let processed = [
+            SynthesizeOptions::new().post_stretch(1.1),
+            SynthesizeOptions::new().pitch_semitones(-2.0),
+            SynthesizeOptions::new().output_sample_rate(16_000),
+            SynthesizeOptions::new().channels(crate::Channels::Stereo { pan: 0.3 }),
+        ];
+        for opts in processed {
+            assert_eq!(cache_key(text, &opts), base, "{:?}", opts);
+        }
+    }
+
+    #[test]
+    fn hit_rate_counts_every_lookup() {
+        let mut cache = ResultCache::new(10_000);
+        assert_eq!(cache.stats().hit_rate, 0.0);
+
+        cache.insert("a".into(), audio(10));
+        cache.get("a");
+        cache.get("a");
+        cache.get("a");
+        cache.get("b");
+
+        let stats = cache.stats();
+        assert_eq!((stats.hits, stats.misses), (3, 1));
+        assert!((stats.hit_rate - 0.75).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,240 @@
+//! On-disk cache of rendered long-form chunks
+//!
+//! Documents that are re-rendered regularly mostly consist of unchanged
+//! sentences. Each chunk's raw model output is stored under a content hash of
+//! everything that determines it (token ids, voice style, speed and model), so
+//! only edited chunks reach the model on the next render. Gain and crossfades
+//! are applied after retrieval, which keeps cached chunks composing exactly
+//! like fresh ones.
+
+use std::fs::{self, File, FileTimes};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Default size limit for the chunk directory
+pub const DEFAULT_CHUNK_CACHE_BYTES: u64 = 256 * 1024 * 1024;
+
+/// Directory of `<hash>.pcm` files (raw little-endian f32 samples)
+#[derive(Clone, Debug)]
+pub(crate) struct ChunkStore {
+    pub dir: PathBuf,
+    pub max_bytes: u64,
+}
+
+impl ChunkStore {
+    pub fn new(dir: PathBuf) -> Self {
+        Self {
+            dir,
+            max_bytes: DEFAULT_CHUNK_CACHE_BYTES,
+        }
+    }
+
+    pub fn load(&self, key: &str) -> Option<Vec<f32>> {
+        let path = self.path(key);
+        let bytes = fs::read(&path).ok()?;
+        if bytes.len() % 4 != 0 {
+            return None;
+        }
+
+        // Access time drives eviction; don't rely on the mount updating it
+        if let Ok(file) = File::options().write(true).open(&path) {
+            let _ = file.set_times(FileTimes::new().set_accessed(SystemTime::now()));
+        }
+
+        Some(
+            bytes
+                .chunks_exact(4)
+                .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+                .collect(),
+        )
+    }
+
+    pub fn store(&self, key: &str, audio: &[f32]) -> Result<(), String> {
+        fs::create_dir_all(&self.dir)
+            .map_err(|e| format!("Failed to create chunk cache directory: {}", e))?;
+
+        let bytes: Vec<u8> = audio.iter().flat_map(|s| s.to_le_bytes()).collect();
+        // Write then rename so a concurrent reader never sees a partial chunk
+        let tmp = self.dir.join(format!("{}.tmp", key));
+        fs::write(&tmp, bytes).map_err(|e| format!("Failed to write chunk: {}", e))?;
+        fs::rename(&tmp, self.path(key)).map_err(|e| format!("Failed to store chunk: {}", e))?;
+
+        self.gc()
+    }
+
+    /// Delete least recently accessed chunks until the directory fits `max_bytes`
+    pub fn gc(&self) -> Result<(), String> {
+        let entries = fs::read_dir(&self.dir)
+            .map_err(|e| format!("Failed to read chunk cache directory: {}", e))?;
+
+        let mut chunks: Vec<(PathBuf, u64, SystemTime)> = entries
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "pcm"))
+            .filter_map(|entry| {
+                let meta = entry.metadata().ok()?;
+                let used = meta.accessed().or_else(|_| meta.modified()).ok()?;
+                Some((entry.path(), meta.len(), used))
+            })
+            .collect();
+
+        let mut total: u64 = chunks.iter().map(|(_, len, _)| len).sum();
+        chunks.sort_by_key(|(_, _, used)| *used);
+
+        for (path, len, _) in chunks {
+            if total <= self.max_bytes {
+                break;
+            }
+            if fs::remove_file(&path).is_ok() {
+                total -= len;
+            }
+        }
+        Ok(())
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{}.pcm", key))
+    }
+}
+
+/// Stable content hash for one chunk's model input
+pub(crate) fn chunk_key(tokens: &[i64], style: &[f32], speed: f32, model: &str) -> String {
+    let mut hash = Fnv64::new();
+    for token in tokens {
+        hash.write(&token.to_le_bytes());
+    }
+    for value in style {
+        hash.write(&value.to_le_bytes());
+    }
+    hash.write(&speed.to_le_bytes());
+    hash.write(model.as_bytes());
+    format!("{:016x}", hash.0)
+}
+
+/// Identifies the model file by path, size and modification time
+///
+/// Hashing the 300 MB file itself on every render would cost more than the
+/// cache saves.
+pub(crate) fn model_fingerprint(model_path: &Path) -> String {
+    let meta = fs::metadata(model_path).ok();
+    let len = meta.as_ref().map(|m| m.len()).unwrap_or(0);
+    let modified = meta
+        .and_then(|m| m.modified().ok())
+        .and_then(|t| t.duration_since(SystemTime::UNIX_EPOCH).ok())
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    format!("{}:{}:{}", model_path.display(), len, modified)
+}
+
+// FNV-1a: tiny and, unlike std's hasher, guaranteed stable across releases
+pub(crate) struct Fnv64(pub u64);
+
+impl Fnv64 {
+    pub fn new() -> Self {
+        Self(0xcbf29ce484222325)
+    }
+
+    pub fn write(&mut self, bytes: &[u8]) {
+        for &b in bytes {
+            self.0 ^= b as u64;
+            self.0 = self.0.wrapping_mul(0x100000001b3);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{SynthesizeOptions, TtsEngine, DEFAULT_VOICE};
+    use std::collections::HashMap;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    const DOCUMENT: &str = "Welcome to the project documentation. This page explains how the \
+        build works and why it is set up this way. The red button starts a full rebuild of \
+        every package. Logs are kept for seven days and then rotated. Questions go to the \
+        team channel, where someone usually answers within the hour.";
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("kokoro_chunks_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn counting_engine(dir: PathBuf, calls: Arc<AtomicUsize>) -> TtsEngine {
+        let mut engine = TtsEngine::counting(calls);
+        engine.set_cache_budget(0);
+        engine.set_chunk_cache_dir(dir);
+        engine
+    }
+
+    #[test]
+    fn unchanged_chunks_skip_inference() {
+        let dir = temp_dir("rerender");
+        let calls = Arc::new(AtomicUsize::new(0));
+        let engine = counting_engine(dir.clone(), calls.clone());
+        let opts = SynthesizeOptions::new().gain(1.4).chunk_cache(true);
+
+        let first = engine.synthesize_with(DOCUMENT, opts.clone()).unwrap();
+        let chunks = calls.load(Ordering::SeqCst);
+        assert!(chunks > 1);
+
+        let second = engine.synthesize_with(DOCUMENT, opts.clone()).unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), chunks);
+        assert_eq!(first, second);
+
+        // Same length edit keeps the chunk boundaries where they were
+        let edited = DOCUMENT.replace("red button", "big button");
+        engine.synthesize_with(&edited, opts).unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), chunks + 1);
+
+        fs::remove_dir_all(dir).ok();
+    }
+
+    #[test]
+    fn disabled_by_default() {
+        let dir = temp_dir("off");
+        let calls = Arc::new(AtomicUsize::new(0));
+        let engine = counting_engine(dir.clone(), calls.clone());
+
+        engine.synthesize_with(DOCUMENT, SynthesizeOptions::new()).unwrap();
+        engine.synthesize_with(DOCUMENT, SynthesizeOptions::new()).unwrap();
+        assert!(!dir.exists());
+        assert!(calls.load(Ordering::SeqCst) > 2);
+    }
+
+    #[test]
+    fn gc_evicts_least_recently_accessed() {
+        let dir = temp_dir("gc");
+        let mut store = ChunkStore::new(dir.clone());
+        store.max_bytes = 1000;
+
+        // 100 samples = 400 bytes per chunk
+        let audio = vec![0.5; 100];
+        store.store("old", &audio).unwrap();
+        store.store("mid", &audio).unwrap();
+        let epoch = SystemTime::UNIX_EPOCH;
+        for (key, secs) in [("old", 10), ("mid", 20)] {
+            let file = File::options().write(true).open(store.path(key)).unwrap();
+            file.set_times(FileTimes::new().set_accessed(epoch + Duration::from_secs(secs)))
+                .unwrap();
+        }
+
+        store.store("new", &audio).unwrap();
+        assert!(store.load("old").is_none());
+        assert_eq!(store.load("mid").unwrap(), audio);
+        assert_eq!(store.load("new").unwrap(), audio);
+
+        fs::remove_dir_all(dir).ok();
+    }
+
+    #[test]
+    fn keys_are_stable_and_input_sensitive() {
+        let key = chunk_key(&[1, 2, 3], &[0.1, 0.2], 0.65, "model");
+        assert_eq!(key, chunk_key(&[1, 2, 3], &[0.1, 0.2], 0.65, "model"));
+        assert_ne!(key, chunk_key(&[1, 2, 4], &[0.1, 0.2], 0.65, "model"));
+        assert_ne!(key, chunk_key(&[1, 2, 3], &[0.1, 0.2], 0.7, "model"));
+        assert_ne!(key, chunk_key(&[1, 2, 3], &[0.1, 0.2], 0.65, "other"));
+        assert_eq!(key.len(), 16);
+    }
+}
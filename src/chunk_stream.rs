@@ -0,0 +1,816 @@
+//! Chunk-by-chunk synthesis, for starting playback before the text is done
+//!
+//! `synthesize_with` returns nothing until the last chunk is rendered, so a
+//! long text means seconds of silence first. `TtsEngine::synthesize_stream`
+//! yields the audio one chunk at a time instead. Concatenated, the chunks are
+//! exactly what `synthesize_with` would have returned. The crossfade into the
+//! next chunk is held back until that chunk exists, so no samples change
+//! after they have been yielded.
+//!
+//! `play_while_synthesizing` hands each chunk to a player thread while the
+//! next one renders, and reports the time to first audio.
+//! `TtsEngine::speak_long` does the same on one output stream, opened once,
+//! so the chunks play as gaplessly as the full render would.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::audio::{fade_in, fade_out};
+use crate::coordinator::{self, PlaybackGuard};
+use crate::output::{write_until, AudioOutput, AudioStreamOut, OutputSpec};
+use crate::resume::{join_chunk, SynthesisPlan};
+use crate::{
+    collect_warnings, is_sound, resample, spelling, BufferPreset, BusyPolicy, KokoroError,
+    SynthesisWarning, SynthesizeOptions, TtsEngine, SAMPLE_RATE, TRIM_MARGIN_SAMPLES,
+};
+
+/// Chunks rendered ahead of playback; more only costs memory
+pub const QUEUE_DEPTH: usize = 2;
+
+/// Audio for one chunk of a streamed synthesis
+#[derive(Clone, Debug, PartialEq)]
+pub struct StreamedChunk {
+    pub index: usize,
+    /// Number of chunks in the whole stream
+    pub count: usize,
+    pub text: String,
+    pub audio: Vec<f32>,
+}
+
+/// Iterator over the chunks of one text, from `TtsEngine::synthesize_stream`
+pub struct ChunkStream<'a> {
+    engine: &'a TtsEngine,
+    plan: SynthesisPlan,
+    style: Vec<f32>,
+    speed: f32,
+    next: usize,
+    // Rendered but not yet yielded: the tail the next chunk crossfades into
+    held: Vec<f32>,
+    started: bool,
+    // Sound seen (trim_silence): leading silence is no longer cut
+    voiced: bool,
+    // Audio yielded so far has had its fade-in
+    faded_in: bool,
+    fallback: Option<Vec<f32>>,
+    warnings: Vec<SynthesisWarning>,
+}
+
+impl ChunkStream<'_> {
+    /// Number of chunks the text was split into
+    pub fn len(&self) -> usize {
+        if self.fallback.is_some() {
+            1
+        } else {
+            self.plan.chunks.len()
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Warnings of this stream so far: the language check and the chunks yielded
+    pub fn warnings(&self) -> &[SynthesisWarning] {
+        &self.warnings
+    }
+}
+
+impl Iterator for ChunkStream<'_> {
+    type Item = Result<StreamedChunk, KokoroError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(audio) = self.fallback.take() {
+            // The fallback message stands in for every chunk
+            self.next = self.plan.chunks.len();
+            return Some(Ok(StreamedChunk {
+                index: 0,
+                count: 1,
+                text: String::new(),
+                audio,
+            }));
+        }
+
+        let idx = self.next;
+        let count = self.plan.chunks.len();
+        if idx >= count {
+            return None;
+        }
+        self.next += 1;
+
+        let (rendered, warnings) = collect_warnings(|| {
+            self.engine
+                .render_chunk(&self.plan, idx, &self.style, self.speed)
+        });
+        self.warnings.extend(warnings);
+        self.engine.warnings.set(self.warnings.clone());
+        let chunk_audio = match rendered {
+            Ok(chunk_audio) => chunk_audio,
+            Err(e) => {
+                // Nothing after a failed chunk
+                self.next = count;
+                return Some(Err(e));
+            }
+        };
+        join_chunk(&self.plan, idx, &mut self.held, &chunk_audio, self.started);
+        self.started |= !self.held.is_empty();
+
+        let last = idx + 1 == count;
+        let mut keep = if last {
+            0
+        } else {
+            self.plan.pacing.crossfade_samples()
+        };
+        if self.plan.trim_silence {
+            keep = keep.max(self.trim_held(last));
+        }
+        let keep = keep.min(self.held.len());
+        let tail = self.held.split_off(self.held.len() - keep);
+        let mut audio = std::mem::replace(&mut self.held, tail);
+        // The edges of the whole render, as `synthesize_with` fades them
+        if !self.faded_in && !audio.is_empty() {
+            fade_in(&mut audio, self.plan.edge_fade_ms);
+            self.faded_in = true;
+        }
+        if last {
+            fade_out(&mut audio, self.plan.edge_fade_ms);
+        }
+
+        Some(Ok(StreamedChunk {
+            index: idx,
+            count,
+            text: self.plan.chunks[idx].clone(),
+            audio,
+        }))
+    }
+}
+
+impl ChunkStream<'_> {
+    // Trim `held` the way trim_silence trims a whole render, returning how
+    // much of it to hold back: silence that may yet turn out to lead the
+    // speech or trail it is never yielded
+    fn trim_held(&mut self, last: bool) -> usize {
+        if !self.voiced {
+            match self.held.iter().position(is_sound) {
+                Some(first) => {
+                    self.held.drain(..first.saturating_sub(TRIM_MARGIN_SAMPLES));
+                    self.voiced = true;
+                }
+                // No sound at all is left as is
+                None if last => return 0,
+                None => return self.held.len(),
+            }
+        }
+
+        let sound_end = self.held.iter().rposition(is_sound).map_or(0, |i| i + 1);
+        if last {
+            self.held
+                .truncate((sound_end + TRIM_MARGIN_SAMPLES).min(self.held.len()));
+            0
+        } else {
+            self.held.len() - sound_end
+        }
+    }
+}
+
+impl TtsEngine {
+    /// Synthesize text one chunk at a time
+    ///
+    /// Voice and language are checked up front; a chunk that fails to render
+    /// ends the stream with its error.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use kokoro_tiny::{SynthesizeOptions, TtsEngine};
+    ///
+    /// # async fn demo() -> Result<(), String> {
+    /// let tts = TtsEngine::new().await?;
+    /// for chunk in tts.synthesize_stream(&std::fs::read_to_string("notes.txt").unwrap(), SynthesizeOptions::new())? {
+    ///     let chunk = chunk?;
+    ///     println!("chunk {}/{}: {} samples", chunk.index + 1, chunk.count, chunk.audio.len());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn synthesize_stream(
+        &self,
+        text: &str,
+        opts: SynthesizeOptions,
+    ) -> Result<ChunkStream<'_>, KokoroError> {
+        let plan = SynthesisPlan::new(text, &opts);
+        let mut stream = ChunkStream {
+            engine: self,
+            style: Vec::new(),
+            speed: self.engine_speed(plan.speed, plan.speed_mode),
+            plan,
+            next: 0,
+            held: Vec::new(),
+            started: false,
+            voiced: false,
+            faded_in: false,
+            fallback: None,
+            warnings: Vec::new(),
+        };
+
+        // Same excuse message as synthesize_with while the model is missing
+        if self.is_fallback() {
+            if let Ok(audio) = self.fallback_audio(text) {
+                stream.fallback = Some(audio);
+                return Ok(stream);
+            }
+        }
+
+        let (checked, warnings) = self.warnings.record(|| self.check_language(&stream.plan));
+        stream.warnings = warnings;
+        checked?;
+        stream.plan.pacing.check()?;
+        spelling::check(&stream.plan.spell_patterns)?;
+        let plan = &stream.plan;
+        stream.style = self.varied_style(&plan.voice, plan.style_temperature, plan.style_seed)?;
+        Ok(stream)
+    }
+
+    /// `synthesize_stream` yielding only the audio
+    ///
+    /// A voice or language problem comes out as the first (and only) item
+    /// instead of up front. Concatenated, the items equal `synthesize_with`.
+    ///
+    /// # Example
+    /// ```
+    /// use kokoro_tiny::{SynthesizeOptions, TtsEngine};
+    ///
+    /// let tts = TtsEngine::without_model();
+    /// let mut audio = Vec::new();
+    /// for chunk in tts.synthesize_chunks("Build finished. Tests passed.", SynthesizeOptions::new()) {
+    ///     audio.extend(chunk?);
+    /// }
+    /// assert_eq!(audio, tts.synthesize_with("Build finished. Tests passed.", SynthesizeOptions::new())?);
+    /// # Ok::<(), kokoro_tiny::KokoroError>(())
+    /// ```
+    pub fn synthesize_chunks(
+        &self,
+        text: &str,
+        opts: SynthesizeOptions,
+    ) -> impl Iterator<Item = Result<Vec<f32>, KokoroError>> + '_ {
+        let (stream, error) = match self.synthesize_stream(text, opts) {
+            Ok(stream) => (Some(stream), None),
+            Err(e) => (None, Some(e)),
+        };
+        error.map(Err).into_iter().chain(
+            stream
+                .into_iter()
+                .flatten()
+                .map(|chunk| chunk.map(|chunk| chunk.audio)),
+        )
+    }
+
+    /// Speak a long text, playing each chunk while the next one renders
+    ///
+    /// Nothing happens until `LongSpeech::wait`, which renders on the calling
+    /// thread and plays on a second one, on a single stream of the engine's
+    /// output (the `set_output` backend, or the selected audio device).
+    /// Crossfades and paragraph gaps come out as in `synthesize_with`.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use kokoro_tiny::{SynthesizeOptions, TtsEngine};
+    ///
+    /// # async fn demo() -> Result<(), kokoro_tiny::KokoroError> {
+    /// let tts = TtsEngine::new().await?;
+    /// let article = std::fs::read_to_string("article.txt")?;
+    /// let speech = tts.speak_long(&article, SynthesizeOptions::new(), 0.8)?;
+    /// // Hand this to a cancel button or a Ctrl-C handler
+    /// let stopper = speech.stopper();
+    /// # drop(stopper);
+    /// let report = speech.wait()?;
+    /// println!("first audio after {:?}", report.time_to_first_audio);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn speak_long(
+        &self,
+        text: &str,
+        opts: SynthesizeOptions,
+        volume: f32,
+    ) -> Result<LongSpeech<'_>, KokoroError> {
+        let output = self.output().map_err(KokoroError::Playback)?;
+        Ok(LongSpeech {
+            chunks: self.synthesize_stream(text, opts)?,
+            output,
+            volume: volume.clamp(0.0, 1.0),
+            buffer: None,
+            stopper: SpeechStopper::default(),
+            stop_on: None,
+            on_event: None,
+        })
+    }
+}
+
+/// Progress of `play_while_synthesizing`, in the order it happened
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StreamEvent {
+    /// Chunk rendered and queued for playback
+    Synthesized { index: usize, count: usize },
+    /// Chunk handed to the player
+    Playing { index: usize, count: usize },
+    /// Player returned from the chunk
+    Played { index: usize, count: usize },
+}
+
+/// How a `play_while_synthesizing` run went
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct StreamReport {
+    pub synthesized: usize,
+    pub played: usize,
+    /// From the call to the first chunk reaching the player
+    pub time_to_first_audio: Option<Duration>,
+    pub elapsed: Duration,
+    /// `stop` was set before the last chunk was played
+    pub interrupted: bool,
+}
+
+/// Play chunks as they are rendered
+///
+/// `chunks` is consumed on the calling thread, `play` runs on a second thread
+/// with up to `QUEUE_DEPTH` chunks waiting for it, so the next chunk renders
+/// while the current one plays. Setting `stop` ends both after the current
+/// chunk (pair it with `stop_playback()` to cut that chunk short too). The
+/// first error from either side is returned.
+pub fn play_while_synthesizing<I, P>(
+    chunks: I,
+    stop: &AtomicBool,
+    on_event: &(dyn Fn(StreamEvent) + Sync),
+    play: P,
+) -> Result<StreamReport, KokoroError>
+where
+    I: Iterator<Item = Result<StreamedChunk, KokoroError>>,
+    P: FnMut(&StreamedChunk) -> Result<(), KokoroError> + Send,
+{
+    pipeline(
+        chunks,
+        &|| stop.load(Ordering::Relaxed),
+        on_event,
+        move || Ok(play),
+    )
+}
+
+// `play_while_synthesizing` with the player made on its own thread, so it may
+// hold what can't be sent there, such as an output stream
+fn pipeline<I, M, P>(
+    mut chunks: I,
+    stopped: &(dyn Fn() -> bool + Sync),
+    on_event: &(dyn Fn(StreamEvent) + Sync),
+    make_player: M,
+) -> Result<StreamReport, KokoroError>
+where
+    I: Iterator<Item = Result<StreamedChunk, KokoroError>>,
+    M: FnOnce() -> Result<P, KokoroError> + Send,
+    P: FnMut(&StreamedChunk) -> Result<(), KokoroError>,
+{
+    let start = Instant::now();
+    let (tx, rx) = crossbeam_channel::bounded::<StreamedChunk>(QUEUE_DEPTH);
+
+    thread::scope(|scope| {
+        let player = scope.spawn(move || {
+            let mut play = make_player()?;
+            let mut played = 0;
+            let mut first_audio = None;
+            for chunk in rx {
+                if stopped() {
+                    break;
+                }
+                on_event(StreamEvent::Playing {
+                    index: chunk.index,
+                    count: chunk.count,
+                });
+                first_audio.get_or_insert_with(|| start.elapsed());
+                play(&chunk)?;
+                played += 1;
+                on_event(StreamEvent::Played {
+                    index: chunk.index,
+                    count: chunk.count,
+                });
+            }
+            Ok::<_, KokoroError>((played, first_audio))
+        });
+
+        let mut synthesized = 0;
+        let mut total = None;
+        let mut error = None;
+        // Checked before rendering, not after, so a stop skips the next chunk
+        while !stopped() {
+            let Some(chunk) = chunks.next() else {
+                break;
+            };
+            let chunk = match chunk {
+                Ok(chunk) => chunk,
+                Err(e) => {
+                    error = Some(e);
+                    break;
+                }
+            };
+            synthesized += 1;
+            total = Some(chunk.count);
+            on_event(StreamEvent::Synthesized {
+                index: chunk.index,
+                count: chunk.count,
+            });
+            // The player stopped early; its result says why
+            if tx.send(chunk).is_err() {
+                break;
+            }
+        }
+        drop(tx);
+
+        let (played, time_to_first_audio) = player
+            .join()
+            .map_err(|_| KokoroError::Playback("Playback thread panicked".to_string()))??;
+        if let Some(e) = error {
+            return Err(e);
+        }
+        Ok(StreamReport {
+            synthesized,
+            played,
+            time_to_first_audio,
+            elapsed: start.elapsed(),
+            interrupted: stopped() && total.is_some_and(|n| played < n),
+        })
+    })
+}
+
+/// Stops a `LongSpeech`, synthesis and playback both, from any thread
+#[derive(Clone, Debug, Default)]
+pub struct SpeechStopper(Arc<AtomicBool>);
+
+impl SpeechStopper {
+    /// Cut off the chunk playing and render nothing more; calling it again
+    /// does nothing
+    pub fn stop(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_stopped(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// A long text ready to be spoken, from `TtsEngine::speak_long`
+pub struct LongSpeech<'a> {
+    chunks: ChunkStream<'a>,
+    output: Arc<dyn AudioOutput>,
+    volume: f32,
+    buffer: Option<BufferPreset>,
+    stopper: SpeechStopper,
+    stop_on: Option<&'a AtomicBool>,
+    on_event: Option<Box<dyn Fn(StreamEvent) + Sync + 'a>>,
+}
+
+impl<'a> LongSpeech<'a> {
+    /// Number of chunks the text was split into
+    pub fn len(&self) -> usize {
+        self.chunks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.chunks.is_empty()
+    }
+
+    /// Set the output buffer size (default: the backend's choice)
+    pub fn buffer(mut self, preset: BufferPreset) -> Self {
+        self.buffer = Some(preset);
+        self
+    }
+
+    /// Call `on_event` as chunks are rendered and played
+    pub fn on_event(mut self, on_event: impl Fn(StreamEvent) + Sync + 'a) -> Self {
+        self.on_event = Some(Box::new(on_event));
+        self
+    }
+
+    /// Also stop once `flag` is set, such as one a Ctrl-C handler sets
+    pub fn stop_on(mut self, flag: &'a AtomicBool) -> Self {
+        self.stop_on = Some(flag);
+        self
+    }
+
+    /// A handle that stops this speech from another thread
+    pub fn stopper(&self) -> SpeechStopper {
+        self.stopper.clone()
+    }
+
+    /// Speak, blocking until the last chunk has played or the speech was
+    /// stopped
+    ///
+    /// `stop_playback()` (or an `Interrupt` caller) stops the whole speech,
+    /// not just the chunk playing.
+    pub fn wait(self) -> Result<StreamReport, KokoroError> {
+        let LongSpeech {
+            chunks,
+            output,
+            volume,
+            buffer,
+            stopper,
+            stop_on,
+            on_event,
+        } = self;
+        let stopped =
+            || stopper.is_stopped() || stop_on.is_some_and(|flag| flag.load(Ordering::Relaxed));
+        let on_event = on_event.unwrap_or_else(|| Box::new(|_| {}));
+
+        pipeline(chunks, &stopped, &*on_event, || {
+            let playing = coordinator::coordinator()
+                .acquire(BusyPolicy::Queue)
+                .map_err(KokoroError::Playback)?;
+            let stream = output
+                .open(&OutputSpec {
+                    sample_rate: SAMPLE_RATE,
+                    buffer,
+                    channels: 1,
+                })
+                .map_err(KokoroError::Playback)?;
+            let mut player = ChunkPlayer {
+                stream,
+                playing,
+                volume,
+                stopper: &stopper,
+                stopped: &stopped,
+            };
+            Ok(move |chunk: &StreamedChunk| player.play(chunk))
+        })
+    }
+}
+
+// The player side of `LongSpeech`: every chunk onto one stream, which plays
+// out when the player is dropped unless the speech was stopped
+struct ChunkPlayer<'a> {
+    stream: Box<dyn AudioStreamOut>,
+    playing: PlaybackGuard<'static>,
+    volume: f32,
+    stopper: &'a SpeechStopper,
+    stopped: &'a (dyn Fn() -> bool + Sync),
+}
+
+impl ChunkPlayer<'_> {
+    fn play(&mut self, chunk: &StreamedChunk) -> Result<(), KokoroError> {
+        let rate = self.stream.info().sample_rate;
+        let frames: Vec<f32> = resample(&chunk.audio, SAMPLE_RATE, rate)
+            .into_iter()
+            .map(|s| s * self.volume)
+            .collect();
+        let (stopped, playing) = (self.stopped, &self.playing);
+        write_until(&mut *self.stream, &frames, &|| {
+            stopped() || playing.interrupted()
+        })
+        .map_err(KokoroError::Playback)?;
+        // An interruption ends the whole speech, synthesis included
+        if self.playing.interrupted() {
+            self.stopper.stop();
+        }
+        Ok(())
+    }
+}
+
+impl Drop for ChunkPlayer<'_> {
+    fn drop(&mut self) {
+        if !(self.stopped)() {
+            self.stream.flush();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{NullOutput, PacingPreset};
+    use std::sync::{Arc, Mutex};
+
+    const LONG: &str = "The first sentence is long enough to be a chunk on its own today. \
+        The second sentence keeps going for quite a while so it also stands alone. \
+        A third one follows, because streaming needs something left to render.\n\n\
+        And the fourth sentence closes the paragraph after everything else.";
+
+    fn opts() -> SynthesizeOptions {
+        // Short chunks, crossfades and a paragraph gap
+        SynthesizeOptions::new().pacing(PacingPreset::Conversational)
+    }
+
+    #[test]
+    fn streamed_chunks_add_up_to_the_full_render() {
+        let engine = TtsEngine::mock(100);
+        let stream = engine.synthesize_stream(LONG, opts()).unwrap();
+        assert!(stream.len() > 2);
+        let chunks: Vec<StreamedChunk> = stream.map(Result::unwrap).collect();
+        assert!(chunks.iter().all(|chunk| !chunk.audio.is_empty()));
+        let streamed: Vec<f32> = chunks.into_iter().flat_map(|chunk| chunk.audio).collect();
+
+        assert_eq!(streamed, engine.synthesize_with(LONG, opts()).unwrap());
+    }
+
+    #[test]
+    fn audio_only_chunks_match_and_report_setup_errors_first() {
+        let engine = TtsEngine::mock(100);
+        let chunks: Vec<Vec<f32>> = engine
+            .synthesize_chunks(LONG, opts())
+            .map(Result::unwrap)
+            .collect();
+        assert!(chunks.len() > 2);
+        assert_eq!(
+            chunks.concat(),
+            engine.synthesize_with(LONG, opts()).unwrap()
+        );
+
+        let mut failed = engine.synthesize_chunks(LONG, opts().voice("af_nobody"));
+        assert!(matches!(
+            failed.next(),
+            Some(Err(KokoroError::VoiceNotFound { .. }))
+        ));
+        assert!(failed.next().is_none());
+    }
+
+    #[test]
+    fn trimmed_streams_match_the_trimmed_render() {
+        let mut engine = TtsEngine::mock(100);
+        // Every chunk comes with silence around it, so joins carry silence
+        // too; the sound alternates in sign so it has no DC offset
+        engine.mock_inference = Some(Arc::new(|tokens: &[i64], _style: &[f32], _speed: f32| {
+            let mut audio = vec![0.0; 3000];
+            audio.extend((0..tokens.len() * 100).map(|i| {
+                let sign = if i % 2 == 0 { 1.0 } else { -1.0 };
+                sign * (0.5 + (i as f32 * 0.01).sin() * 0.1)
+            }));
+            audio.extend(vec![0.0; 5000]);
+            Ok(audio)
+        }));
+        let trimmed = || opts().trim_silence(true);
+
+        let full = engine.synthesize_with(LONG, trimmed()).unwrap();
+        let untrimmed = engine.synthesize_with(LONG, opts()).unwrap();
+        let margin = TRIM_MARGIN_SAMPLES;
+        assert_eq!(
+            full.len(),
+            untrimmed.len() - (3000 - margin) - (5000 - margin)
+        );
+        assert_eq!(full[..margin], vec![0.0; margin]);
+        assert!(is_sound(&full[margin]));
+        assert!(is_sound(&full[full.len() - margin - 1]));
+        assert!(!is_sound(&full[full.len() - margin]));
+
+        let streamed: Vec<f32> = engine
+            .synthesize_stream(LONG, trimmed())
+            .unwrap()
+            .flat_map(|chunk| chunk.unwrap().audio)
+            .collect();
+        assert_eq!(streamed, full);
+
+        // Nothing but silence is left alone, streamed or not
+        engine.clear_cache();
+        engine.mock_inference = Some(Arc::new(|tokens: &[i64], _: &[f32], _: f32| {
+            Ok(vec![0.0; tokens.len() * 10])
+        }));
+        let silent = engine.synthesize_with(LONG, opts()).unwrap();
+        assert_eq!(engine.synthesize_with(LONG, trimmed()).unwrap(), silent);
+        let streamed: Vec<f32> = engine
+            .synthesize_stream(LONG, trimmed())
+            .unwrap()
+            .flat_map(|chunk| chunk.unwrap().audio)
+            .collect();
+        assert_eq!(streamed, silent);
+    }
+
+    #[test]
+    fn first_chunk_plays_before_the_last_is_synthesized() {
+        let mut engine = TtsEngine::mock(100);
+        let count = SynthesisPlan::new(LONG, &opts()).chunks.len();
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let first_played = Arc::new(AtomicBool::new(false));
+
+        // The last chunk waits (up to a timeout) for playback to start, so a
+        // pipeline that renders everything first logs the wrong order
+        let calls = Arc::new(Mutex::new(0));
+        engine.mock_inference = Some({
+            let log = log.clone();
+            let first_played = first_played.clone();
+            Arc::new(move |tokens: &[i64], _: &[f32], _: f32| {
+                let call = {
+                    let mut calls = calls.lock().unwrap();
+                    *calls += 1;
+                    *calls
+                };
+                if call == count {
+                    let deadline = Instant::now() + Duration::from_secs(2);
+                    while !first_played.load(Ordering::SeqCst) && Instant::now() < deadline {
+                        thread::sleep(Duration::from_millis(1));
+                    }
+                }
+                log.lock().unwrap().push(format!("synth {}", call - 1));
+                Ok(vec![0.1; tokens.len() * 10])
+            })
+        });
+
+        let stop = AtomicBool::new(false);
+        let stream = engine.synthesize_stream(LONG, opts()).unwrap();
+        let report = play_while_synthesizing(stream, &stop, &|_| {}, |chunk| {
+            log.lock().unwrap().push(format!("play {}", chunk.index));
+            first_played.store(true, Ordering::SeqCst);
+            Ok(())
+        })
+        .unwrap();
+
+        let log = log.lock().unwrap();
+        let position = |entry: &str| log.iter().position(|e| e == entry).unwrap();
+        assert!(
+            position("play 0") < position(&format!("synth {}", count - 1)),
+            "{:?}",
+            log
+        );
+        assert_eq!(report.synthesized, count);
+        assert_eq!(report.played, count);
+        assert!(report.time_to_first_audio.is_some());
+        assert!(!report.interrupted);
+    }
+
+    #[test]
+    fn stop_ends_synthesis_and_playback() {
+        let engine = TtsEngine::mock(100);
+        let stop = AtomicBool::new(false);
+        let events = Mutex::new(Vec::new());
+        let text = [LONG; 4].join("\n\n");
+        let stream = engine.synthesize_stream(&text, opts()).unwrap();
+        let count = stream.len();
+
+        let report = play_while_synthesizing(
+            stream,
+            &stop,
+            &|event| events.lock().unwrap().push(event),
+            |_| {
+                // Ctrl-C during the first chunk
+                stop.store(true, Ordering::Relaxed);
+                Ok(())
+            },
+        )
+        .unwrap();
+
+        // At most the queue and one blocked send were rendered ahead
+        assert_eq!(report.played, 1);
+        assert!(report.synthesized <= QUEUE_DEPTH + 2, "{:?}", report);
+        assert!(report.synthesized < count);
+        assert!(report.interrupted);
+        let events = events.lock().unwrap();
+        assert_eq!(events[0], StreamEvent::Synthesized { index: 0, count });
+        assert!(events.contains(&StreamEvent::Played { index: 0, count }));
+        assert!(!events.contains(&StreamEvent::Playing { index: 1, count }));
+    }
+
+    #[test]
+    fn long_speech_plays_the_full_render_on_one_stream() {
+        let output = NullOutput::new().with_sample_rate(48_000);
+        let mut engine = TtsEngine::mock(100);
+        engine.set_output(Arc::new(output.clone()));
+
+        let events = Mutex::new(Vec::new());
+        let speech = engine
+            .speak_long(LONG, opts(), 1.0)
+            .unwrap()
+            .on_event(|event| events.lock().unwrap().push(event));
+        let count = speech.len();
+        assert!(count > 2);
+        let report = speech.wait().unwrap();
+
+        assert_eq!((report.synthesized, report.played), (count, count));
+        assert!(!report.interrupted);
+        assert_eq!(output.opened(), 1);
+        assert_eq!(events.lock().unwrap().len(), 3 * count);
+        // Chunk by chunk at the stream's rate, as long as the whole render
+        let full = engine.synthesize_with(LONG, opts()).unwrap();
+        assert_eq!(output.frames().len(), full.len() * 2);
+    }
+
+    #[test]
+    fn stopper_ends_synthesis_and_playback() {
+        // Writes take as long as they would to play
+        let output = NullOutput::new().with_sample_rate(1_000).realtime();
+        let mut engine = TtsEngine::mock(100);
+        engine.set_output(Arc::new(output.clone()));
+        let text = [LONG; 4].join("\n\n");
+
+        let speech = engine.speak_long(&text, opts(), 0.5).unwrap();
+        let count = speech.len();
+        let stopper = speech.stopper();
+        let start = Instant::now();
+        let report = thread::scope(|scope| {
+            scope.spawn(|| {
+                thread::sleep(Duration::from_millis(100));
+                stopper.stop();
+                stopper.stop();
+            });
+            speech.wait().unwrap()
+        });
+
+        assert!(start.elapsed() < Duration::from_millis(500));
+        assert!(report.interrupted);
+        assert!(report.synthesized < count, "{:?}", report);
+        let total = engine.synthesize_with(&text, opts()).unwrap().len() / 24;
+        assert!(output.frames().len() < total / 2);
+    }
+}
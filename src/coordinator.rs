@@ -0,0 +1,338 @@
+//! Process-wide playback coordination
+//!
+//! Every `play*` call on any engine goes through one coordinator, so two
+//! threads (or two MCP tool calls) never open two output streams and talk
+//! over each other. What a call does when audio is already playing is chosen
+//! per call with `PlaybackOptions::on_busy`.
+//!
+//! Only playback holds the coordinator; synthesis never touches it, so the
+//! next utterance can render while the current one is still playing.
+
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, OnceLock};
+
+/// What `play` does when another utterance is already playing
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BusyPolicy {
+    /// Block until nothing is playing or queued; waiters keep no order among themselves
+    #[default]
+    Wait,
+    /// Block and play in call order after everything already queued
+    Queue,
+    /// Stop the current utterance and play right away
+    Interrupt,
+    /// Fail immediately
+    Error,
+}
+
+/// Whether any engine in this process is currently playing audio
+pub fn is_audio_active() -> bool {
+    coordinator().is_active()
+}
+
+/// Cut off whatever is playing, as an `Interrupt` caller would
+///
+/// Returns false if nothing was playing. Queued utterances still play.
+pub fn stop_playback() -> bool {
+    coordinator().stop_current()
+}
+
+pub(crate) fn coordinator() -> &'static Coordinator {
+    static COORDINATOR: OnceLock<Coordinator> = OnceLock::new();
+    COORDINATOR.get_or_init(Coordinator::new)
+}
+
+#[derive(Default)]
+struct State {
+    // Stop flag of the utterance holding the device
+    active: Option<Arc<AtomicBool>>,
+    // Tickets of Queue/Interrupt callers, in the order they get the device
+    line: VecDeque<u64>,
+    next_ticket: u64,
+}
+
+pub(crate) struct Coordinator {
+    state: Mutex<State>,
+    freed: Condvar,
+}
+
+impl Coordinator {
+    pub fn new() -> Self {
+        Self {
+            state: Mutex::new(State::default()),
+            freed: Condvar::new(),
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.lock().active.is_some()
+    }
+
+    pub fn stop_current(&self) -> bool {
+        match &self.lock().active {
+            Some(current) => {
+                current.store(true, Ordering::Release);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Take the device according to `policy`; it is released when the guard drops
+    pub fn acquire(&self, policy: BusyPolicy) -> Result<PlaybackGuard<'_>, String> {
+        let mut state = self.lock();
+
+        let ticket = match policy {
+            BusyPolicy::Error if state.active.is_some() || !state.line.is_empty() => {
+                return Err("Audio is already playing".to_string());
+            }
+            BusyPolicy::Error | BusyPolicy::Wait => None,
+            BusyPolicy::Queue | BusyPolicy::Interrupt => {
+                let ticket = state.next_ticket;
+                state.next_ticket += 1;
+                if policy == BusyPolicy::Queue {
+                    state.line.push_back(ticket);
+                } else {
+                    if let Some(current) = &state.active {
+                        current.store(true, Ordering::Release);
+                    }
+                    state.line.push_front(ticket);
+                }
+                Some(ticket)
+            }
+        };
+
+        loop {
+            let our_turn = match ticket {
+                Some(ticket) => state.line.front() == Some(&ticket),
+                None => state.line.is_empty(),
+            };
+            if state.active.is_none() && our_turn {
+                break;
+            }
+            state = self.freed.wait(state).unwrap_or_else(|e| e.into_inner());
+        }
+
+        if ticket.is_some() {
+            state.line.pop_front();
+        }
+        let stop = Arc::new(AtomicBool::new(false));
+        state.active = Some(stop.clone());
+        Ok(PlaybackGuard { owner: self, stop })
+    }
+
+    // Nothing in State can be left half-updated by a panic, so poisoning is ignored
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Held for the duration of one utterance's playback
+pub(crate) struct PlaybackGuard<'a> {
+    owner: &'a Coordinator,
+    stop: Arc<AtomicBool>,
+}
+
+impl PlaybackGuard<'_> {
+    /// Set when an `Interrupt` caller wants the device; playback should stop
+    pub fn interrupted(&self) -> bool {
+        self.stop.load(Ordering::Acquire)
+    }
+}
+
+impl Drop for PlaybackGuard<'_> {
+    fn drop(&mut self) {
+        self.owner.lock().active = None;
+        self.owner.freed.notify_all();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{SynthesizeOptions, TtsEngine};
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    type Log = Mutex<Vec<String>>;
+
+    // Stub output backend: "plays" for `length`, polling for interruption
+    fn fake_play(
+        coordinator: &Coordinator,
+        policy: BusyPolicy,
+        name: &str,
+        length: Duration,
+        log: &Log,
+    ) -> Result<(), String> {
+        let guard = coordinator.acquire(policy)?;
+        log.lock().unwrap().push(format!("start {}", name));
+        let started = Instant::now();
+        while started.elapsed() < length {
+            if guard.interrupted() {
+                log.lock().unwrap().push(format!("cut {}", name));
+                return Ok(());
+            }
+            thread::sleep(Duration::from_millis(2));
+        }
+        log.lock().unwrap().push(format!("end {}", name));
+        Ok(())
+    }
+
+    fn wait_until_active(coordinator: &Coordinator) {
+        while !coordinator.is_active() {
+            thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    #[test]
+    fn queue_plays_in_call_order() {
+        let coordinator = Coordinator::new();
+        let log = Log::default();
+        let short = Duration::from_millis(60);
+
+        thread::scope(|s| {
+            s.spawn(|| fake_play(&coordinator, BusyPolicy::Queue, "a", short, &log));
+            wait_until_active(&coordinator);
+            for name in ["b", "c", "d"] {
+                let (coordinator, log) = (&coordinator, &log);
+                s.spawn(move || fake_play(coordinator, BusyPolicy::Queue, name, short, log));
+                // Let each caller take its ticket before the next one asks
+                thread::sleep(Duration::from_millis(10));
+            }
+        });
+
+        let log = log.into_inner().unwrap();
+        assert_eq!(
+            log,
+            ["start a", "end a", "start b", "end b", "start c", "end c", "start d", "end d"]
+        );
+        assert!(!coordinator.is_active());
+    }
+
+    #[test]
+    fn interrupt_cuts_the_current_utterance() {
+        let coordinator = Coordinator::new();
+        let log = Log::default();
+        let started = Instant::now();
+
+        thread::scope(|s| {
+            s.spawn(|| {
+                fake_play(
+                    &coordinator,
+                    BusyPolicy::Wait,
+                    "long",
+                    Duration::from_secs(5),
+                    &log,
+                )
+            });
+            wait_until_active(&coordinator);
+            fake_play(
+                &coordinator,
+                BusyPolicy::Interrupt,
+                "urgent",
+                Duration::from_millis(20),
+                &log,
+            )
+            .unwrap();
+        });
+
+        assert_eq!(
+            log.into_inner().unwrap(),
+            ["start long", "cut long", "start urgent", "end urgent"]
+        );
+        assert!(started.elapsed() < Duration::from_secs(2));
+    }
+
+    #[test]
+    fn stop_current_cuts_without_taking_the_device() {
+        let coordinator = Coordinator::new();
+        let log = Log::default();
+
+        assert!(!coordinator.stop_current());
+        thread::scope(|s| {
+            s.spawn(|| {
+                fake_play(
+                    &coordinator,
+                    BusyPolicy::Wait,
+                    "a",
+                    Duration::from_secs(5),
+                    &log,
+                )
+            });
+            wait_until_active(&coordinator);
+            assert!(coordinator.stop_current());
+        });
+
+        assert_eq!(log.into_inner().unwrap(), ["start a", "cut a"]);
+        assert!(!coordinator.is_active());
+    }
+
+    #[test]
+    fn error_policy_fails_only_while_busy() {
+        let coordinator = Coordinator::new();
+        let log = Log::default();
+
+        thread::scope(|s| {
+            s.spawn(|| {
+                fake_play(
+                    &coordinator,
+                    BusyPolicy::Wait,
+                    "a",
+                    Duration::from_millis(80),
+                    &log,
+                )
+            });
+            wait_until_active(&coordinator);
+            let err = fake_play(&coordinator, BusyPolicy::Error, "b", Duration::ZERO, &log);
+            assert!(err.unwrap_err().contains("already playing"));
+        });
+
+        fake_play(&coordinator, BusyPolicy::Error, "c", Duration::ZERO, &log).unwrap();
+        assert_eq!(
+            log.into_inner().unwrap(),
+            ["start a", "end a", "start c", "end c"]
+        );
+    }
+
+    #[test]
+    fn wait_blocks_until_the_device_is_free() {
+        let coordinator = Coordinator::new();
+        let log = Log::default();
+
+        thread::scope(|s| {
+            s.spawn(|| {
+                fake_play(
+                    &coordinator,
+                    BusyPolicy::Wait,
+                    "a",
+                    Duration::from_millis(60),
+                    &log,
+                )
+            });
+            wait_until_active(&coordinator);
+            fake_play(&coordinator, BusyPolicy::Wait, "b", Duration::ZERO, &log).unwrap();
+        });
+
+        assert_eq!(
+            log.into_inner().unwrap(),
+            ["start a", "end a", "start b", "end b"]
+        );
+    }
+
+    #[test]
+    fn synthesis_does_not_wait_for_playback() {
+        let engine = TtsEngine::mock(100);
+
+        // Hold the process-wide device as if something were playing
+        let _playing = coordinator().acquire(BusyPolicy::Error).unwrap();
+        assert!(is_audio_active());
+        let audio = engine
+            .synthesize_with(
+                "Rendering while the speaker is busy.",
+                SynthesizeOptions::new(),
+            )
+            .unwrap();
+        assert!(!audio.is_empty());
+    }
+}
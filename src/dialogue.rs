@@ -0,0 +1,531 @@
+//! Dialogue synthesis - several voices, one continuous track
+//!
+//! Each line is synthesized with its speaker's voice and laid out on a shared
+//! timeline with a short gap between lines. Besides the mixed track, every
+//! speaker can get an isolated stem aligned sample-for-sample with it, so
+//! post-production can re-balance characters later.
+//!
+//! Scripts can also be written as text, one `voice: line` per line:
+//!
+//! ```text
+//! af_sky: Did the build pass?
+//! am_adam: Every test is green.
+//! ```
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::{AudioDuration, KokoroError, SynthesizeOptions, TtsEngine, SAMPLE_RATE};
+
+/// Silence between consecutive lines
+const DIALOGUE_GAP_MS: u32 = 350;
+
+/// One line of a dialogue script
+#[derive(Clone, Debug)]
+pub struct DialogueLine {
+    pub speaker_voice: String,
+    pub text: String,
+    pub opts: Option<SynthesizeOptions>,
+}
+
+impl DialogueLine {
+    pub fn new(speaker_voice: &str, text: &str) -> Self {
+        Self {
+            speaker_voice: speaker_voice.to_string(),
+            text: text.to_string(),
+            opts: None,
+        }
+    }
+}
+
+/// How a whole dialogue is paced and how each speaker sounds
+#[derive(Clone, Debug)]
+pub struct DialogueOptions {
+    /// Silence between consecutive lines
+    pub gap_ms: u32,
+    /// Speaker voice -> options for their lines (speed, gain, ...); a line's
+    /// own `opts` win over these
+    pub speakers: HashMap<String, SynthesizeOptions>,
+}
+
+impl Default for DialogueOptions {
+    fn default() -> Self {
+        Self {
+            gap_ms: DIALOGUE_GAP_MS,
+            speakers: HashMap::new(),
+        }
+    }
+}
+
+impl DialogueOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the silence between lines (default 350 ms)
+    pub fn gap_ms(mut self, ms: u32) -> Self {
+        self.gap_ms = ms;
+        self
+    }
+
+    /// Render every line of `speaker_voice` with `opts`
+    ///
+    /// # Example
+    /// ```
+    /// use kokoro_tiny::dialogue::DialogueOptions;
+    /// use kokoro_tiny::SynthesizeOptions;
+    ///
+    /// let opts = DialogueOptions::new()
+    ///     .gap_ms(500)
+    ///     .speaker("am_adam", SynthesizeOptions::new().speed(0.9).gain(1.2));
+    /// assert_eq!(opts.speakers["am_adam"].speed, 0.9);
+    /// ```
+    pub fn speaker(mut self, speaker_voice: &str, opts: SynthesizeOptions) -> Self {
+        self.speakers.insert(speaker_voice.to_string(), opts);
+        self
+    }
+
+    // Options for `line`: its own, else its speaker's, always in its voice
+    fn line_options(&self, line: &DialogueLine) -> SynthesizeOptions {
+        line.opts
+            .clone()
+            .or_else(|| self.speakers.get(&line.speaker_voice).cloned())
+            .unwrap_or_default()
+            .voice(&line.speaker_voice)
+    }
+}
+
+/// Read a script of `voice: text` lines
+///
+/// Blank lines and lines starting with `#` are skipped; a line without a
+/// speaker continues the one before it.
+///
+/// # Example
+/// ```
+/// use kokoro_tiny::dialogue::parse_script;
+///
+/// let lines = parse_script("af_sky: Hello\nam_adam: Hi there").unwrap();
+/// assert_eq!(lines[1].speaker_voice, "am_adam");
+/// assert_eq!(lines[1].text, "Hi there");
+/// ```
+pub fn parse_script(script: &str) -> Result<Vec<DialogueLine>, String> {
+    let mut lines: Vec<DialogueLine> = Vec::new();
+    for (number, row) in script.lines().enumerate() {
+        let row = row.trim();
+        if row.is_empty() || row.starts_with('#') {
+            continue;
+        }
+        let spoken = row.split_once(':').filter(|(voice, _)| is_voice_name(voice.trim()));
+        match spoken {
+            Some((voice, text)) => lines.push(DialogueLine::new(voice.trim(), text.trim())),
+            None => match lines.last_mut() {
+                Some(line) => {
+                    line.text.push(' ');
+                    line.text.push_str(row);
+                }
+                None => {
+                    return Err(format!(
+                        "Script line {} has no speaker (expected \"voice: text\")",
+                        number + 1
+                    ))
+                }
+            },
+        }
+    }
+    Ok(lines)
+}
+
+// "af_sky", or a mix like "af_sky.5+af_bella.5"; "Note: ..." is speech
+fn is_voice_name(name: &str) -> bool {
+    let voice_char = |c: char| c.is_ascii_lowercase() || c.is_ascii_digit() || "_.+".contains(c);
+    !name.is_empty() && name.chars().all(voice_char)
+}
+
+/// Which tracks `synthesize_dialogue_tracks` should produce
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DialogueOutput {
+    /// Only the mixed track
+    Combined,
+    /// Only the per-speaker stems
+    Stems,
+    /// Mixed track and stems
+    Both,
+}
+
+/// Where a line sits on the dialogue timeline
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct DialogueSpan {
+    pub line: usize,
+    pub speaker: String,
+    pub start_sample: usize,
+    pub end_sample: usize,
+    pub start_ms: u64,
+    pub end_ms: u64,
+}
+
+/// Rendered dialogue: the mix, the stems, or both, plus the line timeline
+#[derive(Clone, Debug, Default)]
+pub struct DialogueTracks {
+    pub combined: Option<Vec<f32>>,
+    /// Speaker voice -> stem, each as long as the combined timeline
+    pub stems: HashMap<String, Vec<f32>>,
+    pub timeline: Vec<DialogueSpan>,
+}
+
+impl TtsEngine {
+    /// Synthesize a dialogue into one continuous track
+    ///
+    /// # Example
+    /// ```no_run
+    /// use kokoro_tiny::dialogue::DialogueLine;
+    /// use kokoro_tiny::TtsEngine;
+    ///
+    /// # async fn demo() -> Result<(), kokoro_tiny::KokoroError> {
+    /// let tts = TtsEngine::new().await?;
+    /// let audio = tts.synthesize_dialogue(&[
+    ///     DialogueLine::new("af_sky", "Did the build pass?"),
+    ///     DialogueLine::new("am_adam", "Every test is green."),
+    /// ])?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn synthesize_dialogue(&self, lines: &[DialogueLine]) -> Result<Vec<f32>, KokoroError> {
+        self.synthesize_dialogue_with(lines, &DialogueOptions::default())
+    }
+
+    /// `synthesize_dialogue` with custom gaps and per-speaker options
+    ///
+    /// # Example
+    /// ```no_run
+    /// use kokoro_tiny::dialogue::{parse_script, DialogueOptions};
+    /// use kokoro_tiny::{SynthesizeOptions, TtsEngine};
+    ///
+    /// # async fn demo() -> Result<(), String> {
+    /// let tts = TtsEngine::new().await?;
+    /// let lines = parse_script("af_sky: Once upon a time...\nam_adam: Who goes there?")?;
+    /// let opts = DialogueOptions::new()
+    ///     .gap_ms(600)
+    ///     .speaker("af_sky", SynthesizeOptions::new().speed(0.9));
+    /// let audio = tts.synthesize_dialogue_with(&lines, &opts)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn synthesize_dialogue_with(
+        &self,
+        lines: &[DialogueLine],
+        opts: &DialogueOptions,
+    ) -> Result<Vec<f32>, KokoroError> {
+        let tracks = self.synthesize_dialogue_tracks_with(lines, DialogueOutput::Combined, opts)?;
+        Ok(tracks.combined.unwrap_or_default())
+    }
+
+    /// Synthesize a dialogue, returning the mixed track and/or per-speaker stems
+    ///
+    /// Stems contain silence wherever another speaker talks, so summing them
+    /// reproduces the combined track exactly.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use kokoro_tiny::dialogue::{DialogueLine, DialogueOutput};
+    /// use kokoro_tiny::TtsEngine;
+    ///
+    /// # async fn demo() -> Result<(), kokoro_tiny::KokoroError> {
+    /// let tts = TtsEngine::new().await?;
+    /// let lines = [
+    ///     DialogueLine::new("af_sky", "Ready?"),
+    ///     DialogueLine::new("am_adam", "Ready."),
+    /// ];
+    /// let tracks = tts.synthesize_dialogue_tracks(&lines, DialogueOutput::Both)?;
+    /// tts.save_dialogue("dialogue", &tracks)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn synthesize_dialogue_tracks(
+        &self,
+        lines: &[DialogueLine],
+        output: DialogueOutput,
+    ) -> Result<DialogueTracks, KokoroError> {
+        self.synthesize_dialogue_tracks_with(lines, output, &DialogueOptions::default())
+    }
+
+    /// `synthesize_dialogue_tracks` with custom gaps and per-speaker options
+    pub fn synthesize_dialogue_tracks_with(
+        &self,
+        lines: &[DialogueLine],
+        output: DialogueOutput,
+        opts: &DialogueOptions,
+    ) -> Result<DialogueTracks, KokoroError> {
+        let gap = AudioDuration::from_millis(u64::from(opts.gap_ms)).to_samples(SAMPLE_RATE);
+
+        let mut rendered = Vec::with_capacity(lines.len());
+        let mut timeline = Vec::with_capacity(lines.len());
+        let mut cursor = 0;
+
+        for (idx, line) in lines.iter().enumerate() {
+            // At the model rate, whatever the speaker's options say
+            let audio = self
+                .synthesize_text(&line.text, &opts.line_options(line))
+                .map_err(|e| {
+                    let message =
+                        format!("Dialogue line {} ({}): {}", idx + 1, line.speaker_voice, e);
+                    e.with_message(message)
+                })?;
+
+            if idx > 0 {
+                cursor += gap;
+            }
+            let start = cursor;
+            cursor += audio.len();
+
+            timeline.push(DialogueSpan {
+                line: idx,
+                speaker: line.speaker_voice.clone(),
+                start_sample: start,
+                end_sample: cursor,
+                start_ms: samples_to_ms(start),
+                end_ms: samples_to_ms(cursor),
+            });
+            rendered.push(audio);
+        }
+
+        let total = cursor;
+        let mut tracks = DialogueTracks {
+            timeline,
+            ..Default::default()
+        };
+
+        if output != DialogueOutput::Stems {
+            let mut combined = vec![0.0; total];
+            for (span, audio) in tracks.timeline.iter().zip(&rendered) {
+                combined[span.start_sample..span.end_sample].copy_from_slice(audio);
+            }
+            tracks.combined = Some(combined);
+        }
+
+        if output != DialogueOutput::Combined {
+            for (span, audio) in tracks.timeline.iter().zip(&rendered) {
+                let stem = tracks
+                    .stems
+                    .entry(span.speaker.clone())
+                    .or_insert_with(|| vec![0.0; total]);
+                stem[span.start_sample..span.end_sample].copy_from_slice(audio);
+            }
+        }
+
+        Ok(tracks)
+    }
+
+    /// Write `combined.wav`, `stem_<speaker>.wav` files and `timeline.json` into `dir`
+    ///
+    /// # Example
+    /// ```
+    /// use kokoro_tiny::dialogue::DialogueTracks;
+    /// use kokoro_tiny::TtsEngine;
+    ///
+    /// let tts = TtsEngine::without_model();
+    /// let tracks = DialogueTracks {
+    ///     combined: Some(vec![0.0; 2400]),
+    ///     ..Default::default()
+    /// };
+    /// let dir = std::env::temp_dir().join("kokoro_doc_dialogue");
+    /// tts.save_dialogue(dir.to_str().unwrap(), &tracks)?;
+    /// assert!(dir.join("combined.wav").exists());
+    /// assert!(dir.join("timeline.json").exists());
+    /// # Ok::<(), kokoro_tiny::KokoroError>(())
+    /// ```
+    pub fn save_dialogue(&self, dir: &str, tracks: &DialogueTracks) -> Result<(), KokoroError> {
+        let dir = Path::new(dir);
+        fs::create_dir_all(dir)?;
+
+        if let Some(combined) = &tracks.combined {
+            self.save_wav(&path_str(&dir.join("combined.wav"))?, combined)?;
+        }
+
+        for (speaker, stem) in &tracks.stems {
+            let file = format!("stem_{}.wav", sanitize_speaker(speaker));
+            self.save_wav(&path_str(&dir.join(file))?, stem)?;
+        }
+
+        let timeline = serde_json::to_string_pretty(&tracks.timeline)
+            .map_err(|e| KokoroError::Other(format!("Failed to serialize timeline: {}", e)))?;
+        fs::write(dir.join("timeline.json"), timeline)?;
+
+        Ok(())
+    }
+}
+
+fn samples_to_ms(samples: usize) -> u64 {
+    AudioDuration::from_samples(samples, SAMPLE_RATE).as_millis()
+}
+
+fn path_str(path: &Path) -> Result<String, KokoroError> {
+    path.to_str()
+        .map(|p| p.to_string())
+        .ok_or_else(|| KokoroError::InvalidInput(format!("Invalid output path: {}", path.display())))
+}
+
+// Voice mixes like "af_sky.5+af_bella.5" aren't friendly file names
+fn sanitize_speaker(speaker: &str) -> String {
+    speaker
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '_' { c } else { '-' })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    fn dialogue_engine() -> TtsEngine {
+        let mut voices = HashMap::new();
+        voices.insert("af_sky".to_string(), vec![0.1; 256]);
+        voices.insert("am_adam".to_string(), vec![0.2; 256]);
+        let mut engine = TtsEngine::with_test_voices(voices);
+        // Voice-dependent constant output so stems are easy to tell apart
+        engine.mock_inference = Some(Arc::new(|tokens: &[i64], style: &[f32], _speed: f32| {
+            Ok(vec![style[0] + 0.1; tokens.len() * 50])
+        }));
+        engine
+    }
+
+    fn script() -> Vec<DialogueLine> {
+        vec![
+            DialogueLine::new("af_sky", "Hello there."),
+            DialogueLine::new("am_adam", "Hi! How are you doing today?"),
+            DialogueLine::new("af_sky", "Great, thanks."),
+        ]
+    }
+
+    #[test]
+    fn stems_sum_to_combined_track() {
+        let engine = dialogue_engine();
+        let tracks = engine
+            .synthesize_dialogue_tracks(&script(), DialogueOutput::Both)
+            .unwrap();
+
+        let combined = tracks.combined.as_ref().unwrap();
+        assert_eq!(tracks.stems.len(), 2);
+
+        for (i, &sample) in combined.iter().enumerate() {
+            let summed: f32 = tracks.stems.values().map(|stem| stem[i]).sum();
+            assert!((summed - sample).abs() < 1e-6, "mismatch at sample {}", i);
+        }
+    }
+
+    #[test]
+    fn timeline_matches_stem_regions() {
+        let engine = dialogue_engine();
+        let tracks = engine
+            .synthesize_dialogue_tracks(&script(), DialogueOutput::Stems)
+            .unwrap();
+        assert!(tracks.combined.is_none());
+
+        let gap = AudioDuration::from_millis(DIALOGUE_GAP_MS.into()).to_samples(SAMPLE_RATE);
+        for (i, span) in tracks.timeline.iter().enumerate() {
+            if i > 0 {
+                assert_eq!(span.start_sample, tracks.timeline[i - 1].end_sample + gap);
+            }
+
+            let stem = &tracks.stems[&span.speaker];
+            // Nonzero from the edge fades in
+            assert!(stem[span.start_sample + 1..span.end_sample - 1]
+                .iter()
+                .all(|&s| s != 0.0));
+            if span.start_sample > 0 {
+                assert_eq!(stem[span.start_sample - 1], 0.0);
+            }
+            if span.end_sample < stem.len() {
+                assert_eq!(stem[span.end_sample], 0.0);
+            }
+        }
+    }
+
+    #[test]
+    fn save_dialogue_writes_stems_and_timeline() {
+        let engine = dialogue_engine();
+        let tracks = engine
+            .synthesize_dialogue_tracks(&script(), DialogueOutput::Both)
+            .unwrap();
+
+        let dir = std::env::temp_dir().join(format!("kokoro_dialogue_{}", std::process::id()));
+        engine.save_dialogue(dir.to_str().unwrap(), &tracks).unwrap();
+
+        assert!(dir.join("combined.wav").exists());
+        assert!(dir.join("stem_af_sky.wav").exists());
+        assert!(dir.join("stem_am_adam.wav").exists());
+
+        let timeline: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(dir.join("timeline.json")).unwrap()).unwrap();
+        assert_eq!(timeline.as_array().unwrap().len(), 3);
+        assert_eq!(timeline[1]["speaker"], "am_adam");
+
+        fs::remove_dir_all(dir).ok();
+    }
+
+    #[test]
+    fn scripts_parse_into_lines() {
+        let lines = parse_script(
+            "# Scene one\naf_sky: Where were you?\n\nam_adam:  Out. Time: late,\n  very late.\n\
+             af_sky.5+am_adam.5: Together now.",
+        )
+        .unwrap();
+        let parsed: Vec<(&str, &str)> = lines
+            .iter()
+            .map(|line| (line.speaker_voice.as_str(), line.text.as_str()))
+            .collect();
+        assert_eq!(
+            parsed,
+            [
+                ("af_sky", "Where were you?"),
+                ("am_adam", "Out. Time: late, very late."),
+                ("af_sky.5+am_adam.5", "Together now."),
+            ]
+        );
+
+        assert!(parse_script("Nobody is speaking here.").is_err());
+    }
+
+    #[test]
+    fn lines_add_up_with_gaps_and_voices_switch_at_boundaries() {
+        let engine = dialogue_engine();
+        let opts = DialogueOptions::new()
+            .gap_ms(100)
+            .speaker("af_sky", SynthesizeOptions::new().gain(0.5));
+        let lines = script();
+        let audio = engine.synthesize_dialogue_with(&lines, &opts).unwrap();
+
+        let parts: Vec<Vec<f32>> = lines
+            .iter()
+            .map(|line| {
+                engine
+                    .synthesize_with(&line.text, opts.line_options(line))
+                    .unwrap()
+            })
+            .collect();
+        let gap = AudioDuration::from_millis(100).to_samples(SAMPLE_RATE);
+        let expected: usize = parts.iter().map(Vec::len).sum::<usize>() + 2 * gap;
+        assert_eq!(audio.len(), expected);
+
+        // Each line is entirely its speaker's, af_sky at half gain
+        let mut start = 0;
+        for (line, part) in lines.iter().zip(&parts) {
+            let level = if line.speaker_voice == "af_sky" { 0.1 } else { 0.3 };
+            // Past the edge fades
+            let spoken = &audio[start + 120..start + part.len() - 120];
+            assert!(
+                spoken.iter().all(|&s| (s - level).abs() < 1e-6),
+                "{}",
+                line.text
+            );
+            start += part.len();
+            if start < audio.len() {
+                assert!(audio[start..start + gap].iter().all(|&s| s == 0.0));
+                start += gap;
+            }
+        }
+    }
+}
@@ -0,0 +1,365 @@
+//! Fetching the model files: mirror lists, timeouts and retries
+//!
+//! Each file has an ordered list of URLs: `EngineBuilder::model_urls` /
+//! `voices_urls`, else `KOKORO_MODEL_URL` / `KOKORO_VOICES_URL` (one URL or
+//! several, separated by commas or whitespace), else the default. They are
+//! tried in turn. Transient failures (connection errors, HTTP 429 and 5xx, no
+//! data for `RetryPolicy::stall_timeout`, a download that fails its checksum)
+//! are retried on the same URL, `RetryPolicy::attempts_per_url` times in all,
+//! with a doubling `RetryPolicy::attempt_delay` in between; anything else
+//! moves straight on to the next URL. If every URL fails, the error names
+//! each one and why.
+//!
+//! All of this is one attempt as far as the backoff in `retry` is concerned.
+
+use std::fs::{self, File};
+use std::io::Write;
+use std::time::Duration;
+
+use crate::integrity::{self, FileDigest};
+use crate::retry::{self, RetryPolicy};
+use crate::{EngineBuilder, KokoroError};
+
+/// Where one file comes from
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct ModelSource {
+    pub(crate) urls: Vec<String>,
+    /// Only for the default URL: a custom source may serve another build
+    pub(crate) digest: Option<FileDigest>,
+}
+
+/// Sources for the model and voices files
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Sources {
+    pub(crate) model: ModelSource,
+    pub(crate) voices: ModelSource,
+}
+
+impl EngineBuilder {
+    // The builder's URLs, else the environment's, else the defaults
+    pub(crate) fn sources(&self) -> Sources {
+        let source = |urls: &Option<Vec<String>>, var, default: &str, digest| match urls
+            .clone()
+            .or_else(|| urls_from_env(var))
+        {
+            Some(urls) => ModelSource { urls, digest: None },
+            None => ModelSource {
+                urls: vec![default.to_string()],
+                digest,
+            },
+        };
+        Sources {
+            model: source(
+                &self.model_urls,
+                "KOKORO_MODEL_URL",
+                self.variant.url(),
+                self.variant.digest(),
+            ),
+            voices: source(
+                &self.voices_urls,
+                "KOKORO_VOICES_URL",
+                self.voice_pack.url(),
+                self.voice_pack.digest(),
+            ),
+        }
+    }
+}
+
+// A list of URLs from the environment; None when unset or empty
+fn urls_from_env(var: &str) -> Option<Vec<String>> {
+    std::env::var(var).ok().and_then(|value| split_urls(&value))
+}
+
+fn split_urls(value: &str) -> Option<Vec<String>> {
+    let urls: Vec<String> = value
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|url| !url.is_empty())
+        .map(str::to_string)
+        .collect();
+    (!urls.is_empty()).then_some(urls)
+}
+
+/// Download whichever of the two files are missing (see `retry::ensure_models`)
+pub(crate) async fn fetch_model_files(
+    model_path: &str,
+    voices_path: &str,
+    sources: &Sources,
+    policy: &RetryPolicy,
+) -> Result<(), KokoroError> {
+    let (model_label, voices_label) = (
+        sources.model.urls.join(", "),
+        sources.voices.urls.join(", "),
+    );
+    let files = [
+        (model_label.as_str(), model_path),
+        (voices_label.as_str(), voices_path),
+    ];
+    retry::ensure_models(
+        &files,
+        policy,
+        std::time::SystemTime::now(),
+        |_, path| async move {
+            #[cfg(not(feature = "as-lib"))]
+            let int8 = sources.model.urls[0] == crate::ModelVariant::Int8.url();
+            if path == model_path && int8 {
+                eprintln!("   📥 Downloading model (int8, 88MB)...");
+            } else if path == model_path {
+                eprintln!("   📥 Downloading model (310MB)...");
+            } else {
+                eprintln!("   📥 Downloading voices (27MB)...");
+            }
+            let source = if path == model_path {
+                &sources.model
+            } else {
+                &sources.voices
+            };
+            fetch(source, &path, policy).await
+        },
+    )
+    .await
+}
+
+// One failed attempt, and whether trying the same URL again could help
+struct Failure {
+    reason: String,
+    transient: bool,
+}
+
+impl Failure {
+    fn transient(reason: impl ToString) -> Self {
+        Failure {
+            reason: reason.to_string(),
+            transient: true,
+        }
+    }
+}
+
+impl From<reqwest::Error> for Failure {
+    fn from(e: reqwest::Error) -> Self {
+        let transient = match e.status() {
+            Some(status) => status.as_u16() == 429 || status.is_server_error(),
+            None => true, // Connection, timeout or body errors
+        };
+        Failure {
+            reason: e.to_string(),
+            transient,
+        }
+    }
+}
+
+impl From<std::io::Error> for Failure {
+    fn from(e: std::io::Error) -> Self {
+        // The disk, not the server: another URL won't fix it either, but the
+        // caller sees the reason
+        Failure {
+            reason: e.to_string(),
+            transient: false,
+        }
+    }
+}
+
+// Every URL in turn until one works; the error lists them all
+async fn fetch(source: &ModelSource, path: &str, policy: &RetryPolicy) -> Result<(), String> {
+    let attempts = policy.attempts_per_url.max(1);
+    let mut failures: Vec<(&str, String)> = Vec::new();
+    for url in &source.urls {
+        let mut delay = policy.attempt_delay;
+        for attempt in 1..=attempts {
+            let failure = match fetch_once(url, path, policy.stall_timeout).await {
+                Ok(()) => match integrity::verify_download(path, source.digest) {
+                    Ok(()) => return Ok(()),
+                    Err(reason) => Failure::transient(reason),
+                },
+                Err(failure) => failure,
+            };
+            if !failure.transient || attempt == attempts {
+                let tries = if attempt > 1 {
+                    format!(" (after {} attempts)", attempt)
+                } else {
+                    String::new()
+                };
+                failures.push((url, format!("{}{}", failure.reason, tries)));
+                break;
+            }
+            #[cfg(not(feature = "as-lib"))]
+            eprintln!(
+                "   ⚠️  {} ({}), retrying in {:?}",
+                url, failure.reason, delay
+            );
+            tokio::time::sleep(delay).await;
+            delay = delay.saturating_mul(2);
+        }
+    }
+    // A single URL is already in the DownloadFailed error
+    if let [(_, reason)] = failures.as_slice() {
+        return Err(reason.clone());
+    }
+    let failures: Vec<String> = failures
+        .iter()
+        .map(|(url, reason)| format!("{}: {}", url, reason))
+        .collect();
+    Err(format!("every source failed: {}", failures.join("; ")))
+}
+
+// Stream one URL to `<path>.part`, renamed once complete, so the model is
+// never held in memory and a cut download never lands at `path`
+async fn fetch_once(url: &str, path: &str, stall_timeout: Duration) -> Result<(), Failure> {
+    let stalled = || Failure::transient(format!("no data for {:?}", stall_timeout));
+    let response = tokio::time::timeout(stall_timeout, reqwest::get(url))
+        .await
+        .map_err(|_| stalled())??;
+    let mut response = response.error_for_status()?;
+
+    let partial = format!("{}.part", path);
+    let mut file = File::create(&partial)?;
+    while let Some(chunk) = tokio::time::timeout(stall_timeout, response.chunk())
+        .await
+        .map_err(|_| stalled())??
+    {
+        file.write_all(&chunk)?;
+    }
+    file.sync_all()?;
+    fs::rename(&partial, path)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::integrity::MODEL_DIGEST;
+    use crate::model_variant::ModelVariant;
+    use crate::MODEL_URL;
+    use std::io::{BufRead, BufReader};
+    use std::net::TcpListener;
+    use std::path::Path;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::thread;
+
+    // Answers each request with the next status in `script` (the last one
+    // repeats); 200s carry `body`. Returns the base URL and a request count.
+    fn server(script: &'static [u16], body: &'static str) -> (String, Arc<AtomicUsize>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(AtomicUsize::new(0));
+        let counter = requests.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut line = String::new();
+                while reader.read_line(&mut line).unwrap() > 2 {
+                    line.clear();
+                }
+                let n = counter.fetch_add(1, Ordering::SeqCst);
+                let status = script[n.min(script.len() - 1)];
+                let body = if status == 200 { body } else { "" };
+                let _ = write!(
+                    stream,
+                    "HTTP/1.1 {} X\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                );
+            }
+        });
+        (url, requests)
+    }
+
+    fn policy() -> RetryPolicy {
+        RetryPolicy {
+            attempts_per_url: 3,
+            attempt_delay: Duration::from_millis(5),
+            stall_timeout: Duration::from_secs(5),
+            ..RetryPolicy::default()
+        }
+    }
+
+    fn target(name: &str) -> String {
+        let dir = std::env::temp_dir().join(format!("kokoro-download-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(name);
+        let _ = fs::remove_file(&path);
+        path.to_str().unwrap().to_string()
+    }
+
+    #[tokio::test]
+    async fn mirrors_are_tried_in_order_with_retries_for_transient_errors() {
+        let (missing, missing_hits) = server(&[404], "");
+        let (flaky, flaky_hits) = server(&[503, 200], "model bytes");
+        let source = ModelSource {
+            urls: vec![format!("{}/0.onnx", missing), format!("{}/0.onnx", flaky)],
+            digest: None,
+        };
+        let path = target("mirrored.onnx");
+        fetch(&source, &path, &policy()).await.unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "model bytes");
+        assert!(!Path::new(&format!("{}.part", path)).exists());
+        // A 404 isn't retried; the 503 is
+        assert_eq!(missing_hits.load(Ordering::SeqCst), 1);
+        assert_eq!(flaky_hits.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn the_error_lists_every_url() {
+        let (missing, _) = server(&[404], "");
+        let (down, down_hits) = server(&[502], "");
+        let (wrong, _) = server(&[200], "not the model");
+        let source = ModelSource {
+            urls: vec![missing.clone(), down.clone(), wrong.clone()],
+            digest: Some(FileDigest {
+                size: 9,
+                sha256: "0000000000000000000000000000000000000000000000000000000000000000",
+            }),
+        };
+        let path = target("failing.onnx");
+        let message = fetch(&source, &path, &policy()).await.unwrap_err();
+
+        assert!(message.starts_with("every source failed"), "{}", message);
+        for url in [&missing, &down, &wrong] {
+            assert!(message.contains(url.as_str()), "{}", message);
+        }
+        assert!(message.contains("404"), "{}", message);
+        assert!(message.contains("after 3 attempts"), "{}", message);
+        assert!(message.contains("13 bytes, expected 9"), "{}", message);
+        assert_eq!(down_hits.load(Ordering::SeqCst), 3);
+        assert!(!Path::new(&path).exists());
+    }
+
+    #[test]
+    fn urls_come_from_the_builder_then_the_environment() {
+        assert_eq!(
+            split_urls("https://a/0.onnx, https://b/0.onnx\nhttps://c/0.onnx"),
+            Some(vec![
+                "https://a/0.onnx".to_string(),
+                "https://b/0.onnx".to_string(),
+                "https://c/0.onnx".to_string(),
+            ])
+        );
+        assert_eq!(split_urls(" , "), None);
+
+        // KOKORO_*_URL aren't set under `cargo test`
+        let sources = EngineBuilder::default().sources();
+        assert_eq!(sources.model.urls, [MODEL_URL]);
+        assert_eq!(sources.model.digest, Some(MODEL_DIGEST));
+        assert!(sources.voices.digest.is_some());
+
+        let sources = EngineBuilder::default()
+            .model_urls(&["https://artifacts.internal/kokoro/0.onnx"])
+            .sources();
+        assert_eq!(
+            sources.model.urls,
+            ["https://artifacts.internal/kokoro/0.onnx"]
+        );
+        assert_eq!(sources.model.digest, None);
+        assert!(sources.voices.digest.is_some());
+
+        let sources = EngineBuilder::default()
+            .variant(ModelVariant::Int8)
+            .sources();
+        assert_eq!(sources.model.urls, [ModelVariant::Int8.url()]);
+        assert_eq!(sources.voices, EngineBuilder::default().sources().voices);
+    }
+}
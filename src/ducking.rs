@@ -0,0 +1,723 @@
+//! Lowering other audio while speaking (requires 'ducking' feature)
+//!
+//! The system output volume is read, set to a fraction of itself, and set
+//! back to exactly what it was when the [`DuckGuard`] drops, whatever the
+//! user did with the volume meanwhile and whatever step size the OS uses.
+//! Where the platform has a level per channel, each one is saved and set
+//! back, so the balance survives too. Each platform goes through its own
+//! volume API:
+//!
+//! - Linux: PulseAudio or PipeWire, through `pactl` (or PipeWire's `wpctl`)
+//! - macOS: the default output device's channel volumes, or AudioToolbox's
+//!   virtual main volume where the device has no per-channel control
+//! - Windows: WASAPI's `IAudioEndpointVolume` on the default render endpoint
+//!
+//! Where none of them answers, volume-down key presses are the last resort,
+//! undone with as many volume-up presses; that can't know the step size, so
+//! the volume may not come back exactly.
+
+use std::thread;
+use std::time::Duration;
+
+use enigo::{Enigo, Key, Keyboard, Settings};
+
+use crate::KokoroError;
+
+/// Output volume of the default device, 0.0 to 1.0 (above 1.0 where the
+/// platform allows boosting)
+pub trait SystemVolume: Send + Sync {
+    fn volume(&self) -> Result<f32, String>;
+
+    fn set_volume(&self, volume: f32) -> Result<(), String>;
+
+    /// The level of each channel; by default the one overall volume
+    fn channel_volumes(&self) -> Result<Vec<f32>, String> {
+        Ok(vec![self.volume()?])
+    }
+
+    /// Set each channel, as `channel_volumes` listed them; by default the
+    /// overall volume to the loudest
+    fn set_channel_volumes(&self, volumes: &[f32]) -> Result<(), String> {
+        self.set_volume(volumes.iter().copied().fold(0.0, f32::max))
+    }
+}
+
+/// The platform's volume API, if this system has one that answers
+pub fn system_volume() -> Option<Box<dyn SystemVolume>> {
+    platform::backends()
+        .into_iter()
+        .find(|backend| backend.volume().is_ok())
+}
+
+/// Other audio stays ducked until this is dropped
+///
+/// For keeping several clips under one duck, e.g. streamed chunks, instead of
+/// ducking and restoring around each one. Dropping restores the volume even
+/// when playback failed in between.
+pub struct DuckGuard {
+    restore: Restore,
+}
+
+enum Restore {
+    // The channel volumes before ducking, set again on drop
+    Saved {
+        backend: Box<dyn SystemVolume>,
+        volumes: Vec<f32>,
+    },
+    // Volume-up presses to undo the volume-down ones
+    Keys {
+        steps: u32,
+    },
+}
+
+/// Duck other audio to `level` (0.0 = mute, 1.0 = no change) until the guard drops
+///
+/// # Example
+/// ```no_run
+/// use kokoro_tiny::{duck_audio, TtsEngine};
+///
+/// # async fn demo() -> Result<(), kokoro_tiny::KokoroError> {
+/// let tts = TtsEngine::new().await?;
+/// let audio = tts.synthesize("Meeting in five minutes", None, None, None)?;
+/// let duck = duck_audio(0.3)?;
+/// tts.play(&audio, 0.8)?;
+/// drop(duck); // back to the volume from before
+/// # Ok(())
+/// # }
+/// ```
+pub fn duck_audio(level: f32) -> Result<DuckGuard, KokoroError> {
+    let guard = match system_volume() {
+        Some(backend) => duck_with(backend, level),
+        None => duck_with_keys(level),
+    }
+    .map_err(KokoroError::Playback)?;
+    // Small delay to let the ducking take effect
+    thread::sleep(Duration::from_millis(50));
+    Ok(guard)
+}
+
+/// Duck through `backend`, for a volume API of your own (or a mock in tests)
+///
+/// # Example
+/// ```
+/// use kokoro_tiny::ducking::{duck_with, SystemVolume};
+/// use std::sync::{Arc, Mutex};
+///
+/// #[derive(Clone, Default)]
+/// struct Mixer(Arc<Mutex<f32>>);
+///
+/// impl SystemVolume for Mixer {
+///     fn volume(&self) -> Result<f32, String> {
+///         Ok(*self.0.lock().unwrap())
+///     }
+///     fn set_volume(&self, volume: f32) -> Result<(), String> {
+///         *self.0.lock().unwrap() = volume;
+///         Ok(())
+///     }
+/// }
+///
+/// let mixer = Mixer::default();
+/// mixer.set_volume(0.6)?;
+/// let duck = duck_with(Box::new(mixer.clone()), 0.5)?;
+/// assert_eq!(mixer.volume()?, 0.3);
+/// drop(duck);
+/// assert_eq!(mixer.volume()?, 0.6);
+/// # Ok::<(), String>(())
+/// ```
+pub fn duck_with(backend: Box<dyn SystemVolume>, level: f32) -> Result<DuckGuard, String> {
+    let volumes = backend.channel_volumes()?;
+    let level = level.clamp(0.0, 1.0);
+    let ducked: Vec<f32> = volumes.iter().map(|volume| volume * level).collect();
+    backend.set_channel_volumes(&ducked)?;
+    Ok(DuckGuard {
+        restore: Restore::Saved { backend, volumes },
+    })
+}
+
+fn duck_with_keys(level: f32) -> Result<DuckGuard, String> {
+    // Assuming each press is ~6% volume change on most systems
+    let steps = ((1.0 - level.clamp(0.0, 1.0)) * 16.0) as u32; // 16 steps = ~100% volume range
+    press(Key::VolumeDown, steps)?;
+    Ok(DuckGuard {
+        restore: Restore::Keys { steps },
+    })
+}
+
+impl DuckGuard {
+    /// Set the volume back now, reporting a failure that dropping would
+    /// only print
+    pub fn restore(mut self) -> Result<(), String> {
+        let result = self.restore_now();
+        std::mem::forget(self);
+        result
+    }
+
+    fn restore_now(&mut self) -> Result<(), String> {
+        match &self.restore {
+            Restore::Saved { backend, volumes } => backend.set_channel_volumes(volumes),
+            Restore::Keys { steps } => press(Key::VolumeUp, *steps),
+        }
+    }
+}
+
+impl Drop for DuckGuard {
+    fn drop(&mut self) {
+        if let Err(_e) = self.restore_now() {
+            #[cfg(not(feature = "as-lib"))]
+            eprintln!("⚠️ Failed to restore audio volume: {}", _e);
+        }
+    }
+}
+
+fn press(key: Key, times: u32) -> Result<(), String> {
+    let mut enigo = Enigo::new(&Settings::default())
+        .map_err(|e| format!("Failed to initialize Enigo: {:?}", e))?;
+    for _ in 0..times {
+        enigo
+            .key(key, enigo::Direction::Click)
+            .map_err(|e| format!("Failed to press {:?}: {:?}", key, e))?;
+        thread::sleep(Duration::from_millis(20)); // Small delay between key presses
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use std::process::Command;
+
+    use super::SystemVolume;
+
+    // PulseAudio's 100%
+    const PA_VOLUME_NORM: f32 = 65536.0;
+
+    pub fn backends() -> Vec<Box<dyn SystemVolume>> {
+        vec![Box::new(Pactl), Box::new(Wpctl)]
+    }
+
+    /// PulseAudio, or PipeWire through pipewire-pulse
+    struct Pactl;
+
+    impl SystemVolume for Pactl {
+        // PulseAudio's own overall volume is its loudest channel
+        fn volume(&self) -> Result<f32, String> {
+            Ok(self.channel_volumes()?.into_iter().fold(0.0, f32::max))
+        }
+
+        fn set_volume(&self, volume: f32) -> Result<(), String> {
+            self.set_channel_volumes(&[volume])
+        }
+
+        fn channel_volumes(&self) -> Result<Vec<f32>, String> {
+            let out = run("pactl", &["get-sink-volume", "@DEFAULT_SINK@"])?;
+            parse_pactl(&out).ok_or_else(|| format!("Unexpected pactl output: {}", out.trim()))
+        }
+
+        // Raw volumes, one per channel (or one for all), so saved levels are
+        // set back without rounding
+        fn set_channel_volumes(&self, volumes: &[f32]) -> Result<(), String> {
+            let raw: Vec<String> = volumes
+                .iter()
+                .map(|volume| ((volume.max(0.0) * PA_VOLUME_NORM).round() as u32).to_string())
+                .collect();
+            let mut args = vec!["set-sink-volume", "@DEFAULT_SINK@"];
+            args.extend(raw.iter().map(String::as_str));
+            run("pactl", &args).map(|_| ())
+        }
+    }
+
+    /// PipeWire without the PulseAudio layer
+    struct Wpctl;
+
+    impl SystemVolume for Wpctl {
+        fn volume(&self) -> Result<f32, String> {
+            let out = run("wpctl", &["get-volume", "@DEFAULT_AUDIO_SINK@"])?;
+            parse_wpctl(&out).ok_or_else(|| format!("Unexpected wpctl output: {}", out.trim()))
+        }
+
+        fn set_volume(&self, volume: f32) -> Result<(), String> {
+            let volume = format!("{:.4}", volume.max(0.0));
+            run("wpctl", &["set-volume", "@DEFAULT_AUDIO_SINK@", &volume]).map(|_| ())
+        }
+    }
+
+    fn run(program: &str, args: &[&str]) -> Result<String, String> {
+        let output = Command::new(program)
+            .args(args)
+            .output()
+            .map_err(|e| format!("Failed to run {}: {}", program, e))?;
+        if !output.status.success() {
+            return Err(format!(
+                "{} failed: {}",
+                program,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+
+    // "Volume: front-left: 26214 /  40% / -23.88 dB,   front-right: ..."; each
+    // channel's raw value, in order
+    pub(super) fn parse_pactl(out: &str) -> Option<Vec<f32>> {
+        let line = out
+            .lines()
+            .find_map(|line| line.trim().strip_prefix("Volume:"))?;
+        line.split(',')
+            .map(|channel| {
+                let raw = channel.split('/').next()?.rsplit(':').next()?;
+                let raw: u32 = raw.trim().parse().ok()?;
+                Some(raw as f32 / PA_VOLUME_NORM)
+            })
+            .collect()
+    }
+
+    // "Volume: 0.40", with " [MUTED]" after it when muted
+    pub(super) fn parse_wpctl(out: &str) -> Option<f32> {
+        out.trim()
+            .strip_prefix("Volume:")?
+            .split_whitespace()
+            .next()?
+            .parse()
+            .ok()
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use std::ffi::c_void;
+    use std::mem::size_of;
+    use std::ptr;
+
+    use super::SystemVolume;
+
+    // From CoreAudio's AudioHardware.h and AudioHardwareBase.h
+    #[repr(C)]
+    struct AudioObjectPropertyAddress {
+        selector: u32,
+        scope: u32,
+        element: u32,
+    }
+
+    const SYSTEM_OBJECT: u32 = 1;
+    const DEFAULT_OUTPUT_DEVICE: u32 = u32::from_be_bytes(*b"dOut");
+    const STEREO_CHANNELS: u32 = u32::from_be_bytes(*b"dch2");
+    const VOLUME_SCALAR: u32 = u32::from_be_bytes(*b"volm");
+    const SCOPE_GLOBAL: u32 = u32::from_be_bytes(*b"glob");
+    const SCOPE_OUTPUT: u32 = u32::from_be_bytes(*b"outp");
+    const ELEMENT_MAIN: u32 = 0;
+    // AudioToolbox's AudioHardwareService.h; not an AudioObject property
+    const VIRTUAL_MAIN_VOLUME: u32 = u32::from_be_bytes(*b"vmvc");
+
+    // AudioObjectGetPropertyData and AudioHardwareServiceGetPropertyData
+    type GetProperty = unsafe extern "C" fn(
+        u32,
+        *const AudioObjectPropertyAddress,
+        u32,
+        *const c_void,
+        *mut u32,
+        *mut c_void,
+    ) -> i32;
+
+    // AudioObjectSetPropertyData and AudioHardwareServiceSetPropertyData
+    type SetProperty = unsafe extern "C" fn(
+        u32,
+        *const AudioObjectPropertyAddress,
+        u32,
+        *const c_void,
+        u32,
+        *const c_void,
+    ) -> i32;
+
+    #[link(name = "CoreAudio", kind = "framework")]
+    extern "C" {
+        fn AudioObjectGetPropertyData(
+            object: u32,
+            address: *const AudioObjectPropertyAddress,
+            qualifier_size: u32,
+            qualifier: *const c_void,
+            data_size: *mut u32,
+            data: *mut c_void,
+        ) -> i32;
+
+        fn AudioObjectSetPropertyData(
+            object: u32,
+            address: *const AudioObjectPropertyAddress,
+            qualifier_size: u32,
+            qualifier: *const c_void,
+            data_size: u32,
+            data: *const c_void,
+        ) -> i32;
+    }
+
+    #[link(name = "AudioToolbox", kind = "framework")]
+    extern "C" {
+        fn AudioHardwareServiceGetPropertyData(
+            object: u32,
+            address: *const AudioObjectPropertyAddress,
+            qualifier_size: u32,
+            qualifier: *const c_void,
+            data_size: *mut u32,
+            data: *mut c_void,
+        ) -> i32;
+
+        fn AudioHardwareServiceSetPropertyData(
+            object: u32,
+            address: *const AudioObjectPropertyAddress,
+            qualifier_size: u32,
+            qualifier: *const c_void,
+            data_size: u32,
+            data: *const c_void,
+        ) -> i32;
+    }
+
+    pub fn backends() -> Vec<Box<dyn SystemVolume>> {
+        vec![Box::new(CoreAudio)]
+    }
+
+    /// Volume of the default output device
+    ///
+    /// Each stereo channel's own volume control where the device has them,
+    /// else the virtual main volume, which AudioToolbox spreads over every
+    /// channel.
+    struct CoreAudio;
+
+    impl SystemVolume for CoreAudio {
+        fn volume(&self) -> Result<f32, String> {
+            let device = default_output()?;
+            get(
+                AudioHardwareServiceGetPropertyData,
+                device,
+                VIRTUAL_MAIN_VOLUME,
+                SCOPE_OUTPUT,
+                ELEMENT_MAIN,
+            )
+            .or_else(|_| Ok(channel_volumes(device)?.into_iter().fold(0.0, f32::max)))
+        }
+
+        fn set_volume(&self, volume: f32) -> Result<(), String> {
+            let device = default_output()?;
+            let volume = volume.clamp(0.0, 1.0);
+            set(
+                AudioHardwareServiceSetPropertyData,
+                device,
+                VIRTUAL_MAIN_VOLUME,
+                ELEMENT_MAIN,
+                volume,
+            )
+            .or_else(|_| set_channel_volumes(device, &[volume; 2]))
+        }
+
+        fn channel_volumes(&self) -> Result<Vec<f32>, String> {
+            let device = default_output()?;
+            channel_volumes(device).or_else(|_| Ok(vec![self.volume()?]))
+        }
+
+        fn set_channel_volumes(&self, volumes: &[f32]) -> Result<(), String> {
+            match volumes {
+                [_, _] => set_channel_volumes(default_output()?, volumes),
+                _ => self.set_volume(volumes.iter().copied().fold(0.0, f32::max)),
+            }
+        }
+    }
+
+    fn default_output() -> Result<u32, String> {
+        get(
+            AudioObjectGetPropertyData,
+            SYSTEM_OBJECT,
+            DEFAULT_OUTPUT_DEVICE,
+            SCOPE_GLOBAL,
+            ELEMENT_MAIN,
+        )
+    }
+
+    // Left and right volume, from the device's own per-channel controls
+    fn channel_volumes(device: u32) -> Result<Vec<f32>, String> {
+        stereo_channels(device)?
+            .iter()
+            .map(|&channel| {
+                get(
+                    AudioObjectGetPropertyData,
+                    device,
+                    VOLUME_SCALAR,
+                    SCOPE_OUTPUT,
+                    channel,
+                )
+            })
+            .collect()
+    }
+
+    fn set_channel_volumes(device: u32, volumes: &[f32]) -> Result<(), String> {
+        for (&channel, &volume) in stereo_channels(device)?.iter().zip(volumes) {
+            set(
+                AudioObjectSetPropertyData,
+                device,
+                VOLUME_SCALAR,
+                channel,
+                volume.clamp(0.0, 1.0),
+            )?;
+        }
+        Ok(())
+    }
+
+    // The elements left and right play on
+    fn stereo_channels(device: u32) -> Result<[u32; 2], String> {
+        get(
+            AudioObjectGetPropertyData,
+            device,
+            STEREO_CHANNELS,
+            SCOPE_OUTPUT,
+            ELEMENT_MAIN,
+        )
+    }
+
+    fn get<T: Default>(
+        api: GetProperty,
+        object: u32,
+        selector: u32,
+        scope: u32,
+        element: u32,
+    ) -> Result<T, String> {
+        let address = AudioObjectPropertyAddress {
+            selector,
+            scope,
+            element,
+        };
+        let mut value = T::default();
+        let mut size = size_of::<T>() as u32;
+        let status = unsafe {
+            api(
+                object,
+                &address,
+                0,
+                ptr::null(),
+                &mut size,
+                &mut value as *mut T as *mut c_void,
+            )
+        };
+        match status {
+            0 => Ok(value),
+            status => Err(format!("CoreAudio error {} reading the volume", status)),
+        }
+    }
+
+    fn set(
+        api: SetProperty,
+        device: u32,
+        selector: u32,
+        element: u32,
+        volume: f32,
+    ) -> Result<(), String> {
+        let address = AudioObjectPropertyAddress {
+            selector,
+            scope: SCOPE_OUTPUT,
+            element,
+        };
+        let status = unsafe {
+            api(
+                device,
+                &address,
+                0,
+                ptr::null(),
+                size_of::<f32>() as u32,
+                &volume as *const f32 as *const c_void,
+            )
+        };
+        match status {
+            0 => Ok(()),
+            status => Err(format!("CoreAudio error {} setting the volume", status)),
+        }
+    }
+}
+
+#[cfg(windows)]
+mod platform {
+    use windows::Win32::Media::Audio::Endpoints::IAudioEndpointVolume;
+    use windows::Win32::Media::Audio::{
+        eConsole, eRender, IMMDeviceEnumerator, MMDeviceEnumerator,
+    };
+    use windows::Win32::System::Com::{
+        CoCreateInstance, CoInitializeEx, CoUninitialize, CLSCTX_ALL, COINIT_MULTITHREADED,
+    };
+
+    use super::SystemVolume;
+
+    pub fn backends() -> Vec<Box<dyn SystemVolume>> {
+        vec![Box::new(Wasapi)]
+    }
+
+    /// Master volume of the default render endpoint
+    struct Wasapi;
+
+    impl SystemVolume for Wasapi {
+        fn volume(&self) -> Result<f32, String> {
+            with_endpoint(|endpoint| unsafe { endpoint.GetMasterVolumeLevelScalar() })
+        }
+
+        fn set_volume(&self, volume: f32) -> Result<(), String> {
+            let volume = volume.clamp(0.0, 1.0);
+            with_endpoint(|endpoint| unsafe {
+                endpoint.SetMasterVolumeLevelScalar(volume, std::ptr::null())
+            })
+        }
+    }
+
+    fn with_endpoint<T>(
+        f: impl FnOnce(&IAudioEndpointVolume) -> windows::core::Result<T>,
+    ) -> Result<T, String> {
+        unsafe {
+            // Fails harmlessly if this thread already joined another apartment
+            let initialized = CoInitializeEx(None, COINIT_MULTITHREADED).is_ok();
+            // Every COM object is released before CoUninitialize
+            let result = (|| {
+                let enumerator: IMMDeviceEnumerator =
+                    CoCreateInstance(&MMDeviceEnumerator, None, CLSCTX_ALL)?;
+                let device = enumerator.GetDefaultAudioEndpoint(eRender, eConsole)?;
+                let endpoint: IAudioEndpointVolume = device.Activate(CLSCTX_ALL, None)?;
+                f(&endpoint)
+            })();
+            if initialized {
+                CoUninitialize();
+            }
+            result.map_err(|e| format!("Windows audio endpoint: {}", e))
+        }
+    }
+}
+
+// Anywhere else only the key presses are left
+#[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+mod platform {
+    use super::SystemVolume;
+
+    pub fn backends() -> Vec<Box<dyn SystemVolume>> {
+        Vec::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    // A mixer the user can also turn while speech plays
+    #[derive(Clone)]
+    struct Mixer {
+        volume: Arc<Mutex<f32>>,
+        fail_sets: Arc<Mutex<u32>>,
+    }
+
+    impl Mixer {
+        fn at(volume: f32) -> Self {
+            Self {
+                volume: Arc::new(Mutex::new(volume)),
+                fail_sets: Arc::new(Mutex::new(0)),
+            }
+        }
+    }
+
+    impl SystemVolume for Mixer {
+        fn volume(&self) -> Result<f32, String> {
+            Ok(*self.volume.lock().unwrap())
+        }
+
+        fn set_volume(&self, volume: f32) -> Result<(), String> {
+            let mut fail = self.fail_sets.lock().unwrap();
+            if *fail > 0 {
+                *fail -= 1;
+                return Err("device went away".to_string());
+            }
+            *self.volume.lock().unwrap() = volume;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn restores_the_exact_volume_even_after_the_user_changed_it() {
+        let mixer = Mixer::at(0.73);
+        let duck = duck_with(Box::new(mixer.clone()), 0.3).unwrap();
+        assert!((mixer.volume().unwrap() - 0.219).abs() < 1e-6);
+
+        // The user turns it down further while speech plays
+        mixer.set_volume(0.05).unwrap();
+        drop(duck);
+        assert_eq!(mixer.volume().unwrap(), 0.73);
+
+        let duck = duck_with(Box::new(mixer.clone()), 0.5).unwrap();
+        duck.restore().unwrap();
+        assert_eq!(mixer.volume().unwrap(), 0.73);
+    }
+
+    // Left and right set separately, like pactl's channel map
+    #[derive(Clone)]
+    struct Balanced(Arc<Mutex<Vec<f32>>>);
+
+    impl SystemVolume for Balanced {
+        fn volume(&self) -> Result<f32, String> {
+            Ok(self.channel_volumes()?.into_iter().fold(0.0, f32::max))
+        }
+
+        fn set_volume(&self, volume: f32) -> Result<(), String> {
+            self.0.lock().unwrap().fill(volume);
+            Ok(())
+        }
+
+        fn channel_volumes(&self) -> Result<Vec<f32>, String> {
+            Ok(self.0.lock().unwrap().clone())
+        }
+
+        fn set_channel_volumes(&self, volumes: &[f32]) -> Result<(), String> {
+            *self.0.lock().unwrap() = volumes.to_vec();
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn every_channel_comes_back_so_the_balance_does_too() {
+        let mixer = Balanced(Arc::new(Mutex::new(vec![0.8, 0.4])));
+        let duck = duck_with(Box::new(mixer.clone()), 0.5).unwrap();
+        assert_eq!(mixer.channel_volumes().unwrap(), [0.4, 0.2]);
+        drop(duck);
+        assert_eq!(mixer.channel_volumes().unwrap(), [0.8, 0.4]);
+    }
+
+    #[test]
+    fn volume_comes_back_when_playback_fails() {
+        let mixer = Mixer::at(0.4);
+        let speak = || -> Result<(), String> {
+            let _duck = duck_with(Box::new(mixer.clone()), 0.2)?;
+            Err("output device disappeared".to_string())
+        };
+        assert!(speak().is_err());
+        assert_eq!(mixer.volume().unwrap(), 0.4);
+    }
+
+    #[test]
+    fn a_failed_duck_leaves_the_volume_alone_and_restore_errors_are_reported() {
+        let mixer = Mixer::at(0.4);
+        *mixer.fail_sets.lock().unwrap() = 1;
+        assert!(duck_with(Box::new(mixer.clone()), 0.2).is_err());
+        assert_eq!(mixer.volume().unwrap(), 0.4);
+
+        let duck = duck_with(Box::new(mixer.clone()), 0.5).unwrap();
+        *mixer.fail_sets.lock().unwrap() = 1;
+        assert_eq!(duck.restore().unwrap_err(), "device went away");
+        assert_eq!(mixer.volume().unwrap(), 0.2);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn pactl_and_wpctl_output_parses() {
+        use platform::{parse_pactl, parse_wpctl};
+
+        let stereo = "Volume: front-left: 26214 /  40% / -23.88 dB,   \
+                      front-right: 13107 /  20% / -41.94 dB\n        balance -0.50\n";
+        assert_eq!(parse_pactl(stereo), Some(vec![0.399_993_9, 0.199_996_95]));
+        assert_eq!(
+            parse_pactl("Volume: mono: 65536 / 100% / 0.00 dB\n"),
+            Some(vec![1.0])
+        );
+        assert_eq!(parse_pactl("No such entity\n"), None);
+
+        assert_eq!(parse_wpctl("Volume: 0.40\n"), Some(0.4));
+        assert_eq!(parse_wpctl("Volume: 1.25 [MUTED]\n"), Some(1.25));
+        assert_eq!(parse_wpctl(""), None);
+    }
+}
@@ -0,0 +1,221 @@
+//! Audio lengths, converted exactly between samples, milliseconds and seconds
+//!
+//! Durations used to be worked out inline as `(len as f32 / 24000.0 * 1000.0)
+//! as u32`, which loses precision past a few hours and wraps instead of
+//! saturating. `AudioDuration` keeps nanosecond precision and does the
+//! arithmetic in integers: `from_samples(n, rate).to_samples(rate) == n` for
+//! every `n` and any rate up to 1 GHz (one sample per nanosecond), and
+//! conversions that can't fit saturate.
+
+// Every conversion here is checked; keep it that way
+#![deny(clippy::as_conversions)]
+
+use std::fmt;
+use std::time::Duration;
+
+use crate::SAMPLE_RATE;
+
+const NANOS_PER_SEC: u128 = 1_000_000_000;
+
+/// A length of audio
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct AudioDuration(Duration);
+
+impl AudioDuration {
+    pub const ZERO: Self = Self(Duration::ZERO);
+
+    /// Length of `samples` mono samples at `sample_rate` (zero if the rate is)
+    ///
+    /// Rounds up to the next nanosecond, so converting back with
+    /// [`to_samples`](Self::to_samples) at the same rate gives `samples`.
+    pub fn from_samples(samples: usize, sample_rate: u32) -> Self {
+        if sample_rate == 0 {
+            return Self::ZERO;
+        }
+        let samples = u128::try_from(samples).unwrap_or(u128::MAX);
+        // usize::MAX * 10^9 still fits comfortably in a u128
+        let nanos = samples
+            .saturating_mul(NANOS_PER_SEC)
+            .div_ceil(u128::from(sample_rate));
+        Self::from_nanos(nanos)
+    }
+
+    /// Length of model output (24kHz)
+    ///
+    /// # Example
+    /// ```
+    /// use kokoro_tiny::AudioDuration;
+    ///
+    /// let audio = vec![0.0; 36_000];
+    /// assert_eq!(AudioDuration::of(&audio).as_millis(), 1500);
+    /// ```
+    pub fn of(audio: &[f32]) -> Self {
+        Self::from_samples(audio.len(), SAMPLE_RATE)
+    }
+
+    pub fn from_millis(ms: u64) -> Self {
+        Self(Duration::from_millis(ms))
+    }
+
+    /// Fractional milliseconds, as filter settings take them (zero if not a
+    /// positive number, saturating if too long)
+    pub fn from_millis_f32(ms: f32) -> Self {
+        if ms.is_nan() || ms <= 0.0 {
+            return Self::ZERO;
+        }
+        Self(Duration::try_from_secs_f64(f64::from(ms) / 1000.0).unwrap_or(Duration::MAX))
+    }
+
+    /// Whole samples at `sample_rate` (rounded down, saturating)
+    pub fn to_samples(&self, sample_rate: u32) -> usize {
+        // At most ~1.8e28 ns times ~4.3e9 Hz, inside u128
+        let samples = self.0.as_nanos() * u128::from(sample_rate) / NANOS_PER_SEC;
+        usize::try_from(samples).unwrap_or(usize::MAX)
+    }
+
+    /// Whole milliseconds (rounded down, saturating)
+    pub fn as_millis(&self) -> u64 {
+        u64::try_from(self.0.as_millis()).unwrap_or(u64::MAX)
+    }
+
+    pub fn as_secs_f64(&self) -> f64 {
+        self.0.as_secs_f64()
+    }
+
+    pub fn as_duration(&self) -> Duration {
+        self.0
+    }
+
+    pub fn saturating_add(self, other: Self) -> Self {
+        Self(self.0.saturating_add(other.0))
+    }
+
+    pub fn saturating_sub(self, other: Self) -> Self {
+        Self(self.0.saturating_sub(other.0))
+    }
+
+    /// Signed difference `self - other` in milliseconds
+    pub fn millis_since(&self, other: Self) -> f64 {
+        if *self >= other {
+            self.saturating_sub(other).as_secs_f64() * 1000.0
+        } else {
+            -other.saturating_sub(*self).as_secs_f64() * 1000.0
+        }
+    }
+
+    fn from_nanos(nanos: u128) -> Self {
+        match u64::try_from(nanos / NANOS_PER_SEC) {
+            Ok(secs) => {
+                let subsec = u32::try_from(nanos % NANOS_PER_SEC).unwrap_or_default();
+                Self(Duration::new(secs, subsec))
+            }
+            Err(_) => Self(Duration::MAX),
+        }
+    }
+}
+
+impl From<Duration> for AudioDuration {
+    fn from(duration: Duration) -> Self {
+        Self(duration)
+    }
+}
+
+impl From<AudioDuration> for Duration {
+    fn from(duration: AudioDuration) -> Self {
+        duration.0
+    }
+}
+
+impl fmt::Display for AudioDuration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}ms", self.as_millis())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    const RATES: [u32; 6] = [8_000, 16_000, 22_050, 24_000, 44_100, 48_000];
+
+    proptest! {
+        #[test]
+        fn samples_round_trip(samples in any::<usize>(), rate in 1u32..=1_000_000_000) {
+            let duration = AudioDuration::from_samples(samples, rate);
+            prop_assert_eq!(duration.to_samples(rate), samples);
+        }
+
+        #[test]
+        fn common_rates_round_trip(samples in 0usize..=1 << 40, rate in prop::sample::select(&RATES[..])) {
+            let duration = AudioDuration::from_samples(samples, rate);
+            prop_assert_eq!(duration.to_samples(rate), samples);
+            // Milliseconds are exact integer division, never off by float error
+            let ms = u128::try_from(samples).unwrap() * 1000 / u128::from(rate);
+            prop_assert_eq!(u128::from(duration.as_millis()), ms);
+        }
+
+        #[test]
+        fn millis_to_samples_matches_integer_math(ms in any::<u32>(), rate in prop::sample::select(&RATES[..])) {
+            let samples = AudioDuration::from_millis(u64::from(ms)).to_samples(rate);
+            let expected = u64::from(ms) * u64::from(rate) / 1000;
+            prop_assert_eq!(u64::try_from(samples).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn fractional_millis_convert_like_whole_ones() {
+        for ms in [1u16, 5, 10, 250, 1500] {
+            assert_eq!(
+                AudioDuration::from_millis_f32(f32::from(ms)),
+                AudioDuration::from_millis(u64::from(ms))
+            );
+        }
+        assert_eq!(
+            AudioDuration::from_millis_f32(0.5).to_samples(SAMPLE_RATE),
+            12
+        );
+    }
+
+    #[test]
+    fn two_hours_is_exact() {
+        let samples = 2 * 60 * 60 * 24_000;
+        let duration = AudioDuration::from_samples(samples, SAMPLE_RATE);
+        assert_eq!(duration.as_millis(), 7_200_000);
+        assert_eq!(duration.as_duration(), Duration::from_secs(7200));
+        // One sample over still rounds down to the same millisecond
+        assert_eq!(
+            AudioDuration::from_samples(samples + 1, SAMPLE_RATE).as_millis(),
+            7_200_000
+        );
+    }
+
+    #[test]
+    fn extremes_saturate() {
+        assert_eq!(
+            AudioDuration::from_samples(0, SAMPLE_RATE),
+            AudioDuration::ZERO
+        );
+        assert_eq!(AudioDuration::from_samples(1000, 0), AudioDuration::ZERO);
+        assert!(AudioDuration::of(&[]).as_millis() == 0);
+
+        let max = AudioDuration::from(Duration::MAX);
+        assert_eq!(max.as_millis(), u64::MAX);
+        assert_eq!(max.to_samples(48_000), usize::MAX);
+        assert_eq!(max.saturating_add(max), max);
+        assert_eq!(AudioDuration::ZERO.saturating_sub(max), AudioDuration::ZERO);
+
+        assert_eq!(
+            AudioDuration::from_millis_f32(f32::NAN),
+            AudioDuration::ZERO
+        );
+        assert_eq!(AudioDuration::from_millis_f32(-5.0), AudioDuration::ZERO);
+        assert_eq!(AudioDuration::from_millis_f32(f32::INFINITY), max);
+
+        let a = AudioDuration::from_millis(1500);
+        let b = AudioDuration::from_millis(1250);
+        assert_eq!(a.millis_since(b), 250.0);
+        assert_eq!(b.millis_since(a), -250.0);
+        assert_eq!(a.to_string(), "1500ms");
+    }
+}
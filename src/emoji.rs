@@ -0,0 +1,474 @@
+//! How emoji in the text are read
+//!
+//! espeak reads emoji inconsistently: some as their full Unicode name ("smiling
+//! face with open mouth and cold sweat"), some as noise. Normalization handles
+//! them first, by `EmojiMode`:
+//!
+//! - `Strip` (default) leaves them out
+//! - `Name` reads a short name ("party popper"); emoji without one are left
+//!   out, repeats ("🎉🎉🎉") are read once
+//! - `Skip` leaves them out with a short pause (a comma) where they stood
+//!
+//! A whole emoji sequence counts as one: skin tones, ZWJ sequences ("👩‍💻"),
+//! flags and keycaps ("1️⃣" reads as "1"). Punctuation next to an emoji is kept
+//! as it was, so "Shipped 🎉!" becomes "Shipped!".
+
+use serde::{Deserialize, Serialize};
+
+/// How emoji are read
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EmojiMode {
+    /// Leave them out
+    #[default]
+    Strip,
+    /// Read a short name: "🎉" -> "party popper"
+    Name,
+    /// Leave them out, with a brief pause in their place
+    Skip,
+}
+
+// Short names by base emoji; skin tones and presentation selectors are
+// removed before the lookup
+const NAMES: &[(char, &str)] = &[
+    ('😀', "grinning face"),
+    ('😃', "grinning face"),
+    ('😄', "grinning face"),
+    ('😁', "beaming face"),
+    ('😆', "laughing face"),
+    ('😅', "nervous laugh"),
+    ('🤣', "rolling on the floor laughing"),
+    ('😂', "tears of joy"),
+    ('🙂', "smiling face"),
+    ('🙃', "upside-down face"),
+    ('😉', "winking face"),
+    ('😊', "smiling face"),
+    ('😇', "halo"),
+    ('🥰', "smiling face with hearts"),
+    ('😍', "heart eyes"),
+    ('🤩', "star-struck"),
+    ('😘', "blowing a kiss"),
+    ('😋', "yum"),
+    ('😛', "tongue out"),
+    ('😜', "winking tongue out"),
+    ('🤪', "zany face"),
+    ('🤔', "thinking face"),
+    ('🤨', "raised eyebrow"),
+    ('😐', "neutral face"),
+    ('😑', "expressionless face"),
+    ('😶', "speechless"),
+    ('😏', "smirk"),
+    ('😒', "unamused face"),
+    ('🙄', "eye roll"),
+    ('😬', "grimace"),
+    ('😌', "relieved face"),
+    ('😔', "pensive face"),
+    ('😴', "sleeping"),
+    ('😷', "face with mask"),
+    ('🤒', "sick face"),
+    ('🤯', "mind blown"),
+    ('🥳', "partying face"),
+    ('😎', "cool"),
+    ('🤓', "nerd face"),
+    ('😕', "confused face"),
+    ('😟', "worried face"),
+    ('😮', "surprised face"),
+    ('😲', "astonished face"),
+    ('😳', "flushed face"),
+    ('🥺', "pleading face"),
+    ('😢', "crying face"),
+    ('😭', "sobbing"),
+    ('😱', "screaming"),
+    ('😞', "disappointed face"),
+    ('😓', "cold sweat"),
+    ('😩', "weary face"),
+    ('😫', "tired face"),
+    ('😤', "huffing"),
+    ('😡', "angry face"),
+    ('😠', "angry face"),
+    ('🤬', "swearing"),
+    ('💀', "skull"),
+    ('💩', "pile of poo"),
+    ('🤡', "clown"),
+    ('👻', "ghost"),
+    ('🤖', "robot"),
+    ('👋', "wave"),
+    ('👌', "OK hand"),
+    ('✌', "victory hand"),
+    ('🤞', "fingers crossed"),
+    ('👈', "pointing left"),
+    ('👉', "pointing right"),
+    ('👆', "pointing up"),
+    ('👇', "pointing down"),
+    ('👍', "thumbs up"),
+    ('👎', "thumbs down"),
+    ('👏', "applause"),
+    ('🙌', "raised hands"),
+    ('🙏', "folded hands"),
+    ('💪', "flexed biceps"),
+    ('🤝', "handshake"),
+    ('👀', "eyes"),
+    ('🧠', "brain"),
+    ('🤷', "shrug"),
+    ('🤦', "facepalm"),
+    ('🙈', "see-no-evil monkey"),
+    ('❤', "red heart"),
+    ('💔', "broken heart"),
+    ('💯', "hundred points"),
+    ('💥', "collision"),
+    ('💤', "zzz"),
+    ('✨', "sparkles"),
+    ('⭐', "star"),
+    ('🌟', "glowing star"),
+    ('🔥', "fire"),
+    ('⚡', "high voltage"),
+    ('☀', "sun"),
+    ('🌈', "rainbow"),
+    ('☕', "coffee"),
+    ('🍕', "pizza"),
+    ('🍺', "beer"),
+    ('🍻', "cheers"),
+    ('🎂', "birthday cake"),
+    ('🎉', "party popper"),
+    ('🎊', "confetti ball"),
+    ('🎁', "gift"),
+    ('🏆', "trophy"),
+    ('🥇', "first place medal"),
+    ('🎯', "bullseye"),
+    ('🚀', "rocket"),
+    ('⏰', "alarm clock"),
+    ('⏳', "hourglass"),
+    ('⌛', "hourglass"),
+    ('📌', "pushpin"),
+    ('📎', "paperclip"),
+    ('📝', "memo"),
+    ('📈', "chart going up"),
+    ('📉', "chart going down"),
+    ('📦', "package"),
+    ('📢', "loudspeaker"),
+    ('🔔', "bell"),
+    ('🔒', "locked"),
+    ('🔑', "key"),
+    ('🔧', "wrench"),
+    ('🔨', "hammer"),
+    ('🛠', "tools"),
+    ('⚙', "gear"),
+    ('🐛', "bug"),
+    ('💡', "light bulb"),
+    ('💻', "laptop"),
+    ('📱', "phone"),
+    ('💰', "money bag"),
+    ('✅', "check mark"),
+    ('✔', "check mark"),
+    ('☑', "check mark"),
+    ('❌', "cross mark"),
+    ('❎', "cross mark"),
+    ('⚠', "warning"),
+    ('🚨', "siren"),
+    ('⛔', "no entry"),
+    ('🚫', "prohibited"),
+    ('❓', "question mark"),
+    ('❗', "exclamation mark"),
+    ('➡', "right arrow"),
+    ('⬅', "left arrow"),
+    ('⬆', "up arrow"),
+    ('⬇', "down arrow"),
+    ('🟢', "green circle"),
+    ('🟡', "yellow circle"),
+    ('🔴', "red circle"),
+    ('🐶', "dog"),
+    ('🐱', "cat"),
+    ('🦀', "crab"),
+    ('🐍', "snake"),
+];
+
+// ZWJ sequences whose parts don't add up to their meaning
+const SEQUENCES: &[(&str, &str)] = &[
+    ("👨\u{200d}👩\u{200d}👧", "family"),
+    ("👨\u{200d}👩\u{200d}👦", "family"),
+    ("👨\u{200d}👩\u{200d}👧\u{200d}👦", "family"),
+    ("👨\u{200d}💻", "technologist"),
+    ("👩\u{200d}💻", "technologist"),
+    ("🧑\u{200d}💻", "technologist"),
+    ("❤\u{200d}🔥", "heart on fire"),
+    ("🏳\u{200d}🌈", "rainbow flag"),
+    ("🏴\u{200d}☠", "pirate flag"),
+    ("😮\u{200d}💨", "exhale"),
+];
+
+const ZWJ: char = '\u{200d}';
+const KEYCAP: char = '\u{20e3}';
+
+/// `text` with its emoji handled per `mode`, or `None` when it has none
+///
+/// # Example
+/// ```
+/// use kokoro_tiny::EmojiMode;
+/// use kokoro_tiny::emoji::replace_emoji;
+///
+/// let text = "Deploy done 🎉🎉! Thanks 👍🏽 all";
+/// assert_eq!(replace_emoji(text, EmojiMode::Strip).as_deref(), Some("Deploy done! Thanks all"));
+/// assert_eq!(
+///     replace_emoji(text, EmojiMode::Name).as_deref(),
+///     Some("Deploy done party popper! Thanks thumbs up all")
+/// );
+/// assert_eq!(replace_emoji(text, EmojiMode::Skip).as_deref(), Some("Deploy done! Thanks, all"));
+/// assert_eq!(replace_emoji("No emoji here.", EmojiMode::Strip), None);
+/// ```
+pub fn replace_emoji(text: &str, mode: EmojiMode) -> Option<String> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut out = String::with_capacity(text.len());
+    let mut changed = false;
+    let mut i = 0;
+    while i < chars.len() {
+        let len = emoji_len(&chars[i..]);
+        if len == 0 {
+            out.push(chars[i]);
+            i += 1;
+            continue;
+        }
+
+        // A run of emoji and the spaces between them, read as one
+        changed = true;
+        let mut names: Vec<String> = Vec::new();
+        let mut end = i;
+        loop {
+            let len = emoji_len(&chars[end..]);
+            if len == 0 {
+                break;
+            }
+            if let Some(name) = name(&chars[end..end + len]) {
+                if names.last() != Some(&name) {
+                    names.push(name);
+                }
+            }
+            end += len;
+            let spaces = chars[end..]
+                .iter()
+                .take_while(|c| c.is_whitespace())
+                .count();
+            if emoji_len(&chars[end + spaces..]) == 0 {
+                break;
+            }
+            end += spaces;
+        }
+        i = end;
+
+        let space_before = out.ends_with(char::is_whitespace);
+        out.truncate(out.trim_end().len());
+        let space_after = chars.get(i).is_some_and(|c| c.is_whitespace());
+        while chars.get(i).is_some_and(|c| c.is_whitespace()) {
+            i += 1;
+        }
+        let opened = out.is_empty() || out.ends_with(['(', '[', '{', '"', '\'']);
+        // Punctuation after the run closes up to the text before it
+        let closed = chars.get(i).is_none_or(|&c| ",.;:!?)]}…".contains(c));
+
+        match mode {
+            EmojiMode::Name if !names.is_empty() => {
+                if !opened {
+                    out.push(' ');
+                }
+                out.push_str(&names.join(" "));
+                if !closed {
+                    out.push(' ');
+                }
+            }
+            EmojiMode::Skip if !opened && !closed => {
+                if !out.ends_with(|c: char| c.is_ascii_punctuation()) {
+                    out.push(',');
+                }
+                out.push(' ');
+            }
+            _ if !opened && !closed && (space_before || space_after) => out.push(' '),
+            _ => {}
+        }
+    }
+    changed.then_some(out)
+}
+
+// Chars the emoji sequence at the start of `chars` takes, 0 if none starts there
+fn emoji_len(chars: &[char]) -> usize {
+    let Some(&first) = chars.first() else {
+        return 0;
+    };
+    let next = chars.get(1).copied();
+
+    // Keycaps: "1️⃣", "#⃣"
+    if first.is_ascii_digit() || first == '#' || first == '*' {
+        return match (next, chars.get(2)) {
+            (Some(KEYCAP), _) => 2,
+            (Some('\u{fe0f}'), Some(&KEYCAP)) => 3,
+            _ => 0,
+        };
+    }
+    // Flags are pairs of regional indicators
+    if is_regional_indicator(first) {
+        return if next.is_some_and(is_regional_indicator) {
+            2
+        } else {
+            1
+        };
+    }
+    let selected = next == Some('\u{fe0f}') && !first.is_alphanumeric() && !first.is_whitespace();
+    if !is_pictographic(first) && !selected {
+        return 0;
+    }
+
+    let mut len = 1;
+    while let Some(&c) = chars.get(len) {
+        if is_modifier(c) {
+            len += 1;
+        } else if c == ZWJ && chars.get(len + 1).is_some_and(|&c| is_pictographic(c)) {
+            len += 2;
+        } else {
+            break;
+        }
+    }
+    len
+}
+
+// Short name of one emoji sequence
+fn name(sequence: &[char]) -> Option<String> {
+    let base: String = sequence.iter().filter(|&&c| !is_modifier(c)).collect();
+    if let Some((_, name)) = SEQUENCES.iter().find(|(seq, _)| *seq == base) {
+        return Some(name.to_string());
+    }
+    let first = *sequence.first()?;
+    if sequence.contains(&KEYCAP) {
+        return Some(first.to_string());
+    }
+    if is_regional_indicator(first) {
+        return Some("flag".to_string());
+    }
+    NAMES
+        .iter()
+        .find(|(emoji, _)| *emoji == first)
+        .map(|(_, name)| name.to_string())
+}
+
+// Pictographs: the emoji blocks plus the older symbols that are emoji
+fn is_pictographic(c: char) -> bool {
+    matches!(
+        c,
+        '\u{1f000}'..='\u{1faff}'
+            | '\u{2600}'..='\u{27bf}'
+            | '\u{231a}' | '\u{231b}' | '\u{2328}' | '\u{23cf}'
+            | '\u{23e9}'..='\u{23fa}'
+            | '\u{2b05}'..='\u{2b07}'
+            | '\u{2b1b}' | '\u{2b1c}' | '\u{2b50}' | '\u{2b55}'
+            | '\u{3030}' | '\u{303d}' | '\u{3297}' | '\u{3299}'
+    ) && !is_regional_indicator(c)
+        && !is_modifier(c)
+}
+
+fn is_regional_indicator(c: char) -> bool {
+    ('\u{1f1e6}'..='\u{1f1ff}').contains(&c)
+}
+
+// Skin tones, presentation selectors, keycap marks and tag characters
+fn is_modifier(c: char) -> bool {
+    matches!(
+        c,
+        '\u{1f3fb}'..='\u{1f3ff}' | '\u{fe0e}' | '\u{fe0f}' | KEYCAP | '\u{e0020}'..='\u{e007f}'
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::split_text_for_tts;
+
+    fn read(text: &str, mode: EmojiMode) -> String {
+        replace_emoji(text, mode).unwrap_or_else(|| text.to_string())
+    }
+
+    #[test]
+    fn sequences_count_as_one_emoji() {
+        let cases = [
+            ("Thanks 👍🏿", "Thanks thumbs up"),
+            ("Hi 👋🏻👋🏼", "Hi wave"),
+            (
+                "Ping the 👩\u{200d}💻 on call",
+                "Ping the technologist on call",
+            ),
+            (
+                "Say hi to the 👨\u{200d}👩\u{200d}👧\u{200d}👦",
+                "Say hi to the family",
+            ),
+            ("She 🤦🏽\u{200d}♀\u{fe0f} again", "She facepalm again"),
+            ("Team 🇩🇪 won", "Team flag won"),
+            ("Press 1\u{fe0f}\u{20e3} now", "Press 1 now"),
+            ("Hot ❤\u{fe0f}\u{200d}🔥", "Hot heart on fire"),
+            ("Ok 🫠 then", "Ok then"),
+        ];
+        for (text, expected) in cases {
+            assert_eq!(read(text, EmojiMode::Name), expected, "{}", text);
+            let stripped = read(text, EmojiMode::Strip);
+            assert!(
+                !stripped
+                    .chars()
+                    .any(|c| emoji_len(&[c]) > 0 || is_modifier(c) || c == ZWJ),
+                "{:?}",
+                stripped
+            );
+        }
+    }
+
+    #[test]
+    fn punctuation_next_to_emoji_stays_put() {
+        let cases = [
+            (
+                "Shipped 🎉!",
+                "Shipped!",
+                "Shipped party popper!",
+                "Shipped!",
+            ),
+            (
+                "🚀 Launch now.",
+                "Launch now.",
+                "rocket Launch now.",
+                "Launch now.",
+            ),
+            (
+                "Done ✅, next 👀.",
+                "Done, next.",
+                "Done check mark, next eyes.",
+                "Done, next.",
+            ),
+            (
+                "Oops😅 again",
+                "Oops again",
+                "Oops nervous laugh again",
+                "Oops, again",
+            ),
+            (
+                "Wait. 🤔 Really?",
+                "Wait. Really?",
+                "Wait. thinking face Really?",
+                "Wait. Really?",
+            ),
+            ("(🎉)", "()", "(party popper)", "()"),
+        ];
+        for (text, strip, name, skip) in cases {
+            assert_eq!(read(text, EmojiMode::Strip), strip, "{}", text);
+            assert_eq!(read(text, EmojiMode::Name), name, "{}", text);
+            assert_eq!(read(text, EmojiMode::Skip), skip, "{}", text);
+        }
+        // Text without emoji, symbols and digits included, is untouched
+        for text in ["Costs 5 * 3 = 15 €, #1 ©", "→ next • done"] {
+            assert_eq!(replace_emoji(text, EmojiMode::Name), None, "{}", text);
+        }
+    }
+
+    #[test]
+    fn sentence_splitting_ignores_emoji() {
+        let text = "Build passed 🎉. Tests are green 👍🏽! Deploying now 🚀... Done ✅";
+        let chunks = split_text_for_tts(text, 30, &[]);
+        let read: Vec<String> = chunks.iter().map(|c| read(c, EmojiMode::Strip)).collect();
+        assert_eq!(
+            read.iter().map(|c| c.trim()).collect::<Vec<_>>(),
+            ["Build passed.", "Tests are green!", "Deploying now... Done",]
+        );
+    }
+}
@@ -0,0 +1,220 @@
+//! Error type for the engine's public API
+//!
+//! Each variant is one kind of failure a caller can act on differently:
+//! `ModelMissing` and `DownloadFailed` mean "no model yet, try later",
+//! `VoiceNotFound` and `UnsupportedLanguage` mean "fix the request", the rest
+//! mean this particular render or write failed. `Display` gives the same text
+//! the old `String` errors did.
+//!
+//! ```
+//! use kokoro_tiny::{KokoroError, TtsEngine};
+//!
+//! let tts = TtsEngine::without_model();
+//! match tts.save_audio("speech.flac", &[0.0; 2400]) {
+//!     Err(KokoroError::AudioEncodingFailed(message)) => eprintln!("{}", message),
+//!     other => panic!("unexpected {:?}", other),
+//! }
+//! ```
+
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::sync::Arc;
+
+use crate::languages;
+
+/// Why an engine call failed
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub enum KokoroError {
+    /// Model or voices file missing, unreadable or not a valid model
+    ModelMissing(String),
+    /// Fetching a model file failed (or is backing off after failures)
+    DownloadFailed {
+        url: String,
+        /// What went wrong, also returned by `Error::source`
+        source: Arc<dyn Error + Send + Sync>,
+    },
+    /// A voice (or mix component) that isn't loaded
+    VoiceNotFound {
+        name: String,
+        /// The whole spec, when `name` is one component of a mix
+        mix: Option<String>,
+        /// Loaded voices with the closest names, best first
+        suggestions: Vec<String>,
+    },
+    /// A voice spec that doesn't parse (bad mix syntax or weights)
+    InvalidVoice(String),
+    /// A language code the model vocabulary can't render
+    UnsupportedLanguage(String),
+    /// espeak couldn't phonemize the text
+    PhonemizationFailed(String),
+    /// The ONNX session failed to run
+    InferenceFailed(String),
+    /// Audio couldn't be encoded (WAV/MP3/OPUS, or the format isn't available)
+    AudioEncodingFailed(String),
+    /// The output device or backend failed
+    Playback(String),
+    /// The request itself is unusable (e.g. no text)
+    InvalidInput(String),
+    /// Reading or writing a file failed
+    Io(Arc<io::Error>),
+    /// Anything else, usually from an older `String` error
+    Other(String),
+}
+
+impl KokoroError {
+    // DownloadFailed for `url`, from an error or a message
+    pub(crate) fn download_failed(
+        url: impl Into<String>,
+        source: impl Into<Box<dyn Error + Send + Sync>>,
+    ) -> Self {
+        Self::DownloadFailed {
+            url: url.into(),
+            source: Arc::from(source.into()),
+        }
+    }
+
+    // Same kind with a more detailed message; variants whose text is built
+    // from their fields keep it
+    pub(crate) fn with_message(self, message: String) -> Self {
+        match self {
+            Self::ModelMissing(_) => Self::ModelMissing(message),
+            Self::InvalidVoice(_) => Self::InvalidVoice(message),
+            Self::PhonemizationFailed(_) => Self::PhonemizationFailed(message),
+            Self::InferenceFailed(_) => Self::InferenceFailed(message),
+            Self::AudioEncodingFailed(_) => Self::AudioEncodingFailed(message),
+            Self::Playback(_) => Self::Playback(message),
+            Self::InvalidInput(_) => Self::InvalidInput(message),
+            Self::Other(_) => Self::Other(message),
+            other => other,
+        }
+    }
+}
+
+impl fmt::Display for KokoroError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::DownloadFailed { url, source } => {
+                write!(f, "Failed to download {}: {}", url, source)
+            }
+            Self::VoiceNotFound {
+                name,
+                mix,
+                suggestions,
+            } => {
+                write!(f, "Voice not found: {}", name)?;
+                if let Some(mix) = mix {
+                    write!(f, " (in mix {})", mix)?;
+                }
+                match suggestions.as_slice() {
+                    [] => Ok(()),
+                    [only] => write!(f, "; did you mean {}?", only),
+                    [rest @ .., last] => {
+                        write!(f, "; did you mean {} or {}?", rest.join(", "), last)
+                    }
+                }
+            }
+            Self::UnsupportedLanguage(code) => {
+                write!(f, "{}", languages::unsupported_error(code))
+            }
+            Self::Io(e) => write!(f, "I/O error: {}", e),
+            Self::ModelMissing(message)
+            | Self::InvalidVoice(message)
+            | Self::PhonemizationFailed(message)
+            | Self::InferenceFailed(message)
+            | Self::AudioEncodingFailed(message)
+            | Self::Playback(message)
+            | Self::InvalidInput(message)
+            | Self::Other(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl Error for KokoroError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Io(e) => Some(&**e),
+            Self::DownloadFailed { source, .. } => Some(&**source),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for KokoroError {
+    fn from(e: io::Error) -> Self {
+        Self::Io(Arc::new(e))
+    }
+}
+
+// Errors from helpers that still return `String`
+impl From<String> for KokoroError {
+    fn from(message: String) -> Self {
+        Self::Other(message)
+    }
+}
+
+impl From<&str> for KokoroError {
+    fn from(message: &str) -> Self {
+        Self::Other(message.to_string())
+    }
+}
+
+// Keeps `Result<_, String>` callers compiling; prefer matching on the variants
+impl From<KokoroError> for String {
+    fn from(e: KokoroError) -> Self {
+        e.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display_matches_the_old_messages() {
+        let missing = |mix: Option<&str>, suggestions: &[&str]| KokoroError::VoiceNotFound {
+            name: "af_nope".into(),
+            mix: mix.map(str::to_string),
+            suggestions: suggestions.iter().map(|s| s.to_string()).collect(),
+        };
+        assert_eq!(missing(None, &[]).to_string(), "Voice not found: af_nope");
+        assert_eq!(
+            missing(Some("af_sky.5+af_nope.5"), &["af_nova", "af_sky"]).to_string(),
+            "Voice not found: af_nope (in mix af_sky.5+af_nope.5); did you mean af_nova or af_sky?"
+        );
+        let download = KokoroError::download_failed("https://example.com/0.onnx", "offline");
+        assert_eq!(
+            download.to_string(),
+            "Failed to download https://example.com/0.onnx: offline"
+        );
+        let lang = KokoroError::UnsupportedLanguage("xx".into()).to_string();
+        assert!(lang.starts_with("Unsupported language 'xx'"), "{}", lang);
+    }
+
+    #[test]
+    fn converts_to_and_from_strings() {
+        fn legacy() -> Result<(), String> {
+            Err(KokoroError::InferenceFailed(
+                "Failed to run inference: boom".into(),
+            ))?
+        }
+        assert_eq!(legacy().unwrap_err(), "Failed to run inference: boom");
+
+        let e: KokoroError = "something broke".into();
+        assert!(matches!(e, KokoroError::Other(ref m) if m == "something broke"));
+    }
+
+    #[test]
+    fn io_and_download_errors_keep_their_source() {
+        let e = KokoroError::from(io::Error::new(io::ErrorKind::NotFound, "no such file"));
+        assert_eq!(e.to_string(), "I/O error: no such file");
+        assert!(e.source().is_some());
+
+        let timeout = io::Error::new(io::ErrorKind::TimedOut, "timed out");
+        let e = KokoroError::download_failed("https://example.com/0.bin", timeout);
+        let source = e.source().unwrap().downcast_ref::<io::Error>().unwrap();
+        assert_eq!(source.kind(), io::ErrorKind::TimedOut);
+        assert!(KokoroError::Other("x".into()).source().is_none());
+    }
+}
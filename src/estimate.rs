@@ -0,0 +1,164 @@
+//! Speech length estimates without running the model
+//!
+//! Kokoro predicts a duration for every input token, divides it by the model
+//! speed and renders at least one frame per token, so output length is close
+//! to linear in the token count. `TtsEngine::estimate_duration` walks the text
+//! through the same plan synthesis uses (chunking, normalization, punctuation
+//! weights), counts each chunk's tokens and converts them at
+//! `SECONDS_PER_TOKEN`, adding the pauses and chunk gaps synthesis would
+//! insert. The model never runs, so it answers in fallback mode too, with the
+//! length the real voice would have, not that of the fallback message.
+//!
+//! Expect estimates within about ±15% of the rendered length for ordinary
+//! sentences; `tests/estimate.rs` checks this against the real model.
+
+use std::time::Duration;
+
+use crate::languages::{self, LanguageSupport};
+use crate::normalize;
+use crate::resume::SynthesisPlan;
+use crate::{unspeakable_pause_ms, KokoroError, SynthesizeOptions, TtsEngine};
+
+/// Average length of one input token at model speed 1.0
+pub const SECONDS_PER_TOKEN: f64 = 0.05;
+
+// One model frame (600 samples at 24kHz), the shortest any token renders
+const FRAME_SECONDS: f64 = 0.025;
+
+impl TtsEngine {
+    /// Roughly how long `text` takes to say, without synthesizing it
+    ///
+    /// `speed` is the natural speed `synthesize` takes. Accurate to about
+    /// ±15%; works in fallback mode and without a model (espeak is still
+    /// needed). Empty text is `KokoroError::InvalidInput`.
+    ///
+    /// # Example
+    /// ```
+    /// use kokoro_tiny::TtsEngine;
+    ///
+    /// let tts = TtsEngine::without_model();
+    /// let normal = tts.estimate_duration("Your build finished in four minutes.", 1.0, "en")?;
+    /// let fast = tts.estimate_duration("Your build finished in four minutes.", 2.0, "en")?;
+    /// assert!(fast < normal);
+    /// # Ok::<(), kokoro_tiny::KokoroError>(())
+    /// ```
+    pub fn estimate_duration(
+        &self,
+        text: &str,
+        speed: f32,
+        lang: &str,
+    ) -> Result<Duration, KokoroError> {
+        self.estimate_duration_with(text, &SynthesizeOptions::new().speed(speed).lang(lang))
+    }
+
+    /// `estimate_duration` for the full options (speed mode, pacing, weights)
+    pub fn estimate_duration_with(
+        &self,
+        text: &str,
+        opts: &SynthesizeOptions,
+    ) -> Result<Duration, KokoroError> {
+        let plan = SynthesisPlan::new(text, opts);
+        if text.trim().is_empty() {
+            return Err(KokoroError::InvalidInput(
+                "No text provided for estimation".to_string(),
+            ));
+        }
+        languages::check_code(&plan.lang)?;
+        if !plan.lenient_language
+            && languages::language_support(&plan.lang) == LanguageSupport::Unsupported
+        {
+            return Err(KokoroError::UnsupportedLanguage(plan.lang.clone()));
+        }
+
+        let speed = f64::from(self.engine_speed(plan.speed, plan.speed_mode));
+        let per_token = (SECONDS_PER_TOKEN / speed).max(FRAME_SECONDS);
+
+        let mut seconds = 0.0;
+        for (idx, chunk) in plan.chunks.iter().enumerate() {
+            let normalized = normalize::normalize_for(chunk, &plan, &self.acronyms, &self.lexicon)
+                .unwrap_or_else(|| chunk.clone());
+            let chunk_seconds = match self.phonemize_weighted(
+                &normalized,
+                Some(&plan.lang),
+                &plan.punctuation_weights,
+            )? {
+                Some(phonemes) => phonemes.chars().count() as f64 * per_token,
+                None => f64::from(unspeakable_pause_ms(&normalized)) / 1000.0,
+            };
+
+            // Mirrors `join_chunk`: a gap of silence, or a crossfade overlap
+            if idx > 0 {
+                let gap_ms = plan.gap_ms(idx);
+                if gap_ms > 0 {
+                    seconds += f64::from(gap_ms) / 1000.0;
+                } else {
+                    let overlap = f64::from(plan.pacing.crossfade_ms) / 1000.0;
+                    seconds -= overlap.min(seconds).min(chunk_seconds);
+                }
+            }
+            seconds += chunk_seconds;
+        }
+
+        Ok(Duration::from_secs_f64(seconds))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PacingPreset;
+
+    const SENTENCE: &str = "The quick brown fox jumps over the lazy dog.";
+
+    #[test]
+    fn estimates_scale_with_length_and_speed() {
+        let tts = TtsEngine::without_model();
+        assert!(tts.is_fallback());
+
+        let short = tts.estimate_duration("Done.", 1.0, "en").unwrap();
+        let long = tts.estimate_duration(SENTENCE, 1.0, "en").unwrap();
+        assert!(short < long, "{:?} vs {:?}", short, long);
+        // An ordinary sentence at normal speed, not the fallback message
+        assert!(
+            long > Duration::from_secs(1) && long < Duration::from_secs(8),
+            "{:?}",
+            long
+        );
+
+        let fast = tts.estimate_duration(SENTENCE, 2.0, "en").unwrap();
+        let ratio = long.as_secs_f64() / fast.as_secs_f64();
+        assert!((1.9..=2.1).contains(&ratio), "{}", ratio);
+
+        // Paragraph gaps add silence
+        let text = format!("{}\n\n{}", SENTENCE, SENTENCE);
+        let plain = tts
+            .estimate_duration_with(&text, &SynthesizeOptions::new())
+            .unwrap();
+        let paced = tts
+            .estimate_duration_with(
+                &text,
+                &SynthesizeOptions::new().pacing(PacingPreset::Audiobook),
+            )
+            .unwrap();
+        assert!(paced > plain, "{:?} vs {:?}", paced, plain);
+    }
+
+    #[test]
+    fn pauses_empty_text_and_unsupported_languages() {
+        let tts = TtsEngine::without_model();
+        let pause = tts.estimate_duration(":)", 1.0, "en").unwrap();
+        assert_eq!(
+            pause,
+            Duration::from_millis(unspeakable_pause_ms(":)").into())
+        );
+
+        assert!(matches!(
+            tts.estimate_duration("   ", 1.0, "en"),
+            Err(KokoroError::InvalidInput(_))
+        ));
+        assert!(matches!(
+            tts.estimate_duration(SENTENCE, 1.0, "ja"),
+            Err(KokoroError::UnsupportedLanguage(_))
+        ));
+    }
+}
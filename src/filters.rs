@@ -0,0 +1,250 @@
+//! EQ for synthesized speech
+//!
+//! The model leaves some energy below the voice that only muddies small
+//! speakers, and alerts sometimes want a deliberately band-limited "radio"
+//! sound. Both are a few second-order IIR filters (biquads, after the RBJ
+//! Audio EQ Cookbook) in a row. `Biquad` is the single filter, stateful so it
+//! can run sample by sample inside a synthesizer; `apply` runs a
+//! `FilterPreset` over finished audio, and `SynthesizeOptions::filter` does
+//! that to a synthesis result.
+//!
+//! Frequencies are at the model's 24kHz.
+
+use std::f32::consts::{FRAC_1_SQRT_2, TAU};
+
+use serde::{Deserialize, Serialize};
+
+use crate::SAMPLE_RATE;
+
+/// Ready-made filter chains
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FilterPreset {
+    /// Rumble below 80 Hz removed and a slight lift around 3 kHz, for
+    /// clarity on laptop and phone speakers
+    CleanSpeech,
+    /// 300-3400 Hz only, like a phone line or a radio
+    Telephone,
+}
+
+impl FilterPreset {
+    /// The filters in the chain, in order, with fresh state
+    pub fn filters(self) -> Vec<Biquad> {
+        match self {
+            FilterPreset::CleanSpeech => vec![
+                Biquad::high_pass(80.0, FRAC_1_SQRT_2),
+                Biquad::high_pass(80.0, FRAC_1_SQRT_2),
+                Biquad::peaking(3000.0, 1.0, 2.0),
+            ],
+            // Two of each: a fourth-order edge at both ends
+            FilterPreset::Telephone => vec![
+                Biquad::high_pass(300.0, FRAC_1_SQRT_2),
+                Biquad::high_pass(300.0, FRAC_1_SQRT_2),
+                Biquad::low_pass(3400.0, FRAC_1_SQRT_2),
+                Biquad::low_pass(3400.0, FRAC_1_SQRT_2),
+            ],
+        }
+    }
+}
+
+/// `audio` run through the filters of `preset`
+///
+/// # Example
+/// ```
+/// use kokoro_tiny::filters::{apply, FilterPreset};
+///
+/// let audio: Vec<f32> = (0..2400).map(|i| (i as f32 * 0.2).sin() * 0.5).collect();
+/// let radio = apply(&audio, FilterPreset::Telephone);
+/// assert_eq!(radio.len(), audio.len());
+/// ```
+pub fn apply(audio: &[f32], preset: FilterPreset) -> Vec<f32> {
+    let mut out = audio.to_vec();
+    for mut filter in preset.filters() {
+        filter.run(&mut out);
+    }
+    out
+}
+
+/// A second-order IIR filter and its state
+///
+/// # Example
+/// ```
+/// use kokoro_tiny::filters::Biquad;
+///
+/// // Two formant-like resonances in a row
+/// let mut first = Biquad::peaking(700.0, 5.0, 12.0);
+/// let mut second = Biquad::peaking(1200.0, 5.0, 9.0);
+/// let pulse = [1.0, 0.0, 0.0, 0.0];
+/// let out: Vec<f32> = pulse.iter().map(|&s| second.process(first.process(s))).collect();
+/// assert!(out[0] > 1.0);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Biquad {
+    b0: f32,
+    b1: f32,
+    b2: f32,
+    a1: f32,
+    a2: f32,
+    z1: f32,
+    z2: f32,
+}
+
+impl Biquad {
+    /// Passes everything above `frequency` Hz; `q` 0.707 is the flattest
+    pub fn high_pass(frequency: f32, q: f32) -> Self {
+        let (cos, alpha) = angle(frequency, q);
+        Self::normalized(
+            [(1.0 + cos) / 2.0, -(1.0 + cos), (1.0 + cos) / 2.0],
+            [1.0 + alpha, -2.0 * cos, 1.0 - alpha],
+        )
+    }
+
+    /// Passes everything below `frequency` Hz; `q` 0.707 is the flattest
+    pub fn low_pass(frequency: f32, q: f32) -> Self {
+        let (cos, alpha) = angle(frequency, q);
+        Self::normalized(
+            [(1.0 - cos) / 2.0, 1.0 - cos, (1.0 - cos) / 2.0],
+            [1.0 + alpha, -2.0 * cos, 1.0 - alpha],
+        )
+    }
+
+    /// Boosts (or, with a negative `gain_db`, cuts) a band around
+    /// `frequency` Hz, narrower the higher `q` is
+    pub fn peaking(frequency: f32, q: f32, gain_db: f32) -> Self {
+        let (cos, alpha) = angle(frequency, q);
+        let amp = 10f32.powf(gain_db / 40.0);
+        Self::normalized(
+            [1.0 + alpha * amp, -2.0 * cos, 1.0 - alpha * amp],
+            [1.0 + alpha / amp, -2.0 * cos, 1.0 - alpha / amp],
+        )
+    }
+
+    /// Filter one sample
+    pub fn process(&mut self, x: f32) -> f32 {
+        // Transposed direct form II
+        let y = self.b0 * x + self.z1;
+        self.z1 = self.b1 * x - self.a1 * y + self.z2;
+        self.z2 = self.b2 * x - self.a2 * y;
+        y
+    }
+
+    /// Filter `audio` in place, carrying the state on from earlier calls
+    pub fn run(&mut self, audio: &mut [f32]) {
+        for sample in audio {
+            *sample = self.process(*sample);
+        }
+    }
+
+    /// Forget earlier samples
+    pub fn reset(&mut self) {
+        self.z1 = 0.0;
+        self.z2 = 0.0;
+    }
+
+    fn normalized(b: [f32; 3], a: [f32; 3]) -> Self {
+        Self {
+            b0: b[0] / a[0],
+            b1: b[1] / a[0],
+            b2: b[2] / a[0],
+            a1: a[1] / a[0],
+            a2: a[2] / a[0],
+            z1: 0.0,
+            z2: 0.0,
+        }
+    }
+}
+
+// Cosine of the center frequency and the cookbook's alpha; frequencies are
+// kept below Nyquist and `q` above zero so the filter stays stable
+fn angle(frequency: f32, q: f32) -> (f32, f32) {
+    let nyquist = SAMPLE_RATE as f32 / 2.0;
+    let frequency = if frequency.is_finite() {
+        frequency.clamp(1.0, nyquist * 0.99)
+    } else {
+        nyquist / 2.0
+    };
+    let q = if q.is_finite() {
+        q.max(0.01)
+    } else {
+        FRAC_1_SQRT_2
+    };
+    let w0 = TAU * frequency / SAMPLE_RATE as f32;
+    (w0.cos(), w0.sin() / (2.0 * q))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::PI;
+
+    fn tones(hz: &[f32], seconds: f32) -> Vec<f32> {
+        let partials: Vec<_> = hz.iter().map(|&f| (f, 0.2)).collect();
+        crate::test_signal::tone(&partials, seconds)
+    }
+
+    // Energy at `hz` over the second half, once the filters have settled
+    // (Goertzel)
+    fn energy(audio: &[f32], hz: f32) -> f64 {
+        let window = &audio[audio.len() / 2..];
+        let coeff = 2.0 * (2.0 * PI * f64::from(hz) / f64::from(SAMPLE_RATE)).cos();
+        let (mut s1, mut s2) = (0.0f64, 0.0f64);
+        for &x in window {
+            let s0 = f64::from(x) + coeff * s1 - s2;
+            s2 = s1;
+            s1 = s0;
+        }
+        (s1 * s1 + s2 * s2 - coeff * s1 * s2) / window.len() as f64
+    }
+
+    fn db(ratio: f64) -> f64 {
+        10.0 * ratio.log10()
+    }
+
+    #[test]
+    fn clean_speech_removes_rumble_and_keeps_the_voice() {
+        let rumble = [30.0, 50.0, 70.0];
+        let audio = tones(&[30.0, 50.0, 70.0, 1000.0], 2.0);
+        let clean = apply(&audio, FilterPreset::CleanSpeech);
+
+        let below = |audio: &[f32]| rumble.iter().map(|&hz| energy(audio, hz)).sum::<f64>();
+        let removed = db(below(&clean) / below(&audio));
+        assert!(removed < -10.0, "below 80 Hz: {} dB", removed);
+        let voice = db(energy(&clean, 1000.0) / energy(&audio, 1000.0));
+        assert!(voice.abs() < 1.0, "1 kHz: {} dB", voice);
+        // The presence lift
+        let presence = tones(&[3000.0], 1.0);
+        let lifted = db(energy(&apply(&presence, FilterPreset::CleanSpeech), 3000.0)
+            / energy(&presence, 3000.0));
+        assert!((lifted - 2.0).abs() < 0.3, "3 kHz: {} dB", lifted);
+    }
+
+    #[test]
+    fn telephone_keeps_only_the_phone_band() {
+        let audio = tones(&[100.0, 1000.0, 7000.0], 1.0);
+        let radio = apply(&audio, FilterPreset::Telephone);
+        let change = |hz| db(energy(&radio, hz) / energy(&audio, hz));
+        assert!(change(100.0) < -30.0, "{}", change(100.0));
+        assert!(change(1000.0).abs() < 1.0, "{}", change(1000.0));
+        assert!(change(7000.0) < -30.0, "{}", change(7000.0));
+    }
+
+    #[test]
+    fn biquads_run_in_pieces_like_in_one_go() {
+        let audio = tones(&[200.0, 5000.0], 0.1);
+        let mut whole = audio.clone();
+        Biquad::low_pass(1000.0, 0.707).run(&mut whole);
+
+        let mut filter = Biquad::low_pass(1000.0, 0.707);
+        let mut pieces = audio.clone();
+        let (first, second) = pieces.split_at_mut(1000);
+        filter.run(first);
+        filter.run(second);
+        assert_eq!(pieces, whole);
+
+        filter.reset();
+        assert_eq!(filter, Biquad::low_pass(1000.0, 0.707));
+        // Out-of-range settings still give a stable filter
+        let mut odd = Biquad::high_pass(f32::NAN, 0.0);
+        assert!(audio.iter().all(|&s| odd.process(s).is_finite()));
+    }
+}
@@ -0,0 +1,219 @@
+//! Built-in formant voice for when there is no model at all
+//!
+//! A trimmed-down cousin of `mem8_voice`: each letter becomes a short
+//! segment (a glottal pulse train through two vowel formants, filtered noise
+//! for fricatives, a click for plosives), rendered straight at the model's
+//! sample rate. It is robotic and only roughly follows the spelling, but it
+//! needs no files, no espeak and no randomness, so `--no-download` can still
+//! say something different for different text.
+
+use std::f32::consts::PI;
+
+use crate::SAMPLE_RATE;
+
+const PITCH_HZ: f32 = 120.0;
+const VOWEL_MS: u32 = 90;
+const CONSONANT_MS: u32 = 60;
+const SPACE_MS: u32 = 70;
+const PAUSE_MS: u32 = 250;
+const FADE_MS: u32 = 5;
+const PEAK: f32 = 0.5;
+
+enum Segment {
+    // First two formant frequencies
+    Vowel(f32, f32),
+    // Noise centered on a frequency
+    Fricative(f32),
+    // Short noise burst, then silence
+    Plosive(f32),
+    // Low voiced hum with one formant
+    Sonorant(f32),
+    Silence(u32),
+}
+
+fn segment(c: char) -> Option<Segment> {
+    use Segment::*;
+    Some(match c.to_ascii_lowercase() {
+        'a' => Vowel(730.0, 1090.0),
+        'e' => Vowel(530.0, 1840.0),
+        'i' | 'y' => Vowel(270.0, 2290.0),
+        'o' => Vowel(570.0, 840.0),
+        'u' | 'w' => Vowel(300.0, 870.0),
+        's' | 'z' | 'c' | 'x' => Fricative(5000.0),
+        'f' | 'v' | 'h' => Fricative(2500.0),
+        'j' | 'g' => Fricative(3000.0),
+        't' | 'd' => Plosive(3500.0),
+        'k' | 'q' => Plosive(1800.0),
+        'p' | 'b' => Plosive(900.0),
+        'm' => Sonorant(250.0),
+        'n' => Sonorant(350.0),
+        'l' => Sonorant(400.0),
+        'r' => Sonorant(500.0),
+        ' ' | '\n' | '\t' | '-' => Silence(SPACE_MS),
+        '.' | ',' | '!' | '?' | ';' | ':' => Silence(PAUSE_MS),
+        c if c.is_alphanumeric() => Vowel(500.0, 1500.0),
+        _ => return None,
+    })
+}
+
+/// Render `text` with the formant voice at the model sample rate
+///
+/// Deterministic: the same text always gives the same samples.
+pub fn synthesize(text: &str) -> Vec<f32> {
+    let mut voice = Voice::default();
+    for c in text.chars() {
+        if let Some(segment) = segment(c) {
+            voice.render(segment);
+        }
+    }
+    // Leading and trailing pauses are just dead air
+    let audio = &voice.audio;
+    let start = audio.iter().position(|s| *s != 0.0).unwrap_or(audio.len());
+    let end = audio
+        .iter()
+        .rposition(|s| *s != 0.0)
+        .map_or(start, |i| i + 1);
+    let mut audio = audio[start..end].to_vec();
+
+    let peak = audio.iter().fold(0.0f32, |m, s| m.max(s.abs()));
+    if peak > 0.0 {
+        for s in &mut audio {
+            *s *= PEAK / peak;
+        }
+    }
+    audio
+}
+
+#[derive(Default)]
+struct Voice {
+    audio: Vec<f32>,
+    // Running sample clock, so the pitch doesn't restart every letter
+    clock: usize,
+    noise: u32,
+}
+
+impl Voice {
+    fn render(&mut self, segment: Segment) {
+        let (ms, formants): (u32, Vec<f32>) = match segment {
+            Segment::Vowel(f1, f2) => (VOWEL_MS, vec![f1, f2]),
+            Segment::Sonorant(f1) => (CONSONANT_MS, vec![f1]),
+            Segment::Fricative(f) | Segment::Plosive(f) => (CONSONANT_MS, vec![f]),
+            Segment::Silence(ms) => (ms, Vec::new()),
+        };
+        let len = samples(ms);
+        let burst = samples(15);
+        let mut resonators: Vec<Resonator> = formants.iter().map(|&f| Resonator::new(f)).collect();
+
+        let mut out = Vec::with_capacity(len);
+        for n in 0..len {
+            let source = match segment {
+                Segment::Vowel(..) | Segment::Sonorant(_) => self.pulse(),
+                Segment::Fricative(_) => self.white() * 0.3,
+                Segment::Plosive(_) if n < burst => self.white(),
+                Segment::Plosive(_) | Segment::Silence(_) => 0.0,
+            };
+            let sample = resonators.iter_mut().map(|r| r.next(source)).sum::<f32>();
+            out.push(if matches!(segment, Segment::Sonorant(_)) {
+                sample * 0.5
+            } else {
+                sample
+            });
+            self.clock += 1;
+        }
+
+        // Short fades so segment joins don't click
+        let fade = samples(FADE_MS).min(len / 2);
+        for i in 0..fade {
+            let gain = i as f32 / fade as f32;
+            out[i] *= gain;
+            out[len - 1 - i] *= gain;
+        }
+        self.audio.extend(out);
+    }
+
+    // Glottal pulse train: one sharp pulse per pitch period
+    fn pulse(&self) -> f32 {
+        let period = (SAMPLE_RATE as f32 / PITCH_HZ) as usize;
+        if self.clock.is_multiple_of(period) {
+            1.0
+        } else {
+            0.0
+        }
+    }
+
+    // xorshift, seeded the same every time
+    fn white(&mut self) -> f32 {
+        if self.noise == 0 {
+            self.noise = 0x9e37_79b9;
+        }
+        self.noise ^= self.noise << 13;
+        self.noise ^= self.noise >> 17;
+        self.noise ^= self.noise << 5;
+        self.noise as f32 / u32::MAX as f32 * 2.0 - 1.0
+    }
+}
+
+fn samples(ms: u32) -> usize {
+    SAMPLE_RATE as usize * ms as usize / 1000
+}
+
+// Two-pole resonator with a fixed bandwidth
+struct Resonator {
+    a: f32,
+    b1: f32,
+    b2: f32,
+    y1: f32,
+    y2: f32,
+}
+
+impl Resonator {
+    fn new(frequency: f32) -> Self {
+        let bandwidth = 100.0 + frequency * 0.05;
+        let r = (-PI * bandwidth / SAMPLE_RATE as f32).exp();
+        let b1 = 2.0 * r * (2.0 * PI * frequency / SAMPLE_RATE as f32).cos();
+        let b2 = -r * r;
+        Self {
+            a: 1.0 - b1 - b2,
+            b1,
+            b2,
+            y1: 0.0,
+            y2: 0.0,
+        }
+    }
+
+    fn next(&mut self, x: f32) -> f32 {
+        let y = self.a * x + self.b1 * self.y1 + self.b2 * self.y2;
+        self.y2 = self.y1;
+        self.y1 = y;
+        y
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn different_text_gives_different_audio() {
+        let build = synthesize("Build complete.");
+        let tests = synthesize("Tests failed!");
+        assert!(!build.is_empty() && !tests.is_empty());
+        assert_ne!(build, tests);
+        assert_eq!(build, synthesize("Build complete."));
+
+        for audio in [&build, &tests] {
+            assert!(audio
+                .iter()
+                .all(|s| s.is_finite() && s.abs() <= PEAK + 1e-6));
+            // At least a consonant's length for most letters
+            assert!(audio.len() > samples(CONSONANT_MS) * 8);
+        }
+    }
+
+    #[test]
+    fn nothing_speakable_is_silent() {
+        assert!(synthesize("").is_empty());
+        assert!(synthesize("  ...  ").is_empty());
+        assert!(synthesize("🎉").is_empty());
+    }
+}
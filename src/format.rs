@@ -0,0 +1,118 @@
+//! File formats `TtsEngine::encode` writes
+//!
+//! One entry point for every encoder, whatever the destination: a file, a
+//! `Vec<u8>`, a socket or an upload stream all take `impl Write`. MP3 and
+//! Ogg Opus exist with their features enabled.
+
+#[cfg(feature = "mp3")]
+use crate::Mp3Options;
+use crate::{Law, PcmFormat};
+
+/// Output format for `TtsEngine::encode`
+///
+/// # Example
+/// ```
+/// use kokoro_tiny::{AudioFormat, PcmFormat, TtsEngine};
+///
+/// let tts = TtsEngine::without_model();
+/// let mut wav = Vec::new();
+/// tts.encode(&[0.0; 2400], AudioFormat::Wav, &mut wav)?;
+/// assert_eq!(&wav[..4], b"RIFF");
+///
+/// let mut raw = Vec::new();
+/// tts.encode(&[0.0; 2400], AudioFormat::Pcm(PcmFormat::S16le), &mut raw)?;
+/// assert_eq!(raw.len(), 4800);
+/// # Ok::<(), String>(())
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum AudioFormat {
+    /// 16-bit PCM WAV, at the audio's own rate and channel count
+    Wav,
+    /// Headerless samples, as `write_pcm` writes them
+    Pcm(PcmFormat),
+    /// 8kHz mono G.711 WAV for telephone systems, as `save_wav_telephony`
+    /// writes it
+    WavTelephony(Law),
+    /// MP3 with these settings, mono at 24kHz (requires 'mp3' feature)
+    #[cfg(feature = "mp3")]
+    Mp3(Mp3Options),
+    /// Ogg Opus at `bitrate` bits per second, mono (requires 'opus-format'
+    /// feature)
+    #[cfg(feature = "opus-format")]
+    OpusOgg { bitrate: i32 },
+}
+
+impl AudioFormat {
+    /// The usual file extension, without the dot
+    pub fn extension(&self) -> &'static str {
+        match self {
+            AudioFormat::Wav => "wav",
+            AudioFormat::Pcm(_) => "raw",
+            AudioFormat::WavTelephony(_) => "wav",
+            #[cfg(feature = "mp3")]
+            AudioFormat::Mp3(_) => "mp3",
+            #[cfg(feature = "opus-format")]
+            AudioFormat::OpusOgg { .. } => "opus",
+        }
+    }
+
+    /// Media type, as in a data URL or a `Content-Type` header; raw samples
+    /// have none of their own
+    pub fn mime_type(&self) -> &'static str {
+        match self {
+            AudioFormat::Wav => "audio/wav",
+            AudioFormat::Pcm(_) => "application/octet-stream",
+            AudioFormat::WavTelephony(_) => "audio/wav",
+            #[cfg(feature = "mp3")]
+            AudioFormat::Mp3(_) => "audio/mpeg",
+            #[cfg(feature = "opus-format")]
+            AudioFormat::OpusOgg { .. } => "audio/ogg",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TtsEngine;
+
+    fn encoded(format: AudioFormat) -> Vec<u8> {
+        let tone: Vec<f32> = (0..12_000).map(|i| (i as f32 * 0.05).sin() * 0.5).collect();
+        let mut bytes = Vec::new();
+        TtsEngine::without_model()
+            .encode(&tone, format, &mut bytes)
+            .unwrap();
+        bytes
+    }
+
+    #[test]
+    fn every_format_writes_into_a_buffer() {
+        let wav = encoded(AudioFormat::Wav);
+        assert_eq!((&wav[..4], &wav[8..12]), (&b"RIFF"[..], &b"WAVE"[..]));
+        assert_eq!(wav.len(), 44 + 24_000);
+        assert_eq!(encoded(AudioFormat::Pcm(PcmFormat::S16le)), wav[44..]);
+        assert_eq!(encoded(AudioFormat::Pcm(PcmFormat::F32le)).len(), 48_000);
+        // Half a second at 8kHz, one byte a sample
+        let pcmu = encoded(AudioFormat::WavTelephony(Law::MuLaw));
+        assert_eq!((&pcmu[..4], pcmu.len()), (&b"RIFF"[..], 58 + 4_000));
+    }
+
+    #[cfg(feature = "mp3")]
+    #[test]
+    fn mp3_starts_with_its_tag() {
+        let opts = Mp3Options {
+            title: Some("Tone".into()),
+            ..Mp3Options::default()
+        };
+        assert_eq!(&encoded(AudioFormat::Mp3(opts))[..3], b"ID3");
+    }
+
+    #[cfg(feature = "opus-format")]
+    #[test]
+    fn opus_is_an_ogg_stream() {
+        let opus = encoded(AudioFormat::OpusOgg { bitrate: 24_000 });
+        assert_eq!(&opus[..4], b"OggS");
+        assert_eq!(&opus[28..36], b"OpusHead");
+    }
+}
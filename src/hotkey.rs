@@ -0,0 +1,443 @@
+//! Global hotkeys for interrupting speech without touching the terminal
+//!
+//! A [`HotkeyBinding`] calls a closure whenever its key combination is
+//! pressed, in any window, and unregisters itself when dropped. Hooks come
+//! from a [`HotkeyBackend`]; the built-in one ([`system_backend`], `hotkey`
+//! feature) grabs keys through X11. Windows and macOS only deliver global
+//! hotkeys to a thread running the platform event loop, which this crate does
+//! not own, so there the built-in backend reports itself unavailable and an
+//! application with its own event loop can plug in a backend instead.
+//!
+//! A missing backend (headless, Wayland, feature off) is not an error: the
+//! caller gets a warning and keeps the other interrupt paths.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+
+/// Called on the backend's thread each time the hotkey is pressed
+pub type HotkeyCallback = Arc<dyn Fn() + Send + Sync>;
+
+/// A key combination such as `ctrl+shift+space`
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Hotkey {
+    pub ctrl: bool,
+    pub alt: bool,
+    pub shift: bool,
+    /// Cmd on macOS, the Windows key elsewhere
+    pub super_key: bool,
+    /// W3C key code name: `Space`, `KeyA`, `Digit1`, `F5`, `Escape`, ...
+    pub key: String,
+}
+
+impl FromStr for Hotkey {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        let mut hotkey = Hotkey {
+            ctrl: false,
+            alt: false,
+            shift: false,
+            super_key: false,
+            key: String::new(),
+        };
+
+        for token in s.split('+').map(str::trim) {
+            if !hotkey.key.is_empty() {
+                return Err(format!("Invalid hotkey '{}': the key must come last", s));
+            }
+            match token.to_lowercase().as_str() {
+                "ctrl" | "control" => hotkey.ctrl = true,
+                "alt" | "option" => hotkey.alt = true,
+                "shift" => hotkey.shift = true,
+                "super" | "cmd" | "command" | "meta" | "win" => hotkey.super_key = true,
+                "" => return Err(format!("Invalid hotkey '{}': empty key", s)),
+                key => {
+                    hotkey.key = key_code(key)
+                        .ok_or_else(|| format!("Unknown key '{}' in hotkey '{}'", token, s))?
+                }
+            }
+        }
+
+        if hotkey.key.is_empty() {
+            return Err(format!(
+                "Invalid hotkey '{}': no key after the modifiers",
+                s
+            ));
+        }
+        // A global grab on a bare letter or Space would swallow normal typing
+        let standalone = hotkey.key == "Pause"
+            || hotkey
+                .key
+                .strip_prefix('F')
+                .is_some_and(|n| n.parse::<u8>().is_ok());
+        if !(hotkey.ctrl || hotkey.alt || hotkey.super_key || standalone) {
+            return Err(format!(
+                "Hotkey '{}' needs ctrl, alt or super (only F-keys and Pause work alone)",
+                s
+            ));
+        }
+        Ok(hotkey)
+    }
+}
+
+impl fmt::Display for Hotkey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (on, name) in [
+            (self.ctrl, "ctrl"),
+            (self.alt, "alt"),
+            (self.shift, "shift"),
+            (self.super_key, "super"),
+        ] {
+            if on {
+                write!(f, "{}+", name)?;
+            }
+        }
+        write!(f, "{}", self.key)
+    }
+}
+
+// Friendly key names to W3C key codes
+fn key_code(name: &str) -> Option<String> {
+    let code = match name {
+        "space" => "Space",
+        "esc" | "escape" => "Escape",
+        "enter" | "return" => "Enter",
+        "tab" => "Tab",
+        "backspace" => "Backspace",
+        "pause" | "break" => "Pause",
+        "home" => "Home",
+        "end" => "End",
+        "up" => "ArrowUp",
+        "down" => "ArrowDown",
+        "left" => "ArrowLeft",
+        "right" => "ArrowRight",
+        "." | "period" => "Period",
+        "," | "comma" => "Comma",
+        "/" | "slash" => "Slash",
+        _ => {
+            let mut chars = name.chars();
+            return match (chars.next(), chars.next()) {
+                (Some(c), None) if c.is_ascii_alphabetic() => {
+                    Some(format!("Key{}", c.to_ascii_uppercase()))
+                }
+                (Some(c), None) if c.is_ascii_digit() => Some(format!("Digit{}", c)),
+                (Some('f'), Some(_)) => match name[1..].parse::<u8>() {
+                    Ok(n @ 1..=24) => Some(format!("F{}", n)),
+                    _ => None,
+                },
+                _ => None,
+            };
+        }
+    };
+    Some(code.to_string())
+}
+
+/// Source of global key hooks
+pub trait HotkeyBackend: Send + Sync {
+    /// Start calling `on_press` whenever `hotkey` is pressed
+    fn register(&self, hotkey: &Hotkey, on_press: HotkeyCallback) -> Result<(), String>;
+
+    /// Stop delivering presses of `hotkey`; unknown hotkeys are ignored
+    fn unregister(&self, hotkey: &Hotkey);
+}
+
+/// A registered hotkey; dropping it unregisters the hook
+pub struct HotkeyBinding {
+    backend: Arc<dyn HotkeyBackend>,
+    hotkey: Hotkey,
+}
+
+impl HotkeyBinding {
+    /// Register `hotkey` on `backend`
+    pub fn new(
+        backend: Arc<dyn HotkeyBackend>,
+        hotkey: Hotkey,
+        on_press: HotkeyCallback,
+    ) -> Result<Self, String> {
+        backend.register(&hotkey, on_press)?;
+        Ok(Self { backend, hotkey })
+    }
+
+    /// Register `keys` on the built-in backend
+    ///
+    /// Returns `Ok(None)` with a warning on stderr if global hotkeys aren't
+    /// available here; a malformed `keys` or a combination already grabbed by
+    /// another program is an error.
+    pub fn system(keys: &str, on_press: HotkeyCallback) -> Result<Option<Self>, String> {
+        let hotkey: Hotkey = keys.parse()?;
+        match system_backend() {
+            Ok(backend) => Self::new(backend, hotkey, on_press).map(Some),
+            Err(reason) => {
+                eprintln!("⚠️ Interrupt hotkey {} disabled: {}", hotkey, reason);
+                Ok(None)
+            }
+        }
+    }
+
+    pub fn hotkey(&self) -> &Hotkey {
+        &self.hotkey
+    }
+}
+
+impl Drop for HotkeyBinding {
+    fn drop(&mut self) {
+        self.backend.unregister(&self.hotkey);
+    }
+}
+
+/// The process-wide global hotkey backend, if this platform session has one
+pub fn system_backend() -> Result<Arc<dyn HotkeyBackend>, String> {
+    #[cfg(all(feature = "hotkey", target_os = "linux"))]
+    {
+        x11::backend()
+    }
+
+    #[cfg(all(feature = "hotkey", not(target_os = "linux")))]
+    {
+        Err(
+            "global hotkeys need the application's event loop on this platform; \
+             register a HotkeyBackend from it instead"
+                .to_string(),
+        )
+    }
+
+    #[cfg(not(feature = "hotkey"))]
+    {
+        Err("built without the hotkey feature".to_string())
+    }
+}
+
+/// Callbacks by hotkey, shared by backends that dispatch from one event stream
+#[derive(Default)]
+pub(crate) struct Dispatch {
+    callbacks: Mutex<HashMap<Hotkey, HotkeyCallback>>,
+}
+
+impl Dispatch {
+    pub fn insert(&self, hotkey: &Hotkey, on_press: HotkeyCallback) -> Result<(), String> {
+        let mut callbacks = self.callbacks.lock().unwrap_or_else(|e| e.into_inner());
+        if callbacks.contains_key(hotkey) {
+            return Err(format!("Hotkey {} is already bound", hotkey));
+        }
+        callbacks.insert(hotkey.clone(), on_press);
+        Ok(())
+    }
+
+    pub fn remove(&self, hotkey: &Hotkey) -> bool {
+        self.callbacks
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(hotkey)
+            .is_some()
+    }
+
+    pub fn len(&self) -> usize {
+        self.callbacks
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .len()
+    }
+
+    /// Run the callback for `hotkey`, outside the lock so it may rebind keys
+    pub fn fire(&self, hotkey: &Hotkey) {
+        let callback = self
+            .callbacks
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(hotkey)
+            .cloned();
+        if let Some(callback) = callback {
+            callback();
+        }
+    }
+}
+
+#[cfg(all(feature = "hotkey", target_os = "linux"))]
+mod x11 {
+    use super::{Dispatch, Hotkey, HotkeyBackend, HotkeyCallback};
+    use global_hotkey::hotkey::HotKey;
+    use global_hotkey::{GlobalHotKeyEvent, GlobalHotKeyManager, HotKeyState};
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex, OnceLock};
+
+    struct X11Backend {
+        manager: GlobalHotKeyManager,
+        dispatch: Dispatch,
+        // global-hotkey reports presses by numeric id
+        by_id: Mutex<HashMap<u32, Hotkey>>,
+    }
+
+    pub(super) fn backend() -> Result<Arc<dyn HotkeyBackend>, String> {
+        // The X11 grab thread exits silently without a display, so check first
+        if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+            return Err("Wayland does not allow global key grabs".to_string());
+        }
+        if std::env::var_os("DISPLAY").is_none() {
+            return Err("no X11 display (headless session)".to_string());
+        }
+
+        static BACKEND: OnceLock<Result<Arc<X11Backend>, String>> = OnceLock::new();
+        let backend = BACKEND.get_or_init(|| {
+            let manager = GlobalHotKeyManager::new().map_err(|e| e.to_string())?;
+            let backend = Arc::new(X11Backend {
+                manager,
+                dispatch: Dispatch::default(),
+                by_id: Mutex::new(HashMap::new()),
+            });
+            let events = Arc::downgrade(&backend);
+            GlobalHotKeyEvent::set_event_handler(Some(move |event: GlobalHotKeyEvent| {
+                if event.state() != HotKeyState::Pressed {
+                    return;
+                }
+                let Some(backend) = events.upgrade() else {
+                    return;
+                };
+                let hotkey = backend
+                    .by_id
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .get(&event.id())
+                    .cloned();
+                if let Some(hotkey) = hotkey {
+                    backend.dispatch.fire(&hotkey);
+                }
+            }));
+            Ok(backend)
+        });
+        backend.clone().map(|b| b as Arc<dyn HotkeyBackend>)
+    }
+
+    fn native(hotkey: &Hotkey) -> Result<HotKey, String> {
+        hotkey
+            .to_string()
+            .parse()
+            .map_err(|e| format!("Unsupported hotkey {}: {}", hotkey, e))
+    }
+
+    impl HotkeyBackend for X11Backend {
+        fn register(&self, hotkey: &Hotkey, on_press: HotkeyCallback) -> Result<(), String> {
+            let native = native(hotkey)?;
+            self.dispatch.insert(hotkey, on_press)?;
+            if let Err(e) = self.manager.register(native) {
+                self.dispatch.remove(hotkey);
+                return Err(format!("Could not grab {}: {}", hotkey, e));
+            }
+            self.by_id
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .insert(native.id(), hotkey.clone());
+            Ok(())
+        }
+
+        fn unregister(&self, hotkey: &Hotkey) {
+            if !self.dispatch.remove(hotkey) {
+                return;
+            }
+            if let Ok(native) = native(hotkey) {
+                self.by_id
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .remove(&native.id());
+                let _ = self.manager.unregister(native);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Backend that records registrations; tests "press" keys with `press`
+    #[derive(Default)]
+    pub(crate) struct MockBackend {
+        pub dispatch: Dispatch,
+        pub registrations: AtomicUsize,
+    }
+
+    impl MockBackend {
+        pub fn press(&self, keys: &str) {
+            self.dispatch.fire(&keys.parse().unwrap());
+        }
+    }
+
+    impl HotkeyBackend for MockBackend {
+        fn register(&self, hotkey: &Hotkey, on_press: HotkeyCallback) -> Result<(), String> {
+            self.dispatch.insert(hotkey, on_press)?;
+            self.registrations.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }
+
+        fn unregister(&self, hotkey: &Hotkey) {
+            self.dispatch.remove(hotkey);
+        }
+    }
+
+    #[test]
+    fn parses_and_normalizes_hotkeys() {
+        let hotkey: Hotkey = "Ctrl + Shift + space".parse().unwrap();
+        assert!(hotkey.ctrl && hotkey.shift && !hotkey.alt);
+        assert_eq!(hotkey.to_string(), "ctrl+shift+Space");
+        assert_eq!(
+            "cmd+.".parse::<Hotkey>().unwrap().to_string(),
+            "super+Period"
+        );
+        assert_eq!("alt+q".parse::<Hotkey>().unwrap().key, "KeyQ");
+        assert_eq!("F9".parse::<Hotkey>().unwrap().to_string(), "F9");
+
+        assert!(
+            "space".parse::<Hotkey>().is_err(),
+            "bare key would eat typing"
+        );
+        assert!("shift+a".parse::<Hotkey>().is_err());
+        assert!("ctrl+".parse::<Hotkey>().is_err());
+        assert!("ctrl+a+shift".parse::<Hotkey>().is_err());
+        assert!("ctrl+f99".parse::<Hotkey>().is_err());
+        assert!("hyper+x".parse::<Hotkey>().is_err());
+    }
+
+    #[test]
+    fn binding_unregisters_on_drop() {
+        let backend = Arc::new(MockBackend::default());
+        let presses = Arc::new(AtomicUsize::new(0));
+        let counter = presses.clone();
+        let on_press: HotkeyCallback = Arc::new(move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+        });
+
+        let binding = HotkeyBinding::new(
+            backend.clone(),
+            "ctrl+shift+space".parse().unwrap(),
+            on_press.clone(),
+        )
+        .unwrap();
+        assert_eq!(backend.dispatch.len(), 1);
+        // The same combination can't be bound twice
+        assert!(HotkeyBinding::new(
+            backend.clone(),
+            "shift+ctrl+space".parse().unwrap(),
+            on_press
+        )
+        .is_err());
+
+        backend.press("ctrl+shift+space");
+        backend.press("ctrl+alt+space");
+        assert_eq!(presses.load(Ordering::SeqCst), 1);
+
+        drop(binding);
+        assert_eq!(backend.dispatch.len(), 0);
+        backend.press("ctrl+shift+space");
+        assert_eq!(presses.load(Ordering::SeqCst), 1);
+    }
+
+    #[cfg(not(feature = "hotkey"))]
+    #[test]
+    fn missing_backend_is_a_warning_not_an_error() {
+        let binding = HotkeyBinding::system("ctrl+shift+space", Arc::new(|| {})).unwrap();
+        assert!(binding.is_none());
+        assert!(HotkeyBinding::system("space", Arc::new(|| {})).is_err());
+    }
+}
@@ -0,0 +1,386 @@
+//! SHA-256 checks for the downloaded model files
+//!
+//! A download cut short leaves a truncated `0.onnx` that ONNX Runtime can't
+//! parse, and before these checks every startup failed on it until the file
+//! was deleted by hand. Now each download is checked against the size and
+//! digest the crate expects, and so is a cached file before it is loaded: a
+//! bad file is deleted and fetched again, once.
+//!
+//! Hashing the 310MB model takes about a second, so a verified file gets a
+//! `<file>.sha256` stamp next to it (digest, size and modification time).
+//! While the stamp matches, only the size is compared.
+//!
+//! Files from a URL the crate doesn't pin (mirrors set with
+//! `EngineBuilder::model_urls` or `KOKORO_MODEL_URL`, the 54-voice pack) have no digest to compare with and
+//! are reported as `FileStatus::Unchecked`. So are files at paths given with
+//! `EngineBuilder::paths` once they exist: they may be the caller's own
+//! build, and are never deleted. Downloads to those paths are still checked.
+
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use sha2::{Digest, Sha256};
+
+use crate::{EngineBuilder, KokoroError, TtsEngine};
+
+/// Size and SHA-256 a file must have
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FileDigest {
+    pub size: u64,
+    /// Lower-case hex
+    pub sha256: &'static str,
+}
+
+// `0.onnx`, as served from MODEL_URL (the git LFS object)
+pub(crate) const MODEL_DIGEST: FileDigest = FileDigest {
+    size: 325_532_387,
+    sha256: "7d5df8ecf7d4b1878015a32686053fd0eebe2bc377234608764cc0ef3636a6c5",
+};
+
+/// What `verify_cache` found for one file
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FileStatus {
+    /// Size and digest match
+    Verified,
+    /// Present, but there is no digest to compare with
+    Unchecked,
+    Missing,
+    /// Usually a download that was cut short
+    WrongSize {
+        expected: u64,
+        actual: u64,
+    },
+    WrongHash {
+        expected: String,
+        actual: String,
+    },
+}
+
+impl FileStatus {
+    /// Whether the engine will load the file as it is
+    pub fn is_usable(&self) -> bool {
+        matches!(self, FileStatus::Verified | FileStatus::Unchecked)
+    }
+}
+
+/// The state of the model and voices files (see `verify_cache`)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CacheStatus {
+    pub model_path: PathBuf,
+    pub model: FileStatus,
+    pub voices_path: PathBuf,
+    pub voices: FileStatus,
+}
+
+impl CacheStatus {
+    /// Both files are there and intact: building the engine won't download
+    pub fn is_ready(&self) -> bool {
+        self.model.is_usable() && self.voices.is_usable()
+    }
+}
+
+/// Check the model files in the cache directory without loading them
+///
+/// For tools that want to know before building an engine whether it will
+/// download (or re-download) anything. Nothing is deleted or fetched.
+///
+/// # Example
+/// ```no_run
+/// use kokoro_tiny::verify_cache;
+///
+/// let status = verify_cache()?;
+/// if !status.is_ready() {
+///     eprintln!("model: {:?}, voices: {:?}", status.model, status.voices);
+/// }
+/// # Ok::<(), kokoro_tiny::KokoroError>(())
+/// ```
+pub fn verify_cache() -> Result<CacheStatus, KokoroError> {
+    TtsEngine::builder().verify_cache()
+}
+
+impl EngineBuilder {
+    /// `verify_cache` for the files this builder would load
+    pub fn verify_cache(&self) -> Result<CacheStatus, KokoroError> {
+        let (model_path, voices_path) = self.file_paths();
+        let (model_digest, voices_digest) = self.cached_digests();
+        Ok(CacheStatus {
+            model: check_file(Path::new(&model_path), model_digest)?,
+            model_path: model_path.into(),
+            voices: check_file(Path::new(&voices_path), voices_digest)?,
+            voices_path: voices_path.into(),
+        })
+    }
+
+    // Digests to hold files already on disk to: only those in the cache
+    // directory, which the engine manages
+    pub(crate) fn cached_digests(&self) -> (Option<FileDigest>, Option<FileDigest>) {
+        let sources = self.sources();
+        (
+            sources.model.digest.filter(|_| self.model_path.is_none()),
+            sources.voices.digest.filter(|_| self.voices_path.is_none()),
+        )
+    }
+}
+
+// Size first, then the stamp, then the full hash
+pub(crate) fn check_file(path: &Path, digest: Option<FileDigest>) -> io::Result<FileStatus> {
+    let metadata = match fs::metadata(path) {
+        Ok(metadata) => metadata,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(FileStatus::Missing),
+        Err(e) => return Err(e),
+    };
+    let Some(digest) = digest else {
+        return Ok(FileStatus::Unchecked);
+    };
+    if metadata.len() != digest.size {
+        return Ok(FileStatus::WrongSize {
+            expected: digest.size,
+            actual: metadata.len(),
+        });
+    }
+
+    let stamp = stamp_for(&metadata, digest);
+    if fs::read_to_string(stamp_path(path)).ok().as_deref() == Some(stamp.as_str()) {
+        return Ok(FileStatus::Verified);
+    }
+    let actual = sha256_file(path)?;
+    if actual != digest.sha256 {
+        return Ok(FileStatus::WrongHash {
+            expected: digest.sha256.to_string(),
+            actual,
+        });
+    }
+    // Best effort: without the stamp the next load just hashes again
+    let _ = fs::write(stamp_path(path), stamp);
+    Ok(FileStatus::Verified)
+}
+
+// Delete a cached file that doesn't match, so it gets downloaded again
+pub(crate) fn discard_if_corrupt(
+    path: &str,
+    digest: Option<FileDigest>,
+) -> Result<(), KokoroError> {
+    let status = check_file(Path::new(path), digest)?;
+    if !status.is_usable() && status != FileStatus::Missing {
+        #[cfg(not(feature = "as-lib"))]
+        eprintln!(
+            "⚠️  {} is damaged ({}), downloading it again",
+            path,
+            describe(&status)
+        );
+        remove(path)?;
+    }
+    Ok(())
+}
+
+// After a download: the error explains the mismatch and the file is gone
+pub(crate) fn verify_download(path: &str, digest: Option<FileDigest>) -> Result<(), String> {
+    let status = check_file(Path::new(path), digest).map_err(|e| e.to_string())?;
+    if status.is_usable() {
+        return Ok(());
+    }
+    remove(path).map_err(|e| e.to_string())?;
+    Err(format!(
+        "Downloaded file failed verification: {}",
+        describe(&status)
+    ))
+}
+
+// Why a file isn't usable, for messages
+pub(crate) fn describe(status: &FileStatus) -> String {
+    match status {
+        FileStatus::WrongSize { expected, actual } => {
+            format!("{} bytes, expected {}", actual, expected)
+        }
+        FileStatus::WrongHash { expected, actual } => {
+            format!("SHA-256 {}, expected {}", actual, expected)
+        }
+        FileStatus::Missing => "missing".to_string(),
+        FileStatus::Verified | FileStatus::Unchecked => "intact".to_string(),
+    }
+}
+
+fn remove(path: &str) -> Result<(), KokoroError> {
+    let _ = fs::remove_file(stamp_path(Path::new(path)));
+    fs::remove_file(path).map_err(KokoroError::from)
+}
+
+fn sha256_file(path: &Path) -> io::Result<String> {
+    let mut file = File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0u8; 1 << 20];
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    Ok(hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect())
+}
+
+fn stamp_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".sha256");
+    PathBuf::from(name)
+}
+
+// Invalidated by any write to the file, which changes its modification time
+fn stamp_for(metadata: &fs::Metadata, digest: FileDigest) -> String {
+    let modified = metadata
+        .modified()
+        .ok()
+        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+        .map(|since| since.as_nanos())
+        .unwrap_or(0);
+    format!("{} {} {}\n", digest.sha256, digest.size, modified)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::voice_pack::VoicePack;
+
+    // sha256("hello world\n")
+    const HELLO: FileDigest = FileDigest {
+        size: 12,
+        sha256: "a948904f2f0f479b8f8197694b30184b0d2ed1c1cd2a1ec0fb85d299a192a447",
+    };
+
+    fn fixture(name: &str, contents: &str) -> String {
+        let dir = std::env::temp_dir().join(format!("kokoro-integrity-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(name);
+        fs::write(&path, contents).unwrap();
+        let _ = fs::remove_file(stamp_path(&path));
+        path.to_str().unwrap().to_string()
+    }
+
+    #[test]
+    fn files_are_checked_by_size_then_hash() {
+        let path = fixture("hello.bin", "hello world\n");
+        assert_eq!(
+            check_file(Path::new(&path), Some(HELLO)).unwrap(),
+            FileStatus::Verified
+        );
+        // The stamp skips the hash next time
+        assert!(stamp_path(Path::new(&path)).exists());
+        assert_eq!(
+            check_file(Path::new(&path), Some(HELLO)).unwrap(),
+            FileStatus::Verified
+        );
+        assert_eq!(
+            check_file(Path::new(&path), None).unwrap(),
+            FileStatus::Unchecked
+        );
+
+        let truncated = fixture("truncated.bin", "hello");
+        assert_eq!(
+            check_file(Path::new(&truncated), Some(HELLO)).unwrap(),
+            FileStatus::WrongSize {
+                expected: 12,
+                actual: 5
+            }
+        );
+
+        // Same size, other bytes; the stale stamp of the old contents doesn't count
+        let wrong = FileDigest {
+            sha256: "0000000000000000000000000000000000000000000000000000000000000000",
+            ..HELLO
+        };
+        let status = check_file(Path::new(&path), Some(wrong)).unwrap();
+        assert!(matches!(&status, FileStatus::WrongHash { actual, .. } if actual == HELLO.sha256));
+        assert!(!status.is_usable());
+
+        let missing = Path::new(&path).with_file_name("missing.bin");
+        assert_eq!(
+            check_file(&missing, Some(HELLO)).unwrap(),
+            FileStatus::Missing
+        );
+    }
+
+    #[test]
+    fn bad_files_are_deleted_good_ones_kept() {
+        let good = fixture("good.bin", "hello world\n");
+        discard_if_corrupt(&good, Some(HELLO)).unwrap();
+        verify_download(&good, Some(HELLO)).unwrap();
+        assert!(Path::new(&good).exists());
+
+        let bad = fixture("bad.bin", "hello");
+        discard_if_corrupt(&bad, Some(HELLO)).unwrap();
+        assert!(!Path::new(&bad).exists());
+
+        let bad = fixture("bad-download.bin", "hello");
+        let message = verify_download(&bad, Some(HELLO)).unwrap_err();
+        assert!(message.contains("5 bytes, expected 12"), "{}", message);
+        assert!(!Path::new(&bad).exists());
+
+        // Missing files are left for the download
+        discard_if_corrupt(&bad, Some(HELLO)).unwrap();
+    }
+
+    #[tokio::test]
+    async fn damaged_cache_files_are_reported_then_replaced() {
+        let dir = Path::new(&fixture("0.onnx", "truncated"))
+            .parent()
+            .unwrap()
+            .to_path_buf();
+        fs::write(dir.join("0.bin"), "truncated").unwrap();
+        let builder = TtsEngine::builder().cache_dir(&dir);
+
+        let status = builder.verify_cache().unwrap();
+        assert!(!status.is_ready());
+        assert_eq!(
+            status.model,
+            FileStatus::WrongSize {
+                expected: MODEL_DIGEST.size,
+                actual: 9
+            }
+        );
+        assert!(matches!(
+            builder.clone().build_cached(),
+            Err(KokoroError::ModelMissing(message)) if message.contains("damaged")
+        ));
+
+        // Explicit paths are the caller's files
+        let (model, voices) = (dir.join("0.onnx"), dir.join("0.bin"));
+        let explicit = builder
+            .clone()
+            .paths(model.to_str().unwrap(), voices.to_str().unwrap());
+        assert!(explicit.verify_cache().unwrap().is_ready());
+
+        // An open circuit keeps the re-download offline; the bad files are gone
+        let now = std::time::SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        crate::retry::RetryState {
+            consecutive_failures: 5,
+            last_attempt: now,
+            circuit_open_until: Some(now + 3600),
+        }
+        .save(&dir)
+        .unwrap();
+        assert!(builder.build().await.unwrap().is_fallback());
+        assert!(!model.exists() && !voices.exists());
+    }
+
+    #[test]
+    fn only_the_bundled_files_in_the_cache_are_pinned() {
+        // KOKORO_*_URL aren't set under `cargo test`
+        let (model, voices) = TtsEngine::builder().cached_digests();
+        assert_eq!(model, Some(MODEL_DIGEST));
+        assert_eq!(voices.map(|d| d.size), Some(28_214_398));
+
+        let full = TtsEngine::builder().voice_pack(VoicePack::Full);
+        assert_eq!(full.cached_digests(), (Some(MODEL_DIGEST), None));
+        let explicit = TtsEngine::builder().paths("m.onnx", "v.bin");
+        assert_eq!(explicit.cached_digests(), (None, None));
+    }
+}
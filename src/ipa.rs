@@ -0,0 +1,188 @@
+//! Synthesis from hand-written IPA, bypassing espeak
+//!
+//! espeak guesses at names, invented words and foreign snippets; when the
+//! right pronunciation is already known, `TtsEngine::synthesize_phonemes`
+//! takes it as IPA and feeds it to the model as is: padded with `$$$` like
+//! espeak output, tokenized against the model vocabulary, rendered with the
+//! options' voice, speed and gain. Unlike `tokenize`, characters the model
+//! has no token for are an error listing them, not silent token 0s.
+
+use crate::rng;
+use crate::{amplify_audio, KokoroError, SynthesizeOptions, TtsEngine, DEFAULT_VOICE};
+
+/// Most tokens the model takes in one pass, padding included
+pub const MAX_PHONEME_TOKENS: usize = 510;
+
+const PADDING: &str = "$$$";
+
+impl TtsEngine {
+    /// Synthesize IPA phonemes directly, without espeak
+    ///
+    /// Whitespace at the ends and any `$` padding already there are replaced
+    /// by the usual `$$$`. `opts.lang`, the caps policy and punctuation
+    /// weights don't apply (there is no text to normalize); voice, speed,
+    /// speed mode, gain and style temperature do. Characters outside the
+    /// model vocabulary, empty input and input over `MAX_PHONEME_TOKENS`
+    /// are `KokoroError::InvalidInput`.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use kokoro_tiny::{SynthesizeOptions, TtsEngine};
+    ///
+    /// # async fn demo() -> Result<(), kokoro_tiny::KokoroError> {
+    /// let tts = TtsEngine::new().await?;
+    /// let audio = tts.synthesize_phonemes("kˈokəɹˌo", SynthesizeOptions::new().voice("af_sky"))?;
+    /// tts.save_wav("kokoro.wav", &audio)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn synthesize_phonemes(
+        &self,
+        ipa: &str,
+        opts: SynthesizeOptions,
+    ) -> Result<Vec<f32>, KokoroError> {
+        if self.is_fallback() {
+            return self.fallback_audio(ipa);
+        }
+        let tokens = self.tokenize_phonemes(ipa)?;
+        let voice = opts.voice.as_deref().unwrap_or(DEFAULT_VOICE);
+        let seed = if opts.style_temperature > 0.0 {
+            opts.style_seed.unwrap_or_else(rng::random_seed)
+        } else {
+            0
+        };
+        let style = self.varied_style(voice, opts.style_temperature, seed)?;
+        let speed = self.engine_speed(opts.speed, opts.speed_mode);
+
+        let audio = self
+            .warnings
+            .record(|| self.run_inference(tokens, style, speed))
+            .0?;
+        Ok(if opts.gain != 1.0 {
+            amplify_audio(&audio, opts.gain)
+        } else {
+            audio
+        })
+    }
+
+    /// Token ids for IPA input, padding added; errors on unknown characters
+    ///
+    /// # Example
+    /// ```
+    /// use kokoro_tiny::TtsEngine;
+    ///
+    /// let tts = TtsEngine::without_model();
+    /// let tokens = tts.tokenize_phonemes("həlˈoʊ")?;
+    /// assert_eq!(tokens, tts.tokenize("$$$həlˈoʊ$$$"));
+    ///
+    /// let err = tts.tokenize_phonemes("hel#o").unwrap_err();
+    /// assert!(err.to_string().contains("'#'"));
+    /// # Ok::<(), kokoro_tiny::KokoroError>(())
+    /// ```
+    pub fn tokenize_phonemes(&self, ipa: &str) -> Result<Vec<i64>, KokoroError> {
+        let ipa = ipa.trim().trim_matches('$').trim();
+        if ipa.is_empty() {
+            return Err(KokoroError::InvalidInput(
+                "No phonemes provided for synthesis".to_string(),
+            ));
+        }
+
+        let mut unknown: Vec<char> = Vec::new();
+        for c in ipa.chars() {
+            if !self.vocab.contains_key(&c) && !unknown.contains(&c) {
+                unknown.push(c);
+            }
+        }
+        if !unknown.is_empty() {
+            let listed: Vec<String> = unknown
+                .iter()
+                .map(|c| format!("'{}' (U+{:04X})", c, u32::from(*c)))
+                .collect();
+            return Err(KokoroError::InvalidInput(format!(
+                "Phonemes not in the model vocabulary: {}",
+                listed.join(", ")
+            )));
+        }
+
+        let tokens = self.tokenize(&format!("{}{}{}", PADDING, ipa, PADDING));
+        if tokens.len() > MAX_PHONEME_TOKENS {
+            return Err(KokoroError::InvalidInput(format!(
+                "{} phoneme tokens (padding included), the model takes at most {}",
+                tokens.len(),
+                MAX_PHONEME_TOKENS
+            )));
+        }
+        Ok(tokens)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+
+    use crate::voices::STYLE_DIM;
+
+    #[test]
+    fn phonemes_skip_espeak_and_keep_the_options() {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let mut engine = TtsEngine::with_test_voices(HashMap::from([(
+            "am_adam".to_string(),
+            vec![0.2; STYLE_DIM],
+        )]));
+        engine.mock_inference = Some({
+            let seen = seen.clone();
+            Arc::new(move |tokens: &[i64], style: &[f32], speed: f32| {
+                seen.lock()
+                    .unwrap()
+                    .push((tokens.to_vec(), style[0], speed));
+                Ok(vec![0.25; tokens.len()])
+            })
+        });
+
+        let opts = SynthesizeOptions::new().voice("am_adam").gain(2.0);
+        let audio = engine
+            .synthesize_phonemes(" $kˈokəɹˌo$ ", opts.clone())
+            .unwrap();
+
+        let (tokens, style, speed) = seen.lock().unwrap().pop().unwrap();
+        assert_eq!(tokens, engine.tokenize("$$$kˈokəɹˌo$$$"));
+        assert_eq!(style, 0.2);
+        assert_eq!(speed, engine.engine_speed(opts.speed, opts.speed_mode));
+        assert_eq!(audio.len(), tokens.len());
+        assert!(audio.iter().all(|s| *s > 0.25));
+
+        assert!(matches!(
+            engine.synthesize_phonemes("k", SynthesizeOptions::new().voice("af_nope")),
+            Err(KokoroError::VoiceNotFound { .. })
+        ));
+    }
+
+    #[test]
+    fn unknown_symbols_and_empty_input_are_errors() {
+        let engine = TtsEngine::without_model();
+        match engine.tokenize_phonemes("kokoro#ココロ") {
+            Err(KokoroError::InvalidInput(message)) => {
+                assert!(message.contains("'#' (U+0023)"), "{}", message);
+                assert!(
+                    message.contains("'コ'") && message.contains("'ロ'"),
+                    "{}",
+                    message
+                );
+                // Each symbol listed once
+                assert_eq!(message.matches("'コ'").count(), 1, "{}", message);
+            }
+            other => panic!("expected InvalidInput, got {:?}", other),
+        }
+        assert!(engine.tokenize_phonemes("  $$$ ").is_err());
+
+        let long = "a".repeat(MAX_PHONEME_TOKENS);
+        assert!(engine.tokenize_phonemes(&long).is_err());
+        let fits = "a".repeat(MAX_PHONEME_TOKENS - 2 * PADDING.len());
+        assert_eq!(
+            engine.tokenize_phonemes(&fits).unwrap().len(),
+            MAX_PHONEME_TOKENS
+        );
+    }
+}
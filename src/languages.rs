@@ -0,0 +1,473 @@
+//! Which `lang` codes the model can actually be fed
+//!
+//! Any code espeak knows gets phonemized, but Kokoro only has tokens for the
+//! IPA symbols in its vocabulary; anything else is tokenized as padding and
+//! comes out as mumbling. The table below lists, for each language we claim,
+//! the symbols espeak-ng emits for it and which of those the vocabulary lacks
+//! (worked out offline; `tests::table_matches_vocab` keeps it honest).
+//!
+//! Coverage is not quality: a fully covered language can still carry an
+//! English accent with the stock voices.
+
+use serde::Serialize;
+
+use crate::KokoroError;
+
+/// How well a language's phonemes map onto the model vocabulary
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum LanguageSupport {
+    /// Every symbol espeak emits has a token
+    Full,
+    /// Speakable, but these symbols are dropped (e.g. nasal vowels lose their nasality)
+    Partial { missing_symbols: Vec<char> },
+    /// Not in the table; output is likely gibberish
+    Unsupported,
+}
+
+struct Language {
+    code: &'static str,
+    // IPA symbols espeak-ng emits for this language
+    inventory: &'static str,
+    // Symbols of `inventory` missing from the vocabulary
+    missing: &'static [char],
+}
+
+// Combining tilde: French, Portuguese and Hindi nasal vowels
+const NASAL: char = '\u{303}';
+
+const LANGUAGES: &[Language] = &[
+    Language {
+        code: "en",
+        inventory: "ɑɐæəɚɛɜɪiʊuʌɔoeaɹɾlmnŋpbtdkɡfvθðszʃʒhwjʧʤʔᵻxːˈˌ",
+        missing: &[],
+    },
+    Language {
+        code: "en-us",
+        inventory: "ɑɐæəɚɛɜɪiʊuʌɔoeaɹɾlmnŋpbtdkɡfvθðszʃʒhwjʧʤʔᵻːˈˌ",
+        missing: &[],
+    },
+    Language {
+        code: "en-gb",
+        inventory: "ɑɒɐæəɛɜɪiʊuʌɔoeaɹlmnŋpbtdkɡfvθðszʃʒhwjʧʤʔᵻxːˈˌ",
+        missing: &[],
+    },
+    Language {
+        code: "es",
+        inventory: "aeiouβðɣɲɾrxθʎʝjwlmnŋpbtdkɡfsʧzˈˌ",
+        missing: &[],
+    },
+    Language {
+        code: "fr",
+        inventory: "aɑeɛiouyøœəɔ\u{303}ʁɥwjlmnɲŋpbtdkɡfvszʃʒˈˌː",
+        missing: &[NASAL],
+    },
+    Language {
+        code: "de",
+        inventory: "aɐeɛɪiɔoøœʊuʏyəɜçxʁʀʔŋpbtdkɡfvszʃʒhjlmnˈˌː",
+        missing: &[],
+    },
+    Language {
+        code: "it",
+        inventory: "aeɛioɔuwjlʎmnɲŋɾrpbtdkɡfvszʃʒʧʤˈˌː",
+        missing: &[],
+    },
+    Language {
+        code: "pt",
+        inventory: "aɐeɛiɨoɔu\u{303}wjlɫʎʁɾmnɲpbtdkɡfvszʃʒˈˌː",
+        missing: &[NASAL],
+    },
+    Language {
+        code: "pt-br",
+        inventory: "aɐeɛiɪoɔuʊ\u{303}wjlʎʁɾhxmnɲpbtdkɡfvszʃʒʧʤˈˌː",
+        missing: &[NASAL],
+    },
+    Language {
+        code: "hi",
+        inventory: "əaɪiʊueɛoɔ\u{303}kɡŋʧʤɲʈɖɳtdnpbmjɾlʋʃʂsɦʰɽːˈˌ",
+        missing: &[NASAL],
+    },
+];
+
+// Voice name prefix letter (`ef_dora` -> 'e') and the language it was
+// recorded in, as named in the upstream voice list
+const VOICE_PREFIXES: &[(char, &str)] = &[
+    ('a', "en-us"),
+    ('b', "en-gb"),
+    ('e', "es"),
+    ('f', "fr"),
+    ('h', "hi"),
+    ('i', "it"),
+    ('j', "ja"),
+    ('p', "pt-br"),
+    ('z', "cmn"),
+];
+
+/// Language a voice speaks, from its name prefix (`"jf_alpha"` -> `"ja"`)
+///
+/// For a mix, the first component decides. Synthesis uses this when no
+/// `lang` is given for a non-English voice, so a Spanish voice reads Spanish
+/// and a Japanese one fails with an "Unsupported language" error instead of
+/// mumbling English phonemes.
+pub fn voice_language(voice: &str) -> Option<&'static str> {
+    let (prefix, _) = voice.trim().split_once('_')?;
+    let mut letters = prefix.chars();
+    let (letter, gender) = (letters.next()?, letters.next()?);
+    if letters.next().is_some() || !matches!(gender, 'f' | 'm') {
+        return None;
+    }
+    VOICE_PREFIXES
+        .iter()
+        .find(|(p, _)| *p == letter)
+        .map(|(_, code)| *code)
+}
+
+// The language of `voice` when `lang` is a wildly different one. English is
+// fine either way: English voices read other languages passably (German has
+// no voices of its own) and every voice reads English, which is how the
+// Japanese and Mandarin ones get used at all. A Spanish voice reading French
+// is a mistake.
+pub(crate) fn voice_conflict(voice: &str, lang: &str) -> Option<&'static str> {
+    let voice_lang = voice_language(voice)?;
+    let base = |code: &str| match code.split(['-', '_']).next().unwrap_or_default() {
+        "zh" => "cmn".to_string(),
+        primary => primary.to_lowercase(),
+    };
+    let (voice_base, base) = (base(voice_lang), base(lang));
+    (voice_base != "en" && base != "en" && voice_base != base).then_some(voice_lang)
+}
+
+// Error for a voice asked to read another language
+pub(crate) fn conflict_error(voice: &str, voice_lang: &str, lang: &str) -> String {
+    format!(
+        "Voice '{}' speaks '{}', not '{}' (leave lang unset to use the voice's language, \
+         pick a voice for '{}', or use SynthesizeOptions::lenient_language to try anyway)",
+        voice, voice_lang, lang, lang
+    )
+}
+
+/// Support level for an espeak language code such as `"fr"` or `"en-GB"`
+///
+/// A regional variant without its own entry (`"es-419"`) falls back to its
+/// base language.
+pub fn language_support(code: &str) -> LanguageSupport {
+    match lookup(code) {
+        Some(language) if language.missing.is_empty() => LanguageSupport::Full,
+        Some(language) => LanguageSupport::Partial {
+            missing_symbols: language.missing.to_vec(),
+        },
+        None => LanguageSupport::Unsupported,
+    }
+}
+
+/// Whether `code` is shaped like an espeak language code (`"es"`, `"pt-br"`,
+/// `"en_US"`, `"cmn-latn-pinyin"`), supported or not
+///
+/// A primary subtag of two or three letters, then any number of subtags of up
+/// to eight letters or digits, separated by `-` or `_`.
+pub fn is_language_code(code: &str) -> bool {
+    let mut subtags = code.split(['-', '_']);
+    let primary = subtags.next().unwrap_or_default();
+    (2..=3).contains(&primary.len())
+        && primary.chars().all(|c| c.is_ascii_alphabetic())
+        && subtags.all(|tag| {
+            (1..=8).contains(&tag.len()) && tag.chars().all(|c| c.is_ascii_alphanumeric())
+        })
+}
+
+// Malformed codes are refused before espeak sees them
+pub(crate) fn check_code(code: &str) -> Result<(), KokoroError> {
+    if is_language_code(code) {
+        Ok(())
+    } else {
+        Err(KokoroError::InvalidInput(format!(
+            "'{}' is not a language code (expected e.g. \"en\", \"es\" or \"pt-br\")",
+            code
+        )))
+    }
+}
+
+/// Codes with full or partial support
+pub fn supported_languages() -> Vec<String> {
+    LANGUAGES.iter().map(|l| l.code.to_string()).collect()
+}
+
+/// Error for a code that isn't in the table
+pub(crate) fn unsupported_error(code: &str) -> String {
+    format!(
+        "Unsupported language '{}': its phonemes don't map onto the Kokoro vocabulary \
+         (supported: {}; use SynthesizeOptions::lenient_language to try anyway)",
+        code,
+        supported_languages().join(", ")
+    )
+}
+
+fn lookup(code: &str) -> Option<&'static Language> {
+    let code = code.trim().to_lowercase().replace('_', "-");
+    let base = code.split('-').next().unwrap_or_default();
+    LANGUAGES
+        .iter()
+        .find(|l| l.code == code)
+        .or_else(|| LANGUAGES.iter().find(|l| l.code == base))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{KokoroError, SynthesisWarning, SynthesizeOptions, TtsEngine};
+
+    #[test]
+    fn table_matches_vocab() {
+        let engine = TtsEngine::mock(100);
+        for language in LANGUAGES {
+            let mut missing: Vec<char> = language
+                .inventory
+                .chars()
+                .filter(|&c| engine.tokenize(&c.to_string()) == [0])
+                .collect();
+            missing.dedup();
+            assert_eq!(
+                missing, language.missing,
+                "stale entry for {}",
+                language.code
+            );
+        }
+
+        for code in ["en", "es", "fr", "de", "it", "pt"] {
+            assert_ne!(
+                language_support(code),
+                LanguageSupport::Unsupported,
+                "{}",
+                code
+            );
+        }
+        assert_eq!(language_support("en-GB"), LanguageSupport::Full);
+        assert_eq!(language_support("es_419"), LanguageSupport::Full);
+        assert_eq!(
+            language_support("fr"),
+            LanguageSupport::Partial {
+                missing_symbols: vec![NASAL]
+            }
+        );
+        assert_eq!(language_support("ja"), LanguageSupport::Unsupported);
+    }
+
+    #[test]
+    fn unsupported_language_is_an_error_unless_lenient() {
+        let engine = TtsEngine::mock(100);
+        let opts = SynthesizeOptions::new().lang("ja");
+
+        let err = engine
+            .synthesize_with("Konnichiwa", opts.clone())
+            .unwrap_err();
+        assert!(matches!(&err, KokoroError::UnsupportedLanguage(code) if code == "ja"));
+        let message = err.to_string();
+        assert!(message.contains("Unsupported language 'ja'"), "{}", message);
+        assert!(message.contains("en-gb"), "{}", message);
+
+        let audio = engine
+            .synthesize_with("Konnichiwa", opts.lenient_language(true))
+            .unwrap();
+        assert!(!audio.is_empty());
+        assert_eq!(
+            engine.last_warnings(),
+            [SynthesisWarning::UnsupportedLanguage {
+                lang: "ja".to_string()
+            }]
+        );
+
+        // Partial coverage always goes ahead, with a warning
+        engine
+            .synthesize_with("Bonjour", SynthesizeOptions::new().lang("fr"))
+            .unwrap();
+        assert_eq!(
+            engine.last_warnings(),
+            [SynthesisWarning::LanguageCoverage {
+                lang: "fr".to_string(),
+                missing_symbols: vec![NASAL],
+            }]
+        );
+    }
+
+    #[test]
+    fn malformed_codes_never_reach_espeak() {
+        for code in [
+            "en",
+            "es",
+            "cmn",
+            "pt-br",
+            "en_US",
+            "es-419",
+            "cmn-latn-pinyin",
+        ] {
+            assert!(is_language_code(code), "{}", code);
+        }
+        for code in [
+            "",
+            "e",
+            "english",
+            "e n",
+            "en-",
+            "1a",
+            "en-toolongsubtag",
+            "es/mx",
+        ] {
+            assert!(!is_language_code(code), "{:?}", code);
+        }
+
+        // Lenient or not, and before any phonemizing
+        let engine = TtsEngine::mock(100);
+        for lenient in [false, true] {
+            let opts = SynthesizeOptions::new()
+                .lang("english")
+                .lenient_language(lenient);
+            match engine.synthesize_with("Hello", opts) {
+                Err(KokoroError::InvalidInput(message)) => {
+                    assert!(message.contains("'english'"), "{}", message)
+                }
+                other => panic!("expected InvalidInput, got {:?}", other),
+            }
+        }
+
+        let engine = TtsEngine::mock(100);
+        engine.import_voice("ef_dora", vec![0.2; 256]).unwrap();
+        let opts = SynthesizeOptions::new()
+            .voice("ef_dora")
+            .lang("es")
+            .speed(0.9);
+        assert!(!engine.synthesize_with("Hola", opts).unwrap().is_empty());
+    }
+
+    #[test]
+    fn voice_prefixes_pick_the_language() {
+        assert_eq!(voice_language("af_sky"), Some("en-us"));
+        assert_eq!(voice_language("bm_george"), Some("en-gb"));
+        assert_eq!(voice_language("ef_dora"), Some("es"));
+        assert_eq!(voice_language("zf_xiaobei"), Some("cmn"));
+        assert_eq!(voice_language("jf_alpha.6+af_sky.4"), Some("ja"));
+        assert_eq!(voice_language("narrator"), None);
+        assert_eq!(voice_language("my_voice"), None);
+        assert_eq!(voice_language("qf_new"), None);
+    }
+
+    #[test]
+    fn non_english_voices_default_to_their_language() {
+        let engine = TtsEngine::mock(100);
+        for voice in ["ef_dora", "jf_alpha", "zf_xiaobei"] {
+            engine.import_voice(voice, vec![0.1; 256]).unwrap();
+        }
+
+        // Spanish is in the table: synthesizes, no language warnings
+        engine
+            .synthesize("Hola, ¿qué tal?", Some("ef_dora"), None, None)
+            .unwrap();
+        assert!(engine.last_warnings().is_empty());
+
+        // Japanese and Mandarin aren't: the usual error, or a warning if lenient
+        let err = engine
+            .synthesize("Konnichiwa", Some("jf_alpha"), None, None)
+            .unwrap_err();
+        assert!(matches!(&err, KokoroError::UnsupportedLanguage(code) if code == "ja"));
+        let err = engine
+            .synthesize("Ni hao", Some("zf_xiaobei"), None, None)
+            .unwrap_err();
+        assert!(matches!(&err, KokoroError::UnsupportedLanguage(code) if code == "cmn"));
+        let opts = SynthesizeOptions::new()
+            .voice("jf_alpha")
+            .lenient_language(true);
+        engine.synthesize_with("Konnichiwa", opts).unwrap();
+        assert_eq!(
+            engine.last_warnings(),
+            [SynthesisWarning::UnsupportedLanguage {
+                lang: "ja".to_string()
+            }]
+        );
+
+        // An explicit lang still wins
+        engine
+            .synthesize_with(
+                "Konnichiwa",
+                SynthesizeOptions::new().voice("jf_alpha").lang("en"),
+            )
+            .unwrap();
+    }
+
+    // Languages espeak was called with since the last call
+    fn espeak_langs() -> Vec<String> {
+        crate::ESPEAK_LANGS.with(|langs| std::mem::take(&mut *langs.borrow_mut()))
+    }
+
+    #[test]
+    fn voices_read_their_own_language_unless_told_otherwise() {
+        let engine = TtsEngine::mock(100);
+        engine.import_voice("ef_dora", vec![0.2; 256]).unwrap();
+        assert_eq!(engine.language_for_voice("ef_dora"), "es");
+        espeak_langs();
+
+        engine
+            .synthesize("Hola. ¿Qué tal?", Some("ef_dora"), None, None)
+            .unwrap();
+        let langs = espeak_langs();
+        assert!(
+            !langs.is_empty() && langs.iter().all(|lang| lang == "es"),
+            "{:?}",
+            langs
+        );
+
+        // English either way is fine, another language for a Spanish voice isn't
+        let dora = SynthesizeOptions::new().voice("ef_dora");
+        engine
+            .synthesize_with("Hello", dora.clone().lang("en"))
+            .unwrap();
+        engine
+            .synthesize_with("Hola", SynthesizeOptions::new().lang("es"))
+            .unwrap();
+        espeak_langs();
+        match engine.synthesize_with("Bonjour", dora.clone().lang("fr")) {
+            Err(KokoroError::InvalidInput(message)) => {
+                assert!(message.contains("'ef_dora' speaks 'es'"), "{}", message)
+            }
+            other => panic!("expected InvalidInput, got {:?}", other),
+        }
+        assert!(espeak_langs().is_empty(), "refused before phonemizing");
+        engine
+            .synthesize_with("Bonjour", dora.lang("fr").lenient_language(true))
+            .unwrap();
+        assert!(engine
+            .last_warnings()
+            .contains(&SynthesisWarning::VoiceLanguageMismatch {
+                voice: "ef_dora".to_string(),
+                voice_lang: "es".to_string(),
+                lang: "fr".to_string(),
+            }));
+        assert_eq!(espeak_langs(), ["fr"]);
+    }
+
+    #[test]
+    fn segments_switch_language_within_one_call() {
+        let mut engine = TtsEngine::mock(100);
+        engine.import_voice("ef_dora", vec![0.2; 256]).unwrap();
+        let english = SynthesizeOptions::new();
+        let spanish = SynthesizeOptions::new().voice("ef_dora");
+
+        let next = engine
+            .synthesize_with("Next stop", english.clone())
+            .unwrap();
+        let plaza = engine
+            .synthesize_with("Plaza de España", spanish.clone())
+            .unwrap();
+        engine.clear_cache();
+        espeak_langs();
+
+        let audio = engine
+            .synthesize_segments(vec![
+                ("Next stop".to_string(), english),
+                ("Plaza de España".to_string(), spanish),
+            ])
+            .unwrap();
+        assert_eq!(audio.len(), next.len() + plaza.len());
+        assert_eq!(espeak_langs(), ["en", "es"]);
+
+        assert!(engine.synthesize_segments(Vec::new()).is_err());
+    }
+}
@@ -0,0 +1,518 @@
+//! Pronunciations set per deployment for names espeak gets wrong
+//!
+//! An entry maps a word to either a respelling, which is read instead of the
+//! word ("Kubernetes" -> "koo ber net eez"), or IPA, which goes straight into
+//! the phoneme stream in place of what espeak would make of the word
+//! ("Nguyen" -> "wˈɪn"). Words match whole and case-insensitively unless the
+//! entry is case-sensitive; a case-sensitive entry wins over one that isn't.
+//! A possessive "'s" is kept ("Nguyen's").
+//!
+//! Lexicon files are TSV, a word and its pronunciation per line with IPA
+//! between slashes and an optional third column `case` for case-sensitive
+//! entries (`\t` is a tab):
+//!
+//! ```text
+//! # word\tpronunciation
+//! Kubernetes\tkoo ber net eez
+//! Nguyen\t/wˈɪn/
+//! Aye\t/ˈaɪ/\tcase
+//! ```
+//!
+//! or JSON (a `.json` file), a list of entries:
+//!
+//! ```text
+//! [
+//!   {"word": "Kubernetes", "text": "koo ber net eez"},
+//!   {"word": "Aye", "ipa": "ˈaɪ", "case_sensitive": true}
+//! ]
+//! ```
+
+use std::collections::HashMap;
+use std::ops::Range;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{KokoroError, TtsEngine};
+
+/// How a lexicon word is read
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Pronunciation {
+    /// Read this text instead, e.g. "koo ber net eez"
+    Text(String),
+    /// These phonemes, bypassing espeak
+    Ipa(String),
+}
+
+/// One line of a lexicon file
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LexiconEntry {
+    pub word: String,
+    #[serde(flatten)]
+    pub pronunciation: Pronunciation,
+    #[serde(default)]
+    pub case_sensitive: bool,
+}
+
+/// Words with a pronunciation of their own
+///
+/// # Example
+/// ```
+/// use kokoro_tiny::lexicon::{Lexicon, Pronunciation};
+///
+/// let mut lexicon = Lexicon::default();
+/// lexicon.insert("Nguyen", Pronunciation::Ipa("wˈɪn".into()));
+/// lexicon.insert_case_sensitive("Aye", Pronunciation::Text("eye".into()));
+/// assert!(lexicon.get("NGUYEN").is_some());
+/// assert!(lexicon.get("aye").is_none());
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Lexicon {
+    exact: HashMap<String, Pronunciation>,
+    folded: HashMap<String, Pronunciation>, // Keyed by the lower-cased word
+}
+
+// A part of a text, for phonemizing
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum Piece<'a> {
+    Text(&'a str),
+    Ipa(String),
+}
+
+impl Lexicon {
+    /// Read `word`, in any case, as `pronunciation`
+    pub fn insert(&mut self, word: &str, pronunciation: Pronunciation) {
+        self.folded.insert(word.to_lowercase(), pronunciation);
+    }
+
+    /// Read `word`, written exactly so, as `pronunciation`
+    pub fn insert_case_sensitive(&mut self, word: &str, pronunciation: Pronunciation) {
+        self.exact.insert(word.to_string(), pronunciation);
+    }
+
+    /// The pronunciation of `word`, if it has one
+    pub fn get(&self, word: &str) -> Option<&Pronunciation> {
+        self.exact
+            .get(word)
+            .or_else(|| self.folded.get(&word.to_lowercase()))
+    }
+
+    /// Number of entries
+    pub fn len(&self) -> usize {
+        self.exact.len() + self.folded.len()
+    }
+
+    /// Whether there are no entries
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Remove every entry
+    pub fn clear(&mut self) {
+        self.exact.clear();
+        self.folded.clear();
+    }
+
+    /// Entries of a lexicon file's contents, TSV or (`json`) JSON
+    ///
+    /// # Example
+    /// ```
+    /// use kokoro_tiny::lexicon::{Lexicon, Pronunciation};
+    ///
+    /// let entries = Lexicon::parse("Nguyen\t/wˈɪn/\nAye\teye\tcase\n", false)?;
+    /// assert_eq!(entries[0].pronunciation, Pronunciation::Ipa("wˈɪn".into()));
+    /// assert!(entries[1].case_sensitive);
+    /// # Ok::<(), kokoro_tiny::KokoroError>(())
+    /// ```
+    pub fn parse(contents: &str, json: bool) -> Result<Vec<LexiconEntry>, KokoroError> {
+        if json {
+            return serde_json::from_str(contents)
+                .map_err(|e| KokoroError::InvalidInput(format!("Invalid lexicon: {}", e)));
+        }
+
+        let mut entries = Vec::new();
+        for (idx, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let columns: Vec<&str> = line.split('\t').map(str::trim).collect();
+            let (word, said, flag) = match columns[..] {
+                [word, said] => (word, said, None),
+                [word, said, flag] => (word, said, Some(flag)),
+                _ => {
+                    return Err(KokoroError::InvalidInput(format!(
+                        "Lexicon line {}: expected a word and a pronunciation separated by a tab",
+                        idx + 1
+                    )))
+                }
+            };
+            if flag.is_some_and(|flag| flag != "case") {
+                return Err(KokoroError::InvalidInput(format!(
+                    "Lexicon line {}: unknown flag {:?}, only \"case\" is allowed",
+                    idx + 1,
+                    flag.unwrap_or_default()
+                )));
+            }
+            let pronunciation = match said.strip_prefix('/').and_then(|s| s.strip_suffix('/')) {
+                Some(ipa) => Pronunciation::Ipa(ipa.to_string()),
+                None => Pronunciation::Text(said.to_string()),
+            };
+            entries.push(LexiconEntry {
+                word: word.to_string(),
+                pronunciation,
+                case_sensitive: flag.is_some(),
+            });
+        }
+        Ok(entries)
+    }
+
+    // Whether `word` (or its possessive) has an entry
+    pub(crate) fn contains(&self, word: &str) -> bool {
+        self.lookup(word).is_some()
+    }
+
+    // `text` with respelled words replaced; `None` when there were none
+    pub(crate) fn respell(&self, text: &str) -> Option<String> {
+        let hits = self.find(text, |said| matches!(said, Pronunciation::Text(_)));
+        if hits.is_empty() {
+            return None;
+        }
+        let mut out = String::with_capacity(text.len());
+        let mut done = 0;
+        for (range, said) in hits {
+            out.push_str(&text[done..range.start]);
+            out.push_str(&said);
+            done = range.end;
+        }
+        out.push_str(&text[done..]);
+        Some(out)
+    }
+
+    // `text` split around its IPA words
+    pub(crate) fn pieces<'a>(&self, text: &'a str) -> Vec<Piece<'a>> {
+        let mut pieces = Vec::new();
+        let mut done = 0;
+        for (range, ipa) in self.find(text, |said| matches!(said, Pronunciation::Ipa(_))) {
+            if range.start > done {
+                pieces.push(Piece::Text(&text[done..range.start]));
+            }
+            pieces.push(Piece::Ipa(ipa));
+            done = range.end;
+        }
+        if done < text.len() || pieces.is_empty() {
+            pieces.push(Piece::Text(&text[done..]));
+        }
+        pieces
+    }
+
+    // Entry for a word, with whether it was found as a possessive
+    fn lookup(&self, word: &str) -> Option<(&Pronunciation, bool)> {
+        if let Some(said) = self.get(word) {
+            return Some((said, false));
+        }
+        let stem = word
+            .strip_suffix("'s")
+            .or_else(|| word.strip_suffix("’s"))?;
+        self.get(stem).map(|said| (said, true))
+    }
+
+    // Byte ranges of the lexicon words in `text` whose entry passes `wanted`,
+    // with what to put there. A whitespace-separated word matches as a whole
+    // ("kokoro-tiny"), else the words inside it do ("Kubernetes-based").
+    fn find(
+        &self,
+        text: &str,
+        wanted: impl Fn(&Pronunciation) -> bool,
+    ) -> Vec<(Range<usize>, String)> {
+        let mut hits = Vec::new();
+        if self.is_empty() {
+            return hits;
+        }
+        let mut hit = |range: Range<usize>| {
+            let (said, possessive) = self.lookup(&text[range.clone()])?;
+            if !wanted(said) {
+                return Some(());
+            }
+            let said = match (said, possessive) {
+                (Pronunciation::Text(respelled), false) => respelled.clone(),
+                (Pronunciation::Text(respelled), true) => format!("{}'s", respelled),
+                (Pronunciation::Ipa(ipa), false) => ipa.clone(),
+                (Pronunciation::Ipa(ipa), true) => format!("{}z", ipa),
+            };
+            hits.push((range, said));
+            Some(())
+        };
+
+        for (start, token) in word_spans(text, char::is_whitespace) {
+            let core = token.trim_matches(|c: char| !c.is_alphanumeric());
+            if core.is_empty() {
+                continue;
+            }
+            let core_start = start
+                + (token.len()
+                    - token
+                        .trim_start_matches(|c: char| !c.is_alphanumeric())
+                        .len());
+            if hit(core_start..core_start + core.len()).is_some() {
+                continue;
+            }
+            for (offset, word) in
+                word_spans(core, |c| !c.is_alphanumeric() && c != '\'' && c != '’')
+            {
+                let word = word.trim_end_matches(['\'', '’']);
+                if !word.is_empty() {
+                    let from = core_start + offset;
+                    hit(from..from + word.len());
+                }
+            }
+        }
+        hits
+    }
+}
+
+// Non-empty runs of `text` between separators, with their byte offsets
+fn word_spans(text: &str, separator: impl Fn(char) -> bool) -> Vec<(usize, &str)> {
+    let mut spans = Vec::new();
+    let mut start = None;
+    for (idx, c) in text.char_indices() {
+        match (separator(c), start) {
+            (true, Some(from)) => {
+                spans.push((from, &text[from..idx]));
+                start = None;
+            }
+            (false, None) => start = Some(idx),
+            _ => {}
+        }
+    }
+    if let Some(from) = start {
+        spans.push((from, &text[from..]));
+    }
+    spans
+}
+
+impl TtsEngine {
+    /// Read `word` as `pronunciation` from now on, in any case
+    ///
+    /// IPA is checked against the model vocabulary; an unknown symbol is
+    /// `KokoroError::InvalidInput`. Entries win over the acronym table (see
+    /// `add_acronym`). Drops cached results, which were rendered with the old
+    /// reading.
+    ///
+    /// # Example
+    /// ```
+    /// use kokoro_tiny::{Pronunciation, TtsEngine};
+    ///
+    /// let mut tts = TtsEngine::without_model();
+    /// tts.add_pronunciation("Kubernetes", Pronunciation::Text("koo ber net eez".into()))?;
+    /// tts.add_pronunciation("Nguyen", Pronunciation::Ipa("wˈɪn".into()))?;
+    /// assert!(tts.add_pronunciation("Aye", Pronunciation::Ipa("#!".into())).is_err());
+    /// # Ok::<(), kokoro_tiny::KokoroError>(())
+    /// ```
+    pub fn add_pronunciation(
+        &mut self,
+        word: &str,
+        pronunciation: Pronunciation,
+    ) -> Result<(), KokoroError> {
+        self.add_lexicon_entry(LexiconEntry {
+            word: word.to_string(),
+            pronunciation,
+            case_sensitive: false,
+        })
+    }
+
+    /// `add_pronunciation` for `word` written exactly so ("Aye" but not "aye")
+    pub fn add_pronunciation_case_sensitive(
+        &mut self,
+        word: &str,
+        pronunciation: Pronunciation,
+    ) -> Result<(), KokoroError> {
+        self.add_lexicon_entry(LexiconEntry {
+            word: word.to_string(),
+            pronunciation,
+            case_sensitive: true,
+        })
+    }
+
+    /// Add every entry of a lexicon file (see `lexicon`), returning how many
+    ///
+    /// Files ending in `.json` are read as JSON, anything else as TSV. Nothing
+    /// is added when any entry is invalid.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use kokoro_tiny::TtsEngine;
+    ///
+    /// let mut tts = TtsEngine::without_model();
+    /// let added = tts.load_lexicon("names.tsv")?;
+    /// println!("{} pronunciations", added);
+    /// # Ok::<(), kokoro_tiny::KokoroError>(())
+    /// ```
+    pub fn load_lexicon(&mut self, path: impl AsRef<Path>) -> Result<usize, KokoroError> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path)?;
+        let json = path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("json"));
+        let entries = Lexicon::parse(&contents, json).map_err(|e| {
+            let message = format!("{}: {}", path.display(), e);
+            e.with_message(message)
+        })?;
+
+        for entry in &entries {
+            self.check_pronunciation(entry)?;
+        }
+        let added = entries.len();
+        for entry in entries {
+            self.insert_pronunciation(entry);
+        }
+        self.clear_cache();
+        Ok(added)
+    }
+
+    /// Remove every pronunciation added so far
+    pub fn clear_lexicon(&mut self) {
+        self.lexicon.clear();
+        self.clear_cache();
+    }
+
+    /// The pronunciations this engine uses
+    pub fn lexicon(&self) -> &Lexicon {
+        &self.lexicon
+    }
+
+    fn add_lexicon_entry(&mut self, entry: LexiconEntry) -> Result<(), KokoroError> {
+        self.check_pronunciation(&entry)?;
+        self.insert_pronunciation(entry);
+        self.clear_cache();
+        Ok(())
+    }
+
+    fn insert_pronunciation(&mut self, entry: LexiconEntry) {
+        let word = entry.word.trim();
+        if entry.case_sensitive {
+            self.lexicon
+                .insert_case_sensitive(word, entry.pronunciation);
+        } else {
+            self.lexicon.insert(word, entry.pronunciation);
+        }
+    }
+
+    fn check_pronunciation(&self, entry: &LexiconEntry) -> Result<(), KokoroError> {
+        let word = entry.word.trim();
+        if word.is_empty() || word.contains(char::is_whitespace) {
+            return Err(KokoroError::InvalidInput(format!(
+                "Lexicon words are single words, got {:?}",
+                entry.word
+            )));
+        }
+        match &entry.pronunciation {
+            Pronunciation::Text(text) if text.trim().is_empty() => Err(KokoroError::InvalidInput(
+                format!("No pronunciation given for {:?}", word),
+            )),
+            Pronunciation::Text(_) => Ok(()),
+            Pronunciation::Ipa(ipa) => self.tokenize_phonemes(ipa).map(|_| ()).map_err(|e| {
+                let message = format!("{:?}: {}", word, e);
+                e.with_message(message)
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lexicon() -> Lexicon {
+        let mut lexicon = Lexicon::default();
+        lexicon.insert("Kubernetes", Pronunciation::Text("koo ber net eez".into()));
+        lexicon.insert("Nguyen", Pronunciation::Ipa("wˈɪn".into()));
+        lexicon.insert("kokoro-tiny", Pronunciation::Text("kokoro tiny".into()));
+        lexicon.insert_case_sensitive("Aye", Pronunciation::Text("eye".into()));
+        lexicon
+    }
+
+    #[test]
+    fn words_match_whole_and_in_any_case_unless_sensitive() {
+        let lexicon = lexicon();
+        let cases = [
+            (
+                "Deploy to KUBERNETES now.",
+                Some("Deploy to koo ber net eez now."),
+            ),
+            ("(Kubernetes-based)", Some("(koo ber net eez-based)")),
+            (
+                "Kubernetes's scheduler",
+                Some("koo ber net eez's scheduler"),
+            ),
+            ("Try kokoro-tiny today", Some("Try kokoro tiny today")),
+            ("Ask Aye, not aye.", Some("Ask eye, not aye.")),
+            ("Kubernetesish", None),
+            ("Nguyen", None), // IPA isn't respelled
+        ];
+        for (text, expected) in cases {
+            assert_eq!(lexicon.respell(text).as_deref(), expected, "{}", text);
+        }
+    }
+
+    #[test]
+    fn ipa_words_are_split_out() {
+        let lexicon = lexicon();
+        assert_eq!(
+            lexicon.pieces("Call Nguyen's office, then NGUYEN."),
+            vec![
+                Piece::Text("Call "),
+                Piece::Ipa("wˈɪnz".into()),
+                Piece::Text(" office, then "),
+                Piece::Ipa("wˈɪn".into()),
+                Piece::Text("."),
+            ]
+        );
+        assert_eq!(
+            lexicon.pieces("Nobody here"),
+            vec![Piece::Text("Nobody here")]
+        );
+        assert_eq!(Lexicon::default().pieces(""), vec![Piece::Text("")]);
+    }
+
+    #[test]
+    fn lexicon_files_are_tsv_or_json() {
+        let tsv = "# names\nKubernetes\tkoo ber net eez\n\nNguyen\t/wˈɪn/\nAye\teye\tcase\n";
+        let entries = Lexicon::parse(tsv, false).unwrap();
+        let json = r#"[
+            {"word": "Kubernetes", "text": "koo ber net eez"},
+            {"word": "Nguyen", "ipa": "wˈɪn"},
+            {"word": "Aye", "text": "eye", "case_sensitive": true}
+        ]"#;
+        assert_eq!(Lexicon::parse(json, true).unwrap(), entries);
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[1].pronunciation, Pronunciation::Ipa("wˈɪn".into()));
+
+        let err = Lexicon::parse("ok\tfine\nbroken line\n", false).unwrap_err();
+        assert!(err.to_string().contains("line 2"), "{}", err);
+        assert!(Lexicon::parse("Aye\teye\tloud\n", false).is_err());
+    }
+
+    #[test]
+    fn engines_load_and_clear_lexicons() {
+        let mut engine = TtsEngine::without_model();
+        let dir = std::env::temp_dir().join(format!("kokoro_lexicon_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("names.tsv");
+        std::fs::write(&path, "Nguyen\t/wˈɪn/\nKubernetes\tkoo ber net eez\n").unwrap();
+
+        assert_eq!(engine.load_lexicon(&path).unwrap(), 2);
+        assert_eq!(engine.lexicon().len(), 2);
+
+        // One bad symbol and nothing from the file is added
+        std::fs::write(&path, "Aye\t/ˈaɪ/\nBad\t/#/\n").unwrap();
+        let err = engine.load_lexicon(&path).unwrap_err();
+        assert!(err.to_string().contains("'#'"), "{}", err);
+        assert!(engine.lexicon().get("Aye").is_none());
+
+        engine.clear_lexicon();
+        assert!(engine.lexicon().is_empty());
+        std::fs::remove_dir_all(dir).ok();
+    }
+}
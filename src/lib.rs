@@ -5,30 +5,62 @@
 //!
 //! # Example
 //! ```no_run
-//! use kokoro_tiny::TtsEngine;
+//! use kokoro_tiny::{SynthesizeOptions, TtsEngine};
 //!
 //! #[tokio::main]
-//! async fn main() {
+//! async fn main() -> Result<(), String> {
 //!     // Initialize with auto-download of model if needed
-//!     let mut tts = TtsEngine::new().await.unwrap();
+//!     let tts = TtsEngine::new().await?;
 //!
-//!     // Generate speech
-//!     let audio = tts.synthesize("Hello world!", None).unwrap();
+//!     // Generate speech with the default voice
+//!     let audio = tts.synthesize("Hello world!", None, None, None)?;
+//!     tts.save_wav("output.wav", &audio)?;
 //!
-//!     // Save to file
-//!     tts.save_wav("output.wav", &audio).unwrap();
+//!     // Or pick voice, speed and gain
+//!     let opts = SynthesizeOptions::new().voice("af_bella").speed(1.1).gain(1.5);
+//!     let audio = tts.synthesize_with("Build complete", opts)?;
+//!     tts.save_wav("build.wav", &audio)?;
+//!     Ok(())
 //! }
 //! ```
+//!
+//! Examples that need the model are compile-checked only; the rest, and
+//! every `rust` block in the README, run as doctests.
+//!
+//! # Thread safety
+//!
+//! Every public type is `Send + Sync`; `tests/thread_safety.rs` checks this
+//! at compile time, so a change that breaks it fails the build.
+//!
+//! - `TtsEngine` can be shared behind an `Arc`. Synthesis, voice
+//!   import/removal, warnings, caches and playback take `&self`, so threads
+//!   can render on one engine without an outer `Mutex`: text planning, caches
+//!   and audio post-processing run in parallel; espeak and the ONNX session
+//!   are single-flight.
+//!   `last_warnings` is the call that finished last; [`collect_warnings`]
+//!   returns the warnings of one call. Settings (`set_speed_scale`,
+//!   `set_output`, ...) still take `&mut self`.
+//! - Playback from any thread or engine is serialized by one process-wide
+//!   coordinator (see [`coordinator`]).
+//! - `StreamingTts::speak_stream` returns a `Send` future.
+//! - `McpServer` only locks stdin/stdout inside `run`, so it can be built on
+//!   one thread and run on another.
+
+#![deny(rustdoc::broken_intra_doc_links)]
 
 use std::collections::HashMap;
+use std::ffi::OsString;
 use std::fs::{self, File};
-use std::io::Write;
+use std::io::{BufWriter, Write};
 use std::path::{Path, PathBuf};
-use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, SystemTime};
 
 use espeak_rs::text_to_phonemes;
+use serde::{Deserialize, Serialize};
+use unicode_segmentation::UnicodeSegmentation;
 
 // MEM-8 Integration module
 pub mod mem8_bridge;
@@ -41,57 +73,349 @@ pub mod mem8_voice;
 
 // MCP Server module for AI collaboration
 pub mod mcp_server;
-use ndarray::{ArrayBase, IxDyn, OwnedRepr};
-use ndarray_npy::NpzReader;
-use ort::{
-    session::{builder::GraphOptimizationLevel, Session, SessionInputValue, SessionInputs},
-    value::{Tensor, Value},
-};
 
+// Multi-voice dialogue synthesis with per-speaker stems
+pub mod dialogue;
+
+// Download backoff and circuit breaker
+pub mod retry;
+pub use retry::RetryPolicy;
+
+// Text normalization ahead of phonemization
+pub mod normalize;
+pub use normalize::CapsPolicy;
+
+// Spoken numbers with units ("4 GB" -> "four gigabytes")
+pub mod units;
+pub use units::UnitWords;
+
+// Spoken numbers, amounts, ordinals and years ("$5.99", "3rd", "in 1984")
+pub mod numbers;
+
+// Spoken URLs, email addresses and paths
+pub mod links;
+pub use links::UrlStyle;
+
+// Hashes, MAC addresses and IDs read character by character
+pub mod spelling;
+pub use spelling::SpellPattern;
+
+// Acronyms spelled letter by letter or read as words ("SQL", "NASA")
+pub mod acronyms;
+pub use acronyms::{Acronyms, Spoken};
+
+// Per-deployment pronunciations: respellings or IPA ("Nguyen" -> "wˈɪn")
+pub mod lexicon;
+pub use lexicon::{Lexicon, LexiconEntry, Pronunciation};
+use lexicon::Piece;
+
+// Look-ahead peak limiter for gain above 1.0
+pub mod limiter;
+
+// Tones and silence for the audio-processing tests
+#[cfg(test)]
+mod test_signal;
+
+// Fades against clicks at the edges of a clip, chunk crossfades, stereo panning
+pub mod audio;
+pub use audio::Channels;
+
+// Windowed-sinc sample rate conversion
+pub mod resample;
+pub use resample::resample;
+
+// Time-stretch (WSOLA) and pitch shift of finished audio
+pub mod stretch;
+pub use stretch::{pitch_shift, time_stretch};
+
+// Biquad EQ: rumble removal, presence, telephone band
+pub mod filters;
+pub use filters::FilterPreset;
+
+// Speech over a ducked background bed (podcast-style intros)
+pub mod mix;
+pub use mix::mix_with_bed;
+
+// Cutting finished audio into segments at its pauses
+pub mod split;
+pub use split::{split_on_silence, split_on_silence_ranges};
+
+// Headerless s16le/f32le samples for piping into ffmpeg or aplay
+pub mod pcm;
+pub use pcm::{to_pcm_s16le, write_pcm, PcmFormat};
+
+// WAV files written front to back, for save_wav and encode
+mod wav;
+
+// 8kHz µ-law and A-law for PBXes and RTP
+pub mod telephony;
+pub use telephony::Law;
+
+// Reading WAV files (and MP3, Ogg Vorbis and FLAC with playback) into buffers
+pub mod load;
 #[cfg(feature = "playback")]
-use rodio::{Decoder, OutputStream, Sink};
+pub use load::{load_audio, load_audio_with};
+pub use load::{decode_wav_bytes, decode_wav_bytes_with, load_wav, load_wav_with, LoadOptions};
+
+// AudioFormat: every encoder behind one TtsEngine::encode
+pub mod format;
+pub use format::AudioFormat;
+
+// MP3 encoding with bitrate, quality and ID3 tags (Mp3Options)
+#[cfg(feature = "mp3")]
+mod mp3;
+#[cfg(feature = "mp3")]
+pub use mp3::Mp3Options;
+
+// Ogg Opus container for save_opus
+#[cfg(feature = "opus-format")]
+mod opus;
+
+// Punctuation weights for pause tuning
+pub mod prosody;
+pub use prosody::PunctuationWeights;
 
-// Cursor is used for in-memory audio operations, not just playback
-use std::io::Cursor;
+// Chunk plans and partial results for resumable long-form synthesis
+pub mod resume;
+pub use resume::{PartialSynthesis, ResumeFile, SynthesisPlan};
 
+// Newline-delimited JSON job protocol for the CLI
+pub mod batch;
+
+// Read-aloud progress for resuming long documents
+pub mod reading;
+
+// Byte-bounded cache of synthesis results
+pub mod cache;
+pub use cache::{CacheStats, DEFAULT_CACHE_BUDGET_BYTES};
+use cache::{CachedAudio, ResultCache};
+
+// On-disk cache of rendered long-form chunks
+pub mod chunk_cache;
+pub use chunk_cache::DEFAULT_CHUNK_CACHE_BYTES;
+use chunk_cache::ChunkStore;
+
+// Pause or duck speech when the user starts talking
+pub mod barge_in;
+pub use barge_in::{BargeIn, BargeInAction};
+
+// Output buffer presets and the playback loop
+pub mod playback;
+pub use playback::{BufferPreset, PlaybackHandle, PlaybackInfo, PlaybackOptions};
+
+// Swappable audio output backends (cpal, rodio, null for tests)
+pub mod output;
+pub use output::{AudioOutput, AudioStreamOut, NullOutput, OutputSpec, StreamInfo};
+
+// Process-wide playback coordination (no talking over each other)
+pub mod coordinator;
+pub use coordinator::{is_audio_active, stop_playback, BusyPolicy};
+
+// Gapless queue of utterances on one output stream
+pub mod speaker;
+pub use speaker::{Speaker, SpeakerEvent};
+
+// Lowering other audio while speaking, restored to the exact saved volume
+#[cfg(feature = "ducking")]
+pub mod ducking;
 #[cfg(feature = "ducking")]
-use enigo::{Enigo, Key, Keyboard, Settings};
+pub use ducking::{duck_audio, DuckGuard, SystemVolume};
+
+// Copy-on-write voice table, safe to change during synthesis, and
+// per-source voice assignment
+pub mod voices;
+pub use voices::{VoiceAssigner, STYLE_DIM};
+
+// Seeded style noise for take-to-take variation
+pub mod variation;
+// SplitMix64 and random seeds, shared by variation, A/B layouts and privacy salts
+mod rng;
+use voices::{VoiceMap, VoiceTable};
+
+// Global hotkeys (push-to-interrupt)
+pub mod hotkey;
+
+// Emoji left out, named or read as a pause
+pub mod emoji;
+pub use emoji::EmojiMode;
+
+// Markdown stripped for reading documents aloud
+pub mod markdown;
+pub use markdown::Preprocess;
+
+// Inline [pause] and *emphasis* markup for plain text
+pub mod markup;
+
+// SSML input: breaks, prosody, say-as and sub
+#[cfg(feature = "ssml")]
+pub mod ssml;
+
+// Named chunk/gap/crossfade presets
+pub mod pacing;
+pub use pacing::{Pacing, PacingPreset, PauseConfig};
+
+// Which espeak languages map onto the model vocabulary
+pub mod languages;
+pub use languages::LanguageSupport;
+
+// A/B corpus renders for regression listening (kokoro-ab)
+pub mod ab;
+
+// Redaction of spoken text in logs, results and state files
+pub mod privacy;
+pub use privacy::{display_text, set_redact_text};
+
+// Chunk-by-chunk synthesis and playback that starts with the first chunk
+pub mod chunk_stream;
+pub use chunk_stream::{
+    ChunkStream, LongSpeech, SpeechStopper, StreamEvent, StreamReport, StreamedChunk,
+};
+
+// Robotic built-in voice for when there is no model (--no-download)
+pub mod formant;
+
+// Exact sample/millisecond conversions for audio lengths
+pub mod duration;
+pub use duration::AudioDuration;
+
+// Voice pack downloads and the NPZ layouts they come in (f16/f32, any rank)
+pub mod voice_pack;
+pub use voice_pack::VoicePack;
+
+// fp32 or int8 model, and the input/output names each export uses
+pub mod model_variant;
+pub use model_variant::ModelVariant;
+
+// Structured errors for the public API (KokoroError)
+pub mod error;
+pub use error::KokoroError;
+
+// Synthesis from hand-written IPA (no espeak)
+pub mod ipa;
+
+// Many texts at once, in parallel (synthesize_batch)
+pub mod parallel;
+
+// Samples that carry their sample rate (AudioBuffer)
+pub mod audio_buffer;
+pub use audio_buffer::{AudioBuffer, AudioSource};
+
+// Speech length estimates without running the model
+pub mod estimate;
+
+// Word timings for captions (WordTiming)
+pub mod timestamps;
+pub use timestamps::WordTiming;
+
+// Progress reports for long-form synthesis
+pub mod progress;
+pub use progress::{ProgressStage, SynthesisProgress};
+
+// Phonemize/inference timings and real-time factor (synthesize_with_stats)
+pub mod stats;
+pub use stats::{AudioStats, ChunkStats, SynthesisStats};
+
+// Engine construction options and ONNX Runtime settings (EngineBuilder)
+pub mod builder;
+pub use builder::{EngineBuilder, GraphOptimizationLevel, SessionOptions};
+
+// Hardware acceleration: CUDA, CoreML and DirectML (features of the same names)
+pub mod providers;
+pub use providers::ExecutionProvider;
+
+// SHA-256 checks for downloaded and cached model files
+pub mod integrity;
+pub use integrity::{verify_cache, CacheStatus, FileStatus};
+
+// Model downloads: mirror lists, per-attempt timeouts, retries
+pub mod download;
+use download::{fetch_model_files, Sources};
+use model_variant::ModelIo;
+
+// The README's code blocks, compiled (and where possible run) as doctests
+#[cfg(doctest)]
+#[doc = include_str!("../README.md")]
+pub struct ReadmeDoctests;
+use ort::{
+    session::{Session, SessionInputValue, SessionInputs},
+    value::{Tensor, TensorElementType, Value},
+};
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
 
 // Constants - Model files stored in GitHub LFS
-const MODEL_URL: &str = "https://github.com/8b-is/kokoro-tiny/raw/main/models/0.onnx";
-const VOICES_URL: &str = "https://github.com/8b-is/kokoro-tiny/raw/main/models/0.bin";
+pub(crate) const MODEL_URL: &str = "https://github.com/8b-is/kokoro-tiny/raw/main/models/0.onnx";
 const SAMPLE_RATE: u32 = 24000; // Kokoro model sample rate
 const DEFAULT_VOICE: &str = "af_sky";
 const DEFAULT_SPEED: f32 = 1.0; // User-facing normal speed (maps to model 0.65)
 const DEFAULT_LANG: &str = "en";
-const SPEED_SCALE: f32 = 0.65; // Model speed = user speed * this scale factor
+const SPEED_SCALE: f32 = 0.65; // Default for TtsEngine::set_speed_scale
+const OPUS_BITRATE: i32 = 24_000; // Default for TtsEngine::set_opus_bitrate
+const MAX_INLINE_DURATION: Duration = Duration::from_secs(60); // Longest audio to_data_url encodes
 const LONG_TEXT_THRESHOLD: usize = 120;
 const MAX_CHARS_PER_CHUNK: usize = 180;
 const CHUNK_CROSSFADE_MS: usize = 45;
 const MIN_ENGINE_SPEED: f32 = 0.35;
 const MAX_ENGINE_SPEED: f32 = 2.2;
+const MIN_PITCH: f32 = 0.5;
+const MAX_PITCH: f32 = 2.0;
+const SILENCE_THRESHOLD: f32 = 0.003;
+const TRIM_MARGIN_SAMPLES: usize = SAMPLE_RATE as usize / 50; // 20 ms kept by trim_silence
 const PAD_TOKEN: char = '$'; // Padding token for beginning/end of phonemes
+const ELLIPSIS_PAUSE_MS: u32 = 500;
 
 // Fallback audio message - "Excuse me, I lost my voice. Give me time to get it back."
 // This is a pre-generated minimal WAV file that can play while downloading
 const FALLBACK_MESSAGE: &[u8] = include_bytes!("../assets/fallback.wav");
 
-// Get cache directory for shared model storage - keeping it minimal like Hue wants!
-fn get_cache_dir() -> PathBuf {
-    let base = std::env::var("HOME").ok()
-        .or_else(|| std::env::var("USERPROFILE").ok())
-        .unwrap_or_else(|| ".".to_string());
+/// Where model files and other state are kept unless told otherwise
+///
+/// The first of: `KOKORO_CACHE_DIR`, `$XDG_CACHE_HOME/kokoro-tiny`,
+/// `%LOCALAPPDATA%\kokoro-tiny` on Windows, and `~/.cache/k`. Set
+/// `KOKORO_CACHE_DIR` where HOME is read-only (containers, systemd
+/// DynamicUser); `EngineBuilder::cache_dir` overrides all of them for one
+/// engine.
+pub fn cache_dir() -> PathBuf {
+    let var = |name: &str| std::env::var_os(name).filter(|value| !value.is_empty());
+    cache_dir_from(var, cfg!(windows))
+}
+
+// `cache_dir` over any environment, so tests needn't touch the real one
+fn cache_dir_from(var: impl Fn(&str) -> Option<OsString>, windows: bool) -> PathBuf {
+    if let Some(dir) = var("KOKORO_CACHE_DIR") {
+        return PathBuf::from(dir);
+    }
+    // The XDG spec says to ignore relative paths
+    if let Some(dir) = var("XDG_CACHE_HOME").filter(|dir| Path::new(dir).is_absolute()) {
+        return Path::new(&dir).join("kokoro-tiny");
+    }
+    if windows {
+        if let Some(dir) = var("LOCALAPPDATA") {
+            return Path::new(&dir).join("kokoro-tiny");
+        }
+    }
+    let base = var("HOME")
+        .or_else(|| var("USERPROFILE"))
+        .unwrap_or_else(|| OsString::from("."));
     Path::new(&base).join(".cache").join("k")
 }
 
+// Chunk cache lives next to the model it was rendered with
+fn chunk_dir_for(model_path: &str) -> PathBuf {
+    Path::new(model_path)
+        .parent()
+        .map(|dir| dir.join("chunks"))
+        .unwrap_or_else(|| cache_dir().join("chunks"))
+}
+
 #[cfg(feature = "playback")]
-fn cache_path() -> PathBuf {
-    get_cache_dir().join("audio_device.txt")
+fn cache_path(cache_dir: &Path) -> PathBuf {
+    cache_dir.join("audio_device.txt")
 }
 
 #[cfg(feature = "playback")]
-fn load_cached_device() -> Option<String> {
-    let path = cache_path();
+fn load_cached_device(cache_dir: &Path) -> Option<String> {
+    let path = cache_path(cache_dir);
     if path.exists() {
         if let Ok(s) = std::fs::read_to_string(&path) {
             let trimmed = s.trim().to_string();
@@ -104,10 +428,10 @@ fn load_cached_device() -> Option<String> {
 }
 
 #[cfg(feature = "playback")]
-fn save_cached_device(name: Option<&str>) -> Result<(), String> {
-    let path = cache_path();
+fn save_cached_device(cache_dir: &Path, name: Option<&str>) -> Result<(), String> {
+    let path = cache_path(cache_dir);
     if let Some(n) = name {
-        std::fs::create_dir_all(path.parent().unwrap_or(&get_cache_dir()))
+        std::fs::create_dir_all(cache_dir)
             .map_err(|e| format!("Failed to create cache dir: {}", e))?;
         std::fs::write(&path, n).map_err(|e| format!("Failed to write cache: {}", e))?;
     } else {
@@ -147,19 +471,272 @@ fn pick_preferred_device(devices: &[String]) -> Option<String> {
         .cloned()
 }
 
+/// Stand-in for the ONNX session in unit tests: (tokens, style, speed) -> samples
+#[cfg(test)]
+pub(crate) type MockInference =
+    Arc<dyn Fn(&[i64], &[f32], f32) -> Result<Vec<f32>, String> + Send + Sync>;
+
+// Warnings of the synthesis calls running on this thread, innermost last
+thread_local! {
+    static CALL_WARNINGS: std::cell::RefCell<Vec<Vec<SynthesisWarning>>> =
+        const { std::cell::RefCell::new(Vec::new()) };
+}
+
+/// Run `render` and collect the warnings of the synthesis it does on this thread
+///
+/// Unlike `TtsEngine::last_warnings`, the result belongs to this call alone,
+/// however many other threads render on the same engine meanwhile. Calls
+/// can nest; an outer call collects the warnings of the inner ones too.
+///
+/// # Example
+/// ```no_run
+/// use kokoro_tiny::{collect_warnings, SynthesizeOptions, TtsEngine};
+///
+/// # async fn demo() -> Result<(), kokoro_tiny::KokoroError> {
+/// let tts = TtsEngine::new().await?;
+/// let (audio, warnings) = collect_warnings(|| {
+///     tts.synthesize_with("WARNING: DISK FULL", SynthesizeOptions::new())
+/// });
+/// for warning in warnings {
+///     eprintln!("{}", warning);
+/// }
+/// tts.save_wav("alert.wav", &audio?)?;
+/// # Ok(())
+/// # }
+/// ```
+pub fn collect_warnings<T>(render: impl FnOnce() -> T) -> (T, Vec<SynthesisWarning>) {
+    // Closes the call even if `render` panics
+    struct Call(usize);
+    impl Drop for Call {
+        fn drop(&mut self) {
+            CALL_WARNINGS.with(|calls| calls.borrow_mut().truncate(self.0));
+        }
+    }
+
+    let call = Call(CALL_WARNINGS.with(|calls| {
+        let mut calls = calls.borrow_mut();
+        calls.push(Vec::new());
+        calls.len() - 1
+    }));
+    let result = render();
+    let warnings = CALL_WARNINGS.with(|calls| {
+        let mut calls = calls.borrow_mut();
+        let warnings = calls.pop().unwrap_or_default();
+        if let Some(outer) = calls.last_mut() {
+            outer.extend(warnings.iter().cloned());
+        }
+        warnings
+    });
+    drop(call);
+    (result, warnings)
+}
+
+// Warnings of the most recent synthesis call to finish on the engine
+#[derive(Default)]
+struct WarningLog(Mutex<Vec<SynthesisWarning>>);
+
+impl WarningLog {
+    // One synthesis call: collects its warnings and makes them the engine's last
+    fn record<T>(&self, call: impl FnOnce() -> T) -> (T, Vec<SynthesisWarning>) {
+        let (result, warnings) = collect_warnings(call);
+        self.set(warnings.clone());
+        (result, warnings)
+    }
+
+    // Goes to the call running on this thread; outside of one, straight to the log
+    fn push(&self, warning: SynthesisWarning) {
+        let warning = warning.redacted();
+        let unclaimed = CALL_WARNINGS.with(|calls| match calls.borrow_mut().last_mut() {
+            Some(call) => {
+                call.push(warning);
+                None
+            }
+            None => Some(warning),
+        });
+        if let (Some(warning), Ok(mut log)) = (unclaimed, self.0.lock()) {
+            log.push(warning);
+        }
+    }
+
+    fn set(&self, warnings: Vec<SynthesisWarning>) {
+        if let Ok(mut log) = self.0.lock() {
+            *log = warnings;
+        }
+    }
+
+    fn get(&self) -> Vec<SynthesisWarning> {
+        self.0.lock().map(|log| log.clone()).unwrap_or_default()
+    }
+}
+
 /// Main TTS engine struct
 pub struct TtsEngine {
-    session: Option<Arc<Mutex<Session>>>,
-    voices: HashMap<String, Vec<f32>>,
+    // The model and where it runs (see providers); try_recover swaps it in
+    session: RwLock<Option<(Arc<Mutex<Session>>, ExecutionProvider)>>,
+    spare_sessions: Mutex<Vec<Arc<Mutex<Session>>>>, // More sessions, for synthesize_batch
+    voices: VoiceTable,
     vocab: HashMap<char, i64>,
-    fallback_mode: bool,
+    fallback_mode: AtomicBool,
+    formant_fallback: bool, // Fallback reads the text (formant voice) instead of the excuse message
+    warnings: WarningLog,                   // Warnings from the most recent synthesis
+    cache: Mutex<ResultCache>,              // Finished audio keyed by text + settings
+    chunk_store: ChunkStore,                // Rendered chunks on disk (opt-in per call)
+    model_files: Option<(String, String)>,  // (model, voices) paths, kept for try_recover
+    retry_policy: RetryPolicy,
+    sources: Sources, // Where missing model files are downloaded from
+    cache_dir: PathBuf, // Engine state such as audio_device.txt (see `cache_dir`)
+    session_options: SessionOptions, // ONNX Runtime settings, kept for try_recover
+    variant: ModelVariant,              // Which model build is (or would be) loaded
+    speed_scale: f32, // Model speed per unit of natural speed
+    opus_bitrate: i32, // Bits per second of .opus files from save_audio
+    max_inline_duration: Duration, // Longest audio to_base64 and to_data_url accept
+    acronyms: Acronyms, // How acronyms are read (see add_acronym)
+    lexicon: Lexicon,   // Pronunciations of single words (see add_pronunciation)
     #[cfg(feature = "playback")]
     audio_device: Option<String>, // Selected audio device name
+    output: Option<Arc<dyn AudioOutput>>, // Set by set_output; else the device above
+    #[cfg(test)]
+    mock_inference: Option<MockInference>,
+}
+
+/// Non-fatal issues noticed while synthesizing
+///
+/// Warnings recorded while redacting (see [`privacy`]) hold
+/// `display_text` of the text instead of the text itself.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SynthesisWarning {
+    /// Text with nothing speakable (punctuation, emoji) was rendered as a pause
+    /// instead of being sent to the model
+    Unspeakable { text: String, pause_ms: u32 },
+    /// Mostly upper-case text was rewritten in sentence case (see `CapsPolicy`)
+    CapsNormalized { original: String, normalized: String },
+    /// The language's phonemes include symbols the model has no token for
+    LanguageCoverage { lang: String, missing_symbols: Vec<char> },
+    /// Synthesized anyway in an unsupported language (`lenient_language`)
+    UnsupportedLanguage { lang: String },
+    /// Synthesized anyway in a language other than the voice's own
+    /// (`lenient_language`)
+    VoiceLanguageMismatch { voice: String, voice_lang: String, lang: String },
+    /// An SSML element or attribute that isn't supported was ignored; its
+    /// text is still read
+    UnsupportedSsml { tag: String },
+    /// Characters with no model token (Cyrillic, box drawing, ...) reached the
+    /// model as padding, which comes out as noise; `positions` are char
+    /// offsets into `text`, the chunk as handed to the phonemizer
+    UnknownCharacters {
+        text: String,
+        characters: Vec<char>,
+        positions: Vec<usize>,
+    },
+    /// The model returned NaN or infinite samples; `count` of them were
+    /// replaced with silence
+    NonFiniteSamples { count: usize },
+}
+
+impl SynthesisWarning {
+    // Text fields through `display_text`, so a redacted warning keeps no text
+    fn redacted(self) -> Self {
+        if !privacy::redact_text() {
+            return self;
+        }
+        let hide = |text: String| privacy::display_text(&text).into_owned();
+        match self {
+            SynthesisWarning::Unspeakable { text, pause_ms } => SynthesisWarning::Unspeakable {
+                text: hide(text),
+                pause_ms,
+            },
+            SynthesisWarning::CapsNormalized { original, normalized } => {
+                SynthesisWarning::CapsNormalized {
+                    original: hide(original),
+                    normalized: hide(normalized),
+                }
+            }
+            SynthesisWarning::UnknownCharacters {
+                text,
+                characters,
+                positions,
+            } => SynthesisWarning::UnknownCharacters {
+                text: hide(text),
+                characters,
+                positions,
+            },
+            other => other,
+        }
+    }
+}
+
+impl std::fmt::Display for SynthesisWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SynthesisWarning::Unspeakable { text, pause_ms } => write!(
+                f,
+                "Nothing speakable in {:?}, rendered as {}ms pause",
+                text, pause_ms
+            ),
+            SynthesisWarning::CapsNormalized { original, normalized } => write!(
+                f,
+                "All-caps text {:?} read as {:?}",
+                original, normalized
+            ),
+            SynthesisWarning::LanguageCoverage { lang, missing_symbols } => write!(
+                f,
+                "Language '{}' is partially supported; {:?} dropped",
+                lang, missing_symbols
+            ),
+            SynthesisWarning::UnsupportedLanguage { lang } => write!(
+                f,
+                "Language '{}' is not supported; output may be unintelligible",
+                lang
+            ),
+            SynthesisWarning::VoiceLanguageMismatch {
+                voice,
+                voice_lang,
+                lang,
+            } => write!(
+                f,
+                "Voice '{}' speaks '{}' but the text is read as '{}'; expect a strong accent",
+                voice, voice_lang, lang
+            ),
+            SynthesisWarning::UnsupportedSsml { tag } => {
+                write!(f, "SSML {} is not supported and was ignored", tag)
+            }
+            SynthesisWarning::UnknownCharacters {
+                text,
+                characters,
+                positions,
+            } => write!(
+                f,
+                "No model token for {:?} in {:?} (at {:?}); expect glitches there",
+                characters, text, positions
+            ),
+            SynthesisWarning::NonFiniteSamples { count } => write!(
+                f,
+                "Model output had {} NaN or infinite samples, replaced with silence",
+                count
+            ),
+        }
+    }
+}
+
+/// One chunk of a dry run through the synthesis pipeline (see `phoneme_report`)
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ChunkReport {
+    pub index: usize,
+    pub text: String,
+    /// Text after normalization, as handed to the phonemizer
+    pub normalized: String,
+    /// Padded phoneme string fed to the model; `None` if rendered as a pause
+    pub phonemes: Option<String>,
+    pub tokens: Vec<i64>,
+    /// Phoneme characters missing from the vocabulary (tokenized as 0)
+    pub unknown_chars: Vec<char>,
+    pub pause_ms: Option<u32>,
 }
 
 /// Baby speech mode for mem8 - handles simple utterances
 pub struct BabyTts {
-    pub engine: TtsEngine,
+    // Shared with whatever else renders through it (see `shared_engine`)
+    engine: Arc<TtsEngine>,
     pub max_words: usize,
     pub voice: String,
     pub speed: f32,
@@ -167,15 +744,100 @@ pub struct BabyTts {
     pub lang: String
 }
 
+/// How `SynthesizeOptions::speed` becomes the speed fed to the model
+///
+/// The model's own 1.0 is noticeably rushed, so by default speeds are scaled:
+///
+/// ```text
+/// model speed = clamp(speed * speed_scale, 0.35, 2.2)   // Natural
+/// model speed = clamp(speed, 0.35, 2.2)                 // Model
+/// ```
+///
+/// `speed_scale` is 0.65 unless changed with `TtsEngine::set_speed_scale`.
+/// `TtsEngine::model_speed` gives the result for a set of options.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SpeedMode {
+    /// 1.0 is a natural speaking pace (model speed 0.65 by default)
+    #[default]
+    Natural,
+    /// The value goes to the model as is, e.g. to match other Kokoro front ends
+    Model,
+}
+
 /// Options builder for synthesis parameters
 ///
 /// Example: `tts.synthesize_with(text, SynthesizeOptions::default().voice("af_sky").speed(1.0))`
-#[derive(Clone, Debug)]
+///
+/// Deserializes from partial documents (missing fields keep their defaults),
+/// e.g. the option files of `kokoro-ab`. New options are added as fields, so
+/// build it with `new()` and the setters rather than a struct literal.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+#[non_exhaustive]
 pub struct SynthesizeOptions {
     pub voice: Option<String>,
     pub speed: f32,
     pub gain: f32,
-    pub lang: Option<String>
+    pub lang: Option<String>,
+    pub caps_policy: CapsPolicy,
+    pub chunk_cache: bool,
+    pub punctuation_weights: PunctuationWeights,
+    pub lenient_language: bool,
+    /// Refuse text with characters the model has no token for
+    pub strict_characters: bool,
+    pub pacing: Option<PacingPreset>,
+    /// Silence inserted at clause boundaries instead of the model's own pauses
+    pub pauses: Option<PauseConfig>,
+    pub speed_mode: SpeedMode,
+    pub extra_units: HashMap<String, UnitWords>,
+    /// Noise on the style vector, in units of the voices' spread (0.0 = off)
+    pub style_temperature: f32,
+    /// Seed for `style_temperature`; `None` picks a fresh one per render
+    pub style_seed: Option<u64>,
+    /// Pitch factor (1.0 = the voice's own), see `pitch`
+    pub pitch: f32,
+    /// Cut leading and trailing silence from the result
+    pub trim_silence: bool,
+    /// Fade at both ends of the result against clicks, 0 for none
+    pub edge_fade_ms: u32,
+    /// Tempo factor applied to the finished audio (1.0 = unchanged), see
+    /// `post_stretch`
+    pub post_stretch: f32,
+    /// Pitch shift applied to the finished audio, see `pitch_semitones`
+    pub pitch_semitones: f32,
+    /// EQ applied to the finished audio, see `filter`
+    pub filter: Option<FilterPreset>,
+    /// Sample rate of the returned audio (`None` = the model's 24kHz)
+    pub output_sample_rate: Option<u32>,
+    /// Mono, or interleaved stereo placed at a pan position
+    pub channels: Channels,
+    /// Longest chunk handed to the model, in chars (`None` = the pacing
+    /// preset's, else 180)
+    pub chunk_max_chars: Option<usize>,
+    /// Text longer than this many chars, or over three lines, is chunked
+    /// (`None` = 120)
+    pub chunk_threshold: Option<usize>,
+    /// Overlap between neighbouring chunks (`None` = the pacing preset's,
+    /// else 45 ms)
+    pub crossfade_ms: Option<u32>,
+    /// Silence between paragraphs (`None` = the pacing preset's, else 600 ms)
+    pub paragraph_gap_ms: Option<u32>,
+    /// Abbreviations whose period doesn't end a sentence, on top of the
+    /// built-in ones
+    pub abbreviations: Vec<String>,
+    /// Send the text to espeak as written, see `raw_text`
+    pub raw_text: bool,
+    /// How much of a URL is read out
+    pub url_style: UrlStyle,
+    /// Read `[pause]` and `*emphasis*` markers, see `markup`
+    pub markup: bool,
+    /// How emoji are read
+    pub emoji: EmojiMode,
+    /// Words read character by character, see `spelling`
+    pub spell_patterns: Vec<SpellPattern>,
+    /// Markup taken out before chunking, see `preprocess`
+    pub preprocess: Preprocess,
 }
 
 impl Default for SynthesizeOptions {
@@ -184,186 +846,1681 @@ impl Default for SynthesizeOptions {
             voice: None,
             speed: DEFAULT_SPEED,
             gain: 1.0,
-            lang: None
+            lang: None,
+            caps_policy: CapsPolicy::default(),
+            chunk_cache: false,
+            punctuation_weights: PunctuationWeights::default(),
+            lenient_language: false,
+            strict_characters: false,
+            pacing: None,
+            pauses: None,
+            speed_mode: SpeedMode::default(),
+            extra_units: HashMap::new(),
+            style_temperature: 0.0,
+            style_seed: None,
+            pitch: 1.0,
+            trim_silence: false,
+            edge_fade_ms: audio::DEFAULT_EDGE_FADE_MS,
+            post_stretch: 1.0,
+            pitch_semitones: 0.0,
+            filter: None,
+            output_sample_rate: None,
+            channels: Channels::Mono,
+            chunk_max_chars: None,
+            chunk_threshold: None,
+            crossfade_ms: None,
+            paragraph_gap_ms: None,
+            abbreviations: Vec::new(),
+            raw_text: false,
+            url_style: UrlStyle::default(),
+            markup: false,
+            emoji: EmojiMode::default(),
+            spell_patterns: Vec::new(),
+            preprocess: Preprocess::default(),
         }
     }
 }
 
 impl SynthesizeOptions {
     /// Create a new options builder (same as `Default::default()`).
+    ///
+    /// # Example
+    /// ```no_run
+    /// use kokoro_tiny::{SynthesizeOptions, TtsEngine};
+    ///
+    /// # async fn demo() -> Result<(), kokoro_tiny::KokoroError> {
+    /// let tts = TtsEngine::new().await?;
+    /// let audio = tts.synthesize_with("Build complete", SynthesizeOptions::new())?;
+    /// tts.play(audio, 0.8)?;
+    /// # Ok(())
+    /// # }
+    /// ```
     pub fn new() -> Self {
         Self::default()
     }
 
     /// Set voice name
+    ///
+    /// # Example
+    /// ```no_run
+    /// use kokoro_tiny::{SynthesizeOptions, TtsEngine};
+    ///
+    /// # async fn demo() -> Result<(), kokoro_tiny::KokoroError> {
+    /// let tts = TtsEngine::new().await?;
+    /// // A single voice or a weighted mix
+    /// let opts = SynthesizeOptions::new().voice("af_sky.6+af_bella.4");
+    /// let audio = tts.synthesize_with("Good morning", opts)?;
+    /// tts.play(audio, 0.8)?;
+    /// # Ok(())
+    /// # }
+    /// ```
     pub fn voice(mut self, voice: &str) -> Self {
         self.voice = Some(voice.to_string());
         self
     }
 
     /// Set user-facing speed (1.0 = normal)
+    ///
+    /// # Example
+    /// ```no_run
+    /// use kokoro_tiny::{SynthesizeOptions, TtsEngine};
+    ///
+    /// # async fn demo() -> Result<(), kokoro_tiny::KokoroError> {
+    /// let tts = TtsEngine::new().await?;
+    /// let opts = SynthesizeOptions::new().speed(1.2);
+    /// let audio = tts.synthesize_with("You have three new messages", opts)?;
+    /// tts.play(audio, 0.8)?;
+    /// # Ok(())
+    /// # }
+    /// ```
     pub fn speed(mut self, speed: f32) -> Self {
         self.speed = speed;
         self
     }
 
+    /// Set how `speed` maps to the model (default: `SpeedMode::Natural`)
+    ///
+    /// # Example
+    /// ```no_run
+    /// use kokoro_tiny::{SpeedMode, SynthesizeOptions, TtsEngine};
+    ///
+    /// # async fn demo() -> Result<(), kokoro_tiny::KokoroError> {
+    /// let tts = TtsEngine::new().await?;
+    /// // Pass 1.0 straight to the model, as other Kokoro front ends do
+    /// let opts = SynthesizeOptions::new().speed(1.0).speed_mode(SpeedMode::Model);
+    /// let audio = tts.synthesize_with("Same pace as the reference renders", opts)?;
+    /// tts.save_wav("reference.wav", &audio)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn speed_mode(mut self, mode: SpeedMode) -> Self {
+        self.speed_mode = mode;
+        self
+    }
+
     /// Set gain multiplier (1.0 = normal)
+    ///
+    /// # Example
+    /// ```no_run
+    /// use kokoro_tiny::{SynthesizeOptions, TtsEngine};
+    ///
+    /// # async fn demo() -> Result<(), kokoro_tiny::KokoroError> {
+    /// let tts = TtsEngine::new().await?;
+    /// let opts = SynthesizeOptions::new().gain(2.0);
+    /// let audio = tts.synthesize_with("Build failed on main", opts)?;
+    /// tts.play(audio, 0.8)?;
+    /// # Ok(())
+    /// # }
+    /// ```
     pub fn gain(mut self, gain: f32) -> Self {
         self.gain = gain;
         self
     }
-}
 
-impl TtsEngine {
-    /// Create a new TTS engine, downloading model files if necessary
-    /// Uses ~/.cache/k for shared model storage (minimal path!)
-    pub async fn new() -> Result<Self, String> {
-        let cache_dir = get_cache_dir();
-        let model_path = cache_dir.join("0.onnx");
-        let voices_path = cache_dir.join("0.bin");
+    /// Set the espeak language code (default: `"en"`, see `languages`)
+    ///
+    /// Synthesis rejects anything that isn't shaped like a language code
+    /// (`"english"`, `"e n"`) as `KokoroError::InvalidInput` before espeak
+    /// sees it, even with `lenient_language`.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use kokoro_tiny::{SynthesizeOptions, TtsEngine};
+    ///
+    /// # async fn demo() -> Result<(), kokoro_tiny::KokoroError> {
+    /// let tts = TtsEngine::new().await?;
+    /// let opts = SynthesizeOptions::new().voice("ef_dora").lang("es");
+    /// let audio = tts.synthesize_with("La próxima estación es Sol", opts)?;
+    /// tts.play(audio, 0.8)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn lang(mut self, lang: &str) -> Self {
+        self.lang = Some(lang.to_string());
+        self
+    }
 
-        Self::with_paths(
-            model_path.to_str().unwrap_or("0.onnx"),
-            voices_path.to_str().unwrap_or("0.bin"),
-        )
-        .await
+    /// Synthesize unsupported languages with a warning instead of failing
+    ///
+    /// # Example
+    /// ```no_run
+    /// use kokoro_tiny::{SynthesizeOptions, TtsEngine};
+    ///
+    /// # async fn demo() -> Result<(), kokoro_tiny::KokoroError> {
+    /// let tts = TtsEngine::new().await?;
+    /// // Japanese isn't covered by the model vocabulary; try anyway
+    /// let opts = SynthesizeOptions::new().lang("ja").lenient_language(true);
+    /// let audio = tts.synthesize_with("こんにちは", opts)?;
+    /// for warning in tts.last_warnings() {
+    ///     eprintln!("{}", warning);
+    /// }
+    /// tts.play(audio, 0.8)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn lenient_language(mut self, lenient: bool) -> Self {
+        self.lenient_language = lenient;
+        self
     }
 
-    /// Create a new TTS engine with custom model paths
-    pub async fn with_paths(model_path: &str, voices_path: &str) -> Result<Self, String> {
-        // Ensure cache directory exists
-        if let Some(parent) = Path::new(model_path).parent() {
-            fs::create_dir_all(parent)
-                .map_err(|e| format!("Failed to create cache directory: {}", e))?;
-        }
+    /// Fail with `KokoroError::InvalidInput` instead of warning
+    /// (`SynthesisWarning::UnknownCharacters`) when the text has characters
+    /// the model has no token for
+    ///
+    /// # Example
+    /// ```no_run
+    /// use kokoro_tiny::{KokoroError, SynthesizeOptions, TtsEngine};
+    ///
+    /// # async fn demo() -> Result<(), kokoro_tiny::KokoroError> {
+    /// let tts = TtsEngine::new().await?;
+    /// let opts = SynthesizeOptions::new().strict_characters(true);
+    /// match tts.synthesize_with("Deploy ✓ on node ⑦", opts) {
+    ///     Ok(audio) => tts.play(audio, 0.8)?,
+    ///     Err(KokoroError::InvalidInput(reason)) => eprintln!("Not spoken: {}", reason),
+    ///     Err(e) => return Err(e),
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn strict_characters(mut self, strict: bool) -> Self {
+        self.strict_characters = strict;
+        self
+    }
 
-        // Check if we need to download
-        let need_download = !Path::new(model_path).exists() || !Path::new(voices_path).exists();
+    /// Set how upper-case text is read (default: `CapsPolicy::Normalize`)
+    ///
+    /// # Example
+    /// ```no_run
+    /// use kokoro_tiny::{CapsPolicy, SynthesizeOptions, TtsEngine};
+    ///
+    /// # async fn demo() -> Result<(), kokoro_tiny::KokoroError> {
+    /// let tts = TtsEngine::new().await?;
+    /// // Read "NASA" as N-A-S-A
+    /// let opts = SynthesizeOptions::new().caps_policy(CapsPolicy::SpellOut);
+    /// let audio = tts.synthesize_with("NASA confirmed the launch window", opts)?;
+    /// tts.play(audio, 0.8)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn caps_policy(mut self, policy: CapsPolicy) -> Self {
+        self.caps_policy = policy;
+        self
+    }
 
-        if need_download {
-            #[cfg(not(feature = "as-lib"))]
-            println!("🎤 First time setup - downloading voice model...");
-            #[cfg(not(feature = "as-lib"))]
-              println!("   (This only happens once, files will be cached in ~/.cache/k)");
+    /// Lengthen or shorten pauses per punctuation mark (heuristic, see `prosody`)
+    ///
+    /// # Example
+    /// ```no_run
+    /// use kokoro_tiny::{PunctuationWeights, SynthesizeOptions, TtsEngine};
+    ///
+    /// # async fn demo() -> Result<(), kokoro_tiny::KokoroError> {
+    /// let tts = TtsEngine::new().await?;
+    /// // Heavier commas for dictation
+    /// let opts = SynthesizeOptions::new().punctuation_weights(PunctuationWeights {
+    ///     comma: 2.0,
+    ///     ..Default::default()
+    /// });
+    /// let audio = tts.synthesize_with("Dear team, the office is closed, as announced, on Friday.", opts)?;
+    /// tts.save_wav("dictation.wav", &audio)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn punctuation_weights(mut self, weights: PunctuationWeights) -> Self {
+        self.punctuation_weights = weights;
+        self
+    }
 
-            // Auto-play fallback message while downloading (if playback is enabled)
-            #[cfg(feature = "playback")]
-            {
-                // Spawn a thread to play the fallback message
-                thread::spawn(|| {
-                    if let Err(e) = play_fallback_message() {
-                        eprintln!("   ℹ️  Could not play fallback message: {}", e);
-                    }
-                });
-            }
+    /// Use a named pacing preset; explicitly set options still take precedence
+    ///
+    /// # Example
+    /// ```no_run
+    /// use kokoro_tiny::{PacingPreset, SynthesizeOptions, TtsEngine};
+    ///
+    /// # async fn demo() -> Result<(), kokoro_tiny::KokoroError> {
+    /// let tts = TtsEngine::new().await?;
+    /// let opts = SynthesizeOptions::new().pacing(PacingPreset::Broadcast);
+    /// let audio = tts.synthesize_with("Top stories this hour. Markets closed higher.", opts)?;
+    /// tts.save_wav("news.wav", &audio)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn pacing(mut self, preset: PacingPreset) -> Self {
+        self.pacing = Some(preset);
+        self
+    }
 
-            // Try to download the files
-            let download_success = {
-                let mut success = true;
-
-                // Download model if needed
-                if !Path::new(model_path).exists() {
-                    #[cfg(not(feature = "as-lib"))]
-                    println!("   📥 Downloading model (310MB)...");
-                    if let Err(e) = download_file(MODEL_URL, model_path).await {
-                        #[cfg(not(feature = "as-lib"))]
-                        eprintln!("   ❌ Failed to download model: {}", e);
-                        success = false;
-                    }
-                }
+    /// Insert exact silence after commas, sentences and paragraphs
+    ///
+    /// Text is rendered clause by clause and joined with the configured
+    /// silence, so pauses stay the same length at any speed. See `pacing`.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use kokoro_tiny::{PauseConfig, SynthesizeOptions, TtsEngine};
+    ///
+    /// # async fn demo() -> Result<(), kokoro_tiny::KokoroError> {
+    /// let tts = TtsEngine::new().await?;
+    /// let pauses = PauseConfig { sentence_ms: 400, ..PauseConfig::default() };
+    /// let opts = SynthesizeOptions::new().pauses(pauses);
+    /// let audio = tts.synthesize_with("Preheat the oven. Mix the flour, sugar and eggs.", opts)?;
+    /// tts.play(audio, 0.8)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn pauses(mut self, pauses: PauseConfig) -> Self {
+        self.pauses = Some(pauses);
+        self
+    }
 
-                // Download voices if needed
-                if success && !Path::new(voices_path).exists() {
-                    #[cfg(not(feature = "as-lib"))]
-                    println!("   📥 Downloading voices (27MB)...");
-                    if let Err(e) = download_file(VOICES_URL, voices_path).await {
-                        #[cfg(not(feature = "as-lib"))]
-                        eprintln!("   ❌ Failed to download voices: {}", e);
-                        success = false;
-                    }
-                }
+    /// Reuse rendered chunks from the on-disk chunk cache (default: off)
+    ///
+    /// Useful for documents that are re-rendered with small edits: only
+    /// changed chunks reach the model.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use kokoro_tiny::{SynthesizeOptions, TtsEngine};
+    ///
+    /// # async fn demo() -> Result<(), kokoro_tiny::KokoroError> {
+    /// let tts = TtsEngine::new().await?;
+    /// // After an edit, only the changed chunks are rendered again
+    /// let chapter = std::fs::read_to_string("chapter1.txt")?;
+    /// let audio = tts.synthesize_with(&chapter, SynthesizeOptions::new().chunk_cache(true))?;
+    /// tts.save_wav("chapter1.wav", &audio)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn chunk_cache(mut self, enabled: bool) -> Self {
+        self.chunk_cache = enabled;
+        self
+    }
 
-                if success {
-                    #[cfg(not(feature = "as-lib"))]
-                    println!("   ✅ Voice model downloaded successfully!");
-                }
+    /// Read more unit symbols after numbers, or override built-in ones
+    ///
+    /// Keys are symbols as written after the number ("rpm", "kWh") and the words
+    /// should be in the synthesis language. Only languages `units` has number
+    /// words for (English and German) expand units at all.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use kokoro_tiny::{SynthesizeOptions, TtsEngine, UnitWords};
+    /// use std::collections::HashMap;
+    ///
+    /// # async fn demo() -> Result<(), kokoro_tiny::KokoroError> {
+    /// let tts = TtsEngine::new().await?;
+    /// let rpm = UnitWords::new("revolution per minute", "revolutions per minute");
+    /// let opts = SynthesizeOptions::new().extra_units(HashMap::from([("rpm".to_string(), rpm)]));
+    /// // "The fan runs at twelve hundred revolutions per minute"
+    /// let audio = tts.synthesize_with("The fan runs at 1200 rpm", opts)?;
+    /// tts.play(audio, 0.8)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn extra_units(mut self, units: HashMap<String, UnitWords>) -> Self {
+        self.extra_units = units;
+        self
+    }
 
-                success
-            };
+    /// Vary the delivery from take to take
+    ///
+    /// Adds seeded noise to the voice's style vector, scaled per dimension by
+    /// how much the loaded voices differ there and kept within their range.
+    /// Around 0.1 to 0.3 gives subtly different takes. The same seed gives
+    /// the same take; `None` gives a new one on every render (and skips the
+    /// result cache). 0.0 (the default) is identical to no variation.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use kokoro_tiny::{SynthesizeOptions, TtsEngine};
+    ///
+    /// # async fn demo() -> Result<(), kokoro_tiny::KokoroError> {
+    /// let tts = TtsEngine::new().await?;
+    /// // Three reproducible takes of one line
+    /// for seed in 1..=3 {
+    ///     let opts = SynthesizeOptions::new().style_temperature(0.2, Some(seed));
+    ///     let audio = tts.synthesize_with("Welcome back", opts)?;
+    ///     tts.save_wav(&format!("take{}.wav", seed), &audio)?;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn style_temperature(mut self, temperature: f32, seed: Option<u64>) -> Self {
+        self.style_temperature = temperature;
+        self.style_seed = seed;
+        self
+    }
 
-            // If download failed, return fallback engine
-            if !download_success {
-                #[cfg(not(feature = "as-lib"))]
-                eprintln!("\n⚠️  Using fallback mode. The model files are not available at:");
-                #[cfg(not(feature = "as-lib"))]
-                eprintln!("   - {}", MODEL_URL);
-                #[cfg(not(feature = "as-lib"))]
-                eprintln!("   - {}", VOICES_URL);
-                #[cfg(not(feature = "as-lib"))]
-                eprintln!("\n💡 Please manually download the model files to ~/.cache/k/");
-
-                return Ok(Self {
-                    session: None,
-                    voices: HashMap::new(),
-                    vocab: build_vocab(),
-                    fallback_mode: true,
-                    #[cfg(feature = "playback")]
-                    audio_device: None,
-                });
-            }
-        }
+    /// Shift the pitch by `factor` (clamped to 0.5 to 2.0; 1.0 = unchanged)
+    ///
+    /// The audio is resampled and the model speed compensated, so the tempo
+    /// stays the same. Formants move with the pitch, so shifts beyond a few
+    /// semitones sound processed.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use kokoro_tiny::{SynthesizeOptions, TtsEngine};
+    ///
+    /// # async fn demo() -> Result<(), kokoro_tiny::KokoroError> {
+    /// let tts = TtsEngine::new().await?;
+    /// // About two semitones up
+    /// let opts = SynthesizeOptions::new().pitch(1.12);
+    /// let audio = tts.synthesize_with("Hi, I'm your assistant", opts)?;
+    /// tts.play(audio, 0.8)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn pitch(mut self, factor: f32) -> Self {
+        self.pitch = factor;
+        self
+    }
 
-        // Load ONNX model
-        let model_bytes =
-            std::fs::read(model_path).map_err(|e| format!("Failed to read model file: {}", e))?;
-        let session = Session::builder()
-            .map_err(|e| format!("Failed to create session builder: {}", e))?
-            .with_optimization_level(GraphOptimizationLevel::Level3)
-            .map_err(|e| format!("Failed to set optimization level: {}", e))?
-            .commit_from_memory(&model_bytes)
-            .map_err(|e| format!("Failed to load model: {}", e))?;
+    /// Cut silence before the first and after the last sound (default: off)
+    ///
+    /// A short margin is kept at both ends; audio without any sound is left
+    /// as is.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use kokoro_tiny::{SynthesizeOptions, TtsEngine};
+    ///
+    /// # async fn demo() -> Result<(), kokoro_tiny::KokoroError> {
+    /// let tts = TtsEngine::new().await?;
+    /// let opts = SynthesizeOptions::new().trim_silence(true);
+    /// let audio = tts.synthesize_with("Door open", opts)?;
+    /// tts.save_wav("door_open.wav", &audio)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn trim_silence(mut self, trim: bool) -> Self {
+        self.trim_silence = trim;
+        self
+    }
+
+    /// Fade the first and last `ms` of the result in and out, so it starts
+    /// and ends at zero instead of clicking (default: 5 ms, 0 turns it off)
+    ///
+    /// # Example
+    /// ```no_run
+    /// use kokoro_tiny::{SynthesizeOptions, TtsEngine};
+    ///
+    /// # async fn demo() -> Result<(), kokoro_tiny::KokoroError> {
+    /// let tts = TtsEngine::new().await?;
+    /// // Pieces spliced into one announcement, without a dip at the seam
+    /// let opts = SynthesizeOptions::new().edge_fade_ms(0);
+    /// let mut track = tts.synthesize_with("Now arriving at", opts.clone())?;
+    /// track.extend(tts.synthesize_with("Central Station", opts)?);
+    /// tts.save_wav("announcement.wav", &track)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn edge_fade_ms(mut self, ms: u32) -> Self {
+        self.edge_fade_ms = ms;
+        self
+    }
+
+    /// Make the finished audio `factor` times as fast without changing its
+    /// pitch (1.1 = 10% shorter; 1.0, the default, leaves it alone)
+    ///
+    /// Unlike `speed`, the model reads the text as it normally would and the
+    /// result is stretched afterwards (see `time_stretch`), so the prosody
+    /// stays the same. Sounds natural from about 0.8 to 1.3. Dialogue and
+    /// streaming output aren't stretched.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use kokoro_tiny::{SynthesizeOptions, TtsEngine};
+    ///
+    /// # async fn demo() -> Result<(), kokoro_tiny::KokoroError> {
+    /// let tts = TtsEngine::new().await?;
+    /// // Squeeze a voice-over into a slightly shorter video slot
+    /// let opts = SynthesizeOptions::new().post_stretch(1.1);
+    /// let audio = tts.synthesize_with("Introducing the new dashboard", opts)?;
+    /// tts.save_wav("voiceover.wav", &audio)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn post_stretch(mut self, factor: f32) -> Self {
+        self.post_stretch = factor;
+        self
+    }
+
+    /// Shift the finished audio's pitch by `semitones` (clamped to ±6; 0.0,
+    /// the default, leaves it alone), keeping its length
+    ///
+    /// Gives one voice a younger (up) or older (down) sound. Unlike `pitch`,
+    /// which has the model compensate the tempo during rendering, this works
+    /// on the result (see `pitch_shift`), so the timing is exactly the
+    /// unshifted one. Formants shift too: ±2 semitones sound natural, beyond
+    /// about 4 the voice sounds processed. Dialogue and streaming output
+    /// aren't shifted.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use kokoro_tiny::{SynthesizeOptions, TtsEngine};
+    ///
+    /// # async fn demo() -> Result<(), kokoro_tiny::KokoroError> {
+    /// let tts = TtsEngine::new().await?;
+    /// // The same voice as an older character
+    /// let opts = SynthesizeOptions::new().pitch_semitones(-2.0);
+    /// let audio = tts.synthesize_with("In my day, we wrote our own drivers", opts)?;
+    /// tts.play(audio, 0.8)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn pitch_semitones(mut self, semitones: f32) -> Self {
+        self.pitch_semitones = semitones;
+        self
+    }
+
+    /// Run the finished audio through an EQ preset (default: none)
+    ///
+    /// `CleanSpeech` takes out rumble that muddies small speakers;
+    /// `Telephone` gives alerts a band-limited radio sound. See `filters`.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use kokoro_tiny::{FilterPreset, SynthesizeOptions, TtsEngine};
+    ///
+    /// # async fn demo() -> Result<(), kokoro_tiny::KokoroError> {
+    /// let tts = TtsEngine::new().await?;
+    /// let opts = SynthesizeOptions::new().filter(FilterPreset::Telephone);
+    /// let audio = tts.synthesize_with("Unit seven, please respond", opts)?;
+    /// tts.play(audio, 0.8)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn filter(mut self, preset: FilterPreset) -> Self {
+        self.filter = Some(preset);
+        self
+    }
+
+    /// Resample the result to `rate` Hz, e.g. 16000 for speech recognition or
+    /// 48000 for mixing (default: the model's 24000)
+    ///
+    /// `synthesize_with` returns plain samples at this rate; to save or play
+    /// them with the right rate use `synthesize_with_buffer`. Dialogue and
+    /// streaming output stay at the model rate.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use kokoro_tiny::{SynthesizeOptions, TtsEngine};
+    ///
+    /// # async fn demo() -> Result<(), kokoro_tiny::KokoroError> {
+    /// let tts = TtsEngine::new().await?;
+    /// // 16kHz for a speech recognizer; the buffer carries the rate into the file
+    /// let opts = SynthesizeOptions::new().output_sample_rate(16_000);
+    /// let buffer = tts.synthesize_with_buffer("Turn on the kitchen lights", opts)?;
+    /// tts.save_wav("asr_test.wav", buffer)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn output_sample_rate(mut self, rate: u32) -> Self {
+        self.output_sample_rate = Some(rate);
+        self
+    }
+
+    /// Return mono (the default) or interleaved stereo with the voice panned
+    /// from -1.0 (left) to 1.0 (right), see `audio::to_stereo`
+    ///
+    /// Use `synthesize_with_buffer` to save or play the result as stereo.
+    /// Dialogue and streaming output stay mono.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use kokoro_tiny::{Channels, SynthesizeOptions, TtsEngine};
+    ///
+    /// # async fn demo() -> Result<(), kokoro_tiny::KokoroError> {
+    /// let tts = TtsEngine::new().await?;
+    /// // Slightly left of center
+    /// let opts = SynthesizeOptions::new().channels(Channels::Stereo { pan: -0.3 });
+    /// let buffer = tts.synthesize_with_buffer("Player one, your turn", opts)?;
+    /// tts.play(buffer, 0.8)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn channels(mut self, channels: Channels) -> Self {
+        self.channels = channels;
+        self
+    }
+
+    /// Set the longest chunk handed to the model, in chars (at least 20)
+    ///
+    /// Short chunks start streaming sooner; long ones keep the cadence of
+    /// long reads. Overrides the pacing preset.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use kokoro_tiny::{SynthesizeOptions, TtsEngine};
+    ///
+    /// # async fn demo() -> Result<(), kokoro_tiny::KokoroError> {
+    /// let tts = TtsEngine::new().await?;
+    /// // Low-latency streaming
+    /// let opts = SynthesizeOptions::new().chunk_max_chars(60);
+    /// let text = "Your order has shipped. It should arrive on Thursday, and you can track it in the app.";
+    /// for chunk in tts.synthesize_stream(text, opts)? {
+    ///     tts.play(chunk?.audio, 0.8)?;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn chunk_max_chars(mut self, chars: usize) -> Self {
+        self.chunk_max_chars = Some(chars);
+        self
+    }
+
+    /// Set the length, in chars, above which text is chunked at all
+    ///
+    /// Text under it (and under `chunk_max_chars`) goes through in one pass.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use kokoro_tiny::{SynthesizeOptions, TtsEngine};
+    ///
+    /// # async fn demo() -> Result<(), kokoro_tiny::KokoroError> {
+    /// let tts = TtsEngine::new().await?;
+    /// // Split anything past a short sentence
+    /// let opts = SynthesizeOptions::new().chunk_threshold(40);
+    /// let audio = tts.synthesize_with("The meeting moved to three. Bring the quarterly numbers.", opts)?;
+    /// tts.play(audio, 0.8)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn chunk_threshold(mut self, chars: usize) -> Self {
+        self.chunk_threshold = Some(chars);
+        self
+    }
+
+    /// Set the overlap between neighbouring chunks (under 500 ms; 0 butts
+    /// them together). Overrides the pacing preset.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use kokoro_tiny::{SynthesizeOptions, TtsEngine};
+    ///
+    /// # async fn demo() -> Result<(), kokoro_tiny::KokoroError> {
+    /// let tts = TtsEngine::new().await?;
+    /// let opts = SynthesizeOptions::new().crossfade_ms(80);
+    /// let article = std::fs::read_to_string("article.txt")?;
+    /// let audio = tts.synthesize_with(&article, opts)?;
+    /// tts.save_wav("article.wav", &audio)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn crossfade_ms(mut self, ms: u32) -> Self {
+        self.crossfade_ms = Some(ms);
+        self
+    }
+
+    /// Set the silence between blank-line separated paragraphs (0 runs them
+    /// together like any other chunks). Overrides the pacing preset.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use kokoro_tiny::{SynthesizeOptions, TtsEngine};
+    ///
+    /// # async fn demo() -> Result<(), kokoro_tiny::KokoroError> {
+    /// let tts = TtsEngine::new().await?;
+    /// let opts = SynthesizeOptions::new().paragraph_gap_ms(800);
+    /// let audio = tts.synthesize_with("Chapter one.\n\nIt was a cold morning in March.", opts)?;
+    /// tts.save_wav("chapter.wav", &audio)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn paragraph_gap_ms(mut self, ms: u32) -> Self {
+        self.paragraph_gap_ms = Some(ms);
+        self
+    }
+
+    /// Add abbreviations that shouldn't end a sentence when chunking
+    ///
+    /// Titles ("Dr."), addresses ("Ave."), Latin ("etc.", "vs."), initials
+    /// ("J.") and dotted abbreviations ("U.S.", "e.g.") are known already.
+    /// The trailing period is optional and case is ignored.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use kokoro_tiny::{SynthesizeOptions, TtsEngine};
+    ///
+    /// # async fn demo() -> Result<(), kokoro_tiny::KokoroError> {
+    /// let tts = TtsEngine::new().await?;
+    /// // "Kokoro Labs Intl. announced..." stays one sentence
+    /// let opts = SynthesizeOptions::new().abbreviations(&["Intl.", "Pty"]);
+    /// let audio = tts.synthesize_with("Kokoro Labs Intl. announced a new model today.", opts)?;
+    /// tts.play(audio, 0.8)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn abbreviations(mut self, abbreviations: &[&str]) -> Self {
+        self.abbreviations
+            .extend(abbreviations.iter().map(|abbr| abbr.to_string()));
+        self
+    }
+
+    /// Skip text normalization (default: off)
+    ///
+    /// Numbers, amounts and units are normally read out in words of `lang`
+    /// ("$5.99" -> "five dollars and ninety-nine cents", see `numbers` and
+    /// `units`) and shouting is toned down (`caps_policy`). Raw text goes to
+    /// espeak exactly as written.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use kokoro_tiny::{SynthesizeOptions, TtsEngine};
+    ///
+    /// # async fn demo() -> Result<(), kokoro_tiny::KokoroError> {
+    /// let tts = TtsEngine::new().await?;
+    /// // Text that was already normalized upstream
+    /// let opts = SynthesizeOptions::new().raw_text(true);
+    /// let audio = tts.synthesize_with("five dollars and ninety-nine cents", opts)?;
+    /// tts.play(audio, 0.8)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn raw_text(mut self, raw: bool) -> Self {
+        self.raw_text = raw;
+        self
+    }
+
+    /// How URLs are read out (default: `UrlStyle::Full`)
+    ///
+    /// Email addresses and paths are always read in full, see `links`.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use kokoro_tiny::{SynthesizeOptions, TtsEngine, UrlStyle};
+    ///
+    /// # async fn demo() -> Result<(), kokoro_tiny::KokoroError> {
+    /// let tts = TtsEngine::new().await?;
+    /// // "Deployed, see a link." rather than the whole address
+    /// let opts = SynthesizeOptions::new().url_style(UrlStyle::Link);
+    /// let audio = tts.synthesize_with("Deployed, see https://ci.example.com/builds/4821", opts)?;
+    /// tts.play(audio, 0.8)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn url_style(mut self, style: UrlStyle) -> Self {
+        self.url_style = style;
+        self
+    }
+
+    /// Read inline `[pause 300ms]` and `*emphasis*` markers (default: off)
+    ///
+    /// Off, brackets and asterisks are ordinary text. On, `synthesize_with`
+    /// splices in the pauses as exact silence and reads emphasized clauses a
+    /// little louder and slower; streaming and resumable renders drop the
+    /// markers without the pauses. See `markup`.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use kokoro_tiny::{SynthesizeOptions, TtsEngine};
+    ///
+    /// # async fn demo() -> Result<(), kokoro_tiny::KokoroError> {
+    /// let tts = TtsEngine::new().await?;
+    /// let opts = SynthesizeOptions::new().markup(true);
+    /// let audio = tts.synthesize_with("Step one. [pause 500ms] Step *two*.", opts)?;
+    /// tts.play(audio, 0.8)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn markup(mut self, markup: bool) -> Self {
+        self.markup = markup;
+        self
+    }
+
+    /// How emoji are read (default: `EmojiMode::Strip`, left out)
+    ///
+    /// See `emoji`; `raw_text` hands them to espeak as they are.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use kokoro_tiny::{EmojiMode, SynthesizeOptions, TtsEngine};
+    ///
+    /// # async fn demo() -> Result<(), kokoro_tiny::KokoroError> {
+    /// let tts = TtsEngine::new().await?;
+    /// // "Deployed 🚀" -> "Deployed rocket"
+    /// let opts = SynthesizeOptions::new().emoji(EmojiMode::Name);
+    /// let audio = tts.synthesize_with("Deployed 🚀", opts)?;
+    /// tts.play(audio, 0.8)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn emoji(mut self, mode: EmojiMode) -> Self {
+        self.emoji = mode;
+        self
+    }
+
+    /// Spell out words matching any of `patterns` ("a3f9c2e" -> "A three F
+    /// nine C two E"); see `spelling`
+    ///
+    /// # Example
+    /// ```no_run
+    /// use kokoro_tiny::{SpellPattern, SynthesizeOptions, TtsEngine};
+    ///
+    /// # async fn demo() -> Result<(), kokoro_tiny::KokoroError> {
+    /// let tts = TtsEngine::new().await?;
+    /// let opts = SynthesizeOptions::new().spell_patterns(vec![
+    ///     SpellPattern::Hex { min_len: 7 },
+    ///     SpellPattern::Regex(r"\bJIRA-\d+\b".to_string()),
+    /// ]);
+    /// let audio = tts.synthesize_with("Commit a3f9c2e fixes JIRA-1234", opts)?;
+    /// tts.play(audio, 0.8)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn spell_patterns(mut self, patterns: Vec<SpellPattern>) -> Self {
+        self.spell_patterns = patterns;
+        self
+    }
+
+    /// Take document markup out before chunking (default: `Preprocess::Plain`)
+    ///
+    /// `Preprocess::Markdown` reads a README as prose: no heading markers,
+    /// bullets, emphasis or link URLs, code blocks left out. See `markdown`.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use kokoro_tiny::{Preprocess, SynthesizeOptions, TtsEngine};
+    ///
+    /// # async fn demo() -> Result<(), kokoro_tiny::KokoroError> {
+    /// let tts = TtsEngine::new().await?;
+    /// let opts = SynthesizeOptions::new().preprocess(Preprocess::Markdown);
+    /// let readme = std::fs::read_to_string("README.md")?;
+    /// let audio = tts.synthesize_with(&readme, opts)?;
+    /// tts.save_wav("readme.wav", &audio)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn preprocess(mut self, preprocess: Preprocess) -> Self {
+        self.preprocess = preprocess;
+        self
+    }
+
+    // Unseeded variation is a new take every time, so it can't come from a cache
+    fn is_repeatable(&self) -> bool {
+        self.style_temperature <= 0.0 || self.style_seed.is_some()
+    }
+}
+
+/// A weighted blend of voices, e.g. `"af_sky.7+af_bella.3"`
+///
+/// Weights in the string form are written in tenths (`af_sky.7` = 0.7) to match
+/// the syntax `synthesize` has always accepted; fractional tenths such as
+/// `af_sky.6.5` (= 0.65) are allowed so any normalized mix can round-trip.
+#[derive(Clone, Debug, PartialEq)]
+pub struct VoiceMix {
+    pub components: Vec<(String, f32)>,
+}
+
+impl VoiceMix {
+    /// Parse a mix string like `"af_sky.4+af_nicole.5"` (a bare name means weight 1.0)
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let mut components = Vec::new();
+        for part in spec.split('+') {
+            let part = part.trim();
+            let (voice_name, weight) = match part.split_once('.') {
+                Some((name, tenths)) => {
+                    let tenths = tenths
+                        .parse::<f32>()
+                        .map_err(|_| format!("Invalid weight: {}", tenths))?;
+                    (name, tenths / 10.0)
+                }
+                None => (part, 1.0),
+            };
+            if voice_name.is_empty() {
+                return Err(format!("Invalid voice format: {}", part));
+            }
+            components.push((voice_name.to_string(), weight));
+        }
+        Self::from_components(components)
+    }
+
+    /// Build a mix from explicit `(voice, weight)` pairs, validating the weights
+    pub fn from_components(components: Vec<(String, f32)>) -> Result<Self, String> {
+        if components.is_empty() {
+            return Err("Voice mix has no components".to_string());
+        }
+        for (name, weight) in &components {
+            if !weight.is_finite() || *weight < 0.0 {
+                return Err(format!("Invalid weight for {}: {}", name, weight));
+            }
+        }
+        if components.iter().all(|(_, w)| *w == 0.0) {
+            return Err("Voice mix weights are all zero".to_string());
+        }
+        Ok(Self { components })
+    }
+
+    /// Same voices with weights scaled to sum to 1.0
+    pub fn normalized(&self) -> Self {
+        let total: f32 = self.components.iter().map(|(_, w)| w).sum();
+        Self {
+            components: self
+                .components
+                .iter()
+                .map(|(name, w)| (name.clone(), w / total))
+                .collect(),
+        }
+    }
+}
+
+impl std::fmt::Display for VoiceMix {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let [(name, weight)] = self.components.as_slice() {
+            if *weight == 1.0 {
+                return write!(f, "{}", name);
+            }
+        }
+        let parts: Vec<String> = self
+            .components
+            .iter()
+            .map(|(name, weight)| {
+                // Round to 1/1000 of a tenth so float noise doesn't leak into the string
+                let tenths = (weight * 10_000.0).round() / 1000.0;
+                format!("{}.{}", name, tenths)
+            })
+            .collect();
+        write!(f, "{}", parts.join("+"))
+    }
+}
+
+impl TtsEngine {
+    /// Create a new TTS engine, downloading model files if necessary
+    /// Uses the cache directory (see `cache_dir`) for shared model storage
+    ///
+    /// # Example
+    /// ```no_run
+    /// use kokoro_tiny::TtsEngine;
+    ///
+    /// # async fn demo() -> Result<(), String> {
+    /// let tts = TtsEngine::new().await?;
+    /// let audio = tts.synthesize("Hello world!", None, None, None)?;
+    /// tts.save_wav("hello.wav", &audio)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn new() -> Result<Self, KokoroError> {
+        let cache_dir = cache_dir();
+        let model_path = cache_dir.join("0.onnx");
+        let voices_path = cache_dir.join("0.bin");
+
+        Self::with_paths(
+            model_path.to_str().unwrap_or("0.onnx"),
+            voices_path.to_str().unwrap_or("0.bin"),
+        )
+        .await
+    }
+
+    /// Create a new TTS engine with custom model paths
+    ///
+    /// # Example
+    /// ```no_run
+    /// use kokoro_tiny::TtsEngine;
+    ///
+    /// # async fn demo() -> Result<(), String> {
+    /// // Missing files are downloaded to these paths
+    /// let tts = TtsEngine::with_paths("models/kokoro.onnx", "models/voices.bin").await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn with_paths(model_path: &str, voices_path: &str) -> Result<Self, KokoroError> {
+        Self::with_retry_policy(model_path, voices_path, RetryPolicy::default()).await
+    }
+
+    /// Create a new TTS engine with custom model paths and download retry policy
+    ///
+    /// Failed downloads are spaced out by the policy (the state is shared across
+    /// processes through the cache directory); while backing off, the engine
+    /// starts in fallback mode without touching the network.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use kokoro_tiny::{RetryPolicy, TtsEngine};
+    /// use std::time::Duration;
+    ///
+    /// # async fn demo() -> Result<(), String> {
+    /// let policy = RetryPolicy {
+    ///     base_delay: Duration::from_secs(30),
+    ///     ..RetryPolicy::default()
+    /// };
+    /// let tts = TtsEngine::with_retry_policy("models/kokoro.onnx", "models/voices.bin", policy).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn with_retry_policy(
+        model_path: &str,
+        voices_path: &str,
+        retry_policy: RetryPolicy,
+    ) -> Result<Self, KokoroError> {
+        Self::builder()
+            .paths(model_path, voices_path)
+            .retry_policy(retry_policy)
+            .build()
+            .await
+    }
+
+    /// Like `new`, but a failed download is an error instead of fallback mode
+    ///
+    /// For servers: a fallback engine answers every request with the canned
+    /// "lost my voice" clip, which a client can't tell from real output. Here
+    /// a missing model that can't be downloaded (including while the retry
+    /// policy is backing off) is a `KokoroError::DownloadFailed`.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use kokoro_tiny::{KokoroError, TtsEngine};
+    ///
+    /// # async fn demo() -> Result<(), KokoroError> {
+    /// let tts = match TtsEngine::new_strict().await {
+    ///     Ok(tts) => tts,
+    ///     Err(e @ KokoroError::DownloadFailed { .. }) => {
+    ///         eprintln!("not ready to serve: {}", e);
+    ///         return Err(e);
+    ///     }
+    ///     Err(e) => return Err(e),
+    /// };
+    /// assert!(!tts.is_fallback());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn new_strict() -> Result<Self, KokoroError> {
+        let cache_dir = cache_dir();
+        let model_path = cache_dir.join("0.onnx");
+        let voices_path = cache_dir.join("0.bin");
+
+        Self::with_paths_strict(
+            model_path.to_str().unwrap_or("0.onnx"),
+            voices_path.to_str().unwrap_or("0.bin"),
+        )
+        .await
+    }
+
+    /// Like `with_paths`, but a failed download is an error (see `new_strict`)
+    pub async fn with_paths_strict(
+        model_path: &str,
+        voices_path: &str,
+    ) -> Result<Self, KokoroError> {
+        Self::builder()
+            .paths(model_path, voices_path)
+            .strict(true)
+            .build()
+            .await
+    }
+
+    /// Create a new TTS engine with a different voice pack
+    ///
+    /// `VoicePack::Full` is the upstream 54-voice pack: the English voices plus
+    /// Spanish (`ef_`/`em_`), French (`ff_`), Hindi (`hf_`/`hm_`), Italian
+    /// (`if_`/`im_`), Japanese (`jf_`/`jm_`), Portuguese (`pf_`/`pm_`) and
+    /// Mandarin (`zf_`/`zm_`). It is cached next to the bundled one, in the
+    /// cache directory. Voices of languages the model vocabulary can't render
+    /// yet are loaded but fail synthesis with an "Unsupported language" error
+    /// (see `languages::voice_language`).
+    ///
+    /// # Example
+    /// ```no_run
+    /// use kokoro_tiny::{TtsEngine, VoicePack};
+    ///
+    /// # async fn demo() -> Result<(), String> {
+    /// let tts = TtsEngine::with_voice_pack("full".parse()?).await?;
+    /// let audio = tts.synthesize("Hola, ¿qué tal?", Some("ef_dora"), None, None)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn with_voice_pack(voice_pack: VoicePack) -> Result<Self, KokoroError> {
+        Self::builder().voice_pack(voice_pack).build().await
+    }
+
+    // Download what's missing, then load; if the download fails, fallback mode
+    // (or the error, when `strict`)
+    pub(crate) async fn open(
+        model_path: &str,
+        voices_path: &str,
+        options: &EngineBuilder,
+    ) -> Result<Self, KokoroError> {
+        // Ensure cache directory exists
+        if let Some(parent) = Path::new(model_path).parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create cache directory: {}", e))?;
+        }
+
+        // A damaged file in the cache (an interrupted download) is fetched again
+        let (model_digest, voices_digest) = options.cached_digests();
+        integrity::discard_if_corrupt(model_path, model_digest)?;
+        integrity::discard_if_corrupt(voices_path, voices_digest)?;
+
+        // Check if we need to download
+        let need_download = !Path::new(model_path).exists() || !Path::new(voices_path).exists();
+
+        if need_download {
+            #[cfg(not(feature = "as-lib"))]
+            eprintln!("🎤 First time setup - downloading voice model...");
+            #[cfg(not(feature = "as-lib"))]
+            eprintln!(
+                "   (This only happens once, files will be cached in {})",
+                Path::new(model_path).parent().unwrap_or(Path::new(".")).display()
+            );
+
+            // Auto-play fallback message while downloading (if playback is enabled)
+            #[cfg(feature = "playback")]
+            {
+                // Spawn a thread to play the fallback message
+                thread::spawn(|| {
+                    if let Err(e) = play_fallback_message() {
+                        eprintln!("   ℹ️  Could not play fallback message: {}", e);
+                    }
+                });
+            }
+
+            // Try to download the files
+            let sources = options.sources();
+            let download =
+                fetch_model_files(model_path, voices_path, &sources, &options.retry_policy).await;
+
+            // If download failed, return fallback engine
+            if let Err(e) = download {
+                if options.strict {
+                    return Err(e);
+                }
+                #[cfg(not(feature = "as-lib"))]
+                eprintln!("   ❌ {}", e);
+                #[cfg(not(feature = "as-lib"))]
+                eprintln!("\n⚠️  Using fallback mode. The model files are not available at:");
+                #[cfg(not(feature = "as-lib"))]
+                for url in sources.model.urls.iter().chain(&sources.voices.urls) {
+                    eprintln!("   - {}", url);
+                }
+                #[cfg(not(feature = "as-lib"))]
+                eprintln!(
+                    "\n💡 Please manually download the model files to {}",
+                    Path::new(model_path).parent().unwrap_or(Path::new(".")).display()
+                );
+
+                return Ok(Self::fallback(
+                    Some((model_path.to_string(), voices_path.to_string())),
+                    chunk_dir_for(model_path),
+                    options,
+                ));
+            }
+
+            #[cfg(not(feature = "as-lib"))]
+            eprintln!("   ✅ Voice model downloaded successfully!");
+        }
+
+        Self::load(model_path, voices_path, options)
+    }
+
+    /// Create a TTS engine that never touches the network
+    ///
+    /// Loads the model from the cache directory if it is there. If not, nothing is
+    /// downloaded: the engine runs in fallback mode with the built-in formant
+    /// voice (see `formant`), which reads the actual text in a robotic voice
+    /// instead of playing the "lost my voice" message, and `try_recover`
+    /// stays offline too: it answers `Ok(false)` without downloading. For
+    /// air-gapped machines and `kokoro-speak --no-download`.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use kokoro_tiny::TtsEngine;
+    ///
+    /// let tts = TtsEngine::without_download()?;
+    /// if tts.is_fallback() {
+    ///     eprintln!("no cached model, using the built-in voice");
+    /// }
+    /// let audio = tts.synthesize("Build complete", None, None, None)?;
+    /// assert!(!audio.is_empty());
+    /// # Ok::<(), String>(())
+    /// ```
+    pub fn without_download() -> Result<Self, KokoroError> {
+        let cache_dir = cache_dir();
+        let model_path = cache_dir.join("0.onnx");
+        let voices_path = cache_dir.join("0.bin");
+
+        if model_path.exists() && voices_path.exists() {
+            return Self::from_cached();
+        }
+
+        let mut engine = Self::fallback(
+            None,
+            chunk_dir_for(model_path.to_str().unwrap_or("0.onnx")),
+            &EngineBuilder::default(),
+        );
+        engine.formant_fallback = true;
+        Ok(engine)
+    }
+
+    /// Load the model from the cache directory synchronously, without a runtime
+    ///
+    /// For programs without tokio (or any async runtime): nothing is
+    /// downloaded and nothing async runs. If either file is missing this is a
+    /// `KokoroError::ModelMissing`, not fallback mode; `TtsEngine::new` (or
+    /// any `kokoro-speak` command) downloads them once.
+    ///
+    /// # Example
+    /// ```
+    /// use kokoro_tiny::{KokoroError, TtsEngine};
+    ///
+    /// fn main() -> Result<(), KokoroError> {
+    ///     let tts = match TtsEngine::from_cached() {
+    ///         Ok(tts) => tts,
+    ///         Err(KokoroError::ModelMissing(message)) => {
+    ///             eprintln!("{}", message);
+    ///             return Ok(());
+    ///         }
+    ///         Err(e) => return Err(e),
+    ///     };
+    ///     let audio = tts.synthesize("Hello without a runtime", None, None, None)?;
+    ///     tts.save_wav("hello.wav", &audio)
+    /// }
+    /// ```
+    pub fn from_cached() -> Result<Self, KokoroError> {
+        Self::builder().build_cached()
+    }
+
+    // Load model files that are already on disk
+    pub(crate) fn load(
+        model_path: &str,
+        voices_path: &str,
+        options: &EngineBuilder,
+    ) -> Result<Self, KokoroError> {
+        // Load ONNX model
+        let session = options.session.load_session(model_path)?;
 
         // Load voices
-        let voices = load_voices(voices_path)?;
+        let voices =
+            voice_pack::load_voices(voices_path).map_err(KokoroError::ModelMissing)?;
 
-        let mut engine = Self {
-            session: Some(Arc::new(Mutex::new(session))),
+        Ok(Self::loaded(
+            session,
+            voices,
+            chunk_dir_for(model_path),
+            Some((model_path.to_string(), voices_path.to_string())),
+            options,
+        ))
+    }
+
+    /// Build an engine from model and voice-pack bytes already in memory
+    ///
+    /// For binaries that embed the model (`include_bytes!`) or fetch it
+    /// themselves: nothing is read from or written to the cache directory for the
+    /// model, and nothing is downloaded. `model_bytes` is the ONNX model,
+    /// `voices_bytes` an NPZ voice pack (`0.bin` or the 54-voice pack). Bad
+    /// bytes are a `KokoroError::ModelMissing`, as a broken file would be.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use kokoro_tiny::{KokoroError, TtsEngine};
+    ///
+    /// // In a real binary: include_bytes!("../models/0.onnx") and "../models/0.bin"
+    /// # static MODEL: &[u8] = &[];
+    /// # static VOICES: &[u8] = &[];
+    ///
+    /// fn main() -> Result<(), KokoroError> {
+    ///     let tts = TtsEngine::from_memory(MODEL, VOICES)?;
+    ///     let audio = tts.synthesize("Hello from memory", None, None, None)?;
+    ///     tts.save_wav("hello.wav", &audio)
+    /// }
+    /// ```
+    pub fn from_memory(model_bytes: &[u8], voices_bytes: &[u8]) -> Result<Self, KokoroError> {
+        Self::builder().build_from_memory(model_bytes, voices_bytes)
+    }
+
+    // The model and voices from bytes; paths and downloads don't apply
+    pub(crate) fn load_from_memory(
+        model_bytes: &[u8],
+        voices_bytes: &[u8],
+        options: &EngineBuilder,
+    ) -> Result<Self, KokoroError> {
+        // Voices first: parsing them is cheap, building the session isn't
+        let voices = voice_pack::read_voices(std::io::Cursor::new(voices_bytes))
+            .map_err(KokoroError::ModelMissing)?;
+        let session = options.session.load_session_from_memory(model_bytes)?;
+
+        Ok(Self::loaded(
+            session,
             voices,
+            options.resolved_cache_dir().join("chunks"),
+            None,
+            options,
+        ))
+    }
+
+    // Engine around a loaded session and voices
+    fn loaded(
+        (session, active_provider): (Session, ExecutionProvider),
+        voices: HashMap<String, Vec<f32>>,
+        chunk_dir: PathBuf,
+        model_files: Option<(String, String)>,
+        options: &EngineBuilder,
+    ) -> Self {
+        let mut engine = Self {
+            session: RwLock::new(Some((Arc::new(Mutex::new(session)), active_provider))),
+            spare_sessions: Mutex::new(Vec::new()),
+            voices: VoiceTable::new(voices),
             vocab: build_vocab(),
-            fallback_mode: false,
+            fallback_mode: AtomicBool::new(false),
+            formant_fallback: false,
+            warnings: WarningLog::default(),
+            cache: Mutex::new(ResultCache::new(DEFAULT_CACHE_BUDGET_BYTES)),
+            chunk_store: ChunkStore::new(chunk_dir),
+            model_files,
+            retry_policy: options.retry_policy.clone(),
+            sources: options.sources(),
+            cache_dir: options.resolved_cache_dir(),
+            session_options: options.session,
+            variant: options.variant,
+            speed_scale: SPEED_SCALE,
+            opus_bitrate: OPUS_BITRATE,
+            max_inline_duration: MAX_INLINE_DURATION,
+            acronyms: Acronyms::default(),
+            lexicon: Lexicon::default(),
             #[cfg(feature = "playback")]
             audio_device: None,
+            output: None,
+            #[cfg(test)]
+            mock_inference: None,
         };
 
         // Initialize audio device selection from cache or choose a preferred device
         #[cfg(feature = "playback")]
         {
             if engine.audio_device.is_none() {
-                if let Some(cached) = load_cached_device() {
+                if let Some(cached) = load_cached_device(&engine.cache_dir) {
                     engine.audio_device = Some(cached);
                 } else if let Ok(devs) = engine.list_audio_devices() {
                     if let Some(pref) = pick_preferred_device(&devs) {
                         // Persist preference but ignore errors
-                        let _ = save_cached_device(Some(&pref));
+                        let _ = save_cached_device(&engine.cache_dir, Some(&pref));
                         engine.audio_device = Some(pref);
                     }
                 }
             }
         }
 
-        Ok(engine)
+        engine
+    }
+
+    /// Engine without a model, for work that never runs it
+    ///
+    /// Text planning (`phonemize`, `tokenize`, `phoneme_report`), voice import,
+    /// the speed and cache settings and the audio helpers (`save_wav`,
+    /// `to_wav_bytes`) behave as usual; synthesis returns the fallback message,
+    /// as while a download is pending. Nothing is downloaded or loaded.
+    ///
+    /// # Example
+    /// ```
+    /// use kokoro_tiny::TtsEngine;
+    ///
+    /// let tts = TtsEngine::without_model();
+    /// let phonemes = tts.phonemize("Hello there", None)?.unwrap();
+    /// assert!(!tts.tokenize(&phonemes).is_empty());
+    /// # Ok::<(), String>(())
+    /// ```
+    pub fn without_model() -> Self {
+        Self::fallback(None, cache_dir().join("chunks"), &EngineBuilder::default())
+    }
+
+    // Engine in fallback mode: no session, no voices
+    fn fallback(
+        model_files: Option<(String, String)>,
+        chunk_dir: PathBuf,
+        options: &EngineBuilder,
+    ) -> Self {
+        Self {
+            session: RwLock::new(None),
+            spare_sessions: Mutex::new(Vec::new()),
+            voices: VoiceTable::new(HashMap::new()),
+            vocab: build_vocab(),
+            fallback_mode: AtomicBool::new(true),
+            formant_fallback: false,
+            warnings: WarningLog::default(),
+            cache: Mutex::new(ResultCache::new(DEFAULT_CACHE_BUDGET_BYTES)),
+            chunk_store: ChunkStore::new(chunk_dir),
+            model_files,
+            retry_policy: options.retry_policy.clone(),
+            sources: options.sources(),
+            cache_dir: options.resolved_cache_dir(),
+            session_options: options.session,
+            variant: options.variant,
+            speed_scale: SPEED_SCALE,
+            opus_bitrate: OPUS_BITRATE,
+            max_inline_duration: MAX_INLINE_DURATION,
+            acronyms: Acronyms::default(),
+            lexicon: Lexicon::default(),
+            #[cfg(feature = "playback")]
+            audio_device: None,
+            output: None,
+            #[cfg(test)]
+            mock_inference: None,
+        }
+    }
+
+    /// Whether the engine is running without the model (see `try_recover`)
+    pub fn is_fallback(&self) -> bool {
+        self.fallback_mode.load(Ordering::Acquire)
+    }
+
+    /// Try to leave fallback mode in place
+    ///
+    /// Downloads the missing model files (subject to the retry policy, so this is
+    /// cheap to call periodically) and loads them into this engine. Returns
+    /// `Ok(true)` once the engine is running the real model, `Ok(false)` if
+    /// the download failed again (see `retry_initialization` for the error).
+    /// An engine shared through an `Arc` recovers for every holder.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use kokoro_tiny::TtsEngine;
+    ///
+    /// # async fn demo() -> Result<(), String> {
+    /// let tts = TtsEngine::new().await?;
+    /// // Periodically, while running on the fallback message
+    /// if tts.try_recover().await? {
+    ///     println!("model loaded");
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn try_recover(&self) -> Result<bool, KokoroError> {
+        // No model paths: a `without_download` engine, which never fetches
+        if self.is_fallback() && self.model_files.is_none() {
+            return Ok(false);
+        }
+        recovered(self.retry_initialization().await)
+    }
+
+    /// Download and load the model again, upgrading a fallback engine in place
+    ///
+    /// Same as `try_recover`, but a failed download is returned as
+    /// `KokoroError::DownloadFailed` rather than `Ok(false)`. Files placed at
+    /// the engine's model paths by hand are picked up without any download.
+    /// Does nothing on an engine that already runs the model.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use kokoro_tiny::TtsEngine;
+    ///
+    /// # async fn demo() -> Result<(), kokoro_tiny::KokoroError> {
+    /// let tts = TtsEngine::new().await?;
+    /// if tts.is_fallback() {
+    ///     tts.retry_initialization().await?;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn retry_initialization(&self) -> Result<(), KokoroError> {
+        let (sources, policy) = (self.sources.clone(), self.retry_policy.clone());
+        self.recover_with(|model_path, voices_path| async move {
+            fetch_model_files(&model_path, &voices_path, &sources, &policy).await
+        })
+        .await
+    }
+
+    // `retry_initialization` with the download injected: `fetch(model, voices)`
+    // puts both files in place
+    async fn recover_with<F, Fut>(&self, fetch: F) -> Result<(), KokoroError>
+    where
+        F: FnOnce(String, String) -> Fut,
+        Fut: std::future::Future<Output = Result<(), KokoroError>>,
+    {
+        if !self.is_fallback() {
+            return Ok(());
+        }
+
+        let (model_path, voices_path) = self
+            .model_files
+            .clone()
+            .ok_or_else(|| {
+                KokoroError::ModelMissing("Engine has no model paths to recover from".to_string())
+            })?;
+
+        fetch(model_path.clone(), voices_path.clone()).await?;
+
+        // Tests stand in for the session with mock inference
+        #[cfg(test)]
+        let session = match &self.mock_inference {
+            Some(_) => None,
+            None => Some(self.session_options.load_session(&model_path)?),
+        };
+        #[cfg(not(test))]
+        let session = Some(self.session_options.load_session(&model_path)?);
+        let voices = voice_pack::load_voices(&voices_path).map_err(KokoroError::ModelMissing)?;
+        self.voices.replace_all(voices);
+        if let Some((session, provider)) = session {
+            if let Ok(mut slot) = self.session.write() {
+                *slot = Some((Arc::new(Mutex::new(session)), provider));
+            }
+        }
+        self.fallback_mode.store(false, Ordering::Release);
+
+        Ok(())
+    }
+
+    /// Pay the first-synthesis start-up cost now instead of on a request
+    ///
+    /// The first `synthesize` is 2-4x slower than the ones after it: ONNX
+    /// Runtime allocates lazily and espeak-ng loads its data on first use.
+    /// This runs a throwaway phonemization and a few-token inference with the
+    /// default voice (or the first one loaded), and returns how long that
+    /// took. Nothing is cached, played or kept. In fallback mode there is no
+    /// model, so only espeak is warmed. `EngineBuilder::warm` does this
+    /// during construction.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use kokoro_tiny::TtsEngine;
+    ///
+    /// # async fn demo() -> Result<(), kokoro_tiny::KokoroError> {
+    /// let tts = TtsEngine::new().await?;
+    /// let took = tts.warm_up()?;
+    /// println!("ready in {:?}", took);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn warm_up(&self) -> Result<Duration, KokoroError> {
+        let start = std::time::Instant::now();
+        let phonemes = self.phonemize("Hello.", Some("en"))?;
+        if self.is_fallback() {
+            return Ok(start.elapsed());
+        }
+
+        let voice = if self.has_voice(DEFAULT_VOICE) {
+            DEFAULT_VOICE.to_string()
+        } else {
+            self.voices().into_iter().next().ok_or_else(|| {
+                KokoroError::ModelMissing("Voice pack has no voices".to_string())
+            })?
+        };
+        let style = self.parse_voice_style(&voice)?;
+        let tokens = self.tokenize(phonemes.as_deref().unwrap_or("$$$həlˈoʊ$$$"));
+        self.run_inference(tokens, style, self.speed_scale)?;
+        Ok(start.elapsed())
+    }
+
+    /// How well `code` is supported (see `languages`)
+    ///
+    /// # Example
+    /// ```
+    /// use kokoro_tiny::{LanguageSupport, TtsEngine};
+    ///
+    /// let tts = TtsEngine::without_model();
+    /// assert_eq!(tts.language_support("en-gb"), LanguageSupport::Full);
+    /// assert!(matches!(tts.language_support("fr"), LanguageSupport::Partial { .. }));
+    /// assert_eq!(tts.language_support("xx"), LanguageSupport::Unsupported);
+    /// ```
+    pub fn language_support(&self, code: &str) -> LanguageSupport {
+        languages::language_support(code)
+    }
+
+    /// Language synthesis uses for `voice` when no `lang` is given
+    ///
+    /// Non-English voices speak their own language (from the name prefix,
+    /// see `languages::voice_language`; for a mix the first voice decides).
+    /// English voices and names without a known prefix get `"en"`.
+    ///
+    /// # Example
+    /// ```
+    /// use kokoro_tiny::TtsEngine;
+    ///
+    /// let tts = TtsEngine::without_model();
+    /// assert_eq!(tts.language_for_voice("ef_dora"), "es");
+    /// assert_eq!(tts.language_for_voice("pf_dora.7+af_sky.3"), "pt-br");
+    /// assert_eq!(tts.language_for_voice("bf_emma"), "en");
+    /// assert_eq!(tts.language_for_voice("narrator"), "en");
+    /// ```
+    pub fn language_for_voice(&self, voice: &str) -> String {
+        default_lang(Some(voice))
     }
 
     /// List all available voices
+    ///
+    /// Without the model this is just `"fallback"`, unless voices were
+    /// imported (see `import_voice`), which are listed as usual.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use kokoro_tiny::TtsEngine;
+    ///
+    /// # async fn demo() -> Result<(), String> {
+    /// let tts = TtsEngine::new().await?;
+    /// for voice in tts.voices() {
+    ///     println!("{}", voice);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
     pub fn voices(&self) -> Vec<String> {
-        if self.fallback_mode {
+        let voices: Vec<String> = self.voices.snapshot().keys().cloned().collect();
+        if self.is_fallback() && voices.is_empty() {
             vec!["fallback".to_string()]
         } else {
-            self.voices.keys().cloned().collect()
+            voices
+        }
+    }
+
+    /// Whether a voice called `name` is loaded (a single name, not a mix)
+    ///
+    /// Always false in fallback mode, where no voices are loaded. An unknown
+    /// voice in a synthesis call is `KokoroError::VoiceNotFound`, which lists
+    /// the closest loaded names.
+    ///
+    /// # Example
+    /// ```
+    /// use kokoro_tiny::TtsEngine;
+    ///
+    /// let tts = TtsEngine::without_model();
+    /// let voice = if tts.has_voice("af_bella") { "af_bella" } else { "af_sky" };
+    /// # assert_eq!(voice, "af_sky");
+    /// ```
+    pub fn has_voice(&self, name: &str) -> bool {
+        self.voices.snapshot().contains_key(name)
+    }
+
+    /// Add a voice from a raw style vector, replacing any voice with that name
+    ///
+    /// Safe to call while other threads synthesize: renders already in progress
+    /// keep the style they started with. Cached results are dropped, since
+    /// they are keyed by voice name rather than style.
+    ///
+    /// # Example
+    /// ```
+    /// use kokoro_tiny::{TtsEngine, VoiceMix, STYLE_DIM};
+    ///
+    /// let tts = TtsEngine::without_model();
+    /// tts.import_voice("narrator", vec![0.1; STYLE_DIM])?;
+    /// tts.validate_voice_mix(&VoiceMix::parse("narrator")?)?;
+    /// # Ok::<(), kokoro_tiny::KokoroError>(())
+    /// ```
+    pub fn import_voice(&self, name: &str, style: Vec<f32>) -> Result<(), KokoroError> {
+        if name.is_empty() || name.contains(['+', '.']) || name.trim() != name {
+            return Err(KokoroError::InvalidInput(format!(
+                "Invalid voice name: {:?}",
+                name
+            )));
+        }
+        if style.len() < STYLE_DIM || !style.len().is_multiple_of(STYLE_DIM) {
+            return Err(KokoroError::InvalidInput(format!(
+                "Style vector for {} has {} values, expected a multiple of {}",
+                name,
+                style.len(),
+                STYLE_DIM
+            )));
+        }
+        if style.iter().any(|v| !v.is_finite()) {
+            return Err(KokoroError::InvalidInput(format!(
+                "Style vector for {} contains NaN or infinite values",
+                name
+            )));
+        }
+
+        self.voices.insert(name, style);
+        self.forget_cached_results();
+        Ok(())
+    }
+
+    /// Remove a voice; returns false if it wasn't loaded
+    ///
+    /// Takes effect for the next synthesis, never one already in progress.
+    /// Cached results are dropped along with it.
+    ///
+    /// # Example
+    /// ```
+    /// use kokoro_tiny::{TtsEngine, STYLE_DIM};
+    ///
+    /// let tts = TtsEngine::without_model();
+    /// tts.import_voice("narrator", vec![0.1; STYLE_DIM])?;
+    /// assert!(tts.remove_voice("narrator"));
+    /// assert!(!tts.remove_voice("narrator"));
+    /// # Ok::<(), kokoro_tiny::KokoroError>(())
+    /// ```
+    pub fn remove_voice(&self, name: &str) -> bool {
+        let removed = self.voices.remove(name);
+        if removed {
+            self.forget_cached_results();
+        }
+        removed
+    }
+
+    // Cache keys name voices, so a changed voice table invalidates them all
+    fn forget_cached_results(&self) {
+        if let Ok(mut cache) = self.cache.lock() {
+            cache.clear();
         }
     }
 
     /// List all available audio output devices (requires 'playback' feature)
+    ///
+    /// # Example
+    /// ```no_run
+    /// use kokoro_tiny::TtsEngine;
+    ///
+    /// # async fn demo() -> Result<(), String> {
+    /// let tts = TtsEngine::new().await?;
+    /// for device in tts.list_audio_devices()? {
+    ///     println!("{}", device);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
     #[cfg(feature = "playback")]
     pub fn list_audio_devices(&self) -> Result<Vec<String>, String> {
         use cpal::traits::{DeviceTrait, HostTrait};
@@ -385,6 +2542,19 @@ impl TtsEngine {
 
     /// Set the audio output device by name (requires 'playback' feature)
     /// Pass None to use system default
+    ///
+    /// # Example
+    /// ```no_run
+    /// use kokoro_tiny::TtsEngine;
+    ///
+    /// # async fn demo() -> Result<(), String> {
+    /// let mut tts = TtsEngine::new().await?;
+    /// tts.set_audio_device(Some("Built-in Output".to_string()))?;
+    /// // Back to the system default
+    /// tts.set_audio_device(None)?;
+    /// # Ok(())
+    /// # }
+    /// ```
     #[cfg(feature = "playback")]
     pub fn set_audio_device(&mut self, device_name: Option<String>) -> Result<(), String> {
         // Verify the device exists if a name was provided
@@ -402,7 +2572,7 @@ impl TtsEngine {
         self.audio_device = device_name;
         // Persist selection
         #[cfg(feature = "playback")]
-        if let Err(e) = save_cached_device(self.audio_device.as_deref()) {
+        if let Err(e) = save_cached_device(&self.cache_dir, self.audio_device.as_deref()) {
             #[cfg(not(feature = "as-lib"))]
             eprintln!("⚠️ Failed to save audio device selection: {}", e);
         }
@@ -410,83 +2580,317 @@ impl TtsEngine {
     }
 
     /// Get the currently selected audio device (requires 'playback' feature)
+    ///
+    /// # Example
+    /// ```no_run
+    /// use kokoro_tiny::TtsEngine;
+    ///
+    /// # async fn demo() -> Result<(), String> {
+    /// let tts = TtsEngine::new().await?;
+    /// println!("output: {}", tts.get_audio_device().unwrap_or("system default"));
+    /// # Ok(())
+    /// # }
+    /// ```
     #[cfg(feature = "playback")]
     pub fn get_audio_device(&self) -> Option<&str> {
         self.audio_device.as_deref()
     }
 
+    /// Send all playback to `output` instead of the audio device
+    ///
+    /// For routing speech into a mixer or JACK client, and for tests
+    /// (`NullOutput`). Takes precedence over `set_audio_device`.
+    ///
+    /// # Example
+    /// ```
+    /// use kokoro_tiny::{NullOutput, PlaybackOptions, TtsEngine};
+    /// use std::sync::Arc;
+    ///
+    /// let output = NullOutput::new().with_sample_rate(48_000);
+    /// let mut tts = TtsEngine::without_model();
+    /// tts.set_output(Arc::new(output.clone()));
+    ///
+    /// let info = tts.play_with_options(&[0.1; 240], &PlaybackOptions::new())?;
+    /// assert_eq!(info.device, "null");
+    /// // Resampled to the stream's rate
+    /// assert_eq!(output.frames().len(), 480);
+    /// # Ok::<(), String>(())
+    /// ```
+    pub fn set_output(&mut self, output: Arc<dyn AudioOutput>) {
+        self.output = Some(output);
+    }
+
+    /// The backend playback goes to: the one from `set_output`, else the device
+    ///
+    /// Without the `playback` feature there is no device backend, so this is
+    /// an error until `set_output` is called.
+    pub fn output(&self) -> Result<Arc<dyn AudioOutput>, String> {
+        if let Some(output) = &self.output {
+            return Ok(output.clone());
+        }
+        #[cfg(feature = "playback")]
+        {
+            Ok(Arc::new(output::CpalOutput::new(self.audio_device.clone())))
+        }
+        #[cfg(not(feature = "playback"))]
+        {
+            Err("Playback feature not enabled and no output set (see set_output)".to_string())
+        }
+    }
+
     /// Synthesize text to speech (simple form)
     ///
-    /// This is the ergonomic two-argument form used by examples and callers:
-    /// - `text`: text to speak
-    /// - `voice`: optional voice name (defaults to `DEFAULT_VOICE`)
+    /// Every argument after `text` is optional:
+    /// - `voice`: voice name or mix (default `"af_sky"`)
+    /// - `speed`: natural speed (default 1.0)
+    /// - `lang`: espeak language code (default `"en"`)
+    ///
+    /// For gain, pacing and the rest, use `synthesize_with`.
     ///
-    /// For callers that need to control speed, use `synthesize_with_speed`.
-    pub fn synthesize(&mut self, text: &str, voice: Option<&str>, speed: Option<f32>, lang: Option<&str>) -> Result<Vec<f32>, String> {
+    /// # Example
+    /// ```no_run
+    /// use kokoro_tiny::TtsEngine;
+    ///
+    /// # async fn demo() -> Result<(), String> {
+    /// let tts = TtsEngine::new().await?;
+    /// // Default voice, speed and language
+    /// let audio = tts.synthesize("Hello world!", None, None, None)?;
+    /// // A 60/40 mix of two voices, a little faster
+    /// let audio = tts.synthesize("Blended voice", Some("af_sky.6+af_nicole.4"), Some(1.1), None)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn synthesize(&self, text: &str, voice: Option<&str>, speed: Option<f32>, lang: Option<&str>) -> Result<Vec<f32>, KokoroError> {
         // Forward to the speed-aware variant with the supplied or default user speed
-        self.synthesize_with_speed(text, voice, speed.unwrap_or(DEFAULT_SPEED), Some(lang.unwrap_or(DEFAULT_LANG)))
+        self.synthesize_with_speed(text, voice, speed.unwrap_or(DEFAULT_SPEED), lang)
     }
 
     /// Backwards-compatible synthesize API which accepted an optional `speed`.
     ///
     /// This method preserves the original three-argument shape for compatibility
     /// with older code that passed `Option<f32>` for speed.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use kokoro_tiny::TtsEngine;
+    ///
+    /// # async fn demo() -> Result<(), String> {
+    /// let tts = TtsEngine::new().await?;
+    /// let audio = tts.synthesize_with_optional_speed("Hello", Some("af_sky"), None, None)?;
+    /// # Ok(())
+    /// # }
+    /// ```
     #[deprecated(note = "use synthesize(text, voice) or synthesize_with_speed for custom speed")]
     pub fn synthesize_with_optional_speed(
-        &mut self,
+        &self,
         text: &str,
         voice: Option<&str>,
         speed: Option<f32>,
         lang: Option<&str>
-    ) -> Result<Vec<f32>, String> {
-        self.synthesize_with_speed(text, voice, speed.unwrap_or(DEFAULT_SPEED), Some(lang.unwrap_or(DEFAULT_LANG)))
+    ) -> Result<Vec<f32>, KokoroError> {
+        self.synthesize_with_speed(text, voice, speed.unwrap_or(DEFAULT_SPEED), lang)
     }
 
     /// Synthesize text to speech with custom speed
     /// Speed: 0.5 = half speed (slower), 1.0 = normal, 2.0 = double speed (faster)
+    ///
+    /// # Example
+    /// ```no_run
+    /// use kokoro_tiny::TtsEngine;
+    ///
+    /// # async fn demo() -> Result<(), String> {
+    /// let tts = TtsEngine::new().await?;
+    /// let audio = tts.synthesize_with_speed("Slowly now", Some("af_sky"), 0.8, None)?;
+    /// # Ok(())
+    /// # }
+    /// ```
     pub fn synthesize_with_speed(
-        &mut self,
+        &self,
         text: &str,
         voice: Option<&str>,
         speed: f32,
         lang: Option<&str>,
-    ) -> Result<Vec<f32>, String> {
-        self.synthesize_with_options(text, voice, speed, 1.0, Some(lang.unwrap_or(DEFAULT_LANG)))
+    ) -> Result<Vec<f32>, KokoroError> {
+        self.synthesize_with_options(text, voice, speed, 1.0, lang)
     }
 
     /// Synthesize using a builder-style options struct for better ergonomics.
     ///
-    /// Example:
-    /// `tts.synthesize_with("Hello", SynthesizeOptions::default().voice("af_sky").speed(1.1))`
+    /// # Example
+    /// ```no_run
+    /// use kokoro_tiny::{PacingPreset, SynthesizeOptions, TtsEngine};
+    ///
+    /// # async fn demo() -> Result<(), String> {
+    /// let tts = TtsEngine::new().await?;
+    /// let opts = SynthesizeOptions::new()
+    ///     .voice("af_bella")
+    ///     .speed(1.1)
+    ///     .gain(1.5)
+    ///     .pacing(PacingPreset::Audiobook);
+    /// let audio = tts.synthesize_with("Chapter one. It was a dark and stormy night.", opts)?;
+    /// # Ok(())
+    /// # }
+    /// ```
     pub fn synthesize_with(
-        &mut self,
+        &self,
         text: &str,
         opts: SynthesizeOptions,
-    ) -> Result<Vec<f32>, String> {
-        let voice_opt = opts.voice.as_deref();
-        self.synthesize_with_options(text, voice_opt, opts.speed, opts.gain, Some(opts.lang.as_deref().unwrap_or(DEFAULT_LANG)))
+    ) -> Result<Vec<f32>, KokoroError> {
+        if opts.output_sample_rate == Some(0) {
+            return Err(KokoroError::InvalidInput(
+                "Output sample rate must be above 0".to_string(),
+            ));
+        }
+        if !(opts.post_stretch.is_finite() && opts.post_stretch > 0.0) {
+            return Err(KokoroError::InvalidInput(format!(
+                "Stretch factor must be a positive number, got {}",
+                opts.post_stretch
+            )));
+        }
+        let mut audio = self.synthesize_text(text, &opts)?;
+        let reshaped = opts.post_stretch != 1.0 || opts.pitch_semitones != 0.0;
+        if reshaped {
+            audio = time_stretch(&audio, opts.post_stretch);
+            audio = pitch_shift(&audio, opts.pitch_semitones);
+        }
+        if let Some(preset) = opts.filter {
+            audio = filters::apply(&audio, preset);
+        }
+        if reshaped || opts.filter.is_some() {
+            // The processed tail no longer ends exactly where the fade did
+            audio::fade_out(&mut audio, opts.edge_fade_ms);
+        }
+        let audio = match opts.output_sample_rate {
+            Some(rate) => resample(&audio, SAMPLE_RATE, rate),
+            None => audio,
+        };
+        Ok(match opts.channels {
+            Channels::Mono => audio,
+            Channels::Stereo { pan } => audio::to_stereo(&audio, pan),
+        })
+    }
+
+    /// `synthesize_with` on tokio's blocking thread pool, for async callers
+    ///
+    /// Inference is hundreds of milliseconds of CPU; run inline it would hold
+    /// an executor thread that long. The engine is shared through its `Arc`,
+    /// so any number of tasks can await this at once. Warnings are recorded
+    /// on the blocking thread, so `collect_warnings` around the `await`
+    /// doesn't see them; `last_warnings` does.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use std::sync::Arc;
+    /// use kokoro_tiny::{SynthesizeOptions, TtsEngine};
+    ///
+    /// # async fn demo() -> Result<(), kokoro_tiny::KokoroError> {
+    /// let tts = Arc::new(TtsEngine::new().await?);
+    /// let (a, b) = tokio::join!(
+    ///     tts.synthesize_async("First request", SynthesizeOptions::new()),
+    ///     tts.synthesize_async("Second request", SynthesizeOptions::new().voice("am_adam")),
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn synthesize_async(
+        self: &Arc<Self>,
+        text: &str,
+        opts: SynthesizeOptions,
+    ) -> impl std::future::Future<Output = Result<Vec<f32>, KokoroError>> + Send + 'static {
+        let engine = Arc::clone(self);
+        let text = text.to_string();
+        async move {
+            tokio::task::spawn_blocking(move || engine.synthesize_with(&text, opts))
+                .await
+                .map_err(|e| KokoroError::InferenceFailed(format!("Synthesis task failed: {}", e)))?
+        }
+    }
+
+    /// Synthesize several pieces, each with its own options, as one clip
+    ///
+    /// For text that switches language (or voice) part way, e.g. an English
+    /// announcement with a Spanish name in it. Pieces are joined end to end;
+    /// the warnings of all of them end up in `last_warnings`.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use kokoro_tiny::{SynthesizeOptions, TtsEngine};
+    ///
+    /// # async fn demo() -> Result<(), kokoro_tiny::KokoroError> {
+    /// let tts = TtsEngine::new().await?;
+    /// let audio = tts.synthesize_segments(vec![
+    ///     ("Next stop:".to_string(), SynthesizeOptions::new().voice("af_sky")),
+    ///     ("Plaza de España.".to_string(), SynthesizeOptions::new().voice("ef_dora")),
+    /// ])?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn synthesize_segments(
+        &self,
+        segments: impl IntoIterator<Item = (String, SynthesizeOptions)>,
+    ) -> Result<Vec<f32>, KokoroError> {
+        self.warnings
+            .record(|| {
+                let mut audio = Vec::new();
+                for (text, opts) in segments {
+                    audio.extend(self.synthesize_with(&text, opts)?);
+                }
+                if audio.is_empty() {
+                    return Err(KokoroError::InvalidInput(
+                        "No text provided for synthesis".to_string(),
+                    ));
+                }
+                Ok(audio)
+            })
+            .0
     }
 
     /// Process long text by splitting into chunks (alias for backwards compatibility)
     /// This method exists for API compatibility - synthesize() already handles long text automatically
+    ///
+    /// # Example
+    /// ```no_run
+    /// use kokoro_tiny::TtsEngine;
+    ///
+    /// # async fn demo() -> Result<(), String> {
+    /// let tts = TtsEngine::new().await?;
+    /// let chapter = std::fs::read_to_string("chapter1.txt").map_err(|e| e.to_string())?;
+    /// let audio = tts.process_long_text(&chapter, Some("af_sky"), None)?;
+    /// # Ok(())
+    /// # }
+    /// ```
     pub fn process_long_text(
-        &mut self,
+        &self,
         text: &str,
         voice: Option<&str>,
         speed: Option<f32>,
-    ) -> Result<Vec<f32>, String> {
+    ) -> Result<Vec<f32>, KokoroError> {
         // Forward to speed-aware variant (use default if None)
-        self.synthesize_with_speed(text, voice, speed.unwrap_or(DEFAULT_SPEED), Some(lang.unwrap_or(DEFAULT_LANG)))
+        self.synthesize_with_speed(text, voice, speed.unwrap_or(DEFAULT_SPEED), None)
     }
 
     /// Synthesize speech from text with validation warnings (backwards compatibility)
     /// Returns both the audio and any warnings about the text
+    ///
+    /// # Example
+    /// ```no_run
+    /// use kokoro_tiny::TtsEngine;
+    ///
+    /// # async fn demo() -> Result<(), String> {
+    /// let tts = TtsEngine::new().await?;
+    /// let (audio, warnings) = tts.synthesize_with_warnings("BUILD FAILED :(", Some("af_sky"), None)?;
+    /// for warning in warnings {
+    ///     eprintln!("{}", warning);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
     pub fn synthesize_with_warnings(
-        &mut self,
+        &self,
         text: &str,
         voice: Option<&str>,
         speed: Option<f32>,
-    ) -> Result<(Vec<f32>, Vec<String>), String> {
+    ) -> Result<(Vec<f32>, Vec<String>), KokoroError> {
         let mut warnings = Vec::new();
 
         if text.is_empty() {
@@ -500,112 +2904,600 @@ impl TtsEngine {
             ));
         }
 
-        let audio = self.synthesize_with_speed(text, voice, speed.unwrap_or(DEFAULT_SPEED), Some(lang.unwrap_or(DEFAULT_LANG)))?;
+        let (audio, synthesis_warnings) = collect_warnings(|| {
+            self.synthesize_with_speed(text, voice, speed.unwrap_or(DEFAULT_SPEED), None)
+        });
+        warnings.extend(synthesis_warnings.iter().map(|w| w.to_string()));
+        let audio = audio?;
         Ok((audio, warnings))
     }
 
     /// Synthesize text to speech with full options
     /// Speed: 0.5 = half speed (slower), 1.0 = normal, 2.0 = double speed (faster)
-    /// Gain: 0.5 = quieter, 1.0 = normal, 2.0 = twice as loud (with soft clipping)
+    /// Gain: 0.5 = quieter, 1.0 = normal, 2.0 = twice as loud (peaks limited)
+    ///
+    /// # Example
+    /// ```no_run
+    /// use kokoro_tiny::TtsEngine;
+    ///
+    /// # async fn demo() -> Result<(), String> {
+    /// let tts = TtsEngine::new().await?;
+    /// let audio = tts.synthesize_with_options("Louder", Some("am_adam"), 1.0, 2.0, Some("en"))?;
+    /// # Ok(())
+    /// # }
+    /// ```
     pub fn synthesize_with_options(
-        &mut self,
+        &self,
         text: &str,
         voice: Option<&str>,
         speed: f32,
         gain: f32,
         lang: Option<&str>
-    ) -> Result<Vec<f32>, String> {
+    ) -> Result<Vec<f32>, KokoroError> {
+        let opts = SynthesizeOptions {
+            voice: voice.map(str::to_string),
+            speed,
+            gain,
+            lang: lang.map(str::to_string),
+            ..Default::default()
+        };
+        self.synthesize_text(text, &opts)
+    }
+
+    fn synthesize_text(
+        &self,
+        text: &str,
+        opts: &SynthesizeOptions,
+    ) -> Result<Vec<f32>, KokoroError> {
+        self.warnings.record(|| self.render_text(text, opts)).0
+    }
+
+    fn render_text(&self, text: &str, opts: &SynthesizeOptions) -> Result<Vec<f32>, KokoroError> {
         // If in fallback mode, return the excuse message audio
-        if self.fallback_mode {
+        if self.is_fallback() {
             // println!("🎤 Playing fallback message while downloading voice model...");
-            return wav_to_f32(FALLBACK_MESSAGE);
+            return self.fallback_audio(text);
+        }
+        if opts.markup {
+            return self.synthesize_markup(text, opts);
         }
 
-        let session = self
-            .session
-            .as_ref()
-            .ok_or_else(|| "TTS engine not initialized".to_string())?;
+        let key = cache::cache_key(text, opts);
+        let cached = if opts.is_repeatable() {
+            self.cache.lock().ok().and_then(|mut cache| cache.get(&key))
+        } else {
+            None
+        };
+        if let Some(cached) = cached {
+            stats::record_cache_hit();
+            for warning in cached.warnings {
+                self.warnings.push(warning);
+            }
+            return Ok(cached.audio);
+        }
 
-        // Map user-facing speed to model speed (user 1.0 = model 0.65)
-        let model_speed = speed * SPEED_SCALE;
-        let clamped_speed = model_speed.clamp(MIN_ENGINE_SPEED, MAX_ENGINE_SPEED);
-        let voice = voice.unwrap_or(DEFAULT_VOICE);
+        let plan = SynthesisPlan::new(text, opts);
+        if plan.chunks.is_empty() {
+            return Err(KokoroError::InvalidInput(
+                "No text provided for synthesis".to_string(),
+            ));
+        }
 
-        // Parse voice style (e.g., "af_sky.8+af_bella.2" for mixing)
-        let style = self.parse_voice_style(voice)?;
+        let (audio, warnings) = collect_warnings(|| self.resume_synthesis(&plan, 0, Vec::new()));
+        let audio = audio.map_err(|partial| partial.into_error())?;
 
-        // Short form: synthesize in one pass for predictable cadence
-        if !needs_chunking(text) {
-            let mut audio = self.synthesize_segment(session, &style, text, clamped_speed, lang)?;
-            if gain != 1.0 {
-                audio = amplify_audio(&audio, gain);
-            }
-            return Ok(audio);
+        if plan.chunks.len() > 1 && audio.is_empty() {
+            return Err(KokoroError::InferenceFailed(
+                "Failed to synthesize combined audio".to_string(),
+            ));
         }
 
-        // Long-form synthesis path - chunk the text while preserving pacing
-        let prepared_chunks: Vec<String> = split_text_for_tts(text, MAX_CHARS_PER_CHUNK)
-            .into_iter()
-            .filter(|chunk| !chunk.trim().is_empty())
-            .collect();
+        if let (true, Ok(mut cache)) = (opts.is_repeatable(), self.cache.lock()) {
+            cache.insert(
+                key,
+                CachedAudio {
+                    audio: audio.clone(),
+                    warnings,
+                },
+            );
+        }
+
+        Ok(audio)
+    }
+
+    /// Synthesize `text` (if not cached yet) and keep it cached regardless of
+    /// eviction pressure, e.g. for phrases spoken at startup
+    ///
+    /// # Example
+    /// ```no_run
+    /// use kokoro_tiny::{SynthesizeOptions, TtsEngine};
+    ///
+    /// # async fn demo() -> Result<(), String> {
+    /// let tts = TtsEngine::new().await?;
+    /// let opts = SynthesizeOptions::new().voice("af_sky");
+    /// tts.pin("Build complete", opts.clone())?;
+    /// // Served from memory from now on
+    /// let audio = tts.synthesize_with("Build complete", opts)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn pin(&self, text: &str, opts: SynthesizeOptions) -> Result<(), KokoroError> {
+        if !opts.is_repeatable() {
+            return Err(KokoroError::InvalidInput(
+                "Unseeded style_temperature gives a new take on every render, so there is \
+                 nothing to pin; pass a seed"
+                    .to_string(),
+            ));
+        }
+        self.synthesize_text(text, &opts)?;
+
+        let key = cache::cache_key(text, &opts);
+        let pinned = self.cache.lock().map(|mut cache| cache.pin(&key)).unwrap_or(false);
+        if pinned {
+            Ok(())
+        } else {
+            Err(KokoroError::InvalidInput(
+                "Audio is too large to cache with the current budget".to_string(),
+            ))
+        }
+    }
+
+    /// Release an entry pinned with `pin`
+    ///
+    /// # Example
+    /// ```no_run
+    /// use kokoro_tiny::{SynthesizeOptions, TtsEngine};
+    ///
+    /// # async fn demo() -> Result<(), String> {
+    /// let tts = TtsEngine::new().await?;
+    /// let opts = SynthesizeOptions::new();
+    /// tts.pin("Good morning", opts.clone())?;
+    /// // Evictable again once the morning is over
+    /// tts.unpin("Good morning", &opts);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn unpin(&self, text: &str, opts: &SynthesizeOptions) {
+        if let Ok(mut cache) = self.cache.lock() {
+            cache.unpin(&cache::cache_key(text, opts));
+        }
+    }
+
+    /// Memory budget for cached results; 0 disables caching
+    ///
+    /// # Example
+    /// ```
+    /// use kokoro_tiny::TtsEngine;
+    ///
+    /// let mut tts = TtsEngine::without_model();
+    /// tts.set_cache_budget(16 * 1024 * 1024);
+    /// assert_eq!(tts.cache_stats().budget_bytes, 16 * 1024 * 1024);
+    /// ```
+    pub fn set_cache_budget(&mut self, bytes: usize) {
+        if let Ok(mut cache) = self.cache.lock() {
+            cache.set_budget(bytes);
+        }
+    }
+
+    /// Drop every cached result, pinned ones included
+    ///
+    /// # Example
+    /// ```
+    /// use kokoro_tiny::TtsEngine;
+    ///
+    /// let mut tts = TtsEngine::without_model();
+    /// tts.clear_cache();
+    /// assert_eq!(tts.cache_stats().entries, 0);
+    /// ```
+    pub fn clear_cache(&mut self) {
+        if let Ok(mut cache) = self.cache.lock() {
+            cache.clear();
+        }
+    }
+
+    /// Directory for the on-disk chunk cache (default: `chunks/` next to the model)
+    ///
+    /// # Example
+    /// ```
+    /// use kokoro_tiny::TtsEngine;
+    ///
+    /// let mut tts = TtsEngine::without_model();
+    /// tts.set_chunk_cache_dir(std::env::temp_dir().join("kokoro-chunks"));
+    /// ```
+    pub fn set_chunk_cache_dir(&mut self, dir: impl Into<PathBuf>) {
+        self.chunk_store.dir = dir.into();
+    }
+
+    /// Size limit for the chunk cache directory; least recently used chunks go first
+    ///
+    /// # Example
+    /// ```
+    /// use kokoro_tiny::TtsEngine;
+    ///
+    /// let mut tts = TtsEngine::without_model();
+    /// // Keep at most 64 MB of rendered chunks on disk
+    /// tts.set_chunk_cache_limit(64 * 1024 * 1024);
+    /// ```
+    pub fn set_chunk_cache_limit(&mut self, bytes: u64) {
+        self.chunk_store.max_bytes = bytes;
+    }
+
+    /// Model speed per unit of natural speed (default 0.65, see `SpeedMode`)
+    ///
+    /// Changing it drops cached results, which were rendered at the old scale.
+    ///
+    /// # Example
+    /// ```
+    /// use kokoro_tiny::{SynthesizeOptions, TtsEngine};
+    ///
+    /// let mut tts = TtsEngine::without_model();
+    /// tts.set_speed_scale(0.75)?;
+    /// let opts = SynthesizeOptions::new().speed(1.0);
+    /// assert_eq!(tts.model_speed(&opts), 0.75);
+    /// assert!(tts.set_speed_scale(0.0).is_err());
+    /// # Ok::<(), kokoro_tiny::KokoroError>(())
+    /// ```
+    pub fn set_speed_scale(&mut self, scale: f32) -> Result<(), KokoroError> {
+        if !scale.is_finite() || scale <= 0.0 {
+            return Err(KokoroError::InvalidInput(format!(
+                "Speed scale must be a positive number, got {}",
+                scale
+            )));
+        }
+        if scale != self.speed_scale {
+            self.speed_scale = scale;
+            self.clear_cache();
+        }
+        Ok(())
+    }
+
+    /// Bits per second of the `.opus` files `save_audio` writes (default
+    /// 24000; Opus takes 6000 to 510000)
+    ///
+    /// # Example
+    /// ```
+    /// use kokoro_tiny::TtsEngine;
+    ///
+    /// let mut tts = TtsEngine::without_model();
+    /// // Music beds and voices that should survive re-encoding
+    /// tts.set_opus_bitrate(64_000)?;
+    /// assert!(tts.set_opus_bitrate(1_000).is_err());
+    /// # Ok::<(), kokoro_tiny::KokoroError>(())
+    /// ```
+    pub fn set_opus_bitrate(&mut self, bits_per_second: i32) -> Result<(), KokoroError> {
+        if !(6_000..=510_000).contains(&bits_per_second) {
+            return Err(KokoroError::InvalidInput(format!(
+                "Opus bitrate must be 6000 to 510000 bits per second, got {}",
+                bits_per_second
+            )));
+        }
+        self.opus_bitrate = bits_per_second;
+        Ok(())
+    }
+
+    /// Longest audio `to_base64`, `to_wav_base64` and `to_data_url` encode
+    /// (default 60 seconds); longer audio is an `InvalidInput` error rather
+    /// than a payload of many megabytes
+    ///
+    /// # Example
+    /// ```
+    /// use kokoro_tiny::TtsEngine;
+    /// use std::time::Duration;
+    ///
+    /// let mut tts = TtsEngine::without_model();
+    /// tts.set_max_inline_duration(Duration::from_secs(5));
+    /// assert!(tts.to_wav_base64(&[0.0; 24_000 * 6]).is_err());
+    /// ```
+    pub fn set_max_inline_duration(&mut self, max: Duration) {
+        self.max_inline_duration = max;
+    }
+
+    /// Read `acronym` as `spoken` from now on
+    ///
+    /// Overrides the built-in table (see `acronyms`) and reaches plurals and
+    /// acronyms inside words too ("SQLs", "MySQL"). Drops cached results, which
+    /// were rendered with the old reading.
+    ///
+    /// # Example
+    /// ```
+    /// use kokoro_tiny::{Spoken, TtsEngine};
+    ///
+    /// let mut tts = TtsEngine::without_model();
+    /// tts.add_acronym("SQL", Spoken::Custom("sequel".into()));
+    /// tts.add_acronym("K8S", Spoken::Custom("kubernetes".into()));
+    /// tts.add_acronym("GIF", Spoken::Letters);
+    /// assert_eq!(tts.acronyms().get("SQL"), Some(Spoken::Custom("sequel".into())));
+    /// ```
+    pub fn add_acronym(&mut self, acronym: &str, spoken: Spoken) {
+        self.acronyms.insert(acronym, spoken);
+        self.clear_cache();
+    }
+
+    /// The acronyms this engine reads in a particular way
+    pub fn acronyms(&self) -> &Acronyms {
+        &self.acronyms
+    }
+
+    /// Model speed per unit of natural speed currently in use
+    ///
+    /// # Example
+    /// ```
+    /// use kokoro_tiny::TtsEngine;
+    ///
+    /// let tts = TtsEngine::without_model();
+    /// assert_eq!(tts.speed_scale(), 0.65);
+    /// ```
+    pub fn speed_scale(&self) -> f32 {
+        self.speed_scale
+    }
+
+    /// Speed the model will actually be run at for `opts` (after scaling and clamping)
+    ///
+    /// # Example
+    /// ```
+    /// use kokoro_tiny::{SpeedMode, SynthesizeOptions, TtsEngine};
+    ///
+    /// let tts = TtsEngine::without_model();
+    /// let natural = SynthesizeOptions::new().speed(1.0);
+    /// assert_eq!(tts.model_speed(&natural), 0.65);
+    /// let raw = SynthesizeOptions::new().speed(1.0).speed_mode(SpeedMode::Model);
+    /// assert_eq!(tts.model_speed(&raw), 1.0);
+    /// // Clamped to what the model handles
+    /// assert_eq!(tts.model_speed(&raw.speed(10.0)), 2.2);
+    /// ```
+    pub fn model_speed(&self, opts: &SynthesizeOptions) -> f32 {
+        self.engine_speed(opts.speed, opts.speed_mode)
+    }
+
+    pub(crate) fn engine_speed(&self, speed: f32, mode: SpeedMode) -> f32 {
+        let speed = match mode {
+            SpeedMode::Natural => speed * self.speed_scale,
+            SpeedMode::Model => speed,
+        };
+        speed.clamp(MIN_ENGINE_SPEED, MAX_ENGINE_SPEED)
+    }
+
+    /// Entry count, memory use, hit rate and evictions of the result cache
+    ///
+    /// # Example
+    /// ```
+    /// use kokoro_tiny::TtsEngine;
+    ///
+    /// let tts = TtsEngine::without_model();
+    /// let stats = tts.cache_stats();
+    /// println!("{} entries, {} bytes, hit rate {:.0}%", stats.entries, stats.bytes, stats.hit_rate * 100.0);
+    /// ```
+    pub fn cache_stats(&self) -> CacheStats {
+        self.cache
+            .lock()
+            .map(|cache| cache.stats())
+            .unwrap_or_default()
+    }
+
+    /// Warnings recorded by the most recent synthesis call
+    ///
+    /// With several threads rendering on one shared engine, this is whichever
+    /// call finished last; wrap a call in [`collect_warnings`] to get its own.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use kokoro_tiny::{SynthesizeOptions, TtsEngine};
+    ///
+    /// # async fn demo() -> Result<(), String> {
+    /// let tts = TtsEngine::new().await?;
+    /// tts.synthesize_with("WARNING: DISK FULL", SynthesizeOptions::new())?;
+    /// for warning in tts.last_warnings() {
+    ///     eprintln!("{}", warning);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn last_warnings(&self) -> Vec<SynthesisWarning> {
+        self.warnings.get()
+    }
 
-        if prepared_chunks.is_empty() {
-            return Err("No text provided for synthesis".to_string());
+    // Refuse languages the vocabulary can't represent or the voice wasn't
+    // recorded in, warning on partial ones
+    fn check_language(&self, plan: &SynthesisPlan) -> Result<(), KokoroError> {
+        let (lang, lenient) = (plan.lang.as_str(), plan.lenient_language);
+        // espeak's own error for these is unhelpful, lenient or not
+        languages::check_code(lang)?;
+        if let Some(voice_lang) = languages::voice_conflict(&plan.voice, lang) {
+            if !lenient {
+                return Err(KokoroError::InvalidInput(languages::conflict_error(
+                    &plan.voice,
+                    voice_lang,
+                    lang,
+                )));
+            }
+            let warning = SynthesisWarning::VoiceLanguageMismatch {
+                voice: plan.voice.clone(),
+                voice_lang: voice_lang.to_string(),
+                lang: lang.to_string(),
+            };
+            #[cfg(not(feature = "as-lib"))]
+            eprintln!("⚠️ {}", warning);
+            self.warnings.push(warning);
         }
+        let warning = match languages::language_support(lang) {
+            LanguageSupport::Full => return Ok(()),
+            LanguageSupport::Partial { missing_symbols } => SynthesisWarning::LanguageCoverage {
+                lang: lang.to_string(),
+                missing_symbols,
+            },
+            LanguageSupport::Unsupported if lenient => SynthesisWarning::UnsupportedLanguage {
+                lang: lang.to_string(),
+            },
+            LanguageSupport::Unsupported => {
+                return Err(KokoroError::UnsupportedLanguage(lang.to_string()))
+            }
+        };
 
-        let chunk_count = prepared_chunks.len();
         #[cfg(not(feature = "as-lib"))]
-        eprintln!(
-            "📚 Long-form synthesis enabled: {} chars -> {} chunk(s) (≤ {} chars each)",
-            text.chars().count(),
-            chunk_count,
-            MAX_CHARS_PER_CHUNK
-        );
+        eprintln!("⚠️ {}", warning);
+        self.warnings.push(warning);
+        Ok(())
+    }
+
+    // Expand units and numbers, apply the caps policy and read acronyms,
+    // noting shouted text as a warning
+    fn normalize_chunk(&self, text: &str, plan: &SynthesisPlan) -> String {
+        let normalized = normalize::normalize_for(text, plan, &self.acronyms, &self.lexicon);
+        let Some(normalized) = normalized else {
+            return text.to_string();
+        };
+
+        if plan.caps_policy == CapsPolicy::Normalize && normalize::is_shouting(text) {
+            self.warnings.push(SynthesisWarning::CapsNormalized {
+                original: text.to_string(),
+                normalized: normalized.clone(),
+            });
+        }
+        normalized
+    }
+
+    // `text` (already normalized) through the model in the plan's language,
+    // punctuation weights and chunk cache setting
+    fn synthesize_segment(
+        &self,
+        style: &[f32],
+        text: &str,
+        speed: f32,
+        plan: &SynthesisPlan,
+    ) -> Result<Vec<f32>, KokoroError> {
+        let mut timer = stats::ChunkTimer::start();
+        let phonemes =
+            self.phonemize_weighted(text, Some(&plan.lang), &plan.punctuation_weights)?;
+        let Some(phonemes) = phonemes else {
+            return Ok(self.unspeakable_pause(text));
+        };
+        if let Some(warning) = self.unknown_characters(text, &phonemes) {
+            if plan.strict_characters {
+                return Err(KokoroError::InvalidInput(warning.to_string()));
+            }
+            self.warnings.push(warning);
+        }
+
+        let tokens = self.tokenize(&phonemes);
+        timer.phonemized(tokens.len());
+        if !plan.chunk_cache {
+            // Run inference with user-specified speed directly
+            let audio = self.run_inference(tokens, style.to_vec(), speed)?;
+            timer.finish(audio.len());
+            return Ok(audio);
+        }
 
-        let overlap = chunk_crossfade_samples();
-        let mut combined_audio = Vec::new();
+        let key = chunk_cache::chunk_key(&tokens, style, speed, &self.model_fingerprint());
+        if let Some(audio) = self.chunk_store.load(&key) {
+            timer.finish(audio.len());
+            return Ok(audio);
+        }
 
-        for (idx, chunk) in prepared_chunks.iter().enumerate() {
+        let audio = self.run_inference(tokens, style.to_vec(), speed)?;
+        timer.finish(audio.len());
+        if let Err(e) = self.chunk_store.store(&key, &audio) {
             #[cfg(not(feature = "as-lib"))]
-            eprintln!(
-                "   → Chunk {}/{} ({} chars)",
-                idx + 1,
-                chunk_count,
-                chunk.chars().count()
-            );
+            eprintln!("⚠️ Failed to cache chunk: {}", e);
+        }
+        Ok(audio)
+    }
+
+    // Changes whenever the model file does, so stale chunks are never reused
+    fn model_fingerprint(&self) -> String {
+        self.model_files
+            .as_ref()
+            .map(|(model, _)| chunk_cache::model_fingerprint(Path::new(model)))
+            .unwrap_or_default()
+    }
+
+    /// Phoneme string exactly as it is fed to the model, padding included
+    ///
+    /// Returns `None` for text with nothing speakable (":)", "…", a lone
+    /// emoji), which synthesis renders as a pause instead.
+    ///
+    /// # Example
+    /// ```
+    /// use kokoro_tiny::TtsEngine;
+    ///
+    /// let tts = TtsEngine::without_model();
+    /// let phonemes = tts.phonemize("Hello there", Some("en"))?.unwrap();
+    /// assert!(phonemes.starts_with("$$$") && phonemes.ends_with("$$$"));
+    /// // Nothing speakable: rendered as a pause
+    /// assert_eq!(tts.phonemize(":)", None)?, None);
+    /// # Ok::<(), String>(())
+    /// ```
+    pub fn phonemize(
+        &self,
+        text: &str,
+        lang: Option<&str>,
+    ) -> Result<Option<String>, KokoroError> {
+        self.phonemize_weighted(text, lang, &PunctuationWeights::default())
+    }
 
-            let chunk_audio = self.synthesize_segment(session, &style, chunk, clamped_speed, lang)?;
-            append_with_crossfade(&mut combined_audio, &chunk_audio, overlap);
+    fn phonemize_weighted(
+        &self,
+        text: &str,
+        lang: Option<&str>,
+        punctuation: &PunctuationWeights,
+    ) -> Result<Option<String>, KokoroError> {
+        // Nothing to say - don't let the model breathe noise at us
+        if !text.chars().any(|c| c.is_alphanumeric()) {
+            return Ok(None);
         }
 
-        if combined_audio.is_empty() {
-            return Err("Failed to synthesize combined audio".to_string());
-        }
+        // Convert text to phonemes; espeak-ng keeps global state, so one
+        // call at a time even when threads share (or each own) an engine.
+        // Words with IPA in the lexicon skip espeak.
+        static ESPEAK: Mutex<()> = Mutex::new(());
+        let mut phonemes = Vec::new();
+        for piece in self.lexicon.pieces(text) {
+            let part = match piece {
+                Piece::Ipa(ipa) => {
+                    phonemes.push(ipa);
+                    continue;
+                }
+                Piece::Text(part) => part,
+            };
+            // Between two IPA words only the pause of any punctuation is left
+            if !part.chars().any(|c| c.is_alphanumeric()) {
+                let pauses: String = part
+                    .chars()
+                    .filter(|&c| punctuation_pause_ms(c).is_some())
+                    .collect();
+                if !pauses.is_empty() {
+                    phonemes.push(pauses);
+                }
+                continue;
+            }
 
-        let mut final_audio = combined_audio;
-        if gain != 1.0 {
-            final_audio = amplify_audio(&final_audio, gain);
+            let lang = lang.unwrap_or(DEFAULT_LANG);
+            let guard = ESPEAK.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+            let spoken = text_to_phonemes(part, lang, None, true, false);
+            drop(guard);
+            #[cfg(test)]
+            ESPEAK_LANGS.with(|langs| langs.borrow_mut().push(lang.to_string()));
+            phonemes.extend(spoken.map_err(|e| {
+                KokoroError::PhonemizationFailed(format!(
+                    "Failed to convert text to phonemes: {}",
+                    e
+                ))
+            })?);
         }
 
-        Ok(final_audio)
-    }
-
-    fn synthesize_segment(
-        &self,
-        session: &Arc<Mutex<Session>>,
-        style: &[f32],
-        text: &str,
-        speed: f32,
-        lang: Option<&str>
-    ) -> Result<Vec<f32>, String> {
-        // Convert text to phonemes
-        let phonemes = text_to_phonemes(text, lang.unwrap_or(DEFAULT_LANG), None, true, false)
-            .map_err(|e| format!("Failed to convert text to phonemes: {}", e))?;
+        // espeak can still hand back nothing but punctuation for odd input
+        if phonemes
+            .iter()
+            .flat_map(|p| p.chars())
+            .all(|c| c.is_whitespace() || punctuation_pause_ms(c).is_some())
+        {
+            return Ok(None);
+        }
 
         // Join phonemes with spaces and add padding tokens at beginning and end
         // Spaces between phonemes create natural pauses for commas and periods
         // Padding tokens are crucial to prevent word dropping at beginning and end
-        let mut phonemes_text = phonemes.join(" ");
+        let mut phonemes_text =
+            prosody::apply_punctuation_weights(&phonemes.join(" "), punctuation);
         // Add multiple padding tokens for better buffering
         phonemes_text.insert_str(0, "$$$");
         phonemes_text.push_str("$$$");
@@ -618,296 +3510,651 @@ impl TtsEngine {
             eprintln!("   Phoneme text length: {} chars", phonemes_text.len());
         }
 
-        let tokens = self.tokenize(phonemes_text);
+        Ok(Some(phonemes_text))
+    }
 
-        // Run inference with user-specified speed directly
-        self.run_inference(session, tokens, style.to_vec(), speed)
+    /// Model token ids for a phoneme string; unknown characters map to 0
+    ///
+    /// # Example
+    /// ```
+    /// use kokoro_tiny::TtsEngine;
+    ///
+    /// let tts = TtsEngine::without_model();
+    /// let tokens = tts.tokenize("$həlˈoʊ$");
+    /// assert_eq!(tokens.len(), 8);
+    /// assert_eq!(tokens[0], tokens[7]);
+    /// ```
+    pub fn tokenize(&self, phonemes: &str) -> Vec<i64> {
+        phonemes
+            .chars()
+            .map(|c| *self.vocab.get(&c).unwrap_or(&0))
+            .collect()
     }
 
-    /// Save audio as WAV file
-    pub fn save_wav(&self, path: &str, audio: &[f32]) -> Result<(), String> {
-        let spec = hound::WavSpec {
-            channels: 1,
-            sample_rate: SAMPLE_RATE,
-            bits_per_sample: 16,
-            sample_format: hound::SampleFormat::Int,
-        };
+    // Symbols of `phonemes` that `tokenize` would map to 0, and where they
+    // (or their upper-case forms) stand in `text`; ASCII punctuation left over
+    // between lexicon words only makes a pause
+    fn unknown_characters(&self, text: &str, phonemes: &str) -> Option<SynthesisWarning> {
+        let mut characters: Vec<char> = Vec::new();
+        for c in phonemes.chars() {
+            let unknown = !self.vocab.contains_key(&c) && !c.is_ascii_punctuation();
+            if unknown && !characters.contains(&c) {
+                characters.push(c);
+            }
+        }
+        if characters.is_empty() {
+            return None;
+        }
+
+        let positions = text
+            .chars()
+            .enumerate()
+            .filter(|(_, c)| c.to_lowercase().any(|c| characters.contains(&c)))
+            .map(|(idx, _)| idx)
+            .collect();
+        Some(SynthesisWarning::UnknownCharacters {
+            text: text.to_string(),
+            characters,
+            positions,
+        })
+    }
 
-        let mut writer = hound::WavWriter::create(path, spec)
-            .map_err(|e| format!("Failed to create WAV file: {}", e))?;
+    /// Walk `text` through the synthesis pipeline without running the model
+    ///
+    /// Each entry shows one chunk of the plan `synthesize_with` would render:
+    /// the normalized text, the padded phoneme string, and the token ids.
+    ///
+    /// # Example
+    /// ```
+    /// use kokoro_tiny::{SynthesizeOptions, TtsEngine};
+    ///
+    /// let tts = TtsEngine::without_model();
+    /// let report = tts.phoneme_report("Hi! :)", &SynthesizeOptions::new())?;
+    /// for chunk in &report {
+    ///     assert!(chunk.phonemes.is_some() || chunk.pause_ms.is_some());
+    /// }
+    /// # Ok::<(), kokoro_tiny::KokoroError>(())
+    /// ```
+    pub fn phoneme_report(
+        &self,
+        text: &str,
+        opts: &SynthesizeOptions,
+    ) -> Result<Vec<ChunkReport>, KokoroError> {
+        let plan = SynthesisPlan::new(text, opts);
+        let mut report = Vec::with_capacity(plan.chunks.len());
+
+        for (index, chunk) in plan.chunks.iter().enumerate() {
+            let normalized = normalize::normalize_for(chunk, &plan, &self.acronyms, &self.lexicon)
+                .unwrap_or_else(|| chunk.clone());
+            let phonemes =
+                self.phonemize_weighted(&normalized, Some(&plan.lang), &plan.punctuation_weights)?;
+
+            let (tokens, unknown_chars, pause_ms) = match &phonemes {
+                Some(phonemes) => {
+                    // The same check synthesis warns (or refuses) with
+                    let unknown = match self.unknown_characters(&normalized, phonemes) {
+                        Some(SynthesisWarning::UnknownCharacters { characters, .. }) => characters,
+                        _ => Vec::new(),
+                    };
+                    (self.tokenize(phonemes), unknown, None)
+                }
+                None => (Vec::new(), Vec::new(), Some(unspeakable_pause_ms(&normalized))),
+            };
 
-        for &sample in audio {
-            let sample_i16 = (sample * 32767.0).clamp(-32768.0, 32767.0) as i16;
-            writer
-                .write_sample(sample_i16)
-                .map_err(|e| format!("Failed to write sample: {}", e))?;
+            report.push(ChunkReport {
+                index,
+                text: chunk.clone(),
+                normalized,
+                phonemes,
+                tokens,
+                unknown_chars,
+                pause_ms,
+            });
         }
 
-        writer
-            .finalize()
-            .map_err(|e| format!("Failed to finalize WAV: {}", e))?;
-        Ok(())
+        Ok(report)
     }
 
-    /// Convert audio to WAV bytes in memory
-    pub fn to_wav_bytes(&self, audio: &[f32]) -> Result<Vec<u8>, String> {
-        let spec = hound::WavSpec {
-            channels: 1,
-            sample_rate: SAMPLE_RATE,
-            bits_per_sample: 16,
-            sample_format: hound::SampleFormat::Int,
-        };
-
-        let mut cursor = Cursor::new(Vec::new());
-        {
-            let mut writer = hound::WavWriter::new(&mut cursor, spec)
-                .map_err(|e| format!("Failed to create WAV writer: {}", e))?;
-
-            for &sample in audio {
-                let sample_i16 = (sample * 32767.0).clamp(-32768.0, 32767.0) as i16;
-                writer
-                    .write_sample(sample_i16)
-                    .map_err(|e| format!("Failed to write sample: {}", e))?;
-            }
+    // Silence standing in for text with nothing speakable, recorded as a warning
+    fn unspeakable_pause(&self, text: &str) -> Vec<f32> {
+        let pause_ms = unspeakable_pause_ms(text);
 
-            writer
-                .finalize()
-                .map_err(|e| format!("Failed to finalize WAV: {}", e))?;
+        // A lone emoji renders as nothing at all, which isn't worth a warning
+        if pause_ms > 0 {
+            self.warnings.push(SynthesisWarning::Unspeakable {
+                text: text.to_string(),
+                pause_ms,
+            });
         }
 
-        Ok(cursor.into_inner())
+        vec![0.0; AudioDuration::from_millis(pause_ms.into()).to_samples(SAMPLE_RATE)]
     }
 
-    /// Save audio as MP3 file (requires 'mp3' feature)
-    #[cfg(feature = "mp3")]
-    pub fn save_mp3(&self, path: &str, audio: &[f32]) -> Result<(), String> {
-        use mp3lame_encoder::{Builder, InterleavedPcm};
-
-        let mut encoder = Builder::new()
-            .ok_or("Failed to create MP3 encoder builder")?
-            .sample_rate(SAMPLE_RATE)
-            .ok_or("Invalid sample rate")?
-            .channels(mp3lame_encoder::channels::Mono)
-            .ok_or("Failed to set mono channel")?
-            .quality(mp3lame_encoder::Quality::Best)
-            .ok_or("Failed to set quality")?
-            .build()
-            .map_err(|e| format!("Failed to build MP3 encoder: {:?}", e))?;
+    /// Save audio as WAV file
+    ///
+    /// Plain samples are written at the model's 24kHz in mono, an
+    /// `AudioBuffer` at its own rate and with its own channel count.
+    ///
+    /// # Example
+    /// ```
+    /// use kokoro_tiny::TtsEngine;
+    ///
+    /// let tts = TtsEngine::without_model();
+    /// // One second of silence (24 kHz mono)
+    /// let path = std::env::temp_dir().join("kokoro_doc_silence.wav");
+    /// tts.save_wav(path.to_str().unwrap(), &[0.0; 24_000])?;
+    /// assert!(path.exists());
+    /// # Ok::<(), String>(())
+    /// ```
+    pub fn save_wav(&self, path: &str, audio: impl AudioSource) -> Result<(), KokoroError> {
+        self.encode_wav(audio, BufWriter::new(File::create(path)?))
+    }
 
-        // Convert to i16
-        let samples_i16: Vec<i16> = audio
-            .iter()
-            .map(|&s| (s * 32767.0).clamp(-32768.0, 32767.0) as i16)
-            .collect();
+    /// Save audio as an 8kHz mono µ-law or A-law WAV file, the format
+    /// telephone systems such as Asterisk play
+    ///
+    /// The audio is resampled from 24kHz and companded; `telephony::to_telephony`
+    /// gives the same bytes without the WAV header, for RTP.
+    ///
+    /// # Example
+    /// ```
+    /// use kokoro_tiny::{Law, TtsEngine};
+    ///
+    /// let tts = TtsEngine::without_model();
+    /// let path = std::env::temp_dir().join("kokoro_doc_pbx.wav");
+    /// tts.save_wav_telephony(path.to_str().unwrap(), &[0.0; 24_000], Law::MuLaw)?;
+    /// // 58 bytes of header, then one byte per 8kHz sample
+    /// assert_eq!(std::fs::metadata(&path)?.len(), 58 + 8_000);
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn save_wav_telephony(
+        &self,
+        path: &str,
+        audio: impl AudioSource,
+        law: Law,
+    ) -> Result<(), KokoroError> {
+        let file = BufWriter::new(File::create(path)?);
+        self.encode(audio, AudioFormat::WavTelephony(law), file)
+    }
+
+    /// Convert audio to WAV bytes in memory
+    ///
+    /// # Example
+    /// ```
+    /// use kokoro_tiny::TtsEngine;
+    ///
+    /// let tts = TtsEngine::without_model();
+    /// let bytes = tts.to_wav_bytes(&[0.0, 0.5, -0.5])?;
+    /// assert_eq!(&bytes[..4], b"RIFF");
+    /// # Ok::<(), String>(())
+    /// ```
+    pub fn to_wav_bytes(&self, audio: impl AudioSource) -> Result<Vec<u8>, KokoroError> {
+        let mut bytes = Vec::new();
+        self.encode_wav(audio, &mut bytes)?;
+        Ok(bytes)
+    }
 
-        let pcm = InterleavedPcm(&samples_i16);
-        let mut mp3_data = Vec::new();
+    /// Write audio as a WAV file to `writer`, which needn't be seekable (a
+    /// socket or a pipe will do)
+    pub fn encode_wav(
+        &self,
+        audio: impl AudioSource,
+        writer: impl Write,
+    ) -> Result<(), KokoroError> {
+        wav::encode(&audio, writer)
+    }
 
-        let mut output = [0u8; 8192];
-        let encoded_size = encoder
-            .encode(pcm, &mut output)
-            .map_err(|e| format!("Failed to encode MP3: {:?}", e))?;
-        mp3_data.extend_from_slice(&output[..encoded_size]);
+    /// Save audio as MP3 file (requires 'mp3' feature), at 64 kbit/s
+    ///
+    /// # Example
+    /// ```no_run
+    /// use kokoro_tiny::TtsEngine;
+    ///
+    /// # async fn demo() -> Result<(), String> {
+    /// let mut tts = TtsEngine::new().await?;
+    /// let audio = tts.synthesize("Hello", None, None, None)?;
+    /// tts.save_mp3("hello.mp3", &audio)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "mp3")]
+    pub fn save_mp3(&self, path: &str, audio: &[f32]) -> Result<(), KokoroError> {
+        self.save_mp3_with(path, audio, Mp3Options::default())
+    }
 
-        let final_size = encoder
-            .flush(&mut output)
-            .map_err(|e| format!("Failed to flush MP3 encoder: {:?}", e))?;
-        mp3_data.extend_from_slice(&output[..final_size]);
+    /// Save audio as MP3 file with a bitrate, quality and tags (requires
+    /// 'mp3' feature)
+    ///
+    /// # Example
+    /// ```no_run
+    /// use kokoro_tiny::{Mp3Options, TtsEngine};
+    ///
+    /// # async fn demo() -> Result<(), String> {
+    /// let mut tts = TtsEngine::new().await?;
+    /// let audio = tts.synthesize("Welcome to episode twelve.", None, None, None)?;
+    /// let opts = Mp3Options {
+    ///     bitrate: 96,
+    ///     title: Some("Episode 12".into()),
+    ///     ..Mp3Options::default()
+    /// };
+    /// tts.save_mp3_with("episode-12.mp3", &audio, opts)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "mp3")]
+    pub fn save_mp3_with(
+        &self,
+        path: &str,
+        audio: &[f32],
+        opts: Mp3Options,
+    ) -> Result<(), KokoroError> {
+        self.encode_mp3(audio, opts, BufWriter::new(File::create(path)?))
+    }
 
-        std::fs::write(path, mp3_data).map_err(|e| format!("Failed to write MP3 file: {}", e))?;
+    /// The MP3 file `save_mp3_with` would write, in memory (requires 'mp3'
+    /// feature)
+    #[cfg(feature = "mp3")]
+    pub fn to_mp3_bytes(&self, audio: &[f32], opts: Mp3Options) -> Result<Vec<u8>, KokoroError> {
+        let mut bytes = Vec::new();
+        self.encode_mp3(audio, opts, &mut bytes)?;
+        Ok(bytes)
+    }
 
-        Ok(())
+    /// Write audio as an MP3 file to `writer` (requires 'mp3' feature)
+    #[cfg(feature = "mp3")]
+    pub fn encode_mp3(
+        &self,
+        audio: &[f32],
+        opts: Mp3Options,
+        writer: impl Write,
+    ) -> Result<(), KokoroError> {
+        mp3::encode(audio, &opts, writer)
     }
 
-    /// Save audio as OPUS file (requires 'opus-format' feature)
+    /// Save audio as an Ogg Opus file at `bitrate` bits per second (requires
+    /// 'opus-format' feature)
+    ///
+    /// # Example
+    /// ```no_run
+    /// use kokoro_tiny::TtsEngine;
+    ///
+    /// # async fn demo() -> Result<(), String> {
+    /// let mut tts = TtsEngine::new().await?;
+    /// let audio = tts.synthesize("Hello", None, None, None)?;
+    /// tts.save_opus("hello.opus", &audio, 24_000)?;
+    /// # Ok(())
+    /// # }
+    /// ```
     #[cfg(feature = "opus-format")]
-    pub fn save_opus(&self, path: &str, audio: &[f32], bitrate: i32) -> Result<(), String> {
-        use audiopus::{coder::Encoder as OpusEncoder, Application, Bitrate, Channels, SampleRate};
-
-        // Convert sample rate from 24000 to 48000 (OPUS prefers 48kHz)
-        let samples_48k = resample_audio(audio, SAMPLE_RATE, 48000);
+    pub fn save_opus(&self, path: &str, audio: &[f32], bitrate: i32) -> Result<(), KokoroError> {
+        self.encode_opus(audio, bitrate, BufWriter::new(File::create(path)?))
+    }
 
-        // Convert to i16
-        let samples_i16: Vec<i16> = samples_48k
-            .iter()
-            .map(|&s| (s * 32767.0).clamp(-32768.0, 32767.0) as i16)
-            .collect();
+    /// Write audio as an Ogg Opus file at `bitrate` bits per second to
+    /// `writer` (requires 'opus-format' feature)
+    #[cfg(feature = "opus-format")]
+    pub fn encode_opus(
+        &self,
+        audio: &[f32],
+        bitrate: i32,
+        writer: impl Write,
+    ) -> Result<(), KokoroError> {
+        opus::encode(audio, bitrate, writer)
+    }
 
-        // Create OPUS encoder
-        let mut encoder = OpusEncoder::new(SampleRate::Hz48000, Channels::Mono, Application::Audio)
-            .map_err(|e| format!("Failed to create OPUS encoder: {:?}", e))?;
-
-        // Set bitrate
-        encoder
-            .set_bitrate(Bitrate::BitsPerSecond(bitrate))
-            .map_err(|e| format!("Failed to set OPUS bitrate: {:?}", e))?;
-
-        // Encode in chunks
-        let frame_size = 960; // 20ms at 48kHz
-        let mut opus_data = Vec::new();
-        let mut output = vec![0u8; 4000];
-
-        for chunk in samples_i16.chunks(frame_size) {
-            if chunk.len() == frame_size {
-                let size = encoder
-                    .encode(chunk, &mut output)
-                    .map_err(|e| format!("Failed to encode OPUS frame: {:?}", e))?;
-                opus_data.extend_from_slice(&output[..size]);
+    /// Write audio in `format` to `writer`: a `Vec<u8>`, a socket, an upload
+    /// stream or a file
+    ///
+    /// MP3 and Opus take mono at the model's rate; other audio is downmixed
+    /// and resampled first.
+    ///
+    /// # Example
+    /// ```
+    /// use kokoro_tiny::{AudioFormat, TtsEngine};
+    ///
+    /// let tts = TtsEngine::without_model();
+    /// let mut socket = Vec::new();
+    /// tts.encode(&[0.0; 2400], AudioFormat::Wav, &mut socket)?;
+    /// assert_eq!(&socket[8..12], b"WAVE");
+    /// # Ok::<(), String>(())
+    /// ```
+    pub fn encode(
+        &self,
+        audio: impl AudioSource,
+        format: AudioFormat,
+        writer: impl Write,
+    ) -> Result<(), KokoroError> {
+        match format {
+            AudioFormat::Wav => self.encode_wav(audio, writer),
+            AudioFormat::Pcm(format) => Ok(write_pcm(writer, audio.samples(), format)?),
+            AudioFormat::WavTelephony(law) => {
+                let mono = audio_buffer::mono_at_model_rate(&audio);
+                wav::encode_companded(&telephony::to_telephony(&mono, law), law, writer)
             }
+            #[cfg(feature = "mp3")]
+            AudioFormat::Mp3(opts) => {
+                self.encode_mp3(&audio_buffer::mono_at_model_rate(&audio), opts, writer)
+            }
+            #[cfg(feature = "opus-format")]
+            AudioFormat::OpusOgg { bitrate } => {
+                self.encode_opus(&audio_buffer::mono_at_model_rate(&audio), bitrate, writer)
+            }
+        }
+    }
+
+    /// Audio in `format`, base64-encoded (standard alphabet, padded), for
+    /// JSON payloads and clients on other machines
+    ///
+    /// Audio longer than `set_max_inline_duration` allows is refused.
+    pub fn to_base64(
+        &self,
+        audio: impl AudioSource,
+        format: AudioFormat,
+    ) -> Result<String, KokoroError> {
+        let frames = audio.samples().len() / usize::from(audio.channels().max(1));
+        let duration = AudioDuration::from_samples(frames, audio.sample_rate());
+        let max = self.max_inline_duration;
+        if u128::from(duration.as_millis()) > max.as_millis() {
+            return Err(KokoroError::InvalidInput(format!(
+                "Audio is {:.1}s long, more than the {:.1}s allowed inline; \
+                 save it to a file or raise set_max_inline_duration",
+                duration.as_millis() as f64 / 1000.0,
+                max.as_secs_f64()
+            )));
         }
+        let mut bytes = Vec::new();
+        self.encode(audio, format, &mut bytes)?;
+        Ok(BASE64.encode(bytes))
+    }
 
-        std::fs::write(path, opus_data).map_err(|e| format!("Failed to write OPUS file: {}", e))?;
+    /// A WAV file of the audio, base64-encoded
+    ///
+    /// # Example
+    /// ```
+    /// use kokoro_tiny::TtsEngine;
+    ///
+    /// let tts = TtsEngine::without_model();
+    /// let wav = tts.to_wav_base64(&[0.0; 2400])?;
+    /// // "RIFF" in base64
+    /// assert!(wav.starts_with("UklGR"));
+    /// # Ok::<(), String>(())
+    /// ```
+    pub fn to_wav_base64(&self, audio: impl AudioSource) -> Result<String, KokoroError> {
+        self.to_base64(audio, AudioFormat::Wav)
+    }
 
-        Ok(())
+    /// The audio as a `data:` URL, for an `<audio src>` or a chat client
+    ///
+    /// # Example
+    /// ```
+    /// use kokoro_tiny::{AudioFormat, TtsEngine};
+    ///
+    /// let tts = TtsEngine::without_model();
+    /// let url = tts.to_data_url(&[0.0; 2400], AudioFormat::Wav)?;
+    /// assert!(url.starts_with("data:audio/wav;base64,UklGR"));
+    /// # Ok::<(), String>(())
+    /// ```
+    pub fn to_data_url(
+        &self,
+        audio: impl AudioSource,
+        format: AudioFormat,
+    ) -> Result<String, KokoroError> {
+        let mime_type = format.mime_type();
+        Ok(format!("data:{};base64,{}", mime_type, self.to_base64(audio, format)?))
     }
 
     /// Save audio file with automatic format detection based on extension
-    pub fn save_audio(&self, path: &str, audio: &[f32]) -> Result<(), String> {
+    ///
+    /// # Example
+    /// ```
+    /// use kokoro_tiny::TtsEngine;
+    ///
+    /// let tts = TtsEngine::without_model();
+    /// let path = std::env::temp_dir().join("kokoro_doc_tone.wav");
+    /// tts.save_audio(path.to_str().unwrap(), &[0.0; 2400])?;
+    /// assert!(tts.save_audio("tone.flac", &[0.0; 2400]).is_err());
+    /// # Ok::<(), String>(())
+    /// ```
+    pub fn save_audio(&self, path: &str, audio: impl AudioSource) -> Result<(), KokoroError> {
         let extension = Path::new(path)
             .extension()
             .and_then(|e| e.to_str())
             .unwrap_or("wav")
             .to_lowercase();
 
-        match extension.as_str() {
-            "wav" => self.save_wav(path, audio),
+        let unsupported = |message: String| Err(KokoroError::AudioEncodingFailed(message));
+        let format = match extension.as_str() {
+            "wav" => AudioFormat::Wav,
 
             #[cfg(feature = "mp3")]
-            "mp3" => self.save_mp3(path, audio),
+            "mp3" => AudioFormat::Mp3(Mp3Options::default()),
             #[cfg(not(feature = "mp3"))]
-            "mp3" => Err("MP3 support not enabled. Add 'mp3' feature to Cargo.toml".to_string()),
+            "mp3" => {
+                return unsupported(
+                    "MP3 support not enabled. Add 'mp3' feature to Cargo.toml".to_string(),
+                )
+            }
 
             #[cfg(feature = "opus-format")]
-            "opus" => self.save_opus(path, audio, 24000),
+            "opus" => AudioFormat::OpusOgg { bitrate: self.opus_bitrate },
             #[cfg(not(feature = "opus-format"))]
             "opus" => {
-                Err("OPUS support not enabled. Add 'opus-format' feature to Cargo.toml".to_string())
+                return unsupported(
+                    "OPUS support not enabled. Add 'opus-format' feature to Cargo.toml".to_string(),
+                )
             }
 
-            "flac" => Err("FLAC format not yet supported".to_string()),
+            "flac" => return unsupported("FLAC format not yet supported".to_string()),
 
-            _ => Err(format!("Unsupported audio format: {}", extension)),
-        }
+            _ => return unsupported(format!("Unsupported audio format: {}", extension)),
+        };
+        self.encode(audio, format, BufWriter::new(File::create(path)?))
     }
 
-    /// Play audio directly through speakers (requires 'playback' feature)
-    #[cfg(feature = "playback")]
-    pub fn play(&self, audio: &[f32], volume: f32) -> Result<(), String> {
-        self.play_with_ducking(audio, volume, false, 0.3)
+    /// Play audio directly through speakers, blocking until done
+    ///
+    /// Goes to the output set with `set_output`, or (with the `playback`
+    /// feature) the selected device. `play_async` is the same without the
+    /// blocking.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use kokoro_tiny::TtsEngine;
+    ///
+    /// # async fn demo() -> Result<(), String> {
+    /// let tts = TtsEngine::new().await?;
+    /// let audio = tts.synthesize("Build complete", None, None, None)?;
+    /// tts.play(&audio, 0.8)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn play(&self, audio: impl AudioSource, volume: f32) -> Result<(), KokoroError> {
+        self.play_async(audio, volume)?.wait().map(|_| ())
+    }
+
+    /// Start playing audio and return right away, with a handle to stop,
+    /// pause or change the volume
+    ///
+    /// Playback waits on its own thread if something else is playing.
+    /// Dropping the handle stops it; call `wait` on it to block until done.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use kokoro_tiny::TtsEngine;
+    ///
+    /// # async fn demo() -> Result<(), String> {
+    /// let tts = TtsEngine::new().await?;
+    /// let audio = tts.synthesize("You can cut me off", None, None, None)?;
+    /// let playing = tts.play_async(&audio, 0.8)?;
+    /// // ... later, from a cancel button
+    /// playing.stop();
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn play_async(
+        &self,
+        audio: impl AudioSource,
+        volume: f32,
+    ) -> Result<PlaybackHandle, KokoroError> {
+        let output = self.output().map_err(KokoroError::Playback)?;
+        let samples = audio_buffer::at_model_rate(&audio).into_owned();
+        PlaybackHandle::spawn(output, samples, audio.channels(), volume)
+            .map_err(KokoroError::Playback)
     }
 
-    /// Play audio with optional ducking (requires 'playback' feature)
+    /// Play audio with optional ducking
     /// Ducking reduces system volume before speaking, then restores it after
+    /// (requires the 'ducking' feature; without it `enable_ducking` is ignored)
     ///
     /// # Arguments
     /// * `audio` - Audio samples to play
     /// * `volume` - Playback volume (0.0 to 1.0)
     /// * `enable_ducking` - Whether to reduce other audio during playback
     /// * `duck_level` - How much to reduce other audio (0.0 = mute, 1.0 = no change)
-    #[cfg(feature = "playback")]
+    ///
+    /// # Example
+    /// ```no_run
+    /// use kokoro_tiny::TtsEngine;
+    ///
+    /// # async fn demo() -> Result<(), String> {
+    /// let tts = TtsEngine::new().await?;
+    /// let audio = tts.synthesize("Important announcement", None, None, None)?;
+    /// // Other audio drops to 30% while this plays
+    /// tts.play_with_ducking(&audio, 0.8, true, 0.3)?;
+    /// # Ok(())
+    /// # }
+    /// ```
     pub fn play_with_ducking(
         &self,
-        audio: &[f32],
+        audio: impl AudioSource,
         volume: f32,
         enable_ducking: bool,
         duck_level: f32,
-    ) -> Result<(), String> {
-        // Duck audio if requested (reduce system volume)
-        #[cfg(feature = "ducking")]
-        if enable_ducking {
-            duck_system_audio(duck_level)?;
-            // Small delay to let the ducking take effect
-            thread::sleep(Duration::from_millis(50));
-        }
+    ) -> Result<(), KokoroError> {
+        let output = self.output().map_err(KokoroError::Playback)?;
+        let playing = coordinator::coordinator()
+            .acquire(BusyPolicy::default())
+            .map_err(KokoroError::Playback)?;
 
-        // Convert audio to WAV format in memory
-        let wav_data = self.to_wav_bytes(audio)?;
-
-        // Setup audio output - use selected device or default
-        let (_stream, stream_handle) = if let Some(ref device_name) = self.audio_device {
-            // Find and use the specified device
-            use cpal::traits::{DeviceTrait, HostTrait};
-            let host = cpal::default_host();
-            let devices = host
-                .output_devices()
-                .map_err(|e| format!("Failed to get output devices: {}", e))?;
-
-            let mut found_device = None;
-            for device in devices {
-                if let Ok(name) = device.name() {
-                    if name == *device_name {
-                        found_device = Some(device);
-                        break;
-                    }
-                }
-            }
-
-            match found_device {
-                Some(device) => OutputStream::try_from_device(&device)
-                    .map_err(|e| format!("Failed to open device '{}': {}", device_name, e))?,
-                None => return Err(format!("Audio device '{}' not found", device_name)),
-            }
-        } else {
-            // Use system default
-            OutputStream::try_default().map_err(|e| format!("Failed to get audio output: {}", e))?
-        };
+        // Duck audio if requested; the guard restores it even if playback fails
+        #[cfg(feature = "ducking")]
+        let _duck = enable_ducking
+            .then(|| ducking::duck_audio(duck_level))
+            .transpose()?;
+        #[cfg(not(feature = "ducking"))]
+        let _ = (enable_ducking, duck_level);
+
+        let controls = playback::Controls::new(volume);
+        let channels = audio.channels();
+        let audio = audio_buffer::at_model_rate(&audio);
+        let opts = PlaybackOptions::new();
+        playback::play_on(&*output, &audio, channels, &opts, &playing, &controls)
+            .map(|_| ())
+            .map_err(KokoroError::Playback)
+    }
 
-        let sink = Sink::try_new(&stream_handle)
-            .map_err(|e| format!("Failed to create audio sink: {}", e))?;
+    /// Engine with an in-memory voice table and no ONNX session (unit tests only)
+    #[cfg(test)]
+    pub(crate) fn with_test_voices(voices: HashMap<String, Vec<f32>>) -> Self {
+        Self {
+            session: RwLock::new(None),
+            spare_sessions: Mutex::new(Vec::new()),
+            voices: VoiceTable::new(voices),
+            vocab: build_vocab(),
+            fallback_mode: AtomicBool::new(false),
+            formant_fallback: false,
+            warnings: WarningLog::default(),
+            cache: Mutex::new(ResultCache::new(DEFAULT_CACHE_BUDGET_BYTES)),
+            chunk_store: ChunkStore::new(cache_dir().join("chunks")),
+            model_files: None,
+            retry_policy: RetryPolicy::default(),
+            sources: EngineBuilder::default().sources(),
+            cache_dir: cache_dir(),
+            session_options: SessionOptions::default(),
+            variant: ModelVariant::default(),
+            speed_scale: SPEED_SCALE,
+            opus_bitrate: OPUS_BITRATE,
+            max_inline_duration: MAX_INLINE_DURATION,
+            acronyms: Acronyms::default(),
+            lexicon: Lexicon::default(),
+            #[cfg(feature = "playback")]
+            audio_device: None,
+            output: None,
+            mock_inference: None,
+        }
+    }
 
-        // Set volume (0.0 to 1.0)
-        sink.set_volume(volume.clamp(0.0, 1.0));
+    /// Test engine with the default voice, rendering every token as
+    /// `samples_per_token` samples of a constant level (unit tests only)
+    #[cfg(test)]
+    pub(crate) fn mock(samples_per_token: usize) -> Self {
+        let voices = HashMap::from([(DEFAULT_VOICE.to_string(), vec![0.1; STYLE_DIM])]);
+        let mut engine = Self::with_test_voices(voices);
+        engine.mock_inference = Some(Arc::new(move |tokens: &[i64], _style: &[f32], _speed: f32| {
+            Ok(vec![0.1; tokens.len() * samples_per_token])
+        }));
+        engine
+    }
 
-        // Create decoder from WAV data
-        let cursor = Cursor::new(wav_data);
-        let decoder =
-            Decoder::new(cursor).map_err(|e| format!("Failed to create audio decoder: {}", e))?;
+    /// `mock` that counts its model calls in `calls`, rendering 40 samples
+    /// per token that depend on the tokens, so chunks differ (unit tests only)
+    #[cfg(test)]
+    pub(crate) fn counting(calls: Arc<std::sync::atomic::AtomicUsize>) -> Self {
+        let mut engine = Self::mock(0);
+        engine.mock_inference = Some(Arc::new(move |tokens: &[i64], _style: &[f32], _speed: f32| {
+            calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            let seed = tokens.iter().sum::<i64>() as usize;
+            Ok((0..tokens.len() * 40).map(|i| ((i + seed) % 90) as f32 / 100.0).collect())
+        }));
+        engine
+    }
 
-        // Play the audio
-        sink.append(decoder);
-        sink.sleep_until_end();
+    // Private helper methods
 
-        // Restore audio if we ducked it
-        #[cfg(feature = "ducking")]
-        if enable_ducking {
-            // Small delay before restoring
-            thread::sleep(Duration::from_millis(50));
-            restore_system_audio(duck_level)?;
+    // What synthesis returns without a model: the excuse message, or the text
+    // read by the formant voice
+    pub(crate) fn fallback_audio(&self, text: &str) -> Result<Vec<f32>, KokoroError> {
+        if self.formant_fallback {
+            Ok(formant::synthesize(text))
+        } else {
+            Ok(wav_to_f32(FALLBACK_MESSAGE)?)
         }
-
-        Ok(())
     }
 
-    // Private helper methods
+    /// Check that every component of a mix names a loaded voice
+    ///
+    /// # Example
+    /// ```
+    /// use kokoro_tiny::{TtsEngine, VoiceMix};
+    ///
+    /// let tts = TtsEngine::without_model();
+    /// let mix = VoiceMix::parse("af_sky.6+af_bella.4")?;
+    /// // Without a model every mix passes; with one, unknown voices are rejected
+    /// assert!(tts.validate_voice_mix(&mix).is_ok());
+    /// # Ok::<(), String>(())
+    /// ```
+    pub fn validate_voice_mix(&self, mix: &VoiceMix) -> Result<(), KokoroError> {
+        if self.is_fallback() {
+            return Ok(());
+        }
+        check_mix(&self.voices.snapshot(), mix)
+    }
 
-    fn parse_voice_style(&self, voice_str: &str) -> Result<Vec<f32>, String> {
-        if self.fallback_mode {
+    fn parse_voice_style(&self, voice_str: &str) -> Result<Vec<f32>, KokoroError> {
+        if self.is_fallback() {
             // Return a dummy style vector for fallback mode
-            return Ok(vec![0.0; 256]);
+            return Ok(vec![0.0; STYLE_DIM]);
         }
 
-        let mut result = vec![0.0; 256];
-        let parts: Vec<&str> = voice_str.split('+').collect();
-
-        for part in parts {
-            let (voice_name, weight) = if part.contains('.') {
-                let pieces: Vec<&str> = part.split('.').collect();
-                if pieces.len() != 2 {
-                    return Err(format!("Invalid voice format: {}", part));
-                }
-                let weight = pieces[1]
-                    .parse::<f32>()
-                    .map_err(|_| format!("Invalid weight: {}", pieces[1]))?;
-                (pieces[0], weight / 10.0)
-            } else {
-                (part, 1.0)
-            };
-
-            let voice_style = self
-                .voices
-                .get(voice_name)
-                .ok_or_else(|| format!("Voice not found: {}", voice_name))?;
+        let mix = VoiceMix::parse(voice_str).map_err(KokoroError::InvalidVoice)?;
+        // Check and blend against one snapshot so a concurrent removal can't
+        // land in between
+        let voices = self.voices.snapshot();
+        check_mix(&voices, &mix)?;
 
+        let mut result = vec![0.0; STYLE_DIM];
+        for (voice_name, weight) in &mix.components {
+            let voice_style = &voices[voice_name];
             for (i, val) in voice_style.iter().enumerate() {
                 if i < result.len() {
                     result[i] += val * weight;
@@ -918,82 +4165,142 @@ impl TtsEngine {
         Ok(result)
     }
 
-    fn tokenize(&self, text: String) -> Vec<i64> {
-        text.chars()
-            .map(|c| *self.vocab.get(&c).unwrap_or(&0))
-            .collect()
+    fn run_inference(
+        &self,
+        tokens: Vec<i64>,
+        style: Vec<f32>,
+        speed: f32,
+    ) -> Result<Vec<f32>, KokoroError> {
+        #[cfg(test)]
+        let output = match &self.mock_inference {
+            Some(mock) => mock(&tokens, &style, speed),
+            None => self.run_session(tokens, style, speed),
+        };
+        #[cfg(not(test))]
+        let output = self.run_session(tokens, style, speed);
+
+        // NaN would reach the WAV encoder and the limiter as garbage
+        let mut audio = output.map_err(KokoroError::InferenceFailed)?;
+        let count = audio::sanitize(&mut audio);
+        if count > 0 {
+            self.warnings.push(SynthesisWarning::NonFiniteSamples { count });
+        }
+        Ok(audio)
     }
 
-    fn run_inference(
+    // One pass through the ONNX session
+    fn run_session(
         &self,
-        session: &Arc<Mutex<Session>>,
         tokens: Vec<i64>,
         style: Vec<f32>,
         speed: f32,
     ) -> Result<Vec<f32>, String> {
-        let mut session = session
+        let main = self
+            .session
+            .read()
+            .ok()
+            .and_then(|slot| slot.as_ref().map(|(session, _)| session.clone()))
+            .ok_or_else(|| "TTS engine not initialized".to_string())?;
+        // Whichever session is idle (see parallel), else wait for the main one
+        let spares = self.spare_sessions.lock().map(|s| s.clone()).unwrap_or_default();
+        for session in std::iter::once(&main).chain(&spares) {
+            if let Ok(mut session) = session.try_lock() {
+                return infer(&mut session, tokens, style, speed);
+            }
+        }
+        let mut session = main
             .lock()
             .map_err(|e| format!("Failed to lock session: {}", e))?;
+        infer(&mut session, tokens, style, speed)
+    }
+}
 
-        let token_count = tokens.len(); // Save count before moving
-
-        // Prepare tokens tensor
+// One inference on `session`; also used to check a new session works.
+// Input and output names and types come from the session (see
+// model_variant), so the fp32 and int8 exports both work.
+pub(crate) fn infer(
+    session: &mut Session,
+    tokens: Vec<i64>,
+    style: Vec<f32>,
+    speed: f32,
+) -> Result<Vec<f32>, String> {
+    let token_count = tokens.len(); // Save count before moving
+    let io = ModelIo::of(session)?;
+
+    // Prepare tokens tensor
+    let tokens_value = if io.tokens_type == TensorElementType::Int32 {
+        let tokens: Vec<i32> = tokens.iter().map(|&t| t as i32).collect();
         let tokens_array = ndarray::Array2::from_shape_vec((1, tokens.len()), tokens)
             .map_err(|e| format!("Failed to create tokens array: {}", e))?;
-        let tokens_tensor = Tensor::from_array(tokens_array)
-            .map_err(|e| format!("Failed to create tokens tensor: {}", e))?;
-
-        // Prepare style tensor
-        let style_array = ndarray::Array2::from_shape_vec((1, style.len()), style)
-            .map_err(|e| format!("Failed to create style array: {}", e))?;
-        let style_tensor = Tensor::from_array(style_array)
-            .map_err(|e| format!("Failed to create style tensor: {}", e))?;
-
-        // Prepare speed tensor
-        let speed_array = ndarray::Array1::from_vec(vec![speed]);
-        let speed_tensor = Tensor::from_array(speed_array)
-            .map_err(|e| format!("Failed to create speed tensor: {}", e))?;
-
-        // Create inputs
-        use std::borrow::Cow;
-        let inputs = SessionInputs::from(vec![
-            (
-                Cow::Borrowed("tokens"),
-                SessionInputValue::Owned(Value::from(tokens_tensor)),
-            ),
-            (
-                Cow::Borrowed("style"),
-                SessionInputValue::Owned(Value::from(style_tensor)),
-            ),
-            (
-                Cow::Borrowed("speed"),
-                SessionInputValue::Owned(Value::from(speed_tensor)),
-            ),
-        ]);
+        Tensor::from_array(tokens_array).map(Value::from)
+    } else {
+        let tokens_array = ndarray::Array2::from_shape_vec((1, tokens.len()), tokens)
+            .map_err(|e| format!("Failed to create tokens array: {}", e))?;
+        Tensor::from_array(tokens_array).map(Value::from)
+    }
+    .map_err(|e| format!("Failed to create tokens tensor: {}", e))?;
 
-        // Run inference
-        let outputs = session
-            .run(inputs)
-            .map_err(|e| format!("Failed to run inference: {}", e))?;
+    // Prepare style tensor
+    let style_array = ndarray::Array2::from_shape_vec((1, style.len()), style)
+        .map_err(|e| format!("Failed to create style array: {}", e))?;
+    let style_tensor = Tensor::from_array(style_array)
+        .map_err(|e| format!("Failed to create style tensor: {}", e))?;
 
-        // Extract audio
-        let (shape, data) = outputs["audio"]
+    // Prepare speed tensor (some exports take a whole number)
+    let speed_value = if io.speed_type == TensorElementType::Int32 {
+        Tensor::from_array(ndarray::Array1::from_vec(vec![speed.round() as i32])).map(Value::from)
+    } else {
+        Tensor::from_array(ndarray::Array1::from_vec(vec![speed])).map(Value::from)
+    }
+    .map_err(|e| format!("Failed to create speed tensor: {}", e))?;
+
+    // Create inputs
+    use std::borrow::Cow;
+    let inputs = SessionInputs::from(vec![
+        (
+            Cow::Owned(io.tokens.clone()),
+            SessionInputValue::Owned(tokens_value),
+        ),
+        (
+            Cow::Owned(io.style.clone()),
+            SessionInputValue::Owned(Value::from(style_tensor)),
+        ),
+        (
+            Cow::Owned(io.speed.clone()),
+            SessionInputValue::Owned(speed_value),
+        ),
+    ]);
+
+    // Run inference
+    let outputs = session
+        .run(inputs)
+        .map_err(|e| format!("Failed to run inference: {}", e))?;
+
+    // Extract audio, as f32 whatever the model returns
+    let output = &outputs[io.output.as_str()];
+    let (shape, data_vec) = if io.output_type == TensorElementType::Float64 {
+        let (shape, data) = output
+            .try_extract_tensor::<f64>()
+            .map_err(|e| format!("Failed to extract audio tensor: {}", e))?;
+        (shape.clone(), data.iter().map(|&x| x as f32).collect::<Vec<f32>>())
+    } else {
+        let (shape, data) = output
             .try_extract_tensor::<f32>()
             .map_err(|e| format!("Failed to extract audio tensor: {}", e))?;
+        (shape.clone(), data.to_vec())
+    };
 
-        // Debug output shape for longer text
-        let data_vec = data.to_vec();
-        #[cfg(not(feature = "as-lib"))]
-        if token_count > 100 {
-            eprintln!(
-                "   Output audio shape: {:?}, samples: {}",
-                shape,
-                data_vec.len()
-            );
-        }
-
-        Ok(data_vec)
+    // Debug output shape for longer text
+    #[cfg(not(feature = "as-lib"))]
+    if token_count > 100 {
+        eprintln!(
+            "   Output audio shape: {:?}, samples: {}",
+            shape,
+            data_vec.len()
+        );
     }
+
+    Ok(data_vec)
 }
 
 // Helper functions
@@ -1014,40 +4321,33 @@ fn build_vocab() -> HashMap<char, i64> {
         .collect()
 }
 
-// Load voices from binary file
-fn load_voices(path: &str) -> Result<HashMap<String, Vec<f32>>, String> {
-    let mut file = File::open(path).map_err(|e| format!("Failed to open voices file: {}", e))?;
-
-    let mut reader =
-        NpzReader::new(&mut file).map_err(|e| format!("Failed to create NPZ reader: {}", e))?;
-
-    let mut voices = HashMap::new();
-
-    for name in reader
-        .names()
-        .map_err(|e| format!("Failed to read NPZ names: {:?}", e))?
-    {
-        let array: ArrayBase<OwnedRepr<f32>, IxDyn> = reader
-            .by_name(&name)
-            .map_err(|e| format!("Failed to read NPZ array {}: {:?}", name, e))?;
-        let data: Vec<f32> = array.iter().cloned().collect();
+// Languages espeak was called with on this thread, for tests to check
+#[cfg(test)]
+thread_local! {
+    static ESPEAK_LANGS: std::cell::RefCell<Vec<String>> =
+        const { std::cell::RefCell::new(Vec::new()) };
+}
 
-        // Clean up the name (remove .npy extension if present)
-        let clean_name = name.trim_end_matches(".npy");
-        voices.insert(clean_name.to_string(), data);
+// Language when none is given: the voice's own, unless it's an English voice,
+// which keeps the generic "en" the engine has always used
+pub(crate) fn default_lang(voice: Option<&str>) -> String {
+    match languages::voice_language(voice.unwrap_or(DEFAULT_VOICE)) {
+        Some(lang) if !lang.starts_with("en") => lang.to_string(),
+        _ => DEFAULT_LANG.to_string(),
     }
-
-    Ok(voices)
 }
 
-// Download file from URL
-async fn download_file(url: &str, path: &str) -> Result<(), Box<dyn std::error::Error>> {
-    let response = reqwest::get(url).await?;
-    let bytes = response.bytes().await?;
-
-    let mut file = File::create(path)?;
-    file.write_all(&bytes)?;
-
+// Every component of the mix must name a voice in the table
+fn check_mix(voices: &VoiceMap, mix: &VoiceMix) -> Result<(), KokoroError> {
+    for (name, _) in &mix.components {
+        if !voices.contains_key(name) {
+            return Err(KokoroError::VoiceNotFound {
+                name: name.clone(),
+                mix: (mix.components.len() > 1).then(|| mix.to_string()),
+                suggestions: voices::closest_voices(name, voices),
+            });
+        }
+    }
     Ok(())
 }
 
@@ -1055,190 +4355,129 @@ async fn download_file(url: &str, path: &str) -> Result<(), Box<dyn std::error::
 #[cfg(feature = "playback")]
 fn play_fallback_message() -> Result<(), String> {
     eprintln!("   🔊 Playing welcome message...");
-    
+
     // Decode the fallback WAV to audio samples
     let audio = wav_to_f32(FALLBACK_MESSAGE)?;
-    
-    // Convert to WAV bytes for playback
-    let wav_data = samples_to_wav_bytes(&audio, SAMPLE_RATE)?;
-    
-    // Setup audio output with default device
-    let (_stream, stream_handle) = OutputStream::try_default()
-        .map_err(|e| format!("Failed to open audio stream: {}", e))?;
-    
-    // Create sink and play
-    let sink = Sink::try_new(&stream_handle)
-        .map_err(|e| format!("Failed to create audio sink: {}", e))?;
-    
-    let cursor = Cursor::new(wav_data);
-    let source = Decoder::new(cursor)
-        .map_err(|e| format!("Failed to decode audio: {}", e))?;
-    
-    sink.append(source);
-    sink.set_volume(0.8);
-    sink.sleep_until_end();
-    
-    Ok(())
-}
 
-// Helper function to convert audio samples to WAV bytes
-fn samples_to_wav_bytes(audio: &[f32], sample_rate: u32) -> Result<Vec<u8>, String> {
-    let mut wav_data = Vec::new();
-    let mut cursor = Cursor::new(&mut wav_data);
-    
-    let spec = hound::WavSpec {
-        channels: 1,
-        sample_rate,
-        bits_per_sample: 16,
-        sample_format: hound::SampleFormat::Int,
-    };
-    
-    let mut writer = hound::WavWriter::new(&mut cursor, spec)
-        .map_err(|e| format!("Failed to create WAV writer: {}", e))?;
-    
-    for &sample in audio {
-        let amplitude = (sample * 32767.0).clamp(-32768.0, 32767.0) as i16;
-        writer
-            .write_sample(amplitude)
-            .map_err(|e| format!("Failed to write sample: {}", e))?;
-    }
-    
-    writer
-        .finalize()
-        .map_err(|e| format!("Failed to finalize WAV: {}", e))?;
-    
-    drop(cursor);
-    Ok(wav_data)
+    // No engine yet, so the default device
+    let output = output::CpalOutput::new(None);
+    let playing = coordinator::coordinator().acquire(BusyPolicy::Wait)?;
+    let controls = playback::Controls::new(0.8);
+    playback::play_on(&output, &audio, 1, &PlaybackOptions::new(), &playing, &controls)?;
+
+    Ok(())
 }
 
-// Convert WAV bytes to f32 samples
+// Convert WAV bytes to f32 samples, mono at the model rate
 fn wav_to_f32(wav_bytes: &[u8]) -> Result<Vec<f32>, String> {
-    let cursor = Cursor::new(wav_bytes);
-    let mut reader =
-        hound::WavReader::new(cursor).map_err(|e| format!("Failed to read WAV: {}", e))?;
-
-    let samples: Result<Vec<f32>, _> = reader
-        .samples::<i16>()
-        .map(|s: Result<i16, _>| s.map(|sample| sample as f32 / 32768.0))
-        .collect();
-
-    samples.map_err(|e| format!("Failed to read samples: {}", e))
+    decode_wav_bytes_with(wav_bytes, LoadOptions::model())
+        .map(AudioBuffer::into_samples)
+        .map_err(|e| e.to_string())
 }
 
-// Simple audio resampling (for OPUS)
-#[cfg(feature = "opus-format")]
-fn resample_audio(input: &[f32], from_rate: u32, to_rate: u32) -> Vec<f32> {
-    let ratio = to_rate as f32 / from_rate as f32;
-    let new_len = (input.len() as f32 * ratio) as usize;
-    let mut output = Vec::with_capacity(new_len);
-
-    for i in 0..new_len {
-        let src_idx = i as f32 / ratio;
-        let idx_floor = src_idx.floor() as usize;
-        let idx_ceil = (idx_floor + 1).min(input.len() - 1);
-        let fraction = src_idx - idx_floor as f32;
-
-        let sample = if idx_floor < input.len() {
-            input[idx_floor] * (1.0 - fraction) + input[idx_ceil] * fraction
-        } else {
-            0.0
-        };
-
-        output.push(sample);
+// `try_recover`'s answer: a failed download only means not yet
+fn recovered(result: Result<(), KokoroError>) -> Result<bool, KokoroError> {
+    match result {
+        Ok(()) => Ok(true),
+        Err(e @ KokoroError::DownloadFailed { .. }) => {
+            #[cfg(not(feature = "as-lib"))]
+            eprintln!("   ⏳ Still in fallback mode: {}", e);
+            Ok(false)
+        }
+        Err(e) => Err(e),
     }
+}
 
-    output
+// Pause length for text with nothing speakable in it
+fn unspeakable_pause_ms(text: &str) -> u32 {
+    text.chars()
+        .filter_map(punctuation_pause_ms)
+        .max()
+        .unwrap_or(0)
+        .max(if text.contains("...") { ELLIPSIS_PAUSE_MS } else { 0 })
 }
 
-fn needs_chunking(text: &str) -> bool {
-    text.chars().count() > LONG_TEXT_THRESHOLD || text.lines().count() > 3
+// Pause length for a punctuation mark, None if the character isn't punctuation
+fn punctuation_pause_ms(c: char) -> Option<u32> {
+    match c {
+        '…' => Some(ELLIPSIS_PAUSE_MS),
+        '.' | '!' | '?' | '。' | '！' | '？' => Some(400),
+        '—' | '–' => Some(300),
+        ',' | ';' | ':' | '、' => Some(200),
+        '«' | '»' | '“' | '”' | '‘' | '’' | '¡' | '¿' => Some(150),
+        c if c.is_ascii_punctuation() => Some(150),
+        _ => None,
+    }
 }
 
-fn chunk_crossfade_samples() -> usize {
-    ((SAMPLE_RATE as usize) * CHUNK_CROSSFADE_MS) / 1000
+// Long-form text: past the threshold, more than three lines or more than one
+// paragraph
+fn needs_chunking(text: &str, threshold: usize) -> bool {
+    text.chars().count() > threshold
+        || text.lines().count() > 3
+        || pacing::paragraphs(text).len() > 1
 }
 
+// Join two chunks of one render (see `audio::crossfade`)
 fn append_with_crossfade(buffer: &mut Vec<f32>, next: &[f32], overlap_samples: usize) {
-    if next.is_empty() {
-        return;
-    }
-
-    if buffer.is_empty() || overlap_samples == 0 {
-        buffer.extend_from_slice(next);
-        return;
-    }
-
-    let overlap = overlap_samples.min(buffer.len()).min(next.len());
-    if overlap == 0 {
-        buffer.extend_from_slice(next);
-        return;
-    }
-
-    let start = buffer.len() - overlap;
-    for i in 0..overlap {
-        let fade_in = i as f32 / overlap as f32;
-        let fade_out = 1.0 - fade_in;
-        buffer[start + i] = buffer[start + i] * fade_out + next[i] * fade_in;
-    }
-
-    buffer.extend_from_slice(&next[overlap..]);
+    audio::crossfade(buffer, next, overlap_samples, audio::CrossfadeCurve::EqualPower);
 }
 
 // Split text into chunks for better synthesis
 // Kokoro model handles shorter text better without dropping words
-fn split_text_for_tts(text: &str, max_chars: usize) -> Vec<String> {
-    // First try to split by sentences
-    let sentences: Vec<&str> = text
-        .split_terminator(&['.', '!', '?'][..])
-        .filter(|s| !s.trim().is_empty())
-        .collect();
+fn split_text_for_tts(text: &str, max_chars: usize, abbreviations: &[String]) -> Vec<String> {
+    // Sentences as they are in the text, each with its own terminator, so
+    // espeak still sees the "?" or "!" that makes the pause after it. The
+    // period of "Dr." or "U.S." doesn't end one.
+    let sentences = split_after(
+        text,
+        |c| matches!(c, '.' | '!' | '?' | '…' | '。' | '！' | '？' | '؟' | '۔'),
+        |piece| ends_with_abbreviation(piece, abbreviations),
+    );
 
     let mut chunks = Vec::new();
     let mut current_chunk = String::new();
 
-    for sentence in sentences {
-        // Add back the punctuation if it was there
-        let full_sentence = if text.contains(&format!("{}.", sentence.trim())) {
-            format!("{}.", sentence.trim())
-        } else if text.contains(&format!("{}!", sentence.trim())) {
-            format!("{}!", sentence.trim())
-        } else if text.contains(&format!("{}?", sentence.trim())) {
-            format!("{}?", sentence.trim())
-        } else {
-            sentence.trim().to_string()
-        };
-
+    // Lengths are in chars: a byte limit would give CJK text a third of it
+    for sentence in sentences {
+        let length = sentence.chars().count();
         // If this sentence alone is too long, split it by commas or words
-        if full_sentence.len() > max_chars {
+        if length > max_chars {
+            // After the sentences before it
+            if !current_chunk.is_empty() {
+                chunks.push(std::mem::take(&mut current_chunk));
+            }
             // Try splitting by commas first
-            let parts: Vec<&str> = full_sentence.split(',').collect();
+            let commas = |c| matches!(c, ',' | '、' | '，' | '،');
+            let parts = split_after(sentence, commas, |_| false);
             if parts.len() > 1 {
                 for part in parts {
-                    if part.trim().len() > max_chars {
+                    if part.chars().count() > max_chars {
                         // Still too long, split by words
                         chunks.extend(split_by_words(part, max_chars));
-                    } else if !part.trim().is_empty() {
-                        chunks.push(part.trim().to_string());
+                    } else {
+                        chunks.push(part.to_string());
                     }
                 }
             } else {
                 // No commas, split by words
-                chunks.extend(split_by_words(&full_sentence, max_chars));
+                chunks.extend(split_by_words(sentence, max_chars));
             }
         }
         // If adding this sentence would make chunk too long, save current and start new
         else if !current_chunk.is_empty()
-            && current_chunk.len() + full_sentence.len() + 1 > max_chars
+            && current_chunk.chars().count() + length + 1 > max_chars
         {
             chunks.push(current_chunk.trim().to_string());
-            current_chunk = full_sentence;
+            current_chunk = sentence.to_string();
         }
         // Add to current chunk
         else {
-            if !current_chunk.is_empty() {
+            // Scripts without spaces don't get one between sentences
+            if current_chunk.chars().next_back().is_some_and(|c| !ends_unspaced(c)) {
                 current_chunk.push(' ');
             }
-            current_chunk.push_str(&full_sentence);
+            current_chunk.push_str(sentence);
         }
     }
 
@@ -1247,12 +4486,100 @@ fn split_text_for_tts(text: &str, max_chars: usize) -> Vec<String> {
         chunks.push(current_chunk.trim().to_string());
     }
 
-    // If no chunks were created (text had no sentence endings), split by words
-    if chunks.is_empty() && !text.trim().is_empty() {
-        chunks = split_by_words(text, max_chars);
+    chunks
+}
+
+// Full-width punctuation ends a sentence or clause with no space after it
+fn ends_unspaced(c: char) -> bool {
+    matches!(c, '。' | '！' | '？' | '、' | '，')
+}
+
+// Trimmed, non-empty pieces of `text`, each ending after a run of characters
+// matching `ends` ("?!", "...") plus any closing quotes or brackets, where
+// whitespace or the end of the text follows (or right after full-width
+// punctuation); "3.14" and "1,000" stay whole, and so does a piece so far
+// that `holds` together with what follows
+fn split_after(
+    text: &str,
+    ends: impl Fn(char) -> bool,
+    holds: impl Fn(&str) -> bool,
+) -> Vec<&str> {
+    let closing = |c: char| {
+        matches!(c, '"' | '\'' | '”' | '’' | '»' | ')' | ']' | '」' | '』' | '）')
+    };
+    let mut pieces = Vec::new();
+    let mut start = 0;
+    let mut chars = text.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        if !ends(c) {
+            continue;
+        }
+        let mut unspaced = ends_unspaced(c);
+        let mut end = i + c.len_utf8();
+        while let Some(&(j, next)) = chars.peek() {
+            if !ends(next) && !closing(next) {
+                break;
+            }
+            unspaced |= ends_unspaced(next);
+            end = j + next.len_utf8();
+            chars.next();
+        }
+        let boundary = unspaced || chars.peek().is_none_or(|&(_, next)| next.is_whitespace());
+        if boundary && !holds(&text[start..end]) {
+            pieces.push(&text[start..end]);
+            start = end;
+        }
+    }
+    pieces.push(&text[start..]);
+    pieces
+        .into_iter()
+        .map(str::trim)
+        .filter(|piece| !piece.is_empty())
+        .collect()
+}
+
+// Words whose period doesn't end a sentence, compared without it and
+// ignoring case; `SynthesizeOptions::abbreviations` adds more
+const ABBREVIATIONS: &[&str] = &[
+    // Titles
+    "mr", "mrs", "ms", "dr", "prof", "rev", "hon", "sr", "jr", "st", "gen", "col", "capt", "lt",
+    "sgt", "gov", "sen", "rep",
+    // Addresses
+    "ave", "blvd", "rd", "mt", "ft", "apt",
+    // Latin
+    "etc", "vs", "cf", "al", "ca", "viz",
+    // Everyday
+    "approx", "dept", "est", "fig", "inc", "ltd", "corp", "vol",
+];
+
+// `piece` ends with an abbreviation's period: a listed word, an initial
+// ("J."), or a dotted one ("U.S.", "e.g."); not an ellipsis
+pub(crate) fn ends_with_abbreviation(piece: &str, extra: &[String]) -> bool {
+    let Some(word) = piece.strip_suffix('.') else {
+        return false;
+    };
+    let word = word
+        .rsplit(|c: char| c.is_whitespace() || matches!(c, '(' | '"' | '\'' | '“'))
+        .next()
+        .unwrap_or_default();
+    if word.is_empty() || word.ends_with('.') {
+        return false;
     }
 
-    chunks
+    let letters: Vec<&str> = word.split('.').collect();
+    let single = |letter: &&str| {
+        let mut chars = letter.chars();
+        chars.next().is_some_and(char::is_alphabetic) && chars.next().is_none()
+    };
+    if letters.iter().all(single) {
+        // A lone lower-case letter is more likely a word ("plan b.")
+        return letters.len() > 1 || word.chars().all(char::is_uppercase);
+    }
+
+    ABBREVIATIONS.iter().any(|abbr| abbr.eq_ignore_ascii_case(word))
+        || extra
+            .iter()
+            .any(|abbr| abbr.trim_end_matches('.').eq_ignore_ascii_case(word))
 }
 
 // Split text by words when sentences are too long
@@ -1262,7 +4589,14 @@ fn split_by_words(text: &str, max_chars: usize) -> Vec<String> {
     let mut current = String::new();
 
     for word in words {
-        if current.len() + word.len() + 1 > max_chars && !current.is_empty() {
+        let length = word.chars().count();
+        // A "word" this long is text without spaces (Japanese, Chinese, a URL)
+        if length > max_chars {
+            if !current.is_empty() {
+                chunks.push(std::mem::take(&mut current));
+            }
+            chunks.extend(split_graphemes(word, max_chars));
+        } else if current.chars().count() + length + 1 > max_chars && !current.is_empty() {
             chunks.push(current.trim().to_string());
             current = word.to_string();
         } else {
@@ -1280,45 +4614,185 @@ fn split_by_words(text: &str, max_chars: usize) -> Vec<String> {
     chunks
 }
 
-// Amplify audio - allows some clipping for maximum loudness
+// Last resort: cut every `max_chars` chars, between grapheme clusters so an
+// emoji sequence or a letter with its combining marks stays whole
+fn split_graphemes(text: &str, max_chars: usize) -> Vec<String> {
+    let mut chunks = Vec::new();
+    let mut current = String::new();
+    let mut length = 0;
+    for grapheme in text.graphemes(true) {
+        let size = grapheme.chars().count();
+        if length + size > max_chars && !current.is_empty() {
+            chunks.push(std::mem::take(&mut current));
+            length = 0;
+        }
+        current.push_str(grapheme);
+        length += size;
+    }
+    if !current.is_empty() {
+        chunks.push(current);
+    }
+    chunks
+}
+
+// Amplify audio; peaks the gain would push past the ceiling are limited
+// rather than clipped
 fn amplify_audio(audio: &[f32], gain: f32) -> Vec<f32> {
-    audio
-        .iter()
-        .map(|&sample| {
-            let amplified = sample * gain;
+    let amplified: Vec<f32> = audio.iter().map(|&sample| sample * gain).collect();
+    if amplified.iter().any(|s| s.abs() > limiter::DEFAULT_CEILING) {
+        limiter::limit(
+            &amplified,
+            limiter::DEFAULT_CEILING,
+            limiter::DEFAULT_ATTACK_MS,
+            limiter::DEFAULT_RELEASE_MS,
+        )
+    } else {
+        amplified
+    }
+}
 
-            // Simple hard clipping at the limits
-            // This allows maximum volume even if it distorts a bit
-            amplified.clamp(-1.0, 1.0)
-        })
-        .collect()
+// Raise (factor > 1.0) or lower the pitch by resampling; the audio gets
+// shorter or longer by the same factor, which render_chunk compensates
+fn shift_pitch(audio: &[f32], factor: f32) -> Vec<f32> {
+    let from_rate = (SAMPLE_RATE as f32 * factor).round() as u32;
+    resample(audio, from_rate, SAMPLE_RATE)
+}
+
+// Pitch factors outside this range are clamped (NaN means unchanged)
+fn clamp_pitch(factor: f32) -> f32 {
+    if factor.is_nan() {
+        1.0
+    } else {
+        factor.clamp(MIN_PITCH, MAX_PITCH)
+    }
+}
+
+// Above this (about -50 dBFS) a sample counts as sound for trim_silence
+fn is_sound(sample: &f32) -> bool {
+    sample.abs() > SILENCE_THRESHOLD
+}
+
+// Cut leading and trailing silence down to the margin; audio without any
+// sound is left alone
+fn trim_silence(audio: &mut Vec<f32>) {
+    let (Some(first), Some(last)) = (
+        audio.iter().position(is_sound),
+        audio.iter().rposition(is_sound),
+    ) else {
+        return;
+    };
+    audio.truncate((last + 1 + TRIM_MARGIN_SAMPLES).min(audio.len()));
+    audio.drain(..first.saturating_sub(TRIM_MARGIN_SAMPLES));
+}
+
+// One engine for every convenience constructor, so BabyTts, Mem8Bridge and
+// the application don't each load (or download) the model
+static SHARED_ENGINE: tokio::sync::OnceCell<Arc<TtsEngine>> =
+    tokio::sync::OnceCell::const_new();
+
+/// The process-wide engine behind `BabyTts::new` and `Mem8Bridge::new`
+///
+/// Built by `TtsEngine::new` on first use; later calls return the same
+/// instance. Hand it to your own code too instead of creating a second
+/// engine, which would load another copy of the model.
+///
+/// # Example
+/// ```no_run
+/// use kokoro_tiny::{shared_engine, BabyTts, KokoroError};
+///
+/// # async fn demo() -> Result<(), KokoroError> {
+/// let engine = shared_engine().await?;
+/// let mut baby = BabyTts::with_engine(engine.clone());
+/// let mama = baby.speak("mama")?;
+/// let hello = engine.synthesize("Hello", None, None, None)?;
+/// # Ok(())
+/// # }
+/// ```
+pub async fn shared_engine() -> Result<Arc<TtsEngine>, KokoroError> {
+    shared_engine_with(TtsEngine::new).await
+}
+
+// `shared_engine`, built by `build` if nobody has built it yet; tests pass a
+// builder that needs no model
+async fn shared_engine_with<F, Fut>(build: F) -> Result<Arc<TtsEngine>, KokoroError>
+where
+    F: FnOnce() -> Fut,
+    Fut: std::future::Future<Output = Result<TtsEngine, KokoroError>>,
+{
+    SHARED_ENGINE
+        .get_or_try_init(|| async { Ok(Arc::new(build().await?)) })
+        .await
+        .cloned()
 }
 
 // BabyTts implementation for mem8 integration
 impl BabyTts {
     /// Create a new baby TTS for mem8 learning
+    ///
+    /// # Example
+    /// ```no_run
+    /// use kokoro_tiny::BabyTts;
+    ///
+    /// # async fn demo() -> Result<(), String> {
+    /// let mut baby = BabyTts::new().await?;
+    /// let audio = baby.speak("mama")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// Renders through `shared_engine`, so it doesn't load a model of its own.
     pub async fn new() -> Result<Self, String> {
-        let engine = TtsEngine::new().await?;
-        Ok(Self {
+        Ok(Self::with_engine(shared_engine().await?))
+    }
+
+    /// Baby TTS with the default settings on an engine that already exists
+    ///
+    /// The engine can be used elsewhere as well; both render through it.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use kokoro_tiny::{BabyTts, KokoroError, TtsEngine};
+    /// use std::sync::Arc;
+    ///
+    /// # async fn demo() -> Result<(), KokoroError> {
+    /// let engine = Arc::new(TtsEngine::new().await?);
+    /// let mut baby = BabyTts::with_engine(engine.clone());
+    /// let mama = baby.speak("mama")?;
+    /// engine.play(mama, 0.8)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_engine(engine: Arc<TtsEngine>) -> Self {
+        Self {
             engine,
             max_words: 5,                // Babies start with short phrases
             voice: "af_sky".to_string(), // Gentle voice for baby
             speed: 0.9,                  // Slightly slower for clarity
             gain: 1.8,                   // Louder for clarity
             lang: DEFAULT_LANG.to_string(),
-        })
+        }
     }
 
     /// Create with custom settings
+    ///
+    /// # Example
+    /// ```no_run
+    /// use kokoro_tiny::BabyTts;
+    ///
+    /// # async fn demo() -> Result<(), String> {
+    /// // Up to three words, a little slower and quieter than the default
+    /// let baby = BabyTts::with_settings(3, "af_nicole", 0.8, 1.2).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
     pub async fn with_settings(
         max_words: usize,
         voice: &str,
         speed: f32,
         gain: f32,
     ) -> Result<Self, String> {
-        let engine = TtsEngine::new().await?;
         Ok(Self {
-            engine,
+            engine: shared_engine().await?,
             max_words,
             voice: voice.to_string(),
             speed,
@@ -1328,6 +4802,18 @@ impl BabyTts {
     }
 
     /// Speak a simple utterance (for mem8 baby learning)
+    ///
+    /// # Example
+    /// ```no_run
+    /// use kokoro_tiny::BabyTts;
+    ///
+    /// # async fn demo() -> Result<(), String> {
+    /// let mut baby = BabyTts::new().await?;
+    /// // Only the first five words are spoken
+    /// let audio = baby.speak("the cat sat on the warm mat")?;
+    /// # Ok(())
+    /// # }
+    /// ```
     pub fn speak(&mut self, text: &str) -> Result<Vec<f32>, String> {
         // Limit to max_words for baby speech
         let words: Vec<&str> = text.split_whitespace().collect();
@@ -1340,25 +4826,59 @@ impl BabyTts {
         };
 
         // Synthesize with baby settings
-self.engine
-             .synthesize_with_options(&limited_text, Some(&self.voice), self.speed, self.gain, Some(&self.lang))
+        self.render(&limited_text, self.speed)
     }
 
     /// Get raw audio samples at 24kHz (for mem8 processing)
+    ///
+    /// # Example
+    /// ```
+    /// use kokoro_tiny::{BabyTts, TtsEngine};
+    /// use std::sync::Arc;
+    ///
+    /// let baby = BabyTts::with_engine(Arc::new(TtsEngine::without_model()));
+    /// let (sample_rate, channels, bits) = baby.get_audio_params();
+    /// assert_eq!((sample_rate, channels, bits), (24_000, 1, 16));
+    /// ```
     pub fn get_audio_params(&self) -> (u32, u16, u16) {
         (SAMPLE_RATE, 1, 16) // 24kHz, mono, 16-bit
     }
 
     /// Process incoming audio for learning (placeholder for mem8 integration)
+    ///
+    /// # Example
+    /// ```
+    /// use kokoro_tiny::{BabyTts, TtsEngine};
+    /// use std::sync::Arc;
+    ///
+    /// let mut baby = BabyTts::with_engine(Arc::new(TtsEngine::without_model()));
+    /// baby.learn_from_audio(&[0.0; 2400], "dada")?;
+    /// # Ok::<(), String>(())
+    /// ```
     pub fn learn_from_audio(&mut self, audio: &[f32], text: &str) -> Result<(), String> {
         // This would integrate with mem8's learning system
         // For now, just log the learning attempt
         #[cfg(not(feature = "as-lib"))]
-        eprintln!("🧠 Baby learning: '{}' ({} samples)", text, audio.len());
+        eprintln!(
+            "🧠 Baby learning: '{}' ({} samples)",
+            privacy::display_text(text),
+            audio.len()
+        );
         Ok(())
     }
 
     /// Babble - generate random baby sounds (for early development stages)
+    ///
+    /// # Example
+    /// ```no_run
+    /// use kokoro_tiny::BabyTts;
+    ///
+    /// # async fn demo() -> Result<(), String> {
+    /// let mut baby = BabyTts::new().await?;
+    /// let audio = baby.babble()?;
+    /// # Ok(())
+    /// # }
+    /// ```
     pub fn babble(&mut self) -> Result<Vec<f32>, String> {
         let baby_sounds = ["ma", "ba", "da", "goo", "ga", "baba", "mama", "dada"];
         // Simple pseudo-random using current time
@@ -1372,14 +4892,37 @@ self.engine
     }
 
     /// Echo mode - repeat what was heard (for learning)
+    ///
+    /// # Example
+    /// ```no_run
+    /// use kokoro_tiny::BabyTts;
+    ///
+    /// # async fn demo() -> Result<(), String> {
+    /// let mut baby = BabyTts::new().await?;
+    /// let audio = baby.echo("ball")?;
+    /// # Ok(())
+    /// # }
+    /// ```
     pub fn echo(&mut self, text: &str) -> Result<Vec<f32>, String> {
         // Simple echo with slightly different intonation
         let echo_speed = self.speed * 1.1; // Slightly faster for echo
-        self.engine
-            .synthesize_with_options(text, Some(&self.voice), echo_speed, self.gain, Some(&self.lang))
+        self.render(text, echo_speed)
     }
 
     /// Grow vocabulary - increase max words as baby learns
+    ///
+    /// # Example
+    /// ```no_run
+    /// use kokoro_tiny::BabyTts;
+    ///
+    /// # async fn demo() -> Result<(), String> {
+    /// let mut baby = BabyTts::new().await?;
+    /// baby.grow();
+    /// // Six words fit now
+    /// let audio = baby.speak("the cat sat on the mat")?;
+    /// # Ok(())
+    /// # }
+    /// ```
     pub fn grow(&mut self) {
         self.max_words = (self.max_words + 1).min(20); // Cap at 20 words for kokoro-tiny
         #[cfg(not(feature = "as-lib"))]
@@ -1388,46 +4931,13 @@ eprintln!(
             self.max_words
         );
     }
-}
-
-// Audio ducking functions - reduce system volume during TTS playback
-#[cfg(feature = "ducking")]
-fn duck_system_audio(level: f32) -> Result<(), String> {
-    // Calculate how many volume-down presses we need
-    // Assuming each press is ~6% volume change on most systems
-    let steps = ((1.0 - level) * 16.0) as u32; // 16 steps = ~100% volume range
-
-    let mut enigo = Enigo::new(&Settings::default())
-        .map_err(|e| format!("Failed to initialize Enigo: {:?}", e))?;
-
-    // Press volume down keys to reduce system volume
-    for _ in 0..steps {
-        enigo
-            .key(Key::VolumeDown, enigo::Direction::Click)
-            .map_err(|e| format!("Failed to press volume down: {:?}", e))?;
-        thread::sleep(Duration::from_millis(20)); // Small delay between key presses
-    }
-
-    Ok(())
-}
-
-#[cfg(feature = "ducking")]
-fn restore_system_audio(level: f32) -> Result<(), String> {
-    // Calculate how many volume-up presses to restore
-    let steps = ((1.0 - level) * 16.0) as u32;
-
-    let mut enigo = Enigo::new(&Settings::default())
-        .map_err(|e| format!("Failed to initialize Enigo: {:?}", e))?;
 
-    // Press volume up keys to restore system volume
-    for _ in 0..steps {
-        enigo
-            .key(Key::VolumeUp, enigo::Direction::Click)
-            .map_err(|e| format!("Failed to press volume up: {:?}", e))?;
-        thread::sleep(Duration::from_millis(20)); // Small delay between key presses
+    // Baby voice, gain and language through the (possibly shared) engine
+    fn render(&self, text: &str, speed: f32) -> Result<Vec<f32>, String> {
+        self.engine
+            .synthesize_with_options(text, Some(&self.voice), speed, self.gain, Some(&self.lang))
+            .map_err(String::from)
     }
-
-    Ok(())
 }
 
 #[cfg(test)]
@@ -1445,13 +4955,848 @@ mod tests {
         assert!((buffer.last().copied().unwrap() - 0.0).abs() < f32::EPSILON);
     }
 
+    #[test]
+    fn cache_dir_follows_the_environment_in_order() {
+        let env = |pairs: &'static [(&'static str, &'static str)]| {
+            move |name: &str| {
+                pairs
+                    .iter()
+                    .find(|(key, _)| *key == name)
+                    .map(|(_, value)| OsString::from(value))
+            }
+        };
+        let all = &[
+            ("KOKORO_CACHE_DIR", "/srv/kokoro"),
+            ("XDG_CACHE_HOME", "/home/me/.xdg"),
+            ("LOCALAPPDATA", "C:/Users/me/AppData/Local"),
+            ("HOME", "/home/me"),
+        ];
+        assert_eq!(cache_dir_from(env(all), false), Path::new("/srv/kokoro"));
+        assert_eq!(
+            cache_dir_from(env(&all[1..]), false),
+            Path::new("/home/me/.xdg/kokoro-tiny")
+        );
+        assert_eq!(
+            cache_dir_from(env(&all[2..]), true),
+            Path::new("C:/Users/me/AppData/Local/kokoro-tiny")
+        );
+        assert_eq!(cache_dir_from(env(&all[2..]), false), Path::new("/home/me/.cache/k"));
+
+        // A relative XDG_CACHE_HOME is ignored, as the spec says
+        let relative = &[("XDG_CACHE_HOME", "cache"), ("HOME", "/home/me")];
+        assert_eq!(cache_dir_from(env(relative), false), Path::new("/home/me/.cache/k"));
+        let nothing = &[];
+        assert_eq!(cache_dir_from(env(nothing), false), Path::new("./.cache/k"));
+    }
+
+    #[test]
+    fn voice_mix_round_trips_through_canonical_string() {
+        let mix = VoiceMix::parse("af_sky.4+af_nicole.5").unwrap();
+        assert_eq!(
+            mix.components,
+            vec![("af_sky".to_string(), 0.4), ("af_nicole".to_string(), 0.5)]
+        );
+
+        let normalized = mix.normalized();
+        let reparsed = VoiceMix::parse(&normalized.to_string()).unwrap();
+        for ((a, wa), (b, wb)) in normalized.components.iter().zip(&reparsed.components) {
+            assert_eq!(a, b);
+            assert!((wa - wb).abs() < 1e-4);
+        }
+
+        assert_eq!(VoiceMix::parse("af_sky").unwrap().to_string(), "af_sky");
+        assert!(VoiceMix::parse("af_sky.x").is_err());
+        assert!(VoiceMix::from_components(vec![("af_sky".to_string(), 0.0)]).is_err());
+    }
+
+    #[test]
+    fn errors_can_be_matched_by_kind() {
+        let calls = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let mut engine = TtsEngine::counting(calls);
+
+        let err = engine.synthesize("Hello", Some("af_nope"), None, None).unwrap_err();
+        assert!(matches!(&err, KokoroError::VoiceNotFound { name, .. } if name == "af_nope"));
+        let err = engine.synthesize("Hello", Some("af_sky.5+"), None, None).unwrap_err();
+        assert!(matches!(err, KokoroError::InvalidVoice(_)), "{:?}", err);
+
+        // A failure after some chunks rendered keeps its kind, with the chunk in the message
+        let long = "The first sentence is long enough to be a chunk on its own today. ".repeat(8);
+        let failures = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        engine.mock_inference = Some(Arc::new(move |tokens: &[i64], _: &[f32], _: f32| {
+            match failures.fetch_add(1, std::sync::atomic::Ordering::SeqCst) {
+                0 => Ok(vec![0.5; tokens.len() * 100]),
+                _ => Err("Failed to run inference: out of memory".to_string()),
+            }
+        }));
+        let err = engine.synthesize(&long, None, None, None).unwrap_err();
+        match err {
+            KokoroError::InferenceFailed(message) => {
+                assert!(message.starts_with("Chunk 2/"), "{}", message);
+                assert!(message.ends_with("out of memory"), "{}", message);
+            }
+            other => panic!("expected InferenceFailed, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn warm_up_runs_one_hidden_inference() {
+        let calls = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let engine = TtsEngine::counting(calls.clone());
+        engine.warm_up().unwrap();
+        assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 1);
+        assert_eq!(engine.cache_stats().entries, 0);
+
+        // Any voice will do when the pack has no default voice
+        let voices = HashMap::from([("am_adam".to_string(), vec![0.1; STYLE_DIM])]);
+        let mut engine = TtsEngine::with_test_voices(voices);
+        engine.mock_inference = Some(Arc::new(|tokens: &[i64], style: &[f32], _: f32| {
+            assert!(tokens.len() > 2 && style[0] == 0.1);
+            Ok(vec![0.0; tokens.len()])
+        }));
+        engine.warm_up().unwrap();
+
+        // No model to warm in fallback mode
+        assert!(TtsEngine::without_model().warm_up().is_ok());
+    }
+
+    #[test]
+    fn unspeakable_input_becomes_pause_without_inference() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let cases = [("…", 500), ("...", 500), ("—", 300), (":)", 200), ("$", 150), ("🎉", 0)];
+        for (text, pause_ms) in cases {
+            let calls = Arc::new(AtomicUsize::new(0));
+            let engine = TtsEngine::counting(calls.clone());
+
+            let audio = engine.synthesize(text, None, None, None).unwrap();
+            assert_eq!(calls.load(Ordering::SeqCst), 0, "inference ran for {:?}", text);
+            assert_eq!(audio.len(), SAMPLE_RATE as usize * pause_ms / 1000, "{:?}", text);
+            assert!(audio.iter().all(|&s| s == 0.0));
+            let expected = match pause_ms {
+                0 => vec![],
+                _ => vec![SynthesisWarning::Unspeakable {
+                    text: text.to_string(),
+                    pause_ms: pause_ms as u32,
+                }],
+            };
+            assert_eq!(engine.last_warnings(), expected, "{:?}", text);
+        }
+    }
+
+    #[test]
+    fn mixed_text_keeps_ellipsis_inline() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let calls = Arc::new(AtomicUsize::new(0));
+        let mut engine = TtsEngine::counting(calls.clone());
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let inner = engine.mock_inference.clone().unwrap();
+        let record = seen.clone();
+        engine.mock_inference = Some(Arc::new(move |tokens: &[i64], style: &[f32], speed: f32| {
+            record.lock().unwrap().push(tokens.to_vec());
+            inner(tokens, style, speed)
+        }));
+
+        // The ellipsis stays inside the spoken segment so the model places the pause
+        let audio = engine.synthesize("Wait… go!", None, None, None).unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert!(!audio.is_empty());
+        assert!(engine.last_warnings().is_empty());
+
+        // ...between the two words, not at either end
+        let ellipsis = engine.vocab[&'…'];
+        let pad = engine.vocab[&PAD_TOKEN];
+        let tokens = seen.lock().unwrap()[0].clone();
+        let at = tokens.iter().position(|&t| t == ellipsis).expect("ellipsis token");
+        assert_eq!(tokens.iter().filter(|&&t| t == ellipsis).count(), 1);
+        let spoken = |part: &[i64]| part.iter().any(|&t| t != pad && t != engine.vocab[&' ']);
+        assert!(spoken(&tokens[..at]) && spoken(&tokens[at + 1..]), "{:?}", tokens);
+    }
+
+    #[test]
+    fn shouted_text_is_normalized_with_warning() {
+        let calls = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let engine = TtsEngine::counting(calls);
+
+        engine.synthesize("CI FAILED ON GPU NODE", None, None, None).unwrap();
+        assert_eq!(
+            engine.last_warnings(),
+            vec![SynthesisWarning::CapsNormalized {
+                original: "CI FAILED ON GPU NODE".to_string(),
+                normalized: "C I failed on G P U node".to_string(),
+            }]
+        );
+
+        let opts = SynthesizeOptions::new().caps_policy(CapsPolicy::Preserve);
+        engine.synthesize_with("CI FAILED ON GPU NODE", opts).unwrap();
+        assert!(engine.last_warnings().is_empty());
+    }
+
+    #[test]
+    fn unknown_characters_are_reported_or_refused() {
+        use std::sync::atomic::Ordering;
+
+        let calls = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let engine = TtsEngine::counting(calls.clone());
+        let text = "Build ok Привет ┃ done";
+
+        let (_, warnings) = engine.synthesize_with_warnings(text, None, None).unwrap();
+        assert_eq!(
+            engine.last_warnings(),
+            vec![SynthesisWarning::UnknownCharacters {
+                text: text.to_string(),
+                characters: "привет┃".chars().collect(),
+                positions: vec![9, 10, 11, 12, 13, 14, 16],
+            }]
+        );
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("'┃'"), "{}", warnings[0]);
+
+        // Plain text has nothing to report
+        engine.synthesize("Build ok, done", None, None, None).unwrap();
+        assert!(engine.last_warnings().is_empty());
+
+        let before = calls.load(Ordering::SeqCst);
+        let opts = SynthesizeOptions::new().strict_characters(true);
+        let err = engine.synthesize_with(text, opts).unwrap_err();
+        assert!(matches!(err, KokoroError::InvalidInput(_)), "{:?}", err);
+        assert_eq!(calls.load(Ordering::SeqCst), before);
+    }
+
+    #[test]
+    fn added_acronyms_reach_synthesis() {
+        let calls = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let mut engine = TtsEngine::counting(calls.clone());
+        let report = |engine: &TtsEngine| {
+            let report = engine
+                .phoneme_report("Back up the SQL database", &SynthesizeOptions::new())
+                .unwrap();
+            report[0].normalized.clone()
+        };
+        assert_eq!(report(&engine), "Back up the S Q L database");
+
+        engine.synthesize("Back up the SQL database", None, None, None).unwrap();
+        engine.add_acronym("SQL", Spoken::Custom("sequel".into()));
+        assert_eq!(report(&engine), "Back up the sequel database");
+        // The cached take had the old reading
+        engine.synthesize("Back up the SQL database", None, None, None).unwrap();
+        assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn lexicon_ipa_replaces_what_espeak_would_say() {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let mut voices = HashMap::new();
+        voices.insert(DEFAULT_VOICE.to_string(), vec![0.1; 256]);
+        let mut engine = TtsEngine::with_test_voices(voices);
+        let sink = seen.clone();
+        engine.mock_inference = Some(Arc::new(move |tokens: &[i64], _style: &[f32], _speed: f32| {
+            sink.lock().unwrap().push(tokens.to_vec());
+            Ok(vec![0.1; tokens.len()])
+        }));
+        let text = "Please call Nguyen today.";
+
+        engine.synthesize(text, None, None, None).unwrap();
+        engine
+            .add_pronunciation("nguyen", Pronunciation::Ipa("wˈɪn".into()))
+            .unwrap();
+        engine.synthesize(text, None, None, None).unwrap();
+
+        let seen = seen.lock().unwrap();
+        assert_eq!(seen.len(), 2, "the cached take must not be reused");
+        assert_ne!(seen[0], seen[1]);
+        let ipa = engine.tokenize("wˈɪn");
+        assert!(seen[1].windows(ipa.len()).any(|w| w == ipa.as_slice()));
+        assert!(!seen[0].windows(ipa.len()).any(|w| w == ipa.as_slice()));
+        // The words around it still come from espeak
+        let report = engine.phoneme_report(text, &SynthesizeOptions::new()).unwrap();
+        assert_eq!(report[0].tokens, seen[1]);
+        let around = engine.phonemize("Please call", None).unwrap().unwrap();
+        let around = around.trim_end_matches('$');
+        assert!(report[0].phonemes.as_deref().unwrap().starts_with(around));
+
+        // A respelling goes through espeak like any text
+        engine
+            .add_pronunciation("Kubernetes", Pronunciation::Text("koo ber net eez".into()))
+            .unwrap();
+        let report = engine
+            .phoneme_report("Kubernetes is up", &SynthesizeOptions::new())
+            .unwrap();
+        assert_eq!(report[0].normalized, "koo ber net eez is up");
+    }
+
+    #[test]
+    fn phoneme_report_matches_what_the_model_receives() {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let mut voices = HashMap::new();
+        voices.insert(DEFAULT_VOICE.to_string(), vec![0.1; 256]);
+        let mut engine = TtsEngine::with_test_voices(voices);
+        let sink = seen.clone();
+        engine.mock_inference = Some(Arc::new(move |tokens: &[i64], _style: &[f32], _speed: f32| {
+            sink.lock().unwrap().push(tokens.to_vec());
+            Ok(vec![0.1; tokens.len()])
+        }));
+
+        let text = "The nightly build failed on the GPU node again. Someone should look at it before \
+            the release goes out tomorrow morning, ideally before lunch. The logs are attached below, \
+            along with the last three runs for comparison. …";
+        let opts = SynthesizeOptions::new();
+        let report = engine.phoneme_report(text, &opts).unwrap();
+        engine.synthesize_with(text, opts).unwrap();
+
+        assert!(report.len() > 1);
+        let spoken: Vec<Vec<i64>> = report
+            .iter()
+            .filter(|chunk| chunk.phonemes.is_some())
+            .map(|chunk| chunk.tokens.clone())
+            .collect();
+        assert_eq!(*seen.lock().unwrap(), spoken);
+
+        for chunk in &report {
+            let phonemes = chunk.phonemes.as_deref().unwrap_or_default();
+            assert_eq!(chunk.tokens, engine.tokenize(phonemes));
+        }
+
+        let shouted = engine
+            .phoneme_report("BUILD FAILED ON THE GPU NODE", &SynthesizeOptions::new())
+            .unwrap();
+        assert_eq!(shouted[0].normalized, "Build failed on the G P U node");
+
+        // Each unknown symbol is listed once, however far apart its repeats are
+        let boxed = engine
+            .phoneme_report("Build ┃ ok ┃ done", &SynthesizeOptions::new())
+            .unwrap();
+        assert_eq!(boxed[0].unknown_chars, ['┃']);
+    }
+
+    #[test]
+    fn report_and_synthesis_agree_on_unknown_characters() {
+        let engine = TtsEngine::mock(10);
+        let text = "Build ┃ ok (really) - done; next: «deploy»";
+
+        let report = engine.phoneme_report(text, &SynthesizeOptions::new()).unwrap();
+        let reported: Vec<char> = report.iter().flat_map(|chunk| chunk.unknown_chars.clone()).collect();
+        let (_, warnings) = collect_warnings(|| engine.synthesize(text, None, None, None).unwrap());
+        let warned: Vec<char> = warnings
+            .into_iter()
+            .flat_map(|warning| match warning {
+                SynthesisWarning::UnknownCharacters { characters, .. } => characters,
+                _ => Vec::new(),
+            })
+            .collect();
+
+        assert_eq!(reported, warned);
+        assert!(reported.contains(&'┃'));
+        assert!(!reported.iter().any(char::is_ascii_punctuation), "{:?}", reported);
+    }
+
+    #[test]
+    fn repeated_text_is_served_from_cache() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let calls = Arc::new(AtomicUsize::new(0));
+        let mut engine = TtsEngine::counting(calls.clone());
+
+        let first = engine.synthesize("BUILD FAILED ON MAIN", None, None, None).unwrap();
+        let again = engine.synthesize("BUILD FAILED ON MAIN", None, None, None).unwrap();
+        assert_eq!(first, again);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        // Warnings come back with the cached audio
+        assert_eq!(engine.last_warnings().len(), 1);
+
+        // Different settings are a different entry
+        engine.synthesize("BUILD FAILED ON MAIN", None, Some(1.2), None).unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+        let stats = engine.cache_stats();
+        assert_eq!((stats.entries, stats.hits, stats.misses), (2, 1, 2));
+
+        engine.set_cache_budget(0);
+        assert_eq!(engine.cache_stats().entries, 0);
+        let err = engine.pin("Ready.", SynthesizeOptions::new()).unwrap_err();
+        assert!(err.to_string().contains("too large"), "{}", err);
+
+        // A fresh take every time is refused for what it is, before rendering
+        engine.set_cache_budget(DEFAULT_CACHE_BUDGET_BYTES);
+        let before = calls.load(Ordering::SeqCst);
+        let unseeded = SynthesizeOptions::new().style_temperature(0.2, None);
+        let err = engine.pin("Ready.", unseeded).unwrap_err();
+        assert!(err.to_string().contains("seed"), "{}", err);
+        assert_eq!(calls.load(Ordering::SeqCst), before);
+    }
+
+    #[test]
+    fn changing_a_voice_drops_its_cached_audio() {
+        let mut engine = TtsEngine::with_test_voices(HashMap::new());
+        engine.mock_inference = Some(Arc::new(|tokens: &[i64], style: &[f32], _speed: f32| {
+            Ok(vec![style[0]; tokens.len() * 100])
+        }));
+
+        engine.import_voice("narrator", vec![0.1; 256]).unwrap();
+        let before = engine.synthesize("Ready.", Some("narrator"), None, None).unwrap();
+        engine.import_voice("narrator", vec![0.3; 256]).unwrap();
+        let after = engine.synthesize("Ready.", Some("narrator"), None, None).unwrap();
+        assert_ne!(before, after);
+        assert_eq!(engine.cache_stats().hits, 0);
+
+        assert!(engine.remove_voice("narrator"));
+        assert_eq!(engine.cache_stats().entries, 0);
+        assert!(engine.synthesize("Ready.", Some("narrator"), None, None).is_err());
+    }
+
+    #[test]
+    fn speed_mode_decides_what_the_model_sees() {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let mut voices = HashMap::new();
+        voices.insert(DEFAULT_VOICE.to_string(), vec![0.1; 256]);
+        let mut engine = TtsEngine::with_test_voices(voices);
+        let record = seen.clone();
+        engine.mock_inference = Some(Arc::new(move |tokens: &[i64], _style: &[f32], speed: f32| {
+            record.lock().unwrap().push(speed);
+            Ok(vec![0.1; tokens.len() * 10])
+        }));
+        let model_speed = |engine: &mut TtsEngine, opts: SynthesizeOptions| {
+            let expected = engine.model_speed(&opts);
+            engine.synthesize_with("Hello there.", opts).unwrap();
+            let speed = seen.lock().unwrap().pop().unwrap();
+            assert_eq!(speed, expected);
+            speed
+        };
+
+        let natural = |speed| SynthesizeOptions::new().speed(speed);
+        let model = |speed| natural(speed).speed_mode(SpeedMode::Model);
+
+        assert_eq!(model_speed(&mut engine, natural(1.0)), 0.65);
+        assert_eq!(model_speed(&mut engine, model(1.0)), 1.0);
+        assert_eq!(model_speed(&mut engine, model(0.8)), 0.8);
+
+        engine.set_speed_scale(0.8).unwrap();
+        assert_eq!(model_speed(&mut engine, natural(1.0)), 0.8);
+        assert_eq!(model_speed(&mut engine, model(1.0)), 1.0);
+
+        // Clamped after scaling: 3.0 * 0.8 = 2.4 and 0.5 * 0.8 = 0.4
+        assert_eq!(model_speed(&mut engine, natural(3.0)), MAX_ENGINE_SPEED);
+        assert_eq!(model_speed(&mut engine, natural(0.5)), 0.4);
+        assert_eq!(model_speed(&mut engine, model(3.0)), MAX_ENGINE_SPEED);
+        assert_eq!(model_speed(&mut engine, model(0.1)), MIN_ENGINE_SPEED);
+
+        assert!(matches!(
+            engine.set_speed_scale(0.0),
+            Err(KokoroError::InvalidInput(_))
+        ));
+        assert!(engine.set_speed_scale(f32::NAN).is_err());
+        assert_eq!(engine.speed_scale(), 0.8);
+    }
+
+    #[test]
+    fn threads_synthesize_concurrently_on_one_engine() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Barrier;
+
+        let in_flight = Arc::new(AtomicUsize::new(0));
+        let overlap = Arc::new(AtomicUsize::new(0));
+        let mut engine = TtsEngine::with_test_voices(HashMap::from([(
+            "af_sky".to_string(),
+            vec![0.1; STYLE_DIM],
+        )]));
+        engine.mock_inference = Some({
+            let (in_flight, overlap) = (in_flight.clone(), overlap.clone());
+            Arc::new(move |tokens: &[i64], _style: &[f32], _speed: f32| {
+                let now = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                overlap.fetch_max(now, Ordering::SeqCst);
+                thread::sleep(Duration::from_millis(50));
+                in_flight.fetch_sub(1, Ordering::SeqCst);
+                Ok(tokens.iter().map(|&t| t as f32 / 1000.0).collect())
+            })
+        });
+
+        let texts = ["Build passed.", "Tests failed twice.", "Deploy is done.", "Hi."];
+        let expected: Vec<Vec<f32>> = texts
+            .iter()
+            .map(|text| engine.synthesize(text, None, None, None).unwrap())
+            .collect();
+        // Render again below rather than hit the cache
+        engine.clear_cache();
+        let engine = Arc::new(engine);
+
+        let barrier = Arc::new(Barrier::new(texts.len()));
+        let handles: Vec<_> = texts
+            .into_iter()
+            .map(|text| {
+                let (engine, barrier) = (engine.clone(), barrier.clone());
+                thread::spawn(move || {
+                    barrier.wait();
+                    engine.synthesize(text, None, None, None).unwrap()
+                })
+            })
+            .collect();
+        let rendered: Vec<Vec<f32>> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+
+        assert_eq!(rendered, expected);
+        // No engine-wide lock around the (mock) model
+        assert!(overlap.load(Ordering::SeqCst) > 1);
+    }
+
+    #[test]
+    fn each_call_collects_its_own_warnings() {
+        use std::sync::Barrier;
+
+        let engine = Arc::new(TtsEngine::mock(100));
+        let barrier = Arc::new(Barrier::new(2));
+        let render = |text: &'static str| {
+            let (engine, barrier) = (engine.clone(), barrier.clone());
+            thread::spawn(move || {
+                barrier.wait();
+                collect_warnings(|| engine.synthesize(text, None, None, None).unwrap()).1
+            })
+        };
+        let shouted = render("CI FAILED ON GPU NODE");
+        let calm = render("Build passed.");
+        assert_eq!(calm.join().unwrap(), vec![]);
+        assert_eq!(
+            shouted.join().unwrap(),
+            vec![SynthesisWarning::CapsNormalized {
+                original: "CI FAILED ON GPU NODE".to_string(),
+                normalized: "C I failed on G P U node".to_string(),
+            }]
+        );
+
+        // An outer call sees the inner ones; nothing is left open afterwards
+        let (_, warnings) = collect_warnings(|| {
+            engine
+                .synthesize_segments(vec![
+                    ("DISK FULL".to_string(), SynthesizeOptions::new()),
+                    ("Retrying.".to_string(), SynthesizeOptions::new()),
+                ])
+                .unwrap()
+        });
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(engine.last_warnings(), warnings);
+        assert!(CALL_WARNINGS.with(|calls| calls.borrow().is_empty()));
+    }
+
+    #[tokio::test]
+    async fn strict_engines_error_instead_of_falling_back() {
+        let dir = std::env::temp_dir().join(format!("kokoro-strict-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let model = dir.join("0.onnx");
+        let voices = dir.join("0.bin");
+        let (model, voices) = (model.to_str().unwrap(), voices.to_str().unwrap());
+
+        // An open circuit: the download fails without touching the network
+        let now = SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_secs();
+        let state = retry::RetryState {
+            consecutive_failures: 5,
+            last_attempt: now,
+            circuit_open_until: Some(now + 3600),
+        };
+        state.save(&dir).unwrap();
+
+        match TtsEngine::with_paths_strict(model, voices).await {
+            Err(KokoroError::DownloadFailed { source, .. }) => {
+                assert!(source.to_string().contains("circuit open"), "{}", source)
+            }
+            Err(e) => panic!("expected DownloadFailed, got {:?}", e),
+            Ok(_) => panic!("strict engine fell back"),
+        }
+
+        let engine = TtsEngine::with_paths(model, voices).await.unwrap();
+        assert!(engine.is_fallback());
+        assert!(matches!(
+            engine.retry_initialization().await,
+            Err(KokoroError::DownloadFailed { .. })
+        ));
+        assert!(!engine.try_recover().await.unwrap());
+        assert!(engine.is_fallback());
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[tokio::test]
+    async fn recovery_leaves_fallback_mode_once_the_download_succeeds() {
+        use voice_pack::tests::{npz, Dtype};
+
+        let dir = std::env::temp_dir().join(format!("kokoro-recover-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let model = dir.join("0.onnx").to_str().unwrap().to_string();
+        let voices = dir.join("0.bin").to_str().unwrap().to_string();
+        let mut engine = TtsEngine::fallback(
+            Some((model.clone(), voices.clone())),
+            dir.join("chunks"),
+            &EngineBuilder::default(),
+        );
+        engine.mock_inference = Some(Arc::new(|tokens: &[i64], _: &[f32], _: f32| {
+            Ok(vec![0.5; tokens.len() * 100])
+        }));
+
+        // Still offline: stays on the fallback message
+        let offline = |url: String, _: String| async move {
+            Err(KokoroError::download_failed(url, "offline"))
+        };
+        assert!(!recovered(engine.recover_with(offline).await).unwrap());
+        assert!(engine.is_fallback());
+        let fallback = engine.synthesize("Hello", None, None, None).unwrap();
+        assert_eq!(fallback, wav_to_f32(FALLBACK_MESSAGE).unwrap());
+
+        // Back online: the files arrive and the engine speaks with the model
+        let style = vec![0.1; STYLE_DIM];
+        let pack = npz(&[(DEFAULT_VOICE, Dtype::F32, &[1, STYLE_DIM], &style)]);
+        let online = |model: String, voices: String| async move {
+            fs::write(model, b"model")?;
+            fs::write(voices, pack)?;
+            Ok::<(), KokoroError>(())
+        };
+        assert!(recovered(engine.recover_with(online).await).unwrap());
+        assert!(!engine.is_fallback());
+        assert!(engine.has_voice(DEFAULT_VOICE));
+        let audio = engine.synthesize("Hello", None, None, None).unwrap();
+        assert_ne!(audio, fallback);
+
+        // Nothing left to recover
+        assert!(engine.try_recover().await.unwrap());
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[tokio::test]
+    async fn a_no_download_engine_never_recovers() {
+        let dir = std::env::temp_dir().join(format!("kokoro-offline-{}", std::process::id()));
+        let mut engine = TtsEngine::fallback(None, dir.join("chunks"), &EngineBuilder::default());
+        engine.formant_fallback = true;
+
+        assert!(!engine.try_recover().await.unwrap());
+        assert!(engine.is_fallback());
+        assert!(matches!(
+            engine.retry_initialization().await,
+            Err(KokoroError::ModelMissing(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn a_shared_engine_recovers_for_every_holder() {
+        use voice_pack::tests::{npz, Dtype};
+
+        let dir = std::env::temp_dir().join(format!("kokoro-recover-shared-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let model = dir.join("0.onnx").to_str().unwrap().to_string();
+        let voices = dir.join("0.bin").to_str().unwrap().to_string();
+        let mut engine = TtsEngine::fallback(
+            Some((model, voices)),
+            dir.join("chunks"),
+            &EngineBuilder::default(),
+        );
+        engine.mock_inference = Some(Arc::new(|tokens: &[i64], _: &[f32], _: f32| {
+            Ok(vec![0.5; tokens.len() * 100])
+        }));
+        let engine = Arc::new(engine);
+        let speaker = Arc::clone(&engine);
+        let fallback = wav_to_f32(FALLBACK_MESSAGE).unwrap();
+        assert_eq!(speaker.synthesize("Hello", None, None, None).unwrap(), fallback);
+
+        let style = vec![0.1; STYLE_DIM];
+        let pack = npz(&[(DEFAULT_VOICE, Dtype::F32, &[1, STYLE_DIM], &style)]);
+        let online = |model: String, voices: String| async move {
+            fs::write(model, b"model")?;
+            fs::write(voices, pack)?;
+            Ok::<(), KokoroError>(())
+        };
+        assert!(recovered(engine.recover_with(online).await).unwrap());
+
+        // The other holder speaks with the model without rebuilding anything
+        assert!(!speaker.is_fallback());
+        let audio = tokio::task::spawn_blocking(move || {
+            speaker.synthesize("Hello", None, None, None).unwrap()
+        })
+        .await
+        .unwrap();
+        assert_ne!(audio, fallback);
+        assert!(engine.try_recover().await.unwrap());
+        engine.warm_up().unwrap();
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[tokio::test]
+    async fn async_synthesis_runs_off_the_executor_thread() {
+        // Each render sleeps; inline on this single-threaded runtime they
+        // would run one after another
+        let running = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let peak = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let voices = HashMap::from([(DEFAULT_VOICE.to_string(), vec![0.1; 256])]);
+        let mut engine = TtsEngine::with_test_voices(voices);
+        let (now, max) = (running.clone(), peak.clone());
+        engine.mock_inference = Some(Arc::new(move |tokens: &[i64], _: &[f32], _: f32| {
+            let n = now.fetch_add(1, std::sync::atomic::Ordering::SeqCst) + 1;
+            max.fetch_max(n, std::sync::atomic::Ordering::SeqCst);
+            thread::sleep(Duration::from_millis(50));
+            now.fetch_sub(1, std::sync::atomic::Ordering::SeqCst);
+            Ok(vec![0.5; tokens.len() * 100])
+        }));
+        let engine = Arc::new(engine);
+
+        let texts = ["One request.", "Another request.", "A third.", "And a fourth."];
+        let [a, b, c, d] =
+            texts.map(|text| engine.synthesize_async(text, SynthesizeOptions::new()));
+        let (a, b, c, d) = tokio::join!(a, b, c, d);
+        for (text, audio) in texts.iter().zip([a, b, c, d]) {
+            let expected = engine.synthesize_with(text, SynthesizeOptions::new()).unwrap();
+            assert_eq!(audio.unwrap(), expected);
+        }
+        assert!(peak.load(std::sync::atomic::Ordering::SeqCst) > 1);
+
+        let err = engine
+            .synthesize_async("Hello", SynthesizeOptions::new().voice("af_nope"))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, KokoroError::VoiceNotFound { .. }), "{:?}", err);
+    }
+
     #[test]
     fn detects_need_for_chunking() {
         let short = "hello world";
-        assert!(!needs_chunking(short));
+        assert!(!needs_chunking(short, LONG_TEXT_THRESHOLD));
 
         let long = "This sentence is intentionally quite a bit longer than the \
                     short sample so that it exceeds the chunking threshold we set.";
-        assert!(needs_chunking(long));
+        assert!(needs_chunking(long, LONG_TEXT_THRESHOLD));
+
+        // Paragraphs are rendered apart however short they are
+        assert!(needs_chunking("Hello.\n\nWorld.", LONG_TEXT_THRESHOLD));
+    }
+
+    // Whitespace-normalized, for comparing chunks with their source
+    fn squash(text: &str) -> String {
+        text.split_whitespace().collect::<Vec<_>>().join(" ")
+    }
+
+    #[test]
+    fn chunks_keep_each_sentences_own_punctuation() {
+        let texts = [
+            // The same sentence twice, ending differently each time
+            "Is it done? Is it done! Is it done.",
+            "Really?! No way... Fine… Wait?",
+            "\"Stop!\" she said. 'Why?' he asked. (It was late.) Then silence.",
+            "Pi is 3.14, roughly. It costs 1,000 dollars! No terminator here",
+        ];
+        for text in texts {
+            let chunks = split_text_for_tts(text, 20, &[]);
+            assert_eq!(squash(&chunks.join(" ")), squash(text), "{:?}", chunks);
+        }
+
+        let chunks = split_text_for_tts("Is it done? Is it done! Is it done.", 12, &[]);
+        assert_eq!(chunks, ["Is it done?", "Is it done!", "Is it done."]);
+        let chunks = split_text_for_tts("\"Stop!\" she said. 'Why?' he asked.", 14, &[]);
+        assert_eq!(chunks, ["\"Stop!\"", "she said.", "'Why?'", "he asked."]);
+        // Commas stay on their clause when a long sentence is split there
+        let chunks = split_text_for_tts("First of all, second of all, third.", 16, &[]);
+        assert_eq!(chunks, ["First of all,", "second of all,", "third."]);
+        assert!(split_text_for_tts(" \n ", 20, &[]).is_empty());
+    }
+
+    #[test]
+    fn chunks_respect_char_limits_in_any_script() {
+        let texts = [
+            // Japanese and Chinese: no spaces, full-width punctuation
+            "今日はとても良い天気ですね。散歩に行きましょうか？公園まで歩いて、少し休んでから帰りましょう！",
+            "我们明天早上八点出发，先去博物馆，然后去公园散步。你觉得怎么样？",
+            // Emoji, including a ZWJ family and a flag
+            "The party was great 🎉🎉 and the whole family 👨‍👩‍👧‍👦 came! Even from 🇯🇵, can you believe it?",
+            // Arabic, with its own comma and question mark
+            "مرحبا بكم في المدينة، نتمنى لكم إقامة سعيدة. هل تحتاجون إلى مساعدة؟ نحن هنا دائما.",
+            // A letter with a combining accent
+            "Cafe\u{301} cafe\u{301} cafe\u{301} cafe\u{301} cafe\u{301}",
+        ];
+        for text in texts {
+            for max_chars in [8, 20, 50] {
+                let chunks = split_text_for_tts(text, max_chars, &[]);
+                if text.chars().count() > max_chars {
+                    assert!(chunks.len() > 1, "{:?}", chunks);
+                }
+                for chunk in &chunks {
+                    // Only a single grapheme may go over the limit
+                    assert!(
+                        chunk.chars().count() <= max_chars || chunk.graphemes(true).count() == 1,
+                        "{:?} over {}",
+                        chunk,
+                        max_chars
+                    );
+                    assert!(!chunk.starts_with(['\u{200d}', '\u{301}', '\u{1f466}']));
+                }
+                let joined: String = chunks.concat();
+                assert_eq!(
+                    joined.replace(' ', ""),
+                    text.replace(' ', ""),
+                    "{:?}",
+                    chunks
+                );
+            }
+        }
+
+        // Sentences end at full-width terminators without a space after them
+        let chunks = split_text_for_tts("はい。いいえ！本当？", 4, &[]);
+        assert_eq!(chunks, ["はい。", "いいえ！", "本当？"]);
+        // and are joined back without one
+        assert_eq!(split_text_for_tts("はい。いいえ！", 10, &[]), ["はい。いいえ！"]);
+        // Cut at the ideographic comma before cutting anywhere
+        let chunks = split_text_for_tts("東京から大阪まで、新幹線で行きます。", 12, &[]);
+        assert_eq!(chunks, ["東京から大阪まで、", "新幹線で行きます。"]);
+        // Char limits, not bytes: nine kana fit in ten chars
+        assert_eq!(split_text_for_tts("あいうえおかきくけ", 10, &[]).len(), 1);
+    }
+
+    #[test]
+    fn abbreviations_dont_end_sentences() {
+        let split = |text, max_chars| split_text_for_tts(text, max_chars, &[]);
+
+        let address = "Dr. Smith lives on St. James Ave. near the U.S. border. He likes it there.";
+        assert_eq!(
+            split(address, 60),
+            [
+                "Dr. Smith lives on St. James Ave. near the U.S. border.",
+                "He likes it there."
+            ]
+        );
+        assert_eq!(
+            split("J. R. R. Tolkien wrote it, i.e. the whole book. Then he rested.", 50),
+            ["J. R. R. Tolkien wrote it, i.e. the whole book.", "Then he rested."]
+        );
+        // Decimals and versions never ended a sentence; ellipses and a lone
+        // lower-case letter still do
+        assert_eq!(
+            split("Pi is 3.14 and v2.0.1 is out. Done.", 30),
+            ["Pi is 3.14 and v2.0.1 is out.", "Done."]
+        );
+        assert_eq!(
+            split("We waited... Then plan b. Done.", 12),
+            ["We waited...", "Then plan b.", "Done."]
+        );
+
+        // Callers can add their own
+        let text = "Nobody was surprised. Acme Intl. announced it.";
+        assert_eq!(
+            split(text, 32),
+            ["Nobody was surprised. Acme Intl.", "announced it."]
+        );
+        let custom = split_text_for_tts(text, 32, &["intl".to_string()]);
+        assert_eq!(custom, ["Nobody was surprised.", "Acme Intl. announced it."]);
+
+        // Threaded through the options
+        let opts = SynthesizeOptions::new().chunk_max_chars(32).abbreviations(&["Intl."]);
+        assert_eq!(crate::SynthesisPlan::new(text, &opts).chunks, custom);
+    }
+
+    #[test]
+    fn long_japanese_paragraph_is_synthesized_in_chunks() {
+        let calls = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let engine = TtsEngine::counting(calls.clone());
+        let paragraph = "吾輩は猫である。名前はまだ無い。どこで生れたかとんと見当がつかぬ、\
+                         何でも薄暗いじめじめした所でニャーニャー泣いていた事だけは記憶している。"
+            .repeat(8);
+        assert!(paragraph.chars().count() >= 500);
+
+        let opts = SynthesizeOptions::new().lang("ja").lenient_language(true);
+        let audio = engine.synthesize_with(&paragraph, opts).unwrap();
+        assert!(!audio.is_empty());
+        assert!(calls.load(std::sync::atomic::Ordering::SeqCst) > 1);
     }
 }
@@ -0,0 +1,195 @@
+//! Peak limiting for loud gain settings
+//!
+//! Multiplying by the gain and clamping to ±1.0 flattens every peak that
+//! goes over, which at gain 2.0 and up is audible as crackle on plosives.
+//! `limit` lowers the level around those peaks instead: it looks a few
+//! milliseconds ahead, eases the gain down before a peak arrives (attack)
+//! and back up after it (release), and follows a soft knee so the reduction
+//! starts gently below the ceiling. The waveform keeps its shape; only its
+//! envelope changes.
+//!
+//! `amplify_audio` runs it whenever the gain would push a sample past
+//! `DEFAULT_CEILING`, so quiet and normal gains are untouched.
+
+use std::collections::VecDeque;
+
+use crate::{AudioDuration, SAMPLE_RATE};
+
+/// Highest level the limiter lets through when gain is applied
+pub const DEFAULT_CEILING: f32 = 0.95;
+
+/// Look-ahead and attack time used when gain is applied
+pub const DEFAULT_ATTACK_MS: f32 = 5.0;
+
+/// Release time used when gain is applied
+pub const DEFAULT_RELEASE_MS: f32 = 80.0;
+
+// The knee starts this far below the ceiling (about 2 dB)
+const KNEE_RATIO: f32 = 0.8;
+
+/// `audio` with its peaks held under `ceiling`
+///
+/// The gain is lowered over `attack_ms` before each peak (which is also how
+/// far the limiter looks ahead) and recovers over `release_ms` after it.
+/// Levels up to a knee just below the ceiling pass unchanged.
+///
+/// # Example
+/// ```
+/// use kokoro_tiny::limiter::limit;
+///
+/// let loud: Vec<f32> = (0..2400).map(|i| 2.5 * (i as f32 * 0.1).sin()).collect();
+/// let limited = limit(&loud, 0.9, 5.0, 50.0);
+/// assert!(limited.iter().all(|s| s.abs() <= 0.9));
+/// ```
+pub fn limit(audio: &[f32], ceiling: f32, attack_ms: f32, release_ms: f32) -> Vec<f32> {
+    let ceiling = if ceiling.is_finite() {
+        ceiling.clamp(0.0, 1.0)
+    } else {
+        1.0
+    };
+    let attack = samples(attack_ms).max(1);
+    let release = samples(release_ms);
+
+    // Gain each sample needs on its own, then the smallest one ahead of it
+    let needed: Vec<f32> = audio.iter().map(|s| knee_gain(s.abs(), ceiling)).collect();
+    let lowest = window_min(&needed, attack);
+
+    // Drops at once (already `attack` samples early), recovers with `release`
+    let recovery = if release == 0 {
+        0.0
+    } else {
+        (-1.0 / release as f32).exp()
+    };
+    let mut held = Vec::with_capacity(audio.len());
+    let mut gain = 1.0f32;
+    for &target in &lowest {
+        gain = if target < gain {
+            target
+        } else {
+            target + (gain - target) * recovery
+        };
+        held.push(gain);
+    }
+
+    // Averaging over the look-ahead turns each drop into a ramp that reaches
+    // the needed gain by the time its peak arrives
+    let mut out = Vec::with_capacity(audio.len());
+    let mut sum = 0.0f64;
+    for (i, &sample) in audio.iter().enumerate() {
+        sum += f64::from(held[i]);
+        if i >= attack {
+            sum -= f64::from(held[i - attack]);
+        }
+        let gain = (sum / (i + 1).min(attack) as f64) as f32;
+        out.push((sample * gain).clamp(-ceiling, ceiling));
+    }
+    out
+}
+
+fn samples(ms: f32) -> usize {
+    AudioDuration::from_millis_f32(ms).to_samples(SAMPLE_RATE)
+}
+
+// Gain that brings `level` onto the soft knee: unchanged below it, then
+// approaching the ceiling without reaching it
+fn knee_gain(level: f32, ceiling: f32) -> f32 {
+    let knee = ceiling * KNEE_RATIO;
+    if level <= knee || !level.is_finite() {
+        return 1.0;
+    }
+    let width = ceiling - knee;
+    let shaped = knee + width * (1.0 - (-(level - knee) / width).exp());
+    shaped / level
+}
+
+// Smallest value in `values[i..i + width]` for every `i`
+fn window_min(values: &[f32], width: usize) -> Vec<f32> {
+    let mut out = vec![1.0; values.len()];
+    let mut candidates: VecDeque<usize> = VecDeque::new();
+    for i in (0..values.len()).rev() {
+        while candidates.back().is_some_and(|&j| values[j] >= values[i]) {
+            candidates.pop_back();
+        }
+        candidates.push_back(i);
+        while candidates.front().is_some_and(|&j| j >= i + width) {
+            candidates.pop_front();
+        }
+        out[i] = values[candidates[0]];
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::PI;
+
+    // 375 Hz divides the sample rate, so every window below holds whole cycles
+    const TONE_HZ: f32 = 375.0;
+    const PERIOD: usize = (SAMPLE_RATE as f32 / TONE_HZ) as usize;
+
+    fn tone(seconds: f32) -> Vec<f32> {
+        crate::test_signal::tone(&[(TONE_HZ, 1.0)], seconds)
+    }
+
+    // Amplitude of `harmonic` times the tone frequency
+    fn amplitude(audio: &[f32], harmonic: usize) -> f32 {
+        let step = 2.0 * PI * (harmonic as f32 * TONE_HZ) / SAMPLE_RATE as f32;
+        let (re, im) = audio
+            .iter()
+            .enumerate()
+            .fold((0.0, 0.0), |(re, im), (i, &s)| {
+                (
+                    re + s * (step * i as f32).cos(),
+                    im - s * (step * i as f32).sin(),
+                )
+            });
+        2.0 * (re * re + im * im).sqrt() / audio.len() as f32
+    }
+
+    fn thd(audio: &[f32]) -> f32 {
+        let harmonics: f32 = (2..=15).map(|h| amplitude(audio, h).powi(2)).sum();
+        harmonics.sqrt() / amplitude(audio, 1)
+    }
+
+    #[test]
+    fn triple_gain_stays_under_the_ceiling_without_clipping() {
+        let loud = crate::amplify_audio(&tone(1.0), 3.0);
+        assert!(loud.iter().all(|s| s.abs() <= DEFAULT_CEILING));
+        // No flattened peaks: exactly ±1.0 never occurs, and no three samples
+        // in a row sit at the same level
+        assert!(!loud.iter().any(|s| s.abs() == 1.0));
+        assert!(!loud.windows(3).any(|w| w[0] == w[1] && w[1] == w[2]));
+
+        let clipped: Vec<f32> = tone(1.0)
+            .iter()
+            .map(|s| (s * 3.0).clamp(-1.0, 1.0))
+            .collect();
+        // Past the attack, in whole cycles
+        let steady = |audio: &[f32]| audio[PERIOD * 20..PERIOD * 360].to_vec();
+        let (limited, hard) = (thd(&steady(&loud)), thd(&steady(&clipped)));
+        assert!(limited < 0.01, "limited THD {}", limited);
+        assert!(hard > 0.2, "clipped THD {}", hard);
+    }
+
+    #[test]
+    fn quiet_audio_passes_unchanged() {
+        let quiet: Vec<f32> = tone(0.2).iter().map(|s| s * 0.5).collect();
+        assert_eq!(limit(&quiet, DEFAULT_CEILING, 5.0, 80.0), quiet);
+        assert_eq!(
+            crate::amplify_audio(&quiet, 1.5),
+            quiet.iter().map(|s| s * 1.5).collect::<Vec<_>>()
+        );
+        assert!(limit(&[], DEFAULT_CEILING, 5.0, 80.0).is_empty());
+
+        // A lone peak is reached gradually, and the audio recovers after it
+        let mut audio = vec![0.5; 4800];
+        audio[2400] = 3.0;
+        let limited = limit(&audio, DEFAULT_CEILING, 5.0, 20.0);
+        assert!(limited[2400] <= DEFAULT_CEILING);
+        let smooth = |audio: &[f32]| audio.windows(2).all(|w| (w[0] - w[1]).abs() < 0.01);
+        assert!(smooth(&limited[..2400]) && smooth(&limited[2401..]));
+        assert_eq!(limited[0], 0.5);
+        assert!((limited[4799] - 0.5).abs() < 0.01);
+    }
+}
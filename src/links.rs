@@ -0,0 +1,425 @@
+//! Spoken form of URLs, email addresses and file paths
+//!
+//! Logs and alerts are full of them, and espeak reads them as one long
+//! string of phonemes. This pass reads them out part by part:
+//!
+//! ```text
+//! https://github.com/8b-is/kokoro-tiny/pull/42
+//!     -> github dot com slash eight b dash is slash kokoro dash tiny slash pull slash forty-two
+//! http://localhost:8080/search?q=tts&page=2
+//!     -> localhost port eight thousand eighty slash search query q equals tts and page equals two
+//! mailto:ops@example.com, ops@example.com
+//!     -> ops at example dot com
+//! /var/log/syslog, ~/notes.txt
+//!     -> slash var slash log slash syslog, home slash notes dot txt
+//! ```
+//!
+//! `UrlStyle` picks how much of a URL is read; addresses and paths are always
+//! read in full. The scheme and trailing punctuation (the full stop after a
+//! URL at the end of a sentence) are left out of the reading. Words are
+//! English except for German text; URLs are read in English in every other
+//! language too, since left alone they aren't read at all.
+
+use serde::{Deserialize, Serialize};
+
+use crate::units;
+
+/// How much of a URL is read out
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UrlStyle {
+    /// Host, port, path, query and fragment
+    #[default]
+    Full,
+    /// Just the host, without "www": "github dot com"
+    Domain,
+    /// "a link"
+    Link,
+}
+
+struct Words {
+    code: &'static str,
+    dot: &'static str,
+    slash: &'static str,
+    dash: &'static str,
+    underscore: &'static str,
+    at: &'static str,
+    plus: &'static str,
+    home: &'static str,
+    port: &'static str,
+    query: &'static str,
+    equals: &'static str,
+    and: &'static str,
+    section: &'static str,
+    link: &'static str,
+}
+
+const WORDS: &[Words] = &[
+    Words {
+        code: "en",
+        dot: "dot",
+        slash: "slash",
+        dash: "dash",
+        underscore: "underscore",
+        at: "at",
+        plus: "plus",
+        home: "home",
+        port: "port",
+        query: "query",
+        equals: "equals",
+        and: "and",
+        section: "section",
+        link: "a link",
+    },
+    Words {
+        code: "de",
+        dot: "Punkt",
+        slash: "Schrägstrich",
+        dash: "Bindestrich",
+        underscore: "Unterstrich",
+        at: "at",
+        plus: "plus",
+        home: "Home",
+        port: "Port",
+        query: "Abfrage",
+        equals: "gleich",
+        and: "und",
+        section: "Abschnitt",
+        link: "ein Link",
+    },
+];
+
+// Around a URL but not part of it: "(see https://x.org/a)."
+const OPENING: &[char] = &['(', '[', '{', '<', '"', '\'', '“', '‘'];
+const CLOSING: &[char] = &[
+    '.', ',', ';', ':', '!', '?', ')', ']', '}', '>', '"', '\'', '”', '’',
+];
+
+/// Rewrite the URLs, email addresses and paths in `text` to be read aloud
+///
+/// Returns `None` when there were none.
+///
+/// # Example
+/// ```
+/// use kokoro_tiny::links::{expand_links, UrlStyle};
+///
+/// let alert = "Build failed, see https://ci.example.com/runs/7 or mail ops@example.com.";
+/// assert_eq!(
+///     expand_links(alert, "en", UrlStyle::Full).as_deref(),
+///     Some("Build failed, see ci dot example dot com slash runs slash seven or mail ops at example dot com.")
+/// );
+/// assert_eq!(
+///     expand_links(alert, "en", UrlStyle::Link).as_deref(),
+///     Some("Build failed, see a link or mail ops at example dot com.")
+/// );
+/// ```
+pub fn expand_links(text: &str, lang: &str, style: UrlStyle) -> Option<String> {
+    let words = units::lookup(lang)
+        .and_then(|locale| WORDS.iter().find(|words| words.code == locale.code))
+        .unwrap_or(&WORDS[0]);
+    let cardinal = units::lookup(lang)
+        .map(|locale| locale.cardinal)
+        .unwrap_or(units::english_cardinal);
+    let reader = Reader { words, cardinal };
+
+    let mut out = String::with_capacity(text.len());
+    let mut changed = false;
+    for piece in text.split_inclusive(char::is_whitespace) {
+        let token = piece.trim_end();
+        let core = token.trim_start_matches(OPENING).trim_end_matches(CLOSING);
+        let spoken = reader
+            .url(core, style)
+            .or_else(|| reader.email(core.strip_prefix("mailto:").unwrap_or(core)))
+            .or_else(|| reader.path(core));
+
+        match spoken {
+            Some(spoken) => {
+                let start = token.len() - token.trim_start_matches(OPENING).len();
+                let end = start + core.len();
+                out.push_str(&token[..start]);
+                out.push_str(&spoken);
+                out.push_str(&token[end..]);
+                changed = true;
+            }
+            None => out.push_str(token),
+        }
+        out.push_str(&piece[token.len()..]);
+    }
+
+    changed.then_some(out)
+}
+
+struct Reader {
+    words: &'static Words,
+    cardinal: fn(u64) -> String,
+}
+
+impl Reader {
+    fn url(&self, token: &str, style: UrlStyle) -> Option<String> {
+        let rest = ["https://", "http://", "ftp://"]
+            .iter()
+            .find_map(|scheme| strip_prefix_ignore_case(token, scheme))
+            .or_else(|| strip_prefix_ignore_case(token, "www.").map(|_| token))?;
+
+        let host_end = rest.find(['/', '?', '#']).unwrap_or(rest.len());
+        let (authority, tail) = rest.split_at(host_end);
+        let (host, port) = match authority.rsplit_once(':') {
+            Some((host, port)) if !port.is_empty() && port.bytes().all(|b| b.is_ascii_digit()) => {
+                (host, Some(port))
+            }
+            _ => (authority, None),
+        };
+        if host.is_empty() || !host.chars().any(char::is_alphanumeric) {
+            return None;
+        }
+
+        match style {
+            UrlStyle::Link => return Some(self.words.link.to_string()),
+            UrlStyle::Domain => {
+                let host = strip_prefix_ignore_case(host, "www.").unwrap_or(host);
+                return Some(self.spell(host));
+            }
+            UrlStyle::Full => {}
+        }
+
+        let mut spoken = vec![self.spell(host)];
+        if let Some(port) = port {
+            spoken.push(self.words.port.to_string());
+            spoken.push(self.spell(port));
+        }
+        let (path, fragment) = tail.split_once('#').unwrap_or((tail, ""));
+        let (path, query) = path.split_once('?').unwrap_or((path, ""));
+        for segment in path.split('/').filter(|segment| !segment.is_empty()) {
+            spoken.push(self.words.slash.to_string());
+            spoken.push(self.spell(segment));
+        }
+        let pairs: Vec<String> = query
+            .split('&')
+            .filter(|pair| !pair.is_empty())
+            .map(|pair| match pair.split_once('=') {
+                Some((key, value)) => {
+                    format!(
+                        "{} {} {}",
+                        self.spell(key),
+                        self.words.equals,
+                        self.spell(value)
+                    )
+                }
+                None => self.spell(pair),
+            })
+            .collect();
+        if !pairs.is_empty() {
+            spoken.push(self.words.query.to_string());
+            spoken.push(pairs.join(&format!(" {} ", self.words.and)));
+        }
+        if !fragment.is_empty() {
+            spoken.push(self.words.section.to_string());
+            spoken.push(self.spell(fragment));
+        }
+        Some(spoken.join(" "))
+    }
+
+    // "user.name@example.com"
+    fn email(&self, token: &str) -> Option<String> {
+        let (local, domain) = token.split_once('@')?;
+        let local_ok = !local.is_empty()
+            && local
+                .chars()
+                .all(|c| c.is_alphanumeric() || ".+-_".contains(c));
+        let domain_ok = domain.contains('.')
+            && !domain.starts_with('.')
+            && !domain.ends_with('.')
+            && domain
+                .chars()
+                .all(|c| c.is_alphanumeric() || ".-".contains(c));
+        if !local_ok || !domain_ok {
+            return None;
+        }
+        Some(format!(
+            "{} {} {}",
+            self.spell(local),
+            self.words.at,
+            self.spell(domain)
+        ))
+    }
+
+    // "/var/log/syslog", "~/notes.txt", "./build.sh"; not "and/or" or "1/2"
+    fn path(&self, token: &str) -> Option<String> {
+        let rooted = ["/", "~/", "./", "../"]
+            .iter()
+            .any(|root| token.starts_with(root));
+        let path_chars = token
+            .chars()
+            .all(|c| c.is_alphanumeric() || "/._-~+".contains(c));
+        if !rooted || !path_chars || !token.chars().any(char::is_alphabetic) {
+            return None;
+        }
+        let (home, rest) = match token.strip_prefix('~') {
+            Some(rest) => (Some(self.words.home), rest),
+            None => (None, token),
+        };
+        let mut spoken: Vec<String> = home.into_iter().map(str::to_string).collect();
+        spoken.push(self.spell(rest));
+        Some(spoken.join(" "))
+    }
+
+    // Word by word: letters as they are, digits as a number, punctuation by
+    // name ("8b-is" -> "eight b dash is")
+    fn spell(&self, part: &str) -> String {
+        let mut spoken = Vec::new();
+        let mut chars = part.char_indices().peekable();
+        while let Some((start, c)) = chars.next() {
+            let same_run = |next: char| {
+                if c.is_ascii_digit() {
+                    next.is_ascii_digit()
+                } else {
+                    next.is_alphabetic()
+                }
+            };
+            if c.is_ascii_digit() || c.is_alphabetic() {
+                let mut end = start + c.len_utf8();
+                while let Some(&(idx, next)) = chars.peek() {
+                    if !same_run(next) {
+                        break;
+                    }
+                    end = idx + next.len_utf8();
+                    chars.next();
+                }
+                spoken.push(self.run(&part[start..end]));
+                continue;
+            }
+            let name = match c {
+                '.' => self.words.dot,
+                '/' => self.words.slash,
+                '-' => self.words.dash,
+                '_' => self.words.underscore,
+                '@' => self.words.at,
+                '+' => self.words.plus,
+                _ => continue,
+            };
+            spoken.push(name.to_string());
+        }
+        spoken.join(" ")
+    }
+
+    // Short numbers as numbers, ids and hashes ("0042", "20240105") digit by digit
+    fn run(&self, run: &str) -> String {
+        let digits = run.bytes().all(|b| b.is_ascii_digit());
+        if !digits {
+            return run.to_string();
+        }
+        match run.parse::<u64>() {
+            Ok(n) if run.len() <= 4 && !(run.len() > 1 && run.starts_with('0')) => {
+                (self.cardinal)(n)
+            }
+            _ => run
+                .bytes()
+                .map(|digit| (self.cardinal)(u64::from(digit - b'0')))
+                .collect::<Vec<_>>()
+                .join(" "),
+        }
+    }
+}
+
+fn strip_prefix_ignore_case<'a>(text: &'a str, prefix: &str) -> Option<&'a str> {
+    let head = text.get(..prefix.len())?;
+    head.eq_ignore_ascii_case(prefix)
+        .then(|| &text[prefix.len()..])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn full(text: &str) -> Option<String> {
+        expand_links(text, "en", UrlStyle::Full)
+    }
+
+    #[test]
+    fn urls_are_read_part_by_part() {
+        let cases = [
+            (
+                "https://github.com/8b-is/kokoro-tiny/pull/42",
+                "github dot com slash eight b dash is slash kokoro dash tiny slash pull slash forty-two",
+            ),
+            (
+                "http://localhost:8080/health",
+                "localhost port eight thousand eighty slash health",
+            ),
+            (
+                "https://example.com/search?q=rust&page=2#results",
+                "example dot com slash search query q equals rust and page equals two section results",
+            ),
+            ("www.example.org/", "www dot example dot org"),
+            (
+                "(see https://docs.rs/ort.)",
+                "(see docs dot rs slash ort.)",
+            ),
+            ("HTTPS://EXAMPLE.COM", "EXAMPLE dot COM"),
+        ];
+        for (text, expected) in cases {
+            assert_eq!(full(text).as_deref(), Some(expected), "{}", text);
+        }
+    }
+
+    #[test]
+    fn styles_read_less_of_the_url() {
+        let text = "Deployed to https://www.example.com:443/app?id=7.";
+        assert_eq!(
+            expand_links(text, "en", UrlStyle::Domain).as_deref(),
+            Some("Deployed to example dot com.")
+        );
+        assert_eq!(
+            expand_links(text, "en", UrlStyle::Link).as_deref(),
+            Some("Deployed to a link.")
+        );
+        assert_eq!(
+            expand_links("Siehe https://example.de/hilfe", "de", UrlStyle::Full).as_deref(),
+            Some("Siehe example Punkt de Schrägstrich hilfe")
+        );
+    }
+
+    #[test]
+    fn emails_and_paths() {
+        let cases = [
+            ("user@example.com", "user at example dot com"),
+            (
+                "mailto:first.last+tts@mail.example.co.uk",
+                "first dot last plus tts at mail dot example dot co dot uk",
+            ),
+            (
+                "Logs in /var/log/syslog.",
+                "Logs in slash var slash log slash syslog.",
+            ),
+            ("~/notes.txt", "home slash notes dot txt"),
+            (
+                "run ./build_all.sh",
+                "run dot slash build underscore all dot sh",
+            ),
+            (
+                "commit 0042 on /srv/20240105",
+                "commit 0042 on slash srv slash two zero two four zero one zero five",
+            ),
+        ];
+        for (text, expected) in cases {
+            assert_eq!(full(text).as_deref(), Some(expected), "{}", text);
+        }
+    }
+
+    #[test]
+    fn everything_else_is_left_alone() {
+        for text in [
+            "and/or",
+            "1/2",
+            "e.g. this",
+            "@alice said",
+            "x@y",
+            "3.14",
+            "user@",
+            "a / b",
+            "http://",
+        ] {
+            assert_eq!(full(text), None, "{}", text);
+        }
+    }
+}
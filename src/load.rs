@@ -0,0 +1,277 @@
+//! Reading audio files into buffers
+//!
+//! For beds to mix under speech, audio for `learn_from_audio`, or output
+//! saved earlier that needs more processing. WAV files of 8 to 32-bit
+//! integers or 32-bit floats are read with hound in every build; with the
+//! playback feature, `load_audio` also decodes MP3, Ogg Vorbis and FLAC
+//! through rodio. Samples come back as `f32` in -1.0 to 1.0, resampled to the
+//! model's 24kHz unless `LoadOptions` says otherwise.
+
+use std::io::Read;
+use std::sync::Arc;
+
+use crate::{audio, AudioBuffer, KokoroError, SAMPLE_RATE};
+
+/// What the loaders do to audio after reading it
+///
+/// # Example
+/// ```
+/// use kokoro_tiny::{decode_wav_bytes_with, LoadOptions, TtsEngine};
+///
+/// let tts = TtsEngine::without_model();
+/// let wav = tts.to_wav_bytes(&[0.0; 2400])?;
+/// // Keep the file's rate, one channel
+/// let opts = LoadOptions { mono: true, sample_rate: None };
+/// let audio = decode_wav_bytes_with(&wav, opts)?;
+/// assert_eq!((audio.channels(), audio.len()), (1, 2400));
+/// # Ok::<(), String>(())
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LoadOptions {
+    /// Average the channels into one
+    pub mono: bool,
+    /// Rate to resample to, `None` to keep the file's (default: 24kHz)
+    pub sample_rate: Option<u32>,
+}
+
+impl Default for LoadOptions {
+    fn default() -> Self {
+        LoadOptions {
+            mono: false,
+            sample_rate: Some(SAMPLE_RATE),
+        }
+    }
+}
+
+impl LoadOptions {
+    /// One channel at 24kHz, the way synthesis output is
+    pub fn model() -> Self {
+        LoadOptions {
+            mono: true,
+            ..LoadOptions::default()
+        }
+    }
+}
+
+/// A WAV file at 24kHz, with its own channels
+///
+/// # Example
+/// ```no_run
+/// use kokoro_tiny::load_wav;
+///
+/// let intro = load_wav("intro.wav")?;
+/// println!("{} channels, {}ms", intro.channels(), intro.duration().as_millis());
+/// # Ok::<(), String>(())
+/// ```
+pub fn load_wav(path: &str) -> Result<AudioBuffer, KokoroError> {
+    load_wav_with(path, LoadOptions::default())
+}
+
+/// A WAV file, downmixed and resampled as `opts` say
+pub fn load_wav_with(path: &str, opts: LoadOptions) -> Result<AudioBuffer, KokoroError> {
+    let reader = hound::WavReader::open(path).map_err(|e| wav_error(path, e))?;
+    read_wav(reader, path, opts)
+}
+
+/// WAV file bytes at 24kHz, with their own channels
+///
+/// # Example
+/// ```
+/// use kokoro_tiny::{decode_wav_bytes, AudioBuffer, TtsEngine};
+///
+/// let tts = TtsEngine::without_model();
+/// let cd = AudioBuffer::new(vec![0.5; 44_100], 44_100);
+/// let audio = decode_wav_bytes(&tts.to_wav_bytes(&cd)?)?;
+/// assert_eq!((audio.sample_rate(), audio.len()), (24_000, 24_000));
+/// # Ok::<(), String>(())
+/// ```
+pub fn decode_wav_bytes(bytes: &[u8]) -> Result<AudioBuffer, KokoroError> {
+    decode_wav_bytes_with(bytes, LoadOptions::default())
+}
+
+/// WAV file bytes, downmixed and resampled as `opts` say
+pub fn decode_wav_bytes_with(bytes: &[u8], opts: LoadOptions) -> Result<AudioBuffer, KokoroError> {
+    let reader = hound::WavReader::new(bytes).map_err(|e| wav_error("data", e))?;
+    read_wav(reader, "data", opts)
+}
+
+/// An MP3, Ogg Vorbis, FLAC or WAV file at 24kHz, with its own channels
+/// (requires 'playback' feature)
+///
+/// # Example
+/// ```no_run
+/// use kokoro_tiny::load_audio;
+///
+/// let bed = load_audio("music.mp3")?;
+/// # Ok::<(), String>(())
+/// ```
+#[cfg(feature = "playback")]
+pub fn load_audio(path: &str) -> Result<AudioBuffer, KokoroError> {
+    load_audio_with(path, LoadOptions::default())
+}
+
+/// An MP3, Ogg Vorbis, FLAC or WAV file, downmixed and resampled as `opts`
+/// say (requires 'playback' feature)
+#[cfg(feature = "playback")]
+pub fn load_audio_with(path: &str, opts: LoadOptions) -> Result<AudioBuffer, KokoroError> {
+    use rodio::Source;
+
+    let file = std::fs::File::open(path)?;
+    let decoder = rodio::Decoder::new(std::io::BufReader::new(file))
+        .map_err(|e| KokoroError::InvalidInput(format!("Can't decode {}: {}", path, e)))?;
+    let (rate, channels) = (decoder.sample_rate(), decoder.channels());
+    let samples = decoder.map(|s| f32::from(s) / 32768.0).collect();
+    Ok(finish(samples, rate, channels, opts))
+}
+
+fn read_wav<R: Read>(
+    mut reader: hound::WavReader<R>,
+    source: &str,
+    opts: LoadOptions,
+) -> Result<AudioBuffer, KokoroError> {
+    let spec = reader.spec();
+    let samples: Result<Vec<f32>, hound::Error> = match spec.sample_format {
+        hound::SampleFormat::Float => reader.samples::<f32>().collect(),
+        hound::SampleFormat::Int => {
+            let scale = 2f32.powi(i32::from(spec.bits_per_sample) - 1);
+            reader
+                .samples::<i32>()
+                .map(|s| s.map(|s| s as f32 / scale))
+                .collect()
+        }
+    };
+    let samples = samples.map_err(|e| wav_error(source, e))?;
+    Ok(finish(samples, spec.sample_rate, spec.channels, opts))
+}
+
+fn finish(samples: Vec<f32>, rate: u32, channels: u16, opts: LoadOptions) -> AudioBuffer {
+    let audio = if opts.mono && channels > 1 {
+        AudioBuffer::new(audio::downmix(&samples, channels), rate)
+    } else {
+        AudioBuffer::interleaved(samples, rate, channels)
+    };
+    match opts.sample_rate {
+        Some(to) if to != rate => audio.resampled(to),
+        _ => audio,
+    }
+}
+
+// A file that can't be read stays an I/O error; one that isn't a WAV, or is
+// cut short, is bad input
+fn wav_error(source: &str, e: hound::Error) -> KokoroError {
+    match e {
+        hound::Error::IoError(e) if e.kind() != std::io::ErrorKind::UnexpectedEof => {
+            KokoroError::Io(Arc::new(e))
+        }
+        e => KokoroError::InvalidInput(format!("Can't read WAV {}: {}", source, e)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    // A second of a 0.5 peak tone at 48kHz, as the file format given
+    fn fixture(bits: u16, format: hound::SampleFormat, channels: u16) -> Vec<u8> {
+        let spec = hound::WavSpec {
+            channels,
+            sample_rate: 48_000,
+            bits_per_sample: bits,
+            sample_format: format,
+        };
+        let mut cursor = Cursor::new(Vec::new());
+        let mut writer = hound::WavWriter::new(&mut cursor, spec).unwrap();
+        let full_scale = 2f32.powi(i32::from(bits) - 1) - 1.0;
+        for i in 0..48_000 {
+            let sample = (i as f32 * 0.05).sin() * 0.5;
+            for channel in 0..channels {
+                // The second channel is silent, so the mono mix is half level
+                let sample = if channel == 0 { sample } else { 0.0 };
+                match format {
+                    hound::SampleFormat::Float => writer.write_sample(sample).unwrap(),
+                    hound::SampleFormat::Int => writer
+                        .write_sample((sample * full_scale).round() as i32)
+                        .unwrap(),
+                }
+            }
+        }
+        writer.finalize().unwrap();
+        cursor.into_inner()
+    }
+
+    fn peak(samples: &[f32]) -> f32 {
+        samples.iter().fold(0.0, |peak: f32, s| peak.max(s.abs()))
+    }
+
+    #[test]
+    fn every_bit_depth_loads_at_the_same_level() {
+        let depths = [
+            (8, hound::SampleFormat::Int),
+            (16, hound::SampleFormat::Int),
+            (24, hound::SampleFormat::Int),
+            (32, hound::SampleFormat::Float),
+        ];
+        for (bits, format) in depths {
+            let audio = decode_wav_bytes(&fixture(bits, format, 1)).unwrap();
+            assert_eq!((audio.sample_rate(), audio.channels()), (24_000, 1));
+            assert_eq!(audio.len(), 24_000, "{} bit", bits);
+            let samples = audio.into_samples();
+            assert!(
+                (peak(&samples) - 0.5).abs() < 0.02,
+                "{} bit: {}",
+                bits,
+                peak(&samples)
+            );
+
+            let native = decode_wav_bytes_with(
+                &fixture(bits, format, 1),
+                LoadOptions {
+                    mono: false,
+                    sample_rate: None,
+                },
+            )
+            .unwrap();
+            assert_eq!((native.sample_rate(), native.len()), (48_000, 48_000));
+        }
+    }
+
+    #[test]
+    fn stereo_stays_stereo_unless_downmixed() {
+        let stereo = fixture(16, hound::SampleFormat::Int, 2);
+        let kept = decode_wav_bytes(&stereo).unwrap();
+        assert_eq!((kept.channels(), kept.len()), (2, 48_000));
+
+        let mono = decode_wav_bytes_with(&stereo, LoadOptions::model()).unwrap();
+        assert_eq!((mono.channels(), mono.len()), (1, 24_000));
+        assert!((peak(&mono.into_samples()) - 0.25).abs() < 0.01);
+    }
+
+    #[test]
+    fn files_load_and_bad_input_is_reported() {
+        let path = std::env::temp_dir().join(format!("kokoro_load_{}.wav", std::process::id()));
+        std::fs::write(&path, fixture(24, hound::SampleFormat::Int, 1)).unwrap();
+        let audio = load_wav(path.to_str().unwrap()).unwrap();
+        std::fs::remove_file(&path).ok();
+        assert_eq!(audio.len(), 24_000);
+
+        assert!(matches!(
+            load_wav("/nonexistent/speech.wav"),
+            Err(KokoroError::Io(_))
+        ));
+        assert!(matches!(
+            decode_wav_bytes(b"ID3 not a wav file"),
+            Err(KokoroError::InvalidInput(_))
+        ));
+    }
+
+    #[cfg(feature = "playback")]
+    #[test]
+    fn load_audio_decodes_through_rodio() {
+        let path = std::env::temp_dir().join(format!("kokoro_rodio_{}.wav", std::process::id()));
+        std::fs::write(&path, fixture(16, hound::SampleFormat::Int, 2)).unwrap();
+        let audio = load_audio_with(path.to_str().unwrap(), LoadOptions::model()).unwrap();
+        std::fs::remove_file(&path).ok();
+        assert_eq!((audio.channels(), audio.len()), (1, 24_000));
+    }
+}
@@ -2,8 +2,27 @@
 //! Perfect for smart-tree integration and system notifications!
 
 use clap::{Parser, Subcommand};
-use kokoro_tiny::TtsEngine;
+use kokoro_tiny::hotkey::HotkeyBinding;
+use kokoro_tiny::mix;
+use kokoro_tiny::privacy::display_preview;
+use kokoro_tiny::resume::SynthesisPlan;
+use kokoro_tiny::voices::{assignments_path, VoiceAssigner};
+use kokoro_tiny::{
+    display_text, set_redact_text, AudioDuration, AudioFormat, AudioStats, KokoroError, PcmFormat,
+    Preprocess, ProgressStage, ResumeFile, StreamReport, SynthesisProgress, SynthesisStats,
+    SynthesizeOptions, TtsEngine,
+};
+#[cfg(feature = "playback")]
+use kokoro_tiny::{BufferPreset, BusyPolicy, PlaybackOptions, StreamEvent};
 use std::io::{self, BufRead};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+// Set by the interrupt hotkey or Ctrl-C (with --stream); stops after the current chunk
+static STOPPED: AtomicBool = AtomicBool::new(false);
+
+// --output value that means stdout
+const STDOUT: &str = "-";
 
 #[derive(Parser)]
 #[command(name = "kokoro-speak")]
@@ -16,14 +35,25 @@ struct Cli {
     #[arg(short, long, default_value = "0.8")]
     volume: f32,
 
-    /// Voice to use
+    /// Voice to use; auto:<key> gives each key (e.g. auto:build-bot) its own
+    /// voice, remembered across runs
     #[arg(short = 'V', long, default_value = "af_sky")]
     voice: String,
 
-    /// Save to file instead of playing
-    #[arg(short, long)]
+    /// Save to file instead of playing; - writes to stdout
+    #[arg(short, long, global = true)]
     output: Option<String>,
 
+    /// Format of --output: wav, or raw headerless 16-bit little-endian PCM at
+    /// 24kHz mono (the default for -, e.g. `| ffmpeg -f s16le -ar 24000 -ac 1 -i -`)
+    #[arg(long, value_enum, global = true, requires = "output")]
+    format: Option<OutputFormat>,
+
+    /// Mix the speech over this WAV file (e.g. intro music), which ducks
+    /// while the voice speaks
+    #[arg(long, value_name = "FILE")]
+    bed: Option<String>,
+
     /// List available voices
     #[arg(short, long)]
     list_voices: bool,
@@ -40,9 +70,33 @@ struct Cli {
     #[arg(short = 's', long, default_value = "1.0")]
     speed: f32,
 
-    /// Audio gain/amplification (0.5 = quieter, 1.0 = normal, 2.0+ = louder, 4.0+ = maximum)
+    /// Audio gain/amplification (0.5 = quieter, 1.0 = normal, 2.0+ = louder, peaks limited)
     #[arg(short = 'g', long, default_value = "1.5")]
     gain: f32,
+
+    /// Use a small output buffer for lower playback latency (may crackle on slow machines)
+    #[arg(long)]
+    low_latency: bool,
+
+    /// Stop speaking when this key combination is pressed in any window, e.g. ctrl+shift+space
+    /// (needs the hotkey feature and an X11 session)
+    #[arg(long, value_name = "KEYS")]
+    interrupt_hotkey: Option<String>,
+
+    /// Keep the spoken text out of status lines, logs and progress files
+    /// (length and a hash are shown instead; also enabled by KOKORO_REDACT=1)
+    #[arg(long, global = true)]
+    redact: bool,
+
+    /// Never touch the network; without a cached model, read the text with
+    /// the built-in formant voice (robotic, but works air-gapped)
+    #[arg(long, global = true)]
+    no_download: bool,
+
+    /// After synthesis, print where the time went: espeak and model time per
+    /// chunk, tokens, audio length and real-time factor
+    #[arg(long, global = true)]
+    verbose: bool,
 }
 
 #[derive(Subcommand)]
@@ -51,10 +105,27 @@ enum Commands {
     Say {
         /// Text to speak
         text: String,
+
+        /// Start speaking as soon as the first chunk is synthesized
+        #[arg(long)]
+        stream: bool,
     },
 
     /// Read from stdin (perfect for piped input)
-    Pipe,
+    Pipe {
+        /// Start speaking as soon as the first chunk is synthesized
+        #[arg(long)]
+        stream: bool,
+
+        /// Read the input as Markdown: no heading markers, bullets, emphasis
+        /// or link URLs, code blocks left out (e.g. `cat README.md | kokoro-speak pipe --markdown`)
+        #[arg(long)]
+        markdown: bool,
+
+        /// With --markdown, say "Code block omitted." for each code block
+        #[arg(long, requires = "markdown")]
+        announce_code: bool,
+    },
 
     /// System alert with preset messages
     Alert {
@@ -74,6 +145,60 @@ enum Commands {
         #[arg(short, long, default_value = "Context summary:")]
         prefix: String,
     },
+
+    /// Process newline-delimited JSON jobs from stdin, one result line per job
+    Jsonl,
+
+    /// Show the phonemes and tokens synthesis would feed the model, without speaking
+    Phonemes {
+        /// Text to analyze
+        text: String,
+
+        /// Language code passed to the phonemizer
+        #[arg(long, default_value = "en")]
+        lang: String,
+
+        /// Voice the plan is made for
+        #[arg(long, default_value = "af_sky")]
+        voice: String,
+
+        /// Print the report as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Continue a render that failed midway, from its .resume file
+    Resume {
+        /// Resume file written next to the partial output
+        file: String,
+    },
+
+    /// Read a text file aloud, remembering how far playback got
+    Read {
+        /// Document to read
+        file: String,
+
+        /// Continue from where the last run stopped
+        #[arg(long, conflicts_with = "restart")]
+        resume: bool,
+
+        /// Forget saved progress and start from the beginning
+        #[arg(long)]
+        restart: bool,
+
+        /// Synthesize the next chunk while the current one plays
+        #[arg(long)]
+        stream: bool,
+    },
+
+    /// Play a click track and report the buffer size, startup latency and underruns
+    LatencyTest,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, PartialEq)]
+enum OutputFormat {
+    Wav,
+    Raw,
 }
 
 #[derive(clap::ValueEnum, Clone)]
@@ -121,12 +246,25 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     let rt = tokio::runtime::Builder::new_current_thread()
         .enable_all()
         .build()?;
-    let cli = Cli::parse();
+    let mut cli = Cli::parse();
+    if cli.redact {
+        set_redact_text(true);
+    }
 
-    // Initialize TTS engine (uses ~/.cache/k automatically)
-    let mut engine = rt
-        .block_on(TtsEngine::new())
-        .map_err(|e| format!("Failed to initialize TTS: {}", e))?;
+    // Initialize TTS engine (uses the cache directory automatically)
+    let mut engine = if cli.no_download {
+        TtsEngine::without_download()
+    } else {
+        rt.block_on(TtsEngine::new())
+    }
+    .map_err(|e| format!("Failed to initialize TTS: {}", e))?;
+    if cli.no_download && engine.is_fallback() {
+        eprintln!(
+            "⚠️  No cached model in {} and --no-download is set: \
+             using the built-in formant voice (degraded quality)",
+            kokoro_tiny::cache_dir().display()
+        );
+    }
 
     // List voices if requested
     if cli.list_voices {
@@ -137,18 +275,94 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         return Ok(());
     }
 
+    if cli.voice.starts_with("auto:") {
+        cli.voice =
+            with_voice_assignments(voice_pool(&engine), |voices| voices.resolve(&cli.voice))?;
+    }
+
+    // Released when main returns
+    let _interrupt = match &cli.interrupt_hotkey {
+        Some(keys) => HotkeyBinding::system(
+            keys,
+            Arc::new(|| {
+                STOPPED.store(true, Ordering::Relaxed);
+                kokoro_tiny::stop_playback();
+            }),
+        )?,
+        None => None,
+    };
+
     // Get text to speak based on command
-    let (text, voice) = match cli.command {
-        Some(Commands::Say { text }) => (text, cli.voice),
+    let mut preprocess = Preprocess::Plain;
+    let (text, voice, stream) = match cli.command {
+        Some(Commands::Jsonl) => {
+            with_voice_assignments(voice_pool(&engine), |voices| {
+                kokoro_tiny::batch::run_jsonl_with(
+                    &mut engine,
+                    voices,
+                    io::stdin().lock(),
+                    io::stdout().lock(),
+                )
+            })??;
+            return Ok(());
+        }
+
+        Some(Commands::Resume { file }) => return resume_render(&mut engine, &file),
 
-        Some(Commands::Pipe) => {
+        Some(Commands::LatencyTest) => return latency_test(&engine, &cli),
+
+        Some(Commands::Read {
+            file,
+            resume,
+            restart,
+            stream,
+        }) => {
+            let opts = SynthesizeOptions::new()
+                .voice(&cli.voice)
+                .speed(cli.speed)
+                .gain(cli.gain);
+            return read_aloud(
+                &mut engine,
+                &file,
+                opts,
+                cli.volume,
+                resume,
+                restart,
+                stream,
+            );
+        }
+
+        Some(Commands::Phonemes {
+            text,
+            lang,
+            voice,
+            json,
+        }) => return print_phoneme_report(&engine, &text, &lang, &voice, json),
+
+        Some(Commands::Say { text, stream }) => (text, cli.voice, stream),
+
+        Some(Commands::Pipe {
+            stream,
+            markdown,
+            announce_code,
+        }) => {
             // Read from stdin
             let stdin = io::stdin();
             let mut lines = Vec::new();
             for line in stdin.lock().lines() {
                 lines.push(line?);
             }
-            (lines.join(" "), cli.voice)
+            if !markdown {
+                (lines.join(" "), cli.voice, stream)
+            } else {
+                // Markdown needs its line structure
+                preprocess = if announce_code {
+                    Preprocess::MarkdownAnnounceCode
+                } else {
+                    Preprocess::Markdown
+                };
+                (lines.join("\n"), cli.voice, stream)
+            }
         }
 
         Some(Commands::Alert {
@@ -157,13 +371,13 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         }) => {
             let text = message.unwrap_or_else(|| alert_type.default_message().to_string());
             let voice = alert_type.voice().to_string();
-            (text, voice)
+            (text, voice, false)
         }
 
         Some(Commands::Context { text, prefix }) => {
             let full_text = format!("{} {}", prefix, text);
             // Use a clear, professional voice for context summaries
-            (full_text, "bf_isabella".to_string())
+            (full_text, "bf_isabella".to_string(), false)
         }
 
         None => {
@@ -173,7 +387,11 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                 eprintln!("\nQuick examples:");
                 eprintln!("  kokoro-speak say \"Hello world!\"");
                 eprintln!("  echo \"Build complete\" | kokoro-speak pipe");
+                eprintln!("  cat notes.txt | kokoro-speak pipe --stream");
+                eprintln!("  cat README.md | kokoro-speak pipe --markdown");
                 eprintln!("  kokoro-speak alert success");
+                eprintln!("  kokoro-speak jsonl < jobs.jsonl");
+                eprintln!("  kokoro-speak phonemes \"Read the README\" --json");
                 eprintln!(
                     "  kokoro-speak context \"Found 5 TypeScript files with 200 lines total\""
                 );
@@ -185,21 +403,100 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             for line in stdin.lock().lines() {
                 lines.push(line?);
             }
-            (lines.join(" "), cli.voice)
+            (lines.join(" "), cli.voice, false)
         }
     };
 
     // Synthesize speech with speed and gain control
-    let audio = engine
-        .synthesize_with_options(&text, Some(&voice), cli.speed, cli.gain, Some("en"))
-        .map_err(|e| format!("Synthesis failed: {}", e))?;
+    let opts = SynthesizeOptions::new()
+        .voice(&voice)
+        .speed(cli.speed)
+        .gain(cli.gain)
+        .preprocess(preprocess);
+    if stream {
+        if cli.output.is_some() {
+            return Err("--stream plays while synthesizing; drop it to save to --output".into());
+        }
+        if cli.bed.is_some() {
+            return Err("--bed mixes the finished speech; drop --stream to use it".into());
+        }
+    }
+    // Text long enough to be chunked plays while it renders, unless the
+    // whole render is needed for a file or a bed
+    let long = cli.output.is_none()
+        && cli.bed.is_none()
+        && SynthesisPlan::new(&text, &opts).chunks.len() > 1;
+    if stream || long {
+        let duck = cli.duck.then_some(cli.duck_level);
+        return speak_streaming(
+            &engine,
+            &text,
+            &voice,
+            opts,
+            cli.volume,
+            cli.low_latency,
+            duck,
+        );
+    }
+    #[cfg(feature = "playback")]
+    let model_speed = engine.model_speed(&opts);
+    let (audio, mut stats) = kokoro_tiny::stats::measure(|| {
+        engine.synthesize_resumable_with_progress(&text, opts, progress_bar())
+    });
+    let audio = match audio {
+        Ok(audio) => audio,
+        Err(partial) => {
+            // End the progress bar's line before reporting
+            if partial.plan.chunks.len() > 1 && atty::is(atty::Stream::Stderr) {
+                eprintln!();
+            }
+            // Keep what was rendered so a long file doesn't start over (a
+            // stream already gone to stdout can't be resumed)
+            let to_file = cli.output.as_deref().filter(|path| *path != STDOUT);
+            if let (Some(output_path), false) = (to_file, partial.completed.is_empty()) {
+                let resume_path = engine.save_partial(&partial, output_path)?;
+                eprintln!("💾 Partial audio saved to: {}", output_path);
+                eprintln!("   Continue with: kokoro-speak resume {}", resume_path);
+            }
+            return Err(format!("Synthesis failed: {}", partial).into());
+        }
+    };
+    if cli.verbose {
+        stats.samples = audio.len();
+        print_stats(&stats);
+    }
+    let audio = match &cli.bed {
+        Some(path) => {
+            let bed = mix::load_bed(path).map_err(|e| format!("Failed to load bed: {}", e))?;
+            mix::mix_with_bed(
+                &audio,
+                &bed,
+                mix::DEFAULT_BED_GAIN_DB,
+                mix::DEFAULT_DUCK_DB,
+                mix::DEFAULT_ATTACK_MS,
+                mix::DEFAULT_RELEASE_MS,
+            )
+        }
+        None => audio,
+    };
+    if cli.verbose {
+        eprintln!("🔈 Levels: {}", AudioStats::measure(&audio, 24_000));
+    }
 
     // Output to file or play
     if let Some(output_path) = cli.output {
-        engine
-            .save_wav(&output_path, &audio)
+        let to_stdout = output_path == STDOUT;
+        let format = cli.format.unwrap_or(if to_stdout {
+            OutputFormat::Raw
+        } else {
+            OutputFormat::Wav
+        });
+        write_output(&engine, &output_path, format, &audio)
             .map_err(|e| format!("Failed to save audio: {}", e))?;
-        println!("💾 Saved to: {}", output_path);
+        // Stdout carries the audio, so nothing else may go there
+        if !to_stdout {
+            println!("💾 Saved to: {}", output_path);
+        }
     } else {
         #[cfg(feature = "playback")]
         {
@@ -216,14 +513,11 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             };
 
             println!(
-                "🔊 Speaking: \"{}\" [voice: {}, speed: {}x, volume: {}{}{}]",
-                if text.len() > 50 {
-                    format!("{}...", &text[..50])
-                } else {
-                    text.clone()
-                },
+                "🔊 Speaking: \"{}\" [voice: {}, speed: {}x (model {:.2}), volume: {}{}{}]",
+                display_preview(&text, 50),
                 voice,
                 cli.speed,
+                model_speed,
                 cli.volume,
                 gain_info,
                 ducking_info
@@ -235,8 +529,14 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                     .play_with_ducking(&audio, cli.volume, true, cli.duck_level)
                     .map_err(|e| format!("Playback with ducking failed: {}", e))?;
             } else {
+                let mut opts = PlaybackOptions::new()
+                    .volume(cli.volume)
+                    .on_busy(BusyPolicy::Queue);
+                if cli.low_latency {
+                    opts = opts.buffer(BufferPreset::LowLatency);
+                }
                 engine
-                    .play(&audio, cli.volume)
+                    .play_with_options(&audio, &opts)
                     .map_err(|e| format!("Playback failed: {}", e))?;
             }
         }
@@ -257,3 +557,432 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     Ok(())
 }
+
+// Voices to hand out to auto:<key>; none while the model is still downloading
+fn voice_pool(engine: &TtsEngine) -> Vec<String> {
+    engine
+        .voices()
+        .into_iter()
+        .filter(|voice| voice != "fallback")
+        .collect()
+}
+
+// Run `f` with the remembered source voices and save what it assigned
+fn with_voice_assignments<T>(
+    pool: Vec<String>,
+    f: impl FnOnce(&mut VoiceAssigner) -> T,
+) -> Result<T, String> {
+    let path = assignments_path();
+    let (mut voices, remember) = match VoiceAssigner::load(&path)? {
+        Some(voices) => (voices, true),
+        // Don't pin every key to the default voice before the voices exist
+        None => (VoiceAssigner::new(pool.clone()), !pool.is_empty()),
+    };
+
+    let before = voices.clone();
+    let result = f(&mut voices);
+    if remember && voices != before {
+        voices.persist(&path)?;
+    }
+    Ok(result)
+}
+
+/// Play a click track and print what the output device negotiated
+#[cfg(feature = "playback")]
+fn latency_test(engine: &TtsEngine, cli: &Cli) -> Result<(), Box<dyn std::error::Error>> {
+    let mut opts = PlaybackOptions::new().volume(cli.volume);
+    if cli.low_latency {
+        opts = opts.buffer(BufferPreset::LowLatency);
+    }
+
+    println!("🎧 Playing a 5 second click track...");
+    let info = engine.latency_test(&opts)?;
+    println!("  device:          {}", info.device);
+    println!("  sample rate:     {} Hz", info.sample_rate);
+    println!(
+        "  buffer:          {} frames ({:.1} ms)",
+        info.buffer_frames,
+        AudioDuration::from_samples(
+            usize::try_from(info.buffer_frames).unwrap_or(usize::MAX),
+            info.sample_rate
+        )
+        .as_secs_f64()
+            * 1000.0
+    );
+    println!("  callback size:   {} frames", info.callback_frames);
+    println!("  startup latency: {:.1} ms", info.startup_latency.as_secs_f64() * 1000.0);
+    println!("  underruns:       {}", info.underruns);
+    if info.underruns > 0 {
+        println!("⚠️ Underruns detected - try a larger buffer (drop --low-latency)");
+    }
+    Ok(())
+}
+
+#[cfg(not(feature = "playback"))]
+fn latency_test(_engine: &TtsEngine, _cli: &Cli) -> Result<(), Box<dyn std::error::Error>> {
+    Err("Playback feature not enabled. Rebuild with --features playback".into())
+}
+
+fn read_aloud(
+    engine: &mut TtsEngine,
+    file: &str,
+    opts: SynthesizeOptions,
+    volume: f32,
+    resume: bool,
+    restart: bool,
+    stream: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    use kokoro_tiny::reading::{self, ReadProgress, ResumePoint};
+
+    let text =
+        std::fs::read_to_string(file).map_err(|e| format!("Failed to read {}: {}", file, e))?;
+    let state = reading::progress_path(std::path::Path::new(file));
+    if restart {
+        ReadProgress::clear(&state)?;
+    }
+
+    let mut offset = 0;
+    if resume {
+        match ReadProgress::load(&state)? {
+            None => println!(
+                "📖 No saved progress for {}, starting at the beginning",
+                file
+            ),
+            Some(progress) => match progress.locate(&text) {
+                ResumePoint::Unchanged(at) => {
+                    println!("⏯️  Resuming after chunk {}", progress.last_chunk + 1);
+                    offset = at;
+                }
+                ResumePoint::Reanchored(at) => {
+                    // Redacted runs store a hash instead of the text
+                    let anchor = if progress.last_chunk_text.is_empty() {
+                        "the last chunk heard".to_string()
+                    } else {
+                        format!("{:?}", display_text(&progress.last_chunk_text))
+                    };
+                    eprintln!(
+                        "⚠️ {} changed since the last run; resuming after {}",
+                        file, anchor
+                    );
+                    offset = at;
+                }
+                ResumePoint::Lost => {
+                    eprintln!(
+                        "⚠️ {} changed since the last run; starting at the beginning",
+                        file
+                    );
+                }
+            },
+        }
+    }
+
+    let segments = reading::segment_document(&text).len();
+    if stream {
+        watch_ctrl_c()?;
+        let engine: &TtsEngine = engine;
+        let report = reading::read_document_streaming(
+            engine,
+            &text,
+            &opts,
+            &state,
+            offset,
+            &STOPPED,
+            |segment, audio| {
+                println!(
+                    "🔊 [{}%] {}",
+                    segment.end * 100 / text.len().max(1),
+                    display_text(&segment.text)
+                );
+                play_segment(engine, audio, volume)
+            },
+        )?;
+        print_stream_report(&report);
+        if report.interrupted {
+            println!("⏸️  Continue with: kokoro-speak read --resume {}", file);
+        } else {
+            println!("✅ Finished reading {} ({} chunks)", file, segments);
+        }
+        return Ok(());
+    }
+
+    reading::read_document(
+        engine,
+        &text,
+        &opts,
+        &state,
+        offset,
+        |engine, segment, audio| {
+            println!(
+                "🔊 [{}%] {}",
+                segment.end * 100 / text.len().max(1),
+                display_text(&segment.text)
+            );
+            play_segment(engine, audio, volume)
+        },
+    )?;
+    println!("✅ Finished reading {} ({} chunks)", file, segments);
+    Ok(())
+}
+
+#[cfg(feature = "playback")]
+fn play_segment(engine: &TtsEngine, audio: &[f32], volume: f32) -> Result<(), String> {
+    let opts = PlaybackOptions::new()
+        .volume(volume)
+        .on_busy(BusyPolicy::Queue);
+    engine.play_with_options(audio, &opts)?;
+    // Not Ok, so the cut-off chunk isn't saved as read and --resume replays it
+    if STOPPED.load(Ordering::Relaxed) {
+        return Err("Stopped (continue with --resume)".to_string());
+    }
+    Ok(())
+}
+
+#[cfg(not(feature = "playback"))]
+fn play_segment(_engine: &TtsEngine, _audio: &[f32], _volume: f32) -> Result<(), String> {
+    Err("Playback feature not enabled. Rebuild with --features playback".to_string())
+}
+
+/// Speak `text` chunk by chunk, starting as soon as the first chunk is ready
+#[cfg(feature = "playback")]
+fn speak_streaming(
+    engine: &TtsEngine,
+    text: &str,
+    voice: &str,
+    opts: SynthesizeOptions,
+    volume: f32,
+    low_latency: bool,
+    duck: Option<f32>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    watch_ctrl_c()?;
+    println!(
+        "🔊 Streaming: \"{}\" [voice: {}, speed: {}x (model {:.2}), volume: {}]",
+        display_preview(text, 50),
+        voice,
+        opts.speed,
+        engine.model_speed(&opts),
+        volume
+    );
+
+    // One stream for every chunk, so they play without gaps
+    let mut speech = engine
+        .speak_long(text, opts, volume)?
+        .stop_on(&STOPPED)
+        .on_event(print_stream_event);
+    if low_latency {
+        speech = speech.buffer(BufferPreset::LowLatency);
+    }
+
+    // One duck for the whole stream; dropped on every way out, Ctrl-C included
+    #[cfg(feature = "ducking")]
+    let _duck = duck.map(kokoro_tiny::duck_audio).transpose()?;
+    #[cfg(not(feature = "ducking"))]
+    let _ = duck;
+
+    let report = speech.wait()?;
+    print_stream_report(&report);
+    Ok(())
+}
+
+#[cfg(not(feature = "playback"))]
+fn speak_streaming(
+    _engine: &TtsEngine,
+    _text: &str,
+    _voice: &str,
+    _opts: SynthesizeOptions,
+    _volume: f32,
+    _low_latency: bool,
+    _duck: Option<f32>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    Err("Playback feature not enabled. Rebuild with --features playback".into())
+}
+
+#[cfg(feature = "playback")]
+fn print_stream_event(event: StreamEvent) {
+    match event {
+        StreamEvent::Synthesized { index, count } => {
+            eprintln!("   ⚙️  Chunk {}/{} synthesized", index + 1, count)
+        }
+        StreamEvent::Playing { index, count } => {
+            eprintln!("   🔊 Playing chunk {}/{}", index + 1, count)
+        }
+        StreamEvent::Played { .. } => {}
+    }
+}
+
+// `audio` to a file, or to stdout for `-`, as WAV or raw s16le
+fn write_output(
+    engine: &TtsEngine,
+    path: &str,
+    format: OutputFormat,
+    audio: &[f32],
+) -> Result<(), KokoroError> {
+    let format = match format {
+        OutputFormat::Wav => AudioFormat::Wav,
+        OutputFormat::Raw => AudioFormat::Pcm(PcmFormat::S16le),
+    };
+    if path == STDOUT {
+        engine.encode(audio, format, io::stdout().lock())
+    } else {
+        engine.encode(audio, format, io::BufWriter::new(std::fs::File::create(path)?))
+    }
+}
+
+// Progress of a long render on stderr: a bar redrawn in place on a terminal,
+// one line per chunk otherwise; single-chunk texts show nothing
+fn progress_bar() -> impl FnMut(SynthesisProgress) {
+    const WIDTH: usize = 30;
+    let tty = atty::is(atty::Stream::Stderr);
+    move |progress| {
+        if progress.total_chunks < 2 {
+            return;
+        }
+        if !tty {
+            if progress.stage == ProgressStage::ChunkStarted {
+                eprintln!(
+                    "   → Chunk {}/{}",
+                    progress.chunk + 1,
+                    progress.total_chunks
+                );
+            }
+            return;
+        }
+        let done = match progress.stage {
+            ProgressStage::ChunkFinished => progress.chunk + 1,
+            _ => progress.chunk,
+        };
+        let filled = ((progress.fraction() * WIDTH as f32).round() as usize).min(WIDTH);
+        eprint!(
+            "\r📚 [{}{}] {}/{} chunks, {:.1}s of audio in {:.1}s",
+            "█".repeat(filled),
+            "░".repeat(WIDTH - filled),
+            done,
+            progress.total_chunks,
+            AudioDuration::from_samples(progress.samples, 24_000).as_secs_f64(),
+            progress.elapsed.as_secs_f64()
+        );
+        if progress.stage == ProgressStage::Completed {
+            eprintln!();
+        }
+    }
+}
+
+fn print_stream_report(report: &StreamReport) {
+    if report.interrupted {
+        println!("⏹️  Stopped after {} chunk(s)", report.played);
+    }
+    match report.time_to_first_audio {
+        Some(first) => println!(
+            "⏱️  Time to first audio: {:.0} ms ({} chunk(s) played in {:.1} s)",
+            first.as_secs_f64() * 1000.0,
+            report.played,
+            report.elapsed.as_secs_f64()
+        ),
+        None => println!("⏱️  Nothing was played"),
+    }
+}
+
+// --verbose: the timings of a render, on stderr so they never mix with output
+fn print_stats(stats: &SynthesisStats) {
+    let ms = |d: std::time::Duration| d.as_secs_f64() * 1000.0;
+    if stats.cached {
+        eprintln!("⏱️  Served from the result cache");
+    }
+    for (i, chunk) in stats.chunks.iter().enumerate() {
+        eprintln!(
+            "⏱️  Chunk {}: {} tokens, phonemize {:.1} ms, inference {:.1} ms, {} samples",
+            i + 1,
+            chunk.tokens,
+            ms(chunk.phonemize),
+            ms(chunk.inference),
+            chunk.samples
+        );
+    }
+    eprintln!(
+        "⏱️  Total: {} tokens, phonemize {:.1} ms, inference {:.1} ms, wall {:.1} ms",
+        stats.tokens(),
+        ms(stats.phonemize()),
+        ms(stats.inference()),
+        ms(stats.total)
+    );
+    eprintln!(
+        "⏱️  Audio: {} samples ({:.2} s), real-time factor {:.3}",
+        stats.samples,
+        stats.audio_duration().as_secs_f64(),
+        stats.real_time_factor()
+    );
+}
+
+// Ctrl-C stops streaming the way the interrupt hotkey does, so ducking is
+// restored and read progress saved on the way out; a second Ctrl-C exits
+fn watch_ctrl_c() -> Result<(), String> {
+    ctrlc::set_handler(|| {
+        if STOPPED.swap(true, Ordering::Relaxed) {
+            std::process::exit(130);
+        }
+        kokoro_tiny::stop_playback();
+    })
+    .map_err(|e| format!("Failed to install the Ctrl-C handler: {}", e))
+}
+
+/// Dry run of the synthesis pipeline for debugging pronunciation
+fn print_phoneme_report(
+    engine: &TtsEngine,
+    text: &str,
+    lang: &str,
+    voice: &str,
+    json: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let opts = SynthesizeOptions::new().voice(voice).lang(lang);
+    let mut report = engine.phoneme_report(text, &opts)?;
+    for chunk in &mut report {
+        chunk.text = display_text(&chunk.text).into_owned();
+        chunk.normalized = display_text(&chunk.normalized).into_owned();
+    }
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return Ok(());
+    }
+
+    for chunk in &report {
+        println!("🔤 Chunk {}/{}", chunk.index + 1, report.len());
+        println!("   text:     {}", chunk.normalized);
+        match &chunk.phonemes {
+            Some(phonemes) => {
+                println!("   phonemes: {}", phonemes);
+                println!("   tokens:   {:?}", chunk.tokens);
+            }
+            None => println!("   (nothing speakable, {}ms pause)", chunk.pause_ms.unwrap_or(0)),
+        }
+        if !chunk.unknown_chars.is_empty() {
+            println!("   ⚠️  unknown to the model: {:?}", chunk.unknown_chars);
+        }
+    }
+    Ok(())
+}
+
+/// Finish a partial render and overwrite its output file
+fn resume_render(engine: &mut TtsEngine, resume_path: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let resume = ResumeFile::load(resume_path)?;
+    let completed = resume.load_audio()?;
+
+    println!(
+        "⏯️  Resuming {} at chunk {}/{}",
+        resume.audio_path,
+        resume.next_chunk + 1,
+        resume.plan.chunks.len()
+    );
+
+    match engine.resume_synthesis(&resume.plan, resume.next_chunk, completed) {
+        Ok(audio) => {
+            engine.save_wav(&resume.audio_path, &audio)?;
+            std::fs::remove_file(resume_path)?;
+            println!("💾 Saved to: {}", resume.audio_path);
+            Ok(())
+        }
+        Err(partial) => {
+            engine.save_partial(&partial, &resume.audio_path)?;
+            Err(format!("Synthesis failed again: {}", partial).into())
+        }
+    }
+}
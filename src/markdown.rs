@@ -0,0 +1,486 @@
+//! Reading Markdown aloud
+//!
+//! With `Preprocess::Markdown` the markup is taken out before the text is
+//! chunked, so a README reads like prose:
+//!
+//! - headings lose their `#` and end in a full stop; list items lose their
+//!   bullet or number, table rows are read cell by cell
+//! - emphasis (`**bold**`, `_italic_`, `~~struck~~`), inline code backticks,
+//!   blockquote markers, HTML tags and horizontal rules are dropped
+//! - links and images read their text, not the URL; reference definitions
+//!   are dropped
+//! - fenced code blocks are left out, or announced with
+//!   `Preprocess::MarkdownAnnounceCode`
+//!
+//! Blocks come out separated by blank lines, so paragraph gaps (see
+//! `pacing`) fall between them.
+
+use serde::{Deserialize, Serialize};
+
+/// What is done to the text before it is chunked
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Preprocess {
+    /// Read the text as written
+    #[default]
+    Plain,
+    /// Strip Markdown syntax, leaving code blocks out
+    Markdown,
+    /// Strip Markdown syntax, saying "Code block omitted." for code blocks
+    MarkdownAnnounceCode,
+}
+
+impl Preprocess {
+    /// `text` as it will be read
+    ///
+    /// # Example
+    /// ```
+    /// use kokoro_tiny::Preprocess;
+    ///
+    /// let text = "## Install\n\nRun `cargo add kokoro-tiny`, see [the docs](https://docs.rs).";
+    /// assert_eq!(
+    ///     Preprocess::Markdown.apply(text),
+    ///     "Install.\n\nRun cargo add kokoro-tiny, see the docs."
+    /// );
+    /// ```
+    pub fn apply(self, text: &str) -> String {
+        match self {
+            Preprocess::Plain => text.to_string(),
+            Preprocess::Markdown => strip_markdown(text, false),
+            Preprocess::MarkdownAnnounceCode => strip_markdown(text, true),
+        }
+    }
+}
+
+const CODE_OMITTED: &str = "Code block omitted.";
+
+// Blocks of spoken text, one per paragraph, heading or list
+#[derive(Default)]
+struct Blocks {
+    blocks: Vec<String>,
+    // The last block is a heading, list or table still missing its full stop
+    unterminated: bool,
+    // The next line continues the last block
+    open: bool,
+}
+
+impl Blocks {
+    // End the current block
+    fn close(&mut self) {
+        if let (true, Some(last)) = (self.unterminated, self.blocks.last_mut()) {
+            full_stop(last);
+        }
+        self.unterminated = false;
+        self.open = false;
+    }
+
+    fn push_line(&mut self, line: &str, separator: &str) {
+        match self.blocks.last_mut() {
+            Some(last) if self.open => {
+                last.push_str(separator);
+                last.push_str(line);
+            }
+            _ => self.blocks.push(line.to_string()),
+        }
+        self.open = true;
+    }
+
+    // A line that is a sentence of its own: list items and table rows stay in
+    // one block, each with a full stop
+    fn push_item(&mut self, line: &str) {
+        if let (true, Some(last)) = (self.unterminated, self.blocks.last_mut()) {
+            full_stop(last);
+        }
+        self.push_line(line, "\n");
+        self.unterminated = true;
+    }
+}
+
+fn strip_markdown(text: &str, announce_code: bool) -> String {
+    let mut blocks = Blocks::default();
+    // Marker char and length of the open code fence
+    let mut fence: Option<(char, usize)> = None;
+    let mut in_list = false;
+
+    for line in text.lines() {
+        let trimmed = line.trim();
+        if let Some((marker, len)) = fence {
+            if trimmed.chars().take_while(|&c| c == marker).count() >= len
+                && trimmed.trim_start_matches(marker).is_empty()
+            {
+                fence = None;
+            }
+            continue;
+        }
+        if let Some(opened) = fence_marker(trimmed) {
+            blocks.close();
+            in_list = false;
+            fence = Some(opened);
+            if announce_code {
+                blocks.blocks.push(CODE_OMITTED.to_string());
+            }
+            continue;
+        }
+        if trimmed.is_empty() {
+            blocks.close();
+            in_list = false;
+            continue;
+        }
+
+        // Underlines make the line above a heading
+        if trimmed.chars().all(|c| c == '=' || c == '-') && blocks.open && !in_list {
+            blocks.unterminated = true;
+            blocks.close();
+            continue;
+        }
+        if is_table_separator(trimmed) {
+            continue;
+        }
+        if is_rule(trimmed) || is_reference(trimmed) {
+            blocks.close();
+            continue;
+        }
+
+        let quoted = trimmed.trim_start_matches(|c: char| c == '>' || c.is_whitespace());
+        if let Some(heading) = heading(quoted) {
+            blocks.close();
+            in_list = false;
+            blocks.push_line(&inline(heading), " ");
+            blocks.unterminated = true;
+            blocks.close();
+        } else if let Some(item) = list_item(quoted) {
+            if !in_list {
+                blocks.close();
+            }
+            in_list = true;
+            blocks.push_item(&inline(item));
+        } else if quoted.starts_with('|') {
+            let cells: Vec<String> = quoted
+                .trim_matches('|')
+                .split('|')
+                .map(|cell| inline(cell.trim()))
+                .filter(|cell| !cell.is_empty())
+                .collect();
+            if !in_list {
+                blocks.close();
+            }
+            in_list = true;
+            blocks.push_item(&cells.join(", "));
+        } else {
+            let spoken = inline(quoted);
+            if !spoken.is_empty() {
+                blocks.push_line(&spoken, " ");
+            }
+        }
+    }
+    blocks.close();
+
+    blocks
+        .blocks
+        .into_iter()
+        .filter(|block| !block.trim().is_empty())
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+// "```rust" or "~~~" opening a code block
+fn fence_marker(line: &str) -> Option<(char, usize)> {
+    let marker = line.chars().next().filter(|&c| c == '`' || c == '~')?;
+    let len = line.chars().take_while(|&c| c == marker).count();
+    (len >= 3).then_some((marker, len))
+}
+
+// "---", "***", "_ _ _"
+fn is_rule(line: &str) -> bool {
+    let marks: Vec<char> = line.chars().filter(|c| !c.is_whitespace()).collect();
+    marks.len() >= 3 && ['-', '*', '_'].contains(&marks[0]) && marks.iter().all(|&c| c == marks[0])
+}
+
+// "[docs]: https://docs.rs"
+fn is_reference(line: &str) -> bool {
+    line.starts_with('[')
+        && line
+            .find("]:")
+            .is_some_and(|end| !line[1..end].contains(']'))
+}
+
+// "|---|:---:|"
+fn is_table_separator(line: &str) -> bool {
+    line.contains('|') && line.contains('-') && line.chars().all(|c| "|-: ".contains(c))
+}
+
+fn heading(line: &str) -> Option<&str> {
+    let level = line.chars().take_while(|&c| c == '#').count();
+    if !(1..=6).contains(&level) {
+        return None;
+    }
+    let rest = &line[level..];
+    if !rest.is_empty() && !rest.starts_with(char::is_whitespace) {
+        return None;
+    }
+    Some(rest.trim().trim_end_matches('#').trim_end())
+}
+
+// "- item", "* item", "+ item", "1. item", "2) item", with "[x] " dropped
+fn list_item(line: &str) -> Option<&str> {
+    let digits = line.chars().take_while(char::is_ascii_digit).count();
+    let after_marker = if digits > 0 {
+        line[digits..].strip_prefix(['.', ')'])?
+    } else {
+        line.strip_prefix(['-', '*', '+'])?
+    };
+    if !after_marker.starts_with(char::is_whitespace) {
+        return None;
+    }
+    let item = after_marker.trim_start();
+    Some(
+        ["[ ] ", "[x] ", "[X] "]
+            .iter()
+            .find_map(|task| item.strip_prefix(task))
+            .unwrap_or(item),
+    )
+}
+
+// Inline markup of one line: links, code spans, HTML and emphasis
+fn inline(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut out = String::with_capacity(text.len());
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            '\\' if chars.get(i + 1).is_some_and(char::is_ascii_punctuation) => {
+                out.push(chars[i + 1]);
+                i += 2;
+            }
+            '`' => {
+                let ticks = chars[i..].iter().take_while(|&&c| c == '`').count();
+                let content = i + ticks;
+                let close = (content..chars.len())
+                    .find(|&j| chars[j..].iter().take_while(|&&c| c == '`').count() == ticks);
+                match close {
+                    Some(close) => {
+                        out.extend(&chars[content..close]);
+                        i = close + ticks;
+                    }
+                    None => {
+                        out.extend(&chars[i..content]);
+                        i = content;
+                    }
+                }
+            }
+            '!' if chars.get(i + 1) == Some(&'[') => match link(&chars, i + 1) {
+                Some((label, end)) => {
+                    out.push_str(&inline(&label));
+                    i = end;
+                }
+                None => {
+                    out.push(c);
+                    i += 1;
+                }
+            },
+            '[' => match link(&chars, i) {
+                Some((label, end)) => {
+                    out.push_str(&inline(&label));
+                    i = end;
+                }
+                None => {
+                    out.push(c);
+                    i += 1;
+                }
+            },
+            '<' => match chars[i..].iter().position(|&c| c == '>') {
+                Some(len) if is_tag(&chars[i + 1..i + len]) => {
+                    let inside: String = chars[i + 1..i + len].iter().collect();
+                    // Autolinks keep their address
+                    if inside.contains("://") || inside.contains('@') {
+                        out.push_str(&inside);
+                    } else {
+                        out.push(' ');
+                    }
+                    i += len + 1;
+                }
+                _ => {
+                    out.push(c);
+                    i += 1;
+                }
+            },
+            '*' | '_' | '~' if is_emphasis(&chars, i) => i += 1,
+            _ => {
+                out.push(c);
+                i += 1;
+            }
+        }
+    }
+    out.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+// "[label](url)", "[label][ref]" or "[label][]" starting at `start`: the
+// label and where the link ends
+fn link(chars: &[char], start: usize) -> Option<(String, usize)> {
+    let mut depth = 0;
+    let mut close = None;
+    for (j, &c) in chars.iter().enumerate().skip(start) {
+        match c {
+            '[' => depth += 1,
+            ']' => {
+                depth -= 1;
+                if depth == 0 {
+                    close = Some(j);
+                    break;
+                }
+            }
+            _ => {}
+        }
+    }
+    let close = close?;
+    let label: String = chars[start + 1..close].iter().collect();
+    let end_char = match chars.get(close + 1) {
+        Some('(') => ')',
+        Some('[') => ']',
+        _ => return None,
+    };
+    let end = (close + 2..chars.len()).find(|&j| chars[j] == end_char)?;
+    Some((label, end + 1))
+}
+
+// "a href=...", "/p", "br/", "!-- comment --", "https://example.com"
+fn is_tag(inside: &[char]) -> bool {
+    match inside.first() {
+        Some(c) if c.is_ascii_alphabetic() => !inside.contains(&' ') || inside.contains(&'='),
+        Some('/') | Some('!') => true,
+        _ => false,
+    }
+}
+
+// A `*`, `_` or `~` that opens or closes emphasis rather than being text:
+// touching a word on one side only ("5 * 3", "snake_case" and "~5" stay)
+fn is_emphasis(chars: &[char], i: usize) -> bool {
+    let run_start = (0..=i)
+        .rev()
+        .take_while(|&j| chars[j] == chars[i])
+        .last()
+        .unwrap_or(i);
+    let run_end = (i..chars.len())
+        .take_while(|&j| chars[j] == chars[i])
+        .last()
+        .unwrap_or(i)
+        + 1;
+    if chars[i] == '~' && run_end - run_start < 2 {
+        return false;
+    }
+    let before = run_start.checked_sub(1).map(|j| chars[j]);
+    let after = chars.get(run_end).copied();
+    let word = |c: Option<char>| c.is_some_and(|c| c.is_alphanumeric());
+    let space = |c: Option<char>| c.is_none_or(char::is_whitespace);
+    let opens = !word(before) && !space(after);
+    let closes = !space(before) && !word(after);
+    opens || closes
+}
+
+fn full_stop(line: &mut String) {
+    let end = line.trim_end();
+    if !end.is_empty() && !end.ends_with(['.', '!', '?', ':', ';', '…']) {
+        line.truncate(end.len());
+        line.push('.');
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const README: &str = r#"# kokoro-tiny
+
+[![Crates.io](https://img.shields.io/crates/v/kokoro-tiny.svg)](https://crates.io/crates/kokoro-tiny)
+
+Minimal, **embeddable** TTS using the *Kokoro* model. See the
+[API docs][docs] or <https://docs.rs/kokoro-tiny>.
+
+## ✨ Features
+
+- 🚀 **Fast**: runs on the `CPU`
+- [x] Streaming playback
+* Voice mixing, e.g. `af_sky.4+af_bella.6`
+
+1. Install espeak-ng
+2) Run `cargo add kokoro-tiny`
+
+> **Note:** needs ~~Python~~ nothing else.
+
+Installation
+------------
+
+```rust
+let tts = TtsEngine::new().await?;
+```
+
+| Feature | Default |
+|---------|:-------:|
+| `playback` | no |
+
+***
+
+<p align="center">Made with care</p>
+
+Use snake_case names and 5 * 3 voices.
+
+[docs]: https://docs.rs/kokoro-tiny
+"#;
+
+    #[test]
+    fn readme_reads_as_prose() {
+        assert_eq!(
+            Preprocess::Markdown.apply(README),
+            "kokoro-tiny.\n\n\
+             Crates.io\n\n\
+             Minimal, embeddable TTS using the Kokoro model. See the API docs or \
+             https://docs.rs/kokoro-tiny.\n\n\
+             ✨ Features.\n\n\
+             🚀 Fast: runs on the CPU.\n\
+             Streaming playback.\n\
+             Voice mixing, e.g. af_sky.4+af_bella.6.\n\n\
+             Install espeak-ng.\n\
+             Run cargo add kokoro-tiny.\n\n\
+             Note: needs Python nothing else.\n\n\
+             Installation.\n\n\
+             Feature, Default.\n\
+             playback, no.\n\n\
+             Made with care\n\n\
+             Use snake_case names and 5 * 3 voices."
+        );
+    }
+
+    #[test]
+    fn code_blocks_are_dropped_or_announced() {
+        let text = "Build it:\n\n~~~~sh\ncargo build\n~~~\n```\n~~~~\nThen run it.";
+        assert_eq!(
+            Preprocess::Markdown.apply(text),
+            "Build it:\n\nThen run it."
+        );
+        assert_eq!(
+            Preprocess::MarkdownAnnounceCode.apply(text),
+            "Build it:\n\nCode block omitted.\n\nThen run it."
+        );
+        assert_eq!(Preprocess::Plain.apply(text), text);
+    }
+
+    #[test]
+    fn inline_markup_is_dropped() {
+        let cases = [
+            ("__bold__ and _it_", "bold and it"),
+            ("***both***", "both"),
+            ("a `` code with ` tick `` span", "a code with ` tick span"),
+            ("![diagram](img.png \"Title\") below", "diagram below"),
+            ("see [*the* guide](./GUIDE.md)!", "see the guide!"),
+            ("[not a link] here", "[not a link] here"),
+            ("escaped \\*stars\\* stay", "escaped *stars* stay"),
+            ("a<br/>b <!-- note --> c", "a b c"),
+            ("x < y and y > z", "x < y and y > z"),
+            ("file_name_here", "file_name_here"),
+        ];
+        for (text, expected) in cases {
+            assert_eq!(inline(text), expected, "{}", text);
+        }
+    }
+}
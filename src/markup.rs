@@ -0,0 +1,331 @@
+//! Inline pause and emphasis markup for plain text
+//!
+//! A lightweight alternative to SSML, read when `SynthesizeOptions::markup`
+//! is on (off by default, so brackets and asterisks in ordinary text are left
+//! alone):
+//!
+//! | Marker | Effect |
+//! |---|---|
+//! | `[pause]` | 250 ms of silence |
+//! | `[pause 300ms]`, `[pause 1.5s]` | Silence of that length, at most 10 s |
+//! | `*word*` | Mild emphasis on the clause around it |
+//!
+//! Emphasis is a touch louder and slower, applied to the whole clause (up to
+//! the nearest `,;:.!?`) since the model can't stress a single word. An
+//! asterisk without a partner ("5 * 3") is read as written.
+//!
+//! ```text
+//! Deploy finished. [pause 500ms] It took *four* minutes.
+//! ```
+
+use crate::{AudioDuration, KokoroError, SynthesizeOptions, TtsEngine, SAMPLE_RATE};
+
+/// Length of a bare `[pause]`
+pub const DEFAULT_PAUSE_MS: u32 = 250;
+
+/// Longest pause a marker can ask for
+pub const MAX_PAUSE_MS: u32 = 10_000;
+
+// How emphasized clauses differ from the rest
+const EMPHASIS_GAIN: f32 = 1.2;
+const EMPHASIS_SPEED: f32 = 0.92;
+
+/// A piece of marked-up text, in reading order
+#[derive(Clone, Debug, PartialEq)]
+pub enum MarkupSegment {
+    /// Text to synthesize, markers removed
+    Text { text: String, emphasis: bool },
+    /// Silence
+    Pause { ms: u32 },
+}
+
+/// Split `text` at its pause markers and emphasized clauses
+///
+/// A bracket that doesn't start with "pause" is ordinary text; a pause
+/// marker with a length that can't be read (`[pause soon]`) is
+/// `KokoroError::InvalidInput`.
+///
+/// # Example
+/// ```
+/// use kokoro_tiny::markup::{parse_markup, MarkupSegment};
+///
+/// let segments = parse_markup("Ready. [pause 300ms] Go *now*!").unwrap();
+/// assert_eq!(
+///     segments,
+///     vec![
+///         MarkupSegment::Text { text: "Ready.".into(), emphasis: false },
+///         MarkupSegment::Pause { ms: 300 },
+///         MarkupSegment::Text { text: "Go now!".into(), emphasis: true },
+///     ]
+/// );
+/// ```
+pub fn parse_markup(text: &str) -> Result<Vec<MarkupSegment>, KokoroError> {
+    let mut segments = Vec::new();
+    let mut rest = text;
+    while let Some((start, end, marker)) = next_pause(rest) {
+        push_text(&mut segments, &rest[..start]);
+        let ms = pause_ms(marker).ok_or_else(|| {
+            KokoroError::InvalidInput(format!("Can't read pause marker {}", &rest[start..end]))
+        })?;
+        segments.push(MarkupSegment::Pause { ms });
+        rest = &rest[end..];
+    }
+    push_text(&mut segments, rest);
+    Ok(segments)
+}
+
+// `text` without any markers, for the paths that render a single chunk plan
+// (streaming, resumable): pauses are dropped and emphasis read plainly
+pub(crate) fn plain_text(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some((start, end, _)) = next_pause(rest) {
+        out.push_str(&rest[..start]);
+        out.push(' ');
+        rest = &rest[end..];
+    }
+    out.push_str(rest);
+    strip_emphasis(&out).0
+}
+
+// The next `[pause ...]` as (start, end, what follows "pause")
+fn next_pause(text: &str) -> Option<(usize, usize, &str)> {
+    let mut from = 0;
+    while let Some(offset) = text[from..].find('[') {
+        let start = from + offset;
+        let inner = &text[start + 1..];
+        if let Some(close) = inner.find(']') {
+            let body = &inner[..close];
+            let keyword = body.get(..5).filter(|k| k.eq_ignore_ascii_case("pause"));
+            if keyword.is_some() && body[5..].chars().next().is_none_or(char::is_whitespace) {
+                return Some((start, start + close + 2, body[5..].trim()));
+            }
+        }
+        from = start + 1;
+    }
+    None
+}
+
+// "" -> the default, "300ms", "300 ms", "1.5s"
+fn pause_ms(length: &str) -> Option<u32> {
+    if length.is_empty() {
+        return Some(DEFAULT_PAUSE_MS);
+    }
+    let length = length.to_ascii_lowercase();
+    let ms = if let Some(ms) = length.strip_suffix("ms") {
+        ms.trim().parse::<f32>().ok()?
+    } else {
+        length.strip_suffix('s')?.trim().parse::<f32>().ok()? * 1000.0
+    };
+    (ms.is_finite() && ms >= 0.0).then(|| (ms.round() as u32).min(MAX_PAUSE_MS))
+}
+
+// Text between pauses, split into emphasized and plain runs of clauses
+fn push_text(segments: &mut Vec<MarkupSegment>, text: &str) {
+    let (plain, emphasized) = strip_emphasis(text);
+    let chars: Vec<char> = plain.chars().collect();
+
+    let mut clause_start = 0;
+    let mut runs: Vec<(String, bool)> = Vec::new();
+    for i in 0..chars.len() {
+        let ends_clause =
+            ",;:.!?".contains(chars[i]) && chars.get(i + 1).is_none_or(|next| next.is_whitespace());
+        if ends_clause || i + 1 == chars.len() {
+            let clause: String = chars[clause_start..=i].iter().collect();
+            let emphasis = emphasized[clause_start..=i].iter().any(|&e| e);
+            match runs.last_mut() {
+                Some((run, run_emphasis)) if *run_emphasis == emphasis => run.push_str(&clause),
+                _ => runs.push((clause, emphasis)),
+            }
+            clause_start = i + 1;
+        }
+    }
+
+    for (run, emphasis) in runs {
+        let text = run.split_whitespace().collect::<Vec<_>>().join(" ");
+        if !text.is_empty() {
+            segments.push(MarkupSegment::Text { text, emphasis });
+        }
+    }
+}
+
+// `text` without the asterisks of `*...*` pairs, and per char of the result
+// whether it was inside one
+fn strip_emphasis(text: &str) -> (String, Vec<bool>) {
+    let chars: Vec<char> = text.chars().collect();
+    let opens = |i: usize| {
+        chars[i] == '*'
+            && chars
+                .get(i + 1)
+                .is_some_and(|c| !c.is_whitespace() && *c != '*')
+            && (i == 0 || !chars[i - 1].is_alphanumeric())
+    };
+    let closes = |i: usize| {
+        chars[i] == '*'
+            && i > 0
+            && !chars[i - 1].is_whitespace()
+            && chars.get(i + 1).is_none_or(|c| !c.is_alphanumeric())
+    };
+
+    let mut out = String::with_capacity(text.len());
+    let mut emphasized = Vec::with_capacity(chars.len());
+    let mut i = 0;
+    while i < chars.len() {
+        let close = opens(i)
+            .then(|| (i + 2..chars.len()).find(|&j| closes(j)))
+            .flatten();
+        match close {
+            Some(close) => {
+                for &c in &chars[i + 1..close] {
+                    out.push(c);
+                    emphasized.push(true);
+                }
+                i = close + 1;
+            }
+            None => {
+                out.push(chars[i]);
+                emphasized.push(false);
+                i += 1;
+            }
+        }
+    }
+    (out, emphasized)
+}
+
+impl TtsEngine {
+    // `synthesize_with` for text with `markup` on: each segment on its own,
+    // pauses spliced in as exact silence
+    pub(crate) fn synthesize_markup(
+        &self,
+        text: &str,
+        opts: &SynthesizeOptions,
+    ) -> Result<Vec<f32>, KokoroError> {
+        let segments = parse_markup(text)?;
+        if segments.is_empty() {
+            return Err(KokoroError::InvalidInput(
+                "No text provided for synthesis".to_string(),
+            ));
+        }
+
+        let plain = opts.clone().markup(false);
+        let mut audio = Vec::new();
+        for segment in segments {
+            match segment {
+                MarkupSegment::Text { text, emphasis } => {
+                    let segment_opts = if emphasis {
+                        plain
+                            .clone()
+                            .speed(plain.speed * EMPHASIS_SPEED)
+                            .gain(plain.gain * EMPHASIS_GAIN)
+                    } else {
+                        plain.clone()
+                    };
+                    audio.extend(self.synthesize_text(&text, &segment_opts)?);
+                }
+                MarkupSegment::Pause { ms } => {
+                    let samples = AudioDuration::from_millis(u64::from(ms)).to_samples(SAMPLE_RATE);
+                    audio.resize(audio.len() + samples, 0.0);
+                }
+            }
+        }
+
+        Ok(audio)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{SynthesisPlan, DEFAULT_VOICE, STYLE_DIM};
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+
+    fn text(text: &str, emphasis: bool) -> MarkupSegment {
+        MarkupSegment::Text {
+            text: text.to_string(),
+            emphasis,
+        }
+    }
+
+    #[test]
+    fn pauses_and_emphasis_become_segments() {
+        let segments = parse_markup(
+            "Build done. [pause 300ms] It took *four* minutes, which is fine.[pause]\
+             [PAUSE 1.5s] Array [0] and 5 * 3 stay. [pause 2 s]\n*Really* done [pause 99s]",
+        )
+        .unwrap();
+        assert_eq!(
+            segments,
+            vec![
+                text("Build done.", false),
+                MarkupSegment::Pause { ms: 300 },
+                text("It took four minutes,", true),
+                text("which is fine.", false),
+                MarkupSegment::Pause {
+                    ms: DEFAULT_PAUSE_MS
+                },
+                MarkupSegment::Pause { ms: 1500 },
+                text("Array [0] and 5 * 3 stay.", false),
+                MarkupSegment::Pause { ms: 2000 },
+                text("Really done", true),
+                MarkupSegment::Pause { ms: MAX_PAUSE_MS },
+            ]
+        );
+
+        assert!(parse_markup("Wait [pause soon] here").is_err());
+        assert_eq!(
+            parse_markup("[paused] *not closed").unwrap(),
+            vec![text("[paused] *not closed", false)]
+        );
+    }
+
+    #[test]
+    fn plans_drop_the_markers_only_with_markup_on() {
+        let text = "Wait [pause 300ms] for *it*.";
+        let marked = SynthesisPlan::new(text, &SynthesizeOptions::new().markup(true));
+        assert_eq!(marked.chunks.len(), 1);
+        assert_eq!(
+            marked.chunks[0].split_whitespace().collect::<Vec<_>>(),
+            ["Wait", "for", "it."]
+        );
+
+        let literal = SynthesisPlan::new(text, &SynthesizeOptions::new());
+        assert_eq!(literal.chunks, [text]);
+    }
+
+    #[test]
+    fn pauses_add_exact_silence_and_markers_are_never_spoken() {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let voices = HashMap::from([(DEFAULT_VOICE.to_string(), vec![0.1; STYLE_DIM])]);
+        let mut engine = TtsEngine::with_test_voices(voices);
+        let sink = seen.clone();
+        engine.mock_inference = Some(Arc::new(move |tokens: &[i64], _: &[f32], _| {
+            sink.lock().unwrap().push(tokens.to_vec());
+            Ok(vec![0.5; tokens.len() * 10])
+        }));
+        let opts = SynthesizeOptions::new();
+
+        let hello = engine
+            .synthesize_with("Hello there.", opts.clone())
+            .unwrap();
+        let world = engine.synthesize_with("Goodbye now", opts.clone()).unwrap();
+        let plain_tokens = seen.lock().unwrap().clone();
+        seen.lock().unwrap().clear();
+        engine.clear_cache();
+
+        let audio = engine
+            .synthesize_with(
+                "Hello there. [pause 300ms] *Goodbye* now",
+                opts.clone().markup(true),
+            )
+            .unwrap();
+        let silence = AudioDuration::from_millis(300).to_samples(SAMPLE_RATE);
+        assert_eq!(audio.len(), hello.len() + silence + world.len());
+        assert!(audio[hello.len()..hello.len() + silence]
+            .iter()
+            .all(|&s| s == 0.0));
+        // Only the words reach the model, the emphasized clause louder
+        assert_eq!(*seen.lock().unwrap(), plain_tokens);
+        assert!(audio[hello.len() + silence + 60] > hello[60]);
+    }
+}
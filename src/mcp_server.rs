@@ -8,14 +8,35 @@
 //! - `speak_with_emotion`: Auto-select voice based on emotion
 //! - `list_voices`: Get all available voice presets
 //! - `synthesize_to_file`: Save audio to file without playing
+//! - `synthesize_to_base64`: Return the audio inline, for clients on other
+//!   machines (up to `TtsEngine::set_max_inline_duration`, 60s by default)
+//! - `preview_mix`: Try out a weighted blend of voices
+//!
+//! Files are only written where the server's `PathsPolicy` allows: by
+//! default `.wav`, `.mp3` or `.opus` files in the temp or working directory.
+//!
+//! Every tool that synthesizes reports its timings under `"stats"` (see
+//! `SynthesisStats::to_json`); `synthesize_to_file` also reports the saved
+//! audio's levels under `"audio_stats"` (see `AudioStats`).
 
-use crate::TtsEngine;
+#[cfg(feature = "mp3")]
+use crate::Mp3Options;
+use crate::{
+    display_text, AudioDuration, AudioFormat, AudioStats, KokoroError, SynthesisStats,
+    SynthesizeOptions, TtsEngine, VoiceMix, SAMPLE_RATE,
+};
+#[cfg(feature = "playback")]
+use crate::{BusyPolicy, PlaybackOptions};
 use serde::{Deserialize, Serialize};
 use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
 
 /// MCP Protocol version
 const PROTOCOL_VERSION: &str = "2024-11-05";
 
+/// Longest text accepted by preview_mix - previews are meant to be short samples
+const MAX_PREVIEW_CHARS: usize = 500;
+
 /// MCP Request structure
 #[derive(Debug, Deserialize)]
 struct McpRequest {
@@ -46,6 +67,40 @@ struct McpError {
     data: Option<serde_json::Value>,
 }
 
+/// Server error codes (the JSON-RPC implementation-defined range)
+const MODEL_UNAVAILABLE: i32 = -32001;
+const SYNTHESIS_FAILED: i32 = -32002;
+const AUDIO_OUTPUT_FAILED: i32 = -32003;
+
+impl McpError {
+    /// Error for a failed engine call, with a code and `data.kind` per variant
+    ///
+    /// Requests the engine can't honour (unknown voice, unsupported language)
+    /// are invalid params; a missing model, a failed render and a failed
+    /// write or playback each get their own server error code.
+    fn engine(context: &str, e: KokoroError) -> Self {
+        let (code, kind) = match &e {
+            KokoroError::VoiceNotFound { .. } => (-32602, "voice_not_found"),
+            KokoroError::InvalidVoice(_) => (-32602, "invalid_voice"),
+            KokoroError::UnsupportedLanguage(_) => (-32602, "unsupported_language"),
+            KokoroError::InvalidInput(_) => (-32602, "invalid_input"),
+            KokoroError::ModelMissing(_) => (MODEL_UNAVAILABLE, "model_missing"),
+            KokoroError::DownloadFailed { .. } => (MODEL_UNAVAILABLE, "download_failed"),
+            KokoroError::PhonemizationFailed(_) => (SYNTHESIS_FAILED, "phonemization_failed"),
+            KokoroError::InferenceFailed(_) => (SYNTHESIS_FAILED, "inference_failed"),
+            KokoroError::AudioEncodingFailed(_) => (AUDIO_OUTPUT_FAILED, "audio_encoding_failed"),
+            KokoroError::Playback(_) => (AUDIO_OUTPUT_FAILED, "playback_failed"),
+            KokoroError::Io(_) => (AUDIO_OUTPUT_FAILED, "io"),
+            _ => (-32603, "internal"),
+        };
+        McpError {
+            code,
+            message: format!("{}: {}", context, e),
+            data: Some(serde_json::json!({ "kind": kind })),
+        }
+    }
+}
+
 /// Tool definition for MCP
 #[derive(Debug, Serialize)]
 struct Tool {
@@ -71,32 +126,240 @@ fn emotion_to_voice(emotion: &str) -> &str {
     }
 }
 
+/// Build a validated voice mix from preview_mix's `components` argument
+fn parse_preview_mix(tts: &TtsEngine, args: &serde_json::Value) -> Result<VoiceMix, McpError> {
+    let components = args.get("components")
+        .and_then(|v| v.as_array())
+        .ok_or_else(|| McpError {
+            code: -32602,
+            message: "Missing 'components' parameter".to_string(),
+            data: None,
+        })?;
+
+    let mut parsed = Vec::with_capacity(components.len());
+    for component in components {
+        let voice = component.get("voice")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| McpError {
+                code: -32602,
+                message: "Each component needs a 'voice' string".to_string(),
+                data: None,
+            })?;
+        let weight = component.get("weight")
+            .and_then(|v| v.as_f64())
+            .unwrap_or(1.0) as f32;
+        parsed.push((voice.to_string(), weight));
+    }
+
+    let mix = VoiceMix::from_components(parsed).map_err(|e| McpError {
+        code: -32602,
+        message: format!("Invalid voice mix: {}", e),
+        data: None,
+    })?;
+
+    tts.validate_voice_mix(&mix).map_err(|e| {
+        let mut available = tts.voices();
+        available.sort();
+        McpError {
+            code: -32602,
+            message: format!("Invalid voice mix: {}", e),
+            data: Some(serde_json::json!({ "available_voices": available })),
+        }
+    })?;
+
+    Ok(mix.normalized())
+}
+
+// Tool calls can arrive while earlier speech is still playing; let them line up
+#[cfg(feature = "playback")]
+fn queued(volume: f32) -> PlaybackOptions {
+    PlaybackOptions::new().volume(volume).on_busy(BusyPolicy::Queue)
+}
+
+/// Where tools may save audio: directories and file extensions
+///
+/// A path is allowed when its extension is listed and its directory, with
+/// symlinks and `..` resolved, lies inside one of the allowed directories.
+///
+/// # Example
+/// ```
+/// use kokoro_tiny::mcp_server::PathsPolicy;
+///
+/// let dir = std::env::temp_dir();
+/// let policy = PathsPolicy::new([&dir]).extensions(&["wav"]);
+/// assert!(policy.check(dir.join("take1.wav").to_str().unwrap()).is_ok());
+/// assert!(policy.check(dir.join("take1.mp3").to_str().unwrap()).is_err());
+/// assert!(policy.check("/etc/take1.wav").is_err());
+/// ```
+#[derive(Clone, Debug)]
+pub struct PathsPolicy {
+    dirs: Vec<PathBuf>,
+    extensions: Vec<String>,
+}
+
+impl Default for PathsPolicy {
+    /// The temp and working directories, for `.wav`, `.mp3` and `.opus` files
+    fn default() -> Self {
+        let mut dirs = vec![std::env::temp_dir()];
+        dirs.extend(std::env::current_dir().ok());
+        Self::new(dirs)
+    }
+}
+
+impl PathsPolicy {
+    /// Allow `.wav`, `.mp3` and `.opus` files inside `dirs`
+    pub fn new(dirs: impl IntoIterator<Item = impl Into<PathBuf>>) -> Self {
+        Self {
+            dirs: dirs.into_iter().map(Into::into).collect(),
+            extensions: vec!["wav".to_string(), "mp3".to_string(), "opus".to_string()],
+        }
+    }
+
+    /// Allow these extensions (without the dot) instead
+    pub fn extensions(mut self, extensions: &[&str]) -> Self {
+        self.extensions = extensions.iter().map(|e| e.to_lowercase()).collect();
+        self
+    }
+
+    /// The path to write `path` to, or `KokoroError::InvalidInput` if the
+    /// policy doesn't allow it
+    ///
+    /// The directory must already exist; the returned path has it resolved,
+    /// so a link swapped in afterwards can't redirect the write.
+    pub fn check(&self, path: &str) -> Result<PathBuf, KokoroError> {
+        let refuse = |why: String| {
+            KokoroError::InvalidInput(format!("Can't save to {}: {}", display_text(path), why))
+        };
+        let file = Path::new(path);
+
+        let extension = file
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(str::to_lowercase);
+        if !extension.is_some_and(|e| self.extensions.contains(&e)) {
+            return Err(refuse(format!(
+                "the file must end in .{}",
+                self.extensions.join(", .")
+            )));
+        }
+
+        let name = file
+            .file_name()
+            .ok_or_else(|| refuse("no file name".to_string()))?;
+        let parent = match file.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent,
+            _ => Path::new("."),
+        };
+        let dir = parent.canonicalize().map_err(|e| refuse(e.to_string()))?;
+        let inside = self
+            .dirs
+            .iter()
+            .filter_map(|allowed| allowed.canonicalize().ok())
+            .any(|allowed| dir.starts_with(allowed));
+        if !inside {
+            let dirs: Vec<_> = self.dirs.iter().map(|d| d.display().to_string()).collect();
+            return Err(refuse(format!("files may only be saved in {}", dirs.join(", "))));
+        }
+
+        let resolved = dir.join(name);
+        if resolved.symlink_metadata().is_ok_and(|m| m.file_type().is_symlink()) {
+            return Err(refuse("the file is a symbolic link".to_string()));
+        }
+        Ok(resolved)
+    }
+}
+
 /// MCP Server implementation
+///
+/// Send + Sync: stdin and stdout are only locked inside `run`, so a server
+/// can be built on one thread and run on another.
 pub struct McpServer {
     tts: TtsEngine,
-    stdin: io::StdinLock<'static>,
-    stdout: io::Stdout,
+    paths: PathsPolicy,
 }
 
 impl McpServer {
     /// Create a new MCP server
+    ///
+    /// # Example
+    /// ```no_run
+    /// use kokoro_tiny::mcp_server::McpServer;
+    ///
+    /// # async fn demo() -> Result<(), String> {
+    /// let mut server = McpServer::new().await?;
+    /// // Speaks MCP over stdin/stdout until stdin closes
+    /// server.run()?;
+    /// # Ok(())
+    /// # }
+    /// ```
     pub async fn new() -> Result<Self, String> {
-        let tts = TtsEngine::new().await?;
-        let stdin = Box::leak(Box::new(io::stdin())).lock();
-        let stdout = io::stdout();
-        
-        Ok(Self { tts, stdin, stdout })
+        Ok(Self::with_engine(TtsEngine::new().await?))
+    }
+
+    /// Server on an engine that already exists, e.g. one with imported voices
+    ///
+    /// # Example
+    /// ```
+    /// use kokoro_tiny::mcp_server::McpServer;
+    /// use kokoro_tiny::TtsEngine;
+    ///
+    /// let server = McpServer::with_engine(TtsEngine::without_model());
+    /// ```
+    pub fn with_engine(tts: TtsEngine) -> Self {
+        Self {
+            tts,
+            paths: PathsPolicy::default(),
+        }
+    }
+
+    /// Where `preview_mix` may save files (default: `PathsPolicy::default()`)
+    ///
+    /// # Example
+    /// ```
+    /// use kokoro_tiny::mcp_server::{McpServer, PathsPolicy};
+    /// use kokoro_tiny::TtsEngine;
+    ///
+    /// let previews = std::env::temp_dir().join("voice-previews");
+    /// std::fs::create_dir_all(&previews)?;
+    /// let server = McpServer::with_engine(TtsEngine::without_model())
+    ///     .with_paths_policy(PathsPolicy::new([previews]).extensions(&["wav"]));
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn with_paths_policy(mut self, paths: PathsPolicy) -> Self {
+        self.paths = paths;
+        self
     }
 
     /// Run the MCP server main loop
+    ///
+    /// # Example
+    /// ```no_run
+    /// use kokoro_tiny::mcp_server::McpServer;
+    /// use kokoro_tiny::TtsEngine;
+    ///
+    /// # async fn demo() -> Result<(), String> {
+    /// // Build on one thread, serve on another
+    /// let mut server = McpServer::with_engine(TtsEngine::new().await?);
+    /// std::thread::spawn(move || server.run()).join().unwrap()?;
+    /// # Ok(())
+    /// # }
+    /// ```
     pub fn run(&mut self) -> Result<(), String> {
         eprintln!("🎤 Kokoro MCP Server starting...");
         eprintln!("📡 Protocol version: {}", PROTOCOL_VERSION);
         eprintln!("🔊 Ready to provide audio collaboration!");
 
-        for line in self.stdin.by_ref().lines() {
-            let line = line.map_err(|e| format!("Failed to read line: {}", e))?;
-            
+        let mut stdin = io::stdin().lock();
+        let mut stdout = io::stdout().lock();
+        let mut line = String::new();
+        loop {
+            line.clear();
+            let read = stdin.read_line(&mut line)
+                .map_err(|e| format!("Failed to read line: {}", e))?;
+            if read == 0 {
+                break;
+            }
+
             if line.trim().is_empty() {
                 continue;
             }
@@ -118,10 +381,10 @@ impl McpServer {
             let response_json = serde_json::to_string(&response)
                 .map_err(|e| format!("Failed to serialize response: {}", e))?;
             
-            writeln!(self.stdout, "{}", response_json)
+            writeln!(stdout, "{}", response_json)
                 .map_err(|e| format!("Failed to write response: {}", e))?;
             
-            self.stdout.flush()
+            stdout.flush()
                 .map_err(|e| format!("Failed to flush stdout: {}", e))?;
         }
 
@@ -241,6 +504,42 @@ impl McpServer {
                     "properties": {}
                 }),
             },
+            Tool {
+                name: "preview_mix".to_string(),
+                description: "Preview a weighted blend of voices. Weights are normalized to sum to 1.0; the returned 'mix' string can be passed as the voice to speak_to_user.".to_string(),
+                input_schema: serde_json::json!({
+                    "type": "object",
+                    "properties": {
+                        "components": {
+                            "type": "array",
+                            "description": "Voices to blend, e.g. [{\"voice\": \"af_sky\", \"weight\": 0.7}, {\"voice\": \"af_bella\", \"weight\": 0.3}]",
+                            "items": {
+                                "type": "object",
+                                "properties": {
+                                    "voice": { "type": "string" },
+                                    "weight": { "type": "number", "minimum": 0.0 }
+                                },
+                                "required": ["voice"]
+                            },
+                            "minItems": 1
+                        },
+                        "text": {
+                            "type": "string",
+                            "description": "Sample text to speak with the mix",
+                            "maxLength": MAX_PREVIEW_CHARS
+                        },
+                        "play": {
+                            "type": "boolean",
+                            "description": "Play the preview immediately. Optional, defaults to false."
+                        },
+                        "save_path": {
+                            "type": "string",
+                            "description": "Also save the preview to this path: a .wav, .mp3 or .opus file in a directory the server allows (by default the temp or working directory). Optional."
+                        }
+                    },
+                    "required": ["components", "text"]
+                }),
+            },
             Tool {
                 name: "synthesize_to_file".to_string(),
                 description: "Synthesize text to speech and save to a file without playing it.".to_string(),
@@ -267,6 +566,33 @@ impl McpServer {
                     "required": ["text", "output_path"]
                 }),
             },
+            Tool {
+                name: "synthesize_to_base64".to_string(),
+                description: "Synthesize text to speech and return the audio inline as base64 (an embedded resource), for clients that can't reach this machine's speakers or files.".to_string(),
+                input_schema: serde_json::json!({
+                    "type": "object",
+                    "properties": {
+                        "text": {
+                            "type": "string",
+                            "description": "The text to synthesize"
+                        },
+                        "voice": {
+                            "type": "string",
+                            "description": "Voice preset to use. Optional, defaults to af_sky."
+                        },
+                        "speed": {
+                            "type": "number",
+                            "description": "Speech speed. Optional, defaults to 1.0."
+                        },
+                        "format": {
+                            "type": "string",
+                            "description": "Audio format: wav, or mp3 and opus when the server was built with them. Optional, defaults to wav.",
+                            "enum": ["wav", "mp3", "opus"]
+                        }
+                    },
+                    "required": ["text"]
+                }),
+            },
         ];
 
         Ok(serde_json::json!({
@@ -304,6 +630,8 @@ impl McpServer {
             "speak_with_emotion" => self.tool_speak_with_emotion(arguments),
             "list_voices" => self.tool_list_voices(),
             "synthesize_to_file" => self.tool_synthesize_to_file(arguments),
+            "synthesize_to_base64" => self.tool_synthesize_to_base64(arguments),
+            "preview_mix" => self.tool_preview_mix(arguments),
             _ => Err(McpError {
                 code: -32602,
                 message: format!("Unknown tool: {}", tool_name),
@@ -333,42 +661,33 @@ impl McpServer {
             .and_then(|v| v.as_f64())
             .unwrap_or(0.8) as f32;
 
-        eprintln!("🔊 Speaking: \"{}\" with voice {:?}", text, voice);
+        eprintln!("🔊 Speaking: \"{}\" with voice {:?}", display_text(text), voice);
 
         // Synthesize audio
-        let audio = self.tts.synthesize_with_speed(text, voice, speed)
-            .map_err(|e| McpError {
-                code: -32603,
-                message: format!("Synthesis failed: {}", e),
-                data: None,
-            })?;
+        let (audio, stats) = self.render(text, voice, speed)?;
 
         // Play audio
         #[cfg(feature = "playback")]
         {
-            self.tts.play(&audio, volume)
-                .map_err(|e| McpError {
-                    code: -32603,
-                    message: format!("Playback failed: {}", e),
-                    data: None,
-                })?;
+            self.tts.play_with_options(&audio, &queued(volume))
+                .map_err(|e| McpError::engine("Playback failed", e))?;
         }
 
-        let duration_ms = (audio.len() as f32 / 24000.0 * 1000.0) as u32;
+        let duration_ms = duration_ms(audio.len());
 
         let played = cfg!(feature = "playback");
 
         let status_text = if played {
             format!(
                 "🔊 Spoke: \"{}\"\nVoice: {}\nDuration: {}ms",
-                text,
+                display_text(text),
                 voice.unwrap_or("af_sky"),
                 duration_ms
             )
         } else {
             format!(
                 "💾 Synthesized (playback disabled): \"{}\"\nVoice: {}\nDuration: {}ms",
-                text,
+                display_text(text),
                 voice.unwrap_or("af_sky"),
                 duration_ms
             )
@@ -381,7 +700,8 @@ impl McpServer {
             }],
             "played": played,
             "duration_ms": duration_ms,
-            "voice": voice.unwrap_or("af_sky")
+            "voice": voice.unwrap_or("af_sky"),
+            "stats": stats.to_json()
         }))
     }
 
@@ -413,31 +733,22 @@ impl McpServer {
         eprintln!("😊 Speaking with emotion '{}': voice={}", emotion, voice);
 
         // Synthesize and play
-        let audio = self.tts.synthesize_with_speed(text, Some(voice), speed)
-            .map_err(|e| McpError {
-                code: -32603,
-                message: format!("Synthesis failed: {}", e),
-                data: None,
-            })?;
+        let (audio, stats) = self.render(text, Some(voice), speed)?;
 
         #[cfg(feature = "playback")]
         {
-            self.tts.play(&audio, 0.8)
-                .map_err(|e| McpError {
-                    code: -32603,
-                    message: format!("Playback failed: {}", e),
-                    data: None,
-                })?;
+            self.tts.play_with_options(&audio, &queued(0.8))
+                .map_err(|e| McpError::engine("Playback failed", e))?;
         }
 
-        let duration_ms = (audio.len() as f32 / 24000.0 * 1000.0) as u32;
+        let duration_ms = duration_ms(audio.len());
 
         Ok(serde_json::json!({
             "content": [{
                 "type": "text",
                 "text": format!("🔊 Spoke with emotion '{}': \"{}\"\nVoice: {}\nDuration: {}ms",
                     emotion,
-                    text,
+                    display_text(text),
                     voice,
                     duration_ms
                 )
@@ -445,7 +756,8 @@ impl McpServer {
             "played": true,
             "emotion": emotion,
             "voice": voice,
-            "duration_ms": duration_ms
+            "duration_ms": duration_ms,
+            "stats": stats.to_json()
         }))
     }
 
@@ -514,36 +826,478 @@ impl McpServer {
         eprintln!("💾 Saving to file: {}", output_path);
 
         // Synthesize audio
-        let audio = self.tts.synthesize_with_speed(text, voice, speed)
-            .map_err(|e| McpError {
-                code: -32603,
-                message: format!("Synthesis failed: {}", e),
-                data: None,
-            })?;
+        let (audio, stats) = self.render(text, voice, speed)?;
 
         // Save to file
         self.tts.save_audio(output_path, &audio)
-            .map_err(|e| McpError {
-                code: -32603,
-                message: format!("Failed to save file: {}", e),
-                data: None,
-            })?;
+            .map_err(|e| McpError::engine("Failed to save file", e))?;
 
-        let duration_ms = (audio.len() as f32 / 24000.0 * 1000.0) as u32;
+        let duration_ms = duration_ms(audio.len());
+        let levels = AudioStats::measure(&audio, SAMPLE_RATE);
 
         Ok(serde_json::json!({
             "content": [{
                 "type": "text",
-                "text": format!("💾 Saved audio to: {}\nDuration: {}ms\nVoice: {}",
+                "text": format!("💾 Saved audio to: {}\nDuration: {}ms\nVoice: {}\nAudio: {}",
                     output_path,
                     duration_ms,
-                    voice.unwrap_or("af_sky")
+                    voice.unwrap_or("af_sky"),
+                    levels
                 )
             }],
             "success": true,
             "path": output_path,
             "duration_ms": duration_ms,
-            "voice": voice.unwrap_or("af_sky")
+            "voice": voice.unwrap_or("af_sky"),
+            "stats": stats.to_json(),
+            "audio_stats": levels
+        }))
+    }
+
+    /// Tool: synthesize_to_base64
+    fn tool_synthesize_to_base64(&mut self, args: &serde_json::Value) -> Result<serde_json::Value, McpError> {
+        let text = args.get("text")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| McpError {
+                code: -32602,
+                message: "Missing 'text' parameter".to_string(),
+                data: None,
+            })?;
+
+        let voice = args.get("voice")
+            .and_then(|v| v.as_str());
+
+        let speed = args.get("speed")
+            .and_then(|v| v.as_f64())
+            .unwrap_or(1.0) as f32;
+
+        let format_name = args.get("format")
+            .and_then(|v| v.as_str())
+            .unwrap_or("wav");
+        let format = self.inline_format(format_name)?;
+        let mime_type = format.mime_type();
+        let extension = format.extension();
+
+        eprintln!("📦 Encoding inline {} audio", extension);
+
+        let (audio, stats) = self.render(text, voice, speed)?;
+        let blob = self.tts.to_base64(&audio, format)
+            .map_err(|e| McpError::engine("Can't return the audio inline", e))?;
+
+        let duration_ms = duration_ms(audio.len());
+
+        Ok(serde_json::json!({
+            "content": [
+                {
+                    "type": "text",
+                    "text": format!("🎵 {} audio, {}ms at {} Hz, {} base64 characters\nVoice: {}",
+                        mime_type,
+                        duration_ms,
+                        SAMPLE_RATE,
+                        blob.len(),
+                        voice.unwrap_or("af_sky")
+                    )
+                },
+                {
+                    "type": "resource",
+                    "resource": {
+                        "uri": format!("kokoro-tiny://speech.{}", extension),
+                        "mimeType": mime_type,
+                        "blob": blob
+                    }
+                }
+            ],
+            "mime_type": mime_type,
+            "duration_ms": duration_ms,
+            "sample_rate": SAMPLE_RATE,
+            "channels": 1,
+            "voice": voice.unwrap_or("af_sky"),
+            "stats": stats.to_json()
         }))
     }
+
+    /// Tool: preview_mix
+    fn tool_preview_mix(&mut self, args: &serde_json::Value) -> Result<serde_json::Value, McpError> {
+        let text = args.get("text")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| McpError {
+                code: -32602,
+                message: "Missing 'text' parameter".to_string(),
+                data: None,
+            })?;
+
+        if text.chars().count() > MAX_PREVIEW_CHARS {
+            return Err(McpError {
+                code: -32602,
+                message: format!("Preview text is limited to {} characters", MAX_PREVIEW_CHARS),
+                data: None,
+            });
+        }
+
+        let mix = parse_preview_mix(&self.tts, args)?;
+        let mix_string = mix.to_string();
+
+        let play = args.get("play")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+
+        let save_path = args.get("save_path")
+            .and_then(|v| v.as_str());
+        // Refuse before rendering, not after
+        let save_to = save_path
+            .map(|path| self.paths.check(path))
+            .transpose()
+            .map_err(|e| McpError::engine("Can't save the preview", e))?;
+
+        eprintln!("🎛️ Previewing mix: {}", mix_string);
+
+        let (audio, stats) = self.render(text, Some(&mix_string), 1.0)?;
+
+        if let Some(path) = &save_to {
+            let path = path.to_str().ok_or_else(|| McpError {
+                code: -32602,
+                message: "save_path must be valid UTF-8".to_string(),
+                data: None,
+            })?;
+            self.tts.save_audio(path, &audio)
+                .map_err(|e| McpError::engine("Failed to save file", e))?;
+        }
+
+        #[cfg(feature = "playback")]
+        if play {
+            self.tts.play_with_options(&audio, &queued(0.8))
+                .map_err(|e| McpError::engine("Playback failed", e))?;
+        }
+
+        let played = play && cfg!(feature = "playback");
+        let duration_ms = duration_ms(audio.len());
+
+        let weights: Vec<_> = mix.components.iter().map(|(voice, weight)| {
+            serde_json::json!({
+                "voice": voice,
+                "weight": weight
+            })
+        }).collect();
+
+        Ok(serde_json::json!({
+            "content": [{
+                "type": "text",
+                "text": format!("🎛️ Mix: {}\nDuration: {}ms{}",
+                    mix_string,
+                    duration_ms,
+                    save_path.map(|p| format!("\nSaved to: {}", p)).unwrap_or_default()
+                )
+            }],
+            "mix": mix_string,
+            "weights": weights,
+            "played": played,
+            "path": save_path,
+            "duration_ms": duration_ms,
+            "stats": stats.to_json()
+        }))
+    }
+
+    // synthesize_to_base64's `format` argument, with the engine's settings for it
+    fn inline_format(&self, name: &str) -> Result<AudioFormat, McpError> {
+        match name {
+            "wav" => Ok(AudioFormat::Wav),
+            #[cfg(feature = "mp3")]
+            "mp3" => Ok(AudioFormat::Mp3(Mp3Options::default())),
+            #[cfg(not(feature = "mp3"))]
+            "mp3" => Err(missing_feature(name, "mp3")),
+            #[cfg(feature = "opus-format")]
+            "opus" => Ok(AudioFormat::OpusOgg { bitrate: self.tts.opus_bitrate }),
+            #[cfg(not(feature = "opus-format"))]
+            "opus" => Err(missing_feature(name, "opus-format")),
+            _ => Err(McpError {
+                code: -32602,
+                message: format!("Unknown format '{}': use wav, mp3 or opus", name),
+                data: None,
+            }),
+        }
+    }
+
+    // Synthesis for a tool, with the timings its result reports
+    fn render(
+        &self,
+        text: &str,
+        voice: Option<&str>,
+        speed: f32,
+    ) -> Result<(Vec<f32>, SynthesisStats), McpError> {
+        let mut opts = SynthesizeOptions::new().speed(speed);
+        if let Some(voice) = voice {
+            opts = opts.voice(voice);
+        }
+        self.tts
+            .synthesize_with_stats(text, opts)
+            .map_err(|e| McpError::engine("Synthesis failed", e))
+    }
+}
+
+// A format left out of this build
+fn missing_feature(format: &str, feature: &str) -> McpError {
+    McpError {
+        code: -32602,
+        message: format!(
+            "Format '{}' needs the '{}' feature, which this server lacks",
+            format, feature
+        ),
+        data: None,
+    }
+}
+
+// `duration_ms` of a tool result, exact at any length
+fn duration_ms(samples: usize) -> u64 {
+    AudioDuration::from_samples(samples, SAMPLE_RATE).as_millis()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_engine() -> TtsEngine {
+        let tts = TtsEngine::mock(100);
+        for name in ["af_bella", "am_adam"] {
+            tts.import_voice(name, vec![0.0; 256]).unwrap();
+        }
+        tts
+    }
+
+    #[test]
+    fn preview_mix_normalizes_weights() {
+        let tts = test_engine();
+        let args = serde_json::json!({
+            "components": [
+                {"voice": "af_sky", "weight": 0.7},
+                {"voice": "af_bella", "weight": 0.7}
+            ],
+            "text": "Hello"
+        });
+
+        let mix = parse_preview_mix(&tts, &args).unwrap();
+        assert_eq!(mix.components[0].1, 0.5);
+        assert_eq!(mix.components[1].1, 0.5);
+        assert_eq!(mix.to_string(), "af_sky.5+af_bella.5");
+    }
+
+    #[test]
+    fn preview_mix_reports_available_voices_for_unknown_component() {
+        let tts = test_engine();
+        let args = serde_json::json!({
+            "components": [
+                {"voice": "af_sky", "weight": 0.5},
+                {"voice": "af_nope", "weight": 0.5}
+            ],
+            "text": "Hello"
+        });
+
+        let err = parse_preview_mix(&tts, &args).unwrap_err();
+        assert_eq!(err.code, -32602);
+        assert!(err.message.contains("af_nope"));
+        let available = err.data.unwrap()["available_voices"].clone();
+        assert_eq!(available, serde_json::json!(["af_bella", "af_sky", "am_adam"]));
+    }
+
+    #[test]
+    fn preview_mix_rejects_negative_weights() {
+        let tts = test_engine();
+        let args = serde_json::json!({
+            "components": [{"voice": "af_sky", "weight": -1.0}],
+            "text": "Hello"
+        });
+
+        assert!(parse_preview_mix(&tts, &args).is_err());
+    }
+
+    #[test]
+    fn preview_mix_returns_the_normalized_mix() {
+        let mut server = McpServer::with_engine(test_engine());
+        let preview = server
+            .tool_preview_mix(&serde_json::json!({
+                "components": [
+                    {"voice": "af_sky", "weight": 0.7},
+                    {"voice": "af_bella", "weight": 0.7}
+                ],
+                "text": "Hello"
+            }))
+            .unwrap();
+
+        assert_eq!(preview["mix"], "af_sky.5+af_bella.5");
+        assert_eq!(
+            preview["weights"],
+            serde_json::json!([
+                {"voice": "af_sky", "weight": 0.5},
+                {"voice": "af_bella", "weight": 0.5}
+            ])
+        );
+        assert_eq!(preview["played"], false);
+        assert!(preview["path"].is_null());
+        assert!(preview["duration_ms"].as_u64().unwrap() > 0);
+    }
+
+    #[test]
+    fn preview_mix_lists_the_voices_when_one_is_unknown() {
+        let mut server = McpServer::with_engine(test_engine());
+        let err = server
+            .tool_preview_mix(&serde_json::json!({
+                "components": [{"voice": "af_nope"}],
+                "text": "Hello"
+            }))
+            .unwrap_err();
+
+        assert_eq!(err.code, -32602);
+        assert!(err.message.contains("af_nope"), "{}", err.message);
+        let available = err.data.unwrap()["available_voices"].clone();
+        assert_eq!(available, serde_json::json!(["af_bella", "af_sky", "am_adam"]));
+    }
+
+    #[test]
+    fn preview_mix_saves_only_where_the_policy_allows() {
+        let root = std::env::temp_dir().join(format!("kokoro_previews_{}", std::process::id()));
+        let (allowed, elsewhere) = (root.join("allowed"), root.join("elsewhere"));
+        std::fs::create_dir_all(&allowed).unwrap();
+        std::fs::create_dir_all(&elsewhere).unwrap();
+        let mut server = McpServer::with_engine(test_engine())
+            .with_paths_policy(PathsPolicy::new([&allowed]));
+        let preview = |path: &Path| {
+            serde_json::json!({
+                "components": [{"voice": "af_sky"}],
+                "text": "Hello",
+                "save_path": path.to_str().unwrap()
+            })
+        };
+
+        let saved = allowed.join("take1.wav");
+        let result = server.tool_preview_mix(&preview(&saved)).unwrap();
+        assert_eq!(result["path"], saved.to_str().unwrap());
+        assert!(saved.exists());
+
+        for refused in [
+            elsewhere.join("take1.wav"),
+            allowed.join("..").join("elsewhere").join("take2.wav"),
+            allowed.join("take1.sh"),
+        ] {
+            let err = server.tool_preview_mix(&preview(&refused)).unwrap_err();
+            assert_eq!(err.code, -32602, "{}", err.message);
+            assert_eq!(err.data.unwrap()["kind"], "invalid_input");
+            assert!(!refused.exists(), "{}", refused.display());
+        }
+
+        std::fs::remove_dir_all(&root).ok();
+    }
+
+    // Without playback the tool only synthesizes, so it can run headless
+    #[cfg(not(feature = "playback"))]
+    #[test]
+    fn speak_result_omits_text_when_redacting() {
+        let tts = test_engine();
+        let mut server = McpServer::with_engine(tts);
+        let args = serde_json::json!({"text": "Patient Jane Roe, room twelve"});
+
+        let shown = server.tool_speak_to_user(&args).unwrap();
+        assert!(shown.to_string().contains("Jane Roe"), "{}", shown);
+        // Timings ride along for monitoring
+        assert!(shown["stats"]["tokens"].as_u64().unwrap() > 0);
+        assert!(shown["stats"]["real_time_factor"].as_f64().is_some());
+
+        let redacted =
+            crate::privacy::with_redact_text(true, || server.tool_speak_to_user(&args).unwrap());
+        assert!(!redacted.to_string().contains("Jane"), "{}", redacted);
+        assert!(redacted["duration_ms"].as_u64().unwrap() > 0);
+    }
+
+    #[test]
+    fn saved_files_report_their_levels() {
+        let tts = test_engine();
+        let mut server = McpServer::with_engine(tts);
+        let path = std::env::temp_dir().join(format!("kokoro_mcp_{}.wav", std::process::id()));
+
+        let saved = server
+            .tool_synthesize_to_file(&serde_json::json!({
+                "text": "Hello",
+                "output_path": path.to_str().unwrap()
+            }))
+            .unwrap();
+        std::fs::remove_file(&path).ok();
+        let levels = &saved["audio_stats"];
+        assert!((levels["peak_dbfs"].as_f64().unwrap() + 20.0).abs() < 0.01, "{}", levels);
+        assert_eq!(levels["clipped_samples"], 0);
+        assert_eq!(levels["duration_ms"], saved["duration_ms"]);
+        assert!(saved["content"][0]["text"].as_str().unwrap().contains("dBFS"));
+    }
+
+    #[test]
+    fn inline_audio_is_a_wav_in_base64() {
+        use base64::{engine::general_purpose::STANDARD, Engine as _};
+
+        let tts = test_engine();
+        let mut server = McpServer::with_engine(tts);
+
+        let inline = server
+            .tool_synthesize_to_base64(&serde_json::json!({"text": "Hello"}))
+            .unwrap();
+        let resource = &inline["content"][1]["resource"];
+        assert_eq!(resource["mimeType"], "audio/wav");
+        let wav = STANDARD.decode(resource["blob"].as_str().unwrap()).unwrap();
+        assert_eq!((&wav[..4], &wav[8..16]), (&b"RIFF"[..], &b"WAVEfmt "[..]));
+        assert_eq!(u32::from_le_bytes(wav[24..28].try_into().unwrap()), SAMPLE_RATE);
+        let samples = (wav.len() - 44) / 2;
+        assert_eq!(inline["duration_ms"].as_u64(), Some(duration_ms(samples)));
+        assert_eq!(inline["sample_rate"], SAMPLE_RATE);
+
+        // Past the limit: a clear error instead of megabytes of JSON
+        server.tts.set_max_inline_duration(std::time::Duration::from_millis(1));
+        let err = server
+            .tool_synthesize_to_base64(&serde_json::json!({"text": "Hello"}))
+            .unwrap_err();
+        assert_eq!(err.code, -32602);
+        assert!(err.message.contains("allowed inline"), "{}", err.message);
+
+        let err = server
+            .tool_synthesize_to_base64(&serde_json::json!({"text": "Hello", "format": "flac"}))
+            .unwrap_err();
+        assert!(err.message.contains("Unknown format"), "{}", err.message);
+    }
+
+    #[test]
+    fn engine_errors_map_to_json_rpc_codes() {
+        let tts = test_engine();
+        let mut server = McpServer::with_engine(tts);
+
+        let err = server
+            .tool_synthesize_to_file(&serde_json::json!({
+                "text": "Hello",
+                "voice": "af_nope",
+                "output_path": "unused.wav"
+            }))
+            .unwrap_err();
+        assert_eq!(err.code, -32602);
+        assert_eq!(err.data.unwrap()["kind"], "voice_not_found");
+        assert!(err.message.contains("af_nope"), "{}", err.message);
+
+        let err = server
+            .tool_synthesize_to_file(&serde_json::json!({
+                "text": "Hello",
+                "output_path": "speech.flac"
+            }))
+            .unwrap_err();
+        assert_eq!(err.code, AUDIO_OUTPUT_FAILED);
+        assert_eq!(err.data.unwrap()["kind"], "audio_encoding_failed");
+
+        let missing = KokoroError::ModelMissing("gone".into());
+        assert_eq!(McpError::engine("Synthesis failed", missing).code, MODEL_UNAVAILABLE);
+        let inference = KokoroError::InferenceFailed("boom".into());
+        let inference = McpError::engine("Synthesis failed", inference);
+        assert_eq!(inference.code, SYNTHESIS_FAILED);
+        assert_eq!(inference.message, "Synthesis failed: boom");
+    }
+
+    #[test]
+    fn duration_ms_is_exact_for_long_audio() {
+        let two_hours = 2 * 60 * 60 * SAMPLE_RATE as usize;
+        assert_eq!(duration_ms(two_hours), 7_200_000);
+        assert_eq!(duration_ms(two_hours + SAMPLE_RATE as usize / 1000 - 1), 7_200_000);
+        assert_eq!(duration_ms(two_hours + SAMPLE_RATE as usize / 1000), 7_200_001);
+        assert_eq!(duration_ms(usize::MAX), usize::MAX as u64 / 24);
+    }
 }
@@ -63,10 +63,13 @@ pub struct Mem8Bridge {
 }
 
 impl Mem8Bridge {
-    /// Initialize the bridge with a baby TTS
+    /// Initialize the bridge with a baby TTS on the shared engine
     pub async fn new() -> Result<Self, String> {
-        let baby = BabyTts::new().await?;
+        Ok(Self::with_baby(BabyTts::new().await?))
+    }
 
+    /// Initialize the bridge around an existing baby TTS (and its engine)
+    pub fn with_baby(baby: BabyTts) -> Self {
         // Map emotions to voices
         let mut voice_mappings = HashMap::new();
         voice_mappings.insert("joy".to_string(), "af_bella".to_string());
@@ -76,13 +79,13 @@ impl Mem8Bridge {
         voice_mappings.insert("love".to_string(), "af_heart".to_string());
         voice_mappings.insert("confusion".to_string(), "am_michael".to_string());
 
-        Ok(Self {
+        Self {
             baby_tts: baby,
             wave_buffer: Arc::new(Mutex::new(Vec::new())),
             current_emotion: EmotionType::Neutral,
             consciousness_level: 0.5,
             voice_mappings,
-        })
+        }
     }
 
     /// Process a salience event from Marine Algorithm
@@ -132,12 +135,13 @@ impl Mem8Bridge {
         eprintln!(
             "🗣️ Speaking with {} emotion: '{}'",
             self.emotion_name(&wave.emotion_type),
-            wave.content
+            crate::privacy::display_text(&wave.content)
         );
 
         self.baby_tts
             .engine
             .synthesize_with_options(&wave.content, Some(&voice), speed, gain, Some("en"))
+            .map_err(String::from)
     }
 
     /// Process interference between multiple waves (consciousness)
@@ -387,3 +391,62 @@ pub async fn demo_baby_consciousness() -> Result<(), String> {
 }
 
 use std::thread;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{shared_engine_with, TtsEngine};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[tokio::test]
+    async fn baby_and_bridge_share_one_engine() {
+        // Everything racing for the engine at once still builds it once. The
+        // mock builder goes first, so nothing here loads (or downloads) a model
+        let builds = AtomicUsize::new(0);
+        let build = || async {
+            builds.fetch_add(1, Ordering::SeqCst);
+            Ok(TtsEngine::mock(100))
+        };
+        let (engine, again, baby, settled, bridge) = tokio::join!(
+            shared_engine_with(build),
+            shared_engine_with(build),
+            BabyTts::new(),
+            BabyTts::with_settings(3, "af_sky", 0.8, 1.2),
+            Mem8Bridge::new()
+        );
+        let (engine, again, mut baby, settled, mut bridge) = (
+            engine.unwrap(),
+            again.unwrap(),
+            baby.unwrap(),
+            settled.unwrap(),
+            bridge.unwrap(),
+        );
+        assert_eq!(builds.load(Ordering::SeqCst), 1);
+        assert!(Arc::ptr_eq(&again, &engine));
+        for other in [&baby.engine, &settled.engine, &bridge.baby_tts.engine] {
+            assert!(Arc::ptr_eq(other, &engine));
+        }
+
+        // Baby speech and the bridge render through the shared instance
+        let before = engine.cache_stats().misses;
+        assert!(!baby.speak("mama").unwrap().is_empty());
+        let wave = MemoryWave {
+            amplitude: 0.5,
+            frequency: 440.0,
+            phase: 0.0,
+            decay_rate: 0.1,
+            emotion_type: EmotionType::Curiosity(0.6),
+            content: "What is that?".to_string(),
+        };
+        assert!(!bridge.wave_to_speech(&wave).unwrap().is_empty());
+        assert_eq!(engine.cache_stats().misses, before + 2);
+    }
+
+    #[test]
+    fn bridge_wraps_a_given_baby() {
+        let baby = BabyTts::with_engine(Arc::new(TtsEngine::without_model()));
+        let engine = baby.engine.clone();
+        let bridge = Mem8Bridge::with_baby(baby);
+        assert!(Arc::ptr_eq(&bridge.baby_tts.engine, &engine));
+    }
+}
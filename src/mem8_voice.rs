@@ -7,6 +7,7 @@
 //! "In the end, Aye should build their own voice from MEM8...
 //!  Kokoro is like having training wheels." - Hue
 
+use crate::AudioDuration;
 use std::f32::consts::PI;
 use std::sync::{Arc, Mutex};
 
@@ -137,7 +138,8 @@ impl Mem8Voice {
 
     /// Generate voice directly from consciousness patterns
     pub fn synthesize_from_consciousness(&self, text: &str, duration_ms: u32) -> Vec<f32> {
-        let num_samples = (MEM8_SAMPLE_RATE * duration_ms / 1000) as usize;
+        let num_samples =
+            AudioDuration::from_millis(duration_ms.into()).to_samples(MEM8_SAMPLE_RATE);
         let mut audio = Vec::with_capacity(num_samples);
 
         // Extract phoneme-like patterns from text
@@ -172,8 +174,8 @@ impl Mem8Voice {
             }
         }
 
-        // Apply envelope to prevent pops
-        apply_envelope(&mut audio);
+        // 10 ms fades to prevent pops
+        crate::audio::fade_samples(&mut audio, (MEM8_SAMPLE_RATE / 100) as usize);
 
         audio
     }
@@ -379,24 +381,6 @@ fn soft_clip(x: f32) -> f32 {
     }
 }
 
-/// Apply envelope to audio to prevent pops
-fn apply_envelope(audio: &mut [f32]) {
-    let fade_samples = (MEM8_SAMPLE_RATE * 10 / 1000) as usize; // 10ms fade
-
-    // Fade in
-    for i in 0..fade_samples.min(audio.len()) {
-        let fade = i as f32 / fade_samples as f32;
-        audio[i] *= fade;
-    }
-
-    // Fade out
-    let start = audio.len().saturating_sub(fade_samples);
-    for i in start..audio.len() {
-        let fade = (audio.len() - i) as f32 / fade_samples as f32;
-        audio[i] *= fade;
-    }
-}
-
 // Temporary random module until we integrate with MEM8's quantum randomness
 mod rand {
     use std::collections::hash_map::RandomState;
@@ -0,0 +1,198 @@
+//! Speech over a background bed
+//!
+//! For podcast-style intros: music (or any other bed) plays under the voice
+//! and drops by a few dB whenever the voice speaks, the way a radio host's
+//! music ducks under them. Speech is found from its level in short windows;
+//! the bed starts going down `attack_ms` before each stretch of speech, so it
+//! is already low when the first word arrives, stays down through the short
+//! gaps between words, and comes back up over `release_ms` once the speech
+//! stops.
+//!
+//! Everything is at the model's 24kHz in mono; `load_bed` converts a WAV file
+//! of any rate and channel count.
+
+use crate::{audio, load_wav_with, AudioDuration, KokoroError, LoadOptions, SAMPLE_RATE};
+
+/// Bed level under `mix_with_bed` when nobody is speaking
+pub const DEFAULT_BED_GAIN_DB: f32 = -12.0;
+
+/// How far the bed drops while the voice speaks
+pub const DEFAULT_DUCK_DB: f32 = 12.0;
+
+/// How long the bed takes to drop, starting before the speech
+pub const DEFAULT_ATTACK_MS: f32 = 80.0;
+
+/// How long the bed takes to come back after the speech
+pub const DEFAULT_RELEASE_MS: f32 = 400.0;
+
+// Window the speech level is measured over
+const WINDOW_MS: u64 = 10;
+// Speech windows louder than this (RMS, dBFS) count as speaking
+const SPEECH_THRESHOLD_DB: f32 = -40.0;
+// Gaps between words shorter than this keep the bed down
+const HOLD_MS: f32 = 250.0;
+
+/// `speech` over `bed`, the bed at `bed_gain_db` and another `duck_db` lower
+/// while the speech is audible
+///
+/// The result is as long as the speech; a shorter bed loops. Its edges are
+/// faded like synthesis results.
+///
+/// # Example
+/// ```
+/// use kokoro_tiny::mix::{mix_with_bed, DEFAULT_ATTACK_MS, DEFAULT_RELEASE_MS};
+///
+/// let speech = vec![0.0; 24_000];
+/// let bed: Vec<f32> = (0..48_000).map(|i| (i as f32 * 0.03).sin() * 0.5).collect();
+/// let intro = mix_with_bed(&speech, &bed, -6.0, 12.0, DEFAULT_ATTACK_MS, DEFAULT_RELEASE_MS);
+/// assert_eq!(intro.len(), 24_000);
+/// ```
+pub fn mix_with_bed(
+    speech: &[f32],
+    bed: &[f32],
+    bed_gain_db: f32,
+    duck_db: f32,
+    attack_ms: f32,
+    release_ms: f32,
+) -> Vec<f32> {
+    if bed.is_empty() {
+        return speech.to_vec();
+    }
+    let level = db_to_gain(bed_gain_db);
+    let ducked = db_to_gain(-duck_db.abs());
+    let speaking = speech_mask(speech, samples(attack_ms));
+
+    // Ramps of fixed length between full and ducked level
+    let attack_step = (1.0 - ducked) / samples(attack_ms).max(1) as f32;
+    let release_step = (1.0 - ducked) / samples(release_ms).max(1) as f32;
+    let mut gain = if speaking.first() == Some(&true) {
+        ducked
+    } else {
+        1.0
+    };
+    let mut out: Vec<f32> = speech
+        .iter()
+        .zip(&speaking)
+        .zip(bed.iter().cycle())
+        .map(|((&voice, &speaking), &bed)| {
+            gain = if speaking {
+                (gain - attack_step).max(ducked)
+            } else {
+                (gain + release_step).min(1.0)
+            };
+            voice + bed * level * gain
+        })
+        .collect();
+    audio::apply_edges(&mut out, audio::DEFAULT_EDGE_FADE_MS);
+    out
+}
+
+/// A WAV file (any sample rate, channel count and sample format) as mono
+/// samples at the model's 24kHz, ready for `mix_with_bed`
+pub fn load_bed(path: &str) -> Result<Vec<f32>, KokoroError> {
+    Ok(load_wav_with(path, LoadOptions::model())?.into_samples())
+}
+
+// Per sample: whether the bed should be down, from `lead` samples before a
+// speech window until `HOLD_MS` after it
+fn speech_mask(speech: &[f32], lead: usize) -> Vec<bool> {
+    let window = AudioDuration::from_millis(WINDOW_MS).to_samples(SAMPLE_RATE);
+    let hold = samples(HOLD_MS);
+    let threshold = db_to_gain(SPEECH_THRESHOLD_DB);
+    let mut mask = vec![false; speech.len()];
+    for (n, chunk) in speech.chunks(window).enumerate() {
+        let rms = (chunk.iter().map(|s| s * s).sum::<f32>() / chunk.len() as f32).sqrt();
+        if rms > threshold {
+            let start = (n * window).saturating_sub(lead);
+            let end = (n * window + chunk.len() + hold).min(speech.len());
+            mask[start..end].fill(true);
+        }
+    }
+    mask
+}
+
+fn samples(ms: f32) -> usize {
+    AudioDuration::from_millis_f32(ms).to_samples(SAMPLE_RATE)
+}
+
+fn db_to_gain(db: f32) -> f32 {
+    10f32.powf(db / 20.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rms(audio: &[f32]) -> f32 {
+        (audio.iter().map(|s| s * s).sum::<f32>() / audio.len() as f32).sqrt()
+    }
+
+    fn to_db(ratio: f32) -> f32 {
+        20.0 * ratio.log10()
+    }
+
+    #[test]
+    fn bed_ducks_under_speech_and_recovers_after() {
+        // One second of silence, one of "speech", one of silence
+        let second = SAMPLE_RATE as usize;
+        let mut speech = vec![0.0; second * 3];
+        for (i, sample) in speech[second..second * 2].iter_mut().enumerate() {
+            *sample = (i as f32 * 0.07).sin() * 0.3;
+        }
+        // A shorter bed, looped
+        let bed: Vec<f32> = (0..second / 2)
+            .map(|i| (i as f32 * 0.011).sin() * 0.5)
+            .collect();
+
+        let mixed = mix_with_bed(&speech, &bed, -6.0, 12.0, 80.0, 400.0);
+        assert_eq!(mixed.len(), speech.len());
+        let under: Vec<f32> = mixed.iter().zip(&speech).map(|(m, s)| m - s).collect();
+
+        // Away from the ramps at either end of the speech
+        let before = rms(&under[second / 4..second * 3 / 4]);
+        let during = rms(&under[second + second / 4..second * 2 - second / 4]);
+        let after = rms(&under[second * 2 + second * 3 / 4..second * 3 - 200]);
+        assert!(
+            to_db(before / during) >= 12.0 - 0.1,
+            "{}",
+            to_db(before / during)
+        );
+        assert!((to_db(before / rms(&bed)) + 6.0).abs() < 0.1);
+        assert!((after - before).abs() < 0.01);
+        // Already down when the speech starts: the bed peaks at 0.5, -6 - 12 dB
+        let ducked = 0.5 * db_to_gain(-18.0) + 1e-4;
+        assert!(under[second..second + 480]
+            .iter()
+            .all(|s| s.abs() <= ducked));
+    }
+
+    #[test]
+    fn beds_load_from_any_wav_at_the_model_rate() {
+        let path = std::env::temp_dir().join(format!("kokoro_bed_{}.wav", std::process::id()));
+        let spec = hound::WavSpec {
+            channels: 2,
+            sample_rate: 48_000,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let mut writer = hound::WavWriter::create(&path, spec).unwrap();
+        for _ in 0..48_000 {
+            writer.write_sample(16_384i16).unwrap();
+            writer.write_sample(0i16).unwrap();
+        }
+        writer.finalize().unwrap();
+
+        let bed = load_bed(path.to_str().unwrap()).unwrap();
+        std::fs::remove_file(&path).ok();
+        assert_eq!(bed.len(), 24_000);
+        assert!(bed.iter().all(|&s| (s - 0.25).abs() < 1e-3));
+        assert!(matches!(
+            load_bed("/nonexistent/bed.wav"),
+            Err(KokoroError::Io(_))
+        ));
+        assert_eq!(
+            mix_with_bed(&[0.1; 10], &[], -6.0, 12.0, 80.0, 400.0),
+            [0.1; 10]
+        );
+    }
+}
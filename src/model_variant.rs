@@ -0,0 +1,251 @@
+//! Model variants: the fp32 model and its int8 quantization
+//!
+//! The int8 model is the upstream Kokoro v1.0 export with dynamically
+//! quantized weights: about a quarter of the size (88MB against 310MB), less
+//! RAM and a faster render on CPUs without much float throughput
+//! (Raspberry Pi-class boards), at a small cost in quality. It takes the same
+//! tokens and style vectors, so voice packs are shared between variants.
+//!
+//! Exports of the model don't agree on names and element types: the fp32 one
+//! takes `tokens` and returns `audio`, the upstream ones take `input_ids` and
+//! return `waveform`, and the element types of `speed` and of the output vary.
+//! `ModelIo` reads what the loaded session actually declares, so inference
+//! feeds and reads any of them.
+
+use std::fmt;
+use std::str::FromStr;
+
+use ort::session::Session;
+use ort::value::TensorElementType;
+
+use crate::integrity::{FileDigest, MODEL_DIGEST};
+use crate::MODEL_URL;
+
+const INT8_URL: &str =
+    "https://github.com/thewh1teagle/kokoro-onnx/releases/download/model-files-v1.0/kokoro-v1.0.int8.onnx";
+
+/// Which build of the model to download and load
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum ModelVariant {
+    /// Full precision (310 MB); the default
+    #[default]
+    Fp32,
+    /// Weights quantized to 8 bits (88 MB), for small devices
+    Int8,
+}
+
+impl ModelVariant {
+    /// Where the model is downloaded from (`KOKORO_MODEL_URL` or
+    /// `EngineBuilder::model_urls` override it)
+    pub fn url(&self) -> &'static str {
+        match self {
+            ModelVariant::Fp32 => MODEL_URL,
+            ModelVariant::Int8 => INT8_URL,
+        }
+    }
+
+    /// File name in the cache directory
+    pub fn file_name(&self) -> &'static str {
+        match self {
+            ModelVariant::Fp32 => "0.onnx",
+            ModelVariant::Int8 => "0-int8.onnx",
+        }
+    }
+
+    /// Expected size and SHA-256 of the download; the int8 export isn't
+    /// pinned yet, so it is loaded unchecked
+    pub fn digest(&self) -> Option<FileDigest> {
+        match self {
+            ModelVariant::Fp32 => Some(MODEL_DIGEST),
+            ModelVariant::Int8 => None,
+        }
+    }
+}
+
+impl FromStr for ModelVariant {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s.trim().to_lowercase().as_str() {
+            "fp32" | "f32" | "default" => Ok(ModelVariant::Fp32),
+            "int8" | "i8" | "quantized" => Ok(ModelVariant::Int8),
+            _ => Err(format!(
+                "Unknown model variant '{}' (expected 'fp32' or 'int8')",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for ModelVariant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ModelVariant::Fp32 => write!(f, "fp32"),
+            ModelVariant::Int8 => write!(f, "int8"),
+        }
+    }
+}
+
+/// Input and output names and element types of a loaded model
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct ModelIo {
+    pub(crate) tokens: String,
+    pub(crate) tokens_type: TensorElementType,
+    pub(crate) style: String,
+    pub(crate) speed: String,
+    pub(crate) speed_type: TensorElementType,
+    pub(crate) output: String,
+    pub(crate) output_type: TensorElementType,
+}
+
+impl ModelIo {
+    pub(crate) fn of(session: &Session) -> Result<Self, String> {
+        let inputs: Vec<(&str, Option<TensorElementType>)> = session
+            .inputs()
+            .iter()
+            .map(|input| (input.name(), input.dtype().tensor_type()))
+            .collect();
+        let outputs: Vec<(&str, Option<TensorElementType>)> = session
+            .outputs()
+            .iter()
+            .map(|output| (output.name(), output.dtype().tensor_type()))
+            .collect();
+        Self::from_outlets(&inputs, &outputs)
+    }
+
+    // Separate from `of` so it can be tested without a session
+    fn from_outlets(
+        inputs: &[(&str, Option<TensorElementType>)],
+        outputs: &[(&str, Option<TensorElementType>)],
+    ) -> Result<Self, String> {
+        let input = |names: &[&str]| inputs.iter().find(|(name, _)| names.contains(name));
+        let (tokens, tokens_type) =
+            input(&["tokens", "input_ids"]).ok_or("Model has no `tokens` or `input_ids` input")?;
+        let (style, _) =
+            input(&["style", "ref_s"]).ok_or("Model has no `style` or `ref_s` input")?;
+        let (speed, speed_type) = input(&["speed"]).ok_or("Model has no `speed` input")?;
+        let (output, output_type) = outputs
+            .iter()
+            .find(|(name, _)| ["audio", "waveform"].contains(name))
+            .or_else(|| outputs.first())
+            .ok_or("Model has no outputs")?;
+
+        let io = ModelIo {
+            tokens: tokens.to_string(),
+            tokens_type: tokens_type.unwrap_or(TensorElementType::Int64),
+            style: style.to_string(),
+            speed: speed.to_string(),
+            speed_type: speed_type.unwrap_or(TensorElementType::Float32),
+            output: output.to_string(),
+            output_type: output_type.unwrap_or(TensorElementType::Float32),
+        };
+        let supported = [
+            (
+                io.tokens_type,
+                &[TensorElementType::Int64, TensorElementType::Int32][..],
+            ),
+            (
+                io.speed_type,
+                &[TensorElementType::Float32, TensorElementType::Int32][..],
+            ),
+            (
+                io.output_type,
+                &[TensorElementType::Float32, TensorElementType::Float64][..],
+            ),
+        ];
+        for (ty, allowed) in supported {
+            if !allowed.contains(&ty) {
+                return Err(format!("Unsupported model tensor type {:?}", ty));
+            }
+        }
+        Ok(io)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use TensorElementType::*;
+
+    #[test]
+    fn both_exports_are_understood() {
+        let original = ModelIo::from_outlets(
+            &[
+                ("tokens", Some(Int64)),
+                ("style", Some(Float32)),
+                ("speed", Some(Float32)),
+            ],
+            &[("audio", Some(Float32))],
+        )
+        .unwrap();
+        assert_eq!(
+            (original.tokens.as_str(), original.output.as_str()),
+            ("tokens", "audio")
+        );
+
+        let upstream = ModelIo::from_outlets(
+            &[
+                ("input_ids", Some(Int64)),
+                ("style", Some(Float32)),
+                ("speed", Some(Int32)),
+            ],
+            &[("waveform", Some(Float64)), ("durations", Some(Int64))],
+        )
+        .unwrap();
+        assert_eq!(upstream.tokens, "input_ids");
+        assert_eq!(upstream.speed_type, Int32);
+        assert_eq!(
+            (upstream.output.as_str(), upstream.output_type),
+            ("waveform", Float64)
+        );
+
+        assert!(ModelIo::from_outlets(&[("speed", None)], &[("audio", None)]).is_err());
+        let half = ModelIo::from_outlets(
+            &[("tokens", None), ("style", None), ("speed", None)],
+            &[("audio", Some(Float16))],
+        );
+        assert!(half.unwrap_err().contains("Float16"));
+    }
+
+    #[test]
+    fn input_names_come_from_the_model() {
+        let renamed = ModelIo::from_outlets(
+            &[
+                ("speed", Some(Float32)),
+                ("ref_s", Some(Float32)),
+                ("input_ids", Some(Int64)),
+            ],
+            &[("waveform", Some(Float32))],
+        )
+        .unwrap();
+        assert_eq!(
+            (
+                renamed.tokens.as_str(),
+                renamed.style.as_str(),
+                renamed.speed.as_str()
+            ),
+            ("input_ids", "ref_s", "speed")
+        );
+
+        let no_style = ModelIo::from_outlets(
+            &[("tokens", None), ("voice", None), ("speed", None)],
+            &[("audio", None)],
+        );
+        assert!(no_style.unwrap_err().contains("style"));
+    }
+
+    #[test]
+    fn variants_have_their_own_files() {
+        assert_ne!(
+            ModelVariant::Int8.file_name(),
+            ModelVariant::Fp32.file_name()
+        );
+        assert_eq!(ModelVariant::Fp32.url(), MODEL_URL);
+        assert_eq!("INT8".parse(), Ok(ModelVariant::Int8));
+        assert_eq!(
+            ModelVariant::Int8.to_string().parse(),
+            Ok(ModelVariant::Int8)
+        );
+        assert!("fp8".parse::<ModelVariant>().is_err());
+    }
+}
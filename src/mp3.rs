@@ -0,0 +1,245 @@
+//! MP3 encoding, for `save_mp3_with`, `to_mp3_bytes` and `encode_mp3`
+//!
+//! The audio goes through LAME a block at a time, each block into an output
+//! buffer sized for it and on to the writer, so clips of any length come out
+//! whole. A title or
+//! artist is written as an ID3v2.4 tag in front of the first frame, where
+//! players and podcast tools look for it.
+
+use std::io::Write;
+
+use mp3lame_encoder::{max_required_buffer_size, Bitrate, Builder, FlushNoGap, MonoPcm, Quality};
+
+use crate::{KokoroError, SAMPLE_RATE};
+
+// Samples handed to LAME at a time: a second, a whole number of frames
+const BLOCK: usize = 576 * 40;
+
+/// MP3 encoder settings
+///
+/// # Example
+/// ```
+/// use kokoro_tiny::Mp3Options;
+///
+/// let opts = Mp3Options {
+///     bitrate: 96,
+///     title: Some("Episode 12".into()),
+///     artist: Some("Kokoro".into()),
+///     ..Mp3Options::default()
+/// };
+/// assert_eq!(opts.quality, 0);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Mp3Options {
+    /// Constant bitrate in kbit/s, rounded to the nearest one MP3 has at
+    /// 24kHz (8 to 160)
+    pub bitrate: u32,
+    /// 0 (best, slowest) to 9 (fastest)
+    pub quality: u8,
+    /// Tagged as the title (ID3v2 `TIT2`)
+    pub title: Option<String>,
+    /// Tagged as the artist (ID3v2 `TPE1`)
+    pub artist: Option<String>,
+}
+
+impl Default for Mp3Options {
+    fn default() -> Self {
+        Mp3Options {
+            bitrate: 64,
+            quality: 0,
+            title: None,
+            artist: None,
+        }
+    }
+}
+
+/// Mono `audio` at the model's rate as an MP3 file into `w`
+pub(crate) fn encode(
+    audio: &[f32],
+    opts: &Mp3Options,
+    mut w: impl Write,
+) -> Result<(), KokoroError> {
+    let encoding = |message: String| KokoroError::AudioEncodingFailed(message);
+    let mut builder =
+        Builder::new().ok_or_else(|| encoding("Failed to create MP3 encoder".to_string()))?;
+    builder
+        .set_num_channels(1)
+        .map_err(|e| encoding(format!("Failed to set mono channel: {:?}", e)))?;
+    builder
+        .set_sample_rate(SAMPLE_RATE)
+        .map_err(|e| encoding(format!("Invalid sample rate: {:?}", e)))?;
+    builder
+        .set_brate(bitrate(opts.bitrate))
+        .map_err(|e| encoding(format!("Failed to set bitrate: {:?}", e)))?;
+    builder
+        .set_quality(quality(opts.quality))
+        .map_err(|e| encoding(format!("Failed to set quality: {:?}", e)))?;
+    let mut encoder = builder
+        .build()
+        .map_err(|e| encoding(format!("Failed to build MP3 encoder: {:?}", e)))?;
+
+    w.write_all(&id3v2(opts.title.as_deref(), opts.artist.as_deref()))?;
+    let mut mp3 = Vec::new();
+    for block in audio.chunks(BLOCK) {
+        let block: Vec<i16> = block
+            .iter()
+            .map(|&s| (s * 32767.0).clamp(-32768.0, 32767.0) as i16)
+            .collect();
+        mp3.clear();
+        mp3.reserve(max_required_buffer_size(block.len()));
+        encoder
+            .encode_to_vec(MonoPcm(&block), &mut mp3)
+            .map_err(|e| encoding(format!("Failed to encode MP3: {:?}", e)))?;
+        w.write_all(&mp3)?;
+    }
+    mp3.clear();
+    mp3.reserve(max_required_buffer_size(0));
+    encoder
+        .flush_to_vec::<FlushNoGap>(&mut mp3)
+        .map_err(|e| encoding(format!("Failed to flush MP3 encoder: {:?}", e)))?;
+    w.write_all(&mp3)?;
+    w.flush()?;
+    Ok(())
+}
+
+// The bitrate MP3 has at 24kHz closest to `kbps`
+fn bitrate(kbps: u32) -> Bitrate {
+    const RATES: [(u32, Bitrate); 12] = [
+        (8, Bitrate::Kbps8),
+        (16, Bitrate::Kbps16),
+        (24, Bitrate::Kbps24),
+        (32, Bitrate::Kbps32),
+        (40, Bitrate::Kbps40),
+        (48, Bitrate::Kbps48),
+        (64, Bitrate::Kbps64),
+        (80, Bitrate::Kbps80),
+        (96, Bitrate::Kbps96),
+        (112, Bitrate::Kbps112),
+        (128, Bitrate::Kbps128),
+        (160, Bitrate::Kbps160),
+    ];
+    RATES
+        .into_iter()
+        .min_by_key(|(rate, _)| rate.abs_diff(kbps))
+        .map_or(Bitrate::Kbps64, |(_, bitrate)| bitrate)
+}
+
+fn quality(level: u8) -> Quality {
+    match level {
+        0 => Quality::Best,
+        1 => Quality::SecondBest,
+        2 => Quality::NearBest,
+        3 => Quality::VeryNice,
+        4 => Quality::Nice,
+        5 => Quality::Good,
+        6 => Quality::Decent,
+        7 => Quality::Ok,
+        8 => Quality::SecondWorst,
+        _ => Quality::Worst,
+    }
+}
+
+// An ID3v2.4 tag with the title and artist given, in UTF-8; nothing when
+// neither is
+fn id3v2(title: Option<&str>, artist: Option<&str>) -> Vec<u8> {
+    let mut frames = Vec::new();
+    for (id, text) in [(b"TIT2", title), (b"TPE1", artist)] {
+        let Some(text) = text else {
+            continue;
+        };
+        frames.extend_from_slice(id);
+        frames.extend_from_slice(&syncsafe(text.len() + 1));
+        frames.extend_from_slice(&[0, 0]); // Flags
+        frames.push(3); // UTF-8
+        frames.extend_from_slice(text.as_bytes());
+    }
+    if frames.is_empty() {
+        return frames;
+    }
+    let mut tag = b"ID3".to_vec();
+    tag.extend_from_slice(&[4, 0, 0]); // Version 2.4.0, no flags
+    tag.extend_from_slice(&syncsafe(frames.len()));
+    tag.extend(frames);
+    tag
+}
+
+// Sizes in ID3v2.4 use seven bits per byte
+fn syncsafe(size: usize) -> [u8; 4] {
+    let size = size as u32;
+    [
+        ((size >> 21) & 0x7f) as u8,
+        ((size >> 14) & 0x7f) as u8,
+        ((size >> 7) & 0x7f) as u8,
+        (size & 0x7f) as u8,
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+    use symphonia::core::formats::FormatOptions;
+    use symphonia::core::io::MediaSourceStream;
+    use symphonia::core::meta::MetadataOptions;
+    use symphonia::core::probe::Hint;
+
+    #[test]
+    fn tags_hold_title_and_artist() {
+        assert!(id3v2(None, None).is_empty());
+        let tag = id3v2(Some("Épisode 12"), Some("Kokoro"));
+        assert_eq!(&tag[..6], b"ID3\x04\x00\x00");
+        // Title frame: 10 bytes of header, the encoding byte, 11 bytes of text
+        let size = 10 + 1 + "Épisode 12".len() + 10 + 1 + "Kokoro".len();
+        assert_eq!(tag[6..10], syncsafe(size));
+        assert_eq!(tag.len(), 10 + size);
+        assert_eq!(&tag[10..14], b"TIT2");
+        assert_eq!(tag[20], 3);
+        assert_eq!(&tag[21..32], "Épisode 12".as_bytes());
+        assert_eq!(&tag[32..36], b"TPE1");
+        assert_eq!(syncsafe(300), [0, 0, 2, 44]);
+    }
+
+    #[test]
+    fn bitrates_round_to_the_nearest_available() {
+        assert!(matches!(bitrate(64), Bitrate::Kbps64));
+        assert!(matches!(bitrate(100), Bitrate::Kbps96));
+        assert!(matches!(bitrate(320), Bitrate::Kbps160));
+        assert!(matches!(bitrate(0), Bitrate::Kbps8));
+    }
+
+    #[test]
+    fn long_audio_encodes_whole() {
+        let seconds = 30;
+        let tone: Vec<f32> = (0..SAMPLE_RATE as usize * seconds)
+            .map(|i| (i as f32 * 0.06).sin() * 0.5)
+            .collect();
+        let tts = crate::TtsEngine::without_model();
+        let mp3 = tts.to_mp3_bytes(&tone, Mp3Options::default()).unwrap();
+        // 64 kbit/s for 30 seconds
+        assert!(mp3.len() > 200_000, "{} bytes", mp3.len());
+
+        let stream = MediaSourceStream::new(Box::new(Cursor::new(mp3)), Default::default());
+        let mut hint = Hint::new();
+        hint.with_extension("mp3");
+        let probed = symphonia::default::get_probe()
+            .format(
+                &hint,
+                stream,
+                &FormatOptions::default(),
+                &MetadataOptions::default(),
+            )
+            .unwrap();
+        let mut format = probed.format;
+        let track = format.default_track().unwrap();
+        let rate = track.codec_params.sample_rate.unwrap();
+        let mut decoder = symphonia::default::get_codecs()
+            .make(&track.codec_params, &Default::default())
+            .unwrap();
+        let mut frames = 0;
+        while let Ok(packet) = format.next_packet() {
+            frames += decoder.decode(&packet).unwrap().frames();
+        }
+        let decoded = frames as f64 / f64::from(rate);
+        assert!((decoded - seconds as f64).abs() < 0.1, "{} s", decoded);
+    }
+}
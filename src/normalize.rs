@@ -0,0 +1,448 @@
+//! Text normalization applied before phonemization
+//!
+//! Input from logs, alerts and chat rarely looks like prose. The passes here
+//! rewrite it into something espeak reads naturally without changing what is
+//! said.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::acronyms::Acronyms;
+use crate::emoji;
+use crate::lexicon::Lexicon;
+use crate::links;
+use crate::numbers;
+use crate::spelling;
+use crate::units::{self, UnitWords};
+use crate::SynthesisPlan;
+
+/// How runs of upper-case text are read
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum CapsPolicy {
+    /// Mostly upper-case text is rewritten in sentence case, keeping known
+    /// acronyms intact ("BUILD FAILED ON CI" -> "Build failed on CI")
+    #[default]
+    Normalize,
+    /// Text is passed through untouched
+    Preserve,
+    /// Every all-caps word is spelled out letter by letter
+    SpellOut,
+}
+
+/// Share of cased letters that must be upper case before text counts as shouting
+const CAPS_RATIO_THRESHOLD: f32 = 0.7;
+
+/// Shorter runs ("OK", "NASA") are left alone, they aren't shouting
+const MIN_CAPS_LETTERS: usize = 6;
+
+/// Apply `policy` to `text`, returning the rewritten text if anything changed
+///
+/// Acronyms (see `acronyms`) are kept upper case when shouted text is
+/// normalized: they are consulted before lowercasing, so "CI FAILED ON GPU
+/// NODE" keeps "CI" and "GPU".
+pub fn apply_caps_policy(text: &str, policy: CapsPolicy) -> Option<String> {
+    caps_policy_after(text, text, policy, &Acronyms::default())
+}
+
+/// Emoji per `plan.emoji`, links read out in `plan.url_style`, words matching
+/// `plan.spell_patterns` spelled, lexicon respellings, then `normalize_chunk`
+/// with the plan's settings; only the respellings for `raw_text`
+pub(crate) fn normalize_for(
+    text: &str,
+    plan: &SynthesisPlan,
+    acronyms: &Acronyms,
+    lexicon: &Lexicon,
+) -> Option<String> {
+    if plan.raw_text {
+        return lexicon.respell(text);
+    }
+    // Nothing but emoji stays as it is, to be rendered as a pause
+    let emoji = emoji::replace_emoji(text, plan.emoji).filter(|t| !t.trim().is_empty());
+    // Before numbers, so a port or path segment isn't read as an amount
+    let linked =
+        links::expand_links(emoji.as_deref().unwrap_or(text), &plan.lang, plan.url_style).or(emoji);
+    let written = linked.as_deref().unwrap_or(text);
+    // Shouting is judged on the whole text as written, spelled IDs included
+    let rest = |piece: &str| {
+        let respelled = lexicon.respell(piece);
+        let piece_text = respelled.as_deref().unwrap_or(piece);
+        normalize_chunk(
+            written,
+            piece_text,
+            &plan.lang,
+            &plan.extra_units,
+            plan.caps_policy,
+            acronyms,
+            lexicon,
+        )
+        .or(respelled)
+    };
+    // Spelled IDs skip the other passes, which would read their digits as
+    // numbers and lowercase their letters; after links, so a hash in a URL
+    // path is spelled but the URL still found
+    if let Some(spelled) = spelling::spell_matches(written, &plan.spell_patterns, &plan.lang, |p| {
+        rest(p).unwrap_or_else(|| p.to_string())
+    }) {
+        return Some(spelled);
+    }
+    rest(written).or(linked)
+}
+
+/// Every pass synthesis runs on a chunk: units, numbers, the caps policy, then
+/// acronyms (except words in `lexicon`, which has the last say)
+///
+/// Whether the chunk is shouting is judged on the text as `written`, so "DISK
+/// AT 95 GB" is still normalized once it reads "ninety-five gigabytes", and a
+/// unit symbol after a number is never spelled out as an acronym.
+pub(crate) fn normalize_chunk(
+    written: &str,
+    text: &str,
+    lang: &str,
+    extra_units: &HashMap<String, UnitWords>,
+    policy: CapsPolicy,
+    acronyms: &Acronyms,
+    lexicon: &Lexicon,
+) -> Option<String> {
+    let with_units = units::expand_units(text, lang, extra_units);
+    let expanded =
+        numbers::expand_numbers(with_units.as_deref().unwrap_or(text), lang).or(with_units);
+    let spoken = expanded.as_deref().unwrap_or(text);
+    let cased = caps_policy_after(written, spoken, policy, acronyms).or(expanded);
+    acronyms
+        .read_all(cased.as_deref().unwrap_or(text), |word| {
+            lexicon.contains(word)
+        })
+        .or(cased)
+}
+
+// `policy` applied to `text`, with `original` deciding whether it's shouting
+fn caps_policy_after(
+    original: &str,
+    text: &str,
+    policy: CapsPolicy,
+    acronyms: &Acronyms,
+) -> Option<String> {
+    let rewritten = match policy {
+        CapsPolicy::Preserve => return None,
+        CapsPolicy::Normalize if !is_shouting(original) => return None,
+        CapsPolicy::Normalize => sentence_case(text, acronyms),
+        CapsPolicy::SpellOut => map_words(text, |word, _| {
+            if is_all_caps(word) && cased_letters(word).count() > 1 {
+                spell_out(word)
+            } else {
+                word.to_string()
+            }
+        }),
+    };
+    (rewritten != text).then_some(rewritten)
+}
+
+/// Whether more than 70% of the cased letters in `text` are upper case
+pub fn is_shouting(text: &str) -> bool {
+    let (upper, total) = cased_letters(text).fold((0, 0), |(upper, total), c| {
+        (upper + c.is_uppercase() as usize, total + 1)
+    });
+    total >= MIN_CAPS_LETTERS && upper as f32 > total as f32 * CAPS_RATIO_THRESHOLD
+}
+
+fn sentence_case(text: &str, acronyms: &Acronyms) -> String {
+    map_words(text, |word, sentence_start| {
+        let core = word.trim_matches(|c: char| !c.is_alphanumeric());
+        let acronym = core
+            .split(|c: char| !c.is_alphanumeric())
+            .all(|part| acronyms.get(part).is_some());
+        if acronym || core == "I" || !is_all_caps(word) {
+            return word.to_string();
+        }
+
+        let lower = word.to_lowercase();
+        if sentence_start {
+            capitalize_first_letter(&lower)
+        } else {
+            lower
+        }
+    })
+}
+
+// Rewrite each whitespace-separated word, keeping the whitespace as-is. The
+// flag tells whether the word starts a sentence.
+fn map_words(text: &str, mut rewrite: impl FnMut(&str, bool) -> String) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut sentence_start = true;
+    let mut rest = text;
+
+    while !rest.is_empty() {
+        let ws_end = rest
+            .find(|c: char| !c.is_whitespace())
+            .unwrap_or(rest.len());
+        out.push_str(&rest[..ws_end]);
+        rest = &rest[ws_end..];
+
+        let word_end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+        if word_end == 0 {
+            break;
+        }
+        let word = &rest[..word_end];
+        out.push_str(&rewrite(word, sentence_start));
+        if word.chars().any(char::is_alphanumeric) {
+            sentence_start = word.ends_with(['.', '!', '?']);
+        }
+        rest = &rest[word_end..];
+    }
+
+    out
+}
+
+fn cased_letters(text: &str) -> impl Iterator<Item = char> + '_ {
+    text.chars()
+        .filter(|c| c.is_uppercase() || c.is_lowercase())
+}
+
+fn is_all_caps(word: &str) -> bool {
+    let mut letters = cased_letters(word).peekable();
+    letters.peek().is_some() && letters.all(char::is_uppercase)
+}
+
+fn capitalize_first_letter(word: &str) -> String {
+    match word.find(char::is_alphabetic) {
+        Some(idx) => {
+            let first = word[idx..].chars().next().unwrap_or_default();
+            let after = idx + first.len_utf8();
+            format!("{}{}{}", &word[..idx], first.to_uppercase(), &word[after..])
+        }
+        None => word.to_string(),
+    }
+}
+
+// "GPU," -> "G P U,"
+fn spell_out(word: &str) -> String {
+    let mut out = String::with_capacity(word.len() * 2);
+    let mut prev_letter = false;
+    for c in word.chars() {
+        let letter = c.is_alphabetic();
+        if letter && prev_letter {
+            out.push(' ');
+        }
+        out.push(c);
+        prev_letter = letter;
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fully_caps_sentences_become_sentence_case() {
+        let out = apply_caps_policy(
+            "BUILD FAILED ON MAIN. RETRYING IN 5 MINUTES!",
+            CapsPolicy::Normalize,
+        );
+        assert_eq!(
+            out.as_deref(),
+            Some("Build failed on main. Retrying in 5 minutes!")
+        );
+    }
+
+    #[test]
+    fn acronyms_are_checked_before_lowercasing() {
+        let out = apply_caps_policy("CI FAILED ON GPU NODE", CapsPolicy::Normalize);
+        assert_eq!(out.as_deref(), Some("CI failed on GPU node"));
+
+        // Mostly caps with some normal words still crosses the threshold
+        let out = apply_caps_policy(
+            "DEPLOY OF THE API SERVER FAILED, see logs",
+            CapsPolicy::Normalize,
+        );
+        assert_eq!(
+            out.as_deref(),
+            Some("Deploy of the API server failed, see logs")
+        );
+    }
+
+    #[test]
+    fn ordinary_text_is_left_alone() {
+        assert_eq!(
+            apply_caps_policy("The CI job on the GPU node failed.", CapsPolicy::Normalize),
+            None
+        );
+        assert_eq!(apply_caps_policy("OK", CapsPolicy::Normalize), None);
+    }
+
+    #[test]
+    fn preserve_and_spell_out_modes() {
+        assert_eq!(
+            apply_caps_policy("BUILD FAILED", CapsPolicy::Preserve),
+            None
+        );
+        assert_eq!(
+            apply_caps_policy("Restart the GPU, then CI.", CapsPolicy::SpellOut).as_deref(),
+            Some("Restart the G P U, then C I.")
+        );
+        assert_eq!(apply_caps_policy("A quiet day", CapsPolicy::SpellOut), None);
+    }
+
+    // `normalize_chunk` without extra units, acronyms or lexicon
+    fn normalized(text: &str, lang: &str, policy: CapsPolicy) -> Option<String> {
+        let (acronyms, lexicon) = (Acronyms::default(), Lexicon::default());
+        normalize_chunk(
+            text,
+            text,
+            lang,
+            &HashMap::new(),
+            policy,
+            &acronyms,
+            &lexicon,
+        )
+    }
+
+    // `normalize_for` without acronyms or lexicon of the engine's own
+    fn normalized_for(text: &str, plan: &SynthesisPlan) -> Option<String> {
+        normalize_for(text, plan, &Acronyms::default(), &Lexicon::default())
+    }
+
+    #[test]
+    fn units_are_expanded_before_the_caps_policy() {
+        // After a number GB is a unit, not an acronym to spell out
+        assert_eq!(
+            normalized("4 GB free, GB total", "en-us", CapsPolicy::SpellOut).as_deref(),
+            Some("four gigabytes free, G B total")
+        );
+        assert_eq!(
+            normalized("DISK FULL AT 95 GB", "en-us", CapsPolicy::Normalize).as_deref(),
+            Some("Disk full at ninety-five gigabytes")
+        );
+        assert_eq!(
+            normalized("Only 4 GB left", "en", CapsPolicy::Preserve).as_deref(),
+            Some("Only four gigabytes left")
+        );
+        assert_eq!(normalized("All good", "en", CapsPolicy::Normalize), None);
+    }
+
+    #[test]
+    fn numbers_are_read_out_unless_raw() {
+        assert_eq!(
+            normalized(
+                "Only $5.99 for 4 GB, 50% off since 1984",
+                "en",
+                CapsPolicy::Normalize
+            )
+            .as_deref(),
+            Some(
+                "Only five dollars and ninety-nine cents for four gigabytes, \
+                 fifty percent off since nineteen eighty-four"
+            )
+        );
+
+        let text = "Pay $5.99 by the 2nd";
+        let plan = SynthesisPlan::new(text, &crate::SynthesizeOptions::new());
+        assert_eq!(
+            normalized_for(text, &plan).as_deref(),
+            Some("Pay five dollars and ninety-nine cents by the second")
+        );
+        let raw = SynthesisPlan::new(text, &crate::SynthesizeOptions::new().raw_text(true));
+        assert_eq!(normalized_for(text, &raw), None);
+    }
+
+    #[test]
+    fn acronyms_are_read_after_the_caps_policy() {
+        assert_eq!(
+            normalized(
+                "DEPLOY OF THE API SERVER FAILED, CHECK CI/CD AND THE PNG LOGS",
+                "en",
+                CapsPolicy::Normalize
+            )
+            .as_deref(),
+            Some("Deploy of the A P I server failed, check C I/C D and the P N G logs")
+        );
+
+        let mut acronyms = Acronyms::default();
+        acronyms.insert("SQL", crate::Spoken::Custom("sequel".into()));
+        let text = "Our GraphQL APIs use MySQL";
+        let plan = SynthesisPlan::new(text, &crate::SynthesizeOptions::new());
+        let mut lexicon = Lexicon::default();
+        assert_eq!(
+            normalize_for(text, &plan, &acronyms, &lexicon).as_deref(),
+            Some("Our Graph Q L A P I's use My sequel")
+        );
+
+        // The lexicon has the last say; IPA words are left for the phonemizer
+        lexicon.insert("GraphQL", crate::Pronunciation::Text("graph Q L".into()));
+        lexicon.insert("APIs", crate::Pronunciation::Ipa("ˈeɪpiːˌaɪz".into()));
+        assert_eq!(
+            normalize_for(text, &plan, &acronyms, &lexicon).as_deref(),
+            Some("Our graph Q L APIs use My sequel")
+        );
+    }
+
+    #[test]
+    fn links_are_read_before_numbers() {
+        let text = "Health check on http://localhost:8080/health failed 3 times";
+        let plan = SynthesisPlan::new(text, &crate::SynthesizeOptions::new());
+        assert_eq!(
+            normalized_for(text, &plan).as_deref(),
+            Some(
+                "Health check on localhost port eight thousand eighty slash health \
+                 failed three times"
+            )
+        );
+        let opts = crate::SynthesizeOptions::new().url_style(crate::UrlStyle::Link);
+        let plan = SynthesisPlan::new(text, &opts);
+        assert_eq!(
+            normalized_for(text, &plan).as_deref(),
+            Some("Health check on a link failed three times")
+        );
+    }
+
+    #[test]
+    fn emoji_go_before_anything_else_reads_them() {
+        let text = "Release 2 is out 🎉🎉 ✅";
+        let plan = SynthesisPlan::new(text, &crate::SynthesizeOptions::new());
+        assert_eq!(
+            normalized_for(text, &plan).as_deref(),
+            Some("Release two is out")
+        );
+        let opts = crate::SynthesizeOptions::new().emoji(crate::EmojiMode::Name);
+        let plan = SynthesisPlan::new(text, &opts);
+        assert_eq!(
+            normalized_for(text, &plan).as_deref(),
+            Some("Release two is out party popper check mark")
+        );
+        // Nothing but emoji is left for the unspeakable pause
+        assert_eq!(
+            normalized_for("🎉 🎉", &plan).as_deref(),
+            Some("party popper")
+        );
+        let plan = SynthesisPlan::new("🎉", &crate::SynthesizeOptions::new());
+        assert_eq!(normalized_for("🎉", &plan), None);
+    }
+
+    #[test]
+    fn spelled_ids_are_not_read_as_numbers_or_acronyms() {
+        let opts = crate::SynthesizeOptions::new()
+            .spell_patterns(vec![crate::SpellPattern::Hex { min_len: 7 }]);
+        let text = "Deploy of a3f9c2e failed after 3 tries";
+        let plan = SynthesisPlan::new(text, &opts);
+        assert_eq!(
+            normalized_for(text, &plan).as_deref(),
+            Some("Deploy of A three F nine C two E failed after three tries")
+        );
+        // The caps policy leaves the spelled letters capitalized
+        let text = "DEPLOY OF a3f9c2e FAILED AFTER 3 TRIES";
+        let plan = SynthesisPlan::new(text, &opts);
+        let normalized = normalized_for(text, &plan).unwrap();
+        assert!(
+            normalized.starts_with("Deploy of A three F nine C two E "),
+            "{}",
+            normalized
+        );
+        assert!(normalized
+            .to_lowercase()
+            .ends_with("failed after three tries"));
+        // Off by default
+        let plan = SynthesisPlan::new(text, &crate::SynthesizeOptions::new());
+        assert!(!normalized_for(text, &plan).unwrap().contains("nine"));
+    }
+}
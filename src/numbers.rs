@@ -0,0 +1,560 @@
+//! Spoken form of numbers ("$1,234.50", "3rd", "1990s", "50%")
+//!
+//! espeak reads plain numbers well enough but stumbles over the rest:
+//! currency amounts come out symbol first, "3rd" as "three r d", "v2.0.1"
+//! digit by digit. This pass rewrites numbers into words of the synthesis
+//! language before phonemization:
+//!
+//! | Written        | English                                            |
+//! |----------------|----------------------------------------------------|
+//! | `1,234`        | one thousand two hundred thirty-four               |
+//! | `3.14`         | three point one four                               |
+//! | `-5`           | minus five                                         |
+//! | `2nd`, `21st`  | second, twenty-first                               |
+//! | `$5.99`        | five dollars and ninety-nine cents (also € and £)  |
+//! | `50%`          | fifty percent                                      |
+//! | `in 1984`      | in nineteen eighty-four                            |
+//! | `1990s`        | nineteen nineties                                  |
+//! | `v2.0.1`       | version two point zero point one                   |
+//!
+//! Decimal and grouping separators follow the language ("3,5" is three point
+//! five in German). A four-digit number is read as a year after words like
+//! "in" or "since" only, so "1984 people" stays a count. Times, dates, ranges
+//! and addresses ("10:30", "2024-01-05", "5-10", "192.168.1.1") and numbers
+//! with a leading zero are left to espeak. English and German have tables
+//! here (German without ordinals, which are written like sentence ends);
+//! other languages pass through unchanged.
+
+use crate::units::{self, Locale};
+
+struct Currency {
+    symbol: char,
+    major: (&'static str, &'static str),
+    minor: (&'static str, &'static str),
+}
+
+struct Words {
+    code: &'static str,
+    percent: &'static str,
+    // Between the major and minor units of an amount
+    and: &'static str,
+    version: &'static str,
+    // Between the parts of a version number
+    dot: &'static str,
+    currencies: [Currency; 3],
+    // Words after which a four-digit number is a year
+    year_cues: &'static [&'static str],
+    year: fn(u64) -> String,
+    // Written after a decade ("1990s", "1990er")
+    decade_suffix: &'static str,
+    decade: fn(String) -> String,
+    ordinal: Option<fn(String) -> String>,
+}
+
+const WORDS: &[Words] = &[
+    Words {
+        code: "en",
+        percent: "percent",
+        and: "and",
+        version: "version",
+        dot: "point",
+        currencies: [
+            Currency {
+                symbol: '$',
+                major: ("dollar", "dollars"),
+                minor: ("cent", "cents"),
+            },
+            Currency {
+                symbol: '€',
+                major: ("euro", "euros"),
+                minor: ("cent", "cents"),
+            },
+            Currency {
+                symbol: '£',
+                major: ("pound", "pounds"),
+                minor: ("penny", "pence"),
+            },
+        ],
+        year_cues: &[
+            "in", "since", "from", "until", "till", "by", "during", "before", "after", "around",
+            "circa", "year",
+        ],
+        year: english_year,
+        decade_suffix: "s",
+        decade: english_decade,
+        ordinal: Some(english_ordinal),
+    },
+    Words {
+        code: "de",
+        percent: "Prozent",
+        and: "und",
+        version: "Version",
+        dot: "Punkt",
+        currencies: [
+            Currency {
+                symbol: '$',
+                major: ("Dollar", "Dollar"),
+                minor: ("Cent", "Cent"),
+            },
+            Currency {
+                symbol: '€',
+                major: ("Euro", "Euro"),
+                minor: ("Cent", "Cent"),
+            },
+            Currency {
+                symbol: '£',
+                major: ("Pfund", "Pfund"),
+                minor: ("Penny", "Pence"),
+            },
+        ],
+        year_cues: &[
+            "im", "jahr", "jahre", "seit", "bis", "von", "ab", "um", "vor", "nach",
+        ],
+        year: german_year,
+        decade_suffix: "er",
+        decade: german_decade,
+        ordinal: None,
+    },
+];
+
+/// Rewrite the numbers in `text` in words of `lang`
+///
+/// Returns `None` when nothing changed, including for languages without
+/// number words here.
+///
+/// # Example
+/// ```
+/// use kokoro_tiny::numbers::expand_numbers;
+///
+/// assert_eq!(
+///     expand_numbers("The 3rd invoice, $1,234.50, is 50% paid", "en").as_deref(),
+///     Some(
+///         "The third invoice, one thousand two hundred thirty-four dollars and fifty cents, \
+///          is fifty percent paid"
+///     )
+/// );
+/// assert_eq!(
+///     expand_numbers("Seit 1984 kostet es 5,99 €", "de").as_deref(),
+///     Some("Seit neunzehnhundertvierundachtzig kostet es fünf Euro und neunundneunzig Cent")
+/// );
+/// ```
+pub fn expand_numbers(text: &str, lang: &str) -> Option<String> {
+    let locale = units::lookup(lang)?;
+    let words = WORDS.iter().find(|words| words.code == locale.code)?;
+    let mut out = String::with_capacity(text.len());
+    let mut copied = 0;
+    let mut idx = 0;
+
+    while let Some(c) = text[idx..].chars().next() {
+        let spoken = if words.currencies.iter().any(|cur| cur.symbol == c) {
+            amount_at(text, idx, locale, words)
+        } else if matches!(c, 'v' | 'V') {
+            version_at(text, idx, locale, words)
+        } else if units::starts_number(text, idx) && !after_time(text, idx) {
+            number_at(text, idx, locale, words)
+        } else {
+            None
+        };
+        if let Some((end, spoken)) = spoken {
+            out.push_str(&text[copied..idx]);
+            out.push_str(&spoken);
+            copied = end;
+            idx = end;
+            continue;
+        }
+        idx += c.len_utf8();
+    }
+
+    if copied == 0 {
+        return None;
+    }
+    out.push_str(&text[copied..]);
+    Some(out)
+}
+
+// The minutes of "10:30"
+fn after_time(text: &str, idx: usize) -> bool {
+    let mut before = text[..idx].chars().rev();
+    before.next() == Some(':') && before.next().is_some_and(|c| c.is_ascii_digit())
+}
+
+// Nothing that makes the number part of something bigger follows: a letter,
+// or a separator into more digits ("10:30", "5-10", "1/2")
+fn ends_cleanly(rest: &str) -> bool {
+    let mut chars = rest.chars();
+    match chars.next() {
+        None => true,
+        Some(c) if c.is_alphanumeric() || c == '_' => false,
+        Some(':' | '-' | '–' | '/' | '.' | ',') => {
+            !chars.next().is_some_and(|c| c.is_ascii_digit())
+        }
+        Some(_) => true,
+    }
+}
+
+// A digit run with a leading zero is a code ("007"), not a quantity
+fn leading_zero(raw: &str) -> bool {
+    let mut digits = raw.chars();
+    digits.next() == Some('0') && digits.next().is_some_and(|c| c.is_ascii_digit())
+}
+
+// The number starting at `start` (maybe signed) and the byte offset it ends at
+fn number_at(text: &str, start: usize, locale: &Locale, words: &Words) -> Option<(usize, String)> {
+    let mut idx = start;
+    let negative = !text[idx..].starts_with(|c: char| c.is_ascii_digit());
+    if negative {
+        idx += text[idx..].chars().next()?.len_utf8();
+    }
+    let number_start = idx;
+    let end = units::number_end(text, idx);
+    let raw = &text[number_start..end];
+    if leading_zero(raw) {
+        return None;
+    }
+    let (integer, fraction) = units::parse_number(raw, locale)?;
+    let rest = &text[end..];
+    let sign = |spoken: String| {
+        if negative {
+            format!("{} {}", locale.minus, spoken)
+        } else {
+            spoken
+        }
+    };
+
+    // "50%" and "50 %"
+    let spaced = rest
+        .strip_prefix([' ', '\u{a0}', '\u{202f}'])
+        .unwrap_or(rest);
+    if let Some(after) = spaced.strip_prefix('%') {
+        let spoken = format!("{} {}", decimal(integer, &fraction, locale), words.percent);
+        return Some((text.len() - after.len(), sign(spoken)));
+    }
+    // "5,99 €"
+    if let Some(currency) = words
+        .currencies
+        .iter()
+        .find(|currency| spaced.starts_with(currency.symbol))
+    {
+        let after = &spaced[currency.symbol.len_utf8()..];
+        if ends_cleanly(after) && !negative {
+            let spoken = amount(integer, &fraction, currency, locale, words);
+            return Some((text.len() - after.len(), spoken));
+        }
+    }
+
+    let whole = fraction.is_empty() && !negative;
+    // "2nd", "21st"
+    if let (Some(ordinal), true) = (words.ordinal, whole) {
+        let suffix = rest.get(..2).map(str::to_ascii_lowercase);
+        if matches!(suffix.as_deref(), Some("st" | "nd" | "rd" | "th")) && ends_cleanly(&rest[2..])
+        {
+            return Some((end + 2, ordinal((locale.cardinal)(integer))));
+        }
+    }
+    // "1990s", "80s"
+    let decade = whole && integer.is_multiple_of(10) && matches!(raw.len(), 2 | 4);
+    if let Some(after) = rest.strip_prefix(words.decade_suffix) {
+        if decade && ends_cleanly(after) {
+            let spoken = if raw.len() == 4 {
+                (words.year)(integer)
+            } else {
+                (locale.cardinal)(integer)
+            };
+            return Some((text.len() - after.len(), (words.decade)(spoken)));
+        }
+    }
+
+    if !ends_cleanly(rest) {
+        return None;
+    }
+    let year = whole && raw.len() == 4 && (1000..2100).contains(&integer);
+    let spoken = if year && follows_year_cue(&text[..start], words) {
+        (words.year)(integer)
+    } else {
+        decimal(integer, &fraction, locale)
+    };
+    Some((end, sign(spoken)))
+}
+
+// "$5.99", "€20", "£1"
+fn amount_at(text: &str, start: usize, locale: &Locale, words: &Words) -> Option<(usize, String)> {
+    let symbol = text[start..].chars().next()?;
+    let currency = words.currencies.iter().find(|cur| cur.symbol == symbol)?;
+    let number_start = start + symbol.len_utf8();
+    let starts_word = text[..start]
+        .chars()
+        .next_back()
+        .is_none_or(|c| c.is_whitespace() || "([{\"'“‘~≈<>=".contains(c));
+    if !starts_word || !text[number_start..].starts_with(|c: char| c.is_ascii_digit()) {
+        return None;
+    }
+
+    let end = units::number_end(text, number_start);
+    let raw = &text[number_start..end];
+    if leading_zero(raw) || !ends_cleanly(&text[end..]) {
+        return None;
+    }
+    let (integer, fraction) = units::parse_number(raw, locale)?;
+    Some((end, amount(integer, &fraction, currency, locale, words)))
+}
+
+fn amount(
+    integer: u64,
+    fraction: &str,
+    currency: &Currency,
+    locale: &Locale,
+    words: &Words,
+) -> String {
+    let count = |n: u64, (singular, plural): (&str, &str)| {
+        if n == 1 {
+            format!("{} {}", locale.one, singular)
+        } else {
+            format!("{} {}", (locale.cardinal)(n), plural)
+        }
+    };
+    // Cents only make sense with two digits (or one: "$5.5")
+    let cents = match fraction.len() {
+        0 => 0,
+        1 | 2 => format!("{:0<2}", fraction).parse().unwrap_or(0),
+        _ => {
+            let value = decimal(integer, fraction, locale);
+            return format!("{} {}", value, currency.major.1);
+        }
+    };
+
+    match (integer, cents) {
+        (0, cents) if cents > 0 => count(cents, currency.minor),
+        (integer, 0) => count(integer, currency.major),
+        (integer, cents) => format!(
+            "{} {} {}",
+            count(integer, currency.major),
+            words.and,
+            count(cents, currency.minor)
+        ),
+    }
+}
+
+// "v2.0.1" -> "version two point zero point one"
+fn version_at(text: &str, start: usize, locale: &Locale, words: &Words) -> Option<(usize, String)> {
+    let number_start = start + 1;
+    let starts_word = text[..start]
+        .chars()
+        .next_back()
+        .is_none_or(|c| c.is_whitespace() || "([{\"'“‘".contains(c));
+    if !starts_word || !text[number_start..].starts_with(|c: char| c.is_ascii_digit()) {
+        return None;
+    }
+
+    let mut end = number_start;
+    let bytes = text.as_bytes();
+    while end < bytes.len()
+        && (bytes[end].is_ascii_digit()
+            || (bytes[end] == b'.' && bytes.get(end + 1).is_some_and(u8::is_ascii_digit)))
+    {
+        end += 1;
+    }
+    if !ends_cleanly(&text[end..]) {
+        return None;
+    }
+
+    let parts = text[number_start..end]
+        .split('.')
+        .map(|part| part.parse().ok().map(locale.cardinal))
+        .collect::<Option<Vec<_>>>()?;
+    let separator = format!(" {} ", words.dot);
+    Some((end, format!("{} {}", words.version, parts.join(&separator))))
+}
+
+// The word before the number asks for a year ("in 1984", "im Jahr 1984")
+fn follows_year_cue(before: &str, words: &Words) -> bool {
+    before
+        .split_whitespace()
+        .next_back()
+        .map(|word| {
+            word.trim_matches(|c: char| !c.is_alphanumeric())
+                .to_lowercase()
+        })
+        .is_some_and(|word| words.year_cues.contains(&word.as_str()))
+}
+
+// "3" or "3 point one four", digits after the point read one by one
+fn decimal(integer: u64, fraction: &str, locale: &Locale) -> String {
+    let mut spoken = vec![(locale.cardinal)(integer)];
+    if !fraction.is_empty() {
+        spoken.push(locale.point.to_string());
+        spoken.extend(
+            fraction
+                .bytes()
+                .map(|digit| (locale.cardinal)(u64::from(digit - b'0'))),
+        );
+    }
+    spoken.join(" ")
+}
+
+// 1984 -> "nineteen eighty-four", 2005 -> "two thousand five",
+// 1905 -> "nineteen oh five", 1900 -> "nineteen hundred"
+fn english_year(n: u64) -> String {
+    let cardinal = units::english_cardinal;
+    let (century, rest) = (n / 100, n % 100);
+    if (2000..2010).contains(&n) || n.is_multiple_of(1000) {
+        return cardinal(n);
+    }
+    match rest {
+        0 => format!("{} hundred", cardinal(century)),
+        1..=9 => format!("{} oh {}", cardinal(century), cardinal(rest)),
+        _ => format!("{} {}", cardinal(century), cardinal(rest)),
+    }
+}
+
+// 1984 -> "neunzehnhundertvierundachtzig", 2005 -> "zweitausendfünf"
+fn german_year(n: u64) -> String {
+    if (1100..2000).contains(&n) {
+        let rest = match n % 100 {
+            0 => String::new(),
+            rest => units::german_below_thousand(rest, true),
+        };
+        return format!(
+            "{}hundert{}",
+            units::german_below_thousand(n / 100, false),
+            rest
+        );
+    }
+    units::german_cardinal(n)
+}
+
+// "nineteen ninety" -> "nineteen nineties", "two thousand" -> "two thousands"
+fn english_decade(spoken: String) -> String {
+    match spoken.strip_suffix('y') {
+        Some(stem) => format!("{}ies", stem),
+        None => format!("{}s", spoken),
+    }
+}
+
+// "neunzehnhundertneunzig" -> "neunzehnhundertneunziger"
+fn german_decade(spoken: String) -> String {
+    format!("{}er", spoken)
+}
+
+// "twenty-one" -> "twenty-first", "one hundred" -> "one hundredth"
+fn english_ordinal(spoken: String) -> String {
+    const IRREGULAR: [(&str, &str); 7] = [
+        ("one", "first"),
+        ("two", "second"),
+        ("three", "third"),
+        ("five", "fifth"),
+        ("eight", "eighth"),
+        ("nine", "ninth"),
+        ("twelve", "twelfth"),
+    ];
+    let split = spoken.rfind([' ', '-']).map_or(0, |idx| idx + 1);
+    let (head, last) = spoken.split_at(split);
+    let last = match IRREGULAR.iter().find(|(cardinal, _)| *cardinal == last) {
+        Some((_, ordinal)) => ordinal.to_string(),
+        None => match last.strip_suffix('y') {
+            Some(stem) => format!("{}ieth", stem),
+            None => format!("{}th", last),
+        },
+    };
+    format!("{}{}", head, last)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CASES: &[(&str, &str, &str)] = &[
+        ("en", "1,234", "one thousand two hundred thirty-four"),
+        ("en", "42", "forty-two"),
+        ("en", "3.14", "three point one four"),
+        ("en", "-5", "minus five"),
+        ("en", "2nd", "second"),
+        ("en", "3rd", "third"),
+        ("en", "21st", "twenty-first"),
+        ("en", "12th", "twelfth"),
+        ("en", "40th", "fortieth"),
+        ("en", "100th", "one hundredth"),
+        ("en", "$5.99", "five dollars and ninety-nine cents"),
+        (
+            "en",
+            "$1,234.50",
+            "one thousand two hundred thirty-four dollars and fifty cents",
+        ),
+        ("en", "$1", "one dollar"),
+        ("en", "$0.01", "one cent"),
+        ("en", "$2.5", "two dollars and fifty cents"),
+        ("en", "€20", "twenty euros"),
+        ("en", "£3.50", "three pounds and fifty pence"),
+        ("en", "20 €", "twenty euros"),
+        ("en", "50%", "fifty percent"),
+        ("en", "12.5 %", "twelve point five percent"),
+        ("en", "in 1984", "in nineteen eighty-four"),
+        ("en", "since 2005", "since two thousand five"),
+        ("en", "In 2024.", "In twenty twenty-four."),
+        ("en", "in 1905", "in nineteen oh five"),
+        ("en", "in 1900", "in nineteen hundred"),
+        (
+            "en",
+            "1984 people",
+            "one thousand nine hundred eighty-four people",
+        ),
+        ("en", "the 1990s", "the nineteen nineties"),
+        ("en", "the 80s", "the eighties"),
+        ("en", "the 2000s", "the two thousands"),
+        ("en", "v2.0.1", "version two point zero point one"),
+        (
+            "en",
+            "Pay $5.99 by the 2nd (50% off)!",
+            "Pay five dollars and ninety-nine cents by the second (fifty percent off)!",
+        ),
+        ("en-gb", "£1", "one pound"),
+        ("de", "1.234", "eintausendzweihundertvierunddreißig"),
+        ("de", "3,14", "drei Komma eins vier"),
+        ("de", "5,99 €", "fünf Euro und neunundneunzig Cent"),
+        ("de", "€1", "ein Euro"),
+        ("de", "50 %", "fünfzig Prozent"),
+        (
+            "de",
+            "im Jahr 1984",
+            "im Jahr neunzehnhundertvierundachtzig",
+        ),
+        ("de", "seit 2005", "seit zweitausendfünf"),
+        ("de", "die 1990er", "die neunzehnhundertneunziger"),
+        ("de", "v1.2", "Version eins Punkt zwei"),
+    ];
+
+    #[test]
+    fn expands_numbers() {
+        for (lang, text, expected) in CASES {
+            assert_eq!(
+                expand_numbers(text, lang).as_deref(),
+                Some(*expected),
+                "{} ({})",
+                text,
+                lang
+            );
+        }
+    }
+
+    #[test]
+    fn leaves_codes_times_and_ranges_alone() {
+        for text in [
+            "10:30",
+            "5-10",
+            "2024-01-05",
+            "1/2",
+            "192.168.1.1",
+            "007",
+            "x86",
+            "3D",
+            "COVID-19",
+            "room4",
+            "$",
+            "env",
+        ] {
+            assert_eq!(expand_numbers(text, "en"), None, "{}", text);
+        }
+        // German ordinals look like sentence ends, so "3." stays a number
+        assert_eq!(expand_numbers("am 3.", "de").as_deref(), Some("am drei."));
+        assert_eq!(expand_numbers("42", "fr"), None);
+    }
+}
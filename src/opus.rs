@@ -0,0 +1,196 @@
+//! Ogg Opus files (RFC 7845), for `save_opus` and `encode_opus`
+//!
+//! Opus packets alone are not a file: players expect them in an Ogg stream
+//! that starts with an identification header (`OpusHead`) and a comment
+//! header (`OpusTags`), each on a page of its own, followed by the audio with
+//! granule positions counting 48kHz samples. Decoded audio starts with
+//! `PRE_SKIP` samples of encoder lookahead, which players drop; the granule
+//! position of the last page marks where the audio really ends, so the zero
+//! padding of the final frame is never heard.
+
+use std::io::{self, Write};
+
+use audiopus::{coder::Encoder, Application, Bitrate, Channels, SampleRate};
+use ogg::writing::{PacketWriteEndInfo, PacketWriter};
+
+use crate::{resample, KokoroError, SAMPLE_RATE};
+
+/// Opus works at 48kHz whatever the input rate was
+pub(crate) const OPUS_RATE: u32 = 48_000;
+
+/// Samples per packet: 20ms
+pub(crate) const FRAME: usize = 960;
+
+/// Encoder lookahead at 48kHz (2.5ms plus the delay compensation of the
+/// audio application), dropped from the start of the decoded audio
+pub(crate) const PRE_SKIP: u16 = 312;
+
+// Any value will do: the file holds a single logical stream
+const SERIAL: u32 = 0x6b6f_6b6f;
+
+// Packets are written whole; the largest a 20ms mono frame gets is well under
+// this
+const MAX_PACKET: usize = 4000;
+
+/// `audio` (mono, at the model's rate) as an Ogg Opus file at `bitrate` bits
+/// per second into `w`
+pub(crate) fn encode(audio: &[f32], bitrate: i32, w: impl Write) -> Result<(), KokoroError> {
+    let encoding = |message: String| KokoroError::AudioEncodingFailed(message);
+    let mut encoder = Encoder::new(SampleRate::Hz48000, Channels::Mono, Application::Audio)
+        .map_err(|e| encoding(format!("Failed to create OPUS encoder: {:?}", e)))?;
+    encoder
+        .set_bitrate(Bitrate::BitsPerSecond(bitrate))
+        .map_err(|e| encoding(format!("Failed to set OPUS bitrate: {:?}", e)))?;
+
+    let mut samples: Vec<i16> = resample(audio, SAMPLE_RATE, OPUS_RATE)
+        .iter()
+        .map(|&s| (s * 32767.0).clamp(-32768.0, 32767.0) as i16)
+        .collect();
+    let length = samples.len() as u64;
+    // Whole frames, enough of them that the last real sample comes out after
+    // the lookahead
+    let frames = (samples.len() + usize::from(PRE_SKIP)).div_ceil(FRAME);
+    samples.resize(frames * FRAME, 0);
+
+    let mut output = vec![0u8; MAX_PACKET];
+    let packets = samples
+        .chunks(FRAME)
+        .map(|frame| {
+            let size = encoder
+                .encode(frame, &mut output)
+                .map_err(|e| encoding(format!("Failed to encode OPUS frame: {:?}", e)))?;
+            Ok(output[..size].to_vec())
+        })
+        .collect::<Result<Vec<_>, KokoroError>>()?;
+
+    container(&packets, length, SAMPLE_RATE, w)?;
+    Ok(())
+}
+
+/// Opus `packets` of `FRAME` samples each in an Ogg stream into `w`; `length`
+/// is how many 48kHz samples of them are audio, after `PRE_SKIP`, and
+/// `input_rate` the rate the audio was at before encoding (for players to
+/// show)
+pub(crate) fn container(
+    packets: &[Vec<u8>],
+    length: u64,
+    input_rate: u32,
+    w: impl Write,
+) -> io::Result<()> {
+    let mut writer = PacketWriter::new(w);
+
+    let mut head = b"OpusHead".to_vec();
+    head.push(1); // Version
+    head.push(1); // Channels
+    head.extend_from_slice(&PRE_SKIP.to_le_bytes());
+    head.extend_from_slice(&input_rate.to_le_bytes());
+    head.extend_from_slice(&0i16.to_le_bytes()); // Output gain
+    head.push(0); // Channel mapping family: mono or stereo
+    writer.write_packet(head.into(), SERIAL, PacketWriteEndInfo::EndPage, 0)?;
+
+    let vendor = concat!("kokoro-tiny ", env!("CARGO_PKG_VERSION"));
+    let mut tags = b"OpusTags".to_vec();
+    tags.extend_from_slice(&(vendor.len() as u32).to_le_bytes());
+    tags.extend_from_slice(vendor.as_bytes());
+    tags.extend_from_slice(&0u32.to_le_bytes()); // No user comments
+    writer.write_packet(tags.into(), SERIAL, PacketWriteEndInfo::EndPage, 0)?;
+
+    // A page's granule position counts the samples decoded by the end of its
+    // last packet, lookahead included; on the last page it stops where the
+    // audio does
+    let end = u64::from(PRE_SKIP) + length;
+    for (i, packet) in packets.iter().enumerate() {
+        let last = i + 1 == packets.len();
+        let granule = ((i as u64 + 1) * FRAME as u64).min(end);
+        let info = if last {
+            PacketWriteEndInfo::EndStream
+        } else {
+            PacketWriteEndInfo::NormalPacket
+        };
+        writer.write_packet(packet.clone().into(), SERIAL, info, granule)?;
+    }
+    writer.into_inner().flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ogg::reading::PacketReader;
+    use std::io::Cursor;
+
+    #[test]
+    fn headers_come_first_and_the_last_granule_ends_the_audio() {
+        let packets = vec![vec![0xf8, 0xff, 0xfe]; 5];
+        let length = 4 * FRAME as u64 + 100;
+        let mut file = Vec::new();
+        container(&packets, length, 24_000, &mut file).unwrap();
+        assert_eq!(&file[..4], b"OggS");
+
+        let mut reader = PacketReader::new(Cursor::new(file));
+        let head = reader.read_packet_expected().unwrap();
+        assert_eq!(&head.data[..8], b"OpusHead");
+        assert_eq!(head.data.len(), 19);
+        assert_eq!((head.data[8], head.data[9]), (1, 1));
+        assert_eq!(u16::from_le_bytes([head.data[10], head.data[11]]), PRE_SKIP);
+        assert_eq!(head.data[12..16], 24_000u32.to_le_bytes());
+        assert!(head.first_in_stream() && head.last_in_page());
+        assert_eq!(head.absgp_page(), 0);
+
+        let tags = reader.read_packet_expected().unwrap();
+        assert_eq!(&tags.data[..8], b"OpusTags");
+        assert!(tags.last_in_page());
+        assert_eq!(tags.absgp_page(), 0);
+
+        let mut audio = Vec::new();
+        while let Some(packet) = reader.read_packet().unwrap() {
+            audio.push(packet);
+        }
+        assert_eq!(audio.len(), 5);
+        assert!(audio.iter().all(|p| p.data == [0xf8, 0xff, 0xfe]));
+        let last = audio.last().unwrap();
+        assert!(last.last_in_stream());
+        assert_eq!(last.absgp_page(), u64::from(PRE_SKIP) + length);
+    }
+
+    // A tone of `seconds` through save_audio and back through the decoder:
+    // the duration the file claims, the samples decoded, and the file size
+    fn round_trip(tts: &crate::TtsEngine, seconds: f32) -> (f64, usize, usize) {
+        use audiopus::{coder::Decoder, packet::Packet, MutSignals};
+
+        let tone = crate::test_signal::tone(&[(200.0, 0.5)], seconds);
+        let path = std::env::temp_dir().join(format!("kokoro_opus_{}.opus", std::process::id()));
+        tts.save_audio(path.to_str().unwrap(), &tone[..]).unwrap();
+        let file = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        let mut reader = PacketReader::new(Cursor::new(file.clone()));
+        reader.read_packet_expected().unwrap();
+        reader.read_packet_expected().unwrap();
+        let mut decoder = Decoder::new(SampleRate::Hz48000, Channels::Mono).unwrap();
+        let mut pcm = vec![0i16; FRAME * 6];
+        let (mut decoded, mut granule) = (0, 0);
+        while let Some(packet) = reader.read_packet().unwrap() {
+            let input = Packet::try_from(&packet.data[..]).unwrap();
+            let output = MutSignals::try_from(&mut pcm[..]).unwrap();
+            decoded += decoder.decode(Some(input), output, false).unwrap();
+            granule = packet.absgp_page();
+        }
+        let claimed = (granule - u64::from(PRE_SKIP)) as f64 / f64::from(OPUS_RATE);
+        (claimed, decoded, file.len())
+    }
+
+    #[test]
+    fn saved_files_decode_to_the_whole_audio() {
+        let mut tts = crate::TtsEngine::without_model();
+        // Not a whole number of frames: the last one is padded, not dropped
+        let (claimed, decoded, _) = round_trip(&tts, 1.013);
+        assert!((claimed - 1.013).abs() < 0.02, "{} s", claimed);
+        assert!(decoded >= (1.013 * f64::from(OPUS_RATE)) as usize + usize::from(PRE_SKIP));
+
+        // save_audio uses the engine's bitrate
+        let (_, _, small) = round_trip(&tts, 1.0);
+        tts.set_opus_bitrate(96_000).unwrap();
+        let (_, _, large) = round_trip(&tts, 1.0);
+        assert!(large > small * 2, "{} vs {} bytes", large, small);
+    }
+}
@@ -0,0 +1,758 @@
+//! Where played audio goes
+//!
+//! Every `play*` call writes its samples to an [`AudioStreamOut`] opened from
+//! the engine's [`AudioOutput`]. With the `playback` feature the default is
+//! `CpalOutput` on the selected device; `RodioOutput` is the rodio
+//! equivalent. Anything else (a JACK client, a custom mixer, WebAudio) plugs
+//! in with `TtsEngine::set_output`, and [`NullOutput`] keeps what it is given
+//! so playback can be tested without an audio device.
+//!
+//! Frames are mono unless the caller asks for stereo and the stream accepts
+//! it (see [`StreamInfo`]): one `f32` per frame, or a left and a right
+//! sample, at the stream's sample rate. Backends copy mono frames to every
+//! device channel.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::playback::BufferPreset;
+
+/// What a caller asks for when opening a stream
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OutputSpec {
+    /// Rate the caller would like to write at; the stream may run at another
+    pub sample_rate: u32,
+    /// `None` lets the backend choose
+    pub buffer: Option<BufferPreset>,
+    /// 1 for mono, 2 for interleaved stereo; the stream may accept fewer
+    pub channels: u16,
+}
+
+/// What an open stream is actually doing
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct StreamInfo {
+    pub device: String,
+    /// Rate `write` expects frames at
+    pub sample_rate: u32,
+    /// Samples per frame `write` expects (0 if unknown, treated as 1)
+    pub channels: u16,
+    /// Buffer size after negotiation with the device (0 if unknown)
+    pub buffer_frames: u32,
+    /// Frames per callback the device actually asked for (0 if unknown)
+    pub callback_frames: u32,
+    /// From opening the stream to the first frames reaching the device
+    pub startup_latency: Duration,
+    /// Times the device ran dry
+    pub underruns: u32,
+}
+
+/// Source of output streams
+pub trait AudioOutput: Send + Sync {
+    /// Open a stream; it plays until dropped
+    fn open(&self, spec: &OutputSpec) -> Result<Box<dyn AudioStreamOut>, String>;
+}
+
+/// An open output stream
+pub trait AudioStreamOut {
+    /// Queue frames, interleaved if the stream has two channels, blocking
+    /// while the stream is full
+    ///
+    /// Returns how many samples were taken, always whole frames, which may be
+    /// fewer than given (including none, if the device stopped asking for
+    /// audio; the engine's writers then give up with an error rather than
+    /// retry).
+    fn write(&mut self, frames: &[f32]) -> Result<usize, String>;
+
+    /// Block until everything written has been played
+    fn flush(&mut self);
+
+    fn info(&self) -> StreamInfo;
+}
+
+// Largest write made at once, so stop flags are checked every ~20 ms
+pub(crate) const WRITE_BLOCK_MS: u32 = 20;
+
+// A write that took nothing: the stream won't take any more
+pub(crate) const STREAM_STALLED: &str = "Audio output stopped taking audio";
+
+/// Write all of `frames` in small blocks, giving up as soon as `stop` says so
+///
+/// Returns the number of samples written, or an error if the stream stops
+/// taking frames.
+pub(crate) fn write_until(
+    stream: &mut dyn AudioStreamOut,
+    frames: &[f32],
+    stop: &dyn Fn() -> bool,
+) -> Result<usize, String> {
+    let info = stream.info();
+    let block = (info.sample_rate * WRITE_BLOCK_MS / 1000).max(1) as usize
+        * usize::from(info.channels.max(1));
+    let mut written = 0;
+    while written < frames.len() && !stop() {
+        let end = (written + block).min(frames.len());
+        match stream.write(&frames[written..end])? {
+            0 => return Err(STREAM_STALLED.to_string()),
+            taken => written += taken,
+        }
+    }
+    Ok(written)
+}
+
+/// Backend that plays nothing and keeps every frame written to it
+///
+/// Streams take as many channels as asked for; stereo frames are kept
+/// interleaved. Clones share the captured frames, so keep one to inspect after handing
+/// another to the engine.
+///
+/// # Example
+/// ```
+/// use kokoro_tiny::output::NullOutput;
+/// use kokoro_tiny::TtsEngine;
+/// use std::sync::Arc;
+///
+/// let output = NullOutput::new();
+/// let mut tts = TtsEngine::without_model();
+/// tts.set_output(Arc::new(output.clone()));
+///
+/// tts.play(&[0.5; 2400], 0.5)?;
+/// assert_eq!(output.frames(), vec![0.25; 2400]);
+/// # Ok::<(), String>(())
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct NullOutput {
+    frames: Arc<Mutex<Vec<f32>>>,
+    opened: Arc<AtomicUsize>,
+    sample_rate: Option<u32>,
+    realtime: bool,
+    stalled: bool,
+}
+
+impl NullOutput {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Run streams at `sample_rate` whatever the caller asks for
+    pub fn with_sample_rate(mut self, sample_rate: u32) -> Self {
+        self.sample_rate = Some(sample_rate);
+        self
+    }
+
+    /// Take as long to write as the frames would take to play
+    pub fn realtime(mut self) -> Self {
+        self.realtime = true;
+        self
+    }
+
+    /// Take no frames at all, like a device that stopped asking for audio
+    pub fn stalled(mut self) -> Self {
+        self.stalled = true;
+        self
+    }
+
+    /// Everything written so far, across all streams
+    pub fn frames(&self) -> Vec<f32> {
+        self.frames
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Number of streams opened
+    pub fn opened(&self) -> usize {
+        self.opened.load(Ordering::SeqCst)
+    }
+}
+
+impl AudioOutput for NullOutput {
+    fn open(&self, spec: &OutputSpec) -> Result<Box<dyn AudioStreamOut>, String> {
+        self.opened.fetch_add(1, Ordering::SeqCst);
+        let sample_rate = self.sample_rate.unwrap_or(spec.sample_rate);
+        Ok(Box::new(NullStream {
+            frames: self.frames.clone(),
+            realtime: self.realtime,
+            stalled: self.stalled,
+            info: StreamInfo {
+                device: "null".to_string(),
+                sample_rate,
+                channels: spec.channels.max(1),
+                buffer_frames: spec.buffer.map_or(0, |b| b.frames(sample_rate)),
+                ..StreamInfo::default()
+            },
+        }))
+    }
+}
+
+struct NullStream {
+    frames: Arc<Mutex<Vec<f32>>>,
+    realtime: bool,
+    stalled: bool,
+    info: StreamInfo,
+}
+
+impl AudioStreamOut for NullStream {
+    fn write(&mut self, frames: &[f32]) -> Result<usize, String> {
+        if self.stalled {
+            return Ok(0);
+        }
+        if self.realtime {
+            let rate = self.info.sample_rate.max(1) as f64 * f64::from(self.info.channels);
+            std::thread::sleep(Duration::from_secs_f64(frames.len() as f64 / rate));
+        }
+        self.frames
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .extend_from_slice(frames);
+        Ok(frames.len())
+    }
+
+    fn flush(&mut self) {}
+
+    fn info(&self) -> StreamInfo {
+        self.info.clone()
+    }
+}
+
+/// The output device called `name`, or the system default
+#[cfg(feature = "playback")]
+pub(crate) fn find_device(name: Option<&str>) -> Result<cpal::Device, String> {
+    use cpal::traits::{DeviceTrait, HostTrait};
+
+    let host = cpal::default_host();
+    match name {
+        Some(name) => host
+            .output_devices()
+            .map_err(|e| format!("Failed to get output devices: {}", e))?
+            .find(|device| device.name().is_ok_and(|n| n == name))
+            .ok_or_else(|| format!("Audio device '{}' not found", name)),
+        None => host
+            .default_output_device()
+            .ok_or_else(|| "No default audio output device".to_string()),
+    }
+}
+
+#[cfg(feature = "playback")]
+pub use self::cpal_output::CpalOutput;
+#[cfg(feature = "playback")]
+pub use self::rodio_output::RodioOutput;
+
+#[cfg(feature = "playback")]
+mod cpal_output {
+    use super::{find_device, AudioOutput, AudioStreamOut, OutputSpec, StreamInfo};
+    use crate::playback::{negotiate_buffer, BufferPreset, BufferRange};
+    use cpal::traits::{DeviceTrait, StreamTrait};
+    use cpal::Sample;
+    use std::collections::VecDeque;
+    use std::sync::{Arc, Condvar, Mutex};
+    use std::time::{Duration, Instant};
+
+    // Callback gaps longer than this many buffer periods count as underruns
+    const UNDERRUN_GAP_FACTOR: f64 = 1.8;
+
+    // Frames queued ahead of the device: at least this long, so a busy writer
+    // thread doesn't starve it, and short enough that pausing is prompt
+    const QUEUE_MIN_MS: u32 = 50;
+
+    /// cpal output with a chosen buffer size, on a named device or the default
+    ///
+    /// A spec without a buffer gets `BufferPreset::platform_default` for the
+    /// device. Streams run at the device's default rate. Stereo goes to the
+    /// device's first two channels (and their average to any others); a mono
+    /// device gets mono streams. Devices that take i16 or u16 samples get
+    /// them converted.
+    #[derive(Clone, Debug, Default)]
+    pub struct CpalOutput {
+        device: Option<String>,
+    }
+
+    impl CpalOutput {
+        /// `None` is the system default device
+        pub fn new(device: Option<String>) -> Self {
+            Self { device }
+        }
+    }
+
+    #[derive(Default)]
+    struct Shared {
+        queue: VecDeque<f32>,
+        first_callback: Option<Instant>,
+        last_callback: Option<Instant>,
+        callback_frames: u32,
+        underruns: u32,
+    }
+
+    struct CpalStream {
+        // Held for its Drop: closing the stream stops the device
+        _stream: cpal::Stream,
+        shared: Arc<(Mutex<Shared>, Condvar)>,
+        capacity: usize,
+        opened: Instant,
+        period: Duration,
+        info: StreamInfo,
+    }
+
+    impl AudioOutput for CpalOutput {
+        fn open(&self, spec: &OutputSpec) -> Result<Box<dyn AudioStreamOut>, String> {
+            let device = find_device(self.device.as_deref())?;
+            let device_name = device.name().unwrap_or_else(|_| "unknown".to_string());
+
+            let default_config = device
+                .default_output_config()
+                .map_err(|e| format!("Failed to query output config: {}", e))?;
+            let sample_rate = default_config.sample_rate().0;
+            let channels = default_config.channels() as usize;
+            let configs: Vec<_> = device
+                .supported_output_configs()
+                .map(|configs| configs.collect())
+                .unwrap_or_default();
+            let supported = supported_buffer(&configs, &default_config);
+
+            let preset = spec
+                .buffer
+                .unwrap_or_else(|| BufferPreset::platform_default(Some(&device_name)));
+            let buffer_frames = negotiate_buffer(preset.frames(sample_rate), supported);
+            // Samples per frame taken from the queue
+            let input = usize::from(spec.channels.clamp(1, 2)).min(channels);
+            let config = cpal::StreamConfig {
+                channels: channels as u16,
+                sample_rate: cpal::SampleRate(sample_rate),
+                buffer_size: cpal::BufferSize::Fixed(buffer_frames),
+            };
+            let period = Duration::from_secs_f64(buffer_frames as f64 / sample_rate as f64);
+
+            let shared = Arc::new((Mutex::new(Shared::default()), Condvar::new()));
+            // One callback's worth of frames from the queue, as f32
+            let fill = {
+                let shared = shared.clone();
+                move |data: &mut [f32]| {
+                    let (state, drained) = &*shared;
+                    let Ok(mut state) = state.lock() else {
+                        data.fill(0.0);
+                        return;
+                    };
+                    let now = Instant::now();
+                    state.first_callback.get_or_insert(now);
+                    state.callback_frames = (data.len() / channels) as u32;
+                    if let Some(last) = state.last_callback {
+                        let expected = period.max(Duration::from_secs_f64(
+                            state.callback_frames as f64 / sample_rate as f64,
+                        ));
+                        if now - last > expected.mul_f64(UNDERRUN_GAP_FACTOR) {
+                            state.underruns += 1;
+                        }
+                    }
+                    state.last_callback = Some(now);
+
+                    for frame in data.chunks_mut(channels) {
+                        if input == 1 {
+                            frame.fill(state.queue.pop_front().unwrap_or(0.0));
+                            continue;
+                        }
+                        let left = state.queue.pop_front().unwrap_or(0.0);
+                        let right = state.queue.pop_front().unwrap_or(0.0);
+                        frame.fill((left + right) / 2.0);
+                        frame[0] = left;
+                        frame[1] = right;
+                    }
+                    drained.notify_all();
+                }
+            };
+            let stream = match default_config.sample_format() {
+                cpal::SampleFormat::F32 => build_stream::<f32>(&device, &config, fill),
+                cpal::SampleFormat::I16 => build_stream::<i16>(&device, &config, fill),
+                cpal::SampleFormat::U16 => build_stream::<u16>(&device, &config, fill),
+                format => return Err(format!("Unsupported output sample format: {}", format)),
+            }
+            .map_err(|e| format!("Failed to open output stream: {}", e))?;
+
+            let opened = Instant::now();
+            stream
+                .play()
+                .map_err(|e| format!("Failed to start output stream: {}", e))?;
+
+            let queue_min = (sample_rate * QUEUE_MIN_MS / 1000) as usize;
+            Ok(Box::new(CpalStream {
+                _stream: stream,
+                shared,
+                capacity: (buffer_frames as usize * 4).max(queue_min) * input,
+                opened,
+                period,
+                info: StreamInfo {
+                    device: device_name,
+                    sample_rate,
+                    channels: input as u16,
+                    buffer_frames,
+                    ..StreamInfo::default()
+                },
+            }))
+        }
+    }
+
+    // Stream of the device's own sample type, filled as f32 by `fill` and
+    // converted in the callback
+    fn build_stream<T>(
+        device: &cpal::Device,
+        config: &cpal::StreamConfig,
+        mut fill: impl FnMut(&mut [f32]) + Send + 'static,
+    ) -> Result<cpal::Stream, cpal::BuildStreamError>
+    where
+        T: cpal::SizedSample + cpal::FromSample<f32>,
+    {
+        let mut frames = Vec::new();
+        device.build_output_stream(
+            config,
+            move |data: &mut [T], _: &cpal::OutputCallbackInfo| {
+                frames.resize(data.len(), 0.0);
+                fill(&mut frames);
+                for (out, &sample) in data.iter_mut().zip(&frames) {
+                    *out = T::from_sample(sample);
+                }
+            },
+            |e| eprintln!("⚠️ Audio stream error: {}", e),
+            None,
+        )
+    }
+
+    /// Buffer limits of the supported config the default one falls in
+    ///
+    /// The default config's own range is often `Unknown` even when the device
+    /// lists limits for that rate, channel count and format.
+    fn supported_buffer(
+        configs: &[cpal::SupportedStreamConfigRange],
+        default: &cpal::SupportedStreamConfig,
+    ) -> BufferRange {
+        let rate = default.sample_rate();
+        configs
+            .iter()
+            .filter(|c| c.channels() == default.channels())
+            .filter(|c| c.sample_format() == default.sample_format())
+            .filter(|c| c.min_sample_rate() <= rate && rate <= c.max_sample_rate())
+            .map(|c| buffer_range(c.buffer_size()))
+            .find(|range| *range != BufferRange::Unknown)
+            .unwrap_or_else(|| buffer_range(default.buffer_size()))
+    }
+
+    fn buffer_range(size: &cpal::SupportedBufferSize) -> BufferRange {
+        match size {
+            cpal::SupportedBufferSize::Range { min, max } => BufferRange::Range {
+                min: *min,
+                max: *max,
+            },
+            cpal::SupportedBufferSize::Unknown => BufferRange::Unknown,
+        }
+    }
+
+    impl AudioStreamOut for CpalStream {
+        fn write(&mut self, frames: &[f32]) -> Result<usize, String> {
+            let (state, drained) = &*self.shared;
+            let mut state = state
+                .lock()
+                .map_err(|e| format!("Output queue poisoned: {}", e))?;
+            // A device that stops calling back must not hang the writer
+            let deadline = Instant::now() + self.period * 4 + Duration::from_millis(100);
+            while state.queue.len() >= self.capacity {
+                let now = Instant::now();
+                if now >= deadline {
+                    return Ok(0);
+                }
+                state = drained
+                    .wait_timeout(state, deadline - now)
+                    .map_err(|e| format!("Output queue poisoned: {}", e))?
+                    .0;
+            }
+            let channels = usize::from(self.info.channels);
+            let n = frames.len().min(self.capacity - state.queue.len()) / channels * channels;
+            state.queue.extend(&frames[..n]);
+            Ok(n)
+        }
+
+        fn flush(&mut self) {
+            let (state, drained) = &*self.shared;
+            let deadline = Instant::now() + Duration::from_secs(5);
+            let Ok(mut state) = state.lock() else {
+                return;
+            };
+            while !state.queue.is_empty() && Instant::now() < deadline {
+                state = match drained.wait_timeout(state, Duration::from_millis(20)) {
+                    Ok((state, _)) => state,
+                    Err(_) => return,
+                };
+            }
+            drop(state);
+            // Let the device play out its own buffer before the stream closes
+            std::thread::sleep(self.period * 2);
+        }
+
+        fn info(&self) -> StreamInfo {
+            let mut info = self.info.clone();
+            if let Ok(state) = self.shared.0.lock() {
+                info.callback_frames = state.callback_frames;
+                info.underruns = state.underruns;
+                info.startup_latency = state
+                    .first_callback
+                    .map(|first| first.saturating_duration_since(self.opened))
+                    .unwrap_or_default();
+            }
+            info
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use cpal::{SampleFormat, SampleRate, SupportedBufferSize, SupportedStreamConfigRange};
+
+        fn range(
+            channels: u16,
+            rates: (u32, u32),
+            buffer: SupportedBufferSize,
+            format: SampleFormat,
+        ) -> SupportedStreamConfigRange {
+            SupportedStreamConfigRange::new(
+                channels,
+                SampleRate(rates.0),
+                SampleRate(rates.1),
+                buffer,
+                format,
+            )
+        }
+
+        #[test]
+        fn negotiation_uses_the_limits_of_the_matching_config() {
+            let configs = [
+                range(
+                    1,
+                    (8_000, 96_000),
+                    SupportedBufferSize::Range { min: 64, max: 512 },
+                    SampleFormat::F32,
+                ),
+                range(
+                    2,
+                    (8_000, 44_100),
+                    SupportedBufferSize::Range { min: 32, max: 256 },
+                    SampleFormat::F32,
+                ),
+                range(
+                    2,
+                    (8_000, 96_000),
+                    SupportedBufferSize::Range {
+                        min: 128,
+                        max: 8192,
+                    },
+                    SampleFormat::I16,
+                ),
+                range(
+                    2,
+                    (8_000, 96_000),
+                    SupportedBufferSize::Unknown,
+                    SampleFormat::F32,
+                ),
+                range(
+                    2,
+                    (44_100, 48_000),
+                    SupportedBufferSize::Range {
+                        min: 512,
+                        max: 2048,
+                    },
+                    SampleFormat::F32,
+                ),
+            ];
+            let default = configs[4].with_sample_rate(SampleRate(48_000));
+
+            let supported = supported_buffer(&configs, &default);
+            assert_eq!(
+                supported,
+                BufferRange::Range {
+                    min: 512,
+                    max: 2048
+                }
+            );
+            assert_eq!(
+                negotiate_buffer(BufferPreset::LowLatency.frames(48_000), supported),
+                512
+            );
+            assert_eq!(
+                negotiate_buffer(BufferPreset::Safe.frames(48_000), supported),
+                2048
+            );
+        }
+
+        #[test]
+        fn negotiation_falls_back_to_the_default_config() {
+            let configs = [range(
+                1,
+                (8_000, 96_000),
+                SupportedBufferSize::Range { min: 64, max: 512 },
+                SampleFormat::F32,
+            )];
+            let stereo = range(
+                2,
+                (48_000, 48_000),
+                SupportedBufferSize::Unknown,
+                SampleFormat::F32,
+            )
+            .with_sample_rate(SampleRate(48_000));
+            assert_eq!(supported_buffer(&configs, &stereo), BufferRange::Unknown);
+            assert_eq!(supported_buffer(&[], &stereo), BufferRange::Unknown);
+            assert_eq!(negotiate_buffer(1024, supported_buffer(&[], &stereo)), 1024);
+        }
+    }
+}
+
+#[cfg(feature = "playback")]
+mod rodio_output {
+    use super::{find_device, AudioOutput, AudioStreamOut, OutputSpec, StreamInfo};
+    use rodio::buffer::SamplesBuffer;
+    use rodio::{OutputStream, Sink};
+    use std::time::Duration;
+
+    // Blocks queued in the sink before `write` waits
+    const MAX_QUEUED: usize = 3;
+
+    /// rodio output on a named device or the default
+    ///
+    /// rodio resamples and maps channels to the device itself, so streams run
+    /// at whatever rate and channel count the caller asks for. The buffer size
+    /// is rodio's choice.
+    #[derive(Clone, Debug, Default)]
+    pub struct RodioOutput {
+        device: Option<String>,
+    }
+
+    impl RodioOutput {
+        /// `None` is the system default device
+        pub fn new(device: Option<String>) -> Self {
+            Self { device }
+        }
+    }
+
+    struct RodioStream {
+        // Held for its Drop, like the sink: dropping either stops playback
+        _stream: OutputStream,
+        sink: Sink,
+        info: StreamInfo,
+    }
+
+    impl AudioOutput for RodioOutput {
+        fn open(&self, spec: &OutputSpec) -> Result<Box<dyn AudioStreamOut>, String> {
+            let (stream, handle) = match &self.device {
+                Some(name) => OutputStream::try_from_device(&find_device(Some(name))?)
+                    .map_err(|e| format!("Failed to open device '{}': {}", name, e))?,
+                None => OutputStream::try_default()
+                    .map_err(|e| format!("Failed to get audio output: {}", e))?,
+            };
+            let sink = Sink::try_new(&handle)
+                .map_err(|e| format!("Failed to create audio sink: {}", e))?;
+            Ok(Box::new(RodioStream {
+                _stream: stream,
+                sink,
+                info: StreamInfo {
+                    device: self.device.clone().unwrap_or_else(|| "default".to_string()),
+                    sample_rate: spec.sample_rate,
+                    channels: spec.channels.clamp(1, 2),
+                    ..StreamInfo::default()
+                },
+            }))
+        }
+    }
+
+    impl AudioStreamOut for RodioStream {
+        fn write(&mut self, frames: &[f32]) -> Result<usize, String> {
+            while self.sink.len() >= MAX_QUEUED {
+                std::thread::sleep(Duration::from_millis(5));
+            }
+            let channels = usize::from(self.info.channels);
+            let whole = frames.len() / channels * channels;
+            self.sink.append(SamplesBuffer::new(
+                self.info.channels,
+                self.info.sample_rate,
+                frames[..whole].to_vec(),
+            ));
+            Ok(whole)
+        }
+
+        fn flush(&mut self) {
+            self.sink.sleep_until_end();
+        }
+
+        fn info(&self) -> StreamInfo {
+            self.info.clone()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn null_output_keeps_frames_across_clones_and_streams() {
+        let output = NullOutput::new();
+        let spec = OutputSpec {
+            sample_rate: 24_000,
+            buffer: Some(BufferPreset::Balanced),
+            channels: 1,
+        };
+
+        let mut first = output.clone().open(&spec).unwrap();
+        assert_eq!(first.write(&[0.1, 0.2]).unwrap(), 2);
+        first.flush();
+        let mut second = output.open(&spec).unwrap();
+        second.write(&[0.3]).unwrap();
+
+        assert_eq!(output.frames(), vec![0.1, 0.2, 0.3]);
+        assert_eq!(output.opened(), 2);
+        let info = second.info();
+        assert_eq!(info.device, "null");
+        assert_eq!(info.sample_rate, 24_000);
+        assert_eq!(info.buffer_frames, 512);
+
+        let fixed = NullOutput::new().with_sample_rate(48_000);
+        assert_eq!(fixed.open(&spec).unwrap().info().sample_rate, 48_000);
+        let stereo = OutputSpec {
+            channels: 2,
+            ..spec
+        };
+        assert_eq!(output.open(&stereo).unwrap().info().channels, 2);
+    }
+
+    #[test]
+    fn write_until_writes_in_blocks_and_stops_early() {
+        let output = NullOutput::new();
+        let spec = OutputSpec {
+            sample_rate: 1_000,
+            buffer: None,
+            channels: 1,
+        };
+        let mut stream = output.open(&spec).unwrap();
+        let frames: Vec<f32> = (0..95).map(|i| i as f32).collect();
+
+        assert_eq!(write_until(&mut *stream, &frames, &|| false).unwrap(), 95);
+        assert_eq!(output.frames(), frames);
+
+        // Stop after the second 20-frame block
+        let blocks = AtomicUsize::new(0);
+        let stop = || blocks.fetch_add(1, Ordering::SeqCst) >= 2;
+        assert_eq!(write_until(&mut *stream, &frames, &stop).unwrap(), 40);
+
+        let mut stalled = NullOutput::new().stalled().open(&spec).unwrap();
+        assert_eq!(
+            write_until(&mut *stalled, &frames, &|| false).unwrap_err(),
+            STREAM_STALLED
+        );
+    }
+
+    #[test]
+    fn realtime_null_output_takes_as_long_as_playing() {
+        let output = NullOutput::new().realtime();
+        let spec = OutputSpec {
+            sample_rate: 1_000,
+            buffer: None,
+            channels: 1,
+        };
+        let start = std::time::Instant::now();
+        output.open(&spec).unwrap().write(&[0.0; 50]).unwrap();
+        assert!(start.elapsed() >= Duration::from_millis(50));
+    }
+}
@@ -0,0 +1,561 @@
+//! Named pacing presets for long-form synthesis
+//!
+//! Chunk size, crossfade, gaps between chunks and paragraphs, and punctuation
+//! weights all change how synthesized speech flows. A `PacingPreset` picks
+//! all of them at once; options set explicitly on `SynthesizeOptions` still
+//! win over the preset.
+//!
+//! | Preset         | Chunk chars | Crossfade | Chunk gap | Paragraph gap | Punctuation weights         |
+//! |----------------|-------------|-----------|-----------|---------------|-----------------------------|
+//! | (none)         | 180         | 45 ms     | 0 ms      | 600 ms        | 1.0                         |
+//! | Conversational | 100         | 20 ms     | 0 ms      | 250 ms        | 1.0                         |
+//! | Broadcast      | 180         | 0 ms      | 150 ms    | 400 ms        | 1.0                         |
+//! | Audiobook      | 180         | 45 ms     | 0 ms      | 900 ms        | comma 1.5, others 2.0       |
+//!
+//! `SynthesizeOptions::chunk_max_chars`, `crossfade_ms` and
+//! `paragraph_gap_ms` override the preset's values; chunks must be at least 20 chars and crossfades under
+//! 500 ms.
+//!
+//! A paragraph is text between blank lines. Paragraphs are hard boundaries:
+//! chunked separately and joined with silence instead of a crossfade (a
+//! paragraph gap of 0 runs them together as before).
+//!
+//! The model's own pauses after punctuation shrink at higher speeds and are
+//! barely there after commas. `SynthesizeOptions::pauses` takes them out of
+//! its hands: text is split into clauses, each rendered on its own and joined
+//! with the `PauseConfig` silence for the punctuation that ended it (at least
+//! the preset's chunk gap), and paragraphs with its `paragraph_ms`.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    AudioDuration, KokoroError, PunctuationWeights, CHUNK_CROSSFADE_MS, MAX_CHARS_PER_CHUNK,
+    SAMPLE_RATE,
+};
+
+const DEFAULT_CROSSFADE_MS: u32 = CHUNK_CROSSFADE_MS as u32;
+const DEFAULT_PARAGRAPH_GAP_MS: u32 = 600;
+// Shorter chunks lose the context the model needs for natural prosody
+const MIN_CHUNK_CHARS: usize = 20;
+// Longer overlaps audibly double the speech
+const MAX_CROSSFADE_MS: u32 = 500;
+
+/// Silence inserted at clause boundaries, see `SynthesizeOptions::pauses`
+///
+/// A duration of 0 leaves that boundary to the model (the clauses aren't
+/// split there).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct PauseConfig {
+    /// After `,` `;` `:`
+    pub comma_ms: u32,
+    /// After `.` `!` `?` `…`
+    pub sentence_ms: u32,
+    /// Between paragraphs (blank lines)
+    pub paragraph_ms: u32,
+}
+
+impl Default for PauseConfig {
+    fn default() -> Self {
+        Self {
+            comma_ms: 120,
+            sentence_ms: 300,
+            paragraph_ms: 500,
+        }
+    }
+}
+
+impl PauseConfig {
+    /// Silence after `clause`, by the punctuation it ends with (closing
+    /// quotes and brackets skipped); 0 if it doesn't end in any
+    pub fn after(&self, clause: &str) -> u32 {
+        let clause = clause.trim_end_matches(|c: char| {
+            c.is_whitespace() || matches!(c, '"' | '\'' | ')' | ']' | '”' | '’' | '»')
+        });
+        match clause.chars().last() {
+            Some(',' | ';' | ':') => self.comma_ms,
+            Some('.' | '!' | '?' | '…') => self.sentence_ms,
+            _ => 0,
+        }
+    }
+}
+
+/// Pacing tuned for a use case (see the module docs for exact values)
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PacingPreset {
+    /// Assistants and notifications: small chunks, short pauses, light crossfade
+    Conversational,
+    /// Announcements: uniform gaps between chunks, no crossfade
+    Broadcast,
+    /// Long reads: paragraph-aware with long pauses
+    Audiobook,
+}
+
+/// How chunks are sized and joined
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Pacing {
+    /// Longest chunk handed to the model, in characters
+    pub chunk_max_chars: usize,
+    /// Overlap between neighbouring chunks; unused where a gap is inserted
+    pub crossfade_ms: u32,
+    /// Silence between chunks of one paragraph (0 = crossfade instead)
+    pub chunk_gap_ms: u32,
+    /// Silence between paragraphs (0 = paragraphs are not treated specially)
+    pub paragraph_gap_ms: u32,
+}
+
+impl Default for Pacing {
+    fn default() -> Self {
+        Self {
+            chunk_max_chars: MAX_CHARS_PER_CHUNK,
+            crossfade_ms: DEFAULT_CROSSFADE_MS,
+            chunk_gap_ms: 0,
+            paragraph_gap_ms: DEFAULT_PARAGRAPH_GAP_MS,
+        }
+    }
+}
+
+impl Pacing {
+    pub fn crossfade_samples(&self) -> usize {
+        ms_to_samples(self.crossfade_ms)
+    }
+
+    /// Chunks of at least 20 chars, crossfades under 500 ms
+    pub fn check(&self) -> Result<(), KokoroError> {
+        if self.chunk_max_chars < MIN_CHUNK_CHARS {
+            return Err(KokoroError::InvalidInput(format!(
+                "chunk_max_chars must be at least {} (got {})",
+                MIN_CHUNK_CHARS, self.chunk_max_chars
+            )));
+        }
+        if self.crossfade_ms >= MAX_CROSSFADE_MS {
+            return Err(KokoroError::InvalidInput(format!(
+                "crossfade_ms must be under {} (got {})",
+                MAX_CROSSFADE_MS, self.crossfade_ms
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl PacingPreset {
+    pub fn pacing(self) -> Pacing {
+        match self {
+            PacingPreset::Conversational => Pacing {
+                chunk_max_chars: 100,
+                crossfade_ms: 20,
+                chunk_gap_ms: 0,
+                paragraph_gap_ms: 250,
+            },
+            PacingPreset::Broadcast => Pacing {
+                chunk_max_chars: MAX_CHARS_PER_CHUNK,
+                crossfade_ms: 0,
+                chunk_gap_ms: 150,
+                paragraph_gap_ms: 400,
+            },
+            PacingPreset::Audiobook => Pacing {
+                chunk_max_chars: MAX_CHARS_PER_CHUNK,
+                crossfade_ms: DEFAULT_CROSSFADE_MS,
+                chunk_gap_ms: 0,
+                paragraph_gap_ms: 900,
+            },
+        }
+    }
+
+    pub fn punctuation_weights(self) -> PunctuationWeights {
+        match self {
+            PacingPreset::Conversational | PacingPreset::Broadcast => PunctuationWeights::default(),
+            PacingPreset::Audiobook => PunctuationWeights {
+                comma: 1.5,
+                period: 2.0,
+                question: 2.0,
+                exclamation: 2.0,
+                ellipsis: 2.0,
+            },
+        }
+    }
+}
+
+/// Blank-line separated paragraphs, trimmed, empty ones dropped
+pub(crate) fn paragraphs(text: &str) -> Vec<&str> {
+    let mut paragraphs = Vec::new();
+    let mut start = 0;
+    let mut offset = 0;
+    let mut blank = false;
+
+    for line in text.split_inclusive('\n') {
+        if line.trim().is_empty() {
+            blank = true;
+        } else if blank {
+            paragraphs.push(&text[start..offset]);
+            start = offset;
+            blank = false;
+        }
+        offset += line.len();
+    }
+    paragraphs.push(&text[start..]);
+
+    paragraphs
+        .into_iter()
+        .map(str::trim)
+        .filter(|p| !p.is_empty())
+        .collect()
+}
+
+// `chunk` split where `pauses` puts silence; an abbreviation's period
+// ("Dr. Smith") doesn't end a clause
+pub(crate) fn pause_clauses(
+    chunk: &str,
+    pauses: &PauseConfig,
+    abbreviations: &[String],
+) -> Vec<String> {
+    let mut clauses: Vec<String> = Vec::new();
+    let mut open = false;
+    for clause in crate::timestamps::split_clauses(chunk) {
+        match clauses.last_mut() {
+            Some(last) if open => {
+                last.push(' ');
+                last.push_str(&clause);
+            }
+            _ => clauses.push(clause),
+        }
+        let last = clauses.last().map(String::as_str).unwrap_or_default();
+        open = pauses.after(last) == 0 || crate::ends_with_abbreviation(last, abbreviations);
+    }
+    clauses
+}
+
+pub(crate) fn ms_to_samples(ms: u32) -> usize {
+    AudioDuration::from_millis(ms.into()).to_samples(SAMPLE_RATE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ProgressStage, SynthesisPlan, SynthesizeOptions, TtsEngine};
+
+    const ARTICLE: &str = "The harbour wakes slowly. Gulls argue over the nets, and the first ferry sounds its horn across the water.\n\n\
+        By noon the market is full. Fishmongers shout prices, children dart between the stalls, and nobody is in a hurry.\n\n\
+        Evening comes in orange. The boats return one by one, and the town settles back into its quiet routine.";
+
+    #[test]
+    fn presets_resolve_to_documented_values() {
+        let resolved = |preset| {
+            let plan = SynthesisPlan::new(ARTICLE, &SynthesizeOptions::new().pacing(preset));
+            (plan.pacing, plan.punctuation_weights)
+        };
+
+        assert_eq!(
+            resolved(PacingPreset::Conversational),
+            (
+                Pacing {
+                    chunk_max_chars: 100,
+                    crossfade_ms: 20,
+                    chunk_gap_ms: 0,
+                    paragraph_gap_ms: 250
+                },
+                PunctuationWeights::default()
+            )
+        );
+        assert_eq!(
+            resolved(PacingPreset::Broadcast),
+            (
+                Pacing {
+                    chunk_max_chars: 180,
+                    crossfade_ms: 0,
+                    chunk_gap_ms: 150,
+                    paragraph_gap_ms: 400
+                },
+                PunctuationWeights::default()
+            )
+        );
+        assert_eq!(
+            resolved(PacingPreset::Audiobook),
+            (
+                Pacing {
+                    chunk_max_chars: 180,
+                    crossfade_ms: 45,
+                    chunk_gap_ms: 0,
+                    paragraph_gap_ms: 900
+                },
+                PunctuationWeights {
+                    comma: 1.5,
+                    period: 2.0,
+                    question: 2.0,
+                    exclamation: 2.0,
+                    ellipsis: 2.0
+                }
+            )
+        );
+
+        // No preset still keeps paragraphs apart
+        let plain = SynthesisPlan::new(ARTICLE, &SynthesizeOptions::new());
+        assert_eq!(plain.pacing, Pacing::default());
+        assert_eq!(plain.pacing.paragraph_gap_ms, 600);
+        assert_eq!(plain.paragraph_breaks.len(), 2);
+    }
+
+    #[test]
+    fn explicit_options_beat_the_preset() {
+        let heavy_commas = PunctuationWeights {
+            comma: 3.0,
+            ..Default::default()
+        };
+        let opts = SynthesizeOptions::new()
+            .punctuation_weights(heavy_commas)
+            .pacing(PacingPreset::Audiobook);
+        let plan = SynthesisPlan::new(ARTICLE, &opts);
+        assert_eq!(plan.punctuation_weights, heavy_commas);
+        assert_eq!(plan.pacing.paragraph_gap_ms, 900);
+    }
+
+    #[test]
+    fn paragraphs_are_chunked_separately() {
+        assert_eq!(paragraphs("a\n\n  \nb\nc\n\n"), ["a", "b\nc"]);
+
+        let plan = SynthesisPlan::new(
+            ARTICLE,
+            &SynthesizeOptions::new().pacing(PacingPreset::Broadcast),
+        );
+        assert_eq!(plan.paragraph_breaks.len(), 2);
+        for &start in &plan.paragraph_breaks {
+            let first_word = plan.chunks[start].split_whitespace().next().unwrap();
+            assert!(
+                ["By", "Evening"].contains(&first_word),
+                "{}",
+                plan.chunks[start]
+            );
+        }
+    }
+
+    #[test]
+    fn paragraphs_are_hard_boundaries() {
+        let engine = engine();
+        let mut reports = Vec::new();
+        let audio = engine
+            .synthesize_with_progress(
+                ARTICLE,
+                SynthesizeOptions::new().paragraph_gap_ms(700),
+                |report| reports.push(report),
+            )
+            .unwrap();
+
+        // Chunks within a paragraph are crossfaded, paragraphs kept apart
+        assert_eq!(gaps(&audio), [ms_to_samples(700); 2]);
+
+        let started: Vec<(usize, usize)> = reports
+            .iter()
+            .filter(|r| r.stage == ProgressStage::ChunkStarted)
+            .map(|r| (r.chunk, r.paragraph))
+            .collect();
+        let plan = SynthesisPlan::new(ARTICLE, &SynthesizeOptions::new());
+        assert_eq!(started.len(), plan.chunks.len());
+        assert!(started
+            .iter()
+            .all(|&(chunk, p)| p == plan.paragraph_of(chunk)));
+        assert_eq!(started.last().map(|&(_, p)| p), Some(2));
+        assert!(reports.iter().all(|r| r.total_paragraphs == 3));
+
+        // A gap of 0 runs them together again
+        let joined = engine
+            .synthesize_with(ARTICLE, SynthesizeOptions::new().paragraph_gap_ms(0))
+            .unwrap();
+        assert!(gaps(&joined).is_empty());
+    }
+
+    // Every token renders as 100 samples of tone, so silence only comes from gaps
+    fn engine() -> TtsEngine {
+        TtsEngine::mock(100)
+    }
+
+    // Silences between sounds; the samples the edge fades end on don't count
+    fn gaps(audio: &[f32]) -> Vec<usize> {
+        audio[1..audio.len() - 1]
+            .split(|s| s.abs() > 1e-6)
+            .map(<[f32]>::len)
+            .filter(|&len| len > 0)
+            .collect()
+    }
+
+    fn longest_silence(audio: &[f32]) -> usize {
+        audio
+            .split(|s| s.abs() > 1e-6)
+            .map(<[f32]>::len)
+            .max()
+            .unwrap_or(0)
+    }
+
+    #[test]
+    fn presets_sound_different() {
+        let engine = engine();
+        let mut durations = Vec::new();
+        for preset in [
+            PacingPreset::Conversational,
+            PacingPreset::Broadcast,
+            PacingPreset::Audiobook,
+        ] {
+            let audio = engine
+                .synthesize_with(ARTICLE, SynthesizeOptions::new().pacing(preset))
+                .unwrap();
+            assert_eq!(
+                longest_silence(&audio),
+                ms_to_samples(preset.pacing().paragraph_gap_ms),
+                "{:?}",
+                preset
+            );
+            durations.push(audio.len());
+        }
+
+        durations.sort();
+        durations.dedup();
+        assert_eq!(
+            durations.len(),
+            3,
+            "presets should differ in length: {:?}",
+            durations
+        );
+    }
+
+    #[test]
+    fn chunk_sizes_and_crossfade_can_be_set_per_call() {
+        let text = ARTICLE.replace("\n\n", " ");
+        assert!(text.chars().count() >= 300);
+        let chunks = |opts: SynthesizeOptions| SynthesisPlan::new(&text, &opts).chunks;
+
+        let default = chunks(SynthesizeOptions::new());
+        let short = chunks(SynthesizeOptions::new().chunk_max_chars(60));
+        assert!(short.len() > default.len(), "{:?}", short);
+        assert!(short.iter().all(|chunk| chunk.chars().count() <= 60));
+
+        // Short text goes through as written unless the threshold is lower
+        let note = "Gulls argue.\nThe ferry sounds its horn.";
+        let plan = |opts: SynthesizeOptions| SynthesisPlan::new(note, &opts).chunks;
+        assert_eq!(plan(SynthesizeOptions::new()), [note]);
+        assert_eq!(
+            plan(SynthesizeOptions::new().chunk_threshold(20)),
+            ["Gulls argue. The ferry sounds its horn."]
+        );
+        assert_eq!(plan(SynthesizeOptions::new().chunk_max_chars(30)).len(), 2);
+
+        // Explicit values beat the preset's
+        let plan = SynthesisPlan::new(
+            &text,
+            &SynthesizeOptions::new()
+                .pacing(PacingPreset::Conversational)
+                .chunk_max_chars(150)
+                .crossfade_ms(0),
+        );
+        assert_eq!(
+            (plan.pacing.chunk_max_chars, plan.pacing.crossfade_ms),
+            (150, 0)
+        );
+        assert_eq!(plan.pacing.paragraph_gap_ms, 250);
+
+        // Without the overlap the chunks play back to back
+        let engine = engine();
+        let render = |opts: SynthesizeOptions| engine.synthesize_with(&text, opts).unwrap().len();
+        let crossfaded = render(SynthesizeOptions::new());
+        let butted = render(SynthesizeOptions::new().crossfade_ms(0));
+        assert_eq!(
+            butted - crossfaded,
+            (default.len() - 1) * ms_to_samples(DEFAULT_CROSSFADE_MS)
+        );
+    }
+
+    #[test]
+    fn out_of_range_chunking_is_rejected() {
+        let engine = engine();
+        for opts in [
+            SynthesizeOptions::new().chunk_max_chars(19),
+            SynthesizeOptions::new().crossfade_ms(500),
+        ] {
+            let err = engine.synthesize_with(ARTICLE, opts.clone()).unwrap_err();
+            assert!(matches!(err, KokoroError::InvalidInput(_)), "{:?}", err);
+            assert!(engine.synthesize_stream(ARTICLE, opts).is_err());
+        }
+        engine
+            .synthesize_with(
+                ARTICLE,
+                SynthesizeOptions::new()
+                    .chunk_max_chars(20)
+                    .crossfade_ms(499),
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn pauses_split_clauses_but_not_abbreviations() {
+        let text = "Dr. Smith waved, then left. Nobody spoke!\n\nLater, rain.";
+        let plan = SynthesisPlan::new(
+            text,
+            &SynthesizeOptions::new().pauses(PauseConfig::default()),
+        );
+        assert_eq!(
+            plan.chunks,
+            [
+                "Dr. Smith waved,",
+                "then left.",
+                "Nobody spoke!",
+                "Later,",
+                "rain."
+            ]
+        );
+        assert_eq!(plan.paragraph_breaks, [3]);
+        let gaps: Vec<u32> = (0..plan.chunks.len()).map(|idx| plan.gap_ms(idx)).collect();
+        assert_eq!(gaps, [0, 120, 300, 500, 120]);
+
+        // A zero duration leaves that boundary to the model
+        let no_commas = PauseConfig {
+            comma_ms: 0,
+            ..PauseConfig::default()
+        };
+        let plan = SynthesisPlan::new(text, &SynthesizeOptions::new().pauses(no_commas));
+        assert_eq!(
+            plan.chunks,
+            [
+                "Dr. Smith waved, then left.",
+                "Nobody spoke!",
+                "Later, rain."
+            ]
+        );
+    }
+
+    #[test]
+    fn pauses_meet_their_minimums_at_any_speed() {
+        let engine = engine();
+        let text = "Hello there, friend. This is a test.\n\nA new paragraph begins, slowly.";
+        let pauses = PauseConfig {
+            comma_ms: 150,
+            sentence_ms: 350,
+            paragraph_ms: 600,
+        };
+        for speed in [1.0, 1.8] {
+            let audio = engine
+                .synthesize_with(text, SynthesizeOptions::new().speed(speed).pauses(pauses))
+                .unwrap();
+            let silences = gaps(&audio);
+            // comma, sentence, paragraph, comma
+            let expected = [150, 350, 600, 150].map(ms_to_samples);
+            assert_eq!(silences.len(), expected.len(), "{:?}", silences);
+            for (silence, minimum) in silences.iter().zip(expected) {
+                assert!(
+                    *silence >= minimum,
+                    "{} < {} at {}x",
+                    silence,
+                    minimum,
+                    speed
+                );
+            }
+            assert_eq!(longest_silence(&audio), ms_to_samples(pauses.paragraph_ms));
+        }
+
+        // Without pauses only the paragraph gap is left
+        let plain = engine
+            .synthesize_with(text, SynthesizeOptions::new())
+            .unwrap();
+        assert_eq!(
+            longest_silence(&plain),
+            ms_to_samples(Pacing::default().paragraph_gap_ms)
+        );
+    }
+}
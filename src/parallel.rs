@@ -0,0 +1,202 @@
+//! Many texts at once: `TtsEngine::synthesize_batch`
+//!
+//! Each worker thread takes the next item, phonemizes it and renders it, so
+//! one item's espeak pass overlaps another's inference. ONNX Runtime runs one
+//! inference per session at a time, so with `parallelism` above 1 the engine
+//! loads up to `parallelism - 1` more sessions of the same model file and
+//! inference goes to whichever session is idle (see `TtsEngine::run_session`).
+//! Each one is another copy of the model in memory (about 300MB for fp32), so
+//! they're dropped again when the batch finishes, and `parallelism` is capped
+//! at the number of CPUs: past that the sessions only compete for cores. An
+//! engine built from memory, or one where loading another fails, shares the
+//! sessions it has.
+
+use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use crate::{KokoroError, SynthesizeOptions, TtsEngine};
+
+impl TtsEngine {
+    /// Synthesize every item, `parallelism` at a time, results in input order
+    ///
+    /// `parallelism` is capped at the number of CPUs
+    /// ([`available_parallelism`](std::thread::available_parallelism)), and the
+    /// extra sessions it loads are freed before this returns.
+    ///
+    /// A failing item (unknown voice, malformed mix, ...) is an `Err` in its
+    /// slot; the others still render. With `parallelism` 1 the items render
+    /// one after another on the calling thread; otherwise on worker threads,
+    /// so `last_warnings` afterwards describes whichever finished last.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use kokoro_tiny::{SynthesizeOptions, TtsEngine};
+    ///
+    /// # async fn demo() -> Result<(), kokoro_tiny::KokoroError> {
+    /// let tts = TtsEngine::new().await?;
+    /// let prompts = vec![
+    ///     ("Press one for sales.".to_string(), SynthesizeOptions::new()),
+    ///     ("Press two for support.".to_string(), SynthesizeOptions::new().voice("am_adam")),
+    /// ];
+    /// for (i, audio) in tts.synthesize_batch(&prompts, 2).into_iter().enumerate() {
+    ///     match audio {
+    ///         Ok(audio) => tts.save_wav(&format!("prompt-{}.wav", i), &audio)?,
+    ///         Err(e) => eprintln!("prompt {} failed: {}", i, e),
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn synthesize_batch(
+        &self,
+        items: &[(String, SynthesizeOptions)],
+        parallelism: usize,
+    ) -> Vec<Result<Vec<f32>, KokoroError>> {
+        let workers = worker_count(parallelism, items.len());
+        if workers == 1 {
+            return items
+                .iter()
+                .map(|(text, opts)| self.synthesize_with(text, opts.clone()))
+                .collect();
+        }
+        self.add_spare_sessions(workers - 1);
+
+        let next = AtomicUsize::new(0);
+        let results = Mutex::new((0..items.len()).map(|_| None).collect::<Vec<_>>());
+        thread::scope(|scope| {
+            for _ in 0..workers {
+                scope.spawn(|| loop {
+                    let index = next.fetch_add(1, Ordering::Relaxed);
+                    let Some((text, opts)) = items.get(index) else {
+                        break;
+                    };
+                    let audio = self.synthesize_with(text, opts.clone());
+                    if let Ok(mut results) = results.lock() {
+                        results[index] = Some(audio);
+                    }
+                });
+            }
+        });
+        self.drop_spare_sessions();
+
+        results
+            .into_inner()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .into_iter()
+            .map(|audio| {
+                audio.unwrap_or_else(|| {
+                    Err(KokoroError::InferenceFailed(
+                        "Batch worker stopped before this item".to_string(),
+                    ))
+                })
+            })
+            .collect()
+    }
+
+    // Load sessions until there are `count` besides the main one; only for
+    // engines running a model file
+    fn add_spare_sessions(&self, count: usize) {
+        let Some((model_path, _)) = &self.model_files else {
+            return;
+        };
+        if self.is_fallback() || self.active_provider().is_none() {
+            return;
+        }
+        let Ok(mut spares) = self.spare_sessions.lock() else {
+            return;
+        };
+        while spares.len() < count {
+            match self.session_options.load_session(model_path) {
+                Ok((session, _)) => spares.push(Arc::new(Mutex::new(session))),
+                Err(e) => {
+                    #[cfg(not(feature = "as-lib"))]
+                    eprintln!(
+                        "⚠️ Batch continues on {} session(s): {}",
+                        spares.len() + 1,
+                        e
+                    );
+                    return;
+                }
+            }
+        }
+    }
+
+    // Free the sessions add_spare_sessions loaded; an inference still running
+    // on one keeps it alive until it's done (see run_session)
+    fn drop_spare_sessions(&self) {
+        if let Ok(mut spares) = self.spare_sessions.lock() {
+            spares.clear();
+        }
+    }
+}
+
+// Threads for a batch: no more than there are items or CPUs, at least one
+fn worker_count(parallelism: usize, items: usize) -> usize {
+    let cpus = thread::available_parallelism().map_or(1, NonZeroUsize::get);
+    parallelism.min(items).min(cpus).max(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::worker_count;
+    use crate::{KokoroError, SynthesizeOptions, TtsEngine, STYLE_DIM};
+
+    fn engine() -> TtsEngine {
+        let mut engine = TtsEngine::mock(10);
+        engine
+            .import_voice("am_adam", vec![0.2; STYLE_DIM])
+            .unwrap();
+        engine.mock_inference = Some(std::sync::Arc::new(|tokens: &[i64], style: &[f32], _| {
+            // Length tells the items apart, the value which voice rendered
+            Ok(vec![style[0]; tokens.len() * 10])
+        }));
+        engine
+    }
+
+    #[test]
+    fn items_fail_on_their_own_and_keep_their_order() {
+        let engine = engine();
+        let items: Vec<(String, SynthesizeOptions)> = [
+            ("Press one for sales.", "af_sky"),
+            ("Press two for support, or stay on the line.", "af_nope"),
+            ("Goodbye.", "am_adam"),
+            ("Your call is important to us.", "af_sky.5+"),
+            ("Please hold while we connect you.", "am_adam"),
+        ]
+        .iter()
+        .map(|(text, voice)| (text.to_string(), SynthesizeOptions::new().voice(voice)))
+        .collect();
+
+        for parallelism in [1, 3, 16] {
+            let results = engine.synthesize_batch(&items, parallelism);
+            assert_eq!(results.len(), items.len());
+            for ((text, opts), result) in items.iter().zip(&results) {
+                match opts.voice.as_deref() {
+                    Some("af_nope") => assert!(matches!(
+                        result,
+                        Err(KokoroError::VoiceNotFound { name, .. }) if name == "af_nope"
+                    )),
+                    Some("af_sky.5+") => {
+                        assert!(matches!(result, Err(KokoroError::InvalidVoice(_))))
+                    }
+                    _ => assert_eq!(
+                        result.as_ref().unwrap(),
+                        &engine.synthesize_with(text, opts.clone()).unwrap()
+                    ),
+                }
+            }
+        }
+        assert!(engine.synthesize_batch(&[], 4).is_empty());
+    }
+
+    #[test]
+    fn workers_are_capped_by_items_and_cpus() {
+        let cpus = std::thread::available_parallelism().unwrap().get();
+        assert_eq!(worker_count(0, 10), 1);
+        assert_eq!(worker_count(4, 0), 1);
+        assert_eq!(worker_count(16, 1), 1);
+        assert_eq!(worker_count(usize::MAX, 10_000), cpus.min(10_000));
+    }
+}
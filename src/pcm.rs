@@ -0,0 +1,128 @@
+//! Headerless PCM, for piping into other tools
+//!
+//! Raw samples have no header to say what they are, so the reader is told
+//! instead: the model's audio is 24kHz mono, e.g.
+//! `ffmpeg -f s16le -ar 24000 -ac 1 -i -` or `aplay -f S16_LE -r 24000 -c 1`.
+//! Samples are written little-endian whatever the machine's byte order.
+
+use std::io::{self, Write};
+
+// Samples converted per write, so long audio isn't copied whole first
+const BLOCK: usize = 4096;
+
+/// Sample encoding for `write_pcm`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PcmFormat {
+    /// 16-bit signed integers (`s16le` to ffmpeg, `S16_LE` to aplay)
+    #[default]
+    S16le,
+    /// 32-bit floats, unclipped (`f32le` to ffmpeg, `FLOAT_LE` to aplay)
+    F32le,
+}
+
+impl PcmFormat {
+    /// Bytes each sample takes
+    pub fn bytes_per_sample(self) -> usize {
+        match self {
+            PcmFormat::S16le => 2,
+            PcmFormat::F32le => 4,
+        }
+    }
+}
+
+/// `audio` as 16-bit little-endian samples, clipped to full scale the way
+/// `save_wav` does
+///
+/// # Example
+/// ```
+/// use kokoro_tiny::to_pcm_s16le;
+///
+/// let bytes = to_pcm_s16le(&[0.0, 0.5, -1.0]);
+/// assert_eq!(bytes, [0x00, 0x00, 0xff, 0x3f, 0x01, 0x80]);
+/// ```
+pub fn to_pcm_s16le(audio: &[f32]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(audio.len() * 2);
+    encode(audio, PcmFormat::S16le, &mut bytes);
+    bytes
+}
+
+/// Write `audio` to `w` as headerless `format` samples
+///
+/// # Example
+/// ```no_run
+/// use kokoro_tiny::{write_pcm, PcmFormat, TtsEngine};
+///
+/// # async fn demo() -> Result<(), Box<dyn std::error::Error>> {
+/// let mut tts = TtsEngine::new().await?;
+/// let audio = tts.synthesize("Hello", None, None, None)?;
+/// // | ffmpeg -f s16le -ar 24000 -ac 1 -i - hello.flac
+/// write_pcm(std::io::stdout().lock(), &audio, PcmFormat::S16le)?;
+/// # Ok(())
+/// # }
+/// ```
+pub fn write_pcm(mut w: impl Write, audio: &[f32], format: PcmFormat) -> io::Result<()> {
+    let mut bytes = Vec::with_capacity(BLOCK * format.bytes_per_sample());
+    for block in audio.chunks(BLOCK) {
+        bytes.clear();
+        encode(block, format, &mut bytes);
+        w.write_all(&bytes)?;
+    }
+    w.flush()
+}
+
+fn encode(audio: &[f32], format: PcmFormat, bytes: &mut Vec<u8>) {
+    match format {
+        PcmFormat::S16le => {
+            for &sample in audio {
+                let sample = (sample * 32767.0).clamp(-32768.0, 32767.0) as i16;
+                bytes.extend_from_slice(&sample.to_le_bytes());
+            }
+        }
+        PcmFormat::F32le => {
+            for &sample in audio {
+                bytes.extend_from_slice(&sample.to_le_bytes());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn s16le_is_two_little_endian_bytes_per_sample() {
+        let tone: Vec<f32> = (0..10_000).map(|i| (i as f32 * 0.1).sin() * 0.5).collect();
+        assert_eq!(to_pcm_s16le(&tone).len(), tone.len() * 2);
+
+        // 0.25 is 8191 (0x1fff): low byte first
+        let bytes = to_pcm_s16le(&[0.25, -0.25, 2.0]);
+        assert_eq!(bytes[..2], [0xff, 0x1f]);
+        assert_eq!(i16::from_le_bytes([bytes[2], bytes[3]]), -8191);
+        assert_eq!(i16::from_le_bytes([bytes[4], bytes[5]]), i16::MAX);
+        assert!(to_pcm_s16le(&[]).is_empty());
+    }
+
+    #[test]
+    fn written_pcm_matches_in_both_formats() {
+        // Longer than a block, so it is written in several
+        let tone: Vec<f32> = (0..BLOCK * 2 + 7)
+            .map(|i| (i as f32 * 0.03).sin() * 0.8)
+            .collect();
+        let mut s16 = Vec::new();
+        write_pcm(&mut s16, &tone, PcmFormat::S16le).unwrap();
+        assert_eq!(s16, to_pcm_s16le(&tone));
+
+        let mut f32s = Vec::new();
+        write_pcm(&mut f32s, &tone, PcmFormat::F32le).unwrap();
+        assert_eq!(f32s.len(), tone.len() * 4);
+        assert_eq!(f32s[..4], tone[0].to_le_bytes());
+        let last: [u8; 4] = f32s[f32s.len() - 4..].try_into().unwrap();
+        assert_eq!(f32::from_le_bytes(last), tone[tone.len() - 1]);
+
+        // 0.25 is 0x3e800000
+        let mut quarter = Vec::new();
+        write_pcm(&mut quarter, &[0.25], PcmFormat::F32le).unwrap();
+        assert_eq!(quarter, [0x00, 0x00, 0x80, 0x3e]);
+    }
+}
@@ -0,0 +1,811 @@
+//! Output buffer tuning for playback
+//!
+//! rodio opens its stream with the backend's default buffer size, which
+//! crackles on Raspberry Pi-class boards and adds avoidable latency on
+//! desktops. `TtsEngine::play_with_options` asks the output backend (see
+//! `crate::output`) for a buffer chosen per call, either from a preset or an
+//! exact frame count; the cpal backend negotiates it against what the device
+//! supports.
+//!
+//! The same loop handles barge-in (see `crate::barge_in`): with
+//! `PlaybackOptions::barge_in` set, the microphone is watched while playing.
+//!
+//! `TtsEngine::play_async` runs that loop on its own thread and returns a
+//! [`PlaybackHandle`] for stopping, pausing or changing the volume from the
+//! caller's thread.
+
+use std::borrow::Cow;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use crate::audio::downmix;
+use crate::barge_in::{frame_db, BargeIn, BargeInAction, BargeInDetector, BargeInEvent};
+use crate::coordinator::{self, PlaybackGuard};
+use crate::output::{AudioOutput, OutputSpec, STREAM_STALLED, WRITE_BLOCK_MS};
+use crate::resample::resample_channels;
+use crate::BusyPolicy;
+use crate::{KokoroError, TtsEngine, SAMPLE_RATE};
+
+/// How large the output buffer should be
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BufferPreset {
+    /// Large buffer for slow boards and flaky drivers (~85 ms)
+    Safe,
+    /// Desktop default (~20 ms)
+    Balanced,
+    /// Smallest buffer that is usually glitch-free on desktops (~5 ms)
+    LowLatency,
+    /// Exact frame count
+    Frames(u32),
+}
+
+impl BufferPreset {
+    /// Buffer size in frames at `sample_rate`, rounded up to a power of two
+    pub fn frames(&self, sample_rate: u32) -> u32 {
+        let ms = match self {
+            BufferPreset::Frames(frames) => return *frames,
+            BufferPreset::Safe => 85,
+            BufferPreset::Balanced => 20,
+            BufferPreset::LowLatency => 5,
+        };
+        (sample_rate * ms / 1000).max(1).next_power_of_two()
+    }
+
+    /// Preset for this machine and output device
+    ///
+    /// Raspberry Pi-class boards and Bluetooth outputs get `Safe`; everything
+    /// else `Balanced`.
+    pub fn platform_default(device_name: Option<&str>) -> Self {
+        let wireless = device_name.is_some_and(|name| {
+            let name = name.to_lowercase();
+            ["bluetooth", "airpods", "bluez", "a2dp"]
+                .iter()
+                .any(|kw| name.contains(kw))
+        });
+
+        if wireless || is_single_board_computer() {
+            BufferPreset::Safe
+        } else {
+            BufferPreset::Balanced
+        }
+    }
+}
+
+/// Options for `TtsEngine::play_with_options`
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PlaybackOptions {
+    /// `None` picks `BufferPreset::platform_default` for the device
+    pub buffer: Option<BufferPreset>,
+    pub volume: Option<f32>,
+    /// What to do if another utterance is already playing
+    pub on_busy: BusyPolicy,
+    /// React to the user talking over the speech
+    pub barge_in: Option<BargeIn>,
+}
+
+impl PlaybackOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the output buffer size
+    pub fn buffer(mut self, preset: BufferPreset) -> Self {
+        self.buffer = Some(preset);
+        self
+    }
+
+    /// Set playback volume (0.0 to 1.0, default 1.0)
+    pub fn volume(mut self, volume: f32) -> Self {
+        self.volume = Some(volume);
+        self
+    }
+
+    /// Wait, queue, interrupt or fail when audio is already playing (default: wait)
+    pub fn on_busy(mut self, policy: BusyPolicy) -> Self {
+        self.on_busy = policy;
+        self
+    }
+
+    /// Pause, duck or stop when the microphone picks up speech
+    pub fn barge_in(mut self, barge_in: BargeIn) -> Self {
+        self.barge_in = Some(barge_in);
+        self
+    }
+}
+
+/// What a playback call actually got
+#[derive(Clone, Debug, PartialEq)]
+pub struct PlaybackInfo {
+    pub device: String,
+    pub sample_rate: u32,
+    /// Requested buffer size after negotiation with the device
+    pub buffer_frames: u32,
+    /// Frames per callback the backend actually delivered
+    pub callback_frames: u32,
+    /// From starting the stream to the first audio callback
+    pub startup_latency: Duration,
+    /// Callbacks that arrived late enough that the device ran dry
+    pub underruns: u32,
+    /// Times the user talked over the speech (always 0 without `barge_in`)
+    pub barge_ins: u32,
+}
+
+/// Buffer sizes a device accepts, mirroring cpal's `SupportedBufferSize`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BufferRange {
+    Range { min: u32, max: u32 },
+    Unknown,
+}
+
+/// Fit the requested frame count into what the device supports
+pub fn negotiate_buffer(requested: u32, supported: BufferRange) -> u32 {
+    match supported {
+        BufferRange::Range { min, max } => requested.clamp(min, max.max(min)),
+        BufferRange::Unknown => requested,
+    }
+}
+
+// Pi-class boards: ARM Linux, where default buffers are the usual source of crackling
+fn is_single_board_computer() -> bool {
+    cfg!(all(
+        target_os = "linux",
+        any(target_arch = "arm", target_arch = "aarch64")
+    ))
+}
+
+/// Click track used by the latency diagnostic: a short 1 kHz blip twice a second
+pub fn click_track(seconds: f32, sample_rate: u32) -> Vec<f32> {
+    let total = (seconds * sample_rate as f32) as usize;
+    let period = sample_rate as usize / 2;
+    let click_len = sample_rate as usize / 200;
+
+    (0..total)
+        .map(|i| {
+            let t = i % period;
+            if t < click_len {
+                let decay = 1.0 - t as f32 / click_len as f32;
+                (t as f32 * 1000.0 * std::f32::consts::TAU / sample_rate as f32).sin() * 0.5 * decay
+            } else {
+                0.0
+            }
+        })
+        .collect()
+}
+
+// Level of the output just played, for telling echo from the user talking
+const OUTPUT_LEVEL_MS: usize = 20;
+
+// Keeps the microphone open; dropping it stops listening
+#[cfg(feature = "playback")]
+type InputStream = cpal::Stream;
+#[cfg(not(feature = "playback"))]
+type InputStream = ();
+
+// Watch the default microphone, pushing one level per callback into `levels`
+#[cfg(feature = "playback")]
+fn open_input_levels(levels: Arc<Mutex<Vec<f32>>>) -> Result<InputStream, String> {
+    use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+
+    let device = cpal::default_host()
+        .default_input_device()
+        .ok_or("No input device available")?;
+    let supported = device
+        .default_input_config()
+        .map_err(|e| format!("Failed to query input config: {}", e))?;
+    let channels = supported.channels() as usize;
+    let config = supported.config();
+    let on_error = |e| eprintln!("⚠️ Input stream error: {}", e);
+
+    // Levels are taken over the first channel only
+    let stream = match supported.sample_format() {
+        cpal::SampleFormat::F32 => device.build_input_stream(
+            &config,
+            move |data: &[f32], _: &cpal::InputCallbackInfo| {
+                let mono: Vec<f32> = data.iter().step_by(channels).copied().collect();
+                if let Ok(mut levels) = levels.lock() {
+                    levels.push(frame_db(&mono));
+                }
+            },
+            on_error,
+            None,
+        ),
+        cpal::SampleFormat::I16 => device.build_input_stream(
+            &config,
+            move |data: &[i16], _: &cpal::InputCallbackInfo| {
+                let mono: Vec<f32> = data
+                    .iter()
+                    .step_by(channels)
+                    .map(|&s| s as f32 / i16::MAX as f32)
+                    .collect();
+                if let Ok(mut levels) = levels.lock() {
+                    levels.push(frame_db(&mono));
+                }
+            },
+            on_error,
+            None,
+        ),
+        format => return Err(format!("Unsupported input sample format: {}", format)),
+    }
+    .map_err(|e| format!("Failed to open input stream: {}", e))?;
+
+    stream
+        .play()
+        .map_err(|e| format!("Failed to start input stream: {}", e))?;
+    Ok(stream)
+}
+
+#[cfg(not(feature = "playback"))]
+fn open_input_levels(_levels: Arc<Mutex<Vec<f32>>>) -> Result<InputStream, String> {
+    Err("microphone input needs the playback feature".to_string())
+}
+
+/// Play `audio` (`channels` interleaved) on `output`, blocking until done;
+/// the caller holds the device
+///
+/// Volume, pausing and stopping come from `controls`, not `opts`.
+pub(crate) fn play_on(
+    output: &dyn AudioOutput,
+    audio: &[f32],
+    channels: u16,
+    opts: &PlaybackOptions,
+    playing: &PlaybackGuard<'_>,
+    controls: &Controls,
+) -> Result<PlaybackInfo, String> {
+    let mut stream = output.open(&OutputSpec {
+        sample_rate: SAMPLE_RATE,
+        buffer: opts.buffer,
+        channels,
+    })?;
+    let info = stream.info();
+    let sample_rate = info.sample_rate;
+    // Stereo on a stream that only takes mono is mixed down first
+    let (audio, channels) = match info.channels.max(1) {
+        taken if taken < channels => (Cow::Owned(downmix(audio, channels)), 1),
+        _ => (Cow::Borrowed(audio), channels.max(1)),
+    };
+    let samples = resample_channels(&audio, channels, SAMPLE_RATE, sample_rate);
+    let started = Instant::now();
+
+    let input_levels = Arc::new(Mutex::new(Vec::new()));
+    let mut barge_in =
+        opts.barge_in
+            .and_then(|config| match open_input_levels(input_levels.clone()) {
+                Ok(input) => Some((BargeInDetector::new(config), input)),
+                Err(_e) => {
+                    #[cfg(not(feature = "as-lib"))]
+                    eprintln!("⚠️ Barge-in disabled: {}", _e);
+                    None
+                }
+            });
+    let level_window = sample_rate as usize * OUTPUT_LEVEL_MS / 1000 * usize::from(channels);
+    let block = (sample_rate * WRITE_BLOCK_MS / 1000).max(1) as usize * usize::from(channels);
+    let mut position: usize = 0;
+    let mut paused = false;
+    let mut gain = 1.0f32;
+    let mut barge_ins = 0;
+    let mut stopped = false;
+
+    // Written a block at a time; the stream's queue paces the loop, so the
+    // checks below run every block
+    while position < samples.len() && !playing.interrupted() && !controls.stopped() {
+        if let Some((detector, _)) = barge_in.as_mut() {
+            let levels = input_levels
+                .lock()
+                .map(|mut levels| std::mem::take(&mut *levels))
+                .unwrap_or_default();
+            let now_ms = started.elapsed().as_millis() as u64;
+            let output_db = if paused {
+                frame_db(&[])
+            } else {
+                frame_db(&samples[position.saturating_sub(level_window)..position])
+            };
+            for input_db in levels {
+                match detector.observe(now_ms, input_db, output_db) {
+                    Some(BargeInEvent::Triggered(action)) => {
+                        barge_ins += 1;
+                        match action {
+                            BargeInAction::Pause => paused = true,
+                            BargeInAction::Duck { db } => gain = 10f32.powf(db / 20.0),
+                            BargeInAction::Stop => stopped = true,
+                        }
+                    }
+                    Some(BargeInEvent::Resumed) => {
+                        paused = false;
+                        gain = 1.0;
+                    }
+                    None => {}
+                }
+            }
+        }
+        if stopped {
+            break;
+        }
+        // Paused: write nothing and hold the position; the device plays silence
+        if paused || controls.paused() {
+            std::thread::sleep(Duration::from_millis(5));
+            continue;
+        }
+
+        let end = (position + block).min(samples.len());
+        let gain = gain * controls.volume();
+        let frames: Vec<f32> = samples[position..end].iter().map(|s| s * gain).collect();
+        match stream.write(&frames)? {
+            0 => return Err(STREAM_STALLED.to_string()),
+            taken => position += taken,
+        }
+    }
+    drop(barge_in);
+    if !playing.interrupted() && !stopped && !controls.stopped() {
+        stream.flush();
+    }
+
+    let info = stream.info();
+    Ok(PlaybackInfo {
+        device: info.device,
+        sample_rate: info.sample_rate,
+        buffer_frames: info.buffer_frames,
+        callback_frames: info.callback_frames,
+        startup_latency: info.startup_latency,
+        underruns: info.underruns,
+        barge_ins,
+    })
+}
+
+/// Stop, pause and volume shared between a `PlaybackHandle` and the
+/// thread playing
+pub(crate) struct Controls {
+    stop: AtomicBool,
+    paused: AtomicBool,
+    // f32 bits
+    volume: AtomicU32,
+}
+
+impl Controls {
+    pub fn new(volume: f32) -> Self {
+        let controls = Self {
+            stop: AtomicBool::new(false),
+            paused: AtomicBool::new(false),
+            volume: AtomicU32::new(0),
+        };
+        controls.set_volume(volume);
+        controls
+    }
+
+    pub fn stopped(&self) -> bool {
+        self.stop.load(Ordering::Acquire)
+    }
+
+    pub fn paused(&self) -> bool {
+        self.paused.load(Ordering::Acquire)
+    }
+
+    pub fn volume(&self) -> f32 {
+        f32::from_bits(self.volume.load(Ordering::Acquire))
+    }
+
+    fn set_volume(&self, volume: f32) {
+        let volume = if volume.is_nan() {
+            1.0
+        } else {
+            volume.clamp(0.0, 1.0)
+        };
+        self.volume.store(volume.to_bits(), Ordering::Release);
+    }
+}
+
+/// Audio playing on a background thread, from `TtsEngine::play_async`
+///
+/// The output stream lives on that thread for as long as the handle does.
+/// Dropping the handle stops playback, so keep it until the audio is done
+/// (or call `wait`) rather than letting it fall out of scope.
+///
+/// # Example
+/// ```no_run
+/// use kokoro_tiny::TtsEngine;
+/// use std::time::Duration;
+///
+/// # async fn demo() -> Result<(), String> {
+/// let tts = TtsEngine::new().await?;
+/// let audio = tts.synthesize("A long announcement", None, None, None)?;
+/// let playing = tts.play_async(&audio, 0.8)?;
+/// std::thread::sleep(Duration::from_secs(1));
+/// playing.set_volume(0.3);
+/// if !playing.is_finished() {
+///     playing.stop();
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct PlaybackHandle {
+    controls: Arc<Controls>,
+    thread: Option<JoinHandle<Result<PlaybackInfo, String>>>,
+}
+
+impl PlaybackHandle {
+    /// Start playing `audio` (`channels` interleaved, at the model rate) on
+    /// a thread of its own, once the device is free
+    pub(crate) fn spawn(
+        output: Arc<dyn AudioOutput>,
+        audio: Vec<f32>,
+        channels: u16,
+        volume: f32,
+    ) -> Result<Self, String> {
+        let controls = Arc::new(Controls::new(volume));
+        let shared = controls.clone();
+        let thread = std::thread::Builder::new()
+            .name("kokoro-playback".to_string())
+            .spawn(move || {
+                let playing = coordinator::coordinator().acquire(BusyPolicy::default())?;
+                let opts = PlaybackOptions::new();
+                play_on(&*output, &audio, channels, &opts, &playing, &shared)
+            })
+            .map_err(|e| format!("Failed to start playback thread: {}", e))?;
+        Ok(Self {
+            controls,
+            thread: Some(thread),
+        })
+    }
+
+    /// Cut playback off; calling it again, or after the end, does nothing
+    pub fn stop(&self) {
+        self.controls.stop.store(true, Ordering::Release);
+    }
+
+    /// Hold the current position; the device plays silence until `resume`
+    pub fn pause(&self) {
+        self.controls.paused.store(true, Ordering::Release);
+    }
+
+    pub fn resume(&self) {
+        self.controls.paused.store(false, Ordering::Release);
+    }
+
+    /// Change the volume (0.0 to 1.0) from the next block on
+    pub fn set_volume(&self, volume: f32) {
+        self.controls.set_volume(volume);
+    }
+
+    /// Whether the audio has played out, been stopped, or failed
+    pub fn is_finished(&self) -> bool {
+        self.thread
+            .as_ref()
+            .is_none_or(|thread| thread.is_finished())
+    }
+
+    /// Block until playback ends, reporting what it got
+    pub fn wait(mut self) -> Result<PlaybackInfo, KokoroError> {
+        let thread = self.thread.take().expect("only wait takes the thread");
+        match thread.join() {
+            Ok(played) => played.map_err(KokoroError::Playback),
+            Err(_) => Err(KokoroError::Playback(
+                "Playback thread panicked".to_string(),
+            )),
+        }
+    }
+}
+
+impl Drop for PlaybackHandle {
+    // The thread notices within one block and releases the device; dropping
+    // doesn't wait for that, so it never blocks a UI thread
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+impl std::fmt::Debug for PlaybackHandle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PlaybackHandle")
+            .field("paused", &self.controls.paused())
+            .field("volume", &self.controls.volume())
+            .field("finished", &self.is_finished())
+            .finish()
+    }
+}
+
+impl TtsEngine {
+    /// Play audio with an explicit output buffer size, blocking until done
+    ///
+    /// # Example
+    /// ```no_run
+    /// use kokoro_tiny::{BufferPreset, BusyPolicy, PlaybackOptions, TtsEngine};
+    ///
+    /// # async fn demo() -> Result<(), String> {
+    /// let tts = TtsEngine::new().await?;
+    /// let audio = tts.synthesize("Low latency", None, None, None)?;
+    /// let opts = PlaybackOptions::new()
+    ///     .buffer(BufferPreset::LowLatency)
+    ///     .on_busy(BusyPolicy::Queue);
+    /// let info = tts.play_with_options(&audio, &opts)?;
+    /// println!("{} frames, {} underruns", info.buffer_frames, info.underruns);
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// Stop talking when the user does:
+    /// ```no_run
+    /// use kokoro_tiny::{BargeIn, BargeInAction, PlaybackOptions, TtsEngine};
+    ///
+    /// # async fn demo() -> Result<(), String> {
+    /// let tts = TtsEngine::new().await?;
+    /// let audio = tts.synthesize("Interrupt me whenever you like", None, None, None)?;
+    /// let opts = PlaybackOptions::new().barge_in(BargeIn::new(BargeInAction::Pause));
+    /// let info = tts.play_with_options(&audio, &opts)?;
+    /// println!("interrupted {} times", info.barge_ins);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn play_with_options(
+        &self,
+        audio: &[f32],
+        opts: &PlaybackOptions,
+    ) -> Result<PlaybackInfo, KokoroError> {
+        let output = self.output().map_err(KokoroError::Playback)?;
+        // Take the device before opening a stream on it
+        let playing = coordinator::coordinator()
+            .acquire(opts.on_busy)
+            .map_err(KokoroError::Playback)?;
+        let controls = Controls::new(opts.volume.unwrap_or(1.0));
+        play_on(&*output, audio, 1, opts, &playing, &controls).map_err(KokoroError::Playback)
+    }
+
+    /// Play a five second click track and report underruns for the chosen buffer
+    ///
+    /// # Example
+    /// ```no_run
+    /// use kokoro_tiny::{BufferPreset, PlaybackOptions, TtsEngine};
+    ///
+    /// # async fn demo() -> Result<(), String> {
+    /// let tts = TtsEngine::new().await?;
+    /// let info = tts.latency_test(&PlaybackOptions::new().buffer(BufferPreset::Safe))?;
+    /// println!("startup {:?}, {} underruns", info.startup_latency, info.underruns);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn latency_test(&self, opts: &PlaybackOptions) -> Result<PlaybackInfo, KokoroError> {
+        self.play_with_options(&click_track(5.0, SAMPLE_RATE), opts)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::coordinator::Coordinator;
+    use crate::NullOutput;
+
+    #[test]
+    fn presets_map_to_power_of_two_frames() {
+        assert_eq!(BufferPreset::LowLatency.frames(48_000), 256);
+        assert_eq!(BufferPreset::Balanced.frames(48_000), 1024);
+        assert_eq!(BufferPreset::Safe.frames(48_000), 4096);
+        assert_eq!(BufferPreset::Balanced.frames(44_100), 1024);
+        assert_eq!(BufferPreset::Frames(300).frames(48_000), 300);
+    }
+
+    #[test]
+    fn negotiation_respects_device_limits() {
+        let range = BufferRange::Range {
+            min: 512,
+            max: 2048,
+        };
+        assert_eq!(negotiate_buffer(256, range), 512);
+        assert_eq!(negotiate_buffer(1024, range), 1024);
+        assert_eq!(negotiate_buffer(4096, range), 2048);
+        assert_eq!(negotiate_buffer(4096, BufferRange::Unknown), 4096);
+
+        // Some drivers report an inverted range; don't panic on it
+        assert_eq!(
+            negotiate_buffer(100, BufferRange::Range { min: 64, max: 32 }),
+            64
+        );
+    }
+
+    #[test]
+    fn wireless_outputs_get_the_safe_preset() {
+        assert_eq!(
+            BufferPreset::platform_default(Some("bluez_sink.AA_BB.a2dp_sink")),
+            BufferPreset::Safe
+        );
+        assert_eq!(
+            BufferPreset::platform_default(Some("AirPods Pro")),
+            BufferPreset::Safe
+        );
+    }
+
+    #[test]
+    fn click_track_has_two_clicks_per_second() {
+        let track = click_track(1.0, 24_000);
+        assert_eq!(track.len(), 24_000);
+
+        // 5 ms blips at 0 s and 0.5 s, silence in between
+        assert!(track[..120].iter().any(|&s| s.abs() > 0.1));
+        assert!(track[120..12_000].iter().all(|&s| s == 0.0));
+        assert!(track[12_000..12_120].iter().any(|&s| s.abs() > 0.1));
+        assert!(track[12_120..].iter().all(|&s| s == 0.0));
+    }
+
+    #[test]
+    fn plays_resampled_scaled_frames_on_the_engine_output() {
+        let output = NullOutput::new().with_sample_rate(48_000);
+        let mut tts = TtsEngine::without_model();
+        tts.set_output(Arc::new(output.clone()));
+
+        let opts = PlaybackOptions::new()
+            .volume(0.5)
+            .buffer(BufferPreset::LowLatency)
+            .on_busy(BusyPolicy::Queue);
+        let info = tts.play_with_options(&[0.5; 2400], &opts).unwrap();
+
+        assert_eq!(output.frames(), vec![0.25; 4800]);
+        assert_eq!(output.opened(), 1);
+        assert_eq!(info.device, "null");
+        assert_eq!(info.sample_rate, 48_000);
+        assert_eq!(info.buffer_frames, 256);
+        assert_eq!(info.barge_ins, 0);
+
+        tts.play([0.5; 2400], 1.0).unwrap();
+        assert_eq!(output.frames().len(), 9600);
+        assert_eq!(output.opened(), 2);
+    }
+
+    #[test]
+    fn stereo_buffers_play_interleaved() {
+        let output = NullOutput::new().with_sample_rate(48_000);
+        let mut tts = TtsEngine::without_model();
+        tts.set_output(Arc::new(output.clone()));
+
+        let stereo = crate::audio::to_stereo(&[0.5; 2400], -1.0);
+        let audio = crate::AudioBuffer::interleaved(stereo, SAMPLE_RATE, 2);
+        tts.play(&audio, 1.0).unwrap();
+        let frames = output.frames();
+        assert_eq!(frames.len(), 9600);
+        assert!(frames.chunks(2).all(|frame| frame == [0.5, 0.0]));
+    }
+
+    #[test]
+    fn interrupt_cuts_playback_short() {
+        let coordinator = Coordinator::new();
+        // One second of audio, written at the pace it would play
+        let output = NullOutput::new().with_sample_rate(1_000).realtime();
+        let audio = vec![0.5; SAMPLE_RATE as usize];
+
+        let start = Instant::now();
+        std::thread::scope(|scope| {
+            scope.spawn(|| {
+                std::thread::sleep(Duration::from_millis(100));
+                assert!(coordinator.stop_current());
+            });
+            let playing = coordinator.acquire(BusyPolicy::Wait).unwrap();
+            let controls = Controls::new(1.0);
+            play_on(
+                &output,
+                &audio,
+                1,
+                &PlaybackOptions::new(),
+                &playing,
+                &controls,
+            )
+            .unwrap();
+        });
+
+        assert!(start.elapsed() < Duration::from_millis(600));
+        let played = output.frames().len();
+        assert!(played > 0 && played < 1_000, "{}", played);
+    }
+
+    #[test]
+    fn a_stalled_output_fails_instead_of_hanging() {
+        let mut tts = TtsEngine::without_model();
+        tts.set_output(Arc::new(NullOutput::new().stalled()));
+        match tts.play([0.5; 2400], 1.0) {
+            Err(KokoroError::Playback(message)) => assert_eq!(message, STREAM_STALLED),
+            other => panic!("expected a playback error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn barge_in_without_a_microphone_still_plays() {
+        let output = NullOutput::new();
+        let coordinator = Coordinator::new();
+        let playing = coordinator.acquire(BusyPolicy::Wait).unwrap();
+        let opts = PlaybackOptions::new().barge_in(BargeIn::new(BargeInAction::Stop));
+
+        // No input device in tests (or no playback feature): a warning, not an error
+        let controls = Controls::new(1.0);
+        let info = play_on(&output, &[0.1; 2400], 1, &opts, &playing, &controls).unwrap();
+        assert_eq!(output.frames().len(), 2400);
+        assert_eq!(info.barge_ins, 0);
+    }
+
+    // One second of audio on an output that takes a second to play it
+    fn realtime_engine() -> (TtsEngine, NullOutput) {
+        let output = NullOutput::new().with_sample_rate(1_000).realtime();
+        let mut tts = TtsEngine::without_model();
+        tts.set_output(Arc::new(output.clone()));
+        (tts, output)
+    }
+
+    #[test]
+    fn play_async_returns_before_the_audio_ends() {
+        let (tts, output) = realtime_engine();
+        let start = Instant::now();
+        let playing = tts.play_async([0.5; 24_000], 1.0).unwrap();
+        assert!(start.elapsed() < Duration::from_millis(100));
+
+        std::thread::sleep(Duration::from_millis(100));
+        playing.stop();
+        playing.stop();
+        let info = playing.wait().unwrap();
+        assert!(start.elapsed() < Duration::from_millis(600));
+        assert_eq!(info.device, "null");
+        let played = output.frames().len();
+        assert!(played > 0 && played < 1_000, "{}", played);
+    }
+
+    #[test]
+    fn pause_holds_and_volume_changes_mid_utterance() {
+        let (tts, output) = realtime_engine();
+        let playing = tts.play_async([0.5; 4_800], 0.5).unwrap();
+        std::thread::sleep(Duration::from_millis(60));
+        playing.pause();
+        playing.set_volume(0.2);
+        std::thread::sleep(Duration::from_millis(40));
+        let held = output.frames().len();
+        std::thread::sleep(Duration::from_millis(100));
+        assert_eq!(output.frames().len(), held);
+        assert!(!playing.is_finished());
+
+        playing.resume();
+        playing.wait().unwrap();
+        let frames = output.frames();
+        assert_eq!(frames.len(), 200);
+        assert_eq!((frames[0], frames[199]), (0.25, 0.1));
+    }
+
+    #[test]
+    fn dropping_the_handle_stops_playback() {
+        let (tts, output) = realtime_engine();
+        drop(tts.play_async([0.5; 24_000], 1.0).unwrap());
+        std::thread::sleep(Duration::from_millis(300));
+        let played = output.frames().len();
+        std::thread::sleep(Duration::from_millis(100));
+        assert_eq!(output.frames().len(), played);
+        assert!(played < 1_000, "{}", played);
+    }
+
+    #[cfg(feature = "playback")]
+    #[test]
+    #[ignore = "needs an audio output device"]
+    fn device_playback_stops_before_the_end() {
+        let tts = TtsEngine::without_model();
+        let tone: Vec<f32> = (0..72_000).map(|i| (i as f32 * 0.05).sin() * 0.2).collect();
+        let start = Instant::now();
+        let playing = tts.play_async(&tone, 0.5).unwrap();
+        std::thread::sleep(Duration::from_millis(300));
+        playing.stop();
+        playing.stop();
+        playing.wait().unwrap();
+        assert!(start.elapsed() < Duration::from_secs(2));
+
+        // Stopping after the end is just as harmless
+        let playing = tts.play_async(&tone[..2_400], 0.5).unwrap();
+        while !playing.is_finished() {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        playing.stop();
+        playing.wait().unwrap();
+    }
+
+    #[cfg(not(feature = "playback"))]
+    #[test]
+    fn no_output_without_the_playback_feature() {
+        let tts = TtsEngine::without_model();
+        let err = tts.play(&[0.0; 10], 1.0).unwrap_err();
+        assert!(
+            matches!(&err, KokoroError::Playback(message) if message.contains("set_output")),
+            "{}",
+            err
+        );
+    }
+}
@@ -0,0 +1,217 @@
+//! Keeping spoken text out of logs, results and state files
+//!
+//! Sometimes the text being spoken is sensitive (patient notes, private
+//! messages). With redaction on, everything that would echo it (log lines,
+//! warnings, the CLI status line, MCP results, reports) shows
+//! `display_text(text)` instead: the length and a salted hash. That is enough
+//! to tell lines of one run apart, but not to recover the text. The result
+//! cache keys on hashes, and reading progress anchors on a hash instead of the
+//! last segment's text. Resume files exist to re-render the text, so none are
+//! written.
+//!
+//! Redaction is process-wide. Turn it on with `set_redact_text(true)`,
+//! `kokoro-speak --redact` or `KOKORO_REDACT=1`; `with_redact_text` overrides
+//! it for one thread while a closure runs. Synthesis is unaffected.
+
+use std::borrow::Cow;
+use std::cell::Cell;
+use std::hash::Hasher;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::OnceLock;
+
+use crate::chunk_cache::Fnv64;
+
+/// Environment variable that turns redaction on at startup
+pub const REDACT_ENV: &str = "KOKORO_REDACT";
+
+static REDACT: OnceLock<AtomicBool> = OnceLock::new();
+
+thread_local! {
+    // Set while a `with_redact_text` closure runs on this thread
+    static OVERRIDE: Cell<Option<bool>> = const { Cell::new(None) };
+}
+
+/// Turn redaction on or off for the whole process
+pub fn set_redact_text(enabled: bool) {
+    flag().store(enabled, Ordering::Relaxed);
+}
+
+/// Whether text is currently redacted (initially: `KOKORO_REDACT` is set to 1/true/yes/on)
+pub fn redact_text() -> bool {
+    OVERRIDE
+        .with(Cell::get)
+        .unwrap_or_else(|| flag().load(Ordering::Relaxed))
+}
+
+/// Run `f` with redaction on or off for the current thread only
+///
+/// The process-wide setting is untouched, and threads `f` starts follow it.
+///
+/// # Example
+/// ```
+/// use kokoro_tiny::privacy::{display_text, with_redact_text};
+///
+/// let shown = with_redact_text(true, || display_text("room 12").into_owned());
+/// assert!(shown.starts_with("[redacted: 7 chars"));
+/// ```
+pub fn with_redact_text<R>(enabled: bool, f: impl FnOnce() -> R) -> R {
+    // Puts the previous override back, even if `f` panics
+    struct Restore(Option<bool>);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            OVERRIDE.with(|o| o.set(self.0));
+        }
+    }
+
+    let _restore = Restore(OVERRIDE.with(|o| o.replace(Some(enabled))));
+    f()
+}
+
+fn flag() -> &'static AtomicBool {
+    REDACT.get_or_init(|| {
+        let enabled = std::env::var(REDACT_ENV).is_ok_and(|value| {
+            matches!(
+                value.trim().to_lowercase().as_str(),
+                "1" | "true" | "yes" | "on"
+            )
+        });
+        AtomicBool::new(enabled)
+    })
+}
+
+/// `text` as it may appear in logs and results: unchanged, or `[redacted: N chars #hash]`
+pub fn display_text(text: &str) -> Cow<'_, str> {
+    if redact_text() {
+        Cow::Owned(format!(
+            "[redacted: {} chars #{:08x}]",
+            text.chars().count(),
+            salted_hash(text, 0) >> 32
+        ))
+    } else {
+        Cow::Borrowed(text)
+    }
+}
+
+/// Like `display_text`, but cut to `max_chars` with `...` when not redacted
+pub fn display_preview(text: &str, max_chars: usize) -> String {
+    if redact_text() || text.chars().count() <= max_chars {
+        return display_text(text).into_owned();
+    }
+    let cut: String = text.chars().take(max_chars).collect();
+    format!("{}...", cut)
+}
+
+/// Key for maps that would otherwise be keyed by text: the text itself, or a
+/// 128-bit salted hash of it when redacting
+pub(crate) fn text_key(text: String) -> String {
+    if redact_text() {
+        format!(
+            "{:016x}{:016x}",
+            salted_hash(&text, 0),
+            salted_hash(&text, 1)
+        )
+    } else {
+        text
+    }
+}
+
+// Salt is random per process, so hashes can't be matched against a
+// precomputed table or across runs
+fn salted_hash(text: &str, lane: u8) -> u64 {
+    static SALT: OnceLock<u64> = OnceLock::new();
+    let salt = SALT.get_or_init(crate::rng::random_seed);
+
+    let mut hash = Fnv64::new();
+    hash.write(&salt.to_le_bytes());
+    hash.write(&[lane]);
+    hash.write(text.as_bytes());
+    hash.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{SynthesisWarning, SynthesizeOptions, TtsEngine};
+    use std::sync::Arc;
+
+    const SENTINEL: &str = "Patient Jane Roe, diagnosis confidential";
+
+    #[test]
+    fn display_text_hides_text_only_when_redacting() {
+        assert_eq!(display_text(SENTINEL), SENTINEL);
+        assert_eq!(display_preview("abcdef", 3), "abc...");
+
+        with_redact_text(true, || {
+            let shown = display_text(SENTINEL);
+            assert!(!shown.contains("Jane"), "{}", shown);
+            assert!(shown.starts_with("[redacted: 40 chars #"), "{}", shown);
+            // Stable within a run so log lines can be correlated
+            assert_eq!(shown, display_text(SENTINEL));
+            assert_ne!(
+                shown,
+                display_text("Something else entirely, same size!!!!!")
+            );
+            assert_eq!(display_preview(SENTINEL, 10), shown);
+            assert!(!text_key(SENTINEL.to_string()).contains("Jane"));
+
+            // Overrides nest and unwind
+            with_redact_text(false, || assert!(!redact_text()));
+            assert!(redact_text());
+        });
+        assert!(!redact_text());
+    }
+
+    #[test]
+    fn warnings_and_errors_are_redacted() {
+        let mut engine = TtsEngine::mock(100);
+        let shouted = "PATIENT JANE ROE IS STABLE";
+
+        let warnings = with_redact_text(true, || {
+            engine
+                .synthesize_with(shouted, SynthesizeOptions::new())
+                .unwrap();
+            engine.last_warnings()
+        });
+        let [SynthesisWarning::CapsNormalized {
+            original,
+            normalized,
+        }] = &warnings[..]
+        else {
+            panic!("expected CapsNormalized, got {:?}", warnings);
+        };
+        // The fields themselves, not just their Display
+        for text in [original, normalized, &warnings[0].to_string()] {
+            assert!(!text.to_lowercase().contains("jane"), "{}", text);
+        }
+        // Kept as recorded once redaction is off again
+        assert_eq!(engine.last_warnings(), warnings);
+        engine
+            .synthesize_with(shouted, SynthesizeOptions::new())
+            .unwrap();
+        assert!(engine.last_warnings()[0].to_string().contains("JANE"));
+
+        // Synthesis itself is unaffected
+        let plain = engine
+            .synthesize_with(SENTINEL, SynthesizeOptions::new())
+            .unwrap();
+        engine.clear_cache();
+        let redacted = with_redact_text(true, || {
+            engine
+                .synthesize_with(SENTINEL, SynthesizeOptions::new())
+                .unwrap()
+        });
+        assert_eq!(plain, redacted);
+
+        // Failing chunks report which chunk, not what it said
+        engine.mock_inference = Some(Arc::new(|_: &[i64], _: &[f32], _: f32| {
+            Err("injected failure".to_string())
+        }));
+        let partial = with_redact_text(true, || {
+            engine
+                .synthesize_resumable(SENTINEL, SynthesizeOptions::new().gain(1.1))
+                .unwrap_err()
+                .to_string()
+        });
+        assert!(!partial.contains("Jane"), "{}", partial);
+    }
+}
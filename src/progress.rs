@@ -0,0 +1,223 @@
+//! Progress reports for long-form synthesis
+//!
+//! A long text is rendered chunk by chunk. `synthesize_with_progress` (and
+//! `synthesize_resumable_with_progress`) call back before and after every
+//! chunk and once when the whole text is done, so a caller can drive a
+//! progress bar or a log of its own. The plain methods pass the same reports
+//! to `log_progress`, which prints the familiar chunk lines to stderr.
+
+use std::time::Duration;
+
+use crate::resume::{PartialSynthesis, SynthesisPlan};
+use crate::{KokoroError, SynthesizeOptions, TtsEngine};
+
+/// Where a report falls in the render
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProgressStage {
+    /// Chunk `chunk` is about to be rendered
+    ChunkStarted,
+    /// Chunk `chunk` is rendered and joined onto the audio
+    ChunkFinished,
+    /// Every chunk is done; `chunk` equals `total_chunks`
+    Completed,
+}
+
+/// One progress report
+#[derive(Clone, Debug, PartialEq)]
+pub struct SynthesisProgress {
+    pub stage: ProgressStage,
+    /// Index of the chunk the report is about
+    pub chunk: usize,
+    pub total_chunks: usize,
+    /// Index of the paragraph that chunk is in (blank-line separated)
+    pub paragraph: usize,
+    pub total_paragraphs: usize,
+    /// Characters in the chunks finished so far
+    pub chars_done: usize,
+    pub total_chars: usize,
+    /// Length of the audio so far (24kHz samples)
+    pub samples: usize,
+    /// Since the render started
+    pub elapsed: Duration,
+}
+
+impl SynthesisProgress {
+    /// Share of the text rendered, 0.0 to 1.0
+    pub fn fraction(&self) -> f32 {
+        if self.total_chars == 0 {
+            1.0
+        } else {
+            self.chars_done as f32 / self.total_chars as f32
+        }
+    }
+}
+
+impl TtsEngine {
+    /// `synthesize_with`, calling `on_progress` before and after each chunk
+    /// and once at the end
+    ///
+    /// The result cache is bypassed, so every call renders. In fallback mode
+    /// the fallback audio is returned after a single `Completed` report.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use kokoro_tiny::{ProgressStage, SynthesizeOptions, TtsEngine};
+    ///
+    /// # async fn demo() -> Result<(), kokoro_tiny::KokoroError> {
+    /// let tts = TtsEngine::new().await?;
+    /// let article = std::fs::read_to_string("article.txt").unwrap();
+    /// let audio = tts.synthesize_with_progress(&article, SynthesizeOptions::new(), |p| {
+    ///     if p.stage == ProgressStage::ChunkFinished {
+    ///         println!("{:.0}% after {:.1?}", p.fraction() * 100.0, p.elapsed);
+    ///     }
+    /// })?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn synthesize_with_progress(
+        &self,
+        text: &str,
+        opts: SynthesizeOptions,
+        on_progress: impl FnMut(SynthesisProgress),
+    ) -> Result<Vec<f32>, KokoroError> {
+        if !self.is_fallback() && SynthesisPlan::new(text, &opts).chunks.is_empty() {
+            return Err(KokoroError::InvalidInput(
+                "No text provided for synthesis".to_string(),
+            ));
+        }
+        self.synthesize_resumable_with_progress(text, opts, on_progress)
+            .map_err(|partial| partial.into_error())
+    }
+
+    /// `synthesize_resumable`, with progress reports as in
+    /// `synthesize_with_progress`
+    pub fn synthesize_resumable_with_progress(
+        &self,
+        text: &str,
+        opts: SynthesizeOptions,
+        mut on_progress: impl FnMut(SynthesisProgress),
+    ) -> Result<Vec<f32>, Box<PartialSynthesis>> {
+        if self.is_fallback() {
+            if let Ok(audio) = self.fallback_audio(text) {
+                let chars = text.chars().count();
+                on_progress(SynthesisProgress {
+                    stage: ProgressStage::Completed,
+                    chunk: 0,
+                    total_chunks: 0,
+                    paragraph: 0,
+                    total_paragraphs: 0,
+                    chars_done: chars,
+                    total_chars: chars,
+                    samples: audio.len(),
+                    elapsed: Duration::ZERO,
+                });
+                return Ok(audio);
+            }
+        }
+
+        let plan = SynthesisPlan::new(text, &opts);
+        self.render_plan(&plan, 0, Vec::new(), &mut on_progress)
+    }
+}
+
+/// Reports as the chunk lines long-form synthesis prints to stderr
+pub(crate) fn log_progress(plan: &SynthesisPlan) -> impl FnMut(SynthesisProgress) + '_ {
+    let mut announced = false;
+    move |progress| {
+        if progress.total_chunks < 2 || progress.stage != ProgressStage::ChunkStarted {
+            return;
+        }
+        #[cfg(not(feature = "as-lib"))]
+        {
+            if !announced {
+                eprintln!(
+                    "📚 Long-form synthesis enabled: {} chars -> {} chunk(s) (≤ {} chars each)",
+                    progress.total_chars, progress.total_chunks, plan.pacing.chunk_max_chars
+                );
+            }
+            eprintln!(
+                "   → Chunk {}/{} ({} chars)",
+                progress.chunk + 1,
+                progress.total_chunks,
+                plan.chunks[progress.chunk].chars().count()
+            );
+        }
+        announced = true;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    use crate::PacingPreset;
+
+    const ARTICLE: &str = "The first sentence is long enough to be a chunk on its own today. \
+        The second sentence keeps going for quite a while so it also stands alone. \
+        A third one follows, because long text needs something left to render. \
+        And the fourth sentence closes the article after everything else.";
+
+    fn opts() -> SynthesizeOptions {
+        // Short chunks, one per sentence
+        SynthesizeOptions::new().pacing(PacingPreset::Conversational)
+    }
+
+    #[test]
+    fn reports_bracket_every_chunk_and_end_with_completion() {
+        let engine = TtsEngine::mock(10);
+        let mut reports = Vec::new();
+        let audio = engine
+            .synthesize_with_progress(ARTICLE, opts(), |p| reports.push(p))
+            .unwrap();
+
+        let count = SynthesisPlan::new(ARTICLE, &opts()).chunks.len();
+        assert!(count > 2);
+        assert_eq!(reports.len(), 2 * count + 1);
+        for (idx, pair) in reports.chunks(2).take(count).enumerate() {
+            assert_eq!(pair[0].stage, ProgressStage::ChunkStarted);
+            assert_eq!(pair[1].stage, ProgressStage::ChunkFinished);
+            assert_eq!((pair[0].chunk, pair[1].chunk), (idx, idx));
+            assert!(pair[1].samples > pair[0].samples);
+            assert!(pair[1].chars_done > pair[0].chars_done);
+        }
+        let done = reports.last().unwrap();
+        assert_eq!(done.stage, ProgressStage::Completed);
+        assert_eq!((done.chunk, done.total_chunks), (count, count));
+        assert_eq!(done.samples, audio.len());
+        assert_eq!(done.chars_done, done.total_chars);
+        assert_eq!(done.fraction(), 1.0);
+        assert!(reports.windows(2).all(|w| w[0].elapsed <= w[1].elapsed));
+
+        // Same audio as the plain call
+        assert_eq!(audio, engine.synthesize_with(ARTICLE, opts()).unwrap());
+    }
+
+    #[test]
+    fn failures_and_fallback_still_report() {
+        let mut engine = TtsEngine::mock(10);
+        engine.mock_inference = Some(Arc::new(|tokens: &[i64], _: &[f32], _: f32| {
+            if tokens.len() > 60 {
+                Err("boom".to_string())
+            } else {
+                Ok(vec![0.25; tokens.len()])
+            }
+        }));
+        let mut stages = Vec::new();
+        let partial = engine
+            .synthesize_resumable_with_progress(ARTICLE, opts(), |p| stages.push(p.stage))
+            .unwrap_err();
+        assert_eq!(stages.last(), Some(&ProgressStage::ChunkStarted));
+        assert!(!stages.contains(&ProgressStage::Completed));
+        assert_eq!(partial.failed_chunk, 0);
+
+        let fallback = TtsEngine::without_model();
+        let mut reports = Vec::new();
+        let audio = fallback
+            .synthesize_with_progress("Hello", opts(), |p| reports.push(p))
+            .unwrap();
+        assert_eq!(reports.len(), 1);
+        assert_eq!(reports[0].stage, ProgressStage::Completed);
+        assert_eq!(reports[0].samples, audio.len());
+    }
+}
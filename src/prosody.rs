@@ -0,0 +1,208 @@
+//! Punctuation weights: heavier or lighter pauses without editing the text
+//!
+//! Kokoro takes most of its pause lengths from the punctuation tokens in the
+//! phoneme string, so repeating a token lengthens the pause and dropping it
+//! shortens it. This is a heuristic, not a duration control: how much longer
+//! a doubled comma sounds depends on the voice and the context.
+
+use serde::{Deserialize, Serialize};
+
+/// Weights above this add little except odd artifacts
+pub const MAX_PUNCTUATION_WEIGHT: f32 = 4.0;
+
+/// How many times each punctuation token is emitted (1.0 = unchanged)
+///
+/// 2.0 doubles every token of that kind, 0.0 strips them all, and fractional
+/// weights are spread evenly by position: at 0.5 every second comma is kept.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PunctuationWeights {
+    pub comma: f32,
+    pub period: f32,
+    pub question: f32,
+    pub exclamation: f32,
+    /// `…` and runs of three or more dots
+    pub ellipsis: f32,
+}
+
+impl Default for PunctuationWeights {
+    fn default() -> Self {
+        Self {
+            comma: 1.0,
+            period: 1.0,
+            question: 1.0,
+            exclamation: 1.0,
+            ellipsis: 1.0,
+        }
+    }
+}
+
+impl PunctuationWeights {
+    /// True if applying these weights leaves phonemes unchanged
+    pub fn is_neutral(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Mark {
+    Comma,
+    Period,
+    Question,
+    Exclamation,
+    Ellipsis,
+}
+
+/// Repeat or drop punctuation tokens in an (unpadded) phoneme string
+pub fn apply_punctuation_weights(phonemes: &str, weights: &PunctuationWeights) -> String {
+    if weights.is_neutral() {
+        return phonemes.to_string();
+    }
+
+    let mut seen = [0u32; 5];
+    let mut out = String::with_capacity(phonemes.len());
+    let chars: Vec<char> = phonemes.chars().collect();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let dots = chars[i..].iter().take_while(|&&c| c == '.').count();
+        let (mark, len) = match chars[i] {
+            '.' if dots >= 3 => (Some(Mark::Ellipsis), dots),
+            '.' => (Some(Mark::Period), 1),
+            '…' => (Some(Mark::Ellipsis), 1),
+            ',' => (Some(Mark::Comma), 1),
+            '?' => (Some(Mark::Question), 1),
+            '!' => (Some(Mark::Exclamation), 1),
+            _ => (None, 1),
+        };
+        let unit: String = chars[i..i + len].iter().collect();
+        i += len;
+
+        let Some(mark) = mark else {
+            out.push_str(&unit);
+            continue;
+        };
+
+        let weight = match mark {
+            Mark::Comma => weights.comma,
+            Mark::Period => weights.period,
+            Mark::Question => weights.question,
+            Mark::Exclamation => weights.exclamation,
+            Mark::Ellipsis => weights.ellipsis,
+        };
+        let n = &mut seen[mark as usize];
+        out.push_str(&unit.repeat(repeat_count(weight, *n)));
+        *n += 1;
+    }
+
+    out
+}
+
+// Copies for the n-th mark of a kind: floor((n+1)w) - floor(nw), so any run of
+// marks comes out at w times its length, rounded by position
+fn repeat_count(weight: f32, n: u32) -> usize {
+    let weight = if weight.is_finite() {
+        weight.clamp(0.0, MAX_PUNCTUATION_WEIGHT) as f64
+    } else {
+        1.0
+    };
+    let n = n as f64;
+    (((n + 1.0) * weight).floor() - (n * weight).floor()) as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{SynthesizeOptions, TtsEngine};
+    use std::sync::Arc;
+
+    const FIXTURE: &str = "wˈɛl, ðæts ɪt, ɪz ɪt, ʃˈʊɹ, wˈʌn mˈɔːɹ. ɹˈiəli? jˈɛs! wˈeɪt... ɔːɹ nˈɑːt…";
+
+    fn count(engine: &TtsEngine, phonemes: &str, mark: char) -> usize {
+        let id = engine.tokenize(&mark.to_string())[0];
+        engine.tokenize(phonemes).iter().filter(|&&t| t == id).count()
+    }
+
+    #[test]
+    fn weights_set_token_multiplicity() {
+        let engine = TtsEngine::mock(100);
+        let base = |mark| count(&engine, FIXTURE, mark);
+        assert_eq!((base(','), base('.'), base('?'), base('!'), base('…')), (4, 4, 1, 1, 1));
+
+        let heavy = apply_punctuation_weights(
+            FIXTURE,
+            &PunctuationWeights {
+                comma: 2.0,
+                question: 3.0,
+                ..Default::default()
+            },
+        );
+        assert_eq!(count(&engine, &heavy, ','), 8);
+        assert_eq!(count(&engine, &heavy, '?'), 3);
+        assert_eq!(count(&engine, &heavy, '.'), 4);
+
+        // Half the commas survive, every second one by position
+        let light = apply_punctuation_weights(
+            FIXTURE,
+            &PunctuationWeights {
+                comma: 0.5,
+                ..Default::default()
+            },
+        );
+        assert_eq!(count(&engine, &light, ','), 2);
+        assert!(light.starts_with("wˈɛl ðæts ɪt, ɪz ɪt ʃˈʊɹ,"));
+
+        // Ellipses are one unit; stripping them leaves the lone period alone
+        let flat = apply_punctuation_weights(
+            FIXTURE,
+            &PunctuationWeights {
+                ellipsis: 0.0,
+                exclamation: 0.0,
+                ..Default::default()
+            },
+        );
+        assert_eq!(count(&engine, &flat, '.'), 1);
+        assert_eq!(count(&engine, &flat, '…'), 0);
+        assert_eq!(count(&engine, &flat, '!'), 0);
+
+        assert_eq!(apply_punctuation_weights(FIXTURE, &PunctuationWeights::default()), FIXTURE);
+    }
+
+    #[test]
+    fn out_of_range_weights_are_tamed() {
+        let wild = PunctuationWeights {
+            comma: 100.0,
+            period: -1.0,
+            question: f32::NAN,
+            ..Default::default()
+        };
+        let out = apply_punctuation_weights("a, b. c?", &wild);
+        assert_eq!(out, "a,,,, b c?");
+    }
+
+    #[test]
+    fn heavier_commas_lengthen_the_audio() {
+        const COMMA_SAMPLES: usize = 2000;
+        let mut engine = TtsEngine::mock(100);
+        let comma = engine.tokenize(",")[0];
+        engine.mock_inference = Some(Arc::new(move |tokens: &[i64], _style: &[f32], _speed: f32| {
+            let commas = tokens.iter().filter(|&&t| t == comma).count();
+            Ok(vec![0.1; tokens.len() * 100 + commas * COMMA_SAMPLES])
+        }));
+
+        let text = "Well, you see, it works, mostly";
+        let mut lengths = Vec::new();
+        for weight in [0.0, 1.0, 2.0] {
+            let opts = SynthesizeOptions::new().punctuation_weights(PunctuationWeights {
+                comma: weight,
+                ..Default::default()
+            });
+            lengths.push(engine.synthesize_with(text, opts).unwrap().len());
+        }
+
+        // Each step adds one token per comma in the text
+        let per_step = 3 * (100 + COMMA_SAMPLES);
+        assert_eq!(lengths[1] - lengths[0], per_step);
+        assert_eq!(lengths[2] - lengths[1], per_step);
+    }
+}
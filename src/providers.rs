@@ -0,0 +1,182 @@
+//! Hardware execution providers for the model session
+//!
+//! Built with the `cuda`, `coreml` or `directml` feature, the engine registers
+//! that ONNX Runtime execution provider when it creates its session. The
+//! default, `ExecutionProvider::Auto`, tries every provider compiled in and
+//! then the CPU. A provider can register and still not work (missing driver
+//! libraries, an unsupported GPU), so before a session on it is accepted a
+//! tiny inference runs on it; if registration, session creation or that
+//! inference fails, the next candidate is tried, ending with the CPU.
+//! `TtsEngine::active_provider` says which one was kept.
+//!
+//! ONNX Runtime may still place single operators the provider lacks on the
+//! CPU inside an accelerated session; that is normal and not reported.
+
+use std::fmt;
+use std::str::FromStr;
+
+use ort::session::{builder::SessionBuilder, Session};
+
+use crate::voices::STYLE_DIM;
+
+/// Where the model runs
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum ExecutionProvider {
+    /// Every accelerator compiled in, in the order below, then the CPU
+    #[default]
+    Auto,
+    Cpu,
+    /// NVIDIA GPUs (`cuda` feature)
+    Cuda,
+    /// Apple Neural Engine and GPU (`coreml` feature)
+    CoreMl,
+    /// Windows GPUs through DirectX 12 (`directml` feature)
+    DirectMl,
+}
+
+impl ExecutionProvider {
+    /// Whether this build can register the provider at all
+    pub fn is_compiled_in(&self) -> bool {
+        match self {
+            ExecutionProvider::Auto | ExecutionProvider::Cpu => true,
+            ExecutionProvider::Cuda => cfg!(feature = "cuda"),
+            ExecutionProvider::CoreMl => cfg!(feature = "coreml"),
+            ExecutionProvider::DirectMl => cfg!(feature = "directml"),
+        }
+    }
+
+    // Providers to try, in order; always ends with the CPU
+    pub(crate) fn candidates(&self) -> Vec<ExecutionProvider> {
+        let accelerators = [
+            ExecutionProvider::Cuda,
+            ExecutionProvider::CoreMl,
+            ExecutionProvider::DirectMl,
+        ];
+        let mut candidates: Vec<ExecutionProvider> = match self {
+            ExecutionProvider::Auto => accelerators
+                .into_iter()
+                .filter(|p| p.is_compiled_in())
+                .collect(),
+            ExecutionProvider::Cpu => Vec::new(),
+            requested if requested.is_compiled_in() => vec![*requested],
+            requested => {
+                #[cfg(not(feature = "as-lib"))]
+                eprintln!(
+                    "⚠️  {} support is not compiled in (enable the `{}` feature); using the CPU",
+                    requested,
+                    requested.feature()
+                );
+                Vec::new()
+            }
+        };
+        candidates.push(ExecutionProvider::Cpu);
+        candidates
+    }
+
+    fn feature(&self) -> &'static str {
+        match self {
+            ExecutionProvider::Cuda => "cuda",
+            ExecutionProvider::CoreMl => "coreml",
+            ExecutionProvider::DirectMl => "directml",
+            ExecutionProvider::Auto | ExecutionProvider::Cpu => "",
+        }
+    }
+
+    // Register on `builder`; the CPU needs nothing
+    pub(crate) fn register(&self, builder: &mut SessionBuilder) -> Result<(), String> {
+        #[allow(unused_imports)]
+        use ort::ep::ExecutionProvider as _;
+
+        match self {
+            ExecutionProvider::Auto | ExecutionProvider::Cpu => Ok(()),
+            #[cfg(feature = "cuda")]
+            ExecutionProvider::Cuda => ort::ep::CUDA::default()
+                .register(builder)
+                .map_err(|e| e.to_string()),
+            #[cfg(feature = "coreml")]
+            ExecutionProvider::CoreMl => ort::ep::CoreML::default()
+                .register(builder)
+                .map_err(|e| e.to_string()),
+            #[cfg(feature = "directml")]
+            ExecutionProvider::DirectMl => ort::ep::DirectML::default()
+                .register(builder)
+                .map_err(|e| e.to_string()),
+            #[allow(unreachable_patterns)]
+            other => Err(format!("{} support is not compiled in", other)),
+        }
+    }
+}
+
+// A few tokens with a neutral style: fails if the provider can't actually run
+// the model
+pub(crate) fn probe(session: &mut Session) -> Result<(), String> {
+    let audio = crate::infer(session, vec![0, 50, 0], vec![0.0; STYLE_DIM], 1.0)?;
+    if audio.is_empty() || audio.iter().any(|s| !s.is_finite()) {
+        return Err("warm-up inference produced no usable audio".to_string());
+    }
+    Ok(())
+}
+
+impl FromStr for ExecutionProvider {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s.trim().to_lowercase().as_str() {
+            "auto" => Ok(ExecutionProvider::Auto),
+            "cpu" => Ok(ExecutionProvider::Cpu),
+            "cuda" => Ok(ExecutionProvider::Cuda),
+            "coreml" => Ok(ExecutionProvider::CoreMl),
+            "directml" => Ok(ExecutionProvider::DirectMl),
+            _ => Err(format!(
+                "Unknown execution provider '{}' (expected auto, cpu, cuda, coreml or directml)",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for ExecutionProvider {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExecutionProvider::Auto => write!(f, "auto"),
+            ExecutionProvider::Cpu => write!(f, "CPU"),
+            ExecutionProvider::Cuda => write!(f, "CUDA"),
+            ExecutionProvider::CoreMl => write!(f, "CoreML"),
+            ExecutionProvider::DirectMl => write!(f, "DirectML"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_choice_ends_on_the_cpu() {
+        assert_eq!(
+            ExecutionProvider::Cpu.candidates(),
+            [ExecutionProvider::Cpu]
+        );
+        for provider in [
+            ExecutionProvider::Auto,
+            ExecutionProvider::Cuda,
+            ExecutionProvider::CoreMl,
+            ExecutionProvider::DirectMl,
+        ] {
+            let candidates = provider.candidates();
+            assert_eq!(candidates.last(), Some(&ExecutionProvider::Cpu));
+            assert!(candidates.iter().all(|p| p.is_compiled_in()));
+        }
+        assert_eq!(
+            ExecutionProvider::Cuda.candidates().len(),
+            if cfg!(feature = "cuda") { 2 } else { 1 }
+        );
+
+        assert_eq!("CUDA".parse(), Ok(ExecutionProvider::Cuda));
+        assert_eq!(
+            ExecutionProvider::DirectMl.to_string().parse(),
+            Ok(ExecutionProvider::DirectMl)
+        );
+        assert!("tpu".parse::<ExecutionProvider>().is_err());
+    }
+}
@@ -0,0 +1,505 @@
+//! Read-aloud progress for long documents (`kokoro-speak read`)
+//!
+//! A document is read segment by segment, and after each segment finishes
+//! playing a small state file records how far playback got: the segment
+//! index, the byte offset just past it, and its text. A later run can seek to
+//! that offset instead of starting over. State files are keyed by document
+//! path and carry a hash of the content, so an edited document is detected;
+//! resuming then re-anchors on the last played segment's text if it can
+//! still be found. With redaction on (see `privacy`) only a hash of that
+//! segment is stored, and re-anchoring looks for a segment with that hash.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::AtomicBool;
+
+use serde::{Deserialize, Serialize};
+
+use crate::chunk_cache::Fnv64;
+use crate::chunk_stream::{play_while_synthesizing, StreamReport, StreamedChunk};
+use crate::{
+    cache_dir, privacy, KokoroError, SynthesizeOptions, TtsEngine, MAX_CHARS_PER_CHUNK,
+};
+
+/// A run of sentences read as one unit, with its byte range in the document
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReadSegment {
+    pub start: usize,
+    pub end: usize,
+    pub text: String,
+}
+
+/// How far a previous run got through a document
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ReadProgress {
+    pub document_hash: String,
+    /// Index of the last segment that finished playing
+    pub last_chunk: usize,
+    /// Byte offset just past that segment
+    pub offset: usize,
+    /// Text of that segment, for re-anchoring in an edited document
+    /// (empty when redacting)
+    pub last_chunk_text: String,
+    /// `document_hash` of that segment, stored instead of the text when redacting
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_chunk_hash: Option<String>,
+}
+
+/// Where to continue reading, see `ReadProgress::locate`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ResumePoint {
+    /// Same document; continue at this offset
+    Unchanged(usize),
+    /// Document changed but the last played segment was found; continue after it
+    Reanchored(usize),
+    /// Document changed beyond recognition
+    Lost,
+}
+
+impl ReadProgress {
+    /// Missing state files are not an error: there is simply nothing to resume
+    pub fn load(path: &Path) -> Result<Option<Self>, String> {
+        match fs::read_to_string(path) {
+            Ok(json) => serde_json::from_str(&json)
+                .map(Some)
+                .map_err(|e| format!("Invalid progress file {}: {}", path.display(), e)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(format!(
+                "Failed to read progress file {}: {}",
+                path.display(),
+                e
+            )),
+        }
+    }
+
+    /// Write via a temp file and rename, so a kill mid-write never leaves a torn file
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)
+                .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        }
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize progress: {}", e))?;
+
+        let tmp = path.with_extension("tmp");
+        fs::write(&tmp, json).map_err(|e| format!("Failed to write progress: {}", e))?;
+        fs::rename(&tmp, path).map_err(|e| format!("Failed to store progress: {}", e))
+    }
+
+    pub fn clear(path: &Path) -> Result<(), String> {
+        match fs::remove_file(path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(format!(
+                "Failed to remove progress file {}: {}",
+                path.display(),
+                e
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Find where to continue in `text`, which may have changed since
+    pub fn locate(&self, text: &str) -> ResumePoint {
+        if self.document_hash == document_hash(text) && text.is_char_boundary(self.offset) {
+            return ResumePoint::Unchanged(self.offset);
+        }
+
+        if let (true, Some(hash)) = (self.last_chunk_text.is_empty(), &self.last_chunk_hash) {
+            let mut matches = segment_document(text)
+                .into_iter()
+                .filter(|segment| document_hash(&segment.text) == *hash);
+            return match (matches.next(), matches.next()) {
+                (Some(segment), None) => ResumePoint::Reanchored(segment.end),
+                _ => ResumePoint::Lost,
+            };
+        }
+
+        // Only trust an anchor that occurs exactly once
+        let anchor = self.last_chunk_text.as_str();
+        match text.find(anchor) {
+            Some(pos) if !anchor.is_empty() && !text[pos + anchor.len()..].contains(anchor) => {
+                ResumePoint::Reanchored(pos + anchor.len())
+            }
+            _ => ResumePoint::Lost,
+        }
+    }
+}
+
+/// Progress file for a document, under the cache directory
+pub fn progress_path(document: &Path) -> PathBuf {
+    let document = fs::canonicalize(document).unwrap_or_else(|_| document.to_path_buf());
+    let mut hash = Fnv64::new();
+    hash.write(document.to_string_lossy().as_bytes());
+    cache_dir()
+        .join("reading")
+        .join(format!("{:016x}.json", hash.0))
+}
+
+/// Content hash stored in progress files
+pub fn document_hash(text: &str) -> String {
+    let mut hash = Fnv64::new();
+    hash.write(text.as_bytes());
+    format!("{:016x}", hash.0)
+}
+
+/// Split a document into segments of whole sentences
+///
+/// Segments never span a paragraph break and stay within
+/// `MAX_CHARS_PER_CHUNK` unless a single sentence is longer.
+pub fn segment_document(text: &str) -> Vec<ReadSegment> {
+    let mut segments = Vec::new();
+    let mut current: Option<(usize, usize)> = None;
+
+    for (start, end) in sentence_spans(text) {
+        current = match current {
+            Some((seg_start, seg_end))
+                if end - seg_start <= MAX_CHARS_PER_CHUNK
+                    && text[seg_end..start].matches('\n').count() < 2 =>
+            {
+                Some((seg_start, end))
+            }
+            Some((seg_start, seg_end)) => {
+                segments.push(segment(text, seg_start, seg_end));
+                Some((start, end))
+            }
+            None => Some((start, end)),
+        };
+    }
+    if let Some((start, end)) = current {
+        segments.push(segment(text, start, end));
+    }
+
+    segments
+}
+
+fn segment(text: &str, start: usize, end: usize) -> ReadSegment {
+    ReadSegment {
+        start,
+        end,
+        text: text[start..end].to_string(),
+    }
+}
+
+// Byte ranges of sentences, trimmed; a blank line also ends a sentence
+fn sentence_spans(text: &str) -> Vec<(usize, usize)> {
+    let mut spans = Vec::new();
+    let mut start = None;
+    let mut chars = text.char_indices().peekable();
+
+    while let Some((i, c)) = chars.next() {
+        let next = chars.peek().map(|&(_, n)| n);
+
+        if c == '\n'
+            && text[i + 1..]
+                .trim_start_matches([' ', '\t', '\r'])
+                .starts_with('\n')
+        {
+            if let Some(s) = start.take() {
+                spans.push((s, s + text[s..i].trim_end().len()));
+            }
+            continue;
+        }
+        if c.is_whitespace() && start.is_none() {
+            continue;
+        }
+
+        let s = *start.get_or_insert(i);
+        if matches!(c, '.' | '!' | '?' | '…') && next.is_none_or(char::is_whitespace) {
+            spans.push((s, i + c.len_utf8()));
+            start = None;
+        }
+    }
+    if let Some(s) = start {
+        spans.push((s, s + text[s..].trim_end().len()));
+    }
+
+    spans
+}
+
+/// Read `text` from `from_offset`, saving progress to `state_path` as segments finish
+///
+/// `play` is called with the engine and each rendered segment and returns
+/// once it has been heard; an error from it (the user stopping playback) ends the read with the
+/// progress of the segments before it kept. A fully read document clears its
+/// progress file.
+pub fn read_document(
+    engine: &mut TtsEngine,
+    text: &str,
+    opts: &SynthesizeOptions,
+    state_path: &Path,
+    from_offset: usize,
+    mut play: impl FnMut(&TtsEngine, &ReadSegment, &[f32]) -> Result<(), String>,
+) -> Result<(), String> {
+    let hash = document_hash(text);
+
+    for (index, segment) in segment_document(text).into_iter().enumerate() {
+        if segment.end <= from_offset {
+            continue;
+        }
+
+        let audio = engine.synthesize_with(&segment.text, opts.clone())?;
+        play(engine, &segment, &audio)?;
+        progress_after(&hash, index, &segment, privacy::redact_text()).save(state_path)?;
+    }
+
+    ReadProgress::clear(state_path)
+}
+
+/// `read_document` that renders the next segment while the current one plays
+///
+/// `play` runs on its own thread (see `chunk_stream::play_while_synthesizing`);
+/// progress is saved after each segment it returns from. Setting `stop` ends
+/// the read after the current segment, keeping the progress file.
+pub fn read_document_streaming(
+    engine: &TtsEngine,
+    text: &str,
+    opts: &SynthesizeOptions,
+    state_path: &Path,
+    from_offset: usize,
+    stop: &AtomicBool,
+    mut play: impl FnMut(&ReadSegment, &[f32]) -> Result<(), String> + Send,
+) -> Result<StreamReport, String> {
+    let hash = document_hash(text);
+    // Read here: the flag is per thread under test
+    let redact = privacy::redact_text();
+    let segments = segment_document(text);
+    let count = segments.len();
+
+    let chunks = segments
+        .iter()
+        .enumerate()
+        .filter(|(_, segment)| segment.end > from_offset)
+        .map(|(index, segment)| {
+            let audio = engine
+                .synthesize_stream(&segment.text, opts.clone())?
+                .map(|chunk| chunk.map(|chunk| chunk.audio))
+                .collect::<Result<Vec<_>, KokoroError>>()?
+                .concat();
+            Ok(StreamedChunk {
+                index,
+                count,
+                text: segment.text.clone(),
+                audio,
+            })
+        });
+
+    let report = play_while_synthesizing(chunks, stop, &|_| {}, |chunk| {
+        let segment = &segments[chunk.index];
+        play(segment, &chunk.audio)?;
+        Ok(progress_after(&hash, chunk.index, segment, redact).save(state_path)?)
+    })?;
+
+    if !report.interrupted {
+        ReadProgress::clear(state_path)?;
+    }
+    Ok(report)
+}
+
+// Progress once `segment` (at `index`) has been heard
+fn progress_after(hash: &str, index: usize, segment: &ReadSegment, redact: bool) -> ReadProgress {
+    let (last_chunk_text, last_chunk_hash) = if redact {
+        (String::new(), Some(document_hash(&segment.text)))
+    } else {
+        (segment.text.clone(), None)
+    };
+    ReadProgress {
+        document_hash: hash.to_string(),
+        last_chunk: index,
+        offset: segment.end,
+        last_chunk_text,
+        last_chunk_hash,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Sentences of ~100 chars, so every segment is a single sentence
+    const BOOK: &str = "Chapter one\n\n\
+        The lighthouse keeper climbed the stairs every evening, counting each of the worn stone steps aloud.\n\
+        She knew there were one hundred and twelve of them, but counting kept the gathering dark at bay.\n\n\
+        At the top the lamp waited, brass fittings cold and smelling faintly of old paraffin and salt.\n\
+        She trimmed the wick, struck a match, and watched the flame steady itself behind the glass.\n\
+        Out at sea a ship answered with three short blasts, and then the long night was quiet once more.";
+
+    fn temp_state(name: &str) -> PathBuf {
+        let path = std::env::temp_dir()
+            .join(format!("kokoro_reading_{}_{}", name, std::process::id()))
+            .join("progress.json");
+        ReadProgress::clear(&path).unwrap();
+        path
+    }
+
+    #[test]
+    fn segments_track_source_offsets() {
+        let segments = segment_document(BOOK);
+        assert_eq!(segments.len(), 6);
+        assert_eq!(segments[0].text, "Chapter one");
+        for segment in &segments {
+            assert_eq!(&BOOK[segment.start..segment.end], segment.text);
+            assert!(!segment.text.contains("\n\n"));
+        }
+
+        // Short sentences share a segment, but not across a paragraph break
+        let short = segment_document("One. Two! Three?\n\nFour… five");
+        let texts: Vec<_> = short.iter().map(|s| s.text.as_str()).collect();
+        assert_eq!(texts, ["One. Two! Three?", "Four… five"]);
+    }
+
+    #[test]
+    fn interrupted_read_resumes_after_last_played_chunk() {
+        let state = temp_state("resume");
+        let mut engine = TtsEngine::mock(10);
+        let opts = SynthesizeOptions::new();
+
+        // Stubbed playback: the user stops during the fourth segment
+        let mut played = Vec::new();
+        let result = read_document(&mut engine, BOOK, &opts, &state, 0, |_, segment, _| {
+            if played.len() == 3 {
+                return Err("stopped".to_string());
+            }
+            played.push(segment.text.clone());
+            Ok(())
+        });
+        assert_eq!(result.unwrap_err(), "stopped");
+        assert!(!state.with_extension("tmp").exists());
+
+        let progress = ReadProgress::load(&state).unwrap().unwrap();
+        assert_eq!(progress.last_chunk, 2);
+        assert_eq!(progress.last_chunk_text, played[2]);
+
+        // A new process picks up with the fourth segment
+        let ResumePoint::Unchanged(offset) = progress.locate(BOOK) else {
+            panic!("document did not change");
+        };
+        let mut resumed = Vec::new();
+        read_document(&mut engine, BOOK, &opts, &state, offset, |_, segment, _| {
+            resumed.push(segment.clone());
+            Ok(())
+        })
+        .unwrap();
+
+        let segments = segment_document(BOOK);
+        assert_eq!(resumed, segments[3..]);
+        assert!(ReadProgress::load(&state).unwrap().is_none());
+    }
+
+    #[test]
+    fn streaming_read_stops_after_the_playing_segment() {
+        let state = temp_state("streaming");
+        let engine = TtsEngine::mock(10);
+        let stop = AtomicBool::new(false);
+
+        let mut played = 0;
+        let report = read_document_streaming(
+            &engine,
+            BOOK,
+            &SynthesizeOptions::new(),
+            &state,
+            0,
+            &stop,
+            |_, _| {
+                played += 1;
+                if played == 3 {
+                    stop.store(true, std::sync::atomic::Ordering::Relaxed);
+                }
+                Ok(())
+            },
+        )
+        .unwrap();
+        assert!(report.interrupted);
+        assert_eq!(report.played, 3);
+
+        let progress = ReadProgress::load(&state).unwrap().unwrap();
+        assert_eq!(progress.last_chunk, 2);
+        assert_eq!(progress.offset, segment_document(BOOK)[2].end);
+        ReadProgress::clear(&state).unwrap();
+    }
+
+    #[test]
+    fn edited_documents_reanchor_or_restart() {
+        let segments = segment_document(BOOK);
+        let progress = ReadProgress {
+            document_hash: document_hash(BOOK),
+            last_chunk: 2,
+            offset: segments[2].end,
+            last_chunk_text: segments[2].text.clone(),
+            last_chunk_hash: None,
+        };
+
+        let edited = format!("Foreword\n\nA note before we start.\n\n{}", BOOK);
+        let ResumePoint::Reanchored(offset) = progress.locate(&edited) else {
+            panic!("expected to re-anchor");
+        };
+        assert!(edited[..offset].ends_with(&segments[2].text));
+        assert!(edited[offset..].trim_start().starts_with(&segments[3].text));
+
+        assert_eq!(
+            progress.locate("Something else entirely."),
+            ResumePoint::Lost
+        );
+    }
+
+    #[test]
+    fn redacted_progress_stores_no_text() {
+        let state = temp_state("redacted");
+        let mut engine = TtsEngine::mock(10);
+
+        let mut heard = 0;
+        let result = crate::privacy::with_redact_text(true, || {
+            read_document(
+                &mut engine,
+                BOOK,
+                &SynthesizeOptions::new(),
+                &state,
+                0,
+                |_, _, _| {
+                    heard += 1;
+                    if heard > 3 {
+                        Err("stopped".to_string())
+                    } else {
+                        Ok(())
+                    }
+                },
+            )
+        });
+        assert!(result.is_err());
+
+        let saved = fs::read_to_string(&state).unwrap();
+        assert!(!saved.contains("lighthouse"), "{}", saved);
+        assert!(!saved.contains("hundred"), "{}", saved);
+
+        // Still re-anchors after an edit, on the hash alone
+        let progress = ReadProgress::load(&state).unwrap().unwrap();
+        let edited = format!("Foreword\n\n{}", BOOK);
+        let segments = segment_document(BOOK);
+        let ResumePoint::Reanchored(offset) = progress.locate(&edited) else {
+            panic!("expected to re-anchor");
+        };
+        assert!(edited[..offset].ends_with(&segments[2].text));
+
+        fs::remove_file(state).ok();
+    }
+
+    #[test]
+    fn saving_replaces_the_state_file_whole() {
+        let state = temp_state("atomic");
+        let mut progress = ReadProgress {
+            document_hash: document_hash(BOOK),
+            last_chunk: 0,
+            offset: 11,
+            last_chunk_text: "Chapter one".to_string(),
+            last_chunk_hash: None,
+        };
+        progress.save(&state).unwrap();
+
+        progress.last_chunk = 1;
+        progress.offset = 103;
+        progress.save(&state).unwrap();
+
+        assert_eq!(ReadProgress::load(&state).unwrap(), Some(progress));
+        assert!(!state.with_extension("tmp").exists());
+        ReadProgress::clear(&state).unwrap();
+        ReadProgress::clear(&state).unwrap();
+    }
+}
@@ -0,0 +1,205 @@
+//! Sample rate conversion
+//!
+//! The model speaks at 24kHz; speech recognizers and telephony want 16kHz,
+//! mixers 44.1 or 48kHz. `resample` converts between any two rates with a
+//! Kaiser-windowed sinc filter: going down it removes everything above the
+//! new Nyquist frequency before it can fold back as aliasing, going up it
+//! removes the images that plain interpolation leaves. Stopband attenuation
+//! is about 80 dB.
+
+use std::sync::OnceLock;
+
+// Zero crossings of the sinc on each side of a sample
+const ZERO_CROSSINGS: usize = 32;
+// Table entries per zero crossing; values in between are interpolated
+const RESOLUTION: usize = 256;
+const KAISER_BETA: f64 = 8.0;
+// Passband edge as a share of the lower Nyquist frequency, leaving the rest
+// for the filter to roll off before aliasing starts
+const PASSBAND: f64 = 0.92;
+
+/// `audio` at `from` Hz converted to `to` Hz
+///
+/// The result has `len * to / from` samples (rounded down). Equal rates give
+/// the input back; a rate of 0 gives no samples.
+///
+/// # Example
+/// ```
+/// use kokoro_tiny::resample;
+///
+/// let model: Vec<f32> = (0..24_000).map(|i| (i as f32 * 0.1).sin()).collect();
+/// let phone = resample(&model, 24_000, 16_000);
+/// assert_eq!(phone.len(), 16_000);
+/// assert_eq!(resample(&model, 24_000, 48_000).len(), 48_000);
+/// ```
+pub fn resample(audio: &[f32], from: u32, to: u32) -> Vec<f32> {
+    if from == to {
+        return audio.to_vec();
+    }
+    if from == 0 || to == 0 || audio.is_empty() {
+        return Vec::new();
+    }
+
+    let len = (audio.len() as u64 * u64::from(to) / u64::from(from)) as usize;
+    let step = f64::from(from) / f64::from(to);
+    // Cutoff relative to the input's Nyquist frequency
+    let cutoff = PASSBAND * (f64::from(to) / f64::from(from)).min(1.0);
+    // The kernel widens as the cutoff drops, so it spans as many zero crossings
+    let reach = ZERO_CROSSINGS as f64 / cutoff;
+    let table = kernel();
+
+    let mut out = Vec::with_capacity(len);
+    let last = audio.len() as i64 - 1;
+    for n in 0..len {
+        let center = n as f64 * step;
+        let (mut sum, mut weights) = (0.0f64, 0.0f64);
+        for k in (center - reach).ceil() as i64..=(center + reach).floor() as i64 {
+            let at = (k as f64 - center).abs() * cutoff * RESOLUTION as f64;
+            let idx = at as usize;
+            if idx + 1 >= table.len() {
+                continue;
+            }
+            let weight = table[idx] + (table[idx + 1] - table[idx]) * (at - idx as f64);
+            // Past either end the edge sample is held, so a chunk resampled
+            // on its own doesn't fade in or out
+            sum += f64::from(audio[k.clamp(0, last) as usize]) * weight;
+            weights += weight;
+        }
+        // Divided by the weights rather than scaled by the cutoff, so a
+        // steady level comes out exactly as it went in
+        out.push((sum / weights) as f32);
+    }
+    out
+}
+
+// Interleaved `audio` converted one channel at a time
+pub(crate) fn resample_channels(audio: &[f32], channels: u16, from: u32, to: u32) -> Vec<f32> {
+    if channels <= 1 || from == to {
+        return resample(audio, from, to);
+    }
+    let channels = usize::from(channels);
+    let split: Vec<Vec<f32>> = (0..channels)
+        .map(|c| {
+            let channel: Vec<f32> = audio.iter().skip(c).step_by(channels).copied().collect();
+            resample(&channel, from, to)
+        })
+        .collect();
+    let frames = split.iter().map(Vec::len).min().unwrap_or(0);
+    (0..frames)
+        .flat_map(|i| split.iter().map(move |channel| channel[i]))
+        .collect()
+}
+
+// One side of the windowed sinc, sampled `RESOLUTION` times per zero crossing
+fn kernel() -> &'static [f64] {
+    static KERNEL: OnceLock<Vec<f64>> = OnceLock::new();
+    KERNEL.get_or_init(|| {
+        let len = ZERO_CROSSINGS * RESOLUTION + 2;
+        let scale = bessel_i0(KAISER_BETA);
+        (0..len)
+            .map(|i| {
+                let x = i as f64 / RESOLUTION as f64;
+                let edge = x / ZERO_CROSSINGS as f64;
+                if edge >= 1.0 {
+                    return 0.0;
+                }
+                let sinc = if i == 0 {
+                    1.0
+                } else {
+                    (std::f64::consts::PI * x).sin() / (std::f64::consts::PI * x)
+                };
+                sinc * bessel_i0(KAISER_BETA * (1.0 - edge * edge).sqrt()) / scale
+            })
+            .collect()
+    })
+}
+
+// Modified Bessel function of the first kind, order 0 (power series)
+fn bessel_i0(x: f64) -> f64 {
+    let mut sum = 1.0;
+    let mut term = 1.0;
+    let half = x / 2.0;
+    for k in 1..50 {
+        term *= (half / k as f64).powi(2);
+        sum += term;
+        if term < sum * 1e-12 {
+            break;
+        }
+    }
+    sum
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::PI;
+
+    fn tone(hz: f64, rate: u32, seconds: f64) -> Vec<f32> {
+        let len = (seconds * f64::from(rate)) as usize;
+        (0..len)
+            .map(|i| (2.0 * PI * hz * i as f64 / f64::from(rate)).sin() as f32 * 0.8)
+            .collect()
+    }
+
+    // Amplitude at `hz` over one second from the middle, where whole-Hz
+    // frequencies land exactly on a bin
+    fn amplitude(audio: &[f32], rate: u32, hz: f64) -> f64 {
+        let window = &audio[rate as usize / 2..rate as usize * 3 / 2];
+        let step = 2.0 * PI * hz / f64::from(rate);
+        let (re, im) = window
+            .iter()
+            .enumerate()
+            .fold((0.0, 0.0), |(re, im), (i, &s)| {
+                let s = f64::from(s);
+                (
+                    re + s * (step * i as f64).cos(),
+                    im - s * (step * i as f64).sin(),
+                )
+            });
+        2.0 * (re * re + im * im).sqrt() / window.len() as f64
+    }
+
+    fn db(ratio: f64) -> f64 {
+        20.0 * ratio.log10()
+    }
+
+    #[test]
+    fn frequencies_survive_and_aliases_are_filtered() {
+        for to in [16_000, 22_050, 44_100, 48_000] {
+            let audio = resample(&tone(1000.0, 24_000, 2.0), 24_000, to);
+            assert_eq!(audio.len(), to as usize * 2);
+            let kept = amplitude(&audio, to, 1000.0);
+            assert!((kept - 0.8).abs() < 0.01, "{} Hz: {}", to, kept);
+            // Nothing else anywhere else
+            for hz in [500.0, 2000.0, 3000.0, 5000.0] {
+                assert!(
+                    db(amplitude(&audio, to, hz) / 0.8) < -50.0,
+                    "{} Hz at {}",
+                    hz,
+                    to
+                );
+            }
+        }
+
+        // 10 kHz can't be kept at 16 kHz; it would alias to 6 kHz
+        let down = resample(&tone(10_000.0, 24_000, 2.0), 24_000, 16_000);
+        assert!(db(amplitude(&down, 16_000, 6000.0) / 0.8) < -50.0);
+        // Going up, the 5 kHz tone has an image at 24 - 5 = 19 kHz
+        let up = resample(&tone(5000.0, 24_000, 2.0), 24_000, 48_000);
+        assert!((amplitude(&up, 48_000, 5000.0) - 0.8).abs() < 0.01);
+        assert!(db(amplitude(&up, 48_000, 19_000.0) / 0.8) < -50.0);
+    }
+
+    #[test]
+    fn edge_cases() {
+        let audio = vec![0.25; 480];
+        assert_eq!(resample(&audio, 24_000, 24_000), audio);
+        assert!(resample(&audio, 0, 24_000).is_empty());
+        assert!(resample(&audio, 24_000, 0).is_empty());
+        assert!(resample(&[], 24_000, 16_000).is_empty());
+        // A steady level stays exactly where it was, up to the edges
+        let down = resample(&audio, 24_000, 8_000);
+        assert_eq!(down, vec![0.25; 160]);
+        assert_eq!(resample(&audio, 24_000, 44_100), vec![0.25; 882]);
+    }
+}
@@ -0,0 +1,707 @@
+//! Resumable long-form synthesis
+//!
+//! Long text is rendered chunk by chunk. When a chunk fails (phonemizer error,
+//! inference failure) the audio rendered so far is handed back together with
+//! the chunk plan instead of being dropped, so callers can keep what exists
+//! and continue from the failed chunk later - even from another process, via
+//! a small `.resume` file saved next to the partial audio.
+
+use std::collections::HashMap;
+use std::fs;
+use std::time::Instant;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    amplify_audio, append_with_crossfade, clamp_pitch, default_lang, markup, needs_chunking,
+    pacing, privacy, progress, rng, shift_pitch, spelling, split_text_for_tts, trim_silence,
+    wav_to_f32, AudioDuration, CapsPolicy, EmojiMode, KokoroError, Pacing, PauseConfig, Preprocess,
+    ProgressStage, PunctuationWeights, SpeedMode, SpellPattern, SynthesisProgress,
+    SynthesizeOptions, TtsEngine, UnitWords, UrlStyle, DEFAULT_VOICE, LONG_TEXT_THRESHOLD,
+    MAX_CHARS_PER_CHUNK, MAX_ENGINE_SPEED, MIN_ENGINE_SPEED, SAMPLE_RATE,
+};
+
+/// Text split into the chunks synthesis will render, plus the settings used
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SynthesisPlan {
+    pub chunks: Vec<String>,
+    pub voice: String,
+    /// User-facing speed (1.0 = normal)
+    pub speed: f32,
+    #[serde(default)]
+    pub speed_mode: SpeedMode,
+    pub gain: f32,
+    pub lang: String,
+    pub caps_policy: CapsPolicy,
+    #[serde(default)]
+    pub chunk_cache: bool,
+    #[serde(default)]
+    pub punctuation_weights: PunctuationWeights,
+    #[serde(default)]
+    pub lenient_language: bool,
+    #[serde(default)]
+    pub strict_characters: bool,
+    #[serde(default)]
+    pub pacing: Pacing,
+    /// Chunks that start a new paragraph (joined with `pacing.paragraph_gap_ms` of silence)
+    #[serde(default)]
+    pub paragraph_breaks: Vec<usize>,
+    #[serde(default)]
+    pub extra_units: HashMap<String, UnitWords>,
+    #[serde(default)]
+    pub style_temperature: f32,
+    /// Resolved seed, so a resumed render keeps the same take
+    #[serde(default)]
+    pub style_seed: u64,
+    /// Pitch factor, already clamped (1.0 = the voice's own)
+    #[serde(default = "unit_pitch")]
+    pub pitch: f32,
+    #[serde(default)]
+    pub trim_silence: bool,
+    /// Fade at both ends of the result, 0 for none; plans saved before it
+    /// existed are rendered without
+    #[serde(default)]
+    pub edge_fade_ms: u32,
+    /// No text normalization (`SynthesizeOptions::raw_text`)
+    #[serde(default)]
+    pub raw_text: bool,
+    /// How much of a URL is read out
+    #[serde(default)]
+    pub url_style: UrlStyle,
+    #[serde(default)]
+    pub emoji: EmojiMode,
+    #[serde(default)]
+    pub spell_patterns: Vec<SpellPattern>,
+    /// Silence at clause boundaries; chunks are then split into clauses
+    #[serde(default)]
+    pub pauses: Option<PauseConfig>,
+}
+
+// Resume files from before `pitch` existed render unshifted
+fn unit_pitch() -> f32 {
+    1.0
+}
+
+impl SynthesisPlan {
+    /// Split `text` the same way `synthesize_with` does
+    pub fn new(text: &str, opts: &SynthesizeOptions) -> Self {
+        let mut pacing = opts.pacing.map(|p| p.pacing()).unwrap_or_default();
+        // Explicit sizes win over the preset's
+        if let Some(chars) = opts.chunk_max_chars {
+            pacing.chunk_max_chars = chars;
+        }
+        if let Some(ms) = opts.crossfade_ms {
+            pacing.crossfade_ms = ms;
+        }
+        if let Some(ms) = opts.paragraph_gap_ms {
+            pacing.paragraph_gap_ms = ms;
+        }
+        let threshold = opts.chunk_threshold.unwrap_or(LONG_TEXT_THRESHOLD);
+        // Explicit weights win; neutral weights mean "not set"
+        let punctuation_weights = match opts.pacing {
+            Some(preset) if opts.punctuation_weights.is_neutral() => preset.punctuation_weights(),
+            _ => opts.punctuation_weights,
+        };
+
+        let preprocessed;
+        let text = if opts.preprocess == Preprocess::Plain {
+            text
+        } else {
+            preprocessed = opts.preprocess.apply(text);
+            preprocessed.as_str()
+        };
+        // The chunk plan has no place for pauses; `synthesize_with` reads them
+        let plain;
+        let text = if opts.markup {
+            plain = markup::plain_text(text);
+            plain.as_str()
+        } else {
+            text
+        };
+
+        // Long-form text is split into paragraphs first, joined with silence
+        let paragraph_gaps = pacing.paragraph_gap_ms > 0 || opts.pauses.is_some();
+        let paragraphs = if paragraph_gaps && needs_chunking(text, threshold) {
+            pacing::paragraphs(text)
+        } else {
+            vec![text]
+        };
+
+        let mut chunks = Vec::new();
+        let mut paragraph_breaks = Vec::new();
+        for paragraph in paragraphs {
+            if !chunks.is_empty() {
+                paragraph_breaks.push(chunks.len());
+            }
+            // Short text goes through in one pass for predictable cadence
+            if needs_chunking(paragraph, threshold)
+                || paragraph.chars().count() > pacing.chunk_max_chars
+            {
+                chunks.extend(
+                    split_text_for_tts(paragraph, pacing.chunk_max_chars, &opts.abbreviations)
+                        .into_iter()
+                        .filter(|chunk| !chunk.trim().is_empty()),
+                );
+            } else {
+                chunks.push(paragraph.to_string());
+            }
+        }
+        // One chunk per clause, so every pause falls on a join
+        if let Some(pauses) = &opts.pauses {
+            let mut clauses = Vec::new();
+            let mut clause_breaks = Vec::new();
+            for (idx, chunk) in chunks.iter().enumerate() {
+                if paragraph_breaks.contains(&idx) {
+                    clause_breaks.push(clauses.len());
+                }
+                clauses.extend(pacing::pause_clauses(chunk, pauses, &opts.abbreviations));
+            }
+            chunks = clauses;
+            paragraph_breaks = clause_breaks;
+        }
+
+        Self {
+            chunks,
+            voice: opts
+                .voice
+                .clone()
+                .unwrap_or_else(|| DEFAULT_VOICE.to_string()),
+            speed: opts.speed,
+            speed_mode: opts.speed_mode,
+            gain: opts.gain,
+            lang: opts
+                .lang
+                .clone()
+                .unwrap_or_else(|| default_lang(opts.voice.as_deref())),
+            caps_policy: opts.caps_policy,
+            chunk_cache: opts.chunk_cache,
+            punctuation_weights,
+            lenient_language: opts.lenient_language,
+            strict_characters: opts.strict_characters,
+            pacing,
+            paragraph_breaks,
+            extra_units: opts.extra_units.clone(),
+            style_temperature: opts.style_temperature,
+            style_seed: if opts.style_temperature > 0.0 {
+                opts.style_seed.unwrap_or_else(rng::random_seed)
+            } else {
+                0
+            },
+            pitch: clamp_pitch(opts.pitch),
+            trim_silence: opts.trim_silence,
+            edge_fade_ms: opts.edge_fade_ms,
+            raw_text: opts.raw_text,
+            url_style: opts.url_style,
+            emoji: opts.emoji,
+            spell_patterns: opts.spell_patterns.clone(),
+            pauses: opts.pauses,
+        }
+    }
+
+    /// Index of the paragraph chunk `idx` is in
+    pub fn paragraph_of(&self, idx: usize) -> usize {
+        self.paragraph_breaks
+            .iter()
+            .filter(|&&start| start <= idx)
+            .count()
+    }
+
+    /// Silence before chunk `idx` (0 = crossfaded onto the previous chunk)
+    pub(crate) fn gap_ms(&self, idx: usize) -> u32 {
+        let paragraph = self.paragraph_breaks.contains(&idx);
+        let gap_ms = if paragraph {
+            self.pacing.paragraph_gap_ms
+        } else {
+            self.pacing.chunk_gap_ms
+        };
+        match (&self.pauses, idx.checked_sub(1)) {
+            (Some(pauses), _) if paragraph => pauses.paragraph_ms,
+            (Some(pauses), Some(previous)) => gap_ms.max(pauses.after(&self.chunks[previous])),
+            _ => gap_ms,
+        }
+    }
+}
+
+/// Long-form synthesis that stopped at a failed chunk
+///
+/// `completed` holds the audio for chunks `0..failed_chunk`; pass it back to
+/// `TtsEngine::resume_synthesis` with `failed_chunk` to continue.
+#[derive(Clone, Debug)]
+pub struct PartialSynthesis {
+    pub completed: Vec<f32>,
+    pub failed_chunk: usize,
+    pub chunk_text: String,
+    pub error: KokoroError,
+    pub plan: SynthesisPlan,
+}
+
+impl std::fmt::Display for PartialSynthesis {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Chunk {}/{} failed after {:.1}s of audio ({:?}): {}",
+            self.failed_chunk + 1,
+            self.plan.chunks.len(),
+            AudioDuration::of(&self.completed).as_secs_f64(),
+            privacy::display_text(&self.chunk_text),
+            self.error
+        )
+    }
+}
+
+impl std::error::Error for PartialSynthesis {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+impl PartialSynthesis {
+    // The error `synthesize_with` reports: nothing rendered yet means
+    // nothing worth reporting beyond the cause
+    pub(crate) fn into_error(self) -> KokoroError {
+        if self.completed.is_empty() {
+            self.error
+        } else {
+            let message = self.to_string();
+            self.error.with_message(message)
+        }
+    }
+}
+
+/// On-disk record for continuing a partial render in a later process
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ResumeFile {
+    pub plan: SynthesisPlan,
+    pub next_chunk: usize,
+    /// WAV holding the audio completed so far
+    pub audio_path: String,
+}
+
+impl ResumeFile {
+    pub fn load(path: &str) -> Result<Self, String> {
+        let json = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read resume file {}: {}", path, e))?;
+        serde_json::from_str(&json).map_err(|e| format!("Invalid resume file {}: {}", path, e))
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize resume file: {}", e))?;
+        fs::write(path, json).map_err(|e| format!("Failed to write resume file {}: {}", path, e))
+    }
+
+    /// Read back the partial audio this file points at
+    pub fn load_audio(&self) -> Result<Vec<f32>, String> {
+        let bytes = fs::read(&self.audio_path)
+            .map_err(|e| format!("Failed to read {}: {}", self.audio_path, e))?;
+        wav_to_f32(&bytes)
+    }
+}
+
+impl TtsEngine {
+    /// Synthesize text, returning whatever was rendered if a chunk fails
+    ///
+    /// This is `synthesize_with` with a richer error: on failure the
+    /// `PartialSynthesis` carries the completed audio and the chunk plan.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use kokoro_tiny::{SynthesizeOptions, TtsEngine};
+    ///
+    /// # async fn demo() -> Result<(), String> {
+    /// let tts = TtsEngine::new().await?;
+    /// let text = std::fs::read_to_string("book.txt").unwrap();
+    /// match tts.synthesize_resumable(&text, SynthesizeOptions::new()) {
+    ///     Ok(audio) => tts.save_wav("book.wav", &audio)?,
+    ///     Err(partial) => {
+    ///         // Keep what was rendered; `kokoro-speak resume book.wav.resume` finishes it
+    ///         tts.save_partial(&partial, "book.wav")?;
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn synthesize_resumable(
+        &self,
+        text: &str,
+        opts: SynthesizeOptions,
+    ) -> Result<Vec<f32>, Box<PartialSynthesis>> {
+        // Same excuse message as synthesize_with while the model is missing
+        if self.is_fallback() {
+            if let Ok(audio) = self.fallback_audio(text) {
+                return Ok(audio);
+            }
+        }
+
+        let plan = SynthesisPlan::new(text, &opts);
+        self.resume_synthesis(&plan, 0, Vec::new())
+    }
+
+    /// Render `plan` from chunk `from_chunk` onwards, appending to `completed`
+    ///
+    /// The result is identical to an uninterrupted run as long as `completed`
+    /// is the audio previously returned for chunks `0..from_chunk`.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use kokoro_tiny::{ResumeFile, TtsEngine};
+    ///
+    /// # async fn demo() -> Result<(), String> {
+    /// let tts = TtsEngine::new().await?;
+    /// let resume = ResumeFile::load("book.wav.resume")?;
+    /// let completed = resume.load_audio()?;
+    /// let audio = tts
+    ///     .resume_synthesis(&resume.plan, resume.next_chunk, completed)
+    ///     .map_err(|partial| partial.to_string())?;
+    /// tts.save_wav(&resume.audio_path, &audio)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn resume_synthesis(
+        &self,
+        plan: &SynthesisPlan,
+        from_chunk: usize,
+        completed: Vec<f32>,
+    ) -> Result<Vec<f32>, Box<PartialSynthesis>> {
+        self.render_plan(
+            plan,
+            from_chunk,
+            completed,
+            &mut progress::log_progress(plan),
+        )
+    }
+
+    /// `resume_synthesis`, reporting progress to `on_progress` instead of stderr
+    pub(crate) fn render_plan(
+        &self,
+        plan: &SynthesisPlan,
+        from_chunk: usize,
+        completed: Vec<f32>,
+        on_progress: &mut dyn FnMut(SynthesisProgress),
+    ) -> Result<Vec<f32>, Box<PartialSynthesis>> {
+        self.warnings
+            .record(|| self.render_chunks(plan, from_chunk, completed, on_progress))
+            .0
+    }
+
+    fn render_chunks(
+        &self,
+        plan: &SynthesisPlan,
+        from_chunk: usize,
+        completed: Vec<f32>,
+        on_progress: &mut dyn FnMut(SynthesisProgress),
+    ) -> Result<Vec<f32>, Box<PartialSynthesis>> {
+        let started_at = Instant::now();
+
+        let mut audio = completed;
+        let fail = |audio: Vec<f32>, idx: usize, error: KokoroError| {
+            Box::new(PartialSynthesis {
+                completed: audio,
+                failed_chunk: idx,
+                chunk_text: plan.chunks.get(idx).cloned().unwrap_or_default(),
+                error,
+                plan: plan.clone(),
+            })
+        };
+
+        let speed = self.engine_speed(plan.speed, plan.speed_mode);
+
+        if let Err(e) = self.check_language(plan) {
+            return Err(fail(audio, from_chunk, e));
+        }
+        if let Err(e) = plan.pacing.check() {
+            return Err(fail(audio, from_chunk, e));
+        }
+        if let Err(e) = spelling::check(&plan.spell_patterns) {
+            return Err(fail(audio, from_chunk, e));
+        }
+
+        // Parse voice style (e.g., "af_sky.8+af_bella.2" for mixing)
+        let style = match self.varied_style(&plan.voice, plan.style_temperature, plan.style_seed) {
+            Ok(style) => style,
+            Err(e) => return Err(fail(audio, from_chunk, e)),
+        };
+
+        let chunk_chars: Vec<usize> = plan.chunks.iter().map(|c| c.chars().count()).collect();
+        let total_chars = chunk_chars.iter().sum();
+        let mut chars_done: usize = chunk_chars.iter().take(from_chunk).sum();
+        let progress = |stage, chunk, chars_done, samples| SynthesisProgress {
+            stage,
+            chunk,
+            total_chunks: plan.chunks.len(),
+            paragraph: plan.paragraph_of(chunk),
+            total_paragraphs: plan.paragraph_of(plan.chunks.len()) + 1,
+            chars_done,
+            total_chars,
+            samples,
+            elapsed: started_at.elapsed(),
+        };
+
+        for (idx, chars) in chunk_chars.iter().enumerate().skip(from_chunk) {
+            on_progress(progress(
+                ProgressStage::ChunkStarted,
+                idx,
+                chars_done,
+                audio.len(),
+            ));
+
+            let chunk_audio = match self.render_chunk(plan, idx, &style, speed) {
+                Ok(chunk_audio) => chunk_audio,
+                Err(e) => return Err(fail(audio, idx, e)),
+            };
+            let started = !audio.is_empty();
+            join_chunk(plan, idx, &mut audio, &chunk_audio, started);
+
+            chars_done += chars;
+            on_progress(progress(
+                ProgressStage::ChunkFinished,
+                idx,
+                chars_done,
+                audio.len(),
+            ));
+        }
+
+        if plan.trim_silence {
+            trim_silence(&mut audio);
+        }
+        crate::audio::apply_edges(&mut audio, plan.edge_fade_ms);
+        let count = plan.chunks.len();
+        on_progress(progress(
+            ProgressStage::Completed,
+            count,
+            chars_done,
+            audio.len(),
+        ));
+        Ok(audio)
+    }
+
+    /// Render chunk `idx` of `plan` on its own (caps policy, model, pitch, gain)
+    pub(crate) fn render_chunk(
+        &self,
+        plan: &SynthesisPlan,
+        idx: usize,
+        style: &[f32],
+        speed: f32,
+    ) -> Result<Vec<f32>, KokoroError> {
+        let text = self.normalize_chunk(&plan.chunks[idx], plan);
+        // Rendered slower (or faster) by the pitch factor, which the shift
+        // then undoes
+        let model_speed = if plan.pitch != 1.0 {
+            (speed / plan.pitch).clamp(MIN_ENGINE_SPEED, MAX_ENGINE_SPEED)
+        } else {
+            speed
+        };
+        let mut chunk_audio = self.synthesize_segment(style, &text, model_speed, plan)?;
+
+        if plan.pitch != 1.0 {
+            chunk_audio = shift_pitch(&chunk_audio, plan.pitch);
+        }
+
+        // Gain goes on per chunk so completed audio never needs re-processing,
+        // and after the chunk cache so cached chunks compose like fresh ones
+        if plan.gain != 1.0 {
+            chunk_audio = amplify_audio(&chunk_audio, plan.gain);
+        }
+        Ok(chunk_audio)
+    }
+
+    /// Save the audio of a partial render to `audio_path` and a resume file next to it
+    ///
+    /// The resume file is written to `<audio_path>.resume`; its path is returned.
+    /// It holds the text, so with redaction on (see `privacy`) only the audio
+    /// is saved and an error says so.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use kokoro_tiny::{SynthesizeOptions, TtsEngine};
+    ///
+    /// # async fn demo() -> Result<(), String> {
+    /// let tts = TtsEngine::new().await?;
+    /// if let Err(partial) = tts.synthesize_resumable("A long text...", SynthesizeOptions::new()) {
+    ///     let resume_path = tts.save_partial(&partial, "partial.wav")?;
+    ///     eprintln!("continue with: kokoro-speak resume {}", resume_path);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn save_partial(
+        &self,
+        partial: &PartialSynthesis,
+        audio_path: &str,
+    ) -> Result<String, String> {
+        self.save_wav(audio_path, &partial.completed)?;
+        if privacy::redact_text() {
+            return Err(format!(
+                "Partial audio saved to {}, but no resume file: it would contain the text \
+                 and redaction is on",
+                audio_path
+            ));
+        }
+
+        let resume_path = format!("{}.resume", audio_path);
+        ResumeFile {
+            plan: partial.plan.clone(),
+            next_chunk: partial.failed_chunk,
+            audio_path: audio_path.to_string(),
+        }
+        .save(&resume_path)?;
+        Ok(resume_path)
+    }
+}
+
+// Append chunk `idx` to `audio`: after a gap of silence if the pacing asks for
+// one and something came before (`started`), crossfaded otherwise
+pub(crate) fn join_chunk(
+    plan: &SynthesisPlan,
+    idx: usize,
+    audio: &mut Vec<f32>,
+    chunk_audio: &[f32],
+    started: bool,
+) {
+    let gap_ms = plan.gap_ms(idx);
+    if gap_ms > 0 && started {
+        audio.resize(audio.len() + pacing::ms_to_samples(gap_ms), 0.0);
+        audio.extend_from_slice(chunk_audio);
+    } else {
+        append_with_crossfade(audio, chunk_audio, plan.pacing.crossfade_samples());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+
+    const LONG_TEXT: &str = "The first sentence sets the scene for everything that follows. \
+        A second sentence keeps the story moving along at a steady pace. \
+        Then a third one arrives, carrying a little more detail than before. \
+        The fourth sentence is where things usually start to go wrong. \
+        Finally the fifth sentence wraps the whole thing up neatly.";
+
+    // Output depends only on the tokens, so a chunk renders the same whether
+    // or not it is retried; inference call number `fail_at` errors
+    fn flaky_engine(fail_at: Arc<AtomicUsize>) -> TtsEngine {
+        let mut engine = TtsEngine::mock(0);
+        let calls = AtomicUsize::new(0);
+        engine.mock_inference = Some(Arc::new(
+            move |tokens: &[i64], _style: &[f32], _speed: f32| {
+                if calls.fetch_add(1, Ordering::SeqCst) == fail_at.load(Ordering::SeqCst) {
+                    return Err("injected failure".to_string());
+                }
+                let seed = tokens.iter().sum::<i64>() as usize;
+                Ok((0..tokens.len() * 40)
+                    .map(|i| ((i + seed) % 100) as f32 / 200.0)
+                    .collect())
+            },
+        ));
+        engine
+    }
+
+    fn opts() -> SynthesizeOptions {
+        SynthesizeOptions::new().gain(1.3)
+    }
+
+    #[test]
+    fn pitch_shifts_keep_the_tempo() {
+        let speeds = Arc::new(Mutex::new(Vec::new()));
+        let mut engine = TtsEngine::mock(0);
+        // Like the model: slower speeds render longer audio
+        engine.mock_inference = Some({
+            let speeds = speeds.clone();
+            Arc::new(move |tokens: &[i64], _style: &[f32], speed: f32| {
+                speeds.lock().unwrap().push(speed);
+                Ok(vec![0.2; (tokens.len() as f32 * 100.0 / speed) as usize])
+            })
+        });
+
+        let text = "Hello there.";
+        let plain = engine
+            .synthesize_with(text, SynthesizeOptions::new())
+            .unwrap();
+        let up = engine
+            .synthesize_with(text, SynthesizeOptions::new().pitch(1.5))
+            .unwrap();
+        let speeds = speeds.lock().unwrap().clone();
+        assert!((speeds[1] * 1.5 - speeds[0]).abs() < 1e-6, "{:?}", speeds);
+        assert!(
+            plain.len().abs_diff(up.len()) <= 2,
+            "{} vs {}",
+            plain.len(),
+            up.len()
+        );
+
+        // Out of range factors are clamped
+        let plan = SynthesisPlan::new(text, &SynthesizeOptions::new().pitch(10.0));
+        assert_eq!(plan.pitch, 2.0);
+        assert_eq!(
+            SynthesisPlan::new(text, &SynthesizeOptions::new()).pitch,
+            1.0
+        );
+    }
+
+    #[test]
+    fn failed_chunk_returns_completed_audio() {
+        let plan = SynthesisPlan::new(LONG_TEXT, &opts());
+        assert!(plan.chunks.len() >= 3);
+
+        let engine = flaky_engine(Arc::new(AtomicUsize::new(2)));
+        let partial = engine.synthesize_resumable(LONG_TEXT, opts()).unwrap_err();
+        assert_eq!(partial.failed_chunk, 2);
+        assert_eq!(partial.chunk_text, plan.chunks[2]);
+        assert!(matches!(
+            &partial.error,
+            KokoroError::InferenceFailed(message) if message == "injected failure"
+        ));
+
+        // Two chunks rendered, joined with one crossfade
+        let reference = flaky_engine(Arc::new(AtomicUsize::new(usize::MAX)));
+        let first_two = SynthesisPlan {
+            chunks: plan.chunks[..2].to_vec(),
+            ..plan.clone()
+        };
+        let expected = reference
+            .resume_synthesis(&first_two, 0, Vec::new())
+            .unwrap();
+        assert_eq!(partial.completed.len(), expected.len());
+    }
+
+    #[test]
+    fn resuming_matches_uninterrupted_run() {
+        let fail_at = Arc::new(AtomicUsize::new(2));
+        let engine = flaky_engine(fail_at.clone());
+        let partial = engine.synthesize_resumable(LONG_TEXT, opts()).unwrap_err();
+
+        fail_at.store(usize::MAX, Ordering::SeqCst);
+        let resumed = engine
+            .resume_synthesis(&partial.plan, partial.failed_chunk, partial.completed)
+            .unwrap();
+
+        let uninterrupted = flaky_engine(Arc::new(AtomicUsize::new(usize::MAX)));
+        let expected = uninterrupted
+            .synthesize_resumable(LONG_TEXT, opts())
+            .unwrap();
+        assert_eq!(resumed, expected);
+    }
+
+    #[test]
+    fn resume_file_round_trips() {
+        let engine = flaky_engine(Arc::new(AtomicUsize::new(1)));
+        let partial = engine.synthesize_resumable(LONG_TEXT, opts()).unwrap_err();
+
+        let dir = std::env::temp_dir().join(format!("kokoro_resume_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let audio_path = dir.join("partial.wav");
+        let resume_path = engine
+            .save_partial(&partial, audio_path.to_str().unwrap())
+            .unwrap();
+
+        let resume = ResumeFile::load(&resume_path).unwrap();
+        assert_eq!(resume.plan, partial.plan);
+        assert_eq!(resume.next_chunk, 1);
+        assert_eq!(resume.load_audio().unwrap().len(), partial.completed.len());
+
+        fs::remove_dir_all(dir).ok();
+    }
+}
@@ -0,0 +1,334 @@
+//! Backoff and circuit breaker for model downloads
+//!
+//! When the network is down, callers that keep constructing engines (or CLI
+//! invocations fired from scripts) would otherwise hit the download URLs on
+//! every attempt. Attempts are spaced with exponential backoff plus jitter,
+//! and after enough consecutive failures the circuit opens and network access
+//! is skipped entirely for a cooldown. The state lives in a small JSON file in
+//! the cache directory so separate processes share it.
+
+use std::fs;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::KokoroError;
+
+/// File (next to the model) holding the persisted retry state
+const RETRY_STATE_FILE: &str = "retry.json";
+
+/// How often model downloads may be retried after failures
+#[derive(Clone, Debug, PartialEq)]
+pub struct RetryPolicy {
+    /// Wait after the first failure; doubles with each further failure
+    pub base_delay: Duration,
+    /// Upper bound for the backoff delay
+    pub max_delay: Duration,
+    /// Consecutive failures that open the circuit
+    pub failure_threshold: u32,
+    /// How long an open circuit blocks all network attempts
+    pub cooldown: Duration,
+    /// Tries per URL within one attempt, for transient errors (see `download`)
+    pub attempts_per_url: u32,
+    /// Wait before the second try of a URL; doubles with each further try
+    pub attempt_delay: Duration,
+    /// A try is abandoned when no data arrives for this long
+    pub stall_timeout: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            base_delay: Duration::from_secs(30),
+            max_delay: Duration::from_secs(60 * 60),
+            failure_threshold: 5,
+            cooldown: Duration::from_secs(15 * 60),
+            attempts_per_url: 3,
+            attempt_delay: Duration::from_secs(1),
+            stall_timeout: Duration::from_secs(30),
+        }
+    }
+}
+
+/// Persisted record of recent download attempts
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct RetryState {
+    pub consecutive_failures: u32,
+    /// Unix seconds of the last attempt
+    pub last_attempt: u64,
+    /// Unix seconds until which the circuit stays open
+    pub circuit_open_until: Option<u64>,
+}
+
+impl RetryState {
+    /// Load the state stored in `cache_dir`, or a fresh state if there is none
+    pub fn load(cache_dir: &Path) -> Self {
+        fs::read_to_string(state_path(cache_dir))
+            .ok()
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default()
+    }
+
+    /// Persist the state into `cache_dir`
+    pub fn save(&self, cache_dir: &Path) -> Result<(), String> {
+        let json = serde_json::to_string(self)
+            .map_err(|e| format!("Failed to serialize retry state: {}", e))?;
+        fs::write(state_path(cache_dir), json)
+            .map_err(|e| format!("Failed to write retry state: {}", e))
+    }
+}
+
+impl RetryPolicy {
+    /// Backoff after `failures` consecutive failures, with up to ±25% jitter
+    ///
+    /// The jitter is derived from the state rather than a random source so a
+    /// given history always yields the same spacing.
+    pub fn delay_after(&self, failures: u32, last_attempt: u64) -> Duration {
+        if failures == 0 {
+            return Duration::ZERO;
+        }
+        let exponent = (failures - 1).min(20);
+        let delay = self
+            .base_delay
+            .saturating_mul(1u32 << exponent)
+            .min(self.max_delay);
+
+        // Cheap deterministic spread in [-0.25, 0.25)
+        let mix = last_attempt
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add((failures as u64).wrapping_mul(1442695040888963407));
+        let jitter = ((mix >> 33) % 1000) as f64 / 2000.0 - 0.25;
+        delay.mul_f64(1.0 + jitter)
+    }
+
+    /// Earliest unix second at which another attempt is allowed
+    pub fn next_attempt_at(&self, state: &RetryState) -> u64 {
+        let backoff = state.last_attempt
+            + self
+                .delay_after(state.consecutive_failures, state.last_attempt)
+                .as_secs();
+        match state.circuit_open_until {
+            Some(until) => until.max(backoff),
+            None => backoff,
+        }
+    }
+
+    /// Whether a network attempt may be made at `now`
+    pub fn allows_attempt(&self, state: &RetryState, now: SystemTime) -> bool {
+        state.consecutive_failures == 0 || unix_secs(now) >= self.next_attempt_at(state)
+    }
+
+    /// Whether the circuit is currently open
+    pub fn circuit_open(&self, state: &RetryState, now: SystemTime) -> bool {
+        state
+            .circuit_open_until
+            .is_some_and(|until| unix_secs(now) < until)
+    }
+
+    pub(crate) fn record_failure(&self, state: &mut RetryState, now: SystemTime) {
+        let now = unix_secs(now);
+        state.consecutive_failures += 1;
+        state.last_attempt = now;
+        if state.consecutive_failures >= self.failure_threshold {
+            state.circuit_open_until = Some(now + self.cooldown.as_secs());
+        }
+    }
+
+    pub(crate) fn record_success(&self, state: &mut RetryState) {
+        *state = RetryState::default();
+    }
+}
+
+/// Download whichever model files are missing, honoring the retry policy
+///
+/// `fetch(url, path)` performs a single download. Returns `Ok(())` once both
+/// files exist; `Err` if the attempt was skipped by the policy or failed.
+pub(crate) async fn ensure_models<F, Fut>(
+    files: &[(&str, &str)],
+    policy: &RetryPolicy,
+    now: SystemTime,
+    mut fetch: F,
+) -> Result<(), KokoroError>
+where
+    F: FnMut(String, String) -> Fut,
+    Fut: Future<Output = Result<(), String>>,
+{
+    let missing: Vec<&(&str, &str)> = files
+        .iter()
+        .filter(|(_, path)| !Path::new(path).exists())
+        .collect();
+    if missing.is_empty() {
+        return Ok(());
+    }
+
+    let cache_dir = Path::new(missing[0].1)
+        .parent()
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|| PathBuf::from("."));
+    let mut state = RetryState::load(&cache_dir);
+
+    if !policy.allows_attempt(&state, now) {
+        let wait = policy
+            .next_attempt_at(&state)
+            .saturating_sub(unix_secs(now));
+        let source = if policy.circuit_open(&state, now) {
+            format!(
+                "Download circuit open after {} failures, next attempt in {}s",
+                state.consecutive_failures, wait
+            )
+        } else {
+            format!("Backing off model download, next attempt in {}s", wait)
+        };
+        return Err(KokoroError::download_failed(missing[0].0, source));
+    }
+
+    for (url, path) in missing {
+        if let Err(e) = fetch(url.to_string(), path.to_string()).await {
+            policy.record_failure(&mut state, now);
+            let _ = state.save(&cache_dir);
+            return Err(KokoroError::download_failed(*url, e));
+        }
+    }
+
+    policy.record_success(&mut state);
+    let _ = state.save(&cache_dir);
+    Ok(())
+}
+
+fn state_path(cache_dir: &Path) -> PathBuf {
+    cache_dir.join(RETRY_STATE_FILE)
+}
+
+fn unix_secs(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    fn temp_cache(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("kokoro_retry_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn at(secs: u64) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(secs)
+    }
+
+    fn policy() -> RetryPolicy {
+        RetryPolicy {
+            base_delay: Duration::from_secs(10),
+            max_delay: Duration::from_secs(300),
+            failure_threshold: 3,
+            cooldown: Duration::from_secs(1000),
+            ..RetryPolicy::default()
+        }
+    }
+
+    #[test]
+    fn backoff_grows_exponentially_within_jitter() {
+        let policy = policy();
+        for failures in 1..=4u32 {
+            let nominal = (10u64 << (failures - 1)) as f64;
+            let delay = policy.delay_after(failures, 12345).as_secs_f64();
+            assert!(delay >= nominal * 0.75 && delay <= nominal * 1.25);
+        }
+        assert!(policy.delay_after(30, 1).as_secs_f64() <= 300.0 * 1.25);
+    }
+
+    #[tokio::test]
+    async fn attempts_are_spaced_and_circuit_opens() {
+        let dir = temp_cache("spacing");
+        let model = dir.join("0.onnx");
+        let files = [("http://example.invalid/0.onnx", model.to_str().unwrap())];
+        let policy = policy();
+        let calls = Cell::new(0);
+
+        let failing = |_url: String, _path: String| {
+            calls.set(calls.get() + 1);
+            async { Err::<(), String>("offline".to_string()) }
+        };
+
+        // First failure at t=1000
+        assert!(ensure_models(&files, &policy, at(1000), failing)
+            .await
+            .is_err());
+        assert_eq!(calls.get(), 1);
+
+        // Immediately retrying is refused without touching the network
+        assert!(ensure_models(&files, &policy, at(1001), failing)
+            .await
+            .is_err());
+        assert_eq!(calls.get(), 1);
+
+        // A separate "process" sees the same persisted state
+        let state = RetryState::load(&dir);
+        assert_eq!(state.consecutive_failures, 1);
+        let next = policy.next_attempt_at(&state);
+        assert!((1007..=1013).contains(&next));
+
+        // Fail twice more at the allowed times to hit the threshold
+        ensure_models(&files, &policy, at(next), failing)
+            .await
+            .unwrap_err();
+        let next = policy.next_attempt_at(&RetryState::load(&dir));
+        ensure_models(&files, &policy, at(next), failing)
+            .await
+            .unwrap_err();
+        assert_eq!(calls.get(), 3);
+
+        let state = RetryState::load(&dir);
+        assert!(policy.circuit_open(&state, at(next + 1)));
+        let err = ensure_models(&files, &policy, at(next + 500), failing)
+            .await
+            .unwrap_err();
+        match &err {
+            KokoroError::DownloadFailed { source, .. } => {
+                assert!(source.to_string().contains("circuit open"))
+            }
+            other => panic!("expected DownloadFailed, got {:?}", other),
+        }
+        assert_eq!(calls.get(), 3);
+
+        fs::remove_dir_all(dir).ok();
+    }
+
+    #[tokio::test]
+    async fn recovers_when_downloads_start_succeeding() {
+        let dir = temp_cache("recover");
+        let model = dir.join("0.onnx");
+        let voices = dir.join("0.bin");
+        let files = [
+            ("http://example.invalid/0.onnx", model.to_str().unwrap()),
+            ("http://example.invalid/0.bin", voices.to_str().unwrap()),
+        ];
+        let policy = policy();
+
+        ensure_models(&files, &policy, at(0), |_u, _p| async {
+            Err("offline".to_string())
+        })
+        .await
+        .unwrap_err();
+
+        let writing = |_url: String, path: String| async move {
+            fs::write(path, b"model").map_err(|e| e.to_string())
+        };
+        ensure_models(&files, &policy, at(100), writing)
+            .await
+            .unwrap();
+
+        assert!(model.exists() && voices.exists());
+        assert_eq!(RetryState::load(&dir), RetryState::default());
+
+        fs::remove_dir_all(dir).ok();
+    }
+}
@@ -0,0 +1,468 @@
+//! One output stream for many utterances
+//!
+//! Every `play` call opens the device and closes it again, which leaves a
+//! 100-300 ms gap between back-to-back utterances and, on PipeWire, the odd
+//! "device busy" error. A [`Speaker`] opens its stream once, on a thread of
+//! its own, and plays whatever is enqueued back to back on it. Enqueueing
+//! while something is playing just extends the queue.
+//!
+//! The speaker takes the process-wide playback coordinator (see
+//! `crate::coordinator`) while it has something to play and lets go when the
+//! queue runs dry, so it still never talks over a `play` call.
+
+use std::collections::VecDeque;
+use std::sync::mpsc;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::thread::{self, JoinHandle};
+
+use crate::audio_buffer::mono_at_model_rate;
+use crate::coordinator;
+use crate::output::{write_until, AudioOutput, AudioStreamOut, OutputSpec};
+use crate::{AudioSource, BusyPolicy, KokoroError, SynthesizeOptions, TtsEngine, SAMPLE_RATE};
+
+/// What happened to an enqueued item, by the id `enqueue` returned
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SpeakerEvent {
+    Started {
+        id: u64,
+    },
+    /// Played to the end
+    Finished {
+        id: u64,
+    },
+    /// Dropped by `clear` or `stop_playback`, before or while playing
+    Skipped {
+        id: u64,
+    },
+    /// The output failed; the stream is reopened for the next item
+    Failed {
+        id: u64,
+        error: String,
+    },
+}
+
+type Callback = Arc<dyn Fn(SpeakerEvent) + Send + Sync>;
+
+#[derive(Default)]
+struct State {
+    queue: VecDeque<(u64, Vec<f32>)>,
+    next_id: u64,
+    // Bumped by `clear`, so the item playing knows to stop
+    generation: u64,
+    // Items have been taken off the queue and not played out yet
+    playing: bool,
+    volume: f32,
+    on_event: Option<Callback>,
+    shutdown: bool,
+}
+
+struct Shared {
+    state: Mutex<State>,
+    // Signalled when the queue gains an item or the speaker shuts down
+    work: Condvar,
+    // Signalled when the queue drains
+    idle: Condvar,
+}
+
+impl Shared {
+    // Nothing in State can be left half-updated by a panic, so poisoning is ignored
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn emit(&self, event: SpeakerEvent) {
+        // Called without the lock held, so the callback may enqueue
+        let callback = self.lock().on_event.clone();
+        if let Some(callback) = callback {
+            callback(event);
+        }
+    }
+}
+
+/// A queue of utterances played gaplessly on one output stream
+///
+/// Dropping the speaker stops it, along with anything still queued.
+///
+/// # Example
+/// ```no_run
+/// use kokoro_tiny::{Speaker, SynthesizeOptions, TtsEngine};
+///
+/// # async fn demo() -> Result<(), kokoro_tiny::KokoroError> {
+/// let tts = TtsEngine::new().await?;
+/// let speaker = Speaker::new(&tts)?;
+/// speaker.enqueue_text(&tts, "Build finished.", SynthesizeOptions::new())?;
+/// speaker.enqueue_text(&tts, "All tests passed.", SynthesizeOptions::new())?;
+/// speaker.wait();
+/// # Ok(())
+/// # }
+/// ```
+pub struct Speaker {
+    shared: Arc<Shared>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Speaker {
+    /// A speaker on the engine's output: the `set_output` backend, or the
+    /// selected audio device
+    pub fn new(engine: &TtsEngine) -> Result<Self, KokoroError> {
+        Self::with_output(engine.output().map_err(KokoroError::Playback)?)
+    }
+
+    /// A speaker on `output`, opened straight away so a missing device is
+    /// reported here rather than on the first item
+    ///
+    /// # Example
+    /// ```
+    /// use kokoro_tiny::{NullOutput, Speaker};
+    /// use std::sync::Arc;
+    ///
+    /// let output = NullOutput::new();
+    /// let speaker = Speaker::with_output(Arc::new(output.clone()))?;
+    /// speaker.enqueue(&[0.5; 2400]);
+    /// speaker.enqueue(&[0.5; 2400]);
+    /// speaker.wait();
+    /// assert_eq!((output.frames().len(), output.opened()), (4800, 1));
+    /// # Ok::<(), kokoro_tiny::KokoroError>(())
+    /// ```
+    pub fn with_output(output: Arc<dyn AudioOutput>) -> Result<Self, KokoroError> {
+        let shared = Arc::new(Shared {
+            state: Mutex::new(State {
+                volume: 1.0,
+                ..State::default()
+            }),
+            work: Condvar::new(),
+            idle: Condvar::new(),
+        });
+        let (opened_tx, opened_rx) = mpsc::channel();
+        let worker = shared.clone();
+        let thread = thread::Builder::new()
+            .name("kokoro-speaker".to_string())
+            .spawn(move || run(&*output, &worker, opened_tx))
+            .map_err(|e| KokoroError::Playback(format!("Failed to start speaker: {}", e)))?;
+
+        match opened_rx.recv() {
+            Ok(Ok(())) => Ok(Self {
+                shared,
+                thread: Some(thread),
+            }),
+            Ok(Err(e)) => Err(KokoroError::Playback(e)),
+            Err(_) => Err(KokoroError::Playback("Speaker thread panicked".to_string())),
+        }
+    }
+
+    /// Queue audio after everything already queued; returns its id for
+    /// `on_event`
+    ///
+    /// Stereo is mixed down, since the stream is mono.
+    pub fn enqueue(&self, audio: impl AudioSource) -> u64 {
+        let samples = mono_at_model_rate(&audio).into_owned();
+        let mut state = self.shared.lock();
+        let id = state.next_id;
+        state.next_id += 1;
+        state.queue.push_back((id, samples));
+        self.shared.work.notify_all();
+        id
+    }
+
+    /// Synthesize `text` and queue it; the engine renders on the calling
+    /// thread while earlier items keep playing
+    pub fn enqueue_text(
+        &self,
+        engine: &TtsEngine,
+        text: &str,
+        opts: SynthesizeOptions,
+    ) -> Result<u64, KokoroError> {
+        let audio = engine.synthesize_with_buffer(text, opts)?;
+        Ok(self.enqueue(&audio))
+    }
+
+    /// Drop everything queued and cut off what is playing
+    pub fn clear(&self) {
+        let skipped: Vec<u64> = {
+            let mut state = self.shared.lock();
+            state.generation += 1;
+            state.queue.drain(..).map(|(id, _)| id).collect()
+        };
+        for id in skipped {
+            self.shared.emit(SpeakerEvent::Skipped { id });
+        }
+    }
+
+    /// Set the volume (0.0 to 1.0, default 1.0) from the next item on
+    pub fn set_volume(&self, volume: f32) {
+        self.shared.lock().volume = volume.clamp(0.0, 1.0);
+    }
+
+    /// Call `callback` as items start, finish or are skipped, on the speaker's
+    /// thread (or the one calling `clear`)
+    pub fn on_event(&self, callback: impl Fn(SpeakerEvent) + Send + Sync + 'static) {
+        self.shared.lock().on_event = Some(Arc::new(callback));
+    }
+
+    /// Items queued, not counting the one playing
+    pub fn len(&self) -> usize {
+        self.shared.lock().queue.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Whether nothing is queued or playing
+    pub fn is_idle(&self) -> bool {
+        let state = self.shared.lock();
+        state.queue.is_empty() && !state.playing
+    }
+
+    /// Block until everything queued so far has played (or been cleared)
+    pub fn wait(&self) {
+        let mut state = self.shared.lock();
+        while !state.queue.is_empty() || state.playing {
+            state = self
+                .shared
+                .idle
+                .wait(state)
+                .unwrap_or_else(|e| e.into_inner());
+        }
+    }
+}
+
+impl Drop for Speaker {
+    fn drop(&mut self) {
+        self.shared.lock().shutdown = true;
+        self.shared.work.notify_all();
+        if let Some(thread) = self.thread.take() {
+            thread.join().ok();
+        }
+    }
+}
+
+impl std::fmt::Debug for Speaker {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Speaker")
+            .field("queued", &self.len())
+            .field("idle", &self.is_idle())
+            .finish()
+    }
+}
+
+fn open(output: &dyn AudioOutput) -> Result<Box<dyn AudioStreamOut>, String> {
+    output.open(&OutputSpec {
+        sample_rate: SAMPLE_RATE,
+        buffer: None,
+        channels: 1,
+    })
+}
+
+// The speaker's thread: owns the stream, which may not leave it
+fn run(output: &dyn AudioOutput, shared: &Shared, opened: mpsc::Sender<Result<(), String>>) {
+    let mut stream = match open(output) {
+        Ok(stream) => Some(stream),
+        Err(e) => {
+            opened.send(Err(e)).ok();
+            return;
+        }
+    };
+    opened.send(Ok(())).ok();
+
+    loop {
+        // Sleep until there is something to play
+        {
+            let mut state = shared.lock();
+            while state.queue.is_empty() && !state.shutdown {
+                state = shared.work.wait(state).unwrap_or_else(|e| e.into_inner());
+            }
+            if state.shutdown {
+                break;
+            }
+        }
+
+        // Hold the device until the queue runs dry; Queue keeps the speaker's
+        // turn in line with other Queue callers, and never fails
+        let Ok(playing) = coordinator::coordinator().acquire(BusyPolicy::Queue) else {
+            continue;
+        };
+        // An interrupted guard stays interrupted, so after `stop_playback` the
+        // device is given up and taken again for the rest of the queue
+        while !playing.interrupted() {
+            let (id, samples, generation) = {
+                let mut state = shared.lock();
+                if state.shutdown {
+                    return;
+                }
+                match state.queue.pop_front() {
+                    Some((id, samples)) => {
+                        state.playing = true;
+                        (id, samples, state.generation)
+                    }
+                    None => break,
+                }
+            };
+            shared.emit(SpeakerEvent::Started { id });
+
+            let event = match stream.take().map_or_else(|| open(output), Ok) {
+                Ok(mut open_stream) => {
+                    let cut = || {
+                        let state = shared.lock();
+                        state.shutdown || state.generation != generation
+                    };
+                    let played = play_item(&mut *open_stream, &samples, shared, &|| {
+                        cut() || playing.interrupted()
+                    });
+                    match played {
+                        Ok(true) => {
+                            stream = Some(open_stream);
+                            SpeakerEvent::Finished { id }
+                        }
+                        Ok(false) => {
+                            stream = Some(open_stream);
+                            SpeakerEvent::Skipped { id }
+                        }
+                        Err(error) => SpeakerEvent::Failed { id, error },
+                    }
+                }
+                Err(error) => SpeakerEvent::Failed { id, error },
+            };
+            shared.emit(event);
+        }
+
+        // Everything queued has been written; let it play out before
+        // reporting idle and giving up the device
+        if !playing.interrupted() {
+            if let Some(stream) = stream.as_mut() {
+                stream.flush();
+            }
+        }
+        drop(playing);
+        shared.lock().playing = false;
+        shared.idle.notify_all();
+    }
+}
+
+// Write one item, returning false if `stop` cut it short
+fn play_item(
+    stream: &mut dyn AudioStreamOut,
+    samples: &[f32],
+    shared: &Shared,
+    stop: &dyn Fn() -> bool,
+) -> Result<bool, String> {
+    let rate = stream.info().sample_rate;
+    let volume = shared.lock().volume;
+    let frames: Vec<f32> = crate::resample(samples, SAMPLE_RATE, rate)
+        .into_iter()
+        .map(|s| s * volume)
+        .collect();
+    let written = write_until(stream, &frames, stop)?;
+    Ok(written >= frames.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::NullOutput;
+    use std::time::{Duration, Instant};
+
+    fn recorder(speaker: &Speaker) -> Arc<Mutex<Vec<SpeakerEvent>>> {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let log = events.clone();
+        speaker.on_event(move |event| log.lock().unwrap().push(event));
+        events
+    }
+
+    #[test]
+    fn twenty_buffers_play_in_order_on_one_stream() {
+        let output = NullOutput::new();
+        let speaker = Speaker::with_output(Arc::new(output.clone())).unwrap();
+        let events = recorder(&speaker);
+
+        let ids: Vec<u64> = (0..20)
+            .map(|i| speaker.enqueue(vec![i as f32 / 100.0; 240 + i * 10]))
+            .collect();
+        speaker.wait();
+        assert!(speaker.is_idle());
+
+        let finished: Vec<u64> = events
+            .lock()
+            .unwrap()
+            .iter()
+            .filter_map(|event| match event {
+                SpeakerEvent::Finished { id } => Some(*id),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(finished, ids);
+        assert_eq!(output.opened(), 1);
+
+        // Back to back, nothing between the items
+        let frames = output.frames();
+        assert_eq!(frames.len(), (0..20).map(|i| 240 + i * 10).sum::<usize>());
+        assert_eq!(
+            (frames[0], frames[240], frames[frames.len() - 1]),
+            (0.0, 0.01, 0.19)
+        );
+    }
+
+    #[test]
+    fn enqueueing_while_playing_extends_the_queue() {
+        let output = NullOutput::new().with_sample_rate(1_000).realtime();
+        let speaker = Speaker::with_output(Arc::new(output.clone())).unwrap();
+        speaker.enqueue([0.5; 2_400]);
+        std::thread::sleep(Duration::from_millis(30));
+        assert!(!speaker.is_idle());
+        speaker.enqueue([0.25; 2_400]);
+        speaker.wait();
+
+        let frames = output.frames();
+        assert_eq!(frames.len(), 200);
+        assert_eq!((frames[99], frames[100]), (0.5, 0.25));
+        assert_eq!(output.opened(), 1);
+    }
+
+    #[test]
+    fn clear_skips_the_rest_of_the_queue() {
+        let output = NullOutput::new().with_sample_rate(1_000).realtime();
+        let speaker = Speaker::with_output(Arc::new(output.clone())).unwrap();
+        let events = recorder(&speaker);
+        for _ in 0..3 {
+            speaker.enqueue([0.5; 12_000]);
+        }
+        std::thread::sleep(Duration::from_millis(100));
+        let start = Instant::now();
+        speaker.clear();
+        speaker.wait();
+
+        assert!(start.elapsed() < Duration::from_millis(300));
+        assert!(output.frames().len() < 500, "{}", output.frames().len());
+        let events = events.lock().unwrap();
+        assert!(events.contains(&SpeakerEvent::Skipped { id: 0 }));
+        assert!(events.contains(&SpeakerEvent::Skipped { id: 2 }));
+        assert!(!events
+            .iter()
+            .any(|e| matches!(e, SpeakerEvent::Finished { .. })));
+
+        // Still usable afterwards
+        drop(events);
+        speaker.enqueue([0.5; 240]);
+        speaker.wait();
+        assert_eq!(output.opened(), 1);
+    }
+
+    #[test]
+    fn text_is_synthesized_and_queued_on_the_engine_output() {
+        let output = NullOutput::new();
+        let mut engine = TtsEngine::mock(100);
+        engine.set_output(Arc::new(output.clone()));
+
+        let speaker = Speaker::new(&engine).unwrap();
+        let opts = SynthesizeOptions::new();
+        let expected = engine
+            .synthesize_with("Hello there.", opts.clone())
+            .unwrap();
+        assert_eq!(
+            speaker.enqueue_text(&engine, "Hello there.", opts).unwrap(),
+            0
+        );
+        speaker.wait();
+        assert_eq!(output.frames(), expected);
+    }
+}
@@ -0,0 +1,266 @@
+//! Identifiers read character by character
+//!
+//! Left to espeak, a commit hash like "a3f9c2e" comes out as a made-up word.
+//! With `SynthesizeOptions::spell_patterns`, words matching one of the
+//! patterns are spelled instead: letters as capitals (which espeak reads by
+//! name), digits one by one in the synthesis language, separators as a short
+//! pause.
+//!
+//! | Pattern | Matches | Read as |
+//! |---|---|---|
+//! | `Hex { min_len: 7 }` | `a3f9c2e` | A three F nine C two E |
+//! | `Mac` | `00:1A:2B:3C:4D:5E` | zero zero, one A, two B, ... |
+//! | `Alphanumeric { min_len: 4 }` | `JIRA-1234`, `X7K2P9` | J I R A, one two three four |
+//! | `Regex("...")` | whatever the expression matches | |
+//!
+//! Punctuation around a match ("commit a3f9c2e.") is left where it was, so
+//! sentences still end and pause where they did.
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::{units, KokoroError};
+
+// Inside an identifier; anything else ends it
+const SEPARATORS: &[char] = &['-', '_', ':', '.', '/', '#'];
+
+/// Which words are spelled out
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SpellPattern {
+    /// Hex strings of at least `min_len` characters with both letters and
+    /// digits in them, so "deadbeef" and "2024" are left alone
+    Hex { min_len: usize },
+    /// MAC addresses, with colons or dashes
+    Mac,
+    /// Words mixing letters and digits, at least `min_len` of them
+    /// ("X7K2P9", "JIRA-1234", "build_42")
+    Alphanumeric { min_len: usize },
+    /// Every match of a regular expression (`regex` crate syntax)
+    Regex(String),
+}
+
+/// `text` spelled out: capital letters, English digit words, a comma for
+/// each separator
+///
+/// # Example
+/// ```
+/// use kokoro_tiny::spelling::say_characters;
+///
+/// assert_eq!(say_characters("a3f9c2e"), "A three F nine C two E");
+/// assert_eq!(say_characters("ID-42"), "I D, four two");
+/// ```
+pub fn say_characters(text: &str) -> String {
+    spell(text, "en")
+}
+
+/// Fails with `KokoroError::InvalidInput` for a `Regex` pattern that doesn't compile
+pub fn check(patterns: &[SpellPattern]) -> Result<(), KokoroError> {
+    for pattern in patterns {
+        if let SpellPattern::Regex(source) = pattern {
+            Regex::new(source).map_err(|e| {
+                KokoroError::InvalidInput(format!("Invalid spell pattern {:?}: {}", source, e))
+            })?;
+        }
+    }
+    Ok(())
+}
+
+// `text` with every match of `patterns` spelled in `lang` and the text
+// between matches passed through `between`, or `None` if nothing matched;
+// patterns that don't compile are skipped (`check` reports them before
+// rendering)
+pub(crate) fn spell_matches(
+    text: &str,
+    patterns: &[SpellPattern],
+    lang: &str,
+    mut between: impl FnMut(&str) -> String,
+) -> Option<String> {
+    let mut spans: Vec<(usize, usize)> = Vec::new();
+    for pattern in patterns {
+        match pattern {
+            SpellPattern::Regex(source) => {
+                let Ok(regex) = Regex::new(source) else {
+                    continue;
+                };
+                spans.extend(
+                    regex
+                        .find_iter(text)
+                        .filter(|m| !m.is_empty())
+                        .map(|m| (m.start(), m.end())),
+                );
+            }
+            _ => spans.extend(
+                words(text).filter(|&(start, end)| matches_word(&text[start..end], pattern)),
+            ),
+        }
+    }
+    if spans.is_empty() {
+        return None;
+    }
+
+    // Earliest first, the longer of two overlapping matches kept
+    spans.sort_by_key(|&(start, end)| (start, std::cmp::Reverse(end)));
+    let mut out = String::with_capacity(text.len() * 3);
+    let mut done = 0;
+    for (start, end) in spans {
+        if start < done {
+            continue;
+        }
+        out.push_str(&between(&text[done..start]));
+        out.push_str(&spell(&text[start..end], lang));
+        done = end;
+    }
+    out.push_str(&between(&text[done..]));
+    Some(out)
+}
+
+// Byte spans of runs of alphanumerics and separators, without separators at
+// either end ("commit a3f9c2e." -> "commit", "a3f9c2e")
+fn words(text: &str) -> impl Iterator<Item = (usize, usize)> + '_ {
+    let in_word = |c: char| c.is_alphanumeric() || SEPARATORS.contains(&c);
+    let mut spans = Vec::new();
+    let mut start = None;
+    for (idx, c) in text.char_indices().chain([(text.len(), ' ')]) {
+        match (start, in_word(c)) {
+            (None, true) => start = Some(idx),
+            (Some(from), false) => {
+                let run = &text[from..idx];
+                let lead = run.len() - run.trim_start_matches(SEPARATORS).len();
+                let word = run.trim_matches(SEPARATORS);
+                if !word.is_empty() {
+                    spans.push((from + lead, from + lead + word.len()));
+                }
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    spans.into_iter()
+}
+
+fn matches_word(word: &str, pattern: &SpellPattern) -> bool {
+    let has_letter = word.chars().any(|c| c.is_alphabetic());
+    let has_digit = word.chars().any(|c| c.is_ascii_digit());
+    match pattern {
+        SpellPattern::Hex { min_len } => {
+            word.len() >= *min_len
+                && word.chars().all(|c| c.is_ascii_hexdigit())
+                && has_letter
+                && has_digit
+        }
+        SpellPattern::Mac => [':', '-'].iter().any(|&separator| {
+            let groups: Vec<&str> = word.split(separator).collect();
+            groups.len() == 6
+                && groups
+                    .iter()
+                    .all(|g| g.len() == 2 && g.chars().all(|c| c.is_ascii_hexdigit()))
+        }),
+        SpellPattern::Alphanumeric { min_len } => {
+            word.chars().filter(|c| c.is_alphanumeric()).count() >= *min_len
+                && has_letter
+                && has_digit
+        }
+        SpellPattern::Regex(_) => false,
+    }
+}
+
+// "JIRA-1234" -> "J I R A, one two three four"
+fn spell(text: &str, lang: &str) -> String {
+    let locale = units::lookup(lang);
+    let mut out = String::with_capacity(text.len() * 4);
+    for c in text.chars() {
+        if c.is_whitespace() {
+            if !out.is_empty() && !out.ends_with(' ') {
+                out.push(' ');
+            }
+            continue;
+        }
+        let spoken = match c.to_digit(10) {
+            // Numerals are left to espeak in languages without number words here
+            Some(digit) => locale.map_or(c.to_string(), |l| (l.cardinal)(u64::from(digit))),
+            None if c.is_alphanumeric() => c.to_uppercase().collect(),
+            // A separator pauses instead of being read
+            None => {
+                if !out.is_empty() && !out.ends_with(',') {
+                    out.push(',');
+                }
+                continue;
+            }
+        };
+        if !out.is_empty() && !out.ends_with(' ') {
+            out.push(' ');
+        }
+        out.push_str(&spoken);
+    }
+    out.trim_end_matches([',', ' ']).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spelled(text: &str, patterns: &[SpellPattern]) -> String {
+        spell_matches(text, patterns, "en", str::to_string).unwrap_or_else(|| text.to_string())
+    }
+
+    #[test]
+    fn hashes_are_spelled_and_words_are_not() {
+        let hex = [SpellPattern::Hex { min_len: 7 }];
+        assert_eq!(
+            spelled("Error in commit a3f9c2e. Rolled back to 0b12ffe!", &hex),
+            "Error in commit A three F nine C two E. Rolled back to zero B one two F F E!"
+        );
+        // Too short, no digits or no letters
+        assert_eq!(
+            spelled("Port 8080 at cafe, deadbeef, 2024, ab12", &hex),
+            "Port 8080 at cafe, deadbeef, 2024, ab12"
+        );
+        assert_eq!(
+            spell_matches("Nothing here", &hex, "en", str::to_string),
+            None
+        );
+        // Digits in the synthesis language
+        assert_eq!(
+            spell_matches("Commit a3f9c2e", &hex, "de", str::to_string).as_deref(),
+            Some("Commit A drei F neun C zwei E")
+        );
+    }
+
+    #[test]
+    fn mac_addresses_pause_between_groups() {
+        assert_eq!(
+            spelled(
+                "Device 00:1A:2B:3C:4D:5E joined, 00-1a-2b-3c-4d-5e too.",
+                &[SpellPattern::Mac]
+            ),
+            "Device zero zero, one A, two B, three C, four D, five E joined, \
+             zero zero, one A, two B, three C, four D, five E too."
+        );
+        // Not six groups of two
+        assert_eq!(
+            spelled("At 10:30:00 on 1:2:3:4:5:6", &[SpellPattern::Mac]),
+            "At 10:30:00 on 1:2:3:4:5:6"
+        );
+    }
+
+    #[test]
+    fn mixed_ids_and_regexes_keep_the_sentence_around_them() {
+        let patterns = [SpellPattern::Alphanumeric { min_len: 4 }];
+        assert_eq!(
+            spelled(
+                "Ticket JIRA-1234 (build X7K2P9) is done; see v2.",
+                &patterns
+            ),
+            "Ticket J I R A, one two three four (build X seven K two P nine) is done; see v2."
+        );
+
+        let ticket = [SpellPattern::Regex(r"\bINC\d+\b".to_string())];
+        assert_eq!(
+            spelled("Paged for INC0042, then INC7.", &ticket),
+            "Paged for I N C zero zero four two, then I N C seven."
+        );
+        assert!(check(&ticket).is_ok());
+        assert!(check(&[SpellPattern::Regex("(unclosed".to_string())]).is_err());
+    }
+}
@@ -0,0 +1,190 @@
+//! Cutting finished audio at its pauses
+//!
+//! One long synthesis is faster than many short ones, but per-sentence files
+//! are easier to cache and reorder. `split_on_silence` finds the pauses in a
+//! rendered buffer and cuts there: the audio is measured in frames of
+//! `HOP_MS`, a frame quieter than the threshold is silent, and every run of
+//! silent frames at least `min_silence_ms` long separates two segments.
+//! Shorter gaps, like those between words, stay inside a segment.
+//!
+//! Boundaries land on frame edges, so they are exact to within one hop. The
+//! ranges are sample offsets, which `AudioDuration::from_samples` turns into
+//! the same milliseconds as `WordTiming` uses, to tell which words ended up in
+//! which segment. Works on any 24kHz mono buffer, synthesized or not.
+
+use std::ops::Range;
+
+use crate::{AudioDuration, SAMPLE_RATE};
+
+/// Length of the frames silence is measured in
+pub const HOP_MS: u32 = 10;
+
+/// `audio` cut at every pause of at least `min_silence_ms` quieter than
+/// `threshold_db` (RMS, dBFS)
+///
+/// Silence before the first and after the last sound is dropped; each
+/// segment keeps up to `keep_ms` of the silence on either side of it, never
+/// more than half of a pause, so segments don't overlap. Audio without any
+/// sound gives no segments.
+///
+/// # Example
+/// ```
+/// use kokoro_tiny::split_on_silence;
+///
+/// // A second of tone, half a second of silence, another second of tone
+/// let mut audio: Vec<f32> = (0..24_000).map(|i| (i as f32 * 0.1).sin() * 0.5).collect();
+/// audio.extend(vec![0.0; 12_000]);
+/// audio.extend(audio[..24_000].to_vec());
+///
+/// let sentences = split_on_silence(&audio, 300, -40.0, 50);
+/// assert_eq!(sentences.len(), 2);
+/// assert_eq!(sentences[0].len(), 24_000 + 1_200);
+/// ```
+pub fn split_on_silence(
+    audio: &[f32],
+    min_silence_ms: u32,
+    threshold_db: f32,
+    keep_ms: u32,
+) -> Vec<Vec<f32>> {
+    split_on_silence_ranges(audio, min_silence_ms, threshold_db, keep_ms)
+        .into_iter()
+        .map(|range| audio[range].to_vec())
+        .collect()
+}
+
+/// Where `split_on_silence` would cut: the sample range of each segment, in
+/// order
+///
+/// # Example
+/// ```
+/// use kokoro_tiny::split_on_silence_ranges;
+///
+/// let mut audio = vec![0.0; 4_800];
+/// audio.extend(vec![0.3; 9_600]);
+/// audio.extend(vec![0.0; 4_800]);
+/// let ranges = split_on_silence_ranges(&audio, 100, -40.0, 0);
+/// assert_eq!((ranges.len(), ranges[0].clone()), (1, 4_800..14_400));
+/// ```
+pub fn split_on_silence_ranges(
+    audio: &[f32],
+    min_silence_ms: u32,
+    threshold_db: f32,
+    keep_ms: u32,
+) -> Vec<Range<usize>> {
+    let hop = samples(HOP_MS);
+    let min_silence = samples(min_silence_ms);
+    let keep = samples(keep_ms);
+    let threshold = 10f32.powf(threshold_db / 20.0);
+
+    // Stretches of sound, joined across pauses too short to cut at
+    let mut sounds: Vec<Range<usize>> = Vec::new();
+    for (n, frame) in audio.chunks(hop).enumerate() {
+        let rms = (frame.iter().map(|s| s * s).sum::<f32>() / frame.len() as f32).sqrt();
+        // A NaN threshold leaves nothing silent
+        if rms < threshold {
+            continue;
+        }
+        let frame = n * hop..n * hop + frame.len();
+        match sounds.last_mut() {
+            Some(last) if frame.start - last.end < min_silence => last.end = frame.end,
+            _ => sounds.push(frame),
+        }
+    }
+
+    // Each segment takes up to `keep` from the pauses around it, at most
+    // half of each
+    let mut ranges = Vec::with_capacity(sounds.len());
+    for (i, sound) in sounds.iter().enumerate() {
+        let before = match i {
+            0 => 0,
+            _ => sound.start - (sound.start - sounds[i - 1].end) / 2,
+        };
+        let after = sounds
+            .get(i + 1)
+            .map_or(audio.len(), |next| sound.end + (next.start - sound.end) / 2);
+        ranges.push(sound.start.saturating_sub(keep).max(before)..(sound.end + keep).min(after));
+    }
+    ranges
+}
+
+fn samples(ms: u32) -> usize {
+    AudioDuration::from_millis(u64::from(ms)).to_samples(SAMPLE_RATE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_signal::silence;
+
+    fn tone(seconds: f32) -> Vec<f32> {
+        crate::test_signal::tone(&[(500.0, 0.4)], seconds)
+    }
+
+    // 105ms of silence, then tone 0.5s, silence 0.4s, tone 0.3s, silence
+    // 0.1s (a gap between words), tone 0.2s, silence 0.5s
+    fn sentences() -> Vec<f32> {
+        [
+            silence(0.105),
+            tone(0.5),
+            silence(0.4),
+            tone(0.3),
+            silence(0.1),
+            tone(0.2),
+            silence(0.5),
+        ]
+        .concat()
+    }
+
+    fn near(actual: usize, expected: usize) -> bool {
+        actual.abs_diff(expected) <= samples(HOP_MS)
+    }
+
+    #[test]
+    fn pauses_split_and_word_gaps_do_not() {
+        let audio = sentences();
+        let ranges = split_on_silence_ranges(&audio, 250, -40.0, 0);
+        assert_eq!(ranges.len(), 2, "{:?}", ranges);
+        let expected = [2_520..14_520, 24_120..38_520];
+        for (range, expected) in ranges.iter().zip(&expected) {
+            assert!(near(range.start, expected.start), "{:?}", ranges);
+            assert!(near(range.end, expected.end), "{:?}", ranges);
+        }
+
+        // A shorter minimum also cuts between the words of the second one
+        let words = split_on_silence_ranges(&audio, 80, -40.0, 0);
+        assert_eq!(words.len(), 3, "{:?}", words);
+        assert!(near(words[1].end, 31_320) && near(words[2].start, 33_720));
+
+        let segments = split_on_silence(&audio, 250, -40.0, 0);
+        assert_eq!(segments.len(), 2);
+        for (segment, range) in segments.iter().zip(&ranges) {
+            assert_eq!(segment[..], audio[range.clone()]);
+        }
+    }
+
+    #[test]
+    fn kept_silence_never_overlaps() {
+        let audio = sentences();
+        let kept = split_on_silence_ranges(&audio, 250, -40.0, 50);
+        assert_eq!(kept.len(), 2);
+        assert!(near(kept[0].start, 2_520 - 1_200));
+        assert!(near(kept[0].end, 14_520 + 1_200));
+        // Asking for more than the pause meets in its middle
+        let wide = split_on_silence_ranges(&audio, 250, -40.0, 1_000);
+        assert_eq!(wide[0].start, 0);
+        assert_eq!(wide[0].end, wide[1].start);
+        assert!(near(wide[0].end, (14_520 + 24_120) / 2));
+        assert_eq!(wide[1].end, audio.len());
+    }
+
+    #[test]
+    fn silence_and_nothing_give_no_segments() {
+        assert!(split_on_silence(&silence(1.0), 250, -40.0, 50).is_empty());
+        assert!(split_on_silence(&[], 250, -40.0, 50).is_empty());
+        // Quieter than the threshold counts as silence
+        let hum = vec![0.001; 24_000];
+        assert!(split_on_silence_ranges(&hum, 250, -40.0, 0).is_empty());
+        let whole = split_on_silence_ranges(&hum, 250, -80.0, 0);
+        assert_eq!(whole, vec![0..hum.len()]);
+    }
+}
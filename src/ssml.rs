@@ -0,0 +1,545 @@
+//! SSML input (feature `ssml`)
+//!
+//! For content pipelines that already produce SSML for cloud voices.
+//! `TtsEngine::synthesize_ssml` reads the subset that maps onto this engine:
+//!
+//! | Element | Effect |
+//! |---|---|
+//! | `<speak>` | Document root (optional) |
+//! | `<break time="500ms"/>`, `<break strength="strong"/>` | Silence, at most 10 s |
+//! | `<prosody rate="slow">`, `rate="120%"` | Speed of the enclosed text |
+//! | `<prosody volume="loud">`, `volume="-6dB"` | Gain of the enclosed text |
+//! | `<say-as interpret-as="characters">` | Letter by letter ("spell-out" too) |
+//! | `<say-as interpret-as="digits">` | Digit by digit |
+//! | `<sub alias="World Wide Web">WWW</sub>` | The alias instead of the text |
+//!
+//! Nested prosody multiplies. Any other element, attribute or `interpret-as`
+//! is ignored with a `SynthesisWarning::UnsupportedSsml`, reading the text
+//! inside it as is. Malformed XML and values that can't be read (`time="soon"`)
+//! are `KokoroError::InvalidInput`.
+//!
+//! ```text
+//! <speak>
+//!   Build <say-as interpret-as="digits">4711</say-as> failed.
+//!   <break time="300ms"/>
+//!   <prosody rate="slow" volume="+6dB">Please check the logs.</prosody>
+//! </speak>
+//! ```
+
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+
+use crate::{
+    AudioDuration, KokoroError, SynthesisWarning, SynthesizeOptions, TtsEngine, SAMPLE_RATE,
+};
+
+/// Longest `<break>`, as in the SSML spec
+pub const MAX_BREAK_MS: u32 = 10_000;
+
+/// A piece of an SSML document, in reading order
+#[derive(Clone, Debug, PartialEq)]
+pub enum SsmlSegment {
+    /// Text read with `rate` times the speed and `volume` times the gain of
+    /// the options
+    Text {
+        text: String,
+        rate: f32,
+        volume: f32,
+    },
+    /// Silence
+    Break { ms: u32 },
+}
+
+/// A parsed SSML document
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SsmlDocument {
+    /// Text with the same prosody is one segment; breaks split segments
+    pub segments: Vec<SsmlSegment>,
+    /// What was ignored, once per element or attribute
+    pub warnings: Vec<SynthesisWarning>,
+}
+
+// What is read inside the innermost element
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Content {
+    Text,
+    Characters,
+    Digits,
+    // Inside <sub>, whose alias was read instead
+    Skipped,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Frame {
+    rate: f32,
+    volume: f32,
+    content: Content,
+}
+
+/// Parse `ssml` into segments
+///
+/// # Example
+/// ```
+/// use kokoro_tiny::ssml::{parse_ssml, SsmlSegment};
+///
+/// let ssml = r#"<speak>Hi <break time="1s"/><prosody rate="50%">there</prosody></speak>"#;
+/// let doc = parse_ssml(ssml)?;
+/// assert_eq!(
+///     doc.segments,
+///     vec![
+///         SsmlSegment::Text { text: "Hi".into(), rate: 1.0, volume: 1.0 },
+///         SsmlSegment::Break { ms: 1000 },
+///         SsmlSegment::Text { text: "there".into(), rate: 0.5, volume: 1.0 },
+///     ]
+/// );
+/// # Ok::<(), kokoro_tiny::KokoroError>(())
+/// ```
+pub fn parse_ssml(ssml: &str) -> Result<SsmlDocument, KokoroError> {
+    let mut reader = Reader::from_str(ssml);
+    let mut doc = SsmlDocument::default();
+    let mut stack = vec![Frame {
+        rate: 1.0,
+        volume: 1.0,
+        content: Content::Text,
+    }];
+
+    loop {
+        let event = reader
+            .read_event()
+            .map_err(|e| invalid(format!("at byte {}: {}", reader.buffer_position(), e)))?;
+        let frame = stack[stack.len() - 1];
+        match event {
+            Event::Start(element) => {
+                let inner = open(&element, frame, &mut doc)?;
+                stack.push(inner);
+            }
+            Event::Empty(element) => {
+                open(&element, frame, &mut doc)?;
+            }
+            Event::End(_) if stack.len() > 1 => {
+                stack.pop();
+            }
+            Event::Text(text) => {
+                let text = text.unescape().map_err(|e| invalid(e.to_string()))?;
+                push_text(&mut doc, &text, frame);
+            }
+            Event::CData(text) => {
+                push_text(
+                    &mut doc,
+                    &String::from_utf8_lossy(&text.into_inner()),
+                    frame,
+                );
+            }
+            Event::Eof if stack.len() > 1 => {
+                return Err(invalid("unclosed element".to_string()));
+            }
+            Event::Eof => break,
+            // Declarations, comments, processing instructions
+            _ => {}
+        }
+    }
+
+    doc.segments.retain_mut(|segment| match segment {
+        SsmlSegment::Text { text, .. } => {
+            *text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+            !text.is_empty()
+        }
+        SsmlSegment::Break { .. } => true,
+    });
+    Ok(doc)
+}
+
+// An opening (or empty) element inside `parent`; returns the frame for its
+// content
+fn open(element: &BytesStart, parent: Frame, doc: &mut SsmlDocument) -> Result<Frame, KokoroError> {
+    let name = String::from_utf8_lossy(element.local_name().as_ref()).to_lowercase();
+    let mut attributes = Vec::new();
+    for attribute in element.attributes() {
+        let attribute = attribute.map_err(|e| invalid(e.to_string()))?;
+        let key = String::from_utf8_lossy(attribute.key.local_name().as_ref()).to_lowercase();
+        let value = attribute
+            .unescape_value()
+            .map_err(|e| invalid(e.to_string()))?;
+        attributes.push((key, value.trim().to_string()));
+    }
+    let attribute = |key: &str| {
+        attributes
+            .iter()
+            .find(|(name, _)| name == key)
+            .map(|(_, value)| value.as_str())
+    };
+    let mut frame = parent;
+
+    match name.as_str() {
+        "speak" => {}
+        "break" => {
+            let ms = match (attribute("time"), attribute("strength")) {
+                (Some(time), _) => parse_time(time)?,
+                (None, Some(strength)) => break_strength(strength)?,
+                (None, None) => break_strength("medium")?,
+            };
+            doc.segments.push(SsmlSegment::Break {
+                ms: ms.min(MAX_BREAK_MS),
+            });
+        }
+        "prosody" => {
+            for (key, value) in &attributes {
+                match key.as_str() {
+                    "rate" => frame.rate *= parse_rate(value)?,
+                    "volume" => frame.volume *= parse_volume(value)?,
+                    other => warn(doc, format!("<prosody {}>", other)),
+                }
+            }
+        }
+        "say-as" => {
+            frame.content = match attribute("interpret-as") {
+                Some("characters") | Some("spell-out") => Content::Characters,
+                Some("digits") => Content::Digits,
+                Some(other) => {
+                    warn(doc, format!("<say-as interpret-as=\"{}\">", other));
+                    parent.content
+                }
+                None => return Err(invalid("<say-as> needs interpret-as".to_string())),
+            };
+        }
+        "sub" => {
+            let alias =
+                attribute("alias").ok_or_else(|| invalid("<sub> needs an alias".to_string()))?;
+            push_text(doc, &format!(" {} ", alias), parent);
+            frame.content = Content::Skipped;
+        }
+        other => warn(doc, format!("<{}>", other)),
+    }
+    Ok(frame)
+}
+
+fn push_text(doc: &mut SsmlDocument, text: &str, frame: Frame) {
+    let text = match frame.content {
+        Content::Text => text.to_string(),
+        Content::Characters => spaced(text, |_| true),
+        Content::Digits => spaced(text, |c| c.is_ascii_digit()),
+        Content::Skipped => return,
+    };
+    match doc.segments.last_mut() {
+        Some(SsmlSegment::Text {
+            text: last,
+            rate,
+            volume,
+        }) if *rate == frame.rate && *volume == frame.volume => last.push_str(&text),
+        _ => doc.segments.push(SsmlSegment::Text {
+            text,
+            rate: frame.rate,
+            volume: frame.volume,
+        }),
+    }
+}
+
+// Every char for which `single` holds on its own: "ABC" -> " A B C "
+fn spaced(text: &str, single: impl Fn(char) -> bool) -> String {
+    let mut out = String::with_capacity(text.len() * 2);
+    for c in text.chars() {
+        if !c.is_whitespace() && single(c) {
+            out.push(' ');
+            out.push(c);
+            out.push(' ');
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+fn warn(doc: &mut SsmlDocument, tag: String) {
+    let warning = SynthesisWarning::UnsupportedSsml { tag };
+    if !doc.warnings.contains(&warning) {
+        doc.warnings.push(warning);
+    }
+}
+
+fn invalid(message: String) -> KokoroError {
+    KokoroError::InvalidInput(format!("Invalid SSML: {}", message))
+}
+
+// "500ms", "1.5s"
+fn parse_time(time: &str) -> Result<u32, KokoroError> {
+    let (number, scale) = match time.strip_suffix("ms") {
+        Some(ms) => (ms, 1.0),
+        None => (time.strip_suffix('s').unwrap_or(""), 1000.0),
+    };
+    match number.trim().parse::<f64>() {
+        Ok(n) if n >= 0.0 && n.is_finite() => Ok((n * scale).round().min(u32::MAX as f64) as u32),
+        _ => Err(invalid(format!("break time {:?}", time))),
+    }
+}
+
+fn break_strength(strength: &str) -> Result<u32, KokoroError> {
+    Ok(match strength {
+        "none" => 0,
+        "x-weak" => 100,
+        "weak" => 250,
+        "medium" => 500,
+        "strong" => 750,
+        "x-strong" => 1200,
+        _ => return Err(invalid(format!("break strength {:?}", strength))),
+    })
+}
+
+// A speed factor: "slow", "80%" (of the current), "+20%" (faster by)
+fn parse_rate(rate: &str) -> Result<f32, KokoroError> {
+    let factor = match rate {
+        "x-slow" => Some(0.5),
+        "slow" => Some(0.75),
+        "medium" | "default" => Some(1.0),
+        "fast" => Some(1.25),
+        "x-fast" => Some(1.5),
+        _ => percentage(rate),
+    };
+    factor
+        .filter(|factor| *factor > 0.0)
+        .ok_or_else(|| invalid(format!("prosody rate {:?}", rate)))
+}
+
+// A gain factor: "loud", "-6dB", "+50%"
+fn parse_volume(volume: &str) -> Result<f32, KokoroError> {
+    let factor = match volume {
+        "silent" => Some(0.0),
+        "x-soft" => Some(0.3),
+        "soft" => Some(0.6),
+        "medium" | "default" => Some(1.0),
+        "loud" => Some(1.4),
+        "x-loud" => Some(1.8),
+        _ => match volume.strip_suffix("dB") {
+            Some(db) => db
+                .trim_start_matches('+')
+                .parse::<f32>()
+                .ok()
+                .map(|db| 10f32.powf(db / 20.0)),
+            None => percentage(volume),
+        },
+    };
+    factor
+        .filter(|factor| *factor >= 0.0 && factor.is_finite())
+        .ok_or_else(|| invalid(format!("prosody volume {:?}", volume)))
+}
+
+// "80%" -> 0.8, "+20%" -> 1.2, "-20%" -> 0.8, "1.5" -> 1.5
+fn percentage(value: &str) -> Option<f32> {
+    let Some(percent) = value.strip_suffix('%') else {
+        return value.parse().ok();
+    };
+    let relative = percent.starts_with(['+', '-']);
+    let n: f32 = percent.trim_start_matches('+').parse().ok()?;
+    Some(if relative { 1.0 + n / 100.0 } else { n / 100.0 })
+}
+
+impl TtsEngine {
+    /// Synthesize an SSML document (see `ssml` for what is supported)
+    ///
+    /// Each text segment is synthesized like `synthesize_with` with the
+    /// options' speed and gain scaled by its prosody; breaks are silence of
+    /// exactly their length. Returns the audio with the warnings of the whole
+    /// document, ignored SSML first; `last_warnings` has the same list.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use kokoro_tiny::{SynthesizeOptions, TtsEngine};
+    ///
+    /// # async fn demo() -> Result<(), kokoro_tiny::KokoroError> {
+    /// let tts = TtsEngine::new().await?;
+    /// let ssml = r#"<speak>
+    ///     Your code is <say-as interpret-as="characters">XK4</say-as>.
+    ///     <break time="500ms"/>
+    ///     <prosody rate="slow">It expires in ten minutes.</prosody>
+    /// </speak>"#;
+    /// let (audio, warnings) = tts.synthesize_ssml(ssml, SynthesizeOptions::new())?;
+    /// for warning in warnings {
+    ///     eprintln!("{}", warning);
+    /// }
+    /// tts.save_wav("code.wav", &audio)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn synthesize_ssml(
+        &self,
+        ssml: &str,
+        opts: SynthesizeOptions,
+    ) -> Result<(Vec<f32>, Vec<SynthesisWarning>), KokoroError> {
+        let doc = parse_ssml(ssml)?;
+        if doc.segments.is_empty() {
+            return Err(KokoroError::InvalidInput(
+                "No text provided for synthesis".to_string(),
+            ));
+        }
+
+        let (audio, warnings) = self.warnings.record(|| {
+            for warning in doc.warnings {
+                self.warnings.push(warning);
+            }
+            let mut audio = Vec::new();
+            for segment in doc.segments {
+                match segment {
+                    SsmlSegment::Text { text, rate, volume } => {
+                        let segment_opts = opts
+                            .clone()
+                            .speed(opts.speed * rate)
+                            .gain(opts.gain * volume);
+                        audio.extend(self.synthesize_with(&text, segment_opts)?);
+                    }
+                    SsmlSegment::Break { ms } => {
+                        let samples =
+                            AudioDuration::from_millis(u64::from(ms)).to_samples(SAMPLE_RATE);
+                        audio.resize(audio.len() + samples, 0.0);
+                    }
+                }
+            }
+            Ok::<_, KokoroError>(audio)
+        });
+        Ok((audio?, warnings))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DEFAULT_VOICE, STYLE_DIM};
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    fn text(text: &str, rate: f32, volume: f32) -> SsmlSegment {
+        SsmlSegment::Text {
+            text: text.to_string(),
+            rate,
+            volume,
+        }
+    }
+
+    #[test]
+    fn segments_follow_breaks_and_prosody() {
+        let doc = parse_ssml(
+            r#"<?xml version="1.0"?>
+            <speak version="1.1" xmlns="http://www.w3.org/2001/10/synthesis">
+              Build <say-as interpret-as="digits">4711</say-as> failed &amp; stopped.
+              <break time="300ms"/><break time="1.5s"/>
+              <prosody rate="slow" volume="+6dB">Check
+                <prosody rate="200%">the <sub alias="World Wide Web">WWW</sub> logs</prosody>
+              </prosody>
+              <!-- not read --><break strength="x-strong"/>
+              Code <say-as interpret-as="characters">XK4</say-as>.
+              <break time="60s"/>
+            </speak>"#,
+        )
+        .unwrap();
+
+        let loud = 10f32.powf(6.0 / 20.0);
+        assert_eq!(
+            doc.segments,
+            vec![
+                text("Build 4 7 1 1 failed & stopped.", 1.0, 1.0),
+                SsmlSegment::Break { ms: 300 },
+                SsmlSegment::Break { ms: 1500 },
+                text("Check", 0.75, loud),
+                text("the World Wide Web logs", 1.5, loud),
+                SsmlSegment::Break { ms: 1200 },
+                text("Code X K 4 .", 1.0, 1.0),
+                SsmlSegment::Break { ms: MAX_BREAK_MS },
+            ]
+        );
+        assert!(doc.warnings.is_empty(), "{:?}", doc.warnings);
+    }
+
+    #[test]
+    fn unknown_markup_is_read_with_a_warning() {
+        let doc = parse_ssml(
+            r#"<speak><emphasis level="strong">Really</emphasis> <mark name="a"/>now,
+            <prosody pitch="high">up</prosody> on <say-as interpret-as="date">12/05</say-as>
+            <emphasis>again</emphasis></speak>"#,
+        )
+        .unwrap();
+        assert_eq!(
+            doc.segments,
+            vec![text("Really now, up on 12/05 again", 1.0, 1.0)]
+        );
+        let tags: Vec<String> = doc
+            .warnings
+            .iter()
+            .map(|warning| match warning {
+                SynthesisWarning::UnsupportedSsml { tag } => tag.clone(),
+                other => panic!("{:?}", other),
+            })
+            .collect();
+        assert_eq!(
+            tags,
+            [
+                "<emphasis>",
+                "<mark>",
+                "<prosody pitch>",
+                "<say-as interpret-as=\"date\">"
+            ]
+        );
+    }
+
+    #[test]
+    fn malformed_documents_are_errors() {
+        for ssml in [
+            "<speak>Unclosed",
+            "<speak>Bad</prosody></speak>",
+            r#"<speak><break time="soon"/></speak>"#,
+            r#"<speak><prosody rate="-100%">x</prosody></speak>"#,
+            r#"<speak><prosody volume="deafening">x</prosody></speak>"#,
+            "<speak><sub>x</sub></speak>",
+        ] {
+            let err = parse_ssml(ssml).unwrap_err();
+            assert!(
+                matches!(err, KokoroError::InvalidInput(_)),
+                "{}: {:?}",
+                ssml,
+                err
+            );
+        }
+    }
+
+    #[test]
+    fn audio_has_each_segment_and_exact_silences() {
+        let voices = HashMap::from([(DEFAULT_VOICE.to_string(), vec![0.1; STYLE_DIM])]);
+        let mut engine = TtsEngine::with_test_voices(voices);
+        // The model speed comes back as the sample value
+        engine.mock_inference = Some(Arc::new(|tokens: &[i64], _: &[f32], speed| {
+            Ok(vec![speed; tokens.len() * 10])
+        }));
+        let opts = SynthesizeOptions::new().speed(1.0).gain(0.5);
+
+        let (audio, warnings) = engine
+            .synthesize_ssml(
+                r#"<speak>Hello there.<break time="250ms"/>
+                <prosody rate="fast" volume="200%">Bye now.</prosody><shout/></speak>"#,
+                opts.clone(),
+            )
+            .unwrap();
+        assert_eq!(
+            warnings,
+            vec![SynthesisWarning::UnsupportedSsml {
+                tag: "<shout>".into()
+            }]
+        );
+        assert_eq!(engine.last_warnings(), warnings);
+
+        let first = engine
+            .synthesize_with("Hello there.", opts.clone())
+            .unwrap();
+        let second = engine
+            .synthesize_with("Bye now.", opts.clone().speed(1.25).gain(1.0))
+            .unwrap();
+        let silence = SAMPLE_RATE as usize / 4;
+        assert_eq!(audio.len(), first.len() + silence + second.len());
+        assert_eq!(&audio[..first.len()], first.as_slice());
+        assert!(audio[first.len()..first.len() + silence]
+            .iter()
+            .all(|&s| s == 0.0));
+        assert_eq!(&audio[first.len() + silence..], second.as_slice());
+        // Faster and louder than the first segment
+        let peak = |samples: &[f32]| samples.iter().fold(0.0f32, |m, s| m.max(s.abs()));
+        assert!(peak(&second) > peak(&first) * 2.0);
+
+        assert!(engine.synthesize_ssml("<speak> </speak>", opts).is_err());
+    }
+}
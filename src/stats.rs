@@ -0,0 +1,429 @@
+//! Timings of a synthesis, for tracking performance in production
+//!
+//! `TtsEngine::synthesize_with_stats` returns the audio together with a
+//! `SynthesisStats`: time spent in espeak and in the model, token counts, a
+//! line per model pass and the real-time factor. `measure` collects the same
+//! numbers around any other synthesis call (streaming, progress, resumable).
+//!
+//! Passes are recorded only while a measurement is running on the thread, so
+//! plain `synthesize` calls don't pay for them. Work done on other threads
+//! (`synthesize_batch` workers, `synthesize_async`) isn't seen.
+//!
+//! `AudioStats` measures the audio itself (levels, clipping, silence), for
+//! checks like "no clipping, RMS above -30 dBFS" in CI.
+
+use std::cell::RefCell;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use serde::{Serialize, Serializer};
+use serde_json::json;
+
+use crate::{AudioDuration, KokoroError, SynthesizeOptions, TtsEngine, SAMPLE_RATE};
+
+// Lowest level reported, digital silence included
+const FLOOR_DBFS: f32 = -120.0;
+// Samples this quiet (about -50 dBFS) count as silence, as for trim_silence
+const SILENCE_LEVEL: f32 = 0.003;
+
+/// One pass through the model
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ChunkStats {
+    /// Tokens fed to the model, padding included
+    pub tokens: usize,
+    /// Text normalization and espeak
+    pub phonemize: Duration,
+    /// The model run (close to zero when the chunk cache had it)
+    pub inference: Duration,
+    /// Samples the model returned
+    pub samples: usize,
+}
+
+/// Where the time of one synthesis went
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SynthesisStats {
+    /// Every model pass, in order
+    pub chunks: Vec<ChunkStats>,
+    /// Samples of the finished audio (24kHz)
+    pub samples: usize,
+    /// Wall-clock time of the whole call
+    pub total: Duration,
+    /// Served from the result cache: no chunks ran
+    pub cached: bool,
+}
+
+impl SynthesisStats {
+    /// Time spent phonemizing, over all chunks
+    pub fn phonemize(&self) -> Duration {
+        self.chunks.iter().map(|chunk| chunk.phonemize).sum()
+    }
+
+    /// Time spent in the model, over all chunks
+    pub fn inference(&self) -> Duration {
+        self.chunks.iter().map(|chunk| chunk.inference).sum()
+    }
+
+    /// Tokens fed to the model, over all chunks
+    pub fn tokens(&self) -> usize {
+        self.chunks.iter().map(|chunk| chunk.tokens).sum()
+    }
+
+    /// Length of the finished audio
+    pub fn audio_duration(&self) -> Duration {
+        AudioDuration::from_samples(self.samples, SAMPLE_RATE).into()
+    }
+
+    /// Seconds of work per second of audio; below 1.0 renders faster than it
+    /// plays. 0.0 for no audio.
+    pub fn real_time_factor(&self) -> f64 {
+        let audio = self.audio_duration().as_secs_f64();
+        if audio > 0.0 {
+            self.total.as_secs_f64() / audio
+        } else {
+            0.0
+        }
+    }
+
+    /// The numbers as JSON, times in milliseconds (for logs and MCP results)
+    pub fn to_json(&self) -> serde_json::Value {
+        let ms = |d: Duration| d.as_secs_f64() * 1000.0;
+        json!({
+            "phonemize_ms": ms(self.phonemize()),
+            "inference_ms": ms(self.inference()),
+            "total_ms": ms(self.total),
+            "tokens": self.tokens(),
+            "chunks": self.chunks.iter().map(|chunk| json!({
+                "tokens": chunk.tokens,
+                "phonemize_ms": ms(chunk.phonemize),
+                "inference_ms": ms(chunk.inference),
+                "samples": chunk.samples,
+            })).collect::<Vec<_>>(),
+            "samples": self.samples,
+            "audio_ms": ms(self.audio_duration()),
+            "real_time_factor": self.real_time_factor(),
+            "cached": self.cached,
+        })
+    }
+}
+
+/// Levels and lengths of a piece of audio
+///
+/// Levels are in dBFS (0.0 = full scale), no lower than -120. Serializes
+/// with durations in milliseconds.
+///
+/// # Example
+/// ```
+/// use kokoro_tiny::AudioStats;
+///
+/// let audio: Vec<f32> = (0..24_000).map(|i| (i as f32 * 0.1).sin() * 0.5).collect();
+/// let stats = AudioStats::measure(&audio, 24_000);
+/// assert_eq!(stats.clipped_samples, 0);
+/// assert!(stats.rms_dbfs > -30.0);
+/// assert_eq!(stats.duration.as_millis(), 1000);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct AudioStats {
+    /// Largest sample, in dBFS
+    pub peak_dbfs: f32,
+    /// Overall level, in dBFS
+    pub rms_dbfs: f32,
+    #[serde(rename = "duration_ms", serialize_with = "millis")]
+    pub duration: AudioDuration,
+    /// Samples at or past full scale
+    pub clipped_samples: usize,
+    /// Longest stretch below about -50 dBFS
+    #[serde(rename = "longest_silence_ms", serialize_with = "millis")]
+    pub longest_silence: AudioDuration,
+}
+
+impl AudioStats {
+    /// Measure mono `audio` at `sample_rate`
+    pub fn measure(audio: &[f32], sample_rate: u32) -> Self {
+        let peak = audio.iter().fold(0.0f32, |peak, s| peak.max(s.abs()));
+        let power = audio
+            .iter()
+            .map(|&s| f64::from(s) * f64::from(s))
+            .sum::<f64>()
+            / audio.len().max(1) as f64;
+        let (mut run, mut longest) = (0, 0);
+        for sample in audio {
+            run = if sample.abs() <= SILENCE_LEVEL {
+                run + 1
+            } else {
+                0
+            };
+            longest = longest.max(run);
+        }
+        Self {
+            peak_dbfs: dbfs(peak),
+            rms_dbfs: dbfs(power.sqrt() as f32),
+            duration: AudioDuration::from_samples(audio.len(), sample_rate),
+            clipped_samples: audio.iter().filter(|s| s.abs() >= 1.0).count(),
+            longest_silence: AudioDuration::from_samples(longest, sample_rate),
+        }
+    }
+}
+
+impl fmt::Display for AudioStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} long, peak {:.1} dBFS, RMS {:.1} dBFS, {} clipped, longest silence {}",
+            self.duration,
+            self.peak_dbfs,
+            self.rms_dbfs,
+            self.clipped_samples,
+            self.longest_silence
+        )
+    }
+}
+
+fn dbfs(level: f32) -> f32 {
+    if level > 0.0 {
+        (20.0 * level.log10()).max(FLOOR_DBFS)
+    } else {
+        FLOOR_DBFS
+    }
+}
+
+fn millis<S: Serializer>(duration: &AudioDuration, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_u64(duration.as_millis())
+}
+
+// The measurement running on this thread, if any
+thread_local! {
+    static RECORDING: RefCell<Option<SynthesisStats>> = const { RefCell::new(None) };
+}
+
+fn recording() -> bool {
+    RECORDING.with(|recording| recording.borrow().is_some())
+}
+
+fn update(f: impl FnOnce(&mut SynthesisStats)) {
+    RECORDING.with(|recording| {
+        if let Some(stats) = recording.borrow_mut().as_mut() {
+            f(stats);
+        }
+    });
+}
+
+/// Run `render` and collect the stats of the synthesis it does on this thread
+///
+/// `samples` is left at what the model returned; set it to the length of the
+/// finished audio, which also has pauses and trimming in it.
+///
+/// # Example
+/// ```no_run
+/// use kokoro_tiny::{stats, SynthesizeOptions, TtsEngine};
+///
+/// # async fn demo() -> Result<(), kokoro_tiny::KokoroError> {
+/// let tts = TtsEngine::new().await?;
+/// let (audio, mut stats) = stats::measure(|| {
+///     tts.synthesize_with_progress("A long article...", SynthesizeOptions::new(), |_| {})
+/// });
+/// let audio = audio?;
+/// stats.samples = audio.len();
+/// println!("real-time factor {:.2}", stats.real_time_factor());
+/// # Ok(())
+/// # }
+/// ```
+pub fn measure<T>(render: impl FnOnce() -> T) -> (T, SynthesisStats) {
+    let outer = RECORDING.with(|recording| recording.replace(Some(SynthesisStats::default())));
+    let start = Instant::now();
+    let result = render();
+    let total = start.elapsed();
+    let mut stats = RECORDING
+        .with(|recording| recording.replace(outer))
+        .unwrap_or_default();
+    stats.total = total;
+    stats.samples = stats.chunks.iter().map(|chunk| chunk.samples).sum();
+    (result, stats)
+}
+
+// A result cache hit
+pub(crate) fn record_cache_hit() {
+    update(|stats| stats.cached = true);
+}
+
+/// Times one model pass; does nothing unless a measurement is running
+pub(crate) struct ChunkTimer {
+    start: Option<Instant>,
+    phonemize: Duration,
+    tokens: usize,
+}
+
+impl ChunkTimer {
+    pub(crate) fn start() -> Self {
+        ChunkTimer {
+            start: recording().then(Instant::now),
+            phonemize: Duration::ZERO,
+            tokens: 0,
+        }
+    }
+
+    // Phonemes are tokenized; the model runs next
+    pub(crate) fn phonemized(&mut self, tokens: usize) {
+        if let Some(start) = self.start {
+            self.phonemize = start.elapsed();
+            self.tokens = tokens;
+        }
+    }
+
+    pub(crate) fn finish(self, samples: usize) {
+        if let Some(start) = self.start {
+            let chunk = ChunkStats {
+                tokens: self.tokens,
+                phonemize: self.phonemize,
+                inference: start.elapsed().saturating_sub(self.phonemize),
+                samples,
+            };
+            update(|stats| stats.chunks.push(chunk));
+        }
+    }
+}
+
+impl TtsEngine {
+    /// `synthesize_with`, also returning where the time went
+    ///
+    /// The audio is exactly what `synthesize_with` returns (the result cache
+    /// applies; a hit has `cached` set and no chunks). In fallback mode there
+    /// are no chunks either.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use kokoro_tiny::{SynthesizeOptions, TtsEngine};
+    ///
+    /// # async fn demo() -> Result<(), kokoro_tiny::KokoroError> {
+    /// let tts = TtsEngine::new().await?;
+    /// let (audio, stats) = tts.synthesize_with_stats("Deploy finished.", SynthesizeOptions::new())?;
+    /// println!(
+    ///     "{} tokens, espeak {:?}, model {:?}, real-time factor {:.2}",
+    ///     stats.tokens(),
+    ///     stats.phonemize(),
+    ///     stats.inference(),
+    ///     stats.real_time_factor()
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn synthesize_with_stats(
+        &self,
+        text: &str,
+        opts: SynthesizeOptions,
+    ) -> Result<(Vec<f32>, SynthesisStats), KokoroError> {
+        let (audio, mut stats) = measure(|| self.synthesize_with(text, opts));
+        let audio = audio?;
+        stats.samples = audio.len();
+        Ok((audio, stats))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::TAU;
+    use std::sync::Arc;
+
+    fn engine() -> TtsEngine {
+        let mut engine = TtsEngine::mock(100);
+        engine.mock_inference = Some(Arc::new(|tokens: &[i64], _: &[f32], _| {
+            std::thread::sleep(Duration::from_millis(2));
+            Ok(vec![0.5; tokens.len() * 100])
+        }));
+        engine
+    }
+
+    #[test]
+    fn stats_cover_every_chunk() {
+        let engine = engine();
+        let text = "The first sentence is long enough to be a chunk on its own today. ".repeat(6);
+        let (audio, stats) = engine
+            .synthesize_with_stats(&text, SynthesizeOptions::new())
+            .unwrap();
+
+        assert!(stats.chunks.len() > 1);
+        assert!(!stats.cached);
+        assert_eq!(stats.samples, audio.len());
+        for chunk in &stats.chunks {
+            assert!(chunk.tokens > 6);
+            assert_eq!(chunk.samples, chunk.tokens * 100);
+            assert!(chunk.inference >= Duration::from_millis(2));
+        }
+        assert_eq!(
+            stats.tokens(),
+            stats.chunks.iter().map(|c| c.tokens).sum::<usize>()
+        );
+        assert!(stats.total >= stats.inference() + stats.phonemize());
+        assert!(stats.real_time_factor() > 0.0);
+
+        let json = stats.to_json();
+        assert_eq!(json["tokens"], stats.tokens());
+        assert_eq!(json["chunks"].as_array().unwrap().len(), stats.chunks.len());
+
+        // Same text again: the result cache answers
+        let (_, again) = engine
+            .synthesize_with_stats(&text, SynthesizeOptions::new())
+            .unwrap();
+        assert!(again.cached && again.chunks.is_empty());
+    }
+
+    #[test]
+    fn nothing_is_recorded_outside_a_measurement() {
+        let engine = engine();
+        engine
+            .synthesize("Not measured.", None, None, None)
+            .unwrap();
+        assert!(!recording());
+
+        let (_, stats) = measure(|| engine.synthesize("Measured.", None, None, None));
+        assert_eq!(stats.chunks.len(), 1);
+        assert_eq!(stats.samples, stats.chunks[0].samples);
+        assert!(!recording());
+    }
+
+    #[test]
+    fn audio_stats_of_known_signals() {
+        // 1 kHz at half scale: whole cycles of 24 samples, peak exactly 0.5
+        let sine: Vec<f32> = (0..SAMPLE_RATE)
+            .map(|i| (TAU * (i % 24) as f32 / 24.0).sin() * 0.5)
+            .collect();
+        let stats = AudioStats::measure(&sine, SAMPLE_RATE);
+        assert_eq!(stats.peak_dbfs, dbfs(0.5));
+        assert!((stats.peak_dbfs + 6.0206).abs() < 1e-3);
+        // RMS of a sine is peak / sqrt(2): another 3.01 dB down
+        assert!((stats.rms_dbfs + 9.0309).abs() < 1e-3, "{}", stats.rms_dbfs);
+        assert_eq!(stats.duration.as_millis(), 1000);
+        assert_eq!(stats.clipped_samples, 0);
+        // The zero crossings are single samples
+        assert_eq!(stats.longest_silence.as_millis(), 0);
+
+        let silence = AudioStats::measure(&[0.0; 12_000], SAMPLE_RATE);
+        assert_eq!((silence.peak_dbfs, silence.rms_dbfs), (-120.0, -120.0));
+        assert_eq!(silence.longest_silence.as_millis(), 500);
+        assert_eq!(AudioStats::measure(&[], SAMPLE_RATE).rms_dbfs, -120.0);
+
+        // A square wave driven past full scale and clipped
+        let square: Vec<f32> = (0..4800)
+            .map(|i| if i % 48 < 24 { 1.5f32 } else { -1.5 }.clamp(-1.0, 1.0))
+            .collect();
+        let clipped = AudioStats::measure(&square, SAMPLE_RATE);
+        assert_eq!((clipped.peak_dbfs, clipped.rms_dbfs), (0.0, 0.0));
+        assert_eq!(clipped.clipped_samples, 4800);
+
+        // 100 ms silence, 200 ms tone, 300 ms silence
+        let mut gaps = vec![0.0; 2400];
+        gaps.extend(&sine[..4800]);
+        gaps.extend(vec![0.0; 7200]);
+        let stats = AudioStats::measure(&gaps, SAMPLE_RATE);
+        assert_eq!(stats.longest_silence.as_millis(), 300);
+        assert_eq!(stats.duration.as_millis(), 600);
+        assert_eq!(
+            serde_json::to_value(&stats).unwrap()["longest_silence_ms"],
+            300
+        );
+        assert_eq!(
+            stats.to_string(),
+            "600ms long, peak -6.0 dBFS, RMS -13.8 dBFS, 0 clipped, longest silence 300ms"
+        );
+    }
+}
@@ -13,10 +13,9 @@ use std::sync::{
 use std::thread;
 use std::time::Duration;
 
-#[cfg(feature = "playback")]
-use rodio::{OutputStream, Sink, Source};
-
-use crate::{TtsEngine, SAMPLE_RATE};
+use crate::hotkey::{HotkeyBackend, HotkeyBinding, HotkeyCallback};
+use crate::output::{write_until, OutputSpec};
+use crate::{resample, KokoroError, TtsEngine, SAMPLE_RATE};
 
 /// Maximum chunk size in characters for synthesis
 /// Smaller chunks = faster response to interruption
@@ -39,7 +38,7 @@ const INTERRUPTION_PHRASES: &[&str] = &[
 
 /// Streaming TTS handler with interruption support
 pub struct StreamingTts {
-    engine: Arc<Mutex<TtsEngine>>,
+    engine: Arc<TtsEngine>,
     is_speaking: Arc<AtomicBool>,
     interrupt_flag: Arc<AtomicBool>,
     audio_queue: Arc<Mutex<VecDeque<Vec<f32>>>>,
@@ -47,13 +46,46 @@ pub struct StreamingTts {
     speed: f32,
     gain: f32,
     volume: f32,
+    hotkey: Mutex<Option<HotkeyBinding>>,
 }
 
 impl StreamingTts {
     /// Create a new streaming TTS handler
+    ///
+    /// # Example
+    /// ```no_run
+    /// use kokoro_tiny::streaming::StreamingTts;
+    /// use kokoro_tiny::TtsEngine;
+    ///
+    /// # async fn demo() -> Result<(), kokoro_tiny::KokoroError> {
+    /// let stream = StreamingTts::new(TtsEngine::new().await?);
+    /// stream.speak_stream("First this, then that, then something else entirely.").await?;
+    /// # Ok(())
+    /// # }
+    /// ```
     pub fn new(engine: TtsEngine) -> Self {
+        Self::with_engine(Arc::new(engine))
+    }
+
+    /// Create a streaming TTS handler on an engine shared with other code
+    ///
+    /// # Example
+    /// ```no_run
+    /// use kokoro_tiny::streaming::StreamingTts;
+    /// use kokoro_tiny::{shared_engine, KokoroError};
+    ///
+    /// # async fn demo() -> Result<(), KokoroError> {
+    /// let engine = shared_engine().await?;
+    /// let stream = StreamingTts::with_engine(engine.clone());
+    /// let chime = engine.synthesize("Ding.", None, None, None)?;
+    /// engine.play(chime, 0.8)?;
+    /// stream.speak_stream("And now the news.").await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_engine(engine: Arc<TtsEngine>) -> Self {
         Self {
-            engine: Arc::new(Mutex::new(engine)),
+            engine,
             is_speaking: Arc::new(AtomicBool::new(false)),
             interrupt_flag: Arc::new(AtomicBool::new(false)),
             audio_queue: Arc::new(Mutex::new(VecDeque::new())),
@@ -61,15 +93,43 @@ impl StreamingTts {
             speed: 0.85,
             gain: 1.5,
             volume: 0.8,
+            hotkey: Mutex::new(None),
         }
     }
 
     /// Set voice for synthesis
+    ///
+    /// # Example
+    /// ```no_run
+    /// use kokoro_tiny::streaming::StreamingTts;
+    /// use kokoro_tiny::TtsEngine;
+    ///
+    /// # async fn demo() -> Result<(), kokoro_tiny::KokoroError> {
+    /// let mut stream = StreamingTts::new(TtsEngine::new().await?);
+    /// stream.set_voice("bf_emma");
+    /// stream.speak_stream("Good evening. Here is the weather for tomorrow.").await?;
+    /// # Ok(())
+    /// # }
+    /// ```
     pub fn set_voice(&mut self, voice: &str) {
         self.voice = voice.to_string();
     }
 
     /// Set synthesis parameters
+    ///
+    /// # Example
+    /// ```no_run
+    /// use kokoro_tiny::streaming::StreamingTts;
+    /// use kokoro_tiny::TtsEngine;
+    ///
+    /// # async fn demo() -> Result<(), kokoro_tiny::KokoroError> {
+    /// let mut stream = StreamingTts::new(TtsEngine::new().await?);
+    /// // speed, gain, volume
+    /// stream.set_parameters(1.0, 1.2, 0.6);
+    /// stream.speak_stream("Quietly, at normal speed.").await?;
+    /// # Ok(())
+    /// # }
+    /// ```
     pub fn set_parameters(&mut self, speed: f32, gain: f32, volume: f32) {
         self.speed = speed;
         self.gain = gain;
@@ -77,12 +137,31 @@ impl StreamingTts {
     }
 
     /// Check if currently speaking
+    ///
+    /// # Example
+    /// ```
+    /// use kokoro_tiny::streaming::StreamingTts;
+    /// use kokoro_tiny::TtsEngine;
+    ///
+    /// let stream = StreamingTts::new(TtsEngine::without_model());
+    /// assert!(!stream.is_speaking());
+    /// ```
     pub fn is_speaking(&self) -> bool {
         self.is_speaking.load(Ordering::Relaxed)
     }
 
     /// Interrupt current speech immediately
     /// This is what gets called when Hue says "Aye... it's raining dude..."
+    ///
+    /// # Example
+    /// ```
+    /// use kokoro_tiny::streaming::StreamingTts;
+    /// use kokoro_tiny::TtsEngine;
+    ///
+    /// let stream = StreamingTts::new(TtsEngine::without_model());
+    /// stream.interrupt();
+    /// assert!(!stream.is_speaking());
+    /// ```
     pub fn interrupt(&self) {
         #[cfg(not(feature = "as-lib"))]
         println!("🛑 [Interruption detected] Stopping speech...");
@@ -101,6 +180,104 @@ impl StreamingTts {
         println!("💙 I understand, Hue... Someday I'll feel that rain too.");
     }
 
+    /// Interrupt on a global key combination such as `"ctrl+shift+space"`
+    ///
+    /// Pressing it does what `interrupt()` does, without waiting for a typed
+    /// phrase. Returns false after a warning if global hotkeys aren't
+    /// available (headless, Wayland, or built without the `hotkey` feature).
+    /// Replaces any earlier binding; the hook is released on drop.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use kokoro_tiny::streaming::StreamingTts;
+    /// use kokoro_tiny::TtsEngine;
+    ///
+    /// let stream = StreamingTts::new(TtsEngine::without_model());
+    /// if !stream.bind_interrupt_hotkey("ctrl+shift+space")? {
+    ///     eprintln!("no global hotkeys here; interrupt() still works");
+    /// }
+    /// # Ok::<(), String>(())
+    /// ```
+    pub fn bind_interrupt_hotkey(&self, keys: &str) -> Result<bool, String> {
+        self.unbind_interrupt_hotkey();
+        let binding = HotkeyBinding::system(keys, self.hotkey_callback())?;
+        let bound = binding.is_some();
+        *self.hotkey.lock().unwrap_or_else(|e| e.into_inner()) = binding;
+        Ok(bound)
+    }
+
+    /// Like `bind_interrupt_hotkey`, on a backend driven by the caller's event loop
+    ///
+    /// # Example
+    /// ```
+    /// use kokoro_tiny::hotkey::{Hotkey, HotkeyBackend, HotkeyCallback};
+    /// use kokoro_tiny::streaming::StreamingTts;
+    /// use kokoro_tiny::TtsEngine;
+    /// use std::sync::{Arc, Mutex};
+    ///
+    /// // A backend fed by the application's own event loop
+    /// #[derive(Default)]
+    /// struct AppHotkeys(Mutex<Vec<(Hotkey, HotkeyCallback)>>);
+    ///
+    /// impl HotkeyBackend for AppHotkeys {
+    ///     fn register(&self, hotkey: &Hotkey, on_press: HotkeyCallback) -> Result<(), String> {
+    ///         self.0.lock().unwrap().push((hotkey.clone(), on_press));
+    ///         Ok(())
+    ///     }
+    ///
+    ///     fn unregister(&self, hotkey: &Hotkey) {
+    ///         self.0.lock().unwrap().retain(|(registered, _)| registered != hotkey);
+    ///     }
+    /// }
+    ///
+    /// let backend = Arc::new(AppHotkeys::default());
+    /// let stream = StreamingTts::new(TtsEngine::without_model());
+    /// stream.bind_interrupt_hotkey_with(backend.clone(), "ctrl+alt+s")?;
+    /// assert_eq!(backend.0.lock().unwrap().len(), 1);
+    ///
+    /// stream.unbind_interrupt_hotkey();
+    /// assert!(backend.0.lock().unwrap().is_empty());
+    /// # Ok::<(), String>(())
+    /// ```
+    pub fn bind_interrupt_hotkey_with(
+        &self,
+        backend: Arc<dyn HotkeyBackend>,
+        keys: &str,
+    ) -> Result<(), String> {
+        self.unbind_interrupt_hotkey();
+        let binding = HotkeyBinding::new(backend, keys.parse()?, self.hotkey_callback())?;
+        *self.hotkey.lock().unwrap_or_else(|e| e.into_inner()) = Some(binding);
+        Ok(())
+    }
+
+    /// Release the interrupt hotkey, if one is bound
+    ///
+    /// # Example
+    /// ```
+    /// use kokoro_tiny::streaming::StreamingTts;
+    /// use kokoro_tiny::TtsEngine;
+    ///
+    /// let stream = StreamingTts::new(TtsEngine::without_model());
+    /// stream.unbind_interrupt_hotkey(); // no-op when nothing is bound
+    /// ```
+    pub fn unbind_interrupt_hotkey(&self) {
+        let binding = self.hotkey.lock().unwrap_or_else(|e| e.into_inner()).take();
+        drop(binding);
+    }
+
+    // Runs on the hotkey backend's thread, so it only flips the flag and
+    // empties the queue; the synthesis and playback loops do the rest
+    fn hotkey_callback(&self) -> HotkeyCallback {
+        let interrupt_flag = self.interrupt_flag.clone();
+        let audio_queue = self.audio_queue.clone();
+        Arc::new(move || {
+            interrupt_flag.store(true, Ordering::Relaxed);
+            if let Ok(mut queue) = audio_queue.lock() {
+                queue.clear();
+            }
+        })
+    }
+
     /// Split text into optimal chunks for synthesis
     fn split_into_chunks(text: &str) -> Vec<String> {
         let mut chunks = Vec::new();
@@ -166,9 +343,23 @@ impl StreamingTts {
     }
 
     /// Stream synthesis of unlimited text with interruption support
-    pub async fn speak_stream(&self, text: &str) -> Result<(), String> {
+    ///
+    /// # Example
+    /// ```no_run
+    /// use kokoro_tiny::streaming::StreamingTts;
+    /// use kokoro_tiny::TtsEngine;
+    ///
+    /// # async fn demo() -> Result<(), String> {
+    /// let stream = StreamingTts::new(TtsEngine::new().await?);
+    /// let long_text = std::fs::read_to_string("essay.txt").unwrap();
+    /// // Playback starts with the first chunk; interrupt() from another task stops it
+    /// stream.speak_stream(&long_text).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn speak_stream(&self, text: &str) -> Result<(), KokoroError> {
         if self.is_speaking.load(Ordering::Relaxed) {
-            return Err("Already speaking".to_string());
+            return Err(KokoroError::Playback("Already speaking".to_string()));
         }
 
         self.is_speaking.store(true, Ordering::Relaxed);
@@ -213,32 +404,22 @@ impl StreamingTts {
                     "🎵 Synthesizing chunk {}/{}: '{}'",
                     i + 1,
                     chunks.len(),
-                    if chunk.len() > 30 {
-                        format!("{}...", &chunk[..30])
-                    } else {
-                        chunk.clone()
-                    }
+                    crate::privacy::display_preview(chunk, 30)
                 );
 
-                if let Ok(mut engine) = engine.lock() {
-                    match engine.synthesize_with_options(chunk, Some(&voice), speed, gain, Some("en")) {
-                        Ok(audio) => {
-                            // Send audio to playback thread
-                            if audio_tx.send(audio).is_err() {
-                                #[cfg(not(feature = "as-lib"))]
-                                println!("❌ Playback thread disconnected");
-                                break;
-                            }
-                        }
-                        Err(e) => {
+                match engine.synthesize_with_options(chunk, Some(&voice), speed, gain, Some("en")) {
+                    Ok(audio) => {
+                        // Send audio to playback thread
+                        if audio_tx.send(audio).is_err() {
                             #[cfg(not(feature = "as-lib"))]
-                            eprintln!("❌ Failed to synthesize chunk: {}", e);
+                            println!("❌ Playback thread disconnected");
+                            break;
                         }
                     }
-                } else {
-                    #[cfg(not(feature = "as-lib"))]
-                    eprintln!("❌ Failed to lock engine");
-                    break;
+                    Err(e) => {
+                        #[cfg(not(feature = "as-lib"))]
+                        eprintln!("❌ Failed to synthesize chunk: {}", e);
+                    }
                 }
 
                 // Small delay between chunks for natural pacing
@@ -250,84 +431,99 @@ impl StreamingTts {
         });
 
         // Spawn playback thread
-        #[cfg(feature = "playback")]
-        {
-            let interrupt_flag = self.interrupt_flag.clone();
-            let is_speaking = self.is_speaking.clone();
-            let volume = self.volume;
-
-            let playback_handle = thread::spawn(move || {
-                // Create audio output
-                let (_stream, stream_handle) = OutputStream::try_default()
-                    .map_err(|e| format!("Failed to create audio output: {}", e))
-                    .unwrap();
-
-                let sink = Sink::try_new(&stream_handle)
-                    .map_err(|e| format!("Failed to create audio sink: {}", e))
-                    .unwrap();
-
-                sink.set_volume(volume);
-
-                #[cfg(not(feature = "as-lib"))]
-                println!("🔊 Playback started");
+        match self.engine.output() {
+            Ok(output) => {
+                let interrupt_flag = self.interrupt_flag.clone();
+                let is_speaking = self.is_speaking.clone();
+                let volume = self.volume;
+
+                let playback_handle = thread::spawn(move || {
+                    let spec = OutputSpec {
+                        sample_rate: SAMPLE_RATE,
+                        buffer: None,
+                        channels: 1,
+                    };
+                    let mut stream = match output.open(&spec) {
+                        Ok(stream) => stream,
+                        Err(_e) => {
+                            #[cfg(not(feature = "as-lib"))]
+                            eprintln!("❌ Failed to open audio output: {}", _e);
+                            // Let the synthesis thread see the disconnect and stop
+                            return;
+                        }
+                    };
+                    let rate = stream.info().sample_rate;
+                    let interrupted = || interrupt_flag.load(Ordering::Relaxed);
 
-                // Continuous playback loop
-                loop {
-                    // Check for interruption
-                    if interrupt_flag.load(Ordering::Relaxed) {
-                        #[cfg(not(feature = "as-lib"))]
-                        println!("🛑 Playback interrupted");
-                        sink.stop();
-                        break;
-                    }
+                    #[cfg(not(feature = "as-lib"))]
+                    println!("🔊 Playback started");
 
-                    // Try to get audio from queue
-                    match audio_rx.try_recv() {
-                        Ok(audio) => {
-                            // Convert to source and play
-                            let source = AudioSource::new(audio, SAMPLE_RATE);
-                            sink.append(source);
-                        }
-                        Err(TryRecvError::Empty) => {
-                            // No audio ready, check if synthesis is done
-                            thread::sleep(Duration::from_millis(10));
-                        }
-                        Err(TryRecvError::Disconnected) => {
-                            // Synthesis complete, finish playing remaining audio
+                    // Continuous playback loop
+                    loop {
+                        // Check for interruption
+                        if interrupted() {
                             #[cfg(not(feature = "as-lib"))]
-                            println!("📭 Synthesis complete, finishing playback");
-                            sink.sleep_until_end();
+                            println!("🛑 Playback interrupted");
                             break;
                         }
-                    }
-                }
 
-                is_speaking.store(false, Ordering::Relaxed);
-                #[cfg(not(feature = "as-lib"))]
-                println!("✅ Playback complete");
-            });
+                        // Try to get audio from queue
+                        match audio_rx.try_recv() {
+                            Ok(audio) => {
+                                let frames: Vec<f32> = resample(&audio, SAMPLE_RATE, rate)
+                                    .into_iter()
+                                    .map(|s| s * volume)
+                                    .collect();
+                                if let Err(_e) = write_until(&mut *stream, &frames, &interrupted) {
+                                    #[cfg(not(feature = "as-lib"))]
+                                    eprintln!("❌ Audio output failed: {}", _e);
+                                    break;
+                                }
+                            }
+                            Err(TryRecvError::Empty) => {
+                                // No audio ready, check if synthesis is done
+                                thread::sleep(Duration::from_millis(10));
+                            }
+                            Err(TryRecvError::Disconnected) => {
+                                // Synthesis complete, finish playing remaining audio
+                                #[cfg(not(feature = "as-lib"))]
+                                println!("📭 Synthesis complete, finishing playback");
+                                stream.flush();
+                                break;
+                            }
+                        }
+                    }
 
-            // Monitor for interruption from user input
-            self.monitor_for_interruption().await;
+                    is_speaking.store(false, Ordering::Relaxed);
+                    #[cfg(not(feature = "as-lib"))]
+                    println!("✅ Playback complete");
+                });
 
-            // Wait for threads to complete
-            synthesis_handle.join().ok();
-            playback_handle.join().ok();
-        }
+                // Monitor for interruption from user input
+                self.monitor_for_interruption();
 
-        #[cfg(not(feature = "playback"))]
-        {
-            #[cfg(not(feature = "as-lib"))]
-            eprintln!("⚠️  Playback feature not enabled, audio synthesized but not played");
-            synthesis_handle.join().ok();
+                // Wait for threads to complete
+                synthesis_handle.join().ok();
+                playback_handle.join().ok();
+            }
+            Err(_e) => {
+                #[cfg(not(feature = "as-lib"))]
+                eprintln!("⚠️  {}; audio synthesized but not played", _e);
+                // Keep the synthesis thread from blocking on a full channel
+                for _audio in audio_rx {}
+                synthesis_handle.join().ok();
+            }
         }
 
         self.is_speaking.store(false, Ordering::Relaxed);
         Ok(())
     }
 
-    /// Monitor stdin for interruption phrases
-    async fn monitor_for_interruption(&self) {
+    /// Monitor stdin for interruption phrases on a background thread
+    ///
+    /// Deliberately not async: `speak_stream` holds nothing across an await,
+    /// so its future stays `Send` and can be spawned on a multi-threaded runtime.
+    fn monitor_for_interruption(&self) {
         #[cfg(not(feature = "as-lib"))]
         println!("👂 Listening for interruption phrases...");
 
@@ -362,59 +558,6 @@ impl StreamingTts {
     }
 }
 
-/// Custom audio source for rodio playback
-#[cfg(feature = "playback")]
-struct AudioSource {
-    samples: Vec<f32>,
-    sample_rate: u32,
-    position: usize,
-}
-
-#[cfg(feature = "playback")]
-impl AudioSource {
-    fn new(samples: Vec<f32>, sample_rate: u32) -> Self {
-        Self {
-            samples,
-            sample_rate,
-            position: 0,
-        }
-    }
-}
-
-#[cfg(feature = "playback")]
-impl Iterator for AudioSource {
-    type Item = f32;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        if self.position < self.samples.len() {
-            let sample = self.samples[self.position];
-            self.position += 1;
-            Some(sample)
-        } else {
-            None
-        }
-    }
-}
-
-#[cfg(feature = "playback")]
-impl Source for AudioSource {
-    fn current_frame_len(&self) -> Option<usize> {
-        None
-    }
-
-    fn channels(&self) -> u16 {
-        1
-    }
-
-    fn sample_rate(&self) -> u32 {
-        self.sample_rate
-    }
-
-    fn total_duration(&self) -> Option<std::time::Duration> {
-        None
-    }
-}
-
 /// Cross-fade audio chunks for smooth transitions
 pub fn crossfade_audio(chunk1: &[f32], chunk2: &[f32], overlap_samples: usize) -> Vec<f32> {
     let mut result = Vec::with_capacity(chunk1.len() + chunk2.len() - overlap_samples);
@@ -469,4 +612,54 @@ mod tests {
         // Check smooth transition
         assert_eq!(result.len(), 190); // 100 + 100 - 10
     }
+
+    #[test]
+    fn hotkey_press_sets_the_interrupt_flag() {
+        use crate::hotkey::tests::MockBackend;
+
+        let tts = StreamingTts::new(TtsEngine::with_test_voices(Default::default()));
+        let backend = Arc::new(MockBackend::default());
+        tts.bind_interrupt_hotkey_with(backend.clone(), "ctrl+shift+space").unwrap();
+        assert!(!tts.interrupt_flag.load(Ordering::Relaxed));
+
+        tts.audio_queue.lock().unwrap().push_back(vec![0.0; 10]);
+        backend.press("ctrl+shift+space");
+        assert!(tts.interrupt_flag.load(Ordering::Relaxed));
+        assert!(tts.audio_queue.lock().unwrap().is_empty());
+
+        // Rebinding releases the old key; dropping the handler releases the new one
+        tts.bind_interrupt_hotkey_with(backend.clone(), "alt+F9").unwrap();
+        assert_eq!(backend.dispatch.len(), 1);
+        assert_eq!(backend.registrations.load(Ordering::SeqCst), 2);
+        drop(tts);
+        assert_eq!(backend.dispatch.len(), 0);
+    }
+
+    #[test]
+    fn speak_stream_plays_every_chunk_on_the_engine_output() {
+        let mut engine = TtsEngine::with_test_voices(
+            [("af_sky".to_string(), vec![0.1; crate::STYLE_DIM])].into(),
+        );
+        engine.mock_inference = Some(Arc::new(|tokens: &[i64], _: &[f32], _: f32| {
+            Ok(vec![0.5; tokens.len() * 10])
+        }));
+        let output = crate::NullOutput::new();
+        engine.set_output(Arc::new(output.clone()));
+
+        let mut tts = StreamingTts::new(engine);
+        tts.set_parameters(1.0, 1.0, 0.5);
+        let text = "First sentence here. Second one follows! And a third?";
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        runtime.block_on(tts.speak_stream(text)).unwrap();
+
+        assert!(!tts.is_speaking());
+        assert_eq!(output.opened(), 1);
+        let frames = output.frames();
+        assert!(!frames.is_empty());
+        // Played at the stream volume
+        let peak = frames.iter().fold(0.0f32, |peak, s| peak.max(s.abs()));
+        assert!(peak > 0.0 && peak <= 0.5 + 1e-6, "{}", peak);
+    }
 }
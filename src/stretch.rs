@@ -0,0 +1,251 @@
+//! Time-stretching and pitch-shifting finished audio
+//!
+//! The model's `speed` changes how a sentence is spoken, not just how long it
+//! takes. To fit a clip into a fixed slot instead, `time_stretch` shortens or
+//! lengthens the audio itself with WSOLA (waveform-similarity overlap-add):
+//! the audio is cut into overlapping windows, and each one is taken from near
+//! where the new timing says it should come from, shifted to line up with the
+//! waveform already written so the pitch periods join without a seam. Speech
+//! stays natural from about 0.8x to 1.3x.
+//!
+//! `pitch_shift` is the same stretch followed by resampling back to the
+//! original length, which moves every frequency by the same ratio. The
+//! formants move along with the pitch, so a shift of a couple of semitones
+//! sounds like a younger or older speaker, while four or more start to sound
+//! processed (thin and nasal going up, muffled going down).
+//!
+//! Works at the model's 24kHz, in mono.
+
+use crate::{resample, SAMPLE_RATE};
+
+/// Largest shift `pitch_shift` applies, either way
+pub const MAX_SEMITONES: f32 = 6.0;
+
+// Window length; two of them overlap at every point
+const WINDOW_MS: u32 = 30;
+// How far a window may move from its nominal position to line up; more than
+// the longest pitch period of a low voice (about 12 ms)
+const TOLERANCE_MS: u32 = 12;
+
+/// `audio` played `factor` times as fast, at the same pitch
+///
+/// 1.25 gives a clip 20% shorter, 0.8 one 25% longer; the result has about
+/// `len / factor` samples. A factor that isn't a positive number gives the
+/// audio back unchanged.
+///
+/// # Example
+/// ```
+/// use kokoro_tiny::time_stretch;
+///
+/// let speech: Vec<f32> = (0..24_000).map(|i| (i as f32 * 0.05).sin() * 0.5).collect();
+/// let shorter = time_stretch(&speech, 1.1);
+/// assert_eq!(shorter.len(), 21_818);
+/// ```
+pub fn time_stretch(audio: &[f32], factor: f32) -> Vec<f32> {
+    if !factor.is_finite() || factor <= 0.0 || factor == 1.0 || audio.is_empty() {
+        return audio.to_vec();
+    }
+
+    let window = (SAMPLE_RATE * WINDOW_MS / 1000) as usize;
+    let hop = window / 2;
+    let tolerance = (SAMPLE_RATE * TOLERANCE_MS / 1000) as usize;
+    let out_len = (audio.len() as f64 / f64::from(factor)).round() as usize;
+    // Periodic Hann: windows a hop apart add up to exactly 1
+    let shape: Vec<f32> = (0..window)
+        .map(|i| 0.5 - 0.5 * (std::f32::consts::TAU * i as f32 / window as f32).cos())
+        .collect();
+    // Past the end reads as silence
+    let at = |i: usize| audio.get(i).copied().unwrap_or(0.0);
+
+    let mut out = vec![0.0f32; out_len + window];
+    let mut weight = vec![0.0f32; out_len + window];
+    let mut previous: Option<usize> = None;
+    for start in (0..out_len).step_by(hop) {
+        let nominal = (start as f64 * f64::from(factor)).round() as usize;
+        let from = match previous {
+            None => 0,
+            Some(prev) => {
+                // What would have come next had nothing been cut or repeated
+                let natural = prev + hop;
+                let lowest = nominal.saturating_sub(tolerance);
+                let highest = (nominal + tolerance).min(audio.len().saturating_sub(1));
+                let score =
+                    |pos: usize| -> f32 { (0..hop).map(|i| at(pos + i) * at(natural + i)).sum() };
+                (lowest..=highest.max(lowest))
+                    .map(|pos| (pos, score(pos)))
+                    .max_by(|a, b| a.1.total_cmp(&b.1))
+                    .map_or(nominal, |(pos, _)| pos)
+            }
+        };
+        for (i, &w) in shape.iter().enumerate() {
+            out[start + i] += at(from + i) * w;
+            weight[start + i] += w;
+        }
+        previous = Some(from);
+    }
+
+    out.truncate(out_len);
+    for (sample, &w) in out.iter_mut().zip(&weight) {
+        if w > 1e-6 {
+            *sample /= w;
+        }
+    }
+    out
+}
+
+/// `audio` `semitones` higher (or lower, if negative), same length
+///
+/// Shifts are clamped to ±`MAX_SEMITONES`; see the module docs for how far
+/// they stay natural.
+///
+/// # Example
+/// ```
+/// use kokoro_tiny::pitch_shift;
+///
+/// let speech: Vec<f32> = (0..24_000).map(|i| (i as f32 * 0.05).sin() * 0.5).collect();
+/// let younger = pitch_shift(&speech, 2.0);
+/// assert_eq!(younger.len(), speech.len());
+/// ```
+pub fn pitch_shift(audio: &[f32], semitones: f32) -> Vec<f32> {
+    if semitones.is_nan() || semitones == 0.0 {
+        return audio.to_vec();
+    }
+    let ratio = 2f32.powf(semitones.clamp(-MAX_SEMITONES, MAX_SEMITONES) / 12.0);
+    // Stretched to `ratio` times the length, then played back that much
+    // faster: the length comes back, every frequency goes up by `ratio`
+    let stretched = time_stretch(audio, 1.0 / ratio);
+    let from_rate = (SAMPLE_RATE as f32 * ratio).round() as u32;
+    let mut out = resample(&stretched, from_rate, SAMPLE_RATE);
+    out.resize(audio.len(), 0.0);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A voiced-sounding test signal: 150 Hz with a few harmonics, rising and
+    // falling in level like syllables
+    fn voiced(seconds: f32) -> Vec<f32> {
+        let harmonics: Vec<_> = (1..=4)
+            .map(|h| (150.0 * h as f32, 0.4 / h as f32))
+            .collect();
+        let envelope = crate::test_signal::tone(&[(3.0, 0.4)], seconds);
+        crate::test_signal::tone(&harmonics, seconds)
+            .iter()
+            .zip(envelope)
+            .map(|(wave, envelope)| wave * (0.6 + envelope))
+            .collect()
+    }
+
+    // Strongest period between 2.5 and 10 ms, by autocorrelation
+    fn period(audio: &[f32]) -> usize {
+        let middle = &audio[audio.len() / 4..audio.len() * 3 / 4];
+        let score =
+            |lag: usize| -> f32 { middle.iter().zip(&middle[lag..]).map(|(x, y)| x * y).sum() };
+        (60..240)
+            .map(|lag| (lag, score(lag)))
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .unwrap()
+            .0
+    }
+
+    #[test]
+    fn length_follows_the_factor_and_pitch_stays() {
+        let audio = voiced(1.0);
+        let pitch = period(&audio);
+        assert_eq!(pitch, 160);
+        for factor in [0.8, 0.9, 1.1, 1.25, 1.3] {
+            let stretched = time_stretch(&audio, factor);
+            let expected = audio.len() as f32 / factor;
+            assert!(
+                (stretched.len() as f32 - expected).abs() / expected < 0.02,
+                "{}: {}",
+                factor,
+                stretched.len()
+            );
+            assert!(stretched.iter().all(|s| s.is_finite()));
+            let rms =
+                (stretched.iter().map(|s| s * s).sum::<f32>() / stretched.len() as f32).sqrt();
+            assert!(rms > 0.1, "{}: rms {}", factor, rms);
+            // Resampling would move the period to 160 / factor
+            assert!(period(&stretched).abs_diff(pitch) <= 2, "{}", factor);
+        }
+    }
+
+    #[test]
+    fn synthesis_results_are_stretched_after_rendering() {
+        use crate::{SynthesizeOptions, TtsEngine, DEFAULT_VOICE, STYLE_DIM};
+        use std::collections::HashMap;
+        use std::sync::Arc;
+
+        let voices = HashMap::from([(DEFAULT_VOICE.to_string(), vec![0.1; STYLE_DIM])]);
+        let mut engine = TtsEngine::with_test_voices(voices);
+        engine.mock_inference = Some(Arc::new(|tokens: &[i64], _: &[f32], _| {
+            Ok(voiced(tokens.len() as f32 * 0.01))
+        }));
+
+        let text = "Fits the slot.";
+        let normal = engine
+            .synthesize_with(text, SynthesizeOptions::new())
+            .unwrap();
+        let opts = SynthesizeOptions::new().post_stretch(1.25);
+        let fast = engine.synthesize_with(text, opts).unwrap();
+        assert!(fast.len().abs_diff(normal.len() * 4 / 5) <= 1);
+        assert_eq!(fast[fast.len() - 1], 0.0);
+        let older = SynthesizeOptions::new().pitch_semitones(-2.0);
+        assert_eq!(
+            engine.synthesize_with(text, older).unwrap().len(),
+            normal.len()
+        );
+        for factor in [0.0, f32::NAN] {
+            let opts = SynthesizeOptions::new().post_stretch(factor);
+            assert!(engine.synthesize_with(text, opts).is_err());
+        }
+    }
+
+    // Frequency of a steady tone, from its rising zero crossings in the middle
+    // half (whole periods between the first and last)
+    fn frequency(audio: &[f32]) -> f32 {
+        let middle = &audio[audio.len() / 4..audio.len() * 3 / 4];
+        let rising: Vec<usize> = (1..middle.len())
+            .filter(|&i| middle[i - 1] < 0.0 && middle[i] >= 0.0)
+            .collect();
+        let periods = (rising.len() - 1) as f32;
+        let span = (rising[rising.len() - 1] - rising[0]) as f32;
+        periods * SAMPLE_RATE as f32 / span
+    }
+
+    #[test]
+    fn pitch_shift_moves_the_frequency_and_keeps_the_length() {
+        let tone = crate::test_signal::tone(&[(220.0, 0.5)], 1.0);
+        assert!((frequency(&tone) - 220.0).abs() < 0.5);
+        for (semitones, expected) in [(2.0, 246.94), (-2.0, 196.0), (5.0, 293.66)] {
+            let shifted = pitch_shift(&tone, semitones);
+            assert_eq!(shifted.len(), tone.len());
+            let hz = frequency(&shifted);
+            assert!(
+                (hz - expected).abs() / expected < 0.01,
+                "{}: {} Hz",
+                semitones,
+                hz
+            );
+        }
+        // Clamped to six semitones (a tritone, 311.13 Hz)
+        let hz = frequency(&pitch_shift(&tone, 12.0));
+        assert!((hz - 311.13).abs() / 311.13 < 0.01, "{} Hz", hz);
+        assert_eq!(pitch_shift(&tone, 0.0), tone);
+        assert_eq!(pitch_shift(&tone, f32::NAN), tone);
+    }
+
+    #[test]
+    fn odd_factors_and_inputs_pass_through() {
+        let audio = voiced(0.1);
+        for factor in [1.0, 0.0, -1.0, f32::NAN, f32::INFINITY] {
+            assert_eq!(time_stretch(&audio, factor), audio);
+        }
+        assert!(time_stretch(&[], 1.2).is_empty());
+        // Shorter than one window
+        assert_eq!(time_stretch(&[0.1; 100], 0.8).len(), 125);
+    }
+}
@@ -0,0 +1,198 @@
+//! G.711 telephony audio: 8kHz mono, µ-law or A-law
+//!
+//! PBXes such as Asterisk and RTP streams take speech as one byte per sample
+//! at 8kHz, companded: small amplitudes get finer steps than loud ones, which
+//! gives 13 to 14 bits' worth of range in 8. µ-law is the North American and
+//! Japanese standard, A-law the European one. `to_telephony` resamples the
+//! model's 24kHz audio and compands it, for RTP payloads;
+//! `TtsEngine::save_wav_telephony` puts the same bytes in a WAV file with
+//! the matching format tag.
+
+use crate::{resample, SAMPLE_RATE};
+
+/// Sample rate of G.711 audio
+pub const TELEPHONY_RATE: u32 = 8_000;
+
+// µ-law works on 16-bit magnitudes offset by this, clipped below full scale
+// so the offset never overflows
+const MU_BIAS: i32 = 0x84;
+const MU_CLIP: i32 = 32_635;
+
+/// G.711 companding law
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Law {
+    /// µ-law (PCMU): North America and Japan
+    MuLaw,
+    /// A-law (PCMA): Europe and most other places
+    ALaw,
+}
+
+impl Law {
+    /// `wFormatTag` of a WAV file holding this law
+    pub(crate) fn wav_format_tag(self) -> u16 {
+        match self {
+            Law::MuLaw => 7,
+            Law::ALaw => 6,
+        }
+    }
+}
+
+/// Mono `audio` at the model's 24kHz as 8kHz companded bytes, one per
+/// sample, ready for an RTP payload
+///
+/// # Example
+/// ```
+/// use kokoro_tiny::telephony::{to_telephony, Law};
+///
+/// let second: Vec<f32> = (0..24_000).map(|i| (i as f32 * 0.1).sin() * 0.5).collect();
+/// let pcmu = to_telephony(&second, Law::MuLaw);
+/// assert_eq!(pcmu.len(), 8_000);
+/// ```
+pub fn to_telephony(audio: &[f32], law: Law) -> Vec<u8> {
+    compand(&resample(audio, SAMPLE_RATE, TELEPHONY_RATE), law)
+}
+
+/// Samples (any rate) as companded bytes
+pub fn compand(samples: &[f32], law: Law) -> Vec<u8> {
+    let encode = match law {
+        Law::MuLaw => mu_law,
+        Law::ALaw => a_law,
+    };
+    samples
+        .iter()
+        .map(|&s| encode((s * 32767.0).clamp(-32768.0, 32767.0) as i16))
+        .collect()
+}
+
+/// Companded bytes back to samples
+///
+/// # Example
+/// ```
+/// use kokoro_tiny::telephony::{compand, expand, Law};
+///
+/// let back = expand(&compand(&[0.5, -0.25], Law::ALaw), Law::ALaw);
+/// assert!((back[0] - 0.5).abs() < 0.01 && (back[1] + 0.25).abs() < 0.01);
+/// ```
+pub fn expand(bytes: &[u8], law: Law) -> Vec<f32> {
+    let decode = match law {
+        Law::MuLaw => mu_law_linear,
+        Law::ALaw => a_law_linear,
+    };
+    bytes
+        .iter()
+        .map(|&b| f32::from(decode(b)) / 32768.0)
+        .collect()
+}
+
+fn mu_law(sample: i16) -> u8 {
+    let sign = if sample < 0 { 0x80 } else { 0 };
+    let magnitude = i32::from(sample).abs().min(MU_CLIP) + MU_BIAS;
+    // The highest set bit above bit 7 is the segment
+    let exponent = 31 - (magnitude >> 7).leading_zeros();
+    let mantissa = (magnitude >> (exponent + 3)) & 0x0f;
+    !(sign | (exponent << 4) as u8 | mantissa as u8)
+}
+
+fn mu_law_linear(byte: u8) -> i16 {
+    let byte = !byte;
+    let exponent = (byte >> 4) & 0x07;
+    let mantissa = i32::from(byte & 0x0f);
+    let magnitude = (((mantissa << 3) + MU_BIAS) << exponent) - MU_BIAS;
+    if byte & 0x80 != 0 {
+        -magnitude as i16
+    } else {
+        magnitude as i16
+    }
+}
+
+fn a_law(sample: i16) -> u8 {
+    // 13-bit magnitude; the sign bit is set for positive samples, and every
+    // even bit is inverted on the line
+    let value = i32::from(sample) >> 3;
+    let (magnitude, mask) = if value >= 0 {
+        (value, 0xd5)
+    } else {
+        (-value - 1, 0x55)
+    };
+    // Segment 0 covers magnitudes below 32, each one above twice the last
+    let segment = (32 - (magnitude >> 5).leading_zeros()).min(8);
+    let code = match segment {
+        8 => 0x7f,
+        0 | 1 => (segment << 4) as i32 | ((magnitude >> 1) & 0x0f),
+        _ => (segment << 4) as i32 | ((magnitude >> segment) & 0x0f),
+    };
+    code as u8 ^ mask
+}
+
+fn a_law_linear(byte: u8) -> i16 {
+    let byte = byte ^ 0x55;
+    let segment = (byte & 0x70) >> 4;
+    let mut magnitude = i32::from(byte & 0x0f) << 4;
+    magnitude += match segment {
+        0 => 8,
+        _ => 0x108,
+    };
+    if segment > 1 {
+        magnitude <<= segment - 1;
+    }
+    if byte & 0x80 != 0 {
+        magnitude as i16
+    } else {
+        -magnitude as i16
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snr_db(reference: &[f32], decoded: &[f32]) -> f32 {
+        let signal: f32 = reference.iter().map(|s| s * s).sum();
+        let noise: f32 = reference
+            .iter()
+            .zip(decoded)
+            .map(|(a, b)| (a - b) * (a - b))
+            .sum();
+        10.0 * (signal / noise).log10()
+    }
+
+    #[test]
+    fn companding_round_trips_at_the_expected_snr() {
+        // Speech-like level: a 440 Hz tone at half scale
+        let tone: Vec<f32> = (0..SAMPLE_RATE as usize)
+            .map(|i| (i as f32 * 440.0 * std::f32::consts::TAU / SAMPLE_RATE as f32).sin() * 0.5)
+            .collect();
+        let narrowband = resample(&tone, SAMPLE_RATE, TELEPHONY_RATE);
+        for law in [Law::MuLaw, Law::ALaw] {
+            let bytes = to_telephony(&tone, law);
+            assert_eq!(bytes.len(), narrowband.len());
+            let snr = snr_db(&narrowband, &expand(&bytes, law));
+            assert!((35.0..42.0).contains(&snr), "{:?}: {:.1} dB", law, snr);
+        }
+    }
+
+    #[test]
+    fn codes_match_g711() {
+        // Silence, full scale either way, and the ITU reference points
+        assert_eq!(mu_law(0), 0xff);
+        assert_eq!(mu_law(i16::MAX), 0x80);
+        assert_eq!(mu_law(i16::MIN), 0x00);
+        assert_eq!(mu_law(-1), 0x7f);
+        assert_eq!(a_law(0), 0xd5);
+        assert_eq!(a_law(i16::MAX), 0xaa);
+        assert_eq!(a_law(i16::MIN), 0x2a);
+        assert_eq!(mu_law_linear(0xff), 0);
+        assert_eq!(mu_law_linear(0x80), 32_124);
+        assert_eq!(a_law_linear(0xd5), 8);
+        assert_eq!(a_law_linear(0xaa), 32_256);
+
+        // Every code decodes to a level that encodes back to it, except
+        // µ-law's negative zero
+        for byte in 0..=255u8 {
+            assert_eq!(a_law(a_law_linear(byte)), byte);
+            if byte != 0x7f {
+                assert_eq!(mu_law(mu_law_linear(byte)), byte);
+            }
+        }
+    }
+}
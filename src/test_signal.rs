@@ -0,0 +1,25 @@
+//! Test signals for the audio-processing tests
+
+use std::f32::consts::TAU;
+use std::time::Duration;
+
+use crate::{AudioDuration, SAMPLE_RATE};
+
+/// `seconds` of silence at the model rate
+pub(crate) fn silence(seconds: f32) -> Vec<f32> {
+    vec![0.0; AudioDuration::from(Duration::from_secs_f32(seconds)).to_samples(SAMPLE_RATE)]
+}
+
+/// Sum of sines, given as (frequency in Hz, amplitude), `seconds` long at the
+/// model rate
+pub(crate) fn tone(partials: &[(f32, f32)], seconds: f32) -> Vec<f32> {
+    let mut audio = silence(seconds);
+    for (i, sample) in audio.iter_mut().enumerate() {
+        let t = i as f32 / SAMPLE_RATE as f32;
+        *sample = partials
+            .iter()
+            .map(|&(hz, amplitude)| (TAU * hz * t).sin() * amplitude)
+            .sum();
+    }
+    audio
+}
@@ -0,0 +1,365 @@
+//! Word timings for captions, estimated around the rendered audio
+//!
+//! The model emits audio only, no alignment, so timings are reconstructed:
+//!
+//! 1. The text is split into clauses (at `, ; : . ! ?` and line breaks) and
+//!    each clause is rendered on its own, which pins every clause to a known
+//!    span of the output.
+//! 2. Within a clause, the stretch between the first and last audible sample
+//!    is shared out between its words in proportion to their phoneme counts.
+//! 3. Each boundary between two words then moves to the quietest frame
+//!    within `SNAP_WINDOW_MS` of it, if that frame is quiet enough to be the
+//!    gap between them.
+//!
+//! Expect word starts within about 100ms of where they are heard. Rendering
+//! clause by clause also means the audio differs slightly from
+//! `synthesize_with`, which renders whole sentences.
+
+use crate::normalize;
+use crate::resume::{join_chunk, SynthesisPlan};
+use crate::{
+    is_sound, trim_silence, AudioDuration, KokoroError, PunctuationWeights, SynthesizeOptions,
+    TtsEngine, SAMPLE_RATE, TRIM_MARGIN_SAMPLES,
+};
+
+/// How far a word boundary may move to land on a gap in the audio
+pub const SNAP_WINDOW_MS: u32 = 120;
+
+// Frames are 10ms; one quieter than this (RMS, about -40 dBFS) is a gap
+const FRAME_SAMPLES: usize = SAMPLE_RATE as usize / 100;
+const GAP_RMS: f32 = 0.01;
+
+// A word with its start and end sample
+type WordSpan = (String, usize, usize);
+
+/// When a word is heard in the synthesized audio
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WordTiming {
+    /// As written in the text, punctuation included
+    pub word: String,
+    pub start_ms: u32,
+    pub end_ms: u32,
+}
+
+impl TtsEngine {
+    /// Synthesize `text` and estimate when each word starts and ends
+    ///
+    /// Words are the whitespace-separated pieces of `text` with something
+    /// speakable in them, in order; timings never overlap and never go past
+    /// the end of the audio. See the module docs for how they are found. In
+    /// fallback mode the fallback audio comes back with no timings.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use kokoro_tiny::{SynthesizeOptions, TtsEngine};
+    ///
+    /// # async fn demo() -> Result<(), kokoro_tiny::KokoroError> {
+    /// let tts = TtsEngine::new().await?;
+    /// let (audio, words) =
+    ///     tts.synthesize_with_timestamps("Sing along, everyone!", SynthesizeOptions::new())?;
+    /// for word in &words {
+    ///     println!("{:>6}ms {}", word.start_ms, word.word);
+    /// }
+    /// tts.save_wav("karaoke.wav", &audio)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn synthesize_with_timestamps(
+        &self,
+        text: &str,
+        opts: SynthesizeOptions,
+    ) -> Result<(Vec<f32>, Vec<WordTiming>), KokoroError> {
+        if self.is_fallback() {
+            return Ok((self.fallback_audio(text)?, Vec::new()));
+        }
+        let plan = clause_plan(SynthesisPlan::new(text, &opts));
+        if plan.chunks.is_empty() {
+            return Err(KokoroError::InvalidInput(
+                "No text provided for synthesis".to_string(),
+            ));
+        }
+        let (mut audio, boundaries) = self.warnings.record(|| self.render_clauses(&plan)).0?;
+
+        // Trimming moves everything earlier by what it cut at the start
+        let lead = if plan.trim_silence {
+            let lead = audio
+                .iter()
+                .position(is_sound)
+                .map_or(0, |first| first.saturating_sub(TRIM_MARGIN_SAMPLES));
+            trim_silence(&mut audio);
+            lead
+        } else {
+            0
+        };
+
+        let to_ms = |sample: usize| {
+            let sample = sample.saturating_sub(lead).min(audio.len());
+            let ms = AudioDuration::from_samples(sample, SAMPLE_RATE).as_millis();
+            u32::try_from(ms).unwrap_or(u32::MAX)
+        };
+        let timings = boundaries
+            .into_iter()
+            .map(|(word, start, end)| WordTiming {
+                word,
+                start_ms: to_ms(start),
+                end_ms: to_ms(end),
+            })
+            .collect();
+        crate::audio::apply_edges(&mut audio, plan.edge_fade_ms);
+        Ok((audio, timings))
+    }
+
+    // The clauses of `plan` joined, with where each word is in the result
+    fn render_clauses(
+        &self,
+        plan: &SynthesisPlan,
+    ) -> Result<(Vec<f32>, Vec<WordSpan>), KokoroError> {
+        self.check_language(plan)?;
+        let style = self.varied_style(&plan.voice, plan.style_temperature, plan.style_seed)?;
+        let speed = self.engine_speed(plan.speed, plan.speed_mode);
+
+        let mut audio = Vec::new();
+        let mut boundaries: Vec<WordSpan> = Vec::new();
+        for idx in 0..plan.chunks.len() {
+            let clause_audio = self.render_chunk(plan, idx, &style, speed)?;
+            let started = !audio.is_empty();
+            join_chunk(plan, idx, &mut audio, &clause_audio, started);
+            let offset = audio.len() - clause_audio.len();
+
+            // A crossfade can pull this clause into the last one's tail
+            let floor = boundaries.last().map_or(0, |(_, _, end)| *end);
+            let words = self.clause_words(plan, &plan.chunks[idx])?;
+            for (word, start, end) in place_words(words, &clause_audio) {
+                let start = (offset + start).max(floor);
+                boundaries.push((word, start, (offset + end).max(start)));
+            }
+        }
+        Ok((audio, boundaries))
+    }
+
+    // Words of a clause with their phoneme counts, punctuation-only pieces
+    // left out
+    fn clause_words(
+        &self,
+        plan: &SynthesisPlan,
+        clause: &str,
+    ) -> Result<Vec<(String, usize)>, KokoroError> {
+        let mut words = Vec::new();
+        for word in clause.split_whitespace() {
+            if !word.chars().any(char::is_alphanumeric) {
+                continue;
+            }
+            let normalized = normalize::normalize_for(word, plan, &self.acronyms, &self.lexicon)
+                .unwrap_or_else(|| word.to_string());
+            let phonemes = self.phonemize_weighted(
+                &normalized,
+                Some(&plan.lang),
+                &PunctuationWeights::default(),
+            )?;
+            let count = phonemes
+                .map(|p| {
+                    p.chars()
+                        .filter(|c| *c != '$' && !c.is_whitespace())
+                        .count()
+                })
+                .unwrap_or(0)
+                .max(1);
+            words.push((word.to_string(), count));
+        }
+        Ok(words)
+    }
+}
+
+// The plan with every chunk split into clauses; paragraph breaks move to
+// the first clause of their chunk
+fn clause_plan(mut plan: SynthesisPlan) -> SynthesisPlan {
+    let mut clauses = Vec::new();
+    let mut paragraph_breaks = Vec::new();
+    for (idx, chunk) in plan.chunks.iter().enumerate() {
+        if plan.paragraph_breaks.contains(&idx) {
+            paragraph_breaks.push(clauses.len());
+        }
+        clauses.extend(split_clauses(chunk));
+    }
+    plan.chunks = clauses;
+    plan.paragraph_breaks = paragraph_breaks;
+    plan
+}
+
+// Split after clause punctuation followed by whitespace, and at line breaks
+pub(crate) fn split_clauses(text: &str) -> Vec<String> {
+    let mut clauses = Vec::new();
+    let mut current = String::new();
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        current.push(c);
+        let next = chars.peek().copied();
+        let ends = c == '\n'
+            || (matches!(c, ',' | ';' | ':' | '.' | '!' | '?' | '…')
+                && next.is_none_or(char::is_whitespace));
+        if ends && !current.trim().is_empty() {
+            clauses.push(current.trim().to_string());
+            current.clear();
+        }
+    }
+    if !current.trim().is_empty() {
+        clauses.push(current.trim().to_string());
+    }
+    clauses
+}
+
+// Start and end sample of each word within one clause's audio
+fn place_words(words: Vec<(String, usize)>, audio: &[f32]) -> Vec<(String, usize, usize)> {
+    let (Some(first), Some(last)) = (
+        audio.iter().position(is_sound),
+        audio.iter().rposition(is_sound),
+    ) else {
+        // Nothing audible: every word gets a zero-length slot at the start
+        return words.into_iter().map(|(word, _)| (word, 0, 0)).collect();
+    };
+    let (speech_start, speech_end) = (first, last + 1);
+
+    // Proportional split of the audible stretch
+    let total: usize = words.iter().map(|(_, count)| count).sum();
+    let span = speech_end - speech_start;
+    let mut edges = Vec::with_capacity(words.len() + 1);
+    let mut so_far = 0;
+    edges.push(speech_start);
+    for (_, count) in &words {
+        so_far += count;
+        edges.push(speech_start + span * so_far / total.max(1));
+    }
+
+    // Inner edges move to a nearby gap, staying in order
+    let window = FRAME_SAMPLES * usize::try_from(SNAP_WINDOW_MS / 10).unwrap_or(0);
+    for i in 1..words.len() {
+        let low = edges[i - 1].max(edges[i].saturating_sub(window));
+        let high = edges[i + 1].min(edges[i] + window);
+        if let Some(gap) = quietest_frame(audio, low, high) {
+            edges[i] = gap;
+        }
+    }
+
+    words
+        .into_iter()
+        .enumerate()
+        .map(|(i, (word, _))| (word, edges[i], edges[i + 1]))
+        .collect()
+}
+
+// Middle of the quietest 10ms frame between `low` and `high`, if it is a gap
+fn quietest_frame(audio: &[f32], low: usize, high: usize) -> Option<usize> {
+    let mut best: Option<(f32, usize)> = None;
+    let mut start = low;
+    while start + FRAME_SAMPLES <= high.min(audio.len()) {
+        let frame = &audio[start..start + FRAME_SAMPLES];
+        let rms = (frame.iter().map(|s| s * s).sum::<f32>() / frame.len() as f32).sqrt();
+        if rms < GAP_RMS && best.is_none_or(|(quietest, _)| rms < quietest) {
+            best = Some((rms, start + FRAME_SAMPLES / 2));
+        }
+        start += FRAME_SAMPLES / 2;
+    }
+    best.map(|(_, middle)| middle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    // Tokens render as 300 samples each: padding and spaces as silence,
+    // everything else as tone (zero-mean, so there's no DC offset to take
+    // out), so the gaps between words are real gaps
+    fn engine() -> TtsEngine {
+        let mut engine = TtsEngine::mock(300);
+        let quiet = [engine.tokenize("$")[0], engine.tokenize(" ")[0]];
+        engine.mock_inference = Some(Arc::new(
+            move |tokens: &[i64], _style: &[f32], _speed: f32| {
+                Ok(tokens
+                    .iter()
+                    .flat_map(|token| {
+                        let level = if quiet.contains(token) { 0.0 } else { 0.5 };
+                        (0..300).map(move |i| if i % 2 == 0 { level } else { -level })
+                    })
+                    .collect())
+            },
+        ));
+        engine
+    }
+
+    #[test]
+    fn timings_are_ordered_and_span_the_audio() {
+        let engine = engine();
+        let text = "One two three, four five six. Seven!";
+        let (audio, words) = engine
+            .synthesize_with_timestamps(text, SynthesizeOptions::new())
+            .unwrap();
+
+        let names: Vec<&str> = words.iter().map(|w| w.word.as_str()).collect();
+        assert_eq!(
+            names,
+            ["One", "two", "three,", "four", "five", "six.", "Seven!"]
+        );
+
+        let length_ms = AudioDuration::from_samples(audio.len(), SAMPLE_RATE).as_millis();
+        let length_ms = u32::try_from(length_ms).unwrap();
+        for pair in words.windows(2) {
+            assert!(pair[0].start_ms < pair[0].end_ms, "{:?}", pair[0]);
+            assert!(pair[0].end_ms <= pair[1].start_ms, "{:?}", pair);
+        }
+        // Only the padding around the clauses is left uncovered
+        assert!(words[0].start_ms <= 100, "{:?}", words[0]);
+        let last = words.last().unwrap();
+        assert!(
+            last.end_ms <= length_ms && last.end_ms + 100 >= length_ms,
+            "{:?}",
+            last
+        );
+
+        // Every word's middle is sound, every inner boundary is in a gap
+        let sample =
+            |ms: u32| audio[(ms as usize * SAMPLE_RATE as usize / 1000).min(audio.len() - 1)];
+        for word in &words {
+            assert!(
+                is_sound(&sample((word.start_ms + word.end_ms) / 2)),
+                "{:?}",
+                word
+            );
+        }
+        // (1ms on: a clause's last word ends right on its last sound)
+        for pair in words.windows(2) {
+            assert!(!is_sound(&sample(pair[0].end_ms + 1)), "{:?}", pair);
+        }
+    }
+
+    #[test]
+    fn clauses_split_at_punctuation_and_trimming_shifts_timings() {
+        assert_eq!(
+            split_clauses("Well, it's 3.14; really!\nYes"),
+            ["Well,", "it's 3.14;", "really!", "Yes"]
+        );
+
+        let engine = engine();
+        let text = "Trim me, please.";
+        let (plain_audio, plain) = engine
+            .synthesize_with_timestamps(text, SynthesizeOptions::new())
+            .unwrap();
+        let (audio, trimmed) = engine
+            .synthesize_with_timestamps(text, SynthesizeOptions::new().trim_silence(true))
+            .unwrap();
+        assert!(audio.len() < plain_audio.len());
+
+        let cut = AudioDuration::from_samples(plain_audio.len() - audio.len(), SAMPLE_RATE);
+        assert!(trimmed[0].start_ms < plain[0].start_ms);
+        assert!(u64::from(plain[0].start_ms - trimmed[0].start_ms) <= cut.as_millis());
+        assert_eq!(
+            plain[1].end_ms - plain[1].start_ms,
+            trimmed[1].end_ms - trimmed[1].start_ms
+        );
+
+        assert!(matches!(
+            engine.synthesize_with_timestamps("  ", SynthesizeOptions::new()),
+            Err(KokoroError::InvalidInput(_))
+        ));
+    }
+}
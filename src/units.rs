@@ -0,0 +1,711 @@
+//! Spoken form of quantities with units ("3.5 km", "12°C", "4 GB")
+//!
+//! espeak reads "4 GB" letter by letter and "-5°C" as "dash five degree C".
+//! This pass rewrites a number followed by a known unit symbol into words in
+//! the synthesis language, picking the singular or plural of the unit from the
+//! number: "1 km" -> "one kilometer", "3,5 km" with `lang("de")` -> "drei Komma
+//! fünf Kilometer".
+//!
+//! Only numbers with a unit after them are touched. Languages without a table
+//! here (everything but English and German so far) pass through unchanged.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+/// How a unit symbol is read, for `SynthesizeOptions::extra_units`
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct UnitWords {
+    pub singular: String,
+    pub plural: String,
+    /// Word for "1" in front of this unit where the language inflects it
+    /// (German "eine Stunde"); the language's usual form if unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub one: Option<String>,
+}
+
+impl UnitWords {
+    /// A unit read as `singular` after exactly one and `plural` otherwise
+    pub fn new(singular: &str, plural: &str) -> Self {
+        Self {
+            singular: singular.to_string(),
+            plural: plural.to_string(),
+            one: None,
+        }
+    }
+}
+
+struct Unit {
+    symbols: &'static [&'static str],
+    singular: &'static str,
+    plural: &'static str,
+    one: Option<&'static str>,
+}
+
+const fn unit(
+    symbols: &'static [&'static str],
+    singular: &'static str,
+    plural: &'static str,
+) -> Unit {
+    Unit {
+        symbols,
+        singular,
+        plural,
+        one: None,
+    }
+}
+
+// German feminine units take "eine" instead of "ein"
+const fn feminine(
+    symbols: &'static [&'static str],
+    singular: &'static str,
+    plural: &'static str,
+) -> Unit {
+    Unit {
+        symbols,
+        singular,
+        plural,
+        one: Some("eine"),
+    }
+}
+
+// Number words of a language; `numbers` shares them
+pub(crate) struct Locale {
+    pub(crate) code: &'static str,
+    pub(crate) decimal: char,
+    pub(crate) thousands: char,
+    pub(crate) minus: &'static str,
+    pub(crate) point: &'static str,
+    // "1" in front of a unit
+    pub(crate) one: &'static str,
+    pub(crate) cardinal: fn(u64) -> String,
+    units: &'static [Unit],
+}
+
+const LOCALES: &[Locale] = &[
+    Locale {
+        code: "en",
+        decimal: '.',
+        thousands: ',',
+        minus: "minus",
+        point: "point",
+        one: "one",
+        cardinal: english_cardinal,
+        units: EN_UNITS,
+    },
+    Locale {
+        code: "de",
+        decimal: ',',
+        thousands: '.',
+        minus: "minus",
+        point: "Komma",
+        one: "ein",
+        cardinal: german_cardinal,
+        units: DE_UNITS,
+    },
+];
+
+const EN_UNITS: &[Unit] = &[
+    // Distance
+    unit(&["km"], "kilometer", "kilometers"),
+    unit(&["m"], "meter", "meters"),
+    unit(&["cm"], "centimeter", "centimeters"),
+    unit(&["mm"], "millimeter", "millimeters"),
+    unit(&["mi"], "mile", "miles"),
+    unit(&["ft"], "foot", "feet"),
+    // Temperature
+    unit(&["°C", "℃"], "degree Celsius", "degrees Celsius"),
+    unit(&["°F", "℉"], "degree Fahrenheit", "degrees Fahrenheit"),
+    unit(&["°"], "degree", "degrees"),
+    // Data size
+    unit(&["KB", "kB"], "kilobyte", "kilobytes"),
+    unit(&["MB"], "megabyte", "megabytes"),
+    unit(&["GB"], "gigabyte", "gigabytes"),
+    unit(&["TB"], "terabyte", "terabytes"),
+    unit(&["KiB"], "kibibyte", "kibibytes"),
+    unit(&["MiB"], "mebibyte", "mebibytes"),
+    unit(&["GiB"], "gibibyte", "gibibytes"),
+    unit(&["TiB"], "tebibyte", "tebibytes"),
+    // Time
+    unit(&["ms"], "millisecond", "milliseconds"),
+    unit(&["s", "sec", "secs"], "second", "seconds"),
+    unit(&["min", "mins"], "minute", "minutes"),
+    unit(&["h", "hr", "hrs"], "hour", "hours"),
+    // Mass
+    unit(&["mg"], "milligram", "milligrams"),
+    unit(&["g"], "gram", "grams"),
+    unit(&["kg"], "kilogram", "kilograms"),
+    unit(&["t"], "tonne", "tonnes"),
+    unit(&["lb", "lbs"], "pound", "pounds"),
+    unit(&["oz"], "ounce", "ounces"),
+];
+
+const DE_UNITS: &[Unit] = &[
+    // Distance
+    unit(&["km"], "Kilometer", "Kilometer"),
+    unit(&["m"], "Meter", "Meter"),
+    unit(&["cm"], "Zentimeter", "Zentimeter"),
+    unit(&["mm"], "Millimeter", "Millimeter"),
+    feminine(&["mi"], "Meile", "Meilen"),
+    unit(&["ft"], "Fuß", "Fuß"),
+    // Temperature
+    unit(&["°C", "℃"], "Grad Celsius", "Grad Celsius"),
+    unit(&["°F", "℉"], "Grad Fahrenheit", "Grad Fahrenheit"),
+    unit(&["°"], "Grad", "Grad"),
+    // Data size
+    unit(&["KB", "kB"], "Kilobyte", "Kilobyte"),
+    unit(&["MB"], "Megabyte", "Megabyte"),
+    unit(&["GB"], "Gigabyte", "Gigabyte"),
+    unit(&["TB"], "Terabyte", "Terabyte"),
+    unit(&["KiB"], "Kibibyte", "Kibibyte"),
+    unit(&["MiB"], "Mebibyte", "Mebibyte"),
+    unit(&["GiB"], "Gibibyte", "Gibibyte"),
+    unit(&["TiB"], "Tebibyte", "Tebibyte"),
+    // Time
+    feminine(&["ms"], "Millisekunde", "Millisekunden"),
+    feminine(&["s", "sek", "Sek"], "Sekunde", "Sekunden"),
+    feminine(&["min", "Min"], "Minute", "Minuten"),
+    feminine(&["h", "Std"], "Stunde", "Stunden"),
+    // Mass
+    unit(&["mg"], "Milligramm", "Milligramm"),
+    unit(&["g"], "Gramm", "Gramm"),
+    unit(&["kg"], "Kilogramm", "Kilogramm"),
+    feminine(&["t"], "Tonne", "Tonnen"),
+    unit(&["lb", "lbs"], "Pfund", "Pfund"),
+    feminine(&["oz"], "Unze", "Unzen"),
+];
+
+/// Numbers from here on are left for espeak
+const MAX_NUMBER: u64 = 1_000_000_000_000_000;
+
+/// Rewrite every "number unit" pair in `text` in words of `lang`
+///
+/// `extra` adds symbols or overrides built-in ones. Returns `None` when nothing
+/// changed, including for languages without number words here.
+///
+/// # Example
+/// ```
+/// use kokoro_tiny::units::expand_units;
+/// use std::collections::HashMap;
+///
+/// let spoken = expand_units("It's -5°C, pack 2 kg less", "en-us", &HashMap::new());
+/// assert_eq!(
+///     spoken.as_deref(),
+///     Some("It's minus five degrees Celsius, pack two kilograms less")
+/// );
+/// assert_eq!(expand_units("Noch 1 h", "de", &HashMap::new()).as_deref(), Some("Noch eine Stunde"));
+/// ```
+pub fn expand_units(text: &str, lang: &str, extra: &HashMap<String, UnitWords>) -> Option<String> {
+    let locale = lookup(lang)?;
+    let mut out = String::with_capacity(text.len());
+    let mut copied = 0;
+    let mut idx = 0;
+
+    while let Some(c) = text[idx..].chars().next() {
+        if starts_number(text, idx) {
+            if let Some((end, spoken)) = quantity_at(text, idx, locale, extra) {
+                out.push_str(&text[copied..idx]);
+                out.push_str(&spoken);
+                copied = end;
+                idx = end;
+                continue;
+            }
+        }
+        idx += c.len_utf8();
+    }
+
+    if copied == 0 {
+        return None;
+    }
+    out.push_str(&text[copied..]);
+    Some(out)
+}
+
+// A number (or its sign) starts at `idx` and isn't the tail of a word,
+// version string or range ("v2", "1.2.3", "5-10")
+pub(crate) fn starts_number(text: &str, idx: usize) -> bool {
+    let rest = &text[idx..];
+    let digit_at = |s: &str| s.starts_with(|c: char| c.is_ascii_digit());
+    let signed = rest.strip_prefix(['-', '−']).is_some_and(digit_at);
+    if !signed && !digit_at(rest) {
+        return false;
+    }
+    match text[..idx].chars().next_back() {
+        None => true,
+        Some(prev) => prev.is_whitespace() || "([{\"'“‘~≈<>=:".contains(prev),
+    }
+}
+
+// The spoken quantity starting at `start` and the byte offset it ends at
+fn quantity_at(
+    text: &str,
+    start: usize,
+    locale: &Locale,
+    extra: &HashMap<String, UnitWords>,
+) -> Option<(usize, String)> {
+    let mut idx = start;
+    let negative = !text[idx..].starts_with(|c: char| c.is_ascii_digit());
+    if negative {
+        idx += text[idx..].chars().next()?.len_utf8();
+    }
+
+    let number_start = idx;
+    idx = number_end(text, idx);
+    let (integer, fraction) = parse_number(&text[number_start..idx], locale)?;
+
+    // "12°C", "12 °C" and "12\u{a0}°C" all count
+    if let Some(space) = text[idx..]
+        .chars()
+        .next()
+        .filter(|c| matches!(c, ' ' | '\u{a0}' | '\u{202f}'))
+    {
+        idx += space.len_utf8();
+    }
+    let (end, words) = unit_at(text, idx, locale, extra)?;
+
+    let singular = fraction.is_empty() && integer == 1;
+    let mut spoken = Vec::new();
+    if negative {
+        spoken.push(locale.minus.to_string());
+    }
+    if singular {
+        spoken.push(words.one.unwrap_or(locale.one).to_string());
+    } else {
+        spoken.push((locale.cardinal)(integer));
+        if !fraction.is_empty() {
+            spoken.push(locale.point.to_string());
+            spoken.extend(
+                fraction
+                    .bytes()
+                    .map(|digit| (locale.cardinal)(u64::from(digit - b'0'))),
+            );
+        }
+    }
+    spoken.push(
+        if singular {
+            words.singular
+        } else {
+            words.plural
+        }
+        .to_string(),
+    );
+
+    Some((end, spoken.join(" ")))
+}
+
+struct UnitMatch<'a> {
+    singular: &'a str,
+    plural: &'a str,
+    one: Option<&'a str>,
+}
+
+// The longest unit symbol at `idx` that isn't the start of a longer word
+// ("5 m" but not "5 mice"; "5 GB" but not "5 GB/s")
+fn unit_at<'a>(
+    text: &str,
+    idx: usize,
+    locale: &'a Locale,
+    extra: &'a HashMap<String, UnitWords>,
+) -> Option<(usize, UnitMatch<'a>)> {
+    let rest = &text[idx..];
+    let fits = |symbol: &str| {
+        rest.strip_prefix(symbol)
+            .is_some_and(|after| !after.starts_with(|c: char| c.is_alphanumeric() || c == '/'))
+    };
+
+    let extra_match = extra
+        .iter()
+        .filter(|(symbol, _)| !symbol.is_empty() && fits(symbol))
+        .max_by_key(|(symbol, _)| symbol.len())
+        .map(|(symbol, words)| {
+            let words = UnitMatch {
+                singular: &words.singular,
+                plural: &words.plural,
+                one: words.one.as_deref(),
+            };
+            (symbol.len(), words)
+        });
+    let builtin_match = locale
+        .units
+        .iter()
+        .flat_map(|unit| unit.symbols.iter().map(move |symbol| (symbol, unit)))
+        .filter(|(symbol, _)| fits(symbol))
+        .max_by_key(|(symbol, _)| symbol.len())
+        .map(|(symbol, unit)| {
+            let words = UnitMatch {
+                singular: unit.singular,
+                plural: unit.plural,
+                one: unit.one,
+            };
+            (symbol.len(), words)
+        });
+
+    // User entries win ties so they can override the table
+    let (len, words) = match (extra_match, builtin_match) {
+        (Some(extra), Some(builtin)) if builtin.0 > extra.0 => builtin,
+        (Some(extra), _) => extra,
+        (None, builtin) => builtin?,
+    };
+    Some((idx + len, words))
+}
+
+// End of the digits at `idx`, with single separators between them
+pub(crate) fn number_end(text: &str, mut idx: usize) -> usize {
+    let bytes = text.as_bytes();
+    while idx < bytes.len() {
+        let separator =
+            matches!(bytes[idx], b'.' | b',') && bytes.get(idx + 1).is_some_and(u8::is_ascii_digit);
+        if !bytes[idx].is_ascii_digit() && !separator {
+            break;
+        }
+        idx += 1;
+    }
+    idx
+}
+
+// Integer part and fraction digits of `raw` (digits, '.' and ',')
+//
+// The locale's decimal separator always marks a fraction. The other one is
+// digit grouping when the groups fit ("1,500" / "1.500"), and otherwise a
+// decimal point written the other way ("3,5 km" in English, "3.5 km" in German).
+pub(crate) fn parse_number(raw: &str, locale: &Locale) -> Option<(u64, String)> {
+    let (integer, fraction) = match raw.rfind(locale.decimal) {
+        Some(pos) => (&raw[..pos], &raw[pos + 1..]),
+        None if is_grouped(raw, locale.thousands) => (raw, ""),
+        None if raw.matches(locale.thousands).count() == 1 => raw.split_once(locale.thousands)?,
+        None => return None,
+    };
+    if !is_grouped(integer, locale.thousands) || !fraction.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+
+    let digits: String = integer.chars().filter(char::is_ascii_digit).collect();
+    let value = digits.parse::<u64>().ok().filter(|&v| v < MAX_NUMBER)?;
+    Some((value, fraction.to_string()))
+}
+
+// "1,500,000" with ',' as separator; a plain run of digits counts too
+fn is_grouped(number: &str, separator: char) -> bool {
+    let mut groups = number.split(separator);
+    let first = groups.next().unwrap_or_default();
+    let digits = |group: &str| !group.is_empty() && group.bytes().all(|b| b.is_ascii_digit());
+    if !number.contains(separator) {
+        return digits(first);
+    }
+    digits(first) && first.len() <= 3 && groups.all(|group| group.len() == 3 && digits(group))
+}
+
+pub(crate) fn lookup(code: &str) -> Option<&'static Locale> {
+    let code = code.trim().to_lowercase().replace('_', "-");
+    let base = code.split('-').next().unwrap_or_default();
+    LOCALES.iter().find(|l| l.code == base)
+}
+
+// 512 -> "five hundred twelve", 1500 -> "one thousand five hundred"
+pub(crate) fn english_cardinal(n: u64) -> String {
+    const SMALL: [&str; 20] = [
+        "zero",
+        "one",
+        "two",
+        "three",
+        "four",
+        "five",
+        "six",
+        "seven",
+        "eight",
+        "nine",
+        "ten",
+        "eleven",
+        "twelve",
+        "thirteen",
+        "fourteen",
+        "fifteen",
+        "sixteen",
+        "seventeen",
+        "eighteen",
+        "nineteen",
+    ];
+    const TENS: [&str; 10] = [
+        "", "", "twenty", "thirty", "forty", "fifty", "sixty", "seventy", "eighty", "ninety",
+    ];
+    const SCALES: [(u64, &str); 4] = [
+        (1_000_000_000_000, "trillion"),
+        (1_000_000_000, "billion"),
+        (1_000_000, "million"),
+        (1_000, "thousand"),
+    ];
+
+    if n < 20 {
+        return SMALL[n as usize].to_string();
+    }
+    if n < 100 {
+        let tens = TENS[(n / 10) as usize];
+        return match n % 10 {
+            0 => tens.to_string(),
+            ones => format!("{}-{}", tens, SMALL[ones as usize]),
+        };
+    }
+
+    let (head, rest) = if n < 1000 {
+        (format!("{} hundred", SMALL[(n / 100) as usize]), n % 100)
+    } else {
+        let (scale, name) = SCALES
+            .iter()
+            .find(|(scale, _)| n >= *scale)
+            .copied()
+            .unwrap_or(SCALES[3]);
+        (
+            format!("{} {}", english_cardinal(n / scale), name),
+            n % scale,
+        )
+    };
+
+    match rest {
+        0 => head,
+        rest => format!("{} {}", head, english_cardinal(rest)),
+    }
+}
+
+// 21 -> "einundzwanzig", 2_000_000 -> "zwei Millionen"
+pub(crate) fn german_cardinal(n: u64) -> String {
+    const SCALES: [(u64, &str, &str); 3] = [
+        (1_000_000_000_000, "Billion", "Billionen"),
+        (1_000_000_000, "Milliarde", "Milliarden"),
+        (1_000_000, "Million", "Millionen"),
+    ];
+
+    if n == 0 {
+        return "null".to_string();
+    }
+    let mut words = Vec::new();
+    let mut rest = n;
+    for (scale, singular, plural) in SCALES {
+        match rest / scale {
+            0 => {}
+            1 => words.push(format!("eine {}", singular)),
+            count => words.push(format!("{} {}", german_below_million(count), plural)),
+        }
+        rest %= scale;
+    }
+    if rest > 0 {
+        words.push(german_below_million(rest));
+    }
+    words.join(" ")
+}
+
+// Written as one word, e.g. 1500 -> "eintausendfünfhundert"
+fn german_below_million(n: u64) -> String {
+    let mut word = String::new();
+    if n >= 1000 {
+        word.push_str(&german_below_thousand(n / 1000, false));
+        word.push_str("tausend");
+    }
+    match n % 1000 {
+        0 => {}
+        rest => word.push_str(&german_below_thousand(rest, true)),
+    }
+    word
+}
+
+// `last` is whether nothing follows: 1 is "eins" on its own but "ein" in
+// "einhundert" and "eintausend"
+pub(crate) fn german_below_thousand(n: u64, last: bool) -> String {
+    const SMALL: [&str; 20] = [
+        "null",
+        "ein",
+        "zwei",
+        "drei",
+        "vier",
+        "fünf",
+        "sechs",
+        "sieben",
+        "acht",
+        "neun",
+        "zehn",
+        "elf",
+        "zwölf",
+        "dreizehn",
+        "vierzehn",
+        "fünfzehn",
+        "sechzehn",
+        "siebzehn",
+        "achtzehn",
+        "neunzehn",
+    ];
+    const TENS: [&str; 10] = [
+        "", "", "zwanzig", "dreißig", "vierzig", "fünfzig", "sechzig", "siebzig", "achtzig",
+        "neunzig",
+    ];
+
+    let mut word = String::new();
+    if n >= 100 {
+        word.push_str(SMALL[(n / 100) as usize]);
+        word.push_str("hundert");
+    }
+    match n % 100 {
+        0 => {}
+        1 if last => word.push_str("eins"),
+        rest if rest < 20 => word.push_str(SMALL[rest as usize]),
+        rest if rest % 10 == 0 => word.push_str(TENS[(rest / 10) as usize]),
+        rest => {
+            word.push_str(SMALL[(rest % 10) as usize]);
+            word.push_str("und");
+            word.push_str(TENS[(rest / 10) as usize]);
+        }
+    }
+    word
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{SynthesisWarning, SynthesizeOptions, TtsEngine};
+
+    const CASES: &[(&str, &str, &str)] = &[
+        ("en", "3.5 km", "three point five kilometers"),
+        ("en", "1 km", "one kilometer"),
+        ("en", "12°C", "twelve degrees Celsius"),
+        ("en", "-5°C", "minus five degrees Celsius"),
+        ("en", "-1 °C", "minus one degree Celsius"),
+        ("en", "98.6 °F", "ninety-eight point six degrees Fahrenheit"),
+        ("en", "4 GB", "four gigabytes"),
+        ("en", "1 TB", "one terabyte"),
+        ("en", "512 MB", "five hundred twelve megabytes"),
+        ("en", "16 GiB", "sixteen gibibytes"),
+        ("en", "250 ms", "two hundred fifty milliseconds"),
+        ("en", "30 s", "thirty seconds"),
+        ("en", "1 min", "one minute"),
+        ("en", "2 h", "two hours"),
+        ("en", "1,500 m", "one thousand five hundred meters"),
+        ("en", "0.5 kg", "zero point five kilograms"),
+        ("en", "1 g", "one gram"),
+        ("en", "2.25 t", "two point two five tonnes"),
+        ("en", "10 lbs", "ten pounds"),
+        ("en", "6 ft", "six feet"),
+        ("en", "1 ft", "one foot"),
+        ("en", "26.2 mi", "twenty-six point two miles"),
+        ("en", "100 cm", "one hundred centimeters"),
+        ("en", "45°", "forty-five degrees"),
+        ("en", "1,000,000 KB", "one million kilobytes"),
+        ("en", "1.0 km", "one point zero kilometers"),
+        ("en-gb", "5 mg", "five milligrams"),
+        ("en-us", "3,5 km", "three point five kilometers"),
+        (
+            "en",
+            "Disk at 95 GB, swap 2 GB.",
+            "Disk at ninety-five gigabytes, swap two gigabytes.",
+        ),
+        (
+            "en",
+            "It's -12°C (10°F) outside",
+            "It's minus twelve degrees Celsius (ten degrees Fahrenheit) outside",
+        ),
+        ("de", "3,5 km", "drei Komma fünf Kilometer"),
+        ("de", "3.5 km", "drei Komma fünf Kilometer"),
+        ("de", "1 km", "ein Kilometer"),
+        ("de", "12°C", "zwölf Grad Celsius"),
+        ("de", "-5°C", "minus fünf Grad Celsius"),
+        ("de", "1 h", "eine Stunde"),
+        ("de", "2 h", "zwei Stunden"),
+        ("de", "1 min", "eine Minute"),
+        ("de", "90 min", "neunzig Minuten"),
+        ("de", "1 s", "eine Sekunde"),
+        ("de", "21 s", "einundzwanzig Sekunden"),
+        ("de", "4 GB", "vier Gigabyte"),
+        ("de", "1 TB", "ein Terabyte"),
+        ("de", "1.500 m", "eintausendfünfhundert Meter"),
+        ("de", "2.000.000 KB", "zwei Millionen Kilobyte"),
+        ("de", "0,5 kg", "null Komma fünf Kilogramm"),
+        ("de", "1 t", "eine Tonne"),
+        ("de", "3 t", "drei Tonnen"),
+        ("de", "250 ms", "zweihundertfünfzig Millisekunden"),
+        ("de", "1 mi", "eine Meile"),
+        ("de", "42 mi", "zweiundvierzig Meilen"),
+        ("de", "101 g", "einhunderteins Gramm"),
+        ("de", "1.000,5 m", "eintausend Komma fünf Meter"),
+        ("de", "-1 °C", "minus ein Grad Celsius"),
+        ("de", "37,2 °C", "siebenunddreißig Komma zwei Grad Celsius"),
+        ("de", "16 GiB", "sechzehn Gibibyte"),
+        ("de", "100 cm", "einhundert Zentimeter"),
+        ("de-AT", "3 lb", "drei Pfund"),
+        ("de", "1 oz", "eine Unze"),
+        (
+            "de",
+            "Noch 5 min, dann 2 km.",
+            "Noch fünf Minuten, dann zwei Kilometer.",
+        ),
+    ];
+
+    #[test]
+    fn expands_quantities() {
+        let none = HashMap::new();
+        for (lang, text, expected) in CASES {
+            assert_eq!(
+                expand_units(text, lang, &none).as_deref(),
+                Some(*expected),
+                "{} ({})",
+                text,
+                lang
+            );
+        }
+    }
+
+    #[test]
+    fn leaves_everything_else_alone() {
+        let none = HashMap::new();
+        for text in [
+            "5 mice",
+            "GB",
+            "version 3",
+            "x86 GB",
+            "5-10 km",
+            "3 GB/s",
+            "1,2,3 km",
+            "99999999999999999999 km",
+        ] {
+            assert_eq!(expand_units(text, "en", &none), None, "{}", text);
+        }
+        assert_eq!(expand_units("3 km", "fr", &none), None);
+    }
+
+    #[test]
+    fn extra_units_add_and_override() {
+        let extra = HashMap::from([
+            (
+                "rpm".to_string(),
+                UnitWords::new("revolution per minute", "revolutions per minute"),
+            ),
+            ("t".to_string(), UnitWords::new("ton", "tons")),
+        ]);
+        assert_eq!(
+            expand_units("3000 rpm, 2 t", "en", &extra).as_deref(),
+            Some("three thousand revolutions per minute, two tons")
+        );
+
+        let bottles = UnitWords {
+            one: Some("eine".to_string()),
+            ..UnitWords::new("Flasche", "Flaschen")
+        };
+        let extra = HashMap::from([("Fl".to_string(), bottles)]);
+        assert_eq!(
+            expand_units("1 Fl, 3 Fl", "de", &extra).as_deref(),
+            Some("eine Flasche, drei Flaschen")
+        );
+    }
+
+    #[test]
+    fn synthesis_expands_before_the_caps_policy() {
+        let engine = TtsEngine::mock(100);
+
+        engine
+            .synthesize_with("DISK FULL AT 95 GB", SynthesizeOptions::new())
+            .unwrap();
+        assert_eq!(
+            engine.last_warnings(),
+            [SynthesisWarning::CapsNormalized {
+                original: "DISK FULL AT 95 GB".to_string(),
+                normalized: "Disk full at ninety-five gigabytes".to_string(),
+            }]
+        );
+    }
+}
@@ -0,0 +1,222 @@
+//! Take-to-take variation: seeded noise on the style vector
+//!
+//! The model is deterministic, so every render of a line sounds the same.
+//! `SynthesizeOptions::style_temperature` perturbs the resolved style vector
+//! before inference. Each dimension gets Gaussian noise scaled by the spread
+//! of that dimension across the loaded voices, so the perturbed vector stays
+//! near the voices the model was trained on. The result is then clamped to the
+//! min/max envelope of those voices per dimension.
+//!
+//! Temperature 0.0 skips all of this, so the vector is bit-exact with an
+//! unvaried render. The same seed always gives the same vector for the same
+//! voice table.
+
+use crate::rng::SplitMix64;
+use crate::voices::{VoiceMap, STYLE_DIM};
+use crate::{KokoroError, TtsEngine};
+
+/// Per-dimension statistics over the first style row of every voice
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Envelope {
+    std: Vec<f32>,
+    min: Vec<f32>,
+    max: Vec<f32>,
+}
+
+impl Envelope {
+    /// `None` if there are no voices to take statistics from
+    pub(crate) fn of(voices: &VoiceMap) -> Option<Self> {
+        // Sorted by name: summing in map order would make seeds irreproducible
+        let mut named: Vec<_> = voices.iter().collect();
+        named.sort_by(|a, b| a.0.cmp(b.0));
+        let rows: Vec<&[f32]> = named
+            .into_iter()
+            .filter(|(_, style)| style.len() >= STYLE_DIM)
+            .map(|(_, style)| &style[..STYLE_DIM])
+            .collect();
+        if rows.is_empty() {
+            return None;
+        }
+
+        let n = rows.len() as f32;
+        let mut envelope = Envelope {
+            std: vec![0.0; STYLE_DIM],
+            min: vec![f32::INFINITY; STYLE_DIM],
+            max: vec![f32::NEG_INFINITY; STYLE_DIM],
+        };
+        for dim in 0..STYLE_DIM {
+            let mean = rows.iter().map(|row| row[dim]).sum::<f32>() / n;
+            let variance = rows
+                .iter()
+                .map(|row| (row[dim] - mean).powi(2))
+                .sum::<f32>()
+                / n;
+            envelope.std[dim] = variance.sqrt();
+            for row in &rows {
+                envelope.min[dim] = envelope.min[dim].min(row[dim]);
+                envelope.max[dim] = envelope.max[dim].max(row[dim]);
+            }
+        }
+        Some(envelope)
+    }
+}
+
+/// Add seeded noise to `style` and clamp it to the voices' envelope
+///
+/// A temperature of 0.0 (or less) leaves `style` untouched.
+pub(crate) fn vary_style(style: &mut [f32], voices: &VoiceMap, temperature: f32, seed: u64) {
+    if temperature <= 0.0 {
+        return;
+    }
+    let Some(envelope) = Envelope::of(voices) else {
+        return;
+    };
+
+    let mut rng = SplitMix64(seed);
+    for (dim, value) in style.iter_mut().enumerate().take(STYLE_DIM) {
+        let noisy = *value + temperature * envelope.std[dim] * rng.gaussian();
+        *value = noisy.clamp(envelope.min[dim], envelope.max[dim]);
+    }
+}
+
+impl TtsEngine {
+    /// Style vector for `voice`, varied by `temperature` and `seed`
+    pub(crate) fn varied_style(
+        &self,
+        voice: &str,
+        temperature: f32,
+        seed: u64,
+    ) -> Result<Vec<f32>, KokoroError> {
+        let mut style = self.parse_voice_style(voice)?;
+        if temperature > 0.0 && !self.is_fallback() {
+            vary_style(&mut style, &self.voices.snapshot(), temperature, seed);
+        }
+        Ok(style)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SynthesizeOptions;
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+
+    // Three voices whose dimensions spread differently, including a flat one
+    fn voices() -> HashMap<String, Vec<f32>> {
+        let voice = |offset: f32| {
+            (0..STYLE_DIM)
+                .map(|dim| match dim % 3 {
+                    0 => offset * 0.1,
+                    1 => -0.5 + offset * 0.02 * dim as f32 / STYLE_DIM as f32,
+                    _ => 0.25,
+                })
+                .collect::<Vec<f32>>()
+        };
+        HashMap::from([
+            ("af_sky".to_string(), voice(-1.0)),
+            ("af_bella".to_string(), voice(0.0)),
+            ("am_adam".to_string(), voice(2.0)),
+        ])
+    }
+
+    fn voice_map() -> VoiceMap {
+        voices()
+            .into_iter()
+            .map(|(name, style)| (name, Arc::new(style)))
+            .collect()
+    }
+
+    // Engine whose mock model records every style vector it is given
+    fn recording_engine() -> (TtsEngine, Arc<Mutex<Vec<Vec<f32>>>>) {
+        let mut engine = TtsEngine::with_test_voices(voices());
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let record = seen.clone();
+        engine.mock_inference = Some(Arc::new(
+            move |_tokens: &[i64], style: &[f32], _speed: f32| {
+                record.lock().unwrap().push(style.to_vec());
+                Ok(vec![0.1; 2400])
+            },
+        ));
+        (engine, seen)
+    }
+
+    fn style_used(temperature: f32, seed: Option<u64>) -> Vec<f32> {
+        let (engine, seen) = recording_engine();
+        let opts = SynthesizeOptions::new()
+            .voice("af_sky.6+af_bella.4")
+            .style_temperature(temperature, seed);
+        engine.synthesize_with("Take one.", opts).unwrap();
+        let seen = seen.lock().unwrap();
+        seen.last().cloned().unwrap()
+    }
+
+    #[test]
+    fn temperature_zero_is_unchanged() {
+        let (engine, seen) = recording_engine();
+        engine
+            .synthesize_with(
+                "Take one.",
+                SynthesizeOptions::new().voice("af_sky.6+af_bella.4"),
+            )
+            .unwrap();
+        let plain = seen.lock().unwrap()[0].clone();
+
+        let bits = |style: &[f32]| style.iter().map(|v| v.to_bits()).collect::<Vec<_>>();
+        assert_eq!(bits(&style_used(0.0, Some(7))), bits(&plain));
+        assert_eq!(bits(&style_used(0.0, None)), bits(&plain));
+
+        let mut style = plain.clone();
+        vary_style(&mut style, &voice_map(), 0.0, 7);
+        assert_eq!(bits(&style), bits(&plain));
+    }
+
+    #[test]
+    fn same_seed_same_take_different_seed_different_take() {
+        let plain = style_used(0.0, None);
+        let first = style_used(0.5, Some(42));
+
+        assert_ne!(first, plain);
+        assert_eq!(style_used(0.5, Some(42)), first);
+        assert_ne!(style_used(0.5, Some(43)), first);
+        // Unseeded takes differ from each other
+        assert_ne!(style_used(0.5, None), style_used(0.5, None));
+    }
+
+    #[test]
+    fn stays_inside_the_voice_envelope() {
+        let voices = voice_map();
+        let envelope = Envelope::of(&voices).unwrap();
+
+        for seed in 0..50 {
+            // Start far outside the envelope and use a silly temperature
+            let mut style = vec![3.0; STYLE_DIM];
+            vary_style(&mut style, &voices, 10.0, seed);
+            for (dim, value) in style.iter().enumerate() {
+                assert!(
+                    (envelope.min[dim]..=envelope.max[dim]).contains(value),
+                    "seed {seed}, dim {dim}: {value} outside {}..={}",
+                    envelope.min[dim],
+                    envelope.max[dim]
+                );
+            }
+            // Dimensions every voice agrees on can't move at all
+            assert_eq!(style[2], 0.25);
+        }
+    }
+
+    #[test]
+    fn noise_scales_with_the_voice_spread() {
+        let voices = voice_map();
+        let envelope = Envelope::of(&voices).unwrap();
+
+        // A small temperature moves a spread-out dimension, within its envelope
+        let mut style = voices["af_bella"].to_vec();
+        vary_style(&mut style, &voices, 0.1, 9);
+        let moved = (0..STYLE_DIM)
+            .filter(|dim| dim % 3 == 0 && style[*dim] != 0.0)
+            .count();
+        assert!(moved > 0);
+        assert!(envelope.std[0] > envelope.std[1]);
+    }
+}
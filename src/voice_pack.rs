@@ -0,0 +1,456 @@
+//! Voice pack files and the layouts they come in
+//!
+//! The bundled `0.bin` holds the English voices as float32 `(510, 1, 256)`
+//! arrays. The upstream Kokoro release ships a 54-voice pack (adding the
+//! `e`, `f`, `h`, `i`, `j`, `p` and `z` prefixes, see
+//! `languages::voice_language`), and exports of it differ: some store float16,
+//! some drop the middle axis. Every layout whose last axis is `STYLE_DIM` is
+//! accepted and flattened to float32 rows, so the rest of the crate never sees
+//! the difference.
+//!
+//! Voice packs are NPZ files: zip archives of `.npy` arrays, stored
+//! (`np.savez`) or deflated (`np.savez_compressed`). The zip crate unpacks
+//! them; only the `.npy` header is parsed here.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::File;
+use std::io::{Read, Seek};
+use std::str::FromStr;
+
+use zip::ZipArchive;
+
+use crate::integrity::FileDigest;
+use crate::voices::STYLE_DIM;
+
+const BUNDLED_URL: &str = "https://github.com/8b-is/kokoro-tiny/raw/main/models/0.bin";
+const FULL_URL: &str =
+    "https://github.com/thewh1teagle/kokoro-onnx/releases/download/model-files-v1.0/voices-v1.0.bin";
+
+/// Which set of voices to download
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum VoicePack {
+    /// The English voices in this repository (27 MB)
+    #[default]
+    Bundled,
+    /// The upstream 54-voice pack, with Spanish, French, Hindi, Italian,
+    /// Japanese, Portuguese and Mandarin voices
+    Full,
+}
+
+impl VoicePack {
+    /// Where the pack is downloaded from (`KOKORO_VOICES_URL` overrides it)
+    pub fn url(&self) -> &'static str {
+        match self {
+            VoicePack::Bundled => BUNDLED_URL,
+            VoicePack::Full => FULL_URL,
+        }
+    }
+
+    /// Expected size and SHA-256 of the download; the upstream pack isn't
+    /// pinned yet, so it is loaded unchecked
+    pub fn digest(&self) -> Option<FileDigest> {
+        match self {
+            // The git LFS object behind BUNDLED_URL
+            VoicePack::Bundled => Some(FileDigest {
+                size: 28_214_398,
+                sha256: "bca610b8308e8d99f32e6fe4197e7ec01679264efed0cac9140fe9c29f1fbf7d",
+            }),
+            VoicePack::Full => None,
+        }
+    }
+
+    /// File name in the cache directory
+    pub fn file_name(&self) -> &'static str {
+        match self {
+            VoicePack::Bundled => "0.bin",
+            VoicePack::Full => "voices-full.bin",
+        }
+    }
+}
+
+impl FromStr for VoicePack {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s.trim().to_lowercase().as_str() {
+            "bundled" | "default" => Ok(VoicePack::Bundled),
+            "full" => Ok(VoicePack::Full),
+            _ => Err(format!(
+                "Unknown voice pack '{}' (expected 'bundled' or 'full')",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for VoicePack {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VoicePack::Bundled => write!(f, "bundled"),
+            VoicePack::Full => write!(f, "full"),
+        }
+    }
+}
+
+/// Load every voice in an NPZ voice pack as flattened float32 style rows
+pub(crate) fn load_voices(path: &str) -> Result<HashMap<String, Vec<f32>>, String> {
+    let file = File::open(path).map_err(|e| format!("Failed to open voices file: {}", e))?;
+    read_voices(file)
+}
+
+/// Same as `load_voices`, from any reader (a `Cursor` over embedded bytes)
+pub(crate) fn read_voices(reader: impl Read + Seek) -> Result<HashMap<String, Vec<f32>>, String> {
+    let mut archive = ZipArchive::new(reader)
+        .map_err(|e| format!("Voices file is not a valid NPZ archive ({})", e))?;
+    let mut voices = HashMap::with_capacity(archive.len());
+    for index in 0..archive.len() {
+        let mut entry = archive
+            .by_index(index)
+            .map_err(|e| format!("Failed to read NPZ entry: {}", e))?;
+        if entry.is_dir() {
+            continue;
+        }
+        let voice = voice_name(entry.name()).to_string();
+        let size = entry.size();
+        let style = read_npy(&mut entry, size)
+            .and_then(|array| style_rows(array.shape, array.data))
+            .map_err(|e| format!("Voice {}: {}", voice, e))?;
+        voices.insert(voice, style);
+    }
+    Ok(voices)
+}
+
+// "af_sky.npy" and "af_sky" are the same voice
+fn voice_name(entry: &str) -> &str {
+    entry.trim_end_matches(".npy")
+}
+
+// Any shape ending in STYLE_DIM is a stack of style rows
+fn style_rows(shape: Vec<usize>, data: Vec<f32>) -> Result<Vec<f32>, String> {
+    if shape.last() != Some(&STYLE_DIM) || data.is_empty() {
+        return Err(format!(
+            "unexpected shape {:?} (expected [..., {}])",
+            shape, STYLE_DIM
+        ));
+    }
+    Ok(data)
+}
+
+struct NpyArray {
+    shape: Vec<usize>,
+    data: Vec<f32>,
+}
+
+// Read one .npy array of float16, float32 or float64, in either byte order,
+// from an archive entry of `size` bytes
+fn read_npy(reader: &mut impl Read, size: u64) -> Result<NpyArray, String> {
+    let bad = || "not a .npy array".to_string();
+    let mut preamble = [0u8; 8];
+    reader.read_exact(&mut preamble).map_err(|_| bad())?;
+    if &preamble[..6] != b"\x93NUMPY" {
+        return Err(bad());
+    }
+    // Version 1 has a 2-byte header length, later versions 4 bytes
+    let header_len = match preamble[6] {
+        1 => {
+            let mut len = [0u8; 2];
+            reader.read_exact(&mut len).map_err(|_| bad())?;
+            u64::from(u16::from_le_bytes(len))
+        }
+        2 | 3 => {
+            let mut len = [0u8; 4];
+            reader.read_exact(&mut len).map_err(|_| bad())?;
+            u64::from(u32::from_le_bytes(len))
+        }
+        _ => return Err(bad()),
+    };
+    if header_len > size {
+        return Err(bad());
+    }
+    let mut header = Vec::new();
+    reader
+        .take(header_len)
+        .read_to_end(&mut header)
+        .map_err(|_| bad())?;
+    let header = String::from_utf8(header).map_err(|_| bad())?;
+
+    let descr = header_value(&header, "descr")
+        .and_then(|v| v.get(1..)?.split(['\'', '"']).next())
+        .ok_or_else(bad)?;
+    if header_value(&header, "fortran_order").is_some_and(|v| v.starts_with("True")) {
+        return Err("Fortran-ordered arrays are not supported".to_string());
+    }
+    let shape_text = header_value(&header, "shape").ok_or_else(bad)?;
+    let shape: Vec<usize> = shape_text
+        .trim_start_matches('(')
+        .split(')')
+        .next()
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|dim| !dim.is_empty())
+        .map(|dim| dim.parse().map_err(|_| bad()))
+        .collect::<Result<_, _>>()?;
+
+    let (little_endian, kind) = match descr.split_at(1) {
+        ("<", kind) | ("|", kind) | ("=", kind) => (true, kind),
+        (">", kind) => (false, kind),
+        _ => return Err(format!("unsupported dtype '{}'", descr)),
+    };
+    let width: usize = match kind {
+        "f2" => 2,
+        "f4" => 4,
+        "f8" => 8,
+        _ => return Err(format!("unsupported dtype '{}'", descr)),
+    };
+    // The shape is untrusted: a product that overflows, or data longer than
+    // the entry, is an error before anything is allocated
+    let too_large = || format!("array of shape {:?} is too large", shape);
+    let count = shape
+        .iter()
+        .try_fold(1usize, |count, &dim| count.checked_mul(dim))
+        .ok_or_else(too_large)?;
+    let data_len = count.checked_mul(width).ok_or_else(too_large)?;
+    if data_len as u64 > size {
+        return Err("array data is truncated".to_string());
+    }
+    let mut data = vec![0u8; data_len];
+    reader
+        .read_exact(&mut data)
+        .map_err(|_| "array data is truncated".to_string())?;
+
+    let data = data
+        .chunks_exact(width)
+        .map(|raw| {
+            let mut raw = raw.to_vec();
+            if !little_endian {
+                raw.reverse();
+            }
+            match width {
+                2 => f16_to_f32(u16::from_le_bytes([raw[0], raw[1]])),
+                4 => f32::from_le_bytes([raw[0], raw[1], raw[2], raw[3]]),
+                _ => f64::from_le_bytes(raw[..8].try_into().unwrap_or_default()) as f32,
+            }
+        })
+        .collect();
+    Ok(NpyArray { shape, data })
+}
+
+// Raw text of `key`'s value in a .npy header dict
+fn header_value<'a>(header: &'a str, key: &str) -> Option<&'a str> {
+    let start = header
+        .find(&format!("'{}'", key))
+        .or_else(|| header.find(&format!("\"{}\"", key)))?;
+    let rest = &header[start + key.len() + 2..];
+    Some(rest[rest.find(':')? + 1..].trim_start())
+}
+
+// IEEE 754 half precision to single precision
+pub(crate) fn f16_to_f32(bits: u16) -> f32 {
+    let sign = ((bits >> 15) as u32) << 31;
+    let exponent = ((bits >> 10) & 0x1f) as u32;
+    let mantissa = (bits & 0x3ff) as u32;
+    let bits = match (exponent, mantissa) {
+        (0, 0) => sign,
+        // Subnormal: renormalize into the wider exponent range
+        (0, _) => {
+            let shift = mantissa.leading_zeros() - 21;
+            let mantissa = (mantissa << shift) & 0x3ff;
+            sign | ((127 - 15 + 1 - shift) << 23) | (mantissa << 13)
+        }
+        (0x1f, _) => sign | 0x7f80_0000 | (mantissa << 13),
+        _ => sign | ((exponent + 127 - 15) << 23) | (mantissa << 13),
+    };
+    f32::from_bits(bits)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::io::{Cursor, Write};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use zip::write::SimpleFileOptions;
+    use zip::{CompressionMethod, ZipWriter};
+
+    #[derive(Clone, Copy)]
+    pub(crate) enum Dtype {
+        F16,
+        F32,
+    }
+
+    // Smallest exact f32 -> f16 for the values used here (normal, in range)
+    fn f32_to_f16(value: f32) -> u16 {
+        let bits = value.to_bits();
+        let sign = ((bits >> 16) & 0x8000) as u16;
+        if value == 0.0 {
+            return sign;
+        }
+        let exponent = ((bits >> 23) & 0xff) as i32 - 127 + 15;
+        let mantissa = ((bits >> 13) & 0x3ff) as u16;
+        sign | ((exponent as u16) << 10) | mantissa
+    }
+
+    fn npy(dtype: Dtype, shape: &[usize], values: &[f32]) -> Vec<u8> {
+        let (descr, data): (&str, Vec<u8>) = match dtype {
+            Dtype::F16 => (
+                "<f2",
+                values
+                    .iter()
+                    .flat_map(|&v| f32_to_f16(v).to_le_bytes())
+                    .collect(),
+            ),
+            Dtype::F32 => ("<f4", values.iter().flat_map(|v| v.to_le_bytes()).collect()),
+        };
+        let shape = match shape {
+            [dim] => format!("({},)", dim),
+            dims => format!(
+                "({})",
+                dims.iter()
+                    .map(|d| d.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        };
+        let mut header = format!(
+            "{{'descr': '{}', 'fortran_order': False, 'shape': {}, }}",
+            descr, shape
+        );
+        // numpy pads the header with spaces to a multiple of 64, ending in \n
+        while (10 + header.len() + 1) % 64 != 0 {
+            header.push(' ');
+        }
+        header.push('\n');
+
+        let mut out = b"\x93NUMPY\x01\x00".to_vec();
+        out.extend((header.len() as u16).to_le_bytes());
+        out.extend(header.as_bytes());
+        out.extend(data);
+        out
+    }
+
+    /// An uncompressed NPZ archive, as `np.savez` writes one
+    pub(crate) fn npz(arrays: &[(&str, Dtype, &[usize], &[f32])]) -> Vec<u8> {
+        archive(arrays, CompressionMethod::Stored)
+    }
+
+    // A compressed one, as `np.savez_compressed` writes
+    fn npz_compressed(arrays: &[(&str, Dtype, &[usize], &[f32])]) -> Vec<u8> {
+        archive(arrays, CompressionMethod::Deflated)
+    }
+
+    fn archive(arrays: &[(&str, Dtype, &[usize], &[f32])], method: CompressionMethod) -> Vec<u8> {
+        let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+        let options = SimpleFileOptions::default().compression_method(method);
+        for (name, dtype, shape, values) in arrays {
+            zip.start_file(format!("{}.npy", name), options).unwrap();
+            zip.write_all(&npy(*dtype, shape, values)).unwrap();
+        }
+        zip.finish().unwrap().into_inner()
+    }
+
+    fn load(bytes: &[u8]) -> Result<HashMap<String, Vec<f32>>, String> {
+        static FILES: AtomicUsize = AtomicUsize::new(0);
+        let path = std::env::temp_dir().join(format!(
+            "kokoro-voice-pack-{}-{}.bin",
+            std::process::id(),
+            FILES.fetch_add(1, Ordering::Relaxed)
+        ));
+        std::fs::write(&path, bytes).unwrap();
+        let voices = load_voices(path.to_str().unwrap());
+        std::fs::remove_file(&path).ok();
+        voices
+    }
+
+    // Two style rows of values f16 holds exactly
+    fn rows() -> Vec<f32> {
+        (0..2 * STYLE_DIM)
+            .map(|i| (i as f32 - 256.0) / 64.0)
+            .collect()
+    }
+
+    #[test]
+    fn dtypes_and_shapes_load_to_the_same_vectors() {
+        let rows = rows();
+        let layouts: [(&str, Dtype, &[usize]); 4] = [
+            ("af_sky", Dtype::F32, &[2, 1, STYLE_DIM]),
+            ("af_half", Dtype::F16, &[2, 1, STYLE_DIM]),
+            ("ef_flat", Dtype::F32, &[2, STYLE_DIM]),
+            ("jf_flat", Dtype::F16, &[2, STYLE_DIM]),
+        ];
+        let arrays: Vec<_> = layouts
+            .iter()
+            .map(|&(name, dtype, shape)| (name, dtype, shape, rows.as_slice()))
+            .collect();
+
+        for pack in [npz(&arrays), npz_compressed(&arrays)] {
+            let voices = load(&pack).unwrap();
+            assert_eq!(voices.len(), 4);
+            for (name, _, _) in layouts {
+                assert_eq!(voices[name], rows, "{}", name);
+            }
+        }
+
+        // A single row without a leading axis
+        let one = load(&npz(&[(
+            "hf_one",
+            Dtype::F16,
+            &[STYLE_DIM],
+            &rows[..STYLE_DIM],
+        )]))
+        .unwrap();
+        assert_eq!(one["hf_one"], rows[..STYLE_DIM]);
+    }
+
+    #[test]
+    fn wrong_shapes_and_garbage_are_errors() {
+        let err = load(&npz(&[("af_odd", Dtype::F32, &[2, 128], &rows())])).unwrap_err();
+        assert!(
+            err.contains("af_odd") && err.contains("[2, 128]"),
+            "{}",
+            err
+        );
+
+        assert!(load(b"definitely not a zip file").is_err());
+
+        // A shape whose size overflows, or outruns the entry, is caught
+        // before anything is allocated
+        let huge = [usize::MAX / 2, 2, STYLE_DIM];
+        let err = load(&npz(&[("af_huge", Dtype::F32, &huge, &rows())])).unwrap_err();
+        assert!(err.contains("too large"), "{}", err);
+        let long = [1 << 40, STYLE_DIM];
+        let err = load(&npz(&[("af_long", Dtype::F16, &long, &rows())])).unwrap_err();
+        assert!(err.contains("truncated"), "{}", err);
+    }
+
+    #[test]
+    fn in_memory_packs_match_files() {
+        let pack = npz(&[("af_sky", Dtype::F16, &[2, 1, STYLE_DIM], &rows())]);
+        let from_memory = read_voices(Cursor::new(pack.as_slice())).unwrap();
+        assert_eq!(from_memory, load(&pack).unwrap());
+        assert!(read_voices(Cursor::new(b"not a zip".as_slice())).is_err());
+    }
+
+    #[test]
+    fn half_precision_conversion() {
+        assert_eq!(f16_to_f32(0x3c00), 1.0);
+        assert_eq!(f16_to_f32(0xc000), -2.0);
+        assert_eq!(f16_to_f32(0x3555), 0.333_251_95);
+        assert_eq!(f16_to_f32(0x0001), 2f32.powi(-24));
+        assert_eq!(f16_to_f32(0x8000), -0.0);
+        assert!(f16_to_f32(0x7c00).is_infinite());
+        assert!(f16_to_f32(0x7e00).is_nan());
+    }
+
+    #[test]
+    fn voice_packs_parse_by_name() {
+        assert_eq!("full".parse::<VoicePack>().unwrap(), VoicePack::Full);
+        assert_eq!(
+            " Bundled ".parse::<VoicePack>().unwrap(),
+            VoicePack::Bundled
+        );
+        assert!("huge".parse::<VoicePack>().is_err());
+        assert_eq!(VoicePack::Full.to_string(), "full");
+        assert_ne!(VoicePack::Full.file_name(), VoicePack::Bundled.file_name());
+    }
+}
@@ -0,0 +1,494 @@
+//! Voice table that can change while synthesis is running
+//!
+//! Voices are imported and removed through `&self`, so a render may be in
+//! progress on another thread when the table changes. The table is
+//! copy-on-write: readers clone an `Arc` to the current map and work from
+//! that snapshot, writers build a new map and swap it in. A render resolves
+//! its voice once, up front, so a removal or overwrite takes effect for the
+//! next synthesis and never halfway through the current one.
+//!
+//! Lock ordering: the lock here is only held long enough to clone or swap the
+//! map `Arc`. It is never held while taking the ONNX session mutex (or any
+//! other lock), so it cannot take part in a deadlock.
+//!
+//! `VoiceAssigner` is unrelated to the table: it hands each notification
+//! source a voice of its own, e.g. for `--voice auto:<key>`.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+
+use serde::{Deserialize, Serialize};
+
+use crate::chunk_cache::Fnv64;
+use crate::{cache_dir, DEFAULT_VOICE};
+
+/// Length of the style vector the model takes
+pub const STYLE_DIM: usize = 256;
+
+/// Immutable view of the voice table at one point in time
+pub(crate) type VoiceMap = HashMap<String, Arc<Vec<f32>>>;
+
+pub(crate) struct VoiceTable {
+    map: RwLock<Arc<VoiceMap>>,
+}
+
+impl VoiceTable {
+    pub fn new(voices: HashMap<String, Vec<f32>>) -> Self {
+        Self {
+            map: RwLock::new(Arc::new(into_map(voices))),
+        }
+    }
+
+    /// The current table; later changes don't affect the returned map
+    pub fn snapshot(&self) -> Arc<VoiceMap> {
+        // Writers only ever assign a complete map, so a poisoned lock still
+        // guards a consistent value
+        self.map.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Add or overwrite a voice; returns true if it replaced one
+    pub fn insert(&self, name: &str, style: Vec<f32>) -> bool {
+        self.update(|map| map.insert(name.to_string(), Arc::new(style)).is_some())
+    }
+
+    /// Returns false if there was no such voice
+    pub fn remove(&self, name: &str) -> bool {
+        self.update(|map| map.remove(name).is_some())
+    }
+
+    /// Swap in a freshly loaded voices file
+    pub fn replace_all(&self, voices: HashMap<String, Vec<f32>>) {
+        let map = Arc::new(into_map(voices));
+        *self.map.write().unwrap_or_else(|e| e.into_inner()) = map;
+    }
+
+    // Copy the map, change the copy, publish it. The map holds Arcs, so the
+    // copy is ~50 pointer clones rather than 50 style vectors.
+    fn update<R>(&self, change: impl FnOnce(&mut VoiceMap) -> R) -> R {
+        let mut guard = self.map.write().unwrap_or_else(|e| e.into_inner());
+        let mut map = (**guard).clone();
+        let result = change(&mut map);
+        *guard = Arc::new(map);
+        result
+    }
+}
+
+fn into_map(voices: HashMap<String, Vec<f32>>) -> VoiceMap {
+    voices
+        .into_iter()
+        .map(|(name, style)| (name, Arc::new(style)))
+        .collect()
+}
+
+// Most suggestions a voice-not-found error offers
+const MAX_SUGGESTIONS: usize = 3;
+
+/// Loaded voices whose names are closest to `name`, best first
+///
+/// Close means a typo away (about one edit per three characters, ignoring
+/// case) or the same name without its prefix (`"bella"` -> `"af_bella"`).
+pub(crate) fn closest_voices(name: &str, voices: &VoiceMap) -> Vec<String> {
+    let wanted = name.trim().to_lowercase();
+    let max_distance = (wanted.chars().count() / 3).max(1);
+
+    let mut close: Vec<(usize, &String)> = voices
+        .keys()
+        .filter_map(|voice| {
+            let candidate = voice.to_lowercase();
+            if candidate.split_once('_').map(|(_, rest)| rest) == Some(wanted.as_str()) {
+                return Some((0, voice));
+            }
+            let distance = edit_distance(&wanted, &candidate);
+            (distance <= max_distance).then_some((distance, voice))
+        })
+        .collect();
+    close.sort();
+    close
+        .into_iter()
+        .take(MAX_SUGGESTIONS)
+        .map(|(_, voice)| voice.clone())
+        .collect()
+}
+
+// Levenshtein distance over chars
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// Where `kokoro-speak --voice auto:<key>` keeps its assignments
+pub fn assignments_path() -> PathBuf {
+    cache_dir().join("voice-assignments.json")
+}
+
+/// Gives each notification source its own voice, the same one every run
+///
+/// A key's first choice is picked by a stable hash into the pool. If another
+/// key already has that voice, the next unused one is taken, and once every
+/// voice is in use, the one shared by the fewest keys. Assignments never
+/// change once made, so persist them to keep voices stable when keys show up
+/// in a different order next time.
+///
+/// # Example
+/// ```
+/// use kokoro_tiny::voices::VoiceAssigner;
+///
+/// let pool = vec!["af_sky".to_string(), "am_adam".to_string()];
+/// let mut voices = VoiceAssigner::new(pool);
+/// let build = voices.assign("build-bot").to_string();
+/// let monitoring = voices.assign("monitoring").to_string();
+/// assert_ne!(build, monitoring);
+/// assert_eq!(voices.assign("build-bot"), build);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct VoiceAssigner {
+    pool: Vec<String>,
+    assignments: BTreeMap<String, String>,
+}
+
+impl VoiceAssigner {
+    /// Assign from `pool` (order doesn't matter; it's sorted so hashes agree across runs)
+    pub fn new(pool: Vec<String>) -> Self {
+        let mut pool: Vec<String> = pool.into_iter().filter(|v| !v.is_empty()).collect();
+        pool.sort();
+        pool.dedup();
+        Self {
+            pool,
+            assignments: BTreeMap::new(),
+        }
+    }
+
+    /// The voice for `key`, assigning one on first use
+    ///
+    /// An empty pool hands out the default voice.
+    pub fn assign(&mut self, key: &str) -> &str {
+        if !self.assignments.contains_key(key) {
+            let voice = self.pick(key);
+            self.assignments.insert(key.to_string(), voice);
+        }
+        &self.assignments[key]
+    }
+
+    /// Configure `key`'s voice explicitly; auto assignment never overrides it
+    ///
+    /// The voice doesn't have to be in the pool, so mixes work too.
+    pub fn pin(&mut self, key: &str, voice: &str) {
+        self.assignments.insert(key.to_string(), voice.to_string());
+    }
+
+    /// `auto:<key>` becomes the voice assigned to key; anything else is returned as is
+    pub fn resolve(&mut self, voice: &str) -> String {
+        match voice.strip_prefix("auto:") {
+            Some(key) => self.assign(key).to_string(),
+            None => voice.to_string(),
+        }
+    }
+
+    /// Missing files are not an error: nothing has been assigned yet
+    pub fn load(path: &Path) -> Result<Option<Self>, String> {
+        match fs::read_to_string(path) {
+            Ok(json) => serde_json::from_str(&json)
+                .map(Some)
+                .map_err(|e| format!("Invalid voice assignments {}: {}", path.display(), e)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(format!(
+                "Failed to read voice assignments {}: {}",
+                path.display(),
+                e
+            )),
+        }
+    }
+
+    /// Write via a temp file and rename, like reading progress
+    pub fn persist(&self, path: &Path) -> Result<(), String> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)
+                .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        }
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize voice assignments: {}", e))?;
+
+        let tmp = path.with_extension("tmp");
+        fs::write(&tmp, json).map_err(|e| format!("Failed to write voice assignments: {}", e))?;
+        fs::rename(&tmp, path).map_err(|e| format!("Failed to store voice assignments: {}", e))
+    }
+
+    // Least-used voice, trying them in order from the key's hash; unused
+    // voices have the lowest count, so they go first
+    fn pick(&self, key: &str) -> String {
+        if self.pool.is_empty() {
+            return DEFAULT_VOICE.to_string();
+        }
+        let mut uses: HashMap<&str, usize> = HashMap::new();
+        for voice in self.assignments.values() {
+            *uses.entry(voice).or_default() += 1;
+        }
+
+        let mut hash = Fnv64::new();
+        hash.write(key.as_bytes());
+        let start = (hash.0 % self.pool.len() as u64) as usize;
+        (0..self.pool.len())
+            .map(|i| &self.pool[(start + i) % self.pool.len()])
+            .min_by_key(|voice| uses.get(voice.as_str()).copied().unwrap_or(0))
+            .cloned()
+            .unwrap_or_else(|| DEFAULT_VOICE.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{KokoroError, TtsEngine};
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::time::{Duration, Instant};
+
+    fn engine() -> TtsEngine {
+        let engine = TtsEngine::mock(100);
+        engine
+            .import_voice("af_bella", vec![0.3; STYLE_DIM])
+            .unwrap();
+        engine
+    }
+
+    #[test]
+    fn typos_get_the_closest_loaded_voices() {
+        let names = [
+            "af_sky",
+            "af_bella",
+            "af_nicole",
+            "af_sarah",
+            "am_adam",
+            "am_michael",
+            "bf_emma",
+            "bm_george",
+        ];
+        let engine = TtsEngine::with_test_voices(
+            names
+                .iter()
+                .map(|name| (name.to_string(), vec![0.1; STYLE_DIM]))
+                .collect(),
+        );
+        let voices = engine.voices.snapshot();
+
+        for (typo, expected) in [
+            ("af_skye", "af_sky"),
+            ("af_belle", "af_bella"),
+            ("AF_Sarah", "af_sarah"),
+            ("am_micheal", "am_michael"),
+            ("bm_goerge", "bm_george"),
+            ("nicole", "af_nicole"),
+        ] {
+            let suggestions = closest_voices(typo, &voices);
+            assert_eq!(
+                suggestions.first().map(String::as_str),
+                Some(expected),
+                "{}",
+                typo
+            );
+        }
+        assert!(closest_voices("zz_nothing_like_it", &voices).is_empty());
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+
+        assert!(engine.has_voice("af_sky"));
+        assert!(!engine.has_voice("af_skye"));
+        assert!(!engine.has_voice("af_sky.5+af_bella.5"));
+
+        // The error names the bad component of a mix and suggests a fix
+        match engine.parse_voice_style("af_sky.8+af_belle.2") {
+            Err(e @ KokoroError::VoiceNotFound { .. }) => {
+                assert_eq!(
+                    e.to_string(),
+                    "Voice not found: af_belle (in mix af_sky.8+af_belle.2); did you mean af_bella?"
+                );
+            }
+            other => panic!("expected VoiceNotFound, got {:?}", other),
+        }
+        match engine.parse_voice_style("af_skye") {
+            Err(KokoroError::VoiceNotFound {
+                name,
+                mix,
+                suggestions,
+            }) => {
+                assert_eq!(name, "af_skye");
+                assert_eq!(mix, None);
+                assert_eq!(suggestions, ["af_sky"]);
+            }
+            other => panic!("expected VoiceNotFound, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn snapshots_outlive_removal() {
+        let engine = engine();
+        let before = engine.voices.snapshot();
+
+        assert!(engine.remove_voice("af_sky"));
+        assert!(!engine.remove_voice("af_sky"));
+
+        // Work that already resolved its voices keeps them
+        assert_eq!(before["af_sky"][0], 0.1);
+        // The next lookup sees the removal
+        assert!(matches!(
+            engine.parse_voice_style("af_sky"),
+            Err(KokoroError::VoiceNotFound { name, .. }) if name == "af_sky"
+        ));
+        assert!(!engine.voices().contains(&"af_sky".to_string()));
+    }
+
+    #[test]
+    fn import_validates_style_vectors() {
+        let engine = engine();
+        assert!(matches!(
+            engine.import_voice("custom", vec![0.2; 10]),
+            Err(KokoroError::InvalidInput(_))
+        ));
+        assert!(engine.import_voice("custom", vec![f32::NAN; STYLE_DIM]).is_err());
+        assert!(engine.import_voice("", vec![0.2; STYLE_DIM]).is_err());
+        assert!(engine.import_voice("bad+name", vec![0.2; STYLE_DIM]).is_err());
+
+        engine.import_voice("custom", vec![0.2; STYLE_DIM]).unwrap();
+        assert_eq!(engine.parse_voice_style("custom").unwrap(), vec![0.2; STYLE_DIM]);
+
+        // Overwrites replace the vector wholesale
+        engine.import_voice("custom", vec![0.4; STYLE_DIM]).unwrap();
+        assert_eq!(engine.parse_voice_style("custom.5+af_sky.5").unwrap()[0], 0.25);
+    }
+
+    // Writers keep overwriting and removing a voice whose vector is a single
+    // repeated value; readers must only ever see "not found" or a whole vector
+    #[test]
+    fn concurrent_import_remove_and_lookup() {
+        let engine = engine();
+        let stop = AtomicBool::new(false);
+        let deadline = Instant::now() + Duration::from_millis(500);
+
+        std::thread::scope(|s| {
+            for writer in 0..2 {
+                let (engine, stop) = (&engine, &stop);
+                s.spawn(move || {
+                    let mut i = 0u32;
+                    while !stop.load(Ordering::Relaxed) {
+                        let value = (writer * 1000 + i % 1000) as f32;
+                        engine.import_voice("flux", vec![value; STYLE_DIM]).unwrap();
+                        if i.is_multiple_of(3) {
+                            engine.remove_voice("flux");
+                        }
+                        i += 1;
+                    }
+                });
+            }
+
+            for _ in 0..4 {
+                let (engine, stop) = (&engine, &stop);
+                s.spawn(move || {
+                    while !stop.load(Ordering::Relaxed) {
+                        match engine.parse_voice_style("flux.5+af_sky.5") {
+                            Ok(style) => {
+                                assert_eq!(style.len(), STYLE_DIM);
+                                assert!(style.iter().all(|&v| v == style[0]), "torn style vector");
+                            }
+                            Err(e) => assert!(
+                                matches!(&e, KokoroError::VoiceNotFound { name, .. } if name == "flux"),
+                                "{}",
+                                e
+                            ),
+                        }
+                        assert!(engine.voices().contains(&"af_sky".to_string()));
+                    }
+                });
+            }
+
+            while Instant::now() < deadline {
+                std::thread::sleep(Duration::from_millis(10));
+            }
+            stop.store(true, Ordering::Relaxed);
+        });
+    }
+
+    fn pool(n: usize) -> Vec<String> {
+        (0..n).map(|i| format!("voice_{}", i)).collect()
+    }
+
+    #[test]
+    fn assignments_survive_a_restart() {
+        let keys: Vec<String> = (0..20).map(|i| format!("source-{}", i)).collect();
+        let mut first = VoiceAssigner::new(pool(6));
+        let assigned: Vec<String> = keys.iter().map(|k| first.assign(k).to_string()).collect();
+
+        // Unused voices go first
+        let mut distinct = assigned[..6].to_vec();
+        distinct.sort();
+        distinct.dedup();
+        assert_eq!(distinct.len(), 6);
+
+        // Same pool in another order, same keys in the same order: same voices
+        let mut shuffled = pool(6);
+        shuffled.reverse();
+        let mut again = VoiceAssigner::new(shuffled);
+        for (key, voice) in keys.iter().zip(&assigned) {
+            assert_eq!(again.assign(key), voice);
+        }
+
+        // A new process sees the keys in reverse order but loads the file first
+        let path = std::env::temp_dir()
+            .join(format!("kokoro_voices_{}", std::process::id()))
+            .join("assignments.json");
+        assert_eq!(VoiceAssigner::load(&path).unwrap(), None);
+        first.persist(&path).unwrap();
+        let mut restarted = VoiceAssigner::load(&path).unwrap().unwrap();
+        for (key, voice) in keys.iter().zip(&assigned).rev() {
+            assert_eq!(restarted.assign(key), voice);
+        }
+        fs::remove_dir_all(path.parent().unwrap()).ok();
+    }
+
+    #[test]
+    fn many_keys_spread_evenly() {
+        let voices = pool(8);
+        let mut assigner = VoiceAssigner::new(voices.clone());
+        let mut counts: HashMap<String, usize> = HashMap::new();
+        for i in 0..500 {
+            *counts
+                .entry(assigner.assign(&format!("key-{}", i)).to_string())
+                .or_default() += 1;
+        }
+
+        let expected = 500.0 / voices.len() as f64;
+        let chi_square: f64 = voices
+            .iter()
+            .map(|v| {
+                let observed = counts.get(v).copied().unwrap_or(0) as f64;
+                (observed - expected).powi(2) / expected
+            })
+            .sum();
+        // 7 degrees of freedom, 5% critical value
+        assert!(chi_square < 14.07, "chi-square {}", chi_square);
+    }
+
+    #[test]
+    fn configured_voices_win() {
+        let mut assigner = VoiceAssigner::new(pool(3));
+        assigner.pin("calendar", "af_heart.6+af_sky.4");
+        assert_eq!(assigner.assign("calendar"), "af_heart.6+af_sky.4");
+        assert_eq!(assigner.resolve("auto:calendar"), "af_heart.6+af_sky.4");
+        assert_eq!(assigner.resolve("am_adam"), "am_adam");
+
+        let auto = assigner.resolve("auto:build");
+        assigner.pin("build", "am_michael");
+        assert_ne!(auto, "am_michael");
+        assert_eq!(assigner.resolve("auto:build"), "am_michael");
+
+        assert_eq!(VoiceAssigner::new(Vec::new()).assign("x"), DEFAULT_VOICE);
+    }
+}
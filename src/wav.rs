@@ -0,0 +1,153 @@
+//! WAV files, for `save_wav`, `save_wav_telephony`, `to_wav_bytes` and
+//! `encode`
+//!
+//! The audio's length is known before the first byte goes out, so the header
+//! is written complete up front and the samples follow: no seeking back to
+//! patch sizes, which lets the file go straight to a socket or a pipe.
+//! Samples are 16-bit PCM, or G.711 bytes for telephony.
+
+use std::io::Write;
+
+use crate::telephony::{Law, TELEPHONY_RATE};
+use crate::{pcm, AudioSource, KokoroError, PcmFormat};
+
+// RIFF, fmt and data chunk headers together
+const HEADER_LEN: u32 = 44;
+
+/// `audio` as a WAV file into `w`, at its own rate and channel count
+pub(crate) fn encode(audio: &impl AudioSource, mut w: impl Write) -> Result<(), KokoroError> {
+    let channels = audio.channels().max(1);
+    let rate = audio.sample_rate();
+    let data_len = u32::try_from(audio.samples().len() * 2)
+        .ok()
+        .filter(|len| len.checked_add(HEADER_LEN - 8).is_some())
+        .ok_or_else(|| {
+            KokoroError::AudioEncodingFailed("Audio too long for a WAV file".to_string())
+        })?;
+
+    let mut header = Vec::with_capacity(HEADER_LEN as usize);
+    header.extend_from_slice(b"RIFF");
+    header.extend_from_slice(&(HEADER_LEN - 8 + data_len).to_le_bytes());
+    header.extend_from_slice(b"WAVE");
+    header.extend_from_slice(b"fmt ");
+    header.extend_from_slice(&16u32.to_le_bytes());
+    header.extend_from_slice(&1u16.to_le_bytes()); // PCM
+    header.extend_from_slice(&channels.to_le_bytes());
+    header.extend_from_slice(&rate.to_le_bytes());
+    header.extend_from_slice(&(rate * u32::from(channels) * 2).to_le_bytes()); // Bytes per second
+    header.extend_from_slice(&(channels * 2).to_le_bytes()); // Bytes per frame
+    header.extend_from_slice(&16u16.to_le_bytes()); // Bits per sample
+    header.extend_from_slice(b"data");
+    header.extend_from_slice(&data_len.to_le_bytes());
+    w.write_all(&header)?;
+
+    pcm::write_pcm(w, audio.samples(), PcmFormat::S16le)?;
+    Ok(())
+}
+
+/// 8kHz mono G.711 `data` as a WAV file into `w`
+///
+/// Compressed formats have an 18-byte fmt chunk and a fact chunk with the
+/// sample count, which players of µ-law and A-law files look for.
+pub(crate) fn encode_companded(
+    data: &[u8],
+    law: Law,
+    mut w: impl Write,
+) -> Result<(), KokoroError> {
+    let too_long = || KokoroError::AudioEncodingFailed("Audio too long for a WAV file".to_string());
+    let data_len = u32::try_from(data.len()).map_err(|_| too_long())?;
+    // Chunks start on even offsets
+    let pad = data_len % 2;
+    let riff_len = (4 + 8 + 18 + 8 + 4 + 8 + pad)
+        .checked_add(data_len)
+        .ok_or_else(too_long)?;
+
+    let mut header = Vec::with_capacity(58);
+    header.extend_from_slice(b"RIFF");
+    header.extend_from_slice(&riff_len.to_le_bytes());
+    header.extend_from_slice(b"WAVE");
+    header.extend_from_slice(b"fmt ");
+    header.extend_from_slice(&18u32.to_le_bytes());
+    header.extend_from_slice(&law.wav_format_tag().to_le_bytes());
+    header.extend_from_slice(&1u16.to_le_bytes()); // Mono
+    header.extend_from_slice(&TELEPHONY_RATE.to_le_bytes());
+    header.extend_from_slice(&TELEPHONY_RATE.to_le_bytes()); // Bytes per second
+    header.extend_from_slice(&1u16.to_le_bytes()); // Bytes per frame
+    header.extend_from_slice(&8u16.to_le_bytes()); // Bits per sample
+    header.extend_from_slice(&0u16.to_le_bytes()); // No extra format bytes
+    header.extend_from_slice(b"fact");
+    header.extend_from_slice(&4u32.to_le_bytes());
+    header.extend_from_slice(&data_len.to_le_bytes()); // Samples
+    header.extend_from_slice(b"data");
+    header.extend_from_slice(&data_len.to_le_bytes());
+    w.write_all(&header)?;
+    w.write_all(data)?;
+    if pad == 1 {
+        w.write_all(&[0])?;
+    }
+    w.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AudioBuffer;
+    use std::io::Cursor;
+
+    // What hound writes for the same audio, through a seekable buffer
+    fn hound_wav(audio: &impl AudioSource) -> Vec<u8> {
+        let spec = hound::WavSpec {
+            channels: audio.channels(),
+            sample_rate: audio.sample_rate(),
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let mut cursor = Cursor::new(Vec::new());
+        let mut writer = hound::WavWriter::new(&mut cursor, spec).unwrap();
+        for &sample in audio.samples() {
+            let sample = (sample * 32767.0).clamp(-32768.0, 32767.0) as i16;
+            writer.write_sample(sample).unwrap();
+        }
+        writer.finalize().unwrap();
+        cursor.into_inner()
+    }
+
+    #[test]
+    fn streamed_files_match_seeking_ones() {
+        let tone: Vec<f32> = (0..5_001).map(|i| (i as f32 * 0.07).sin() * 0.9).collect();
+        let mut mono = Vec::new();
+        encode(&tone, &mut mono).unwrap();
+        assert_eq!(mono, hound_wav(&tone));
+
+        let stereo = AudioBuffer::interleaved(tone[..5_000].to_vec(), 48_000, 2);
+        let mut file = Vec::new();
+        encode(&stereo, &mut file).unwrap();
+        assert_eq!(file, hound_wav(&stereo));
+        let reader = hound::WavReader::new(Cursor::new(file)).unwrap();
+        assert_eq!(
+            (reader.spec().channels, reader.spec().sample_rate),
+            (2, 48_000)
+        );
+        assert_eq!(reader.duration(), 2_500);
+    }
+
+    #[test]
+    fn telephony_files_carry_the_law_in_their_format_tag() {
+        for (law, tag) in [(Law::MuLaw, 7u16), (Law::ALaw, 6)] {
+            let mut file = Vec::new();
+            encode_companded(&[0xff; 801], law, &mut file).unwrap();
+            assert_eq!(file.len(), 58 + 801 + 1);
+            assert_eq!(&file[..4], b"RIFF");
+            let riff_len = u32::from_le_bytes(file[4..8].try_into().unwrap());
+            assert_eq!(riff_len as usize, file.len() - 8);
+            assert_eq!(&file[12..16], b"fmt ");
+            assert_eq!(u16::from_le_bytes([file[20], file[21]]), tag);
+            assert_eq!(u32::from_le_bytes(file[24..28].try_into().unwrap()), 8_000);
+            assert_eq!(u16::from_le_bytes([file[34], file[35]]), 8);
+            assert_eq!(&file[38..42], b"fact");
+            assert_eq!(&file[50..54], b"data");
+            assert_eq!(u32::from_le_bytes(file[54..58].try_into().unwrap()), 801);
+        }
+    }
+}
@@ -0,0 +1,68 @@
+//! Cache directory resolution from the real environment
+//!
+//! Its own test binary, with a single test: the variables are process-wide,
+//! and changing them would confuse any test running alongside.
+
+use std::env;
+
+use kokoro_tiny::{cache_dir, voices, KokoroError, TtsEngine};
+
+#[test]
+fn environment_picks_the_cache_dir() {
+    let root = env::temp_dir().join(format!("kokoro-cache-dir-{}", std::process::id()));
+    let home = root.join("home");
+    let xdg = root.join("xdg");
+    let custom = root.join("custom");
+    env::set_var("HOME", &home);
+    env::remove_var("KOKORO_CACHE_DIR");
+    env::remove_var("XDG_CACHE_HOME");
+
+    if cfg!(windows) {
+        assert_eq!(cache_dir().file_name().unwrap(), "kokoro-tiny");
+    } else {
+        assert_eq!(cache_dir(), home.join(".cache").join("k"));
+    }
+
+    env::set_var("XDG_CACHE_HOME", &xdg);
+    assert_eq!(cache_dir(), xdg.join("kokoro-tiny"));
+    // Empty counts as unset
+    env::set_var("KOKORO_CACHE_DIR", "");
+    assert_eq!(cache_dir(), xdg.join("kokoro-tiny"));
+
+    env::set_var("KOKORO_CACHE_DIR", &custom);
+    assert_eq!(cache_dir(), custom);
+    assert_eq!(
+        voices::assignments_path(),
+        custom.join("voice-assignments.json")
+    );
+
+    // The model is looked for there, and nothing is created looking
+    match TtsEngine::from_cached() {
+        Err(KokoroError::ModelMissing(message)) => {
+            let expected = custom.join("0.onnx");
+            assert!(message.contains(expected.to_str().unwrap()), "{}", message);
+        }
+        Err(e) => panic!("expected ModelMissing, got {:?}", e),
+        Ok(_) => panic!("loaded a model from an empty cache"),
+    }
+    assert!(!custom.exists());
+
+    // The builder's directory beats the environment, and explicit paths
+    // beat both
+    let engine = TtsEngine::builder().cache_dir(&root).build_cached();
+    assert!(matches!(
+        engine,
+        Err(KokoroError::ModelMissing(message)) if message.contains(root.join("0.onnx").to_str().unwrap())
+    ));
+    let engine = TtsEngine::builder()
+        .cache_dir(&root)
+        .paths("models/kokoro.onnx", "models/voices.bin")
+        .build_cached();
+    assert!(matches!(
+        engine,
+        Err(KokoroError::ModelMissing(message)) if message.contains("models/kokoro.onnx")
+    ));
+
+    let fallback = TtsEngine::without_model();
+    assert_eq!(fallback.cache_dir(), custom);
+}
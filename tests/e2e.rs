@@ -0,0 +1,303 @@
+//! First-run experience, end to end, against a local fixture server
+//!
+//! Serves a tiny stand-in model (two ONNX nodes: `audio = sin(float(tokens))`)
+//! and a one-voice NPZ over plain HTTP. The engine downloads them into a temp
+//! directory through `KOKORO_MODEL_URL` / `KOKORO_VOICES_URL`, synthesizes
+//! through the full pipeline (espeak, tokenizer, ONNX Runtime) and saves the
+//! result. A second construction must not touch the network. A fallback
+//! engine must pick up the same files once they are placed by hand. The
+//! upstream export's names (`input_ids`, a float64 `waveform`) must work too.
+//!
+//! Needs espeak-ng and the ONNX Runtime library, so it is ignored by default:
+//! `cargo test --test e2e -- --ignored`
+
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use kokoro_tiny::retry::RetryState;
+use kokoro_tiny::{ModelVariant, SynthesizeOptions, TtsEngine, STYLE_DIM};
+use ndarray::Array2;
+use ndarray_npy::NpzWriter;
+
+// Minimal protobuf writer, enough for an ONNX ModelProto
+#[derive(Default)]
+struct Proto(Vec<u8>);
+
+impl Proto {
+    fn varint(&mut self, mut v: u64) {
+        while v >= 0x80 {
+            self.0.push(v as u8 | 0x80);
+            v >>= 7;
+        }
+        self.0.push(v as u8);
+    }
+
+    fn int(mut self, field: u64, v: u64) -> Self {
+        self.varint(field << 3);
+        self.varint(v);
+        self
+    }
+
+    fn bytes(mut self, field: u64, bytes: &[u8]) -> Self {
+        self.varint(field << 3 | 2);
+        self.varint(bytes.len() as u64);
+        self.0.extend_from_slice(bytes);
+        self
+    }
+
+    fn str(self, field: u64, s: &str) -> Self {
+        self.bytes(field, s.as_bytes())
+    }
+
+    fn msg(self, field: u64, msg: Proto) -> Self {
+        self.bytes(field, &msg.0)
+    }
+}
+
+const ONNX_FLOAT: u64 = 1;
+const ONNX_INT64: u64 = 7;
+const ONNX_DOUBLE: u64 = 11;
+
+// ValueInfoProto for a tensor; `None` dims are symbolic
+fn tensor_info(name: &str, elem_type: u64, dims: &[Option<u64>]) -> Proto {
+    let shape = dims.iter().fold(Proto::default(), |shape, dim| {
+        let dim = match dim {
+            Some(size) => Proto::default().int(1, *size),
+            None => Proto::default().str(2, "n"),
+        };
+        shape.msg(1, dim)
+    });
+    let tensor = Proto::default().int(1, elem_type).msg(2, shape);
+    Proto::default()
+        .str(1, name)
+        .msg(2, Proto::default().msg(1, tensor))
+}
+
+// Takes the engine's inputs (tokens, style, speed), returns a short tone-ish
+// "audio" with one sample per token
+fn fixture_model() -> Vec<u8> {
+    fixture_export("tokens", "audio", ONNX_FLOAT)
+}
+
+// The same graph under other input/output names and output element type, as
+// the upstream (and int8) exports declare them
+fn fixture_export(tokens: &str, audio: &str, audio_type: u64) -> Vec<u8> {
+    let cast = Proto::default()
+        .str(1, tokens)
+        .str(2, "tokens_f")
+        .str(4, "Cast")
+        .msg(
+            5,
+            Proto::default().str(1, "to").int(20, 2).int(3, audio_type),
+        );
+    let sin = Proto::default()
+        .str(1, "tokens_f")
+        .str(2, audio)
+        .str(4, "Sin");
+    let graph = Proto::default()
+        .msg(1, cast)
+        .msg(1, sin)
+        .str(2, "fixture")
+        .msg(11, tensor_info(tokens, ONNX_INT64, &[Some(1), None]))
+        .msg(
+            11,
+            tensor_info("style", ONNX_FLOAT, &[Some(1), Some(STYLE_DIM as u64)]),
+        )
+        .msg(11, tensor_info("speed", ONNX_FLOAT, &[Some(1)]))
+        .msg(12, tensor_info(audio, audio_type, &[Some(1), None]));
+    Proto::default()
+        .int(1, 7)
+        .msg(7, graph)
+        .msg(8, Proto::default().str(1, "").int(2, 13))
+        .0
+}
+
+fn fixture_voices(dir: &Path) -> Vec<u8> {
+    let path = dir.join("fixture-voices.npz");
+    let mut npz = NpzWriter::new(fs::File::create(&path).unwrap());
+    let style = Array2::from_shape_fn((1, STYLE_DIM), |(_, i)| i as f32 / STYLE_DIM as f32);
+    npz.add_array("af_sky.npy", &style).unwrap();
+    npz.finish().unwrap();
+    fs::read(&path).unwrap()
+}
+
+// Serves `/0.onnx` and `/0.bin`, counting requests
+fn serve(model: Vec<u8>, voices: Vec<u8>) -> (String, Arc<AtomicUsize>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let base = format!("http://{}", listener.local_addr().unwrap());
+    let requests = Arc::new(AtomicUsize::new(0));
+    let counter = requests.clone();
+
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let Ok(mut stream) = stream else { continue };
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut request_line = String::new();
+            if reader.read_line(&mut request_line).is_err() {
+                continue;
+            }
+            // Drain the headers
+            let mut line = String::new();
+            while reader.read_line(&mut line).is_ok_and(|n| n > 2) {
+                line.clear();
+            }
+            counter.fetch_add(1, Ordering::SeqCst);
+
+            let path = request_line.split_whitespace().nth(1).unwrap_or("");
+            let (status, body) = match path {
+                "/0.onnx" => ("200 OK", &model),
+                "/0.bin" => ("200 OK", &voices),
+                _ => ("404 Not Found", &Vec::new()),
+            };
+            let head = format!(
+                "HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                status,
+                body.len()
+            );
+            let _ = stream.write_all(head.as_bytes());
+            let _ = stream.write_all(body);
+        }
+    });
+
+    (base, requests)
+}
+
+#[tokio::test]
+#[ignore = "needs espeak-ng and the ONNX Runtime library"]
+async fn e2e_first_run_downloads_synthesizes_and_saves() {
+    let dir = std::env::temp_dir().join(format!("kokoro-e2e-{}", std::process::id()));
+    let cache = dir.join("cache");
+    fs::create_dir_all(&dir).unwrap();
+
+    let (base, requests) = serve(fixture_model(), fixture_voices(&dir));
+    // Only test in this binary, so nothing else sees the variables
+    std::env::set_var("KOKORO_MODEL_URL", format!("{}/0.onnx", base));
+    std::env::set_var("KOKORO_VOICES_URL", format!("{}/0.bin", base));
+
+    let model = cache.join("0.onnx");
+    let voices = cache.join("0.bin");
+    let (model, voices) = (model.to_str().unwrap(), voices.to_str().unwrap());
+
+    // First run: both files come from the server
+    let tts = TtsEngine::with_paths(model, voices).await.unwrap();
+    assert_eq!(requests.load(Ordering::SeqCst), 2);
+    assert_eq!(tts.voices(), vec!["af_sky".to_string()]);
+    assert_eq!(fs::read(model).unwrap(), fixture_model());
+
+    // Cache layout: the two files, no leftover partial downloads
+    let mut names: Vec<String> = fs::read_dir(&cache)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+        .collect();
+    names.sort();
+    assert!(names.contains(&"0.onnx".to_string()) && names.contains(&"0.bin".to_string()));
+    assert!(
+        !names.iter().any(|name| name.ends_with(".part")),
+        "{names:?}"
+    );
+
+    // Full pipeline through the fixture model
+    let audio = tts
+        .synthesize_with("Hello from the fixture server.", SynthesizeOptions::new())
+        .unwrap();
+    assert!(!audio.is_empty());
+    assert!(audio.iter().all(|s| s.is_finite() && s.abs() <= 1.0));
+
+    let wav = dir.join("hello.wav");
+    tts.save_wav(wav.to_str().unwrap(), &audio).unwrap();
+    let reader = hound::WavReader::open(&wav).unwrap();
+    assert_eq!(reader.spec().sample_rate, 24000);
+    assert_eq!(reader.len() as usize, audio.len());
+
+    #[cfg(feature = "mp3")]
+    {
+        let mp3 = dir.join("hello.mp3");
+        tts.save_mp3(mp3.to_str().unwrap(), &audio).unwrap();
+        assert!(fs::metadata(&mp3).unwrap().len() > 0);
+    }
+
+    // Second run: everything is cached, no requests at all
+    drop(tts);
+    let again = TtsEngine::with_paths(model, voices).await.unwrap();
+    assert_eq!(requests.load(Ordering::SeqCst), 2);
+    assert_eq!(again.voices(), vec!["af_sky".to_string()]);
+
+    fs::remove_dir_all(&dir).ok();
+}
+
+#[tokio::test]
+#[ignore = "needs espeak-ng and the ONNX Runtime library"]
+async fn e2e_retry_initialization_picks_up_placed_files() {
+    let dir = std::env::temp_dir().join(format!("kokoro-e2e-retry-{}", std::process::id()));
+    let cache = dir.join("cache");
+    fs::create_dir_all(&cache).unwrap();
+
+    // Downloads are refused outright, so this never touches the network
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    let state = RetryState {
+        consecutive_failures: 5,
+        last_attempt: now,
+        circuit_open_until: Some(now + 3600),
+    };
+    state.save(&cache).unwrap();
+
+    let model = cache.join("0.onnx");
+    let voices = cache.join("0.bin");
+    let (model_path, voices_path) = (model.to_str().unwrap(), voices.to_str().unwrap());
+
+    assert!(TtsEngine::with_paths_strict(model_path, voices_path)
+        .await
+        .is_err());
+    let tts = TtsEngine::with_paths(model_path, voices_path)
+        .await
+        .unwrap();
+    assert!(tts.is_fallback());
+
+    // Placed by hand, e.g. copied over from another machine
+    fs::write(&model, fixture_model()).unwrap();
+    fs::write(&voices, fixture_voices(&dir)).unwrap();
+
+    tts.retry_initialization().await.unwrap();
+    assert!(!tts.is_fallback());
+    assert_eq!(tts.voices(), vec!["af_sky".to_string()]);
+    let audio = tts
+        .synthesize_with("Back from fallback.", SynthesizeOptions::new())
+        .unwrap();
+    assert!(!audio.is_empty());
+
+    fs::remove_dir_all(&dir).ok();
+}
+
+#[test]
+#[ignore = "needs espeak-ng and the ONNX Runtime library"]
+fn e2e_upstream_export_names_and_types_are_understood() {
+    let dir = std::env::temp_dir().join(format!("kokoro-e2e-int8-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let model = dir.join("0-int8.onnx");
+    let voices = dir.join("0.bin");
+    fs::write(&model, fixture_export("input_ids", "waveform", ONNX_DOUBLE)).unwrap();
+    fs::write(&voices, fixture_voices(&dir)).unwrap();
+
+    let tts = TtsEngine::builder()
+        .paths(model.to_str().unwrap(), voices.to_str().unwrap())
+        .variant(ModelVariant::Int8)
+        .build_cached()
+        .unwrap();
+    assert_eq!(tts.model_variant(), ModelVariant::Int8);
+    let audio = tts
+        .synthesize_with("Hello from the quantized model.", SynthesizeOptions::new())
+        .unwrap();
+    assert!(!audio.is_empty());
+    assert!(audio.iter().all(|s| s.is_finite() && s.abs() <= 1.0));
+
+    fs::remove_dir_all(&dir).ok();
+}
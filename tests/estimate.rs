@@ -0,0 +1,46 @@
+//! `estimate_duration` against what the model actually renders
+//!
+//! Needs the real model in ~/.cache/k, so it is ignored by default:
+//! `cargo test --test estimate -- --ignored --nocapture` (prints each ratio,
+//! which is what to look at when refitting `SECONDS_PER_TOKEN`)
+
+use kokoro_tiny::{AudioBuffer, TtsEngine};
+
+const PHRASES: &[&str] = &[
+    "Build complete.",
+    "You have three new messages.",
+    "The quick brown fox jumps over the lazy dog.",
+    "Your meeting with the design team starts in fifteen minutes, in room four.",
+    "Deployment finished. All forty two checks passed, and the release notes \
+     have been sent to the mailing list.",
+];
+
+#[test]
+#[ignore = "needs the Kokoro model files in ~/.cache/k"]
+fn estimates_are_within_fifteen_percent_of_synthesis() {
+    let tts = TtsEngine::from_cached().unwrap();
+
+    for speed in [0.8, 1.0, 1.5] {
+        for phrase in PHRASES {
+            let estimate = tts.estimate_duration(phrase, speed, "en").unwrap();
+            let audio = tts
+                .synthesize_buffer(phrase, None, Some(speed), Some("en"))
+                .unwrap();
+            let actual = audio.duration().as_duration();
+
+            let ratio = estimate.as_secs_f64() / actual.as_secs_f64();
+            println!(
+                "{:.1}x {:.2} {:?} vs {:?} {}",
+                speed, ratio, estimate, actual, phrase
+            );
+            assert!(
+                (0.85..=1.15).contains(&ratio),
+                "{:.1}x \"{}\": estimated {:?}, rendered {:?}",
+                speed,
+                phrase,
+                estimate,
+                actual
+            );
+        }
+    }
+}
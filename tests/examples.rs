@@ -0,0 +1,215 @@
+//! The examples' core paths, run without the model or audio hardware
+//!
+//! Each example keeps engine setup and playback in `main` and the rest in
+//! `pub fn`s, which are pulled in here with `#[path]` and run against a
+//! model-free engine (`TtsEngine::without_model`, which renders the fallback
+//! message). A signature change that breaks an example breaks `cargo test`.
+//!
+//! Not run here: `unlimited_speech` (waits on stdin and plays as it goes) and
+//! the device examples (`list_devices`, `device_select`, `test_devices`),
+//! which only do something with real outputs. `cargo test` still builds them.
+
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use kokoro_tiny::mem8_bridge::Mem8Bridge;
+use kokoro_tiny::{BabyTts, KokoroError, TtsEngine, STYLE_DIM};
+
+#[path = "../examples/all_voices.rs"]
+mod all_voices;
+#[path = "../examples/async_server.rs"]
+mod async_server;
+#[path = "../examples/baby_speech.rs"]
+mod baby_speech;
+#[path = "../examples/debug_phonemes.rs"]
+mod debug_phonemes;
+#[path = "../examples/debug_tokens.rs"]
+mod debug_tokens;
+#[path = "../examples/debug_words.rs"]
+mod debug_words;
+#[path = "../examples/demo_fix.rs"]
+mod demo_fix;
+#[path = "../examples/from_memory.rs"]
+mod from_memory;
+#[path = "../examples/ipa_kokoro.rs"]
+mod ipa_kokoro;
+#[path = "../examples/mcp_example.rs"]
+mod mcp_example;
+#[path = "../examples/mem8_baby.rs"]
+mod mem8_baby;
+#[path = "../examples/simple.rs"]
+mod simple;
+#[path = "../examples/story_time.rs"]
+mod story_time;
+#[path = "../examples/stream_to_wav.rs"]
+mod stream_to_wav;
+#[path = "../examples/test_american.rs"]
+mod test_american;
+#[path = "../examples/test_backwards_compat.rs"]
+mod test_backwards_compat;
+#[path = "../examples/test_english.rs"]
+mod test_english;
+#[path = "../examples/test_v2_punctuation.rs"]
+mod test_v2_punctuation;
+#[path = "../examples/variant_bench.rs"]
+mod variant_bench;
+// thread_bench.rs comes in through variant_bench.rs
+use variant_bench::thread_bench;
+
+// Model-free engine with the voices the examples ask for
+fn engine() -> TtsEngine {
+    let tts = TtsEngine::without_model();
+    for voice in ["af_sky", "am_adam", "bf_emma", "jf_alpha"] {
+        tts.import_voice(voice, vec![0.0; STYLE_DIM]).unwrap();
+    }
+    tts
+}
+
+// Scratch directory for the WAVs the examples write
+fn out_dir(example: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!(
+        "kokoro-examples-{}-{}",
+        example,
+        std::process::id()
+    ));
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn wav_count(dir: &PathBuf) -> usize {
+    fs::read_dir(dir)
+        .unwrap()
+        .filter(|entry| {
+            entry
+                .as_ref()
+                .is_ok_and(|e| e.path().extension().is_some_and(|ext| ext == "wav"))
+        })
+        .count()
+}
+
+#[test]
+fn single_utterance_examples_render_and_save() {
+    type Example =
+        fn(&mut TtsEngine, &std::path::Path) -> Result<Vec<f32>, Box<dyn std::error::Error>>;
+    let examples: [(&str, Example); 7] = [
+        ("simple", simple::run),
+        ("ipa_kokoro", ipa_kokoro::run),
+        ("demo_fix", demo_fix::run),
+        ("story_time", story_time::run),
+        ("stream_to_wav", stream_to_wav::run),
+        ("test_american", test_american::run),
+        ("test_english", test_english::run),
+    ];
+
+    for (name, run) in examples {
+        let dir = out_dir(name);
+        let audio = run(&mut engine(), &dir).unwrap_or_else(|e| panic!("{name}: {e}"));
+        assert!(!audio.is_empty(), "{name}");
+        assert_eq!(wav_count(&dir), 1, "{name}");
+        fs::remove_dir_all(&dir).ok();
+    }
+}
+
+#[test]
+fn from_memory_synthesizes_and_rejects_a_bad_voice_pack() {
+    assert!(!from_memory::run(&mut engine()).unwrap().is_empty());
+
+    match TtsEngine::from_memory(b"", b"not a voice pack") {
+        Err(KokoroError::ModelMissing(_)) => {}
+        Err(e) => panic!("expected ModelMissing, got {:?}", e),
+        Ok(_) => panic!("loaded a model from garbage"),
+    }
+}
+
+#[test]
+fn all_voices_picks_english_voices() {
+    let voices: Vec<String> = ["jf_alpha", "bm_george", "af_sky", "am_adam", "zf_xiaobei"]
+        .map(String::from)
+        .to_vec();
+    assert_eq!(
+        all_voices::english_voices(&voices),
+        ["af_sky", "am_adam", "bm_george"]
+    );
+
+    // Every imported English voice renders; jf_alpha is skipped
+    let rendered = all_voices::run(&mut engine());
+    let names: Vec<&str> = rendered.iter().map(|(voice, _)| voice.as_str()).collect();
+    assert_eq!(names, ["af_sky", "am_adam", "bf_emma"]);
+    assert!(rendered.iter().all(|(_, audio)| !audio.is_empty()));
+}
+
+#[tokio::test]
+async fn async_server_answers_every_request() {
+    let responses = async_server::run(Arc::new(engine())).await.unwrap();
+    assert_eq!(responses.len(), async_server::REQUESTS.len());
+    assert!(responses.iter().all(|wav| wav.starts_with(b"RIFF")));
+}
+
+#[test]
+fn debug_words_renders_every_phrase_at_every_speed() {
+    let dir = out_dir("debug_words");
+    let rendered = debug_words::run(&mut engine(), &dir).unwrap();
+    let expected = debug_words::TEST_PHRASES.len() * debug_words::SPEEDS.len();
+    assert_eq!(rendered.len(), expected);
+    assert_eq!(wav_count(&dir), expected);
+    fs::remove_dir_all(&dir).ok();
+}
+
+#[test]
+fn punctuation_example_measures_silence() {
+    let mut audio = vec![0.5; 100];
+    audio.extend([0.0; 30]);
+    audio.extend([0.5; 10]);
+    audio.extend([0.0005; 40]);
+    assert_eq!(test_v2_punctuation::longest_silence(&audio), 40);
+    assert_eq!(test_v2_punctuation::longest_silence(&[]), 0);
+
+    let dir = out_dir("test_v2_punctuation");
+    test_v2_punctuation::run(&mut engine(), &dir).unwrap();
+    assert_eq!(wav_count(&dir), 1);
+    fs::remove_dir_all(&dir).ok();
+}
+
+#[test]
+fn thread_bench_measures_a_real_time_factor() {
+    let rtf = thread_bench::real_time_factor(&mut engine(), thread_bench::TEXT, 2).unwrap();
+    assert!(rtf.is_finite() && rtf >= 0.0, "{}", rtf);
+}
+
+#[test]
+fn variant_bench_labels_each_engine() {
+    let results = variant_bench::compare(&mut [engine()], thread_bench::TEXT, 1).unwrap();
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].0, kokoro_tiny::ModelVariant::Fp32);
+}
+
+#[test]
+fn debugging_examples_complete() {
+    assert!(debug_tokens::run(&mut engine()));
+    test_backwards_compat::run(&mut engine()).unwrap();
+    // espeak may or may not be installed; the example reports either way
+    assert!(debug_phonemes::run("Hello world") <= 4);
+}
+
+#[test]
+fn baby_examples_complete() {
+    baby_speech::run(&mut BabyTts::with_engine(Arc::new(engine()))).unwrap();
+
+    let mut bridge = Mem8Bridge::with_baby(BabyTts::with_engine(Arc::new(engine())));
+    mem8_baby::run(&mut bridge, Duration::ZERO).unwrap();
+}
+
+#[test]
+fn mcp_example_requests_are_valid_json_rpc() {
+    let requests = mcp_example::requests();
+    assert!(!requests.is_empty());
+    for (id, (name, request)) in requests.iter().enumerate() {
+        let request: serde_json::Value =
+            serde_json::from_str(request).unwrap_or_else(|e| panic!("{name}: {e}"));
+        assert_eq!(request["jsonrpc"], "2.0", "{name}");
+        assert_eq!(request["id"], id as u64 + 1, "{name}");
+        assert!(request["method"].is_string(), "{name}");
+    }
+}
@@ -0,0 +1,28 @@
+//! `TtsEngine::from_cached` without a runtime and without a cached model
+//!
+//! Its own test binary: HOME is process-wide, and pointing it at an empty
+//! directory would confuse any test running alongside.
+
+use kokoro_tiny::{KokoroError, TtsEngine};
+
+#[test]
+fn missing_model_is_an_error_not_fallback() {
+    let home = std::env::temp_dir().join(format!("kokoro-from-cached-{}", std::process::id()));
+    std::fs::create_dir_all(&home).unwrap();
+    std::env::set_var("HOME", &home);
+    std::env::remove_var("KOKORO_CACHE_DIR");
+    std::env::remove_var("XDG_CACHE_HOME");
+
+    // Plain #[test]: no tokio runtime anywhere
+    match TtsEngine::from_cached() {
+        Err(KokoroError::ModelMissing(message)) => {
+            assert!(message.contains("0.onnx"), "{}", message)
+        }
+        Err(e) => panic!("expected ModelMissing, got {:?}", e),
+        Ok(_) => panic!("loaded a model from an empty cache"),
+    }
+    // Nothing was fetched or created
+    assert!(!home.join(".cache").exists());
+
+    std::fs::remove_dir_all(&home).ok();
+}
@@ -0,0 +1,57 @@
+//! Drives `kokoro-speak jsonl` over a pipe
+//!
+//! Needs the real model in ~/.cache/k (first run downloads it), so it is
+//! ignored by default: `cargo test --test jsonl_cli -- --ignored`
+
+use assert_cmd::Command;
+use serde_json::{json, Value};
+
+#[test]
+#[ignore = "needs the Kokoro model files in ~/.cache/k"]
+fn jsonl_jobs_report_per_line_and_exit_on_shutdown() {
+    let dir = std::env::temp_dir().join(format!("kokoro_jsonl_cli_{}", std::process::id()));
+    let a1 = dir.join("out").join("a1.wav");
+    let a3 = dir.join("out").join("a3.wav");
+
+    let input = [
+        json!({"id": "a1", "text": "Build complete.", "voice": "af_sky", "output": a1}).to_string(),
+        json!({"id": "a2", "text": "Hello", "voice": "zz_missing", "output": a1}).to_string(),
+        "this is not json".to_string(),
+        json!({"id": "a3", "text": "Tests passed.", "output": a3}).to_string(),
+        json!({"cmd": "shutdown"}).to_string(),
+        json!({"id": "a4", "text": "Never processed", "output": a1}).to_string(),
+    ]
+    .join("\n");
+
+    let output = Command::cargo_bin("kokoro-speak")
+        .unwrap()
+        .arg("jsonl")
+        .write_stdin(input)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let results: Vec<Value> = String::from_utf8(output.stdout)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).expect("stdout must be pure JSONL"))
+        .collect();
+    assert_eq!(results.len(), 4);
+
+    assert_eq!(results[0]["id"], "a1");
+    assert_eq!(results[0]["ok"], true);
+    assert!(results[0]["duration_ms"].as_u64().unwrap() > 0);
+    assert!(a1.exists());
+
+    assert_eq!(results[1]["id"], "a2");
+    assert_eq!(results[1]["ok"], false);
+
+    assert_eq!(results[2]["ok"], false);
+    assert_eq!(results[2]["line"], 3);
+
+    assert_eq!(results[3]["id"], "a3");
+    assert_eq!(results[3]["ok"], true);
+    assert!(a3.exists());
+
+    std::fs::remove_dir_all(dir).ok();
+}
@@ -0,0 +1,143 @@
+//! `kokoro-speak --no-download` on a machine with no cached model
+//!
+//! Each run gets an empty HOME (so an empty ~/.cache/k) and download URLs
+//! pointing at a local listener that counts connections; the listener must
+//! never see one. Synthesis falls back to the built-in formant voice, so
+//! different text must give different audio.
+
+use std::net::TcpListener;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+
+use assert_cmd::Command;
+use serde_json::{json, Value};
+
+const NOTICE: &str = "degraded quality";
+
+struct Sandbox {
+    home: PathBuf,
+    url: String,
+    connections: Arc<AtomicUsize>,
+}
+
+impl Sandbox {
+    fn new(name: &str) -> Self {
+        let home = std::env::temp_dir().join(format!(
+            "kokoro-no-download-{}-{}",
+            name,
+            std::process::id()
+        ));
+        std::fs::create_dir_all(&home).unwrap();
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let connections = Arc::new(AtomicUsize::new(0));
+        let counter = connections.clone();
+        thread::spawn(move || {
+            for _ in listener.incoming() {
+                counter.fetch_add(1, Ordering::SeqCst);
+            }
+        });
+
+        Self {
+            home,
+            url,
+            connections,
+        }
+    }
+
+    // Runs kokoro-speak --no-download; returns (stdout, stderr)
+    fn run(&self, args: &[&str], stdin: &str) -> (String, String) {
+        let output = Command::cargo_bin("kokoro-speak")
+            .unwrap()
+            .env("HOME", &self.home)
+            .env_remove("KOKORO_CACHE_DIR")
+            .env_remove("XDG_CACHE_HOME")
+            .env("KOKORO_MODEL_URL", format!("{}/0.onnx", self.url))
+            .env("KOKORO_VOICES_URL", format!("{}/0.bin", self.url))
+            .arg("--no-download")
+            .args(args)
+            .write_stdin(stdin)
+            .output()
+            .unwrap();
+        let stderr = String::from_utf8_lossy(&output.stderr).into_owned();
+        assert!(output.status.success(), "{:?}: {}", args, stderr);
+        assert_eq!(stderr.matches(NOTICE).count(), 1, "{:?}: {}", args, stderr);
+        (String::from_utf8_lossy(&output.stdout).into_owned(), stderr)
+    }
+
+    fn path(&self, name: &str) -> PathBuf {
+        self.home.join(name)
+    }
+}
+
+impl Drop for Sandbox {
+    fn drop(&mut self) {
+        assert_eq!(self.connections.load(Ordering::SeqCst), 0);
+        assert!(!self.home.join(".cache").join("k").join("0.onnx").exists());
+        std::fs::remove_dir_all(&self.home).ok();
+    }
+}
+
+fn samples(path: &Path) -> Vec<i16> {
+    hound::WavReader::open(path)
+        .unwrap()
+        .into_samples::<i16>()
+        .map(Result::unwrap)
+        .collect()
+}
+
+#[test]
+fn say_pipe_and_alert_write_distinct_audio_offline() {
+    let sandbox = Sandbox::new("say");
+    let (say, pipe, alert) = (
+        sandbox.path("say.wav"),
+        sandbox.path("pipe.wav"),
+        sandbox.path("alert.wav"),
+    );
+
+    sandbox.run(
+        &["--output", say.to_str().unwrap(), "say", "Build complete."],
+        "",
+    );
+    sandbox.run(
+        &["--output", pipe.to_str().unwrap(), "pipe"],
+        "Tests failed on the main branch.\n",
+    );
+    sandbox.run(
+        &["--output", alert.to_str().unwrap(), "alert", "deploy"],
+        "",
+    );
+
+    let (say, pipe, alert) = (samples(&say), samples(&pipe), samples(&alert));
+    for audio in [&say, &pipe, &alert] {
+        assert!(!audio.is_empty());
+    }
+    assert_ne!(say, pipe);
+    assert_ne!(say, alert);
+}
+
+#[test]
+fn jsonl_jobs_render_offline() {
+    let sandbox = Sandbox::new("jsonl");
+    let (a1, a2) = (sandbox.path("a1.wav"), sandbox.path("a2.wav"));
+    let input = [
+        json!({"id": "a1", "text": "Backup finished.", "output": a1}).to_string(),
+        json!({"id": "a2", "text": "Disk almost full!", "output": a2}).to_string(),
+    ]
+    .join("\n");
+
+    let (stdout, _) = sandbox.run(&["jsonl"], &input);
+    let results: Vec<Value> = stdout
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(results.len(), 2);
+    assert!(results.iter().all(|r| r["ok"] == true), "{:?}", results);
+
+    let (a1, a2) = (samples(&a1), samples(&a2));
+    assert!(!a1.is_empty() && !a2.is_empty());
+    assert_ne!(a1, a2);
+}
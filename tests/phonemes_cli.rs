@@ -0,0 +1,31 @@
+//! `kokoro-speak phonemes --json` must agree with the library pipeline
+//!
+//! Needs the real model in ~/.cache/k, so it is ignored by default:
+//! `cargo test --test phonemes_cli -- --ignored`
+
+use assert_cmd::Command;
+use kokoro_tiny::{SynthesizeOptions, TtsEngine};
+
+const FIXTURE: &str = "DEPLOY FAILED ON THE API SERVER. Dr. Smith read the README at 3:15 — twice… \
+    Then the release notes went out to everyone on the mailing list, including the people who \
+    had asked to be removed from it last quarter, which caused a small stir.";
+
+#[tokio::test]
+#[ignore = "needs the Kokoro model files in ~/.cache/k"]
+async fn phoneme_report_cli_matches_library() {
+    let output = Command::cargo_bin("kokoro-speak")
+        .unwrap()
+        .args(["phonemes", FIXTURE, "--lang", "en", "--voice", "af_sky", "--json"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let from_cli: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+
+    let engine = TtsEngine::new().await.unwrap();
+    let mut opts = SynthesizeOptions::new().voice("af_sky");
+    opts.lang = Some("en".to_string());
+    let direct = serde_json::to_value(engine.phoneme_report(FIXTURE, &opts).unwrap()).unwrap();
+
+    assert_eq!(from_cli, direct);
+    assert!(from_cli.as_array().unwrap().len() > 1);
+}
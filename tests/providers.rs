@@ -0,0 +1,28 @@
+//! Execution provider selection against the real model
+//!
+//! Needs the model in ~/.cache/k, so it is ignored by default. Run it with an
+//! acceleration feature to check that provider on this machine:
+//! `cargo test --features cuda --test providers -- --ignored --nocapture`
+
+use kokoro_tiny::{ExecutionProvider, TtsEngine};
+
+#[test]
+#[ignore = "needs the Kokoro model files in ~/.cache/k"]
+fn synthesizes_on_the_provider_it_reports() {
+    let tts = TtsEngine::builder().build_cached().unwrap();
+    let provider = tts.active_provider().expect("a model is loaded");
+    println!("running on {}", provider);
+    assert_ne!(provider, ExecutionProvider::Auto);
+
+    let audio = tts
+        .synthesize("Hello from the accelerator.", None, None, None)
+        .unwrap();
+    assert!(!audio.is_empty());
+
+    // Asking for the CPU always gets it
+    let cpu = TtsEngine::builder()
+        .execution_provider(ExecutionProvider::Cpu)
+        .build_cached()
+        .unwrap();
+    assert_eq!(cpu.active_provider(), Some(ExecutionProvider::Cpu));
+}
@@ -0,0 +1,84 @@
+//! Engine startup must not buffer the model file on the Rust heap
+//!
+//! Counts Rust allocations around `TtsEngine::with_paths` on a large dummy
+//! model. ONNX Runtime's own (C++) allocations aren't visible here, which is
+//! the point: the model should only ever be read by the runtime, not copied
+//! into a Vec first.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::fs::{self, File};
+use std::io::Write;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use kokoro_tiny::TtsEngine;
+
+struct PeakAlloc;
+
+static CURRENT: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+fn grew(size: usize) {
+    let now = CURRENT.fetch_add(size, Ordering::Relaxed) + size;
+    PEAK.fetch_max(now, Ordering::Relaxed);
+}
+
+unsafe impl GlobalAlloc for PeakAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            grew(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        CURRENT.fetch_sub(layout.size(), Ordering::Relaxed);
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new = System.realloc(ptr, layout, new_size);
+        if !new.is_null() {
+            CURRENT.fetch_sub(layout.size(), Ordering::Relaxed);
+            grew(new_size);
+        }
+        new
+    }
+}
+
+#[global_allocator]
+static ALLOC: PeakAlloc = PeakAlloc;
+
+const FIXTURE_MB: usize = 64;
+const BUDGET_BYTES: usize = 8 << 20;
+
+#[tokio::test]
+async fn engine_startup_does_not_buffer_the_model() {
+    let dir = std::env::temp_dir().join(format!("kokoro-startup-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let model = dir.join("0.onnx");
+    let voices = dir.join("0.bin");
+
+    // Not a valid model: loading fails, but only after the file has been read
+    let mut file = File::create(&model).unwrap();
+    let block = vec![0x5au8; 1 << 20];
+    for _ in 0..FIXTURE_MB {
+        file.write_all(&block).unwrap();
+    }
+    drop((file, block));
+    fs::write(&voices, b"not an npz").unwrap();
+
+    let baseline = CURRENT.load(Ordering::Relaxed);
+    PEAK.store(baseline, Ordering::Relaxed);
+    let result = TtsEngine::with_paths(model.to_str().unwrap(), voices.to_str().unwrap()).await;
+    let peak = PEAK.load(Ordering::Relaxed) - baseline;
+
+    fs::remove_dir_all(&dir).ok();
+    assert!(result.is_err(), "dummy model should not load");
+    assert!(
+        peak < BUDGET_BYTES,
+        "startup allocated {} MB on the Rust heap for a {} MB model",
+        peak >> 20,
+        FIXTURE_MB
+    );
+}
@@ -0,0 +1,95 @@
+//! Compile-time Send/Sync guarantees for the public API
+//!
+//! Nothing here runs; if a change makes one of these types (or the
+//! `speak_stream` future) lose Send or Sync, this file stops compiling.
+
+use static_assertions::assert_impl_all;
+
+use kokoro_tiny::dialogue::{DialogueLine, DialogueOutput, DialogueSpan, DialogueTracks};
+use kokoro_tiny::hotkey::{Hotkey, HotkeyBinding};
+use kokoro_tiny::mcp_server::McpServer;
+use kokoro_tiny::mem8_bridge::{Mem8Bridge, MemoryWave, SalienceEvent};
+use kokoro_tiny::mem8_voice::Mem8Voice;
+use kokoro_tiny::reading::{ReadProgress, ReadSegment, ResumePoint};
+use kokoro_tiny::retry::RetryState;
+use kokoro_tiny::streaming::StreamingTts;
+use kokoro_tiny::{
+    AudioBuffer, AudioDuration, AudioStats, BabyTts, BufferPreset, BusyPolicy, CacheStats,
+    CapsPolicy, ChunkReport, EngineBuilder, KokoroError, NullOutput, PartialSynthesis,
+    PlaybackHandle, PlaybackInfo, PlaybackOptions, PunctuationWeights, ResumeFile, RetryPolicy,
+    SessionOptions, Speaker, SpeakerEvent, StreamInfo, SynthesisPlan, SynthesisStats,
+    SynthesisWarning, SynthesizeOptions, TtsEngine, VoiceMix, WordTiming,
+};
+
+// Engines and servers: shared across threads or moved onto a runtime
+assert_impl_all!(TtsEngine: Send, Sync);
+assert_impl_all!(BabyTts: Send, Sync);
+assert_impl_all!(StreamingTts: Send, Sync);
+assert_impl_all!(McpServer: Send, Sync);
+assert_impl_all!(Mem8Bridge: Send, Sync);
+assert_impl_all!(Mem8Voice: Send, Sync);
+assert_impl_all!(HotkeyBinding: Send, Sync);
+// Playback: a queue fed from one thread while another watches its events
+assert_impl_all!(Speaker: Send, Sync);
+assert_impl_all!(PlaybackHandle: Send, Sync);
+// Output backends are shared by every engine clone and playback thread
+assert_impl_all!(NullOutput: Send, Sync, Clone);
+
+// Plain data passed between threads
+assert_impl_all!(AudioBuffer: Send, Sync, Clone);
+assert_impl_all!(SynthesizeOptions: Send, Sync, Clone);
+assert_impl_all!(VoiceMix: Send, Sync);
+assert_impl_all!(SynthesisWarning: Send, Sync);
+assert_impl_all!(ChunkReport: Send, Sync);
+assert_impl_all!(CacheStats: Send, Sync);
+assert_impl_all!(RetryPolicy: Send, Sync);
+assert_impl_all!(EngineBuilder: Send, Sync, Clone);
+assert_impl_all!(SessionOptions: Send, Sync, Copy);
+assert_impl_all!(RetryState: Send, Sync);
+assert_impl_all!(CapsPolicy: Send, Sync);
+assert_impl_all!(PunctuationWeights: Send, Sync);
+assert_impl_all!(SynthesisPlan: Send, Sync);
+assert_impl_all!(PartialSynthesis: Send, Sync);
+// Errors cross threads (and `?` into Box<dyn Error + Send + Sync>)
+assert_impl_all!(KokoroError: Send, Sync, std::error::Error);
+assert_impl_all!(ResumeFile: Send, Sync);
+assert_impl_all!(PlaybackOptions: Send, Sync);
+assert_impl_all!(PlaybackInfo: Send, Sync);
+assert_impl_all!(StreamInfo: Send, Sync);
+assert_impl_all!(BufferPreset: Send, Sync);
+assert_impl_all!(BusyPolicy: Send, Sync);
+assert_impl_all!(Hotkey: Send, Sync);
+assert_impl_all!(ReadProgress: Send, Sync);
+assert_impl_all!(ReadSegment: Send, Sync);
+assert_impl_all!(ResumePoint: Send, Sync);
+assert_impl_all!(DialogueLine: Send, Sync);
+assert_impl_all!(DialogueOutput: Send, Sync);
+assert_impl_all!(DialogueSpan: Send, Sync);
+assert_impl_all!(DialogueTracks: Send, Sync);
+assert_impl_all!(MemoryWave: Send, Sync);
+assert_impl_all!(SalienceEvent: Send, Sync);
+assert_impl_all!(SpeakerEvent: Send, Sync);
+assert_impl_all!(AudioStats: Send, Sync);
+assert_impl_all!(SynthesisStats: Send, Sync);
+assert_impl_all!(WordTiming: Send, Sync);
+assert_impl_all!(AudioDuration: Send, Sync, Copy);
+
+// Futures that callers hand to `tokio::spawn`
+#[allow(dead_code)]
+fn speak_stream_future_is_send(tts: &StreamingTts) {
+    fn is_send<T: Send>(_: &T) {}
+    is_send(&tts.speak_stream("hello"));
+}
+
+#[allow(dead_code)]
+fn synthesize_async_future_is_send(tts: &std::sync::Arc<TtsEngine>) {
+    fn is_send<T: Send + 'static>(_: &T) {}
+    is_send(&tts.synthesize_async("hello", SynthesizeOptions::new()));
+}
+
+#[allow(dead_code)]
+fn engine_and_server_constructors_are_send() {
+    fn is_send<T: Send>(_: &T) {}
+    is_send(&TtsEngine::new());
+    is_send(&McpServer::new());
+}
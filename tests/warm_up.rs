@@ -0,0 +1,34 @@
+//! `warm_up` takes the start-up cost out of the first real synthesis
+//!
+//! Needs the cached model, espeak-ng and the ONNX Runtime library, so it is
+//! ignored by default: `cargo test --release --test warm_up -- --ignored`
+
+use std::time::{Duration, Instant};
+
+use kokoro_tiny::{SynthesizeOptions, TtsEngine};
+
+// Time one uncached render
+fn render(tts: &mut TtsEngine, text: &str) -> Duration {
+    tts.clear_cache();
+    let start = Instant::now();
+    tts.synthesize_with(text, SynthesizeOptions::new()).unwrap();
+    start.elapsed()
+}
+
+#[test]
+#[ignore = "needs the cached model, espeak-ng and the ONNX Runtime library"]
+fn first_synthesis_after_warm_up_is_not_a_spike() {
+    let mut tts = TtsEngine::builder().warm(true).build_cached().unwrap();
+    assert!(!tts.is_fallback());
+
+    let text = "The build finished and every test passed.";
+    let second = render(&mut tts, text);
+    let third = render(&mut tts, text);
+    // Without warm-up the first render is 2-4x the later ones
+    assert!(
+        second < third * 2,
+        "second render took {:?}, third {:?}",
+        second,
+        third
+    );
+}